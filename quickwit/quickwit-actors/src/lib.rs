@@ -68,7 +68,9 @@ pub use self::actor_state::ActorState;
 pub use self::channel_with_priority::{QueueCapacity, RecvError, SendError, TrySendError};
 pub use self::mailbox::{Inbox, Mailbox, WeakMailbox};
 pub use self::registry::ActorObservation;
-pub use self::supervisor::{Supervisor, SupervisorMetrics, SupervisorState};
+pub use self::supervisor::{
+    ForgetRestart, RestartPolicy, Supervisor, SupervisorMetrics, SupervisorState,
+};
 
 /// Heartbeat used to verify that actors are progressing.
 ///
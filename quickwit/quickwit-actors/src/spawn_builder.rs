@@ -27,7 +27,7 @@ use crate::envelope::Envelope;
 use crate::mailbox::{create_mailbox, Inbox};
 use crate::registry::{ActorJoinHandle, ActorRegistry};
 use crate::scheduler::{NoAdvanceTimeGuard, SchedulerClient};
-use crate::supervisor::Supervisor;
+use crate::supervisor::{RestartPolicy, Supervisor};
 use crate::{
     Actor, ActorContext, ActorExitStatus, ActorHandle, KillSwitch, Mailbox, QueueCapacity,
 };
@@ -80,6 +80,7 @@ pub struct SpawnBuilder<A: Actor> {
     #[allow(clippy::type_complexity)]
     mailboxes: Option<(Mailbox<A>, Inbox<A>)>,
     backpressure_micros_counter_opt: Option<IntCounter>,
+    restart_policy: RestartPolicy,
 }
 
 impl<A: Actor> SpawnBuilder<A> {
@@ -88,6 +89,7 @@ impl<A: Actor> SpawnBuilder<A> {
             spawn_ctx,
             mailboxes: None,
             backpressure_micros_counter_opt: None,
+            restart_policy: RestartPolicy::default(),
         }
     }
 
@@ -125,6 +127,15 @@ impl<A: Actor> SpawnBuilder<A> {
         self
     }
 
+    /// Sets the policy used to restart the actor when it fails.
+    ///
+    /// This is only relevant for supervised actors. By default, the actor is restarted with an
+    /// exponential backoff and the supervisor never gives up.
+    pub fn set_restart_policy(mut self, restart_policy: RestartPolicy) -> Self {
+        self.restart_policy = restart_policy;
+        self
+    }
+
     fn take_or_create_mailboxes(&mut self, actor: &A) -> (Mailbox<A>, Inbox<A>) {
         if let Some((mailbox, inbox)) = self.mailboxes.take() {
             return (mailbox, inbox);
@@ -177,12 +188,19 @@ impl<A: Actor> SpawnBuilder<A> {
     ) -> (Mailbox<A>, ActorHandle<Supervisor<A>>) {
         let actor = actor_factory();
         let actor_name = actor.name();
+        let restart_policy = self.restart_policy;
         let (mailbox, inbox) = self.take_or_create_mailboxes(&actor);
         self.mailboxes = Some((mailbox, inbox.clone()));
         let child_ctx = self.spawn_ctx.child_context();
         let parent_spawn_ctx = std::mem::replace(&mut self.spawn_ctx, child_ctx);
         let (mailbox, actor_handle) = self.spawn(actor);
-        let supervisor = Supervisor::new(actor_name, Box::new(actor_factory), inbox, actor_handle);
+        let supervisor = Supervisor::new(
+            actor_name,
+            Box::new(actor_factory),
+            inbox,
+            actor_handle,
+            restart_policy,
+        );
         let (_supervisor_mailbox, supervisor_handle) =
             parent_spawn_ctx.spawn_builder().spawn(supervisor);
         (mailbox, supervisor_handle)
@@ -17,20 +17,103 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::time::Duration;
+
 use async_trait::async_trait;
 use serde::Serialize;
-use tracing::{info, warn};
+use tracing::{error, info, warn};
 
 use crate::mailbox::Inbox;
 use crate::{
-    Actor, ActorContext, ActorExitStatus, ActorHandle, ActorState, Handler, Health, Supervisable,
+    Actor, ActorContext, ActorExitStatus, ActorHandle, ActorState, Handler, Health, Mailbox,
+    Supervisable,
 };
 
+/// Policy governing how a failing actor is restarted.
+///
+/// The delay before a restart grows exponentially with the number of restarts that occurred
+/// within the last `restart_window`, starting at `initial_backoff` and capped at `max_backoff`.
+/// Once `max_restarts_opt` restarts have occurred within the window, the actor is considered to
+/// be crash-looping beyond repair and the supervisor gives up.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize)]
+pub struct RestartPolicy {
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub restart_window: Duration,
+    pub max_restarts_opt: Option<usize>,
+}
+
+impl RestartPolicy {
+    /// Returns the delay to wait for before restarting an actor that was already restarted
+    /// `num_recent_restarts` times within the restart window.
+    pub fn backoff(&self, num_recent_restarts: usize) -> Duration {
+        // Protect against a number of restarts that would lead to an overflow.
+        let exponent = (num_recent_restarts as u32).min(31);
+        self.initial_backoff
+            .saturating_mul(2u32.pow(exponent))
+            .min(self.max_backoff)
+    }
+
+    /// Returns true if the actor should not be restarted anymore.
+    pub fn should_give_up(&self, num_recent_restarts: usize) -> bool {
+        self.max_restarts_opt
+            .is_some_and(|max_restarts| num_recent_restarts >= max_restarts)
+    }
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        RestartPolicy {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(5 * 60),
+            restart_window: Duration::from_secs(10 * 60),
+            max_restarts_opt: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize)]
 pub struct SupervisorMetrics {
     pub num_panics: usize,
     pub num_errors: usize,
     pub num_kills: usize,
+    /// Number of restarts that occurred within the restart window of the restart policy.
+    pub num_recent_restarts: usize,
+    /// True if the actor failed again while a previous restart was still within the restart
+    /// window, or if the supervisor gave up on restarting it.
+    pub is_crash_looping: bool,
+}
+
+impl SupervisorMetrics {
+    /// Accounts for a failure of the supervised actor and returns the delay to wait for before
+    /// restarting it, or `None` if the restart policy gives up on it.
+    pub fn on_failure(&mut self, restart_policy: &RestartPolicy) -> Option<Duration> {
+        let num_recent_restarts = self.num_recent_restarts;
+
+        if restart_policy.should_give_up(num_recent_restarts) {
+            self.is_crash_looping = true;
+            return None;
+        }
+        if num_recent_restarts > 0 {
+            self.is_crash_looping = true;
+        }
+        Some(restart_policy.backoff(num_recent_restarts))
+    }
+
+    /// Accounts for a restart of the supervised actor. The caller is expected to send itself a
+    /// [`ForgetRestart`] message once the restart window has elapsed.
+    pub fn on_restart(&mut self) {
+        self.num_recent_restarts += 1;
+    }
+
+    /// Forgets a restart that fell out of the restart window.
+    pub fn forget_restart(&mut self) {
+        self.num_recent_restarts = self.num_recent_restarts.saturating_sub(1);
+
+        if self.num_recent_restarts == 0 {
+            self.is_crash_looping = false;
+        }
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize)]
@@ -53,12 +136,23 @@ pub struct Supervisor<A: Actor> {
     actor_factory: Box<dyn Fn() -> A + Send>,
     inbox: Inbox<A>,
     handle_opt: Option<ActorHandle<A>>,
+    // Mailbox of the actor while we wait for the restart backoff to elapse.
+    restart_mailbox_opt: Option<Mailbox<A>>,
+    restart_policy: RestartPolicy,
     metrics: SupervisorMetrics,
 }
 
 #[derive(Debug, Copy, Clone)]
 struct SuperviseLoop;
 
+#[derive(Debug, Copy, Clone)]
+struct Restart;
+
+/// Message scheduled by a supervising actor to forget a restart once the restart window of its
+/// restart policy has elapsed.
+#[derive(Debug, Copy, Clone)]
+pub struct ForgetRestart;
+
 #[async_trait]
 impl<A: Actor> Actor for Supervisor<A> {
     type ObservableState = SupervisorState<A::ObservableState>;
@@ -120,12 +214,15 @@ impl<A: Actor> Supervisor<A> {
         actor_factory: Box<dyn Fn() -> A + Send>,
         inbox: Inbox<A>,
         handle: ActorHandle<A>,
+        restart_policy: RestartPolicy,
     ) -> Self {
         Supervisor {
             actor_name,
             actor_factory,
             inbox,
             handle_opt: Some(handle),
+            restart_mailbox_opt: None,
+            restart_policy,
             metrics: Default::default(),
         }
     }
@@ -134,10 +231,10 @@ impl<A: Actor> Supervisor<A> {
         &mut self,
         ctx: &ActorContext<Supervisor<A>>,
     ) -> Result<(), ActorExitStatus> {
-        let handle_ref = self
-            .handle_opt
-            .as_ref()
-            .expect("The actor handle should always be set.");
+        let Some(handle_ref) = self.handle_opt.as_ref() else {
+            // The actor is waiting to be restarted.
+            return Ok(());
+        };
         match handle_ref.check_health(true) {
             Health::Healthy => {
                 handle_ref.refresh_observe();
@@ -180,6 +277,36 @@ impl<A: Actor> Supervisor<A> {
                 self.metrics.num_panics += 1;
             }
         }
+        let num_recent_restarts = self.metrics.num_recent_restarts;
+
+        let Some(backoff) = self.metrics.on_failure(&self.restart_policy) else {
+            error!(
+                num_recent_restarts=%num_recent_restarts,
+                restart_window=?self.restart_policy.restart_window,
+                "crash-looping-actor"
+            );
+            return Err(ActorExitStatus::from(anyhow::anyhow!(
+                "actor `{}` was restarted {} times within {:?}, giving up",
+                self.actor_name,
+                num_recent_restarts,
+                self.restart_policy.restart_window
+            )));
+        };
+        self.restart_mailbox_opt = Some(actor_mailbox);
+
+        if backoff.is_zero() {
+            self.restart(ctx).await;
+        } else {
+            info!(backoff=?backoff, "delaying-actor-respawn");
+            ctx.schedule_self_msg(backoff, Restart).await;
+        }
+        Ok(())
+    }
+
+    async fn restart(&mut self, ctx: &ActorContext<Supervisor<A>>) {
+        let Some(actor_mailbox) = self.restart_mailbox_opt.take() else {
+            return;
+        };
         info!("respawning-actor");
         let (_, actor_handle) = ctx
             .spawn_actor()
@@ -187,7 +314,9 @@ impl<A: Actor> Supervisor<A> {
             .set_kill_switch(ctx.kill_switch().child())
             .spawn((*self.actor_factory)());
         self.handle_opt = Some(actor_handle);
-        Ok(())
+        self.metrics.on_restart();
+        ctx.schedule_self_msg(self.restart_policy.restart_window, ForgetRestart)
+            .await;
     }
 }
 
@@ -207,6 +336,34 @@ impl<A: Actor> Handler<SuperviseLoop> for Supervisor<A> {
     }
 }
 
+#[async_trait]
+impl<A: Actor> Handler<Restart> for Supervisor<A> {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        _msg: Restart,
+        ctx: &ActorContext<Self>,
+    ) -> Result<Self::Reply, ActorExitStatus> {
+        self.restart(ctx).await;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<A: Actor> Handler<ForgetRestart> for Supervisor<A> {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        _msg: ForgetRestart,
+        _ctx: &ActorContext<Self>,
+    ) -> Result<Self::Reply, ActorExitStatus> {
+        self.metrics.forget_restart();
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
@@ -214,7 +371,7 @@ mod tests {
     use async_trait::async_trait;
     use tracing::info;
 
-    use crate::supervisor::SupervisorMetrics;
+    use crate::supervisor::{RestartPolicy, SupervisorMetrics};
     use crate::tests::{Ping, PingReceiverActor};
     use crate::{Actor, ActorContext, ActorExitStatus, AskError, Handler, Observe, Universe};
 
@@ -306,7 +463,9 @@ mod tests {
             SupervisorMetrics {
                 num_panics: 1,
                 num_errors: 0,
-                num_kills: 0
+                num_kills: 0,
+                num_recent_restarts: 1,
+                is_crash_looping: false,
             }
         );
         assert!(!matches!(
@@ -338,7 +497,9 @@ mod tests {
             SupervisorMetrics {
                 num_panics: 0,
                 num_errors: 1,
-                num_kills: 0
+                num_kills: 0,
+                num_recent_restarts: 1,
+                is_crash_looping: false,
             }
         );
         assert!(!matches!(
@@ -347,6 +508,116 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_restart_policy_backoff() {
+        let restart_policy = RestartPolicy {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(10),
+            restart_window: Duration::from_secs(60),
+            max_restarts_opt: Some(3),
+        };
+        assert_eq!(restart_policy.backoff(0), Duration::from_secs(1));
+        assert_eq!(restart_policy.backoff(1), Duration::from_secs(2));
+        assert_eq!(restart_policy.backoff(3), Duration::from_secs(8));
+        assert_eq!(restart_policy.backoff(4), Duration::from_secs(10));
+        assert_eq!(restart_policy.backoff(usize::MAX), Duration::from_secs(10));
+
+        assert!(!restart_policy.should_give_up(2));
+        assert!(restart_policy.should_give_up(3));
+
+        let restart_policy = RestartPolicy::default();
+        assert!(!restart_policy.should_give_up(usize::MAX));
+    }
+
+    #[tokio::test]
+    async fn test_supervisor_detects_crash_loop() {
+        let universe = Universe::with_accelerated_time();
+        let restart_policy = RestartPolicy {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(10),
+            restart_window: Duration::from_secs(60),
+            max_restarts_opt: None,
+        };
+        let actor = FailingActor::default();
+        let (mailbox, supervisor_handle) = universe
+            .spawn_builder()
+            .set_restart_policy(restart_policy)
+            .supervise(actor);
+
+        for _ in 0..2 {
+            assert!(mailbox.ask(FailingActorMessage::ReturnError).await.is_err());
+            assert_eq!(
+                mailbox.ask(FailingActorMessage::Increment).await.unwrap(),
+                1
+            );
+        }
+        assert_eq!(
+            supervisor_handle.observe().await.metrics,
+            SupervisorMetrics {
+                num_panics: 0,
+                num_errors: 2,
+                num_kills: 0,
+                num_recent_restarts: 2,
+                is_crash_looping: true,
+            }
+        );
+        // Restarts are forgotten once the restart window has elapsed.
+        universe.sleep(Duration::from_secs(90)).await;
+
+        assert_eq!(
+            supervisor_handle.observe().await.metrics,
+            SupervisorMetrics {
+                num_panics: 0,
+                num_errors: 2,
+                num_kills: 0,
+                num_recent_restarts: 0,
+                is_crash_looping: false,
+            }
+        );
+        assert!(!matches!(
+            supervisor_handle.quit().await.0,
+            ActorExitStatus::Panicked
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_supervisor_gives_up_after_max_restarts() {
+        let universe = Universe::with_accelerated_time();
+        let restart_policy = RestartPolicy {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(10),
+            restart_window: Duration::from_secs(600),
+            max_restarts_opt: Some(2),
+        };
+        let actor = FailingActor::default();
+        let (mailbox, supervisor_handle) = universe
+            .spawn_builder()
+            .set_restart_policy(restart_policy)
+            .supervise(actor);
+
+        for _ in 0..2 {
+            assert!(mailbox.ask(FailingActorMessage::ReturnError).await.is_err());
+            assert_eq!(
+                mailbox.ask(FailingActorMessage::Increment).await.unwrap(),
+                1
+            );
+        }
+        assert!(mailbox.ask(FailingActorMessage::ReturnError).await.is_err());
+
+        let (exit_status, supervisor_state) = supervisor_handle.join().await;
+        assert!(matches!(exit_status, ActorExitStatus::Failure(_)));
+        assert_eq!(
+            supervisor_state.metrics,
+            SupervisorMetrics {
+                num_panics: 0,
+                num_errors: 3,
+                num_kills: 0,
+                num_recent_restarts: 2,
+                is_crash_looping: true,
+            }
+        );
+    }
+
     #[tokio::test]
     async fn test_supervisor_kills_and_restart_frozen_actor() {
         let universe = Universe::with_accelerated_time();
@@ -365,7 +636,9 @@ mod tests {
             SupervisorMetrics {
                 num_panics: 0,
                 num_errors: 0,
-                num_kills: 0
+                num_kills: 0,
+                num_recent_restarts: 0,
+                is_crash_looping: false,
             }
         );
         mailbox
@@ -383,7 +656,9 @@ mod tests {
             SupervisorMetrics {
                 num_panics: 0,
                 num_errors: 0,
-                num_kills: 1
+                num_kills: 1,
+                num_recent_restarts: 1,
+                is_crash_looping: false,
             }
         );
         assert!(!matches!(
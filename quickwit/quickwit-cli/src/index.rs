@@ -75,8 +75,22 @@ pub fn build_index_command() -> Command {
                 ])
             )
         .subcommand(
-            Command::new("clear")
+            Command::new("update")
                 .display_order(2)
+                .about("Updates an index from an index config file.")
+                .long_about("Updates the doc mapping, the search settings, the retention policy, and the indexing settings of an index from an index config file. The index ID and the index URI cannot be modified. New field mappings and tag fields can be appended to the doc mapping, but existing field mappings cannot be modified or removed.")
+                .args(&[
+                    arg!(--index <INDEX> "ID of the target index")
+                        .display_order(1)
+                        .required(true),
+                    arg!(--"index-config" <INDEX_CONFIG> "Location of the index config file.")
+                        .display_order(2)
+                        .required(true),
+                ])
+            )
+        .subcommand(
+            Command::new("clear")
+                .display_order(3)
                 .alias("clr")
                 .about("Clears an index: deletes all splits and resets checkpoint.")
                 .long_about("Deletes all its splits and resets its checkpoint. This operation is destructive and cannot be undone, proceed with caution.")
@@ -88,7 +102,7 @@ pub fn build_index_command() -> Command {
             )
         .subcommand(
             Command::new("delete")
-                .display_order(3)
+                .display_order(4)
                 .alias("del")
                 .about("Deletes an index.")
                 .long_about("Deletes an index. This operation is destructive and cannot be undone, proceed with caution.")
@@ -102,7 +116,7 @@ pub fn build_index_command() -> Command {
             )
         .subcommand(
             Command::new("describe")
-                .display_order(4)
+                .display_order(5)
                 .about("Displays descriptive statistics of an index.")
                 .long_about("Displays descriptive statistics of an index. Displayed statistics are: number of published splits, number of documents, splits min/max timestamps, size of splits.")
                 .args(&[
@@ -113,12 +127,12 @@ pub fn build_index_command() -> Command {
         .subcommand(
             Command::new("list")
                 .alias("ls")
-                .display_order(5)
+                .display_order(6)
                 .about("List indexes.")
             )
         .subcommand(
             Command::new("ingest")
-                .display_order(6)
+                .display_order(7)
                 .about("Ingest NDJSON documents with the ingest API.")
                 .long_about("Reads NDJSON documents from a file or streamed from stdin and sends them into ingest API.")
                 .args(&[
@@ -155,7 +169,7 @@ pub fn build_index_command() -> Command {
             )
         .subcommand(
             Command::new("search")
-                .display_order(7)
+                .display_order(8)
                 .about("Searches an index.")
                 .args(&[
                     arg!(--index <INDEX> "ID of the target index")
@@ -204,6 +218,13 @@ pub struct CreateIndexArgs {
     pub assume_yes: bool,
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub struct UpdateIndexArgs {
+    pub client_args: ClientArgs,
+    pub index_id: String,
+    pub index_config_uri: Uri,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub struct DescribeIndexArgs {
     pub client_args: ClientArgs,
@@ -256,6 +277,7 @@ pub enum IndexCliCommand {
     Ingest(IngestDocsArgs),
     List(ListIndexesArgs),
     Search(SearchIndexArgs),
+    Update(UpdateIndexArgs),
 }
 
 impl IndexCliCommand {
@@ -278,6 +300,7 @@ impl IndexCliCommand {
             "ingest" => Self::parse_ingest_args(submatches),
             "list" => Self::parse_list_args(submatches),
             "search" => Self::parse_search_args(submatches),
+            "update" => Self::parse_update_args(submatches),
             _ => bail!("unknown index subcommand `{subcommand}`"),
         }
     }
@@ -312,6 +335,23 @@ impl IndexCliCommand {
         }))
     }
 
+    fn parse_update_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
+        let client_args = ClientArgs::parse(&mut matches)?;
+        let index_id = matches
+            .remove_one::<String>("index")
+            .expect("`index` should be a required arg.");
+        let index_config_uri = matches
+            .remove_one::<String>("index-config")
+            .map(|uri| Uri::from_str(&uri))
+            .expect("`index-config` should be a required arg.")?;
+
+        Ok(Self::Update(UpdateIndexArgs {
+            client_args,
+            index_id,
+            index_config_uri,
+        }))
+    }
+
     fn parse_describe_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
         let client_args = ClientArgs::parse(&mut matches)?;
         let index_id = matches
@@ -438,6 +478,7 @@ impl IndexCliCommand {
             Self::Ingest(args) => ingest_docs_cli(args).await,
             Self::List(args) => list_index_cli(args).await,
             Self::Search(args) => search_index_cli(args).await,
+            Self::Update(args) => update_index_cli(args).await,
         }
     }
 }
@@ -489,6 +530,24 @@ pub async fn create_index_cli(args: CreateIndexArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
+pub async fn update_index_cli(args: UpdateIndexArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "update-index");
+    println!("❯ Updating index...");
+    let storage_resolver = StorageResolver::unconfigured();
+    let file_content = load_file(&storage_resolver, &args.index_config_uri).await?;
+    let index_config_str: String = std::str::from_utf8(&file_content)
+        .with_context(|| format!("Invalid utf8: `{}`", args.index_config_uri))?
+        .to_string();
+    let config_format = ConfigFormat::sniff_from_uri(&args.index_config_uri)?;
+    let qw_client = args.client_args.client();
+    qw_client
+        .indexes()
+        .update(&args.index_id, &index_config_str, config_format)
+        .await?;
+    println!("{} Index successfully updated.", "✔".color(GREEN_COLOR));
+    Ok(())
+}
+
 pub async fn list_index_cli(args: ListIndexesArgs) -> anyhow::Result<()> {
     debug!(args=?args, "list-index");
     let qw_client = args.client_args.client();
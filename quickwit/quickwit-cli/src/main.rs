@@ -96,7 +96,7 @@ mod tests {
     use quickwit_cli::cli::{build_cli, CliCommand};
     use quickwit_cli::index::{
         ClearIndexArgs, CreateIndexArgs, DeleteIndexArgs, DescribeIndexArgs, IndexCliCommand,
-        IngestDocsArgs, SearchIndexArgs,
+        IngestDocsArgs, SearchIndexArgs, UpdateIndexArgs,
     };
     use quickwit_cli::split::{DescribeSplitArgs, SplitCliCommand};
     use quickwit_cli::tool::{
@@ -181,6 +181,32 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_update_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "update",
+            "--index",
+            "wikipedia",
+            "--index-config",
+            "index-conf.yaml",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        let expected_index_config_uri = Uri::from_str(&format!(
+            "file://{}/index-conf.yaml",
+            std::env::current_dir().unwrap().display()
+        ))
+        .unwrap();
+        let expected_cmd = CliCommand::Index(IndexCliCommand::Update(UpdateIndexArgs {
+            client_args: ClientArgs::default(),
+            index_id: "wikipedia".to_string(),
+            index_config_uri: expected_index_config_uri,
+        }));
+        assert_eq!(command, expected_cmd);
+        Ok(())
+    }
+
     #[test]
     fn test_parse_ingest_v2_args() {
         let app = build_cli().no_binary_name(true);
@@ -678,6 +704,7 @@ mod tests {
                 index_id,
                 grace_period,
                 dry_run: false,
+                orphans: false,
                 ..
             })) if &index_id == "wikipedia" && grace_period == Duration::from_secs(60 * 60)
         ));
@@ -693,6 +720,7 @@ mod tests {
             "--config",
             "/config.yaml",
             "--dry-run",
+            "--orphans",
         ])?;
         let command = CliCommand::parse_cli_args(matches)?;
         let expected_config_uri = Uri::from_str("file:///config.yaml").unwrap();
@@ -703,6 +731,7 @@ mod tests {
                 grace_period,
                 config_uri,
                 dry_run: true,
+                orphans: true,
             })) if &index_id == "wikipedia" && grace_period == Duration::from_secs(5 * 60) && config_uri == expected_config_uri
         ));
         Ok(())
@@ -33,13 +33,14 @@ use quickwit_actors::{ActorExitStatus, ActorHandle, Universe};
 use quickwit_cluster::{ChannelTransport, Cluster, ClusterMember, FailureDetectorConfig};
 use quickwit_common::pubsub::EventBroker;
 use quickwit_common::runtimes::RuntimesConfig;
+use quickwit_common::tower::ChannelFactory;
 use quickwit_common::uri::Uri;
 use quickwit_config::service::QuickwitService;
 use quickwit_config::{
     IndexerConfig, NodeConfig, SourceConfig, SourceInputFormat, SourceParams, TransformConfig,
     VecSourceParams, CLI_INGEST_SOURCE_ID,
 };
-use quickwit_index_management::{clear_cache_directory, IndexService};
+use quickwit_index_management::{clear_cache_directory, IndexService, OrphanFilesRemovalInfo};
 use quickwit_indexing::actors::{IndexingService, MergePipeline, MergePipelineId};
 use quickwit_indexing::models::{
     DetachIndexingPipeline, DetachMergePipeline, IndexingStatistics, SpawnPipeline,
@@ -80,7 +81,7 @@ pub fn build_tool_command() -> Command {
                         .required(true),
                     arg!(--"input-path" <INPUT_PATH> "Location of the input file.")
                         .required(false),
-                    arg!(--"input-format" <INPUT_FORMAT> "Format of the input data.")
+                    arg!(--"input-format" <INPUT_FORMAT> "Format of the input data: `json`, `plain` or `csv`.")
                         .default_value("json")
                         .required(false),
                     arg!(--overwrite "Overwrites pre-existing index.")
@@ -151,6 +152,8 @@ pub fn build_tool_command() -> Command {
                         .required(false),
                     arg!(--"dry-run" "Executes the command in dry run mode and only displays the list of splits candidates for garbage collection.")
                         .required(false),
+                    arg!(--orphans "Also garbage collects the split files of the index storage that are not referenced by the metastore and are older than the grace period.")
+                        .required(false),
                 ])
             )
         .subcommand(
@@ -201,6 +204,7 @@ pub struct GarbageCollectIndexArgs {
     pub index_id: String,
     pub grace_period: Duration,
     pub dry_run: bool,
+    pub orphans: bool,
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -359,10 +363,12 @@ impl ToolCliCommand {
             .map(|duration_str: &String| humantime::parse_duration(duration_str))
             .expect("`grace-period` should have a default value.")?;
         let dry_run = matches.get_flag("dry-run");
+        let orphans = matches.get_flag("orphans");
         Ok(Self::GarbageCollect(GarbageCollectIndexArgs {
             index_id,
             grace_period,
             dry_run,
+            orphans,
             config_uri,
         }))
     }
@@ -551,6 +557,10 @@ pub async fn local_search_cli(args: LocalSearchArgs) -> anyhow::Result<()> {
         format: BodyFormat::Json,
         sort_by,
         count_all: CountHits::CountAll,
+        timeout: None,
+        profile: false,
+        collapse_field: None,
+        collapse_inner_hits: None,
     };
     let search_request =
         search_request_from_api_request(vec![args.index_id], search_request_query_string)?;
@@ -659,6 +669,13 @@ pub async fn garbage_collect_index_cli(args: GarbageCollectIndexArgs) -> anyhow:
         get_resolvers(&config.storage_configs, &config.metastore_configs);
     let metastore = metastore_resolver.resolve(&config.metastore_uri).await?;
     let mut index_service = IndexService::new(metastore, storage_resolver);
+
+    if args.orphans {
+        let orphan_files_removal_info = index_service
+            .garbage_collect_orphan_files(&args.index_id, args.grace_period, args.dry_run)
+            .await?;
+        print_orphan_files_removal_info(&orphan_files_removal_info, args.dry_run);
+    }
     let removal_info = index_service
         .garbage_collect_index(&args.index_id, args.grace_period, args.dry_run)
         .await?;
@@ -715,6 +732,35 @@ pub async fn garbage_collect_index_cli(args: GarbageCollectIndexArgs) -> anyhow:
     Ok(())
 }
 
+fn print_orphan_files_removal_info(removal_info: &OrphanFilesRemovalInfo, dry_run: bool) {
+    if removal_info.removed_files.is_empty() && removal_info.failed_files.is_empty() {
+        println!("No orphan split files to garbage collect.");
+        return;
+    }
+    if dry_run {
+        println!("The following orphan split files will be garbage collected.");
+        for file_metadata in &removal_info.removed_files {
+            println!(" - {}", file_metadata.path.display());
+        }
+        println!(
+            "{}MB of storage can be reclaimed.",
+            removal_info.num_removed_bytes() / 1_000_000
+        );
+        return;
+    }
+    if !removal_info.failed_files.is_empty() {
+        println!("The following orphan split files were attempted to be removed, but failed.");
+        for file_metadata in &removal_info.failed_files {
+            println!(" - {}", file_metadata.path.display());
+        }
+    }
+    println!(
+        "{}MB of storage garbage collected from {} orphan split file(s).",
+        removal_info.num_removed_bytes() / 1_000_000,
+        removal_info.removed_files.len()
+    );
+}
+
 async fn extract_split_cli(args: ExtractSplitArgs) -> anyhow::Result<()> {
     debug!(args=?args, "extract-split");
     println!("❯ Extracting split...");
@@ -946,6 +992,7 @@ async fn create_empty_cluster(config: &NodeConfig) -> anyhow::Result<Cluster> {
         config.gossip_advertise_addr,
         Vec::new(),
         FailureDetectorConfig::default(),
+        ChannelFactory::default(),
         &ChannelTransport::default(),
     )
     .await?;
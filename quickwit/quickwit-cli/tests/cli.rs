@@ -664,6 +664,7 @@ async fn test_garbage_collect_cli_no_grace() {
         index_id: index_id.clone(),
         grace_period: Duration::from_secs(3600),
         dry_run,
+        orphans: false,
     };
 
     let splits_metadata = metastore
@@ -772,6 +773,7 @@ async fn test_garbage_collect_index_cli() {
         index_id: index_id.clone(),
         grace_period: Duration::from_secs(grace_period_secs),
         dry_run: false,
+        orphans: false,
     };
 
     let mut metastore = MetastoreResolver::unconfigured()
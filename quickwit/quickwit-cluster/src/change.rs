@@ -22,7 +22,7 @@ use std::collections::BTreeMap;
 
 use chitchat::{ChitchatId, NodeState};
 use quickwit_common::sorted_iter::{KeyDiff, SortedByKeyIterator};
-use quickwit_common::tower::{make_channel, warmup_channel};
+use quickwit_common::tower::{warmup_channel, ChannelFactory};
 use quickwit_proto::types::NodeId;
 use tonic::transport::Channel;
 use tracing::{info, warn};
@@ -42,6 +42,7 @@ pub enum ClusterChange {
 pub(crate) async fn compute_cluster_change_events(
     cluster_id: &str,
     self_chitchat_id: &ChitchatId,
+    channel_factory: &ChannelFactory,
     previous_nodes: &mut BTreeMap<NodeId, ClusterNode>,
     previous_node_states: &BTreeMap<ChitchatId, NodeState>,
     new_node_states: &BTreeMap<ChitchatId, NodeState>,
@@ -58,6 +59,7 @@ pub(crate) async fn compute_cluster_change_events(
                 let node_events = compute_cluster_change_events_on_added(
                     cluster_id,
                     self_chitchat_id,
+                    channel_factory,
                     chitchat_id,
                     node_state,
                     previous_nodes,
@@ -106,6 +108,7 @@ pub(crate) async fn compute_cluster_change_events(
 async fn compute_cluster_change_events_on_added(
     cluster_id: &str,
     self_chitchat_id: &ChitchatId,
+    channel_factory: &ChannelFactory,
     new_chitchat_id: &ChitchatId,
     new_node_state: &NodeState,
     previous_nodes: &mut BTreeMap<NodeId, ClusterNode>,
@@ -148,8 +151,14 @@ async fn compute_cluster_change_events_on_added(
             new_chitchat_id.node_id
         );
     }
-    let Some(new_node) =
-        try_new_node(cluster_id, new_chitchat_id, new_node_state, is_self_node).await
+    let Some(new_node) = try_new_node(
+        cluster_id,
+        channel_factory,
+        new_chitchat_id,
+        new_node_state,
+        is_self_node,
+    )
+    .await
     else {
         return events;
     };
@@ -273,13 +282,14 @@ fn try_new_node_with_channel(
 
 async fn try_new_node(
     cluster_id: &str,
+    channel_factory: &ChannelFactory,
     chitchat_id: &ChitchatId,
     node_state: &NodeState,
     is_self_node: bool,
 ) -> Option<ClusterNode> {
     match node_state.grpc_advertise_addr() {
         Ok(socket_addr) => {
-            let channel = make_channel(socket_addr).await;
+            let channel = channel_factory.make_channel(socket_addr).await;
             try_new_node_with_channel(cluster_id, chitchat_id, node_state, channel, is_self_node)
         }
         Err(error) => {
@@ -390,6 +400,7 @@ mod tests {
             let events = compute_cluster_change_events_on_added(
                 &cluster_id,
                 &self_chitchat_id,
+                &ChannelFactory::default(),
                 &new_chitchat_id,
                 &new_node_state,
                 &mut previous_nodes,
@@ -412,6 +423,7 @@ mod tests {
             let events = compute_cluster_change_events_on_added(
                 &cluster_id,
                 &self_chitchat_id,
+                &ChannelFactory::default(),
                 &new_chitchat_id,
                 &new_node_state,
                 &mut previous_nodes,
@@ -440,6 +452,7 @@ mod tests {
             let events = compute_cluster_change_events_on_added(
                 &cluster_id,
                 &self_chitchat_id,
+                &ChannelFactory::default(),
                 &new_chitchat_id,
                 &new_node_state,
                 &mut previous_nodes,
@@ -462,6 +475,7 @@ mod tests {
             let events = compute_cluster_change_events_on_added(
                 &cluster_id,
                 &self_chitchat_id,
+                &ChannelFactory::default(),
                 &rejoined_chitchat_id,
                 &new_node_state,
                 &mut previous_nodes,
@@ -490,6 +504,7 @@ mod tests {
             let events = compute_cluster_change_events_on_added(
                 &cluster_id,
                 &self_chitchat_id,
+                &ChannelFactory::default(),
                 &new_chitchat_id,
                 &new_node_state,
                 &mut previous_nodes,
@@ -514,6 +529,7 @@ mod tests {
             let events = compute_cluster_change_events_on_added(
                 &cluster_id,
                 &self_chitchat_id,
+                &ChannelFactory::default(),
                 &new_chitchat_id,
                 &new_node_state,
                 &mut previous_nodes,
@@ -819,6 +835,7 @@ mod tests {
             let events = compute_cluster_change_events(
                 &cluster_id,
                 &self_chitchat_id,
+                &ChannelFactory::default(),
                 &mut previous_nodes,
                 &previous_node_states,
                 &new_node_states,
@@ -848,6 +865,7 @@ mod tests {
             let events = compute_cluster_change_events(
                 &cluster_id,
                 &self_chitchat_id,
+                &ChannelFactory::default(),
                 &mut previous_nodes,
                 &previous_node_states,
                 &new_node_states,
@@ -865,6 +883,7 @@ mod tests {
             let events = compute_cluster_change_events(
                 &cluster_id,
                 &self_chitchat_id,
+                &ChannelFactory::default(),
                 &mut previous_nodes,
                 &previous_node_states,
                 &new_node_states,
@@ -879,6 +898,7 @@ mod tests {
             let events = compute_cluster_change_events(
                 &cluster_id,
                 &self_chitchat_id,
+                &ChannelFactory::default(),
                 &mut previous_nodes,
                 &new_node_states,
                 &new_node_states,
@@ -911,6 +931,7 @@ mod tests {
             let events = compute_cluster_change_events(
                 &cluster_id,
                 &self_chitchat_id,
+                &ChannelFactory::default(),
                 &mut previous_nodes,
                 &previous_node_states,
                 &new_node_states,
@@ -930,6 +951,7 @@ mod tests {
             let events = compute_cluster_change_events(
                 &cluster_id,
                 &self_chitchat_id,
+                &ChannelFactory::default(),
                 &mut previous_nodes,
                 &previous_node_states,
                 &new_node_states,
@@ -32,6 +32,7 @@ use chitchat::{
 };
 use futures::Stream;
 use itertools::Itertools;
+use quickwit_common::tower::ChannelFactory;
 use quickwit_proto::indexing::{IndexingPipelineId, IndexingTask, PipelineMetrics};
 use quickwit_proto::types::{NodeId, PipelineUid, ShardId};
 use serde::{Deserialize, Serialize};
@@ -116,6 +117,7 @@ impl Cluster {
         gossip_listen_addr: SocketAddr,
         peer_seed_addrs: Vec<String>,
         failure_detector_config: FailureDetectorConfig,
+        channel_factory: ChannelFactory,
         transport: &dyn Transport,
     ) -> anyhow::Result<Self> {
         info!(
@@ -170,6 +172,7 @@ impl Cluster {
             cluster_id: cluster_id.clone(),
             self_chitchat_id: self_node.chitchat_id(),
             chitchat_handle,
+            channel_factory,
             live_nodes: BTreeMap::new(),
             change_stream_subscribers: Vec::new(),
             ready_members_rx,
@@ -504,6 +507,7 @@ async fn spawn_ready_nodes_change_stream_task(cluster: Cluster) {
     let cluster_guard = cluster.inner.read().await;
     let cluster_id = cluster_guard.cluster_id.clone();
     let self_chitchat_id = cluster_guard.self_chitchat_id.clone();
+    let channel_factory = cluster_guard.channel_factory.clone();
     let chitchat = cluster_guard.chitchat_handle.chitchat();
     let weak_cluster = Arc::downgrade(&cluster.inner);
     drop(cluster_guard);
@@ -523,6 +527,7 @@ async fn spawn_ready_nodes_change_stream_task(cluster: Cluster) {
             let events = compute_cluster_change_events(
                 &cluster_id,
                 &self_chitchat_id,
+                &channel_factory,
                 previous_live_nodes,
                 &previous_live_node_states,
                 &new_live_node_states,
@@ -547,6 +552,8 @@ struct InnerCluster {
     cluster_id: String,
     self_chitchat_id: ChitchatId,
     chitchat_handle: ChitchatHandle,
+    /// Creates the channels to the gRPC servers of the nodes joining the cluster.
+    channel_factory: ChannelFactory,
     live_nodes: BTreeMap<NodeId, ClusterNode>,
     change_stream_subscribers: Vec<mpsc::UnboundedSender<ClusterChange>>,
     ready_members_rx: watch::Receiver<Vec<ClusterMember>>,
@@ -640,6 +647,7 @@ pub async fn create_cluster_for_test_with_id(
         gossip_advertise_addr,
         peer_seed_addrs,
         failure_detector_config,
+        ChannelFactory::default(),
         transport,
     )
     .await?;
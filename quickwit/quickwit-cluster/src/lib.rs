@@ -24,9 +24,12 @@ mod cluster;
 mod member;
 mod node;
 
+use anyhow::Context;
 pub use chitchat::transport::ChannelTransport;
 use chitchat::transport::UdpTransport;
 pub use chitchat::{FailureDetectorConfig, KeyChangeEvent, ListenerHandle};
+use quickwit_common::tls::GrpcClientTls;
+use quickwit_common::tower::ChannelFactory;
 use quickwit_config::service::QuickwitService;
 use quickwit_config::NodeConfig;
 use quickwit_proto::indexing::CpuCapacity;
@@ -61,7 +64,21 @@ impl From<u64> for GenerationId {
     }
 }
 
+/// Creates the factory of the channels connecting the node to the other nodes of the cluster. The
+/// channels use TLS if it is enabled on the gRPC server of the node, and present the certificate
+/// of the node.
+fn make_channel_factory(node_config: &NodeConfig) -> anyhow::Result<ChannelFactory> {
+    let Some(tls_config) = &node_config.grpc_config.tls else {
+        return Ok(ChannelFactory::default());
+    };
+    let grpc_client_tls =
+        GrpcClientTls::load(tls_config.tls_files(), tls_config.server_name.clone())
+            .context("failed to load gRPC client TLS configuration")?;
+    Ok(ChannelFactory::new(Some(grpc_client_tls)))
+}
+
 pub async fn start_cluster_service(node_config: &NodeConfig) -> anyhow::Result<Cluster> {
+    let channel_factory = make_channel_factory(node_config)?;
     let cluster_id = node_config.cluster_id.clone();
     let gossip_listen_addr = node_config.gossip_listen_addr;
     let peer_seed_addrs = node_config.peer_seed_addrs().await?;
@@ -91,6 +108,7 @@ pub async fn start_cluster_service(node_config: &NodeConfig) -> anyhow::Result<C
         gossip_listen_addr,
         peer_seed_addrs,
         FailureDetectorConfig::default(),
+        channel_factory,
         &UdpTransport,
     )
     .await?;
@@ -41,6 +41,7 @@ pub mod stream_utils;
 pub mod temp_dir;
 #[cfg(any(test, feature = "testsuite"))]
 pub mod test_utils;
+pub mod tls;
 pub mod tower;
 pub mod type_map;
 pub mod uri;
@@ -133,6 +134,11 @@ pub fn is_false(value: &bool) -> bool {
     !*value
 }
 
+/// For use with the `skip_serializing_if` serde attribute.
+pub fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
 pub fn no_color() -> bool {
     matches!(env::var("NO_COLOR"), Ok(value) if !value.is_empty())
 }
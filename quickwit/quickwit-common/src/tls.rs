@@ -0,0 +1,586 @@
+// Copyright (C) 2024 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! TLS support for the REST and gRPC listeners of a node, and for the gRPC clients connecting to
+//! the other nodes of the cluster.
+//!
+//! Certificates, private keys and CA certificates are read from PEM files, which are polled for
+//! changes so that certificates can be rotated without restarting the node.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{bail, Context as _};
+use http::Uri;
+use rustls::server::AllowAnyAuthenticatedClient;
+use rustls::{Certificate, ClientConfig, PrivateKey, RootCertStore, ServerConfig, ServerName};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tonic::transport::server::Connected;
+use tower::Service;
+use tracing::{debug, error, info, warn};
+
+use crate::tower::BoxFuture;
+
+/// Interval at which the TLS files are checked for modifications.
+const TLS_FILES_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Maximum duration of a TLS handshake on the server side.
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// ALPN protocol of HTTP/2, the only protocol spoken by gRPC.
+pub const ALPN_H2: &[u8] = b"h2";
+
+/// ALPN protocol of HTTP/1.1.
+pub const ALPN_HTTP_1_1: &[u8] = b"http/1.1";
+
+/// Paths of the PEM files making up a TLS configuration.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TlsFiles {
+    /// Certificate chain presented to the peers.
+    pub cert_path: PathBuf,
+    /// Private key of the certificate.
+    pub key_path: PathBuf,
+    /// CA certificates used to verify the certificates of the peers.
+    pub ca_path_opt: Option<PathBuf>,
+}
+
+impl TlsFiles {
+    fn paths(&self) -> impl Iterator<Item = &Path> {
+        [
+            Some(&self.cert_path),
+            Some(&self.key_path),
+            self.ca_path_opt.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(PathBuf::as_path)
+    }
+
+    fn last_modified(&self) -> Vec<Option<SystemTime>> {
+        self.paths()
+            .map(|path| {
+                path.metadata()
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+            })
+            .collect()
+    }
+
+    fn load_certs(&self) -> anyhow::Result<Vec<Certificate>> {
+        let certs = load_certs(&self.cert_path)?;
+        if certs.is_empty() {
+            bail!("no certificate found in `{}`", self.cert_path.display());
+        }
+        Ok(certs)
+    }
+
+    fn load_private_key(&self) -> anyhow::Result<PrivateKey> {
+        let key_path = &self.key_path;
+        let mut reader = open_pem_file(key_path)?;
+        let items = rustls_pemfile::read_all(&mut reader)
+            .with_context(|| format!("failed to parse PEM file `{}`", key_path.display()))?;
+        for item in items {
+            match item {
+                rustls_pemfile::Item::PKCS8Key(key)
+                | rustls_pemfile::Item::RSAKey(key)
+                | rustls_pemfile::Item::ECKey(key) => return Ok(PrivateKey(key)),
+                _ => {}
+            }
+        }
+        bail!("no private key found in `{}`", key_path.display());
+    }
+
+    fn load_root_cert_store(&self) -> anyhow::Result<Option<RootCertStore>> {
+        let Some(ca_path) = &self.ca_path_opt else {
+            return Ok(None);
+        };
+        let mut root_cert_store = RootCertStore::empty();
+        for cert in load_certs(ca_path)? {
+            root_cert_store
+                .add(&cert)
+                .with_context(|| format!("invalid CA certificate in `{}`", ca_path.display()))?;
+        }
+        if root_cert_store.is_empty() {
+            bail!("no CA certificate found in `{}`", ca_path.display());
+        }
+        Ok(Some(root_cert_store))
+    }
+
+    /// Builds the configuration of a TLS server. If `validate_client` is true, the clients must
+    /// present a certificate signed by one of the CA certificates.
+    pub fn server_config(
+        &self,
+        validate_client: bool,
+        alpn_protocols: &[&[u8]],
+    ) -> anyhow::Result<ServerConfig> {
+        let config_builder = ServerConfig::builder().with_safe_defaults();
+        let config_builder = if validate_client {
+            let Some(root_cert_store) = self.load_root_cert_store()? else {
+                bail!("validating client certificates requires a CA certificate");
+            };
+            config_builder.with_client_cert_verifier(
+                AllowAnyAuthenticatedClient::new(root_cert_store).boxed(),
+            )
+        } else {
+            config_builder.with_no_client_auth()
+        };
+        let mut server_config = config_builder
+            .with_single_cert(self.load_certs()?, self.load_private_key()?)
+            .context("invalid certificate or private key")?;
+        server_config.alpn_protocols = alpn_protocols
+            .iter()
+            .map(|protocol| protocol.to_vec())
+            .collect();
+        Ok(server_config)
+    }
+
+    /// Builds the configuration of a TLS client verifying the certificates of the servers with
+    /// the CA certificates, and presenting its own certificate to the servers.
+    pub fn client_config(&self) -> anyhow::Result<ClientConfig> {
+        let Some(root_cert_store) = self.load_root_cert_store()? else {
+            bail!("verifying server certificates requires a CA certificate");
+        };
+        let mut client_config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(root_cert_store)
+            .with_client_auth_cert(self.load_certs()?, self.load_private_key()?)
+            .context("invalid certificate or private key")?;
+        client_config.alpn_protocols = vec![ALPN_H2.to_vec()];
+        Ok(client_config)
+    }
+}
+
+fn open_pem_file(path: &Path) -> anyhow::Result<BufReader<File>> {
+    let file = File::open(path)
+        .with_context(|| format!("failed to open PEM file `{}`", path.display()))?;
+    Ok(BufReader::new(file))
+}
+
+fn load_certs(path: &Path) -> anyhow::Result<Vec<Certificate>> {
+    let mut reader = open_pem_file(path)?;
+    let certs = rustls_pemfile::certs(&mut reader)
+        .with_context(|| format!("failed to parse PEM file `{}`", path.display()))?
+        .into_iter()
+        .map(Certificate)
+        .collect();
+    Ok(certs)
+}
+
+/// A TLS configuration rebuilt from its files whenever one of them is modified. A modification
+/// leading to an invalid configuration is logged and ignored, and the previous configuration is
+/// kept.
+pub struct ReloadableTlsConfig<T> {
+    current: Arc<RwLock<Arc<T>>>,
+}
+
+impl<T> Clone for ReloadableTlsConfig<T> {
+    fn clone(&self) -> Self {
+        ReloadableTlsConfig {
+            current: self.current.clone(),
+        }
+    }
+}
+
+impl<T: Send + Sync + 'static> ReloadableTlsConfig<T> {
+    /// Builds the configuration and starts watching its files. The files stop being watched once
+    /// the configuration and all its clones are dropped.
+    ///
+    /// The function must be called from a tokio runtime.
+    pub fn load<F>(tls_files: TlsFiles, build_fn: F) -> anyhow::Result<Self>
+    where F: Fn(&TlsFiles) -> anyhow::Result<T> + Send + 'static {
+        Self::load_with_poll_interval(tls_files, build_fn, TLS_FILES_POLL_INTERVAL)
+    }
+
+    fn load_with_poll_interval<F>(
+        tls_files: TlsFiles,
+        build_fn: F,
+        poll_interval: Duration,
+    ) -> anyhow::Result<Self>
+    where
+        F: Fn(&TlsFiles) -> anyhow::Result<T> + Send + 'static,
+    {
+        let mut last_modified = tls_files.last_modified();
+        let config = build_fn(&tls_files)?;
+        let current = Arc::new(RwLock::new(Arc::new(config)));
+        let current_weak = Arc::downgrade(&current);
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(poll_interval);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let Some(current) = current_weak.upgrade() else {
+                    return;
+                };
+                let modified = tls_files.last_modified();
+                if modified == last_modified {
+                    continue;
+                }
+                last_modified = modified;
+
+                match build_fn(&tls_files) {
+                    Ok(config) => {
+                        *current.write().expect("the lock should not be poisoned") =
+                            Arc::new(config);
+                        info!(cert_path=%tls_files.cert_path.display(), "reloaded TLS configuration");
+                    }
+                    Err(error) => {
+                        error!(cert_path=%tls_files.cert_path.display(), error=?error, "failed to reload TLS configuration");
+                    }
+                }
+            }
+        });
+        Ok(ReloadableTlsConfig { current })
+    }
+
+    /// Returns the latest valid configuration.
+    pub fn get(&self) -> Arc<T> {
+        self.current
+            .read()
+            .expect("the lock should not be poisoned")
+            .clone()
+    }
+}
+
+/// A TLS connection accepted by a server.
+pub struct TlsServerStream {
+    inner: tokio_rustls::server::TlsStream<TcpStream>,
+    remote_addr: SocketAddr,
+}
+
+impl AsyncRead for TlsServerStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for TlsServerStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+impl Connected for TlsServerStream {
+    type ConnectInfo = SocketAddr;
+
+    fn connect_info(&self) -> Self::ConnectInfo {
+        self.remote_addr
+    }
+}
+
+/// Stream of the TLS connections accepted by a server.
+pub type TlsIncoming = UnboundedReceiverStream<io::Result<TlsServerStream>>;
+
+/// Binds a TCP listener to `listen_addr` and returns the stream of the TLS connections accepted
+/// on it. The handshakes run concurrently, and the connections whose handshake fails are dropped.
+///
+/// The listener is closed once the stream is dropped.
+pub async fn bind_tls_incoming(
+    listen_addr: SocketAddr,
+    server_config: ReloadableTlsConfig<ServerConfig>,
+) -> io::Result<TlsIncoming> {
+    let listener = TcpListener::bind(listen_addr).await?;
+    let (incoming_tx, incoming_rx) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        loop {
+            let accept_res = tokio::select! {
+                accept_res = listener.accept() => accept_res,
+                _ = incoming_tx.closed() => return,
+            };
+            let (tcp_stream, remote_addr) = match accept_res {
+                Ok(accepted) => accepted,
+                Err(error) => {
+                    // Errors such as reaching the maximum number of open files are transient.
+                    warn!(error=?error, "failed to accept TCP connection");
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            };
+            let tls_acceptor = TlsAcceptor::from(server_config.get());
+            let incoming_tx = incoming_tx.clone();
+
+            tokio::spawn(async move {
+                let accept_fut = tls_acceptor.accept(tcp_stream);
+                match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, accept_fut).await {
+                    Ok(Ok(inner)) => {
+                        let tls_stream = TlsServerStream { inner, remote_addr };
+                        let _ = incoming_tx.send(Ok(tls_stream));
+                    }
+                    Ok(Err(error)) => {
+                        debug!(remote_addr=%remote_addr, error=?error, "TLS handshake failed");
+                    }
+                    Err(_) => {
+                        debug!(remote_addr=%remote_addr, "TLS handshake timed out");
+                    }
+                }
+            });
+        }
+    });
+    Ok(UnboundedReceiverStream::new(incoming_rx))
+}
+
+/// Connector establishing the TLS connections of the gRPC clients of a node to the other nodes of
+/// the cluster.
+#[derive(Clone)]
+pub struct GrpcClientTls {
+    client_config: ReloadableTlsConfig<ClientConfig>,
+    server_name_opt: Option<String>,
+}
+
+impl GrpcClientTls {
+    /// Builds a connector presenting the certificate of `tls_files`. The certificates of the other
+    /// nodes are verified against the CA certificates and `server_name_opt`, or their IP address
+    /// if not set.
+    ///
+    /// The function must be called from a tokio runtime.
+    pub fn load(tls_files: TlsFiles, server_name_opt: Option<String>) -> anyhow::Result<Self> {
+        let client_config = ReloadableTlsConfig::load(tls_files, TlsFiles::client_config)?;
+        Ok(GrpcClientTls {
+            client_config,
+            server_name_opt,
+        })
+    }
+}
+
+impl fmt::Debug for GrpcClientTls {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GrpcClientTls")
+            .field("server_name_opt", &self.server_name_opt)
+            .finish()
+    }
+}
+
+impl Service<Uri> for GrpcClientTls {
+    type Response = tokio_rustls::client::TlsStream<TcpStream>;
+    type Error = io::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let tls_connector = TlsConnector::from(self.client_config.get());
+        let server_name_opt = self.server_name_opt.clone();
+
+        Box::pin(async move {
+            let invalid_input =
+                |message: String| io::Error::new(io::ErrorKind::InvalidInput, message);
+            let host = uri
+                .host()
+                .ok_or_else(|| invalid_input(format!("missing host in URI `{uri}`")))?
+                .trim_start_matches('[')
+                .trim_end_matches(']');
+            let port = uri
+                .port_u16()
+                .ok_or_else(|| invalid_input(format!("missing port in URI `{uri}`")))?;
+            let server_name_str = server_name_opt.as_deref().unwrap_or(host);
+            let server_name = ServerName::try_from(server_name_str).map_err(|_| {
+                invalid_input(format!("invalid TLS server name `{server_name_str}`"))
+            })?;
+            let tcp_stream = TcpStream::connect((host, port)).await?;
+            tcp_stream.set_nodelay(true)?;
+            tls_connector.connect(server_name, tcp_stream).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+
+    fn test_tls_files() -> TlsFiles {
+        let tls_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/tests/tls");
+        TlsFiles {
+            cert_path: tls_dir.join("node.pem"),
+            key_path: tls_dir.join("node.key"),
+            ca_path_opt: Some(tls_dir.join("ca.pem")),
+        }
+    }
+
+    #[test]
+    fn test_tls_files_build_configs() {
+        let tls_files = test_tls_files();
+        let server_config = tls_files.server_config(true, &[ALPN_H2]).unwrap();
+        assert_eq!(server_config.alpn_protocols, vec![b"h2".to_vec()]);
+        tls_files.client_config().unwrap();
+
+        let tls_files_without_ca = TlsFiles {
+            ca_path_opt: None,
+            ..test_tls_files()
+        };
+        tls_files_without_ca.server_config(false, &[]).unwrap();
+        let error = tls_files_without_ca.server_config(true, &[]).unwrap_err();
+        assert!(error.to_string().contains("requires a CA certificate"));
+        tls_files_without_ca.client_config().unwrap_err();
+
+        let tls_files_with_swapped_files = TlsFiles {
+            cert_path: tls_files.key_path.clone(),
+            key_path: tls_files.cert_path.clone(),
+            ..test_tls_files()
+        };
+        let error = tls_files_with_swapped_files
+            .server_config(false, &[])
+            .unwrap_err();
+        assert!(error.to_string().contains("no certificate found"));
+
+        let tls_files_with_missing_file = TlsFiles {
+            cert_path: PathBuf::from("does/not/exist.pem"),
+            ..test_tls_files()
+        };
+        let error = tls_files_with_missing_file
+            .server_config(false, &[])
+            .unwrap_err();
+        assert!(error.to_string().contains("failed to open PEM file"));
+    }
+
+    #[tokio::test]
+    async fn test_tls_incoming_with_client_validation() {
+        let server_config = ReloadableTlsConfig::load(test_tls_files(), |tls_files| {
+            tls_files.server_config(true, &[ALPN_H2])
+        })
+        .unwrap();
+        let listen_addr: SocketAddr = ([127, 0, 0, 1], 0).into();
+        let listener = std::net::TcpListener::bind(listen_addr).unwrap();
+        let listen_addr = listener.local_addr().unwrap();
+        drop(listener);
+        let mut tls_incoming = bind_tls_incoming(listen_addr, server_config).await.unwrap();
+
+        let mut grpc_client_tls = GrpcClientTls::load(test_tls_files(), None).unwrap();
+        let uri: Uri = format!("https://{listen_addr}").parse().unwrap();
+        let mut client_stream = grpc_client_tls.call(uri.clone()).await.unwrap();
+        let mut server_stream = tls_incoming.next().await.unwrap().unwrap();
+        assert_eq!(server_stream.connect_info().ip(), listen_addr.ip());
+
+        client_stream.write_all(b"hello").await.unwrap();
+        client_stream.flush().await.unwrap();
+        let mut buffer = [0u8; 5];
+        server_stream.read_exact(&mut buffer).await.unwrap();
+        assert_eq!(&buffer, b"hello");
+
+        // The certificate is not valid for this name.
+        let mut grpc_client_tls_with_wrong_name = GrpcClientTls {
+            server_name_opt: Some("quickwit.io".to_string()),
+            ..grpc_client_tls.clone()
+        };
+        grpc_client_tls_with_wrong_name.call(uri).await.unwrap_err();
+
+        // Clients without a certificate are rejected.
+        let mut root_cert_store = RootCertStore::empty();
+        for cert in load_certs(test_tls_files().ca_path_opt.as_ref().unwrap()).unwrap() {
+            root_cert_store.add(&cert).unwrap();
+        }
+        let client_config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(root_cert_store)
+            .with_no_client_auth();
+        let tcp_stream = TcpStream::connect(listen_addr).await.unwrap();
+        let server_name = ServerName::try_from("localhost").unwrap();
+        let connect_res = TlsConnector::from(Arc::new(client_config))
+            .connect(server_name, tcp_stream)
+            .await;
+        if let Ok(mut client_stream) = connect_res {
+            // With TLS 1.3, the client learns that it was rejected on its first read.
+            let read_res = client_stream.read(&mut buffer).await;
+            assert!(matches!(read_res, Err(_) | Ok(0)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reloadable_tls_config() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let test_tls_files = test_tls_files();
+        let tls_files = TlsFiles {
+            cert_path: temp_dir.path().join("node.pem"),
+            key_path: temp_dir.path().join("node.key"),
+            ca_path_opt: Some(temp_dir.path().join("ca.pem")),
+        };
+        std::fs::copy(&test_tls_files.cert_path, &tls_files.cert_path).unwrap();
+        std::fs::copy(&test_tls_files.key_path, &tls_files.key_path).unwrap();
+        std::fs::copy(
+            test_tls_files.ca_path_opt.unwrap(),
+            tls_files.ca_path_opt.as_ref().unwrap(),
+        )
+        .unwrap();
+
+        let poll_interval = Duration::from_millis(50);
+        let reloadable_config = ReloadableTlsConfig::load_with_poll_interval(
+            tls_files.clone(),
+            TlsFiles::client_config,
+            poll_interval,
+        )
+        .unwrap();
+        let initial_config = reloadable_config.get();
+
+        tokio::time::sleep(poll_interval * 3).await;
+        assert!(Arc::ptr_eq(&initial_config, &reloadable_config.get()));
+
+        // An invalid configuration is ignored.
+        std::fs::write(&tls_files.key_path, "invalid key").unwrap();
+        tokio::time::sleep(poll_interval * 3).await;
+        assert!(Arc::ptr_eq(&initial_config, &reloadable_config.get()));
+
+        std::fs::copy(&test_tls_files.key_path, &tls_files.key_path).unwrap();
+        tokio::time::sleep(poll_interval * 3).await;
+        assert!(!Arc::ptr_eq(&initial_config, &reloadable_config.get()));
+    }
+}
@@ -0,0 +1,192 @@
+// Copyright (C) 2024 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures::{ready, Future};
+use http::HeaderValue;
+use pin_project::pin_project;
+use tokio::time::Sleep;
+use tower::timeout::error::Elapsed;
+use tower::{Layer, Service};
+
+use super::BoxError;
+
+const GRPC_TIMEOUT_HEADER: &str = "grpc-timeout";
+
+/// Applies a timeout to gRPC requests. The timeout of a request is read from its `grpc-timeout`
+/// header, which is set with [`tonic::Request::set_timeout`], and falls back to
+/// `default_timeout` if the header is absent.
+#[derive(Debug, Clone)]
+pub struct GrpcTimeout<S> {
+    inner: S,
+    default_timeout: Duration,
+}
+
+impl<S> GrpcTimeout<S> {
+    pub fn new(inner: S, default_timeout: Duration) -> Self {
+        Self {
+            inner,
+            default_timeout,
+        }
+    }
+}
+
+impl<S, B> Service<http::Request<B>> for GrpcTimeout<S>
+where
+    S: Service<http::Request<B>>,
+    S::Error: Into<BoxError>,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let timeout = request
+            .headers()
+            .get(GRPC_TIMEOUT_HEADER)
+            .and_then(parse_grpc_timeout)
+            .unwrap_or(self.default_timeout);
+        ResponseFuture {
+            inner: self.inner.call(request),
+            sleep: tokio::time::sleep(timeout),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GrpcTimeoutLayer {
+    default_timeout: Duration,
+}
+
+impl GrpcTimeoutLayer {
+    pub fn new(default_timeout: Duration) -> Self {
+        Self { default_timeout }
+    }
+}
+
+impl<S> Layer<S> for GrpcTimeoutLayer {
+    type Service = GrpcTimeout<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        GrpcTimeout::new(service, self.default_timeout)
+    }
+}
+
+/// Response future for [`GrpcTimeout`].
+#[pin_project]
+pub struct ResponseFuture<F> {
+    #[pin]
+    inner: F,
+    #[pin]
+    sleep: Sleep,
+}
+
+impl<F, T, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<T, E>>,
+    E: Into<BoxError>,
+{
+    type Output = Result<T, BoxError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        if let Poll::Ready(result) = this.inner.poll(cx) {
+            return Poll::Ready(result.map_err(Into::into));
+        }
+        ready!(this.sleep.poll(cx));
+        Poll::Ready(Err(Elapsed::new().into()))
+    }
+}
+
+/// Parses the value of a `grpc-timeout` header, made of a positive integer followed by a time
+/// unit.
+fn parse_grpc_timeout(header_value: &HeaderValue) -> Option<Duration> {
+    let header_value = header_value.to_str().ok()?;
+
+    if header_value.len() < 2 {
+        return None;
+    }
+    let (amount_str, unit) = header_value.split_at(header_value.len() - 1);
+    let amount: u64 = amount_str.parse().ok()?;
+
+    let timeout = match unit {
+        "H" => Duration::from_secs(amount.saturating_mul(3_600)),
+        "M" => Duration::from_secs(amount.saturating_mul(60)),
+        "S" => Duration::from_secs(amount),
+        "m" => Duration::from_millis(amount),
+        "u" => Duration::from_micros(amount),
+        "n" => Duration::from_nanos(amount),
+        _ => return None,
+    };
+    Some(timeout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_grpc_timeout() {
+        let parse = |value: &'static str| parse_grpc_timeout(&HeaderValue::from_static(value));
+
+        assert_eq!(parse("2H"), Some(Duration::from_secs(7_200)));
+        assert_eq!(parse("3M"), Some(Duration::from_secs(180)));
+        assert_eq!(parse("4S"), Some(Duration::from_secs(4)));
+        assert_eq!(parse("1500m"), Some(Duration::from_millis(1_500)));
+        assert_eq!(parse("10u"), Some(Duration::from_micros(10)));
+        assert_eq!(parse("10n"), Some(Duration::from_nanos(10)));
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("m"), None);
+        assert_eq!(parse("10"), None);
+        assert_eq!(parse("10x"), None);
+        assert_eq!(parse("-10m"), None);
+    }
+
+    #[tokio::test]
+    async fn test_grpc_timeout() {
+        let layer = GrpcTimeoutLayer::new(Duration::from_millis(50));
+        let mut service = layer.layer(tower::service_fn(|_request: http::Request<()>| async {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            Ok::<_, BoxError>(())
+        }));
+        let error = service.call(http::Request::new(())).await.unwrap_err();
+        assert!(error.is::<Elapsed>());
+
+        let request = http::Request::builder()
+            .header(GRPC_TIMEOUT_HEADER, "1S")
+            .body(())
+            .unwrap();
+        service.call(request).await.unwrap();
+
+        let request = http::Request::builder()
+            .header(GRPC_TIMEOUT_HEADER, "10m")
+            .body(())
+            .unwrap();
+        let error = service.call(request).await.unwrap_err();
+        assert!(error.is::<Elapsed>());
+    }
+}
@@ -23,6 +23,7 @@ mod buffer;
 mod change;
 mod estimate_rate;
 mod event_listener;
+mod grpc_timeout;
 mod metrics;
 mod pool;
 mod rate;
@@ -41,13 +42,14 @@ pub use change::Change;
 pub use estimate_rate::{EstimateRate, EstimateRateLayer};
 pub use event_listener::{EventListener, EventListenerLayer};
 use futures::Future;
+pub use grpc_timeout::{GrpcTimeout, GrpcTimeoutLayer};
 pub use metrics::{PrometheusMetrics, PrometheusMetricsLayer};
 pub use pool::Pool;
 pub use rate::{ConstantRate, Rate};
 pub use rate_estimator::{RateEstimator, SmaRateEstimator};
 pub use rate_limit::{RateLimit, RateLimitLayer};
 pub use retry::{RetryLayer, RetryPolicy};
-pub use transport::{make_channel, warmup_channel, BalanceChannel};
+pub use transport::{make_channel, warmup_channel, BalanceChannel, ChannelFactory};
 
 pub type BoxError = Box<dyn error::Error + Send + Sync + 'static>;
 
@@ -39,6 +39,7 @@ use tower::load::{CompleteOnResponse, PendingRequestsDiscover};
 use tower::{BoxError, Service, ServiceExt};
 
 use super::{BoxFuture, Change};
+use crate::tls::GrpcClientTls;
 use crate::BoxStream;
 
 // Transforms a boxed stream of `Change<K, Channel>` into a stream of `Result<TowerChange<K,
@@ -188,16 +189,48 @@ where K: Hash + Eq + Clone + Send + Sync + 'static
 ///
 /// The function is marked as `async` because it requires an executor (`connect_lazy`).
 pub async fn make_channel(socket_addr: SocketAddr) -> Channel {
-    let uri = Uri::builder()
-        .scheme("http")
-        .authority(socket_addr.to_string())
-        .path_and_query("/")
-        .build()
-        .expect("The provided arguments should be valid.");
-    Endpoint::from(uri)
-        .connect_timeout(Duration::from_secs(5))
-        .timeout(Duration::from_secs(30))
-        .connect_lazy()
+    ChannelFactory::default().make_channel(socket_addr).await
+}
+
+/// Creates the channels connecting a node to the gRPC servers of the other nodes of the cluster,
+/// over TLS if the factory holds a TLS connector.
+#[derive(Clone, Debug, Default)]
+pub struct ChannelFactory {
+    grpc_client_tls_opt: Option<GrpcClientTls>,
+}
+
+impl ChannelFactory {
+    pub fn new(grpc_client_tls_opt: Option<GrpcClientTls>) -> Self {
+        ChannelFactory {
+            grpc_client_tls_opt,
+        }
+    }
+
+    /// Creates a channel from a socket address.
+    ///
+    /// The function is marked as `async` because it requires an executor (`connect_lazy`).
+    pub async fn make_channel(&self, socket_addr: SocketAddr) -> Channel {
+        let scheme = if self.grpc_client_tls_opt.is_some() {
+            "https"
+        } else {
+            "http"
+        };
+        let uri = Uri::builder()
+            .scheme(scheme)
+            .authority(socket_addr.to_string())
+            .path_and_query("/")
+            .build()
+            .expect("The provided arguments should be valid.");
+        let endpoint = Endpoint::from(uri)
+            .connect_timeout(Duration::from_secs(5))
+            .timeout(Duration::from_secs(30));
+
+        if let Some(grpc_client_tls) = &self.grpc_client_tls_opt {
+            endpoint.connect_with_connector_lazy(grpc_client_tls.clone())
+        } else {
+            endpoint.connect_lazy()
+        }
+    }
 }
 
 /// Forces a channel to initiate the underlying HTTP connection. Calling this function only makes
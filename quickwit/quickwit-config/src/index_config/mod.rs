@@ -19,17 +19,18 @@
 
 pub(crate) mod serialize;
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::num::NonZeroU32;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 
-use anyhow::Context;
+use anyhow::{bail, Context};
 use bytesize::ByteSize;
 use chrono::Utc;
 use cron::Schedule;
 use humantime::parse_duration;
+use quickwit_common::is_zero;
 use quickwit_common::uri::Uri;
 use quickwit_doc_mapper::{
     DefaultDocMapper, DefaultDocMapperBuilder, DocMapper, FieldMappingEntry, Mode, ModeType,
@@ -89,6 +90,95 @@ pub struct DocMapping {
     pub max_num_partitions: NonZeroU32,
     #[serde(default)]
     pub tokenizers: Vec<TokenizerEntry>,
+    /// Version of the doc mapping, incremented every time the doc mapping of the index is
+    /// updated.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_zero")]
+    pub doc_mapping_version: u64,
+    /// Doc mapping version that introduced each of the field mappings appended to the doc
+    /// mapping after the creation of the index, keyed by field name.
+    #[schema(value_type = Object)]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub field_mapping_versions: BTreeMap<String, u64>,
+}
+
+impl DocMapping {
+    /// Returns the doc mapping resulting from the update of this doc mapping with
+    /// `new_doc_mapping`.
+    ///
+    /// Only appending field mappings and adding tag fields are supported: the splits created with
+    /// the previous versions of the doc mapping must remain searchable and mergeable with the
+    /// splits created with the new version. The version of the doc mapping is incremented if it is
+    /// modified.
+    pub fn evolve(&self, new_doc_mapping: DocMapping) -> anyhow::Result<DocMapping> {
+        let num_field_mappings = self.field_mappings.len();
+
+        if new_doc_mapping.field_mappings.len() < num_field_mappings
+            || new_doc_mapping.field_mappings[..num_field_mappings] != self.field_mappings[..]
+        {
+            bail!(
+                "existing field mappings cannot be modified or removed, new field mappings can \
+                 only be appended"
+            );
+        }
+        if !self.tag_fields.is_subset(&new_doc_mapping.tag_fields) {
+            bail!("existing tag fields cannot be removed");
+        }
+        let mut other_settings = new_doc_mapping.clone();
+        other_settings.field_mappings = self.field_mappings.clone();
+        other_settings.tag_fields = self.tag_fields.clone();
+        other_settings.doc_mapping_version = self.doc_mapping_version;
+        other_settings.field_mapping_versions = self.field_mapping_versions.clone();
+
+        if other_settings != *self {
+            bail!("only field mappings and tag fields can be added to the doc mapping of an index");
+        }
+        if new_doc_mapping.field_mappings.len() == num_field_mappings
+            && new_doc_mapping.tag_fields == self.tag_fields
+        {
+            return Ok(self.clone());
+        }
+        let doc_mapping_version = self.doc_mapping_version + 1;
+        let mut field_mapping_versions = self.field_mapping_versions.clone();
+
+        for field_mapping in &new_doc_mapping.field_mappings[num_field_mappings..] {
+            field_mapping_versions.insert(field_mapping.name.clone(), doc_mapping_version);
+        }
+        let evolved_doc_mapping = DocMapping {
+            doc_mapping_version,
+            field_mapping_versions,
+            ..new_doc_mapping
+        };
+        // The schema of the splits created with the current doc mapping must be a prefix of the
+        // new schema, so that the splits of both versions can be merged together.
+        let search_settings = SearchSettings::default();
+        let schema = build_doc_mapper(self, &search_settings)?.schema();
+        let evolved_schema = build_doc_mapper(&evolved_doc_mapping, &search_settings)?.schema();
+        let num_fields = schema.fields().count();
+
+        for ((_, field_entry), (_, evolved_field_entry)) in
+            schema.fields().zip(evolved_schema.fields())
+        {
+            if field_entry != evolved_field_entry {
+                bail!(
+                    "field `{}` of the index schema cannot be modified",
+                    field_entry.name()
+                );
+            }
+        }
+        for (_, field_entry) in evolved_schema.fields().skip(num_fields) {
+            // Older splits do not hold any field norms for the new fields, which would make them
+            // impossible to merge with newer splits.
+            if field_entry.has_fieldnorms() {
+                bail!(
+                    "field norms cannot be enabled on field `{}` appended to the doc mapping",
+                    field_entry.name()
+                );
+            }
+        }
+        Ok(evolved_doc_mapping)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, utoipa::ToSchema)]
@@ -306,6 +396,31 @@ pub struct IndexConfig {
 }
 
 impl IndexConfig {
+    /// Validates the settings of the index config that depend on each other: the retention
+    /// policy, the default search fields, and the merge policy.
+    ///
+    /// This validation is performed on creation as well as on update of an index config.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let Some(retention_policy) = &self.retention_policy {
+            retention_policy.validate()?;
+
+            if self.doc_mapping.timestamp_field.is_none() {
+                anyhow::bail!(
+                    "failed to validate index config. the retention policy requires a timestamp \
+                     field, but the indexing settings do not declare one"
+                );
+            }
+        }
+
+        // Note: this needs a deep refactoring to separate the doc mapping configuration,
+        // and doc mapper implementations.
+        // TODO see if we should store the byproducton the IndexConfig.
+        build_doc_mapper(&self.doc_mapping, &self.search_settings)?;
+
+        self.indexing_settings.merge_policy.validate()?;
+        Ok(())
+    }
+
     #[cfg(any(test, feature = "testsuite"))]
     pub fn for_test(index_id: &str, index_uri: &str) -> Self {
         let index_uri = Uri::from_str(index_uri).unwrap();
@@ -453,6 +568,8 @@ impl TestableForRegression for IndexConfig {
             max_num_partitions: NonZeroU32::new(100).unwrap(),
             timestamp_field: Some("timestamp".to_string()),
             tokenizers: vec![tokenizer],
+            doc_mapping_version: 0,
+            field_mapping_versions: BTreeMap::new(),
         };
         let retention_policy = Some(RetentionPolicy::new(
             "90 days".to_string(),
@@ -509,6 +626,10 @@ impl TestableForRegression for IndexConfig {
             self.doc_mapping.store_source,
             other.doc_mapping.store_source,
         );
+        assert_eq!(
+            self.doc_mapping.doc_mapping_version,
+            other.doc_mapping.doc_mapping_version,
+        );
         assert_eq!(self.indexing_settings, other.indexing_settings);
         assert_eq!(self.search_settings, other.search_settings);
     }
@@ -530,6 +651,8 @@ pub fn build_doc_mapper(
         partition_key: doc_mapping.partition_key.clone(),
         max_num_partitions: doc_mapping.max_num_partitions,
         tokenizers: doc_mapping.tokenizers.clone(),
+        doc_mapping_version: doc_mapping.doc_mapping_version,
+        field_mapping_versions: doc_mapping.field_mapping_versions.clone(),
     };
     Ok(Arc::new(builder.try_build()?))
 }
@@ -890,6 +1013,81 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_doc_mapping_evolve() {
+        let doc_mapping: DocMapping = serde_json::from_str(
+            r#"{
+                "store_source": true,
+                "field_mappings": [
+                    {"name": "body", "type": "text"},
+                    {"name": "tenant_id", "type": "u64"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let unchanged_doc_mapping = doc_mapping.evolve(doc_mapping.clone()).unwrap();
+        assert_eq!(unchanged_doc_mapping, doc_mapping);
+
+        let mut new_doc_mapping = doc_mapping.clone();
+        new_doc_mapping.tag_fields.insert("tenant_id".to_string());
+        new_doc_mapping.field_mappings.push(
+            serde_json::from_str(r#"{"name": "latency", "type": "u64", "fast": true}"#).unwrap(),
+        );
+        let evolved_doc_mapping = doc_mapping.evolve(new_doc_mapping.clone()).unwrap();
+        assert_eq!(evolved_doc_mapping.doc_mapping_version, 1);
+        assert_eq!(evolved_doc_mapping.field_mappings.len(), 3);
+        assert_eq!(evolved_doc_mapping.tag_fields.len(), 1);
+        assert_eq!(
+            evolved_doc_mapping.field_mapping_versions,
+            BTreeMap::from_iter([("latency".to_string(), 1)])
+        );
+        // The version is ignored in the new doc mapping.
+        let evolved_doc_mapping_bis = evolved_doc_mapping.evolve(new_doc_mapping).unwrap();
+        assert_eq!(evolved_doc_mapping_bis, evolved_doc_mapping);
+
+        let doc_mapper =
+            build_doc_mapper(&evolved_doc_mapping, &SearchSettings::default()).unwrap();
+        assert_eq!(doc_mapper.doc_mapping_version(), 1);
+        {
+            let mut new_doc_mapping = doc_mapping.clone();
+            new_doc_mapping.field_mappings.pop();
+            let error = doc_mapping.evolve(new_doc_mapping).unwrap_err();
+            assert!(error.to_string().contains("cannot be modified or removed"));
+        }
+        {
+            let mut new_doc_mapping = doc_mapping.clone();
+            new_doc_mapping.field_mappings.reverse();
+            doc_mapping.evolve(new_doc_mapping).unwrap_err();
+        }
+        {
+            let mut new_doc_mapping = doc_mapping.clone();
+            new_doc_mapping.store_source = false;
+            let error = doc_mapping.evolve(new_doc_mapping).unwrap_err();
+            assert!(error
+                .to_string()
+                .contains("only field mappings and tag fields can be added"));
+        }
+        {
+            let error = evolved_doc_mapping.evolve(doc_mapping.clone()).unwrap_err();
+            assert!(error.to_string().contains("cannot be modified or removed"));
+        }
+        {
+            let mut new_doc_mapping = evolved_doc_mapping.clone();
+            new_doc_mapping.tag_fields.clear();
+            let error = evolved_doc_mapping.evolve(new_doc_mapping).unwrap_err();
+            assert!(error.to_string().contains("tag fields cannot be removed"));
+        }
+        {
+            let mut new_doc_mapping = doc_mapping.clone();
+            new_doc_mapping.field_mappings.push(
+                serde_json::from_str(r#"{"name": "message", "type": "text", "fieldnorms": true}"#)
+                    .unwrap(),
+            );
+            let error = doc_mapping.evolve(new_doc_mapping).unwrap_err();
+            assert!(error.to_string().contains("field norms cannot be enabled"));
+        }
+    }
+
     #[test]
     fn test_retention_schedule_duration() {
         let schedule_test_helper_fn = |schedule_str: &str| {
@@ -23,8 +23,8 @@ use serde::{Deserialize, Serialize};
 use tracing::info;
 
 use crate::{
-    build_doc_mapper, validate_identifier, ConfigFormat, DocMapping, IndexConfig, IndexingSettings,
-    RetentionPolicy, SearchSettings,
+    validate_identifier, ConfigFormat, DocMapping, IndexConfig, IndexingSettings, RetentionPolicy,
+    SearchSettings,
 };
 
 /// Alias for the latest serialization format.
@@ -88,32 +88,16 @@ impl IndexConfigForSerialization {
 
         let index_uri = self.index_uri_or_fallback_to_default(default_index_root_uri)?;
 
-        if let Some(retention_policy) = &self.retention_policy {
-            retention_policy.validate()?;
-
-            if self.doc_mapping.timestamp_field.is_none() {
-                anyhow::bail!(
-                    "failed to validate index config. the retention policy requires a timestamp \
-                     field, but the indexing settings do not declare one"
-                );
-            }
-        }
-
-        // Note: this needs a deep refactoring to separate the doc mapping configuration,
-        // and doc mapper implementations.
-        // TODO see if we should store the byproducton the IndexConfig.
-        build_doc_mapper(&self.doc_mapping, &self.search_settings)?;
-
-        self.indexing_settings.merge_policy.validate()?;
-
-        Ok(IndexConfig {
+        let index_config = IndexConfig {
             index_id: self.index_id,
             index_uri,
             doc_mapping: self.doc_mapping,
             indexing_settings: self.indexing_settings,
             search_settings: self.search_settings,
             retention_policy: self.retention_policy,
-        })
+        };
+        index_config.validate()?;
+        Ok(index_config)
     }
 }
 
@@ -20,17 +20,18 @@
 mod serialize;
 
 use std::collections::{HashMap, HashSet};
-use std::env;
 use std::net::SocketAddr;
 use std::num::{NonZeroU32, NonZeroU64, NonZeroUsize};
 use std::path::PathBuf;
 use std::time::Duration;
+use std::{env, fmt};
 
 use anyhow::{bail, ensure};
 use bytesize::ByteSize;
 use http::HeaderMap;
 use once_cell::sync::Lazy;
 use quickwit_common::net::HostAddr;
+use quickwit_common::tls::TlsFiles;
 use quickwit_common::uri::Uri;
 use quickwit_proto::indexing::CpuCapacity;
 use serde::{Deserialize, Serialize};
@@ -39,7 +40,7 @@ use tracing::warn;
 use crate::node_config::serialize::load_node_config_with_env;
 use crate::service::QuickwitService;
 use crate::storage_config::StorageConfigs;
-use crate::{ConfigFormat, MetastoreConfigs};
+use crate::{validate_identifier, validate_index_id_pattern, ConfigFormat, MetastoreConfigs};
 
 pub const DEFAULT_QW_CONFIG_PATH: &str = "config/quickwit.yaml";
 
@@ -50,6 +51,105 @@ pub struct RestConfig {
     pub cors_allow_origins: Vec<String>,
     #[serde(with = "http_serde::header_map")]
     pub extra_headers: HeaderMap,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls: Option<TlsConfig>,
+    /// If not empty, requests to the REST API must be authenticated with one of these keys.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub api_keys: Vec<ApiKeyConfig>,
+}
+
+/// Operations an API key grants access to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKeyPermission {
+    /// Search the indexes and read their metadata.
+    Search,
+    /// Ingest documents into the indexes.
+    Ingest,
+    /// Manage the indexes, their sources, and the cluster. Implies the other permissions.
+    Admin,
+}
+
+impl ApiKeyPermission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Search => "search",
+            Self::Ingest => "ingest",
+            Self::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for ApiKeyPermission {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKeyConfig {
+    /// Name of the key, used in logs in place of the key itself.
+    pub name: String,
+    #[serde(skip_serializing)]
+    pub key: String,
+    pub permissions: Vec<ApiKeyPermission>,
+    /// Indexes the permissions apply to.
+    #[serde(default = "ApiKeyConfig::default_index_id_patterns")]
+    pub index_id_patterns: Vec<String>,
+}
+
+impl ApiKeyConfig {
+    fn default_index_id_patterns() -> Vec<String> {
+        vec!["*".to_string()]
+    }
+
+    fn validate(api_keys: &[ApiKeyConfig]) -> anyhow::Result<()> {
+        let mut names = HashSet::with_capacity(api_keys.len());
+        let mut keys = HashSet::with_capacity(api_keys.len());
+
+        for api_key in api_keys {
+            validate_identifier("API key name", &api_key.name)?;
+
+            if !names.insert(api_key.name.as_str()) {
+                bail!("API key name `{}` is not unique", api_key.name);
+            }
+            if api_key.key.is_empty() {
+                bail!("API key `{}` is empty", api_key.name);
+            }
+            if !keys.insert(api_key.key.as_str()) {
+                bail!("API key `{}` is not unique", api_key.name);
+            }
+            if api_key.permissions.is_empty() {
+                bail!(
+                    "API key `{}` must grant at least one permission",
+                    api_key.name
+                );
+            }
+            if api_key.index_id_patterns.is_empty() {
+                bail!(
+                    "API key `{}` must apply to at least one index ID pattern",
+                    api_key.name
+                );
+            }
+            for index_id_pattern in &api_key.index_id_patterns {
+                validate_index_id_pattern(index_id_pattern)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// The key is deliberately left out.
+impl fmt::Debug for ApiKeyConfig {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("ApiKeyConfig")
+            .field("name", &self.name)
+            .field("permissions", &self.permissions)
+            .field("index_id_patterns", &self.index_id_patterns)
+            .finish()
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -57,6 +157,10 @@ pub struct RestConfig {
 pub struct GrpcConfig {
     #[serde(default = "GrpcConfig::default_max_message_size")]
     pub max_message_size: ByteSize,
+    /// If set, the gRPC server only accepts TLS connections, and the gRPC clients of the node
+    /// connect to the other nodes of the cluster over TLS, presenting the certificate of the node.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls: Option<TlsConfig>,
 }
 
 impl GrpcConfig {
@@ -70,6 +174,14 @@ impl GrpcConfig {
             "max gRPC message size (`grpc.max_message_size`) must be at least 1MB, got `{}`",
             self.max_message_size
         );
+        if let Some(tls_config) = &self.tls {
+            tls_config.validate("grpc")?;
+            ensure!(
+                tls_config.ca_path.is_some(),
+                "gRPC TLS requires a CA certificate (`grpc.tls.ca_path`) to verify the \
+                 certificates of the other nodes of the cluster"
+            );
+        }
         Ok(())
     }
 }
@@ -78,6 +190,47 @@ impl Default for GrpcConfig {
     fn default() -> Self {
         Self {
             max_message_size: Self::default_max_message_size(),
+            tls: None,
+        }
+    }
+}
+
+/// TLS settings of the REST or gRPC server of a node. The certificates are reloaded when their
+/// files are modified.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConfig {
+    /// Path to the PEM file of the certificate chain of the node.
+    pub cert_path: PathBuf,
+    /// Path to the PEM file of the private key of the certificate.
+    pub key_path: PathBuf,
+    /// Path to the PEM file of the CA certificates used to verify the certificates of the peers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_path: Option<PathBuf>,
+    /// Whether clients must present a certificate signed by one of the CA certificates.
+    #[serde(default)]
+    pub validate_client: bool,
+    /// Name the gRPC clients verify the certificates of the other nodes against, instead of their
+    /// IP address. Only supported for gRPC.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_name: Option<String>,
+}
+
+impl TlsConfig {
+    fn validate(&self, section: &str) -> anyhow::Result<()> {
+        ensure!(
+            !self.validate_client || self.ca_path.is_some(),
+            "validating client certificates (`{section}.tls.validate_client`) requires a CA \
+             certificate (`{section}.tls.ca_path`)"
+        );
+        Ok(())
+    }
+
+    pub fn tls_files(&self) -> TlsFiles {
+        TlsFiles {
+            cert_path: self.cert_path.clone(),
+            key_path: self.key_path.clone(),
+            ca_path_opt: self.ca_path.clone(),
         }
     }
 }
@@ -99,6 +252,9 @@ pub struct IndexerConfig {
     pub enable_cooperative_indexing: bool,
     #[serde(default = "IndexerConfig::default_cpu_capacity")]
     pub cpu_capacity: CpuCapacity,
+    /// Policy used to respawn the indexing pipelines after one of their actors failed.
+    #[serde(default)]
+    pub pipeline_restart_policy: PipelineRestartPolicyConfig,
 }
 
 impl IndexerConfig {
@@ -133,6 +289,10 @@ impl IndexerConfig {
         CpuCapacity::one_cpu_thread() * (num_cpus::get() as u32)
     }
 
+    pub fn validate(&self) -> anyhow::Result<()> {
+        self.pipeline_restart_policy.validate()
+    }
+
     #[cfg(any(test, feature = "testsuite"))]
     pub fn for_test() -> anyhow::Result<Self> {
         use quickwit_proto::indexing::PIPELINE_FULL_CAPACITY;
@@ -143,6 +303,7 @@ impl IndexerConfig {
             split_store_max_num_splits: 3,
             max_concurrent_split_uploads: 4,
             cpu_capacity: PIPELINE_FULL_CAPACITY * 4u32,
+            pipeline_restart_policy: PipelineRestartPolicyConfig::default(),
         };
         Ok(indexer_config)
     }
@@ -157,6 +318,85 @@ impl Default for IndexerConfig {
             split_store_max_num_splits: Self::default_split_store_max_num_splits(),
             max_concurrent_split_uploads: Self::default_max_concurrent_split_uploads(),
             cpu_capacity: Self::default_cpu_capacity(),
+            pipeline_restart_policy: PipelineRestartPolicyConfig::default(),
+        }
+    }
+}
+
+/// Policy used to respawn an indexing pipeline after one of its actors failed.
+///
+/// The delay before respawning the pipeline doubles with every restart that occurred within the
+/// restart window, starting at `initial_backoff_secs` and capped at `max_backoff_secs`. Once the
+/// pipeline was restarted `max_restarts` times within the window, the indexer gives up on it and
+/// reports it as failed. Failures to spawn the pipeline are retried indefinitely and do not count
+/// as restarts.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineRestartPolicyConfig {
+    #[serde(default = "PipelineRestartPolicyConfig::default_initial_backoff_secs")]
+    initial_backoff_secs: NonZeroU64,
+    #[serde(default = "PipelineRestartPolicyConfig::default_max_backoff_secs")]
+    max_backoff_secs: NonZeroU64,
+    #[serde(default = "PipelineRestartPolicyConfig::default_restart_window_secs")]
+    restart_window_secs: NonZeroU64,
+    /// Maximum number of restarts within the restart window. If `None`, the pipeline is
+    /// restarted indefinitely.
+    #[serde(default = "PipelineRestartPolicyConfig::default_max_restarts")]
+    pub max_restarts: Option<usize>,
+}
+
+impl PipelineRestartPolicyConfig {
+    pub fn initial_backoff(&self) -> Duration {
+        Duration::from_secs(self.initial_backoff_secs.get())
+    }
+
+    pub fn max_backoff(&self) -> Duration {
+        Duration::from_secs(self.max_backoff_secs.get())
+    }
+
+    pub fn restart_window(&self) -> Duration {
+        Duration::from_secs(self.restart_window_secs.get())
+    }
+
+    fn default_initial_backoff_secs() -> NonZeroU64 {
+        NonZeroU64::new(1).unwrap()
+    }
+
+    fn default_max_backoff_secs() -> NonZeroU64 {
+        NonZeroU64::new(600).unwrap() // 10 min
+    }
+
+    fn default_restart_window_secs() -> NonZeroU64 {
+        NonZeroU64::new(1800).unwrap() // 30 min
+    }
+
+    fn default_max_restarts() -> Option<usize> {
+        Some(10)
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        ensure!(
+            self.initial_backoff_secs <= self.max_backoff_secs,
+            "pipeline restart initial backoff \
+             (`indexer.pipeline_restart_policy.initial_backoff_secs`) must be less than or equal \
+             to its max backoff (`indexer.pipeline_restart_policy.max_backoff_secs`)"
+        );
+        ensure!(
+            self.max_restarts != Some(0),
+            "pipeline restart max restarts (`indexer.pipeline_restart_policy.max_restarts`) must \
+             be strictly positive, set it to `null` to restart pipelines indefinitely"
+        );
+        Ok(())
+    }
+}
+
+impl Default for PipelineRestartPolicyConfig {
+    fn default() -> Self {
+        Self {
+            initial_backoff_secs: Self::default_initial_backoff_secs(),
+            max_backoff_secs: Self::default_max_backoff_secs(),
+            restart_window_secs: Self::default_restart_window_secs(),
+            max_restarts: Self::default_max_restarts(),
         }
     }
 }
@@ -206,6 +446,9 @@ pub struct SearcherConfig {
     // TODO document and fix if necessary.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub split_cache: Option<SplitCacheLimits>,
+    /// If true, the root caches the per-split results of aggregation requests, so that requests
+    /// that only differ by their time range do not search the splits they fully cover again.
+    pub enable_partial_aggregation_cache: bool,
 }
 
 impl Default for SearcherConfig {
@@ -219,6 +462,7 @@ impl Default for SearcherConfig {
             aggregation_memory_limit: ByteSize::mb(500),
             aggregation_bucket_limit: 65000,
             split_cache: None,
+            enable_partial_aggregation_cache: true,
         }
     }
 }
@@ -311,6 +555,10 @@ pub struct JaegerConfig {
     /// The maximum number of spans that can be retrieved in a single request.
     #[serde(default = "JaegerConfig::default_max_fetch_spans")]
     pub max_fetch_spans: NonZeroU64,
+    /// The maximum number of spans scanned to compute the dependencies between services. Beyond
+    /// that, the dependencies are computed from the spans scanned so far.
+    #[serde(default = "JaegerConfig::default_max_dependency_spans")]
+    pub max_dependency_spans: NonZeroU64,
 }
 
 impl JaegerConfig {
@@ -344,6 +592,10 @@ impl JaegerConfig {
     fn default_max_fetch_spans() -> NonZeroU64 {
         NonZeroU64::new(10_000).unwrap() // 10k spans
     }
+
+    fn default_max_dependency_spans() -> NonZeroU64 {
+        NonZeroU64::new(100_000).unwrap() // 100k spans
+    }
 }
 
 impl Default for JaegerConfig {
@@ -353,6 +605,7 @@ impl Default for JaegerConfig {
             lookback_period_hours: Self::default_lookback_period_hours(),
             max_trace_duration_secs: Self::default_max_trace_duration_secs(),
             max_fetch_spans: Self::default_max_fetch_spans(),
+            max_dependency_spans: Self::default_max_dependency_spans(),
         }
     }
 }
@@ -538,12 +791,64 @@ mod tests {
     fn test_grpc_config_validate() {
         let grpc_config = GrpcConfig {
             max_message_size: ByteSize::mb(1),
+            tls: None,
         };
         assert!(grpc_config.validate().is_ok());
 
         let grpc_config = GrpcConfig {
             max_message_size: ByteSize::kb(1),
+            tls: None,
         };
         assert!(grpc_config.validate().is_err());
+
+        let tls_config = TlsConfig {
+            cert_path: PathBuf::from("node.pem"),
+            key_path: PathBuf::from("node.key"),
+            ca_path: Some(PathBuf::from("ca.pem")),
+            validate_client: true,
+            server_name: None,
+        };
+        let grpc_config = GrpcConfig {
+            tls: Some(tls_config.clone()),
+            ..Default::default()
+        };
+        assert!(grpc_config.validate().is_ok());
+
+        let grpc_config = GrpcConfig {
+            tls: Some(TlsConfig {
+                ca_path: None,
+                validate_client: false,
+                ..tls_config
+            }),
+            ..Default::default()
+        };
+        let error = grpc_config.validate().unwrap_err();
+        assert!(error.to_string().contains("`grpc.tls.ca_path`"));
+    }
+
+    #[test]
+    fn test_tls_config_serialization() {
+        let tls_config: TlsConfig = serde_yaml::from_str(
+            r#"
+                cert_path: /etc/quickwit/node.pem
+                key_path: /etc/quickwit/node.key
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            tls_config.cert_path,
+            PathBuf::from("/etc/quickwit/node.pem")
+        );
+        assert_eq!(tls_config.key_path, PathBuf::from("/etc/quickwit/node.key"));
+        assert!(tls_config.ca_path.is_none());
+        assert!(!tls_config.validate_client);
+        assert!(tls_config.validate("rest").is_ok());
+
+        let tls_config = TlsConfig {
+            validate_client: true,
+            ..tls_config
+        };
+        let error = tls_config.validate("rest").unwrap_err();
+        assert!(error.to_string().contains("`rest.tls.ca_path`"));
     }
 }
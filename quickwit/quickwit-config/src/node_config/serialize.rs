@@ -29,7 +29,7 @@ use quickwit_common::uri::Uri;
 use serde::{Deserialize, Serialize};
 use tracing::{info, warn};
 
-use super::{GrpcConfig, RestConfig};
+use super::{ApiKeyConfig, GrpcConfig, RestConfig, TlsConfig};
 use crate::config_value::ConfigValue;
 use crate::qw_env_vars::*;
 use crate::service::QuickwitService;
@@ -242,6 +242,7 @@ impl NodeConfigBuilder {
             .build_and_validate(listen_ip, env_vars)?;
 
         self.grpc_config.validate()?;
+        self.indexer_config.validate()?;
 
         let gossip_listen_port = self
             .gossip_listen_port
@@ -372,6 +373,10 @@ struct RestConfigBuilder {
     #[serde(with = "http_serde::header_map")]
     #[serde(default)]
     pub extra_headers: HeaderMap,
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+    #[serde(default)]
+    pub api_keys: Vec<ApiKeyConfig>,
 }
 
 impl RestConfigBuilder {
@@ -386,10 +391,20 @@ impl RestConfigBuilder {
             listen_port_from_config_or_default,
         )
         .resolve(env_vars)?;
+        if let Some(tls_config) = &self.tls {
+            tls_config.validate("rest")?;
+            if tls_config.server_name.is_some() {
+                bail!("`rest.tls.server_name` is only supported for gRPC");
+            }
+        }
+        ApiKeyConfig::validate(&self.api_keys)?;
+
         let rest_config = RestConfig {
             listen_addr: SocketAddr::new(listen_ip, listen_port),
             cors_allow_origins: self.cors_allow_origins,
             extra_headers: self.extra_headers,
+            tls: self.tls,
+            api_keys: self.api_keys,
         };
         Ok(rest_config)
     }
@@ -427,6 +442,8 @@ pub fn node_config_for_test() -> NodeConfig {
         listen_addr: rest_listen_addr,
         cors_allow_origins: Vec::new(),
         extra_headers: HeaderMap::new(),
+        tls: None,
+        api_keys: Vec::new(),
     };
     NodeConfig {
         cluster_id: default_cluster_id().unwrap(),
@@ -456,13 +473,15 @@ mod tests {
     use std::env;
     use std::net::Ipv4Addr;
     use std::num::NonZeroU64;
-    use std::path::Path;
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
 
     use bytesize::ByteSize;
     use itertools::Itertools;
 
     use super::*;
     use crate::storage_config::StorageBackendFlavor;
+    use crate::{ApiKeyPermission, PipelineRestartPolicyConfig};
 
     fn get_config_filepath(config_filename: &str) -> String {
         format!(
@@ -556,6 +575,12 @@ mod tests {
                 max_concurrent_split_uploads: 8,
                 cpu_capacity: IndexerConfig::default_cpu_capacity(),
                 enable_cooperative_indexing: false,
+                pipeline_restart_policy: PipelineRestartPolicyConfig {
+                    initial_backoff_secs: NonZeroU64::new(5).unwrap(),
+                    max_backoff_secs: NonZeroU64::new(300).unwrap(),
+                    restart_window_secs: NonZeroU64::new(3600).unwrap(),
+                    max_restarts: Some(20),
+                },
             }
         );
         assert_eq!(
@@ -576,6 +601,7 @@ mod tests {
                 max_num_concurrent_split_searches: 150,
                 max_num_concurrent_split_streams: 120,
                 split_cache: None,
+                enable_partial_aggregation_cache: true,
             }
         );
         assert_eq!(
@@ -585,6 +611,7 @@ mod tests {
                 lookback_period_hours: NonZeroU64::new(24).unwrap(),
                 max_trace_duration_secs: NonZeroU64::new(600).unwrap(),
                 max_fetch_spans: NonZeroU64::new(1_000).unwrap(),
+                max_dependency_spans: NonZeroU64::new(50_000).unwrap(),
             }
         );
         Ok(())
@@ -1202,6 +1229,173 @@ mod tests {
         .expect_err("Config should not allow empty origins.");
     }
 
+    #[tokio::test]
+    async fn test_node_config_tls() {
+        let node_config_yaml = r#"
+            version: 0.7
+            rest:
+              tls:
+                cert_path: /etc/quickwit/rest.pem
+                key_path: /etc/quickwit/rest.key
+            grpc:
+              tls:
+                cert_path: /etc/quickwit/node.pem
+                key_path: /etc/quickwit/node.key
+                ca_path: /etc/quickwit/ca.pem
+                validate_client: true
+                server_name: quickwit-node
+        "#;
+        let config = load_node_config_with_env(
+            ConfigFormat::Yaml,
+            node_config_yaml.as_bytes(),
+            &Default::default(),
+        )
+        .await
+        .unwrap();
+        let rest_tls_config = config.rest_config.tls.unwrap();
+        assert_eq!(
+            rest_tls_config.cert_path,
+            PathBuf::from("/etc/quickwit/rest.pem")
+        );
+        assert!(rest_tls_config.ca_path.is_none());
+        assert!(!rest_tls_config.validate_client);
+
+        let grpc_tls_config = config.grpc_config.tls.unwrap();
+        assert_eq!(
+            grpc_tls_config.ca_path.unwrap(),
+            PathBuf::from("/etc/quickwit/ca.pem")
+        );
+        assert!(grpc_tls_config.validate_client);
+        assert_eq!(grpc_tls_config.server_name.unwrap(), "quickwit-node");
+
+        let node_config_yaml = r#"
+            version: 0.7
+            grpc:
+              tls:
+                cert_path: /etc/quickwit/node.pem
+                key_path: /etc/quickwit/node.key
+        "#;
+        let error = load_node_config_with_env(
+            ConfigFormat::Yaml,
+            node_config_yaml.as_bytes(),
+            &Default::default(),
+        )
+        .await
+        .unwrap_err();
+        assert!(error.to_string().contains("`grpc.tls.ca_path`"));
+
+        let node_config_yaml = r#"
+            version: 0.7
+            rest:
+              tls:
+                cert_path: /etc/quickwit/rest.pem
+                key_path: /etc/quickwit/rest.key
+                server_name: quickwit-node
+        "#;
+        let error = load_node_config_with_env(
+            ConfigFormat::Yaml,
+            node_config_yaml.as_bytes(),
+            &Default::default(),
+        )
+        .await
+        .unwrap_err();
+        assert!(error.to_string().contains("`rest.tls.server_name`"));
+    }
+
+    #[tokio::test]
+    async fn test_node_config_api_keys() {
+        let node_config_yaml = r#"
+            version: 0.7
+            rest:
+              api_keys:
+                - name: admin
+                  key: admin-secret-key
+                  permissions: [admin]
+                - name: logs-pipeline
+                  key: logs-secret-key
+                  permissions: [search, ingest]
+                  index_id_patterns: [logs-*]
+        "#;
+        let config = load_node_config_with_env(
+            ConfigFormat::Yaml,
+            node_config_yaml.as_bytes(),
+            &Default::default(),
+        )
+        .await
+        .unwrap();
+        let api_keys = &config.rest_config.api_keys;
+        assert_eq!(api_keys.len(), 2);
+        assert_eq!(api_keys[0].name, "admin");
+        assert_eq!(api_keys[0].permissions, [ApiKeyPermission::Admin]);
+        assert_eq!(api_keys[0].index_id_patterns, ["*"]);
+
+        assert_eq!(api_keys[1].key, "logs-secret-key");
+        assert_eq!(
+            api_keys[1].permissions,
+            [ApiKeyPermission::Search, ApiKeyPermission::Ingest]
+        );
+        assert_eq!(api_keys[1].index_id_patterns, ["logs-*"]);
+
+        // Keys must never be exposed, by the node config API for instance.
+        let rest_config_json = serde_json::to_string(&config.rest_config).unwrap();
+        assert!(!rest_config_json.contains("secret-key"));
+        assert!(!format!("{:?}", config.rest_config).contains("secret-key"));
+
+        let node_config_yaml = r#"
+            version: 0.7
+            rest:
+              api_keys:
+                - name: ingest
+                  key: first-secret-key
+                  permissions: [ingest]
+                - name: ingest
+                  key: second-secret-key
+                  permissions: [ingest]
+        "#;
+        let error = load_node_config_with_env(
+            ConfigFormat::Yaml,
+            node_config_yaml.as_bytes(),
+            &Default::default(),
+        )
+        .await
+        .unwrap_err();
+        assert!(error.to_string().contains("is not unique"));
+
+        let node_config_yaml = r#"
+            version: 0.7
+            rest:
+              api_keys:
+                - name: search
+                  key: search-secret-key
+                  permissions: []
+        "#;
+        let error = load_node_config_with_env(
+            ConfigFormat::Yaml,
+            node_config_yaml.as_bytes(),
+            &Default::default(),
+        )
+        .await
+        .unwrap_err();
+        assert!(error.to_string().contains("at least one permission"));
+
+        let node_config_yaml = r#"
+            version: 0.7
+            rest:
+              api_keys:
+                - name: search
+                  key: search-secret-key
+                  permissions: [search]
+                  index_id_patterns: [logs**]
+        "#;
+        load_node_config_with_env(
+            ConfigFormat::Yaml,
+            node_config_yaml.as_bytes(),
+            &Default::default(),
+        )
+        .await
+        .unwrap_err();
+    }
+
     #[tokio::test]
     async fn test_node_config_validates_ingest_config() {
         let ingest_config = IngestApiConfig {
@@ -1233,4 +1427,59 @@ mod tests {
         .to_string();
         assert!(error_message.contains("replication factor"));
     }
+    #[tokio::test]
+    async fn test_node_config_validates_pipeline_restart_policy() {
+        let node_config_yaml = r#"
+            version: 0.7
+            indexer:
+              pipeline_restart_policy:
+                initial_backoff_secs: 60
+                max_backoff_secs: 10
+        "#;
+        let error_message = load_node_config_with_env(
+            ConfigFormat::Yaml,
+            node_config_yaml.as_bytes(),
+            &Default::default(),
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+        assert!(error_message.contains("initial_backoff_secs"));
+
+        let node_config_yaml = r#"
+            version: 0.7
+            indexer:
+              pipeline_restart_policy:
+                max_restarts: 0
+        "#;
+        let error_message = load_node_config_with_env(
+            ConfigFormat::Yaml,
+            node_config_yaml.as_bytes(),
+            &Default::default(),
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+        assert!(error_message.contains("max_restarts"));
+
+        let node_config_yaml = r#"
+            version: 0.7
+            indexer:
+              pipeline_restart_policy:
+                max_restarts: null
+        "#;
+        let node_config = load_node_config_with_env(
+            ConfigFormat::Yaml,
+            node_config_yaml.as_bytes(),
+            &Default::default(),
+        )
+        .await
+        .unwrap();
+        let pipeline_restart_policy = node_config.indexer_config.pipeline_restart_policy;
+        assert_eq!(pipeline_restart_policy.max_restarts, None);
+        assert_eq!(
+            pipeline_restart_policy.initial_backoff(),
+            Duration::from_secs(1)
+        );
+    }
 }
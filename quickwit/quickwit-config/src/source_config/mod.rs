@@ -19,7 +19,7 @@
 
 pub(crate) mod serialize;
 
-use std::num::NonZeroUsize;
+use std::num::{NonZeroU64, NonZeroUsize};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
@@ -103,6 +103,7 @@ impl SourceConfig {
             SourceParams::Kafka(_) => SourceType::Kafka,
             SourceParams::Kinesis(_) => SourceType::Kinesis,
             SourceParams::Pulsar(_) => SourceType::Pulsar,
+            SourceParams::StoragePrefix(_) => SourceType::StoragePrefix,
             SourceParams::Vec(_) => SourceType::Vec,
             SourceParams::Void(_) => SourceType::Void,
         }
@@ -119,6 +120,7 @@ impl SourceConfig {
             SourceParams::Kafka(params) => serde_json::to_value(params),
             SourceParams::Kinesis(params) => serde_json::to_value(params),
             SourceParams::Pulsar(params) => serde_json::to_value(params),
+            SourceParams::StoragePrefix(params) => serde_json::to_value(params),
             SourceParams::Vec(params) => serde_json::to_value(params),
             SourceParams::Void(params) => serde_json::to_value(params),
         }
@@ -214,6 +216,9 @@ pub enum SourceInputFormat {
     OtlpTraceProtobuf,
     #[serde(alias = "plain")]
     PlainText,
+    /// Comma-separated values. The first record holds the field names. Like any other record, it
+    /// may span several lines if some of its fields are quoted.
+    Csv,
 }
 
 impl FromStr for SourceInputFormat {
@@ -223,6 +228,7 @@ impl FromStr for SourceInputFormat {
         match format_str {
             "json" => Ok(Self::Json),
             "plain" => Ok(Self::PlainText),
+            "csv" => Ok(Self::Csv),
             unknown => Err(format!("unknown source input format: `{unknown}`")),
         }
     }
@@ -241,6 +247,7 @@ pub enum SourceParams {
     Kafka(KafkaSourceParams),
     Kinesis(KinesisSourceParams),
     Pulsar(PulsarSourceParams),
+    StoragePrefix(StoragePrefixSourceParams),
     Vec(VecSourceParams),
     Void(VoidSourceParams),
 }
@@ -297,6 +304,21 @@ impl FileSourceParams {
     }
 }
 
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct StoragePrefixSourceParams {
+    /// URI of the prefix of the files to ingest, for instance `s3://my-bucket/logs/2024-`. The
+    /// prefix is matched as a plain string, like object storages do.
+    #[schema(value_type = String)]
+    pub prefix_uri: Uri,
+    /// When set, the source lists the prefix again every `polling_interval_secs` seconds to
+    /// ingest newly added files. Otherwise, the source exits once all the files are ingested.
+    #[schema(value_type = Option<u64>)]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub polling_interval_secs: Option<NonZeroU64>,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct KafkaSourceParams {
@@ -815,6 +837,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_storage_prefix_source_params_serialization() {
+        {
+            let yaml = r#"
+                prefix_uri: s3://my-bucket/logs/2024-
+            "#;
+            let params = serde_yaml::from_str::<StoragePrefixSourceParams>(yaml).unwrap();
+            assert_eq!(
+                params,
+                StoragePrefixSourceParams {
+                    prefix_uri: Uri::for_test("s3://my-bucket/logs/2024-"),
+                    polling_interval_secs: None,
+                }
+            );
+        }
+        {
+            let yaml = r#"
+                prefix_uri: s3://my-bucket/logs/
+                polling_interval_secs: 60
+            "#;
+            let params = serde_yaml::from_str::<StoragePrefixSourceParams>(yaml).unwrap();
+            assert_eq!(params.polling_interval_secs, NonZeroU64::new(60));
+
+            let params_yaml = serde_yaml::to_string(&params).unwrap();
+            assert_eq!(
+                serde_yaml::from_str::<StoragePrefixSourceParams>(&params_yaml).unwrap(),
+                params,
+            );
+        }
+        {
+            let yaml = r#"
+                prefix_uri: s3://my-bucket/logs/
+                polling_interval_secs: 0
+            "#;
+            serde_yaml::from_str::<StoragePrefixSourceParams>(yaml).unwrap_err();
+        }
+    }
+
     #[test]
     fn test_kinesis_source_params_serialization() {
         {
@@ -1207,4 +1267,52 @@ mod tests {
                 .unwrap();
         assert_eq!(source_config.input_format, SourceInputFormat::PlainText);
     }
+
+    #[tokio::test]
+    async fn test_source_config_csv_input_format() {
+        let file_content = r#"{
+            "version": "0.7",
+            "source_id": "logs-file-source",
+            "desired_num_pipelines": 1,
+            "max_num_pipelines_per_indexer": 1,
+            "source_type": "file",
+            "params": {"filepath": "s3://logs/2024-01-01.csv.gz"},
+            "input_format": "csv"
+        }"#;
+        let source_config =
+            load_source_config_from_user_config(ConfigFormat::Json, file_content.as_bytes())
+                .unwrap();
+        assert_eq!(source_config.input_format, SourceInputFormat::Csv);
+
+        let file_content = r#"{
+            "version": "0.7",
+            "source_id": "logs-storage-prefix-source",
+            "desired_num_pipelines": 1,
+            "max_num_pipelines_per_indexer": 1,
+            "source_type": "storage_prefix",
+            "params": {"prefix_uri": "s3://logs/2024-", "polling_interval_secs": 30},
+            "input_format": "csv"
+        }"#;
+        let source_config =
+            load_source_config_from_user_config(ConfigFormat::Json, file_content.as_bytes())
+                .unwrap();
+        assert_eq!(source_config.source_type(), SourceType::StoragePrefix);
+        assert_eq!(source_config.input_format, SourceInputFormat::Csv);
+
+        let file_content = r#"{
+            "version": "0.7",
+            "source_id": "logs-kafka-source",
+            "desired_num_pipelines": 1,
+            "max_num_pipelines_per_indexer": 1,
+            "source_type": "kafka",
+            "params": {"topic": "logs"},
+            "input_format": "csv"
+        }"#;
+        let error =
+            load_source_config_from_user_config(ConfigFormat::Json, file_content.as_bytes())
+                .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("`csv` input format is only supported by file and storage prefix sources"));
+    }
 }
@@ -89,7 +89,10 @@ impl SourceConfigForSerialization {
                     )
                 }
             }
-            SourceParams::Kafka(_) | SourceParams::Kinesis(_) | SourceParams::Pulsar(_) => {
+            SourceParams::Kafka(_)
+            | SourceParams::Kinesis(_)
+            | SourceParams::Pulsar(_)
+            | SourceParams::StoragePrefix(_) => {
                 // TODO consider any validation opportunity
             }
             SourceParams::GcpPubSub(_)
@@ -108,6 +111,14 @@ impl SourceConfigForSerialization {
             }
         }
 
+        if self.input_format == SourceInputFormat::Csv
+            && !matches!(
+                self.source_params,
+                SourceParams::File(_) | SourceParams::StoragePrefix(_)
+            )
+        {
+            bail!("the `csv` input format is only supported by file and storage prefix sources");
+        }
         if let Some(transform_config) = &self.transform {
             if matches!(
                 self.input_format,
@@ -30,7 +30,7 @@ use quickwit_actors::{
 use quickwit_common::pubsub::EventSubscriber;
 use quickwit_config::SourceConfig;
 use quickwit_ingest::{IngesterPool, LocalShardsUpdate};
-use quickwit_metastore::IndexMetadata;
+use quickwit_metastore::{IndexMetadata, IndexMetadataResponseExt};
 use quickwit_proto::control_plane::{
     ControlPlaneError, ControlPlaneResult, GetDebugStateRequest, GetDebugStateResponse,
     GetOrCreateOpenShardsRequest, GetOrCreateOpenShardsResponse, PhysicalIndexingPlanEntry,
@@ -40,8 +40,8 @@ use quickwit_proto::indexing::ShardPositionsUpdate;
 use quickwit_proto::metastore::{
     serde_utils as metastore_serde_utils, AddSourceRequest, CreateIndexRequest,
     CreateIndexResponse, DeleteIndexRequest, DeleteShardsRequest, DeleteShardsSubrequest,
-    DeleteSourceRequest, EmptyResponse, MetastoreError, MetastoreService, MetastoreServiceClient,
-    ToggleSourceRequest,
+    DeleteSourceRequest, EmptyResponse, EntityKind, IndexMetadataResponse, MetastoreError,
+    MetastoreService, MetastoreServiceClient, ToggleSourceRequest, UpdateIndexRequest,
 };
 use quickwit_proto::types::{IndexUid, NodeId, ShardId, SourceUid};
 use serde::Serialize;
@@ -392,6 +392,57 @@ impl Handler<DeleteIndexRequest> for ControlPlane {
     }
 }
 
+// This handler is a metastore call proxied through the control plane: we must first forward the
+// request to the metastore, and then act on the event.
+#[async_trait]
+impl Handler<UpdateIndexRequest> for ControlPlane {
+    type Reply = ControlPlaneResult<IndexMetadataResponse>;
+
+    async fn handle(
+        &mut self,
+        request: UpdateIndexRequest,
+        _ctx: &ActorContext<Self>,
+    ) -> Result<Self::Reply, ActorExitStatus> {
+        let index_uid: IndexUid = request.index_uid.clone().into();
+
+        // The model is checked before the metastore is updated, so that the request does not fail
+        // after the update went through.
+        if !self.model.has_index(&index_uid) {
+            let metastore_error = MetastoreError::NotFound(EntityKind::Index {
+                index_id: index_uid.index_id().to_string(),
+            });
+            return Ok(Err(ControlPlaneError::from(metastore_error)));
+        }
+        let response = match self.metastore.update_index(request).await {
+            Ok(response) => response,
+            Err(metastore_error) => return convert_metastore_error(metastore_error),
+        };
+        let index_metadata = match response.deserialize_index_metadata() {
+            Ok(index_metadata) => index_metadata,
+            Err(metastore_error) => return Ok(Err(ControlPlaneError::from(metastore_error))),
+        };
+        let has_changed = match self
+            .model
+            .update_index_config(&index_uid, index_metadata.into_index_config())
+        {
+            Ok(has_changed) => has_changed,
+            Err(error) => {
+                // The index is updated in the metastore at this point, so the request succeeds.
+                error!(index_uid=%index_uid, %error, "failed to update index config in model");
+                false
+            }
+        };
+
+        // The indexing pipelines of the index must be respawned to pick up the new indexing
+        // settings.
+        if has_changed {
+            self.indexing_scheduler
+                .restart_index_pipelines(&index_uid, &self.model);
+        }
+        Ok(Ok(response))
+    }
+}
+
 // This handler is a metastore call proxied through the control plane: we must first forward the
 // request to the metastore, and then act on the event.
 #[async_trait]
@@ -613,7 +664,7 @@ mod tests {
     use quickwit_config::{IndexConfig, SourceParams, INGEST_V2_SOURCE_ID};
     use quickwit_indexing::IndexingService;
     use quickwit_metastore::{
-        CreateIndexRequestExt, IndexMetadata, ListIndexesMetadataResponseExt,
+        CreateIndexRequestExt, IndexMetadata, ListIndexesMetadataResponseExt, UpdateIndexRequestExt,
     };
     use quickwit_proto::control_plane::GetOrCreateOpenShardsSubrequest;
     use quickwit_proto::indexing::{ApplyIndexingPlanRequest, CpuCapacity, IndexingServiceClient};
@@ -858,6 +909,223 @@ mod tests {
         universe.assert_quit().await;
     }
 
+    #[tokio::test]
+    async fn test_control_plane_update_index() {
+        let universe = Universe::with_accelerated_time();
+        let node_id = NodeId::new("control-plane-node".to_string());
+        let indexer_pool = IndexerPool::default();
+        let (client_mailbox, client_inbox) = universe.create_test_mailbox();
+        let client = IndexingServiceClient::from_mailbox::<IndexingService>(client_mailbox);
+        let indexer_node_info = IndexerNodeInfo {
+            client,
+            indexing_tasks: Vec::new(),
+            indexing_capacity: CpuCapacity::from_cpu_millis(4_000),
+        };
+        indexer_pool.insert("indexer-node-1".to_string(), indexer_node_info);
+        let ingester_pool = IngesterPool::default();
+        let mut mock_metastore = MetastoreServiceClient::mock();
+
+        let mut index_0 = IndexMetadata::for_test("test-index-0", "ram:///test-index-0");
+        let mut source = SourceConfig::ingest_v2_default();
+        source.enabled = true;
+        index_0.add_source(source).unwrap();
+
+        let index_0_clone = index_0.clone();
+        mock_metastore.expect_list_indexes_metadata().return_once(
+            move |_list_indexes_request: ListIndexesMetadataRequest| {
+                Ok(
+                    ListIndexesMetadataResponse::try_from_indexes_metadata(vec![index_0_clone])
+                        .unwrap(),
+                )
+            },
+        );
+        let mut shard = Shard {
+            index_uid: index_0.index_uid.to_string(),
+            source_id: INGEST_V2_SOURCE_ID.to_string(),
+            shard_id: Some(ShardId::from(17)),
+            leader_id: "test_node".to_string(),
+            ..Default::default()
+        };
+        shard.set_shard_state(ShardState::Open);
+
+        let index_uid_clone = index_0.index_uid.clone();
+        mock_metastore.expect_list_shards().return_once(
+            move |_list_shards_request: ListShardsRequest| {
+                let list_shards_resp = ListShardsResponse {
+                    subresponses: vec![ListShardsSubresponse {
+                        index_uid: index_uid_clone.to_string(),
+                        source_id: INGEST_V2_SOURCE_ID.to_string(),
+                        shards: vec![shard],
+                    }],
+                };
+                Ok(list_shards_resp)
+            },
+        );
+        let mut updated_index_0 = index_0.clone();
+        updated_index_0
+            .index_config
+            .indexing_settings
+            .commit_timeout_secs = 5;
+        let updated_index_0_clone = updated_index_0.clone();
+        mock_metastore.expect_update_index().return_once(
+            move |update_index_request: UpdateIndexRequest| {
+                assert_eq!(update_index_request.index_uid, "test-index-0:0");
+                Ok(IndexMetadataResponse::try_from_index_metadata(updated_index_0_clone).unwrap())
+            },
+        );
+        let (control_plane_mailbox, _control_plane_handle) = ControlPlane::spawn(
+            &universe,
+            "cluster".to_string(),
+            node_id,
+            indexer_pool,
+            ingester_pool,
+            MetastoreServiceClient::from(mock_metastore),
+            1,
+        );
+        let control_plane_obs: ControlPlaneObservableState =
+            control_plane_mailbox.ask(Observe).await.unwrap();
+        let last_applied_physical_plan = control_plane_obs
+            .indexing_scheduler
+            .last_applied_physical_plan
+            .unwrap();
+        let indexing_tasks = last_applied_physical_plan
+            .indexing_tasks_per_indexer()
+            .get("indexer-node-1")
+            .unwrap();
+        assert_eq!(indexing_tasks.len(), 1);
+        let pipeline_uid = indexing_tasks[0].pipeline_uid();
+        let _ = client_inbox.drain_for_test();
+
+        let update_index_request = UpdateIndexRequest::try_from_updates(
+            index_0.index_uid.clone(),
+            &updated_index_0.index_config.doc_mapping,
+            &updated_index_0.index_config.search_settings,
+            &updated_index_0.index_config.retention_policy,
+            &updated_index_0.index_config.indexing_settings,
+        )
+        .unwrap();
+        let update_index_response = control_plane_mailbox
+            .ask_for_res(update_index_request)
+            .await
+            .unwrap();
+        assert_eq!(
+            update_index_response.deserialize_index_metadata().unwrap(),
+            updated_index_0
+        );
+
+        // The pipeline of the index is restarted with a new pipeline UID.
+        let control_plane_obs: ControlPlaneObservableState =
+            control_plane_mailbox.ask(Observe).await.unwrap();
+        let last_applied_physical_plan = control_plane_obs
+            .indexing_scheduler
+            .last_applied_physical_plan
+            .unwrap();
+        let indexing_tasks = last_applied_physical_plan
+            .indexing_tasks_per_indexer()
+            .get("indexer-node-1")
+            .unwrap();
+        assert_eq!(indexing_tasks.len(), 1);
+        assert_eq!(indexing_tasks[0].shard_ids, [ShardId::from(17)]);
+        assert_ne!(indexing_tasks[0].pipeline_uid(), pipeline_uid);
+
+        let results: Vec<ApplyIndexingPlanRequest> =
+            client_inbox.drain_for_test_typed::<ApplyIndexingPlanRequest>();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].indexing_tasks.len(), 1);
+        assert_ne!(results[0].indexing_tasks[0].pipeline_uid(), pipeline_uid);
+
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_control_plane_update_index_errors_do_not_kill_the_control_plane() {
+        let universe = Universe::with_accelerated_time();
+
+        let cluster_id = "test-cluster".to_string();
+        let self_node_id: NodeId = "test-node".into();
+        let indexer_pool = IndexerPool::default();
+        let ingester_pool = IngesterPool::default();
+
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .returning(move |_| {
+                Ok(ListIndexesMetadataResponse::try_from_indexes_metadata(vec![
+                    index_metadata.clone()
+                ])
+                .unwrap())
+            });
+        // The metastore is not called for the index the control plane model does not know about.
+        mock_metastore
+            .expect_update_index()
+            .times(1)
+            .returning(|_| {
+                Ok(IndexMetadataResponse {
+                    index_metadata_serialized_json: "{".to_string(),
+                })
+            });
+        let replication_factor = 1;
+
+        let (control_plane_mailbox, control_plane_handle) = ControlPlane::spawn(
+            &universe,
+            cluster_id,
+            self_node_id,
+            indexer_pool,
+            ingester_pool,
+            MetastoreServiceClient::from(mock_metastore),
+            replication_factor,
+        );
+        let index_config = IndexConfig::for_test("test-index", "ram:///test-index");
+        let update_index_request = UpdateIndexRequest::try_from_updates(
+            "test-index:0",
+            &index_config.doc_mapping,
+            &index_config.search_settings,
+            &index_config.retention_policy,
+            &index_config.indexing_settings,
+        )
+        .unwrap();
+        let error = control_plane_mailbox
+            .ask_for_res(update_index_request)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            AskError::ErrorReply(ControlPlaneError::Metastore(
+                MetastoreError::JsonDeserializeError { .. }
+            ))
+        ));
+        let update_index_request = UpdateIndexRequest::try_from_updates(
+            "unknown-index:0",
+            &index_config.doc_mapping,
+            &index_config.search_settings,
+            &index_config.retention_policy,
+            &index_config.indexing_settings,
+        )
+        .unwrap();
+        let error = control_plane_mailbox
+            .ask_for_res(update_index_request)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            AskError::ErrorReply(ControlPlaneError::Metastore(MetastoreError::NotFound(
+                EntityKind::Index { .. }
+            )))
+        ));
+        // The control plane was not restarted.
+        control_plane_mailbox.ask(Observe).await.unwrap();
+        assert_eq!(
+            control_plane_handle
+                .process_pending_and_observe()
+                .await
+                .metrics
+                .num_errors,
+            0
+        );
+        universe.assert_quit().await;
+    }
+
     #[tokio::test]
     async fn test_control_plane_delete_source() {
         let universe = Universe::with_accelerated_time();
@@ -1084,7 +1352,9 @@ mod tests {
             SupervisorMetrics {
                 num_panics: 0,
                 num_errors: 0,
-                num_kills: 0
+                num_kills: 0,
+                num_recent_restarts: 0,
+                is_crash_looping: false,
             }
         );
 
@@ -1105,7 +1375,9 @@ mod tests {
             SupervisorMetrics {
                 num_panics: 0,
                 num_errors: 1,
-                num_kills: 0
+                num_kills: 0,
+                num_recent_restarts: 1,
+                is_crash_looping: false,
             }
         );
 
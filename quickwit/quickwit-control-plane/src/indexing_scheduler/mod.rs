@@ -30,7 +30,7 @@ use quickwit_proto::indexing::{
     ApplyIndexingPlanRequest, CpuCapacity, IndexingService, IndexingTask, PIPELINE_FULL_CAPACITY,
 };
 use quickwit_proto::metastore::SourceType;
-use quickwit_proto::types::{NodeId, ShardId};
+use quickwit_proto::types::{IndexUid, NodeId, PipelineUid, ShardId};
 use scheduling::{SourceToSchedule, SourceToScheduleType};
 use serde::Serialize;
 use tracing::{debug, error, info, warn};
@@ -164,7 +164,8 @@ fn get_sources_to_schedule(model: &ControlPlaneModel) -> Vec<SourceToSchedule> {
             | SourceType::Kinesis
             | SourceType::GcpPubsub
             | SourceType::Nats
-            | SourceType::Pulsar => {
+            | SourceType::Pulsar
+            | SourceType::StoragePrefix => {
                 sources.push(SourceToSchedule {
                     source_uid,
                     source_type: SourceToScheduleType::NonSharded {
@@ -240,6 +241,44 @@ impl IndexingScheduler {
         self.state.num_schedule_indexing_plan += 1;
     }
 
+    /// Restarts the indexing pipelines of an index, typically after its index config has been
+    /// updated.
+    ///
+    /// The indexing tasks of the index are assigned new pipeline UIDs in the last applied plan,
+    /// so the indexers shut down the current pipelines and spawn new ones with the latest index
+    /// config.
+    pub(crate) fn restart_index_pipelines(
+        &mut self,
+        index_uid: &IndexUid,
+        model: &ControlPlaneModel,
+    ) {
+        let Some(last_applied_plan) = self.state.last_applied_physical_plan.as_mut() else {
+            return;
+        };
+        let mut num_restarted_pipelines = 0;
+
+        for indexing_tasks in last_applied_plan
+            .indexing_tasks_per_indexer_mut()
+            .values_mut()
+        {
+            for indexing_task in indexing_tasks.iter_mut() {
+                if indexing_task.index_uid == index_uid.as_str() {
+                    indexing_task.pipeline_uid = Some(PipelineUid::new());
+                    num_restarted_pipelines += 1;
+                }
+            }
+        }
+        if num_restarted_pipelines == 0 {
+            return;
+        }
+        info!(
+            index_uid=%index_uid,
+            num_pipelines=num_restarted_pipelines,
+            "restarting indexing pipelines"
+        );
+        self.schedule_indexing_plan_if_needed(model);
+    }
+
     /// Checks if the last applied plan corresponds to the running indexing tasks present in the
     /// chitchat cluster state. If true, do nothing.
     /// - If node IDs differ, schedule a new indexing plan.
@@ -27,7 +27,7 @@ use std::time::Instant;
 use anyhow::bail;
 use fnv::{FnvHashMap, FnvHashSet};
 use quickwit_common::Progress;
-use quickwit_config::SourceConfig;
+use quickwit_config::{IndexConfig, SourceConfig};
 use quickwit_ingest::ShardInfos;
 use quickwit_metastore::{IndexMetadata, ListIndexesMetadataResponseExt};
 use quickwit_proto::control_plane::{ControlPlaneError, ControlPlaneResult};
@@ -155,6 +155,10 @@ impl ControlPlaneModel {
         self.index_uid_table.get(index_id).cloned()
     }
 
+    pub(crate) fn has_index(&self, index_uid: &IndexUid) -> bool {
+        self.index_table.contains_key(index_uid)
+    }
+
     pub(crate) fn get_source_configs(
         &self,
     ) -> impl Iterator<Item = (SourceUid, &SourceConfig)> + '_ {
@@ -238,6 +242,24 @@ impl ControlPlaneModel {
         Ok(has_changed)
     }
 
+    /// Updates the config of an index. Returns `true` if the doc mapping or the indexing settings
+    /// have changed, `false` otherwise. Returns an error if the index could not be found.
+    pub(crate) fn update_index_config(
+        &mut self,
+        index_uid: &IndexUid,
+        index_config: IndexConfig,
+    ) -> ControlPlaneResult<bool> {
+        let index_model = self.index_table.get_mut(index_uid).ok_or_else(|| {
+            MetastoreError::NotFound(EntityKind::Index {
+                index_id: index_uid.to_string(),
+            })
+        })?;
+        let has_changed = index_model.index_config.doc_mapping != index_config.doc_mapping
+            || index_model.index_config.indexing_settings != index_config.indexing_settings;
+        index_model.index_config = index_config;
+        Ok(has_changed)
+    }
+
     pub(crate) fn all_shards_mut(&mut self) -> impl Iterator<Item = &mut ShardEntry> + '_ {
         self.shard_table.all_shards_mut()
     }
@@ -508,4 +530,50 @@ mod tests {
             assert!(!has_changed);
         }
     }
+
+    #[test]
+    fn test_control_plane_model_update_index_config() {
+        let mut model = ControlPlaneModel::default();
+        let index_metadata = IndexMetadata::for_test("test-index", "ram://");
+        let index_uid = index_metadata.index_uid.clone();
+        let mut index_config = index_metadata.index_config.clone();
+        model.add_index(index_metadata);
+
+        index_config.search_settings.default_search_fields = vec!["owner".to_string()];
+        let has_changed = model
+            .update_index_config(&index_uid, index_config.clone())
+            .unwrap();
+        assert!(!has_changed);
+        assert_eq!(
+            model.index_table[&index_uid]
+                .index_config
+                .search_settings
+                .default_search_fields,
+            ["owner"]
+        );
+
+        index_config.indexing_settings.commit_timeout_secs = 5;
+        let has_changed = model
+            .update_index_config(&index_uid, index_config.clone())
+            .unwrap();
+        assert!(has_changed);
+        assert_eq!(
+            model.index_table[&index_uid]
+                .index_config
+                .indexing_settings
+                .commit_timeout_secs,
+            5
+        );
+
+        index_config.doc_mapping.doc_mapping_version = 1;
+        let has_changed = model
+            .update_index_config(&index_uid, index_config.clone())
+            .unwrap();
+        assert!(has_changed);
+
+        let unknown_index_uid = IndexUid::new_with_random_ulid("unknown-index");
+        model
+            .update_index_config(&unknown_index_uid, index_config)
+            .unwrap_err();
+    }
 }
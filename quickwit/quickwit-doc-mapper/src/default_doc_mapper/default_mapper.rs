@@ -36,9 +36,11 @@ use tantivy::TantivyDocument as Document;
 
 use super::field_mapping_entry::RAW_TOKENIZER_NAME;
 use super::DefaultDocMapperBuilder;
-use crate::default_doc_mapper::mapping_tree::{build_mapping_tree, MappingNode};
-use crate::default_doc_mapper::FieldMappingType;
+use crate::default_doc_mapper::mapping_tree::{
+    build_mapping_tree, extend_mapping_tree, MappingNode,
+};
 pub use crate::default_doc_mapper::QuickwitJsonOptions;
+use crate::default_doc_mapper::{FieldMappingEntry, FieldMappingType};
 use crate::doc_mapper::{JsonObject, Partition};
 use crate::query_builder::build_query;
 use crate::routing_expression::RoutingExpr;
@@ -91,6 +93,11 @@ pub struct DefaultDocMapper {
     tokenizer_entries: Vec<TokenizerEntry>,
     /// Tokenizer manager.
     tokenizer_manager: TokenizerManager,
+    /// Version of the doc mapping.
+    doc_mapping_version: u64,
+    /// Doc mapping version that introduced each of the field mappings appended to the doc
+    /// mapping after the creation of the index.
+    field_mapping_versions: BTreeMap<String, u64>,
 }
 
 impl DefaultDocMapper {
@@ -140,6 +147,51 @@ fn validate_timestamp_field(
     Ok(())
 }
 
+/// Splits the field mappings into the field mappings of the first version of the doc mapping and
+/// the field mappings appended by subsequent versions, which must come last, in ascending version
+/// order.
+fn split_field_mappings_by_version<'a>(
+    field_mappings: &'a [FieldMappingEntry],
+    field_mapping_versions: &BTreeMap<String, u64>,
+    doc_mapping_version: u64,
+) -> anyhow::Result<(&'a [FieldMappingEntry], &'a [FieldMappingEntry])> {
+    let num_initial_field_mappings = field_mappings
+        .iter()
+        .take_while(|entry| !field_mapping_versions.contains_key(&entry.name))
+        .count();
+    let (initial_field_mappings, appended_field_mappings) =
+        field_mappings.split_at(num_initial_field_mappings);
+    let mut previous_version = 0;
+
+    for entry in appended_field_mappings {
+        let Some(&version) = field_mapping_versions.get(&entry.name) else {
+            bail!(
+                "field mapping `{}` was not appended to the doc mapping after the field mappings \
+                 of the previous doc mapping versions",
+                entry.name
+            );
+        };
+        if version == 0 || version > doc_mapping_version {
+            bail!(
+                "field mapping `{}` has an invalid doc mapping version `{version}`",
+                entry.name
+            );
+        }
+        if version < previous_version {
+            bail!(
+                "field mapping `{}` must be defined after the field mappings of doc mapping \
+                 version `{previous_version}`",
+                entry.name
+            );
+        }
+        previous_version = version;
+    }
+    if appended_field_mappings.len() != field_mapping_versions.len() {
+        bail!("`field_mapping_versions` references fields that are not mapped");
+    }
+    Ok((initial_field_mappings, appended_field_mappings))
+}
+
 impl TryFrom<DefaultDocMapperBuilder> for DefaultDocMapper {
     type Error = anyhow::Error;
 
@@ -154,13 +206,25 @@ impl TryFrom<DefaultDocMapperBuilder> for DefaultDocMapper {
             None
         };
 
+        let (initial_field_mappings, appended_field_mappings) = split_field_mappings_by_version(
+            &builder.field_mappings,
+            &builder.field_mapping_versions,
+            builder.doc_mapping_version,
+        )?;
         // Adding regular fields.
-        let field_mappings = build_mapping_tree(&builder.field_mappings, &mut schema_builder)?;
+        let mut field_mappings = build_mapping_tree(initial_field_mappings, &mut schema_builder)?;
         let source_field = if builder.store_source {
             Some(schema_builder.add_json_field(SOURCE_FIELD_NAME, STORED))
         } else {
             None
         };
+        // Fields appended by doc mapping updates are added last so that the schemas of the splits
+        // created with previous versions of the doc mapping remain a prefix of the current one.
+        extend_mapping_tree(
+            &mut field_mappings,
+            appended_field_mappings,
+            &mut schema_builder,
+        )?;
 
         if let Some(timestamp_field_path) = builder.timestamp_field.as_ref() {
             validate_timestamp_field(timestamp_field_path, &field_mappings)?;
@@ -262,6 +326,8 @@ impl TryFrom<DefaultDocMapperBuilder> for DefaultDocMapper {
             mode: builder.mode,
             tokenizer_entries: builder.tokenizers,
             tokenizer_manager,
+            doc_mapping_version: builder.doc_mapping_version,
+            field_mapping_versions: builder.field_mapping_versions,
         })
     }
 }
@@ -367,6 +433,8 @@ impl From<DefaultDocMapper> for DefaultDocMapperBuilder {
             partition_key: partition_key_opt,
             max_num_partitions: default_doc_mapper.max_num_partitions,
             tokenizers: default_doc_mapper.tokenizer_entries,
+            doc_mapping_version: default_doc_mapper.doc_mapping_version,
+            field_mapping_versions: default_doc_mapper.field_mapping_versions,
         }
     }
 }
@@ -638,6 +706,10 @@ impl DocMapper for DefaultDocMapper {
         self.tag_field_names.clone()
     }
 
+    fn doc_mapping_version(&self) -> u64 {
+        self.doc_mapping_version
+    }
+
     fn max_num_partitions(&self) -> NonZeroU32 {
         self.max_num_partitions
     }
@@ -711,6 +783,92 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_doc_mapper_appended_field_mappings_come_after_source_field() {
+        let doc_mapper = serde_json::from_str::<DefaultDocMapper>(
+            r#"{
+                "store_source": true,
+                "mode": "lenient",
+                "doc_mapping_version": 2,
+                "field_mapping_versions": {"severity": 1, "latency": 2},
+                "field_mappings": [
+                    {"name": "body", "type": "text"},
+                    {"name": "severity", "type": "text", "tokenizer": "raw"},
+                    {"name": "latency", "type": "u64", "fast": true}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(doc_mapper.doc_mapping_version(), 2);
+
+        let schema = doc_mapper.schema();
+        let field_names: Vec<&str> = schema
+            .fields()
+            .map(|(_field, field_entry)| field_entry.name())
+            .collect();
+        assert_eq!(
+            field_names,
+            [
+                FIELD_PRESENCE_FIELD_NAME,
+                "body",
+                SOURCE_FIELD_NAME,
+                "severity",
+                "latency"
+            ]
+        );
+        let doc_mapper_json = serde_json::to_value(&doc_mapper).unwrap();
+        assert_eq!(doc_mapper_json["doc_mapping_version"], json!(2));
+        assert_eq!(
+            doc_mapper_json["field_mapping_versions"],
+            json!({"severity": 1, "latency": 2})
+        );
+    }
+
+    #[test]
+    fn test_doc_mapper_invalid_field_mapping_versions() {
+        let error = serde_json::from_str::<DefaultDocMapper>(
+            r#"{
+                "doc_mapping_version": 1,
+                "field_mapping_versions": {"body": 1},
+                "field_mappings": [
+                    {"name": "body", "type": "text"},
+                    {"name": "severity", "type": "text"}
+                ]
+            }"#,
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("field mapping `severity` was not appended"));
+
+        let error = serde_json::from_str::<DefaultDocMapper>(
+            r#"{
+                "doc_mapping_version": 1,
+                "field_mapping_versions": {"severity": 2},
+                "field_mappings": [
+                    {"name": "body", "type": "text"},
+                    {"name": "severity", "type": "text"}
+                ]
+            }"#,
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("invalid doc mapping version `2`"));
+
+        let error = serde_json::from_str::<DefaultDocMapper>(
+            r#"{
+                "doc_mapping_version": 1,
+                "field_mapping_versions": {"unknown": 1},
+                "field_mappings": [
+                    {"name": "body", "type": "text"}
+                ]
+            }"#,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("fields that are not mapped"));
+    }
+
     #[test]
     fn test_parsing_document() {
         let json_doc = example_json_doc_value();
@@ -17,8 +17,10 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::BTreeMap;
 use std::num::NonZeroU32;
 
+use quickwit_common::is_zero;
 use serde::{Deserialize, Serialize};
 
 use super::tokenizer_entry::TokenizerEntry;
@@ -80,6 +82,16 @@ pub struct DefaultDocMapperBuilder {
     /// User-defined tokenizers.
     #[serde(default)]
     pub tokenizers: Vec<TokenizerEntry>,
+    /// Version of the doc mapping, incremented every time the doc mapping of the index is
+    /// updated.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_zero")]
+    pub doc_mapping_version: u64,
+    /// Doc mapping version that introduced each of the field mappings appended to the doc
+    /// mapping after the creation of the index, keyed by field name.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub field_mapping_versions: BTreeMap<String, u64>,
 }
 
 /// Defines how an unmapped field should be handled.
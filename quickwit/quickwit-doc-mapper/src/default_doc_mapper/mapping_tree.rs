@@ -539,12 +539,33 @@ pub(crate) fn build_mapping_tree(
     build_mapping_tree_from_entries(entries, &mut field_path, schema)
 }
 
+/// Appends the fields defined by `entries` to the root node of an existing mapping tree. The
+/// associated tantivy fields are added to the schema after the fields already present.
+pub(crate) fn extend_mapping_tree(
+    mapping_node: &mut MappingNode,
+    entries: &[FieldMappingEntry],
+    schema: &mut SchemaBuilder,
+) -> anyhow::Result<()> {
+    let mut field_path = Vec::new();
+    extend_mapping_tree_from_entries(mapping_node, entries, &mut field_path, schema)
+}
+
 fn build_mapping_tree_from_entries<'a>(
     entries: &'a [FieldMappingEntry],
     field_path: &mut Vec<&'a str>,
     schema: &mut SchemaBuilder,
 ) -> anyhow::Result<MappingNode> {
     let mut mapping_node = MappingNode::default();
+    extend_mapping_tree_from_entries(&mut mapping_node, entries, field_path, schema)?;
+    Ok(mapping_node)
+}
+
+fn extend_mapping_tree_from_entries<'a>(
+    mapping_node: &mut MappingNode,
+    entries: &'a [FieldMappingEntry],
+    field_path: &mut Vec<&'a str>,
+    schema: &mut SchemaBuilder,
+) -> anyhow::Result<()> {
     for entry in entries {
         field_path.push(&entry.name);
         if mapping_node.branches.contains_key(&entry.name) {
@@ -554,7 +575,7 @@ fn build_mapping_tree_from_entries<'a>(
         field_path.pop();
         mapping_node.insert(&entry.name, child_tree);
     }
-    Ok(mapping_node)
+    Ok(())
 }
 
 fn get_numeric_options_for_bool_field(
@@ -123,6 +123,12 @@ pub trait DocMapper: Send + Sync + Debug + DynClone + 'static {
         Default::default()
     }
 
+    /// Returns the version of the doc mapping, which is incremented every time the doc mapping
+    /// of the index is updated.
+    fn doc_mapping_version(&self) -> u64 {
+        0
+    }
+
     /// Returns the tag `NameField`s on the current schema.
     /// Returns an error if a tag field is not found in this schema.
     fn tag_named_fields(&self) -> anyhow::Result<Vec<NamedField>> {
@@ -22,8 +22,8 @@ use std::convert::Infallible;
 use std::ops::Bound;
 
 use quickwit_query::query_ast::{
-    FieldPresenceQuery, FullTextQuery, PhrasePrefixQuery, QueryAst, QueryAstVisitor, RangeQuery,
-    TermSetQuery, WildcardQuery,
+    FieldPresenceQuery, FullTextQuery, FuzzyQuery, PhrasePrefixQuery, QueryAst, QueryAstVisitor,
+    RangeQuery, RegexQuery, TermSetQuery, WildcardQuery,
 };
 use quickwit_query::tokenizers::TokenizerManager;
 use quickwit_query::{find_field_or_hit_dynamic, InvalidQuery};
@@ -125,6 +125,16 @@ pub(crate) fn build_query(
     Ok((query, warmup_info))
 }
 
+/// Returns true if the field does not exist in the schema, which happens when a split was created
+/// with a doc mapping version preceding the addition of the field. Queries on such fields are
+/// built as `MatchNone` queries when validation is disabled, so there is nothing to warm up.
+fn is_missing_field(schema: &Schema, field_name: &str) -> bool {
+    matches!(
+        find_field_or_hit_dynamic(field_name, schema),
+        Err(InvalidQuery::FieldDoesNotExist { .. })
+    )
+}
+
 fn is_fast_field(schema: &Schema, field_name: &str) -> bool {
     if let Ok((_field, field_entry, _path)) = find_field_or_hit_dynamic(field_name, schema) {
         return field_entry.is_fast();
@@ -151,6 +161,9 @@ impl<'a, 'b> QueryAstVisitor<'a> for ExtractTermSetFields<'b> {
 
     fn visit_term_set(&mut self, term_set_query: &'a TermSetQuery) -> anyhow::Result<()> {
         for field in term_set_query.terms_per_field.keys() {
+            if is_missing_field(self.schema, field) {
+                continue;
+            }
             if let Ok((field, _field_entry, _path)) = find_field_or_hit_dynamic(field, self.schema)
             {
                 self.term_dict_fields_to_warm_up.insert(field);
@@ -160,6 +173,26 @@ impl<'a, 'b> QueryAstVisitor<'a> for ExtractTermSetFields<'b> {
         }
         Ok(())
     }
+
+    // Regex and fuzzy queries run an automaton over the whole term dictionary.
+    fn visit_regex(&mut self, regex_query: &'a RegexQuery) -> anyhow::Result<()> {
+        if is_missing_field(self.schema, &regex_query.field) {
+            return Ok(());
+        }
+        let (field, _regex) = regex_query.to_field_and_regex(self.schema)?;
+        self.term_dict_fields_to_warm_up.insert(field);
+        Ok(())
+    }
+
+    fn visit_fuzzy(&mut self, fuzzy_query: &'a FuzzyQuery) -> anyhow::Result<()> {
+        if is_missing_field(self.schema, &fuzzy_query.field) {
+            return Ok(());
+        }
+        let (field, _field_entry, _path) =
+            find_field_or_hit_dynamic(&fuzzy_query.field, self.schema)?;
+        self.term_dict_fields_to_warm_up.insert(field);
+        Ok(())
+    }
 }
 
 fn extract_term_set_query_fields(
@@ -244,6 +277,9 @@ impl<'a, 'b: 'a> QueryAstVisitor<'a> for ExtractPrefixTermRanges<'b> {
         &mut self,
         phrase_prefix: &'a PhrasePrefixQuery,
     ) -> Result<(), Self::Err> {
+        if is_missing_field(self.schema, &phrase_prefix.field) {
+            return Ok(());
+        }
         let (_, terms) = phrase_prefix.get_terms(self.schema, self.tokenizer_manager)?;
         if let Some((_, term)) = terms.last() {
             self.add_prefix_term(term.clone(), phrase_prefix.max_expansions, terms.len() > 1);
@@ -252,6 +288,9 @@ impl<'a, 'b: 'a> QueryAstVisitor<'a> for ExtractPrefixTermRanges<'b> {
     }
 
     fn visit_wildcard(&mut self, wildcard_query: &'a WildcardQuery) -> Result<(), Self::Err> {
+        if is_missing_field(self.schema, &wildcard_query.field) {
+            return Ok(());
+        }
         let (_, term) = wildcard_query.extract_prefix_term(self.schema, self.tokenizer_manager)?;
         self.add_prefix_term(term, u32::MAX, false);
         Ok(())
@@ -272,7 +311,7 @@ fn extract_prefix_term_ranges(
 mod test {
     use quickwit_datetime::{parse_date_time_str, DateTimeInputFormat};
     use quickwit_query::create_default_quickwit_tokenizer_manager;
-    use quickwit_query::query_ast::query_ast_from_user_text;
+    use quickwit_query::query_ast::{query_ast_from_user_text, FuzzyQuery, QueryAst, RegexQuery};
     use tantivy::columnar::MonotonicallyMappableToU64;
     use tantivy::schema::{Schema, FAST, INDEXED, STORED, TEXT};
     use tantivy::{DateOptions, DateTime, DateTimePrecision};
@@ -679,4 +718,91 @@ mod test {
         .unwrap();
         assert!(warmup_info.term_dict_fields.is_empty());
     }
+
+    #[test]
+    fn test_build_query_warmup_info_regex_and_fuzzy() {
+        let regex_query: QueryAst = RegexQuery {
+            field: "desc".to_string(),
+            regex: "hel.*".to_string(),
+        }
+        .into();
+        let fuzzy_query: QueryAst = FuzzyQuery {
+            field: "title".to_string(),
+            value: "helo".to_string(),
+            distance: 1,
+            transpositions: true,
+        }
+        .into();
+        for (query_ast, field_id) in [(regex_query, 1), (fuzzy_query, 0)] {
+            let (_, warmup_info) = build_query(
+                &query_ast,
+                make_schema(true),
+                &create_default_quickwit_tokenizer_manager(),
+                &[],
+                true,
+            )
+            .unwrap();
+            assert_eq!(warmup_info.term_dict_fields.len(), 1);
+            assert!(warmup_info
+                .term_dict_fields
+                .contains(&tantivy::schema::Field::from_field_id(field_id)));
+        }
+    }
+
+    #[test]
+    fn test_build_query_on_field_missing_from_split_schema() {
+        // `new_field` was appended to the doc mapping after the split was created.
+        let user_queries = [
+            "new_field:hello",
+            "new_field:hel*",
+            "new_field:\"hello wor\"*",
+            "new_field: IN [hello world]",
+            "title:hello OR new_field:hel*",
+        ];
+        let mut query_asts: Vec<QueryAst> = user_queries
+            .iter()
+            .map(|user_query| {
+                query_ast_from_user_text(user_query, None)
+                    .parse_user_query(&[])
+                    .unwrap()
+            })
+            .collect();
+        query_asts.push(
+            RegexQuery {
+                field: "new_field".to_string(),
+                regex: "hel.*".to_string(),
+            }
+            .into(),
+        );
+        query_asts.push(
+            FuzzyQuery {
+                field: "new_field".to_string(),
+                value: "helo".to_string(),
+                distance: 1,
+                transpositions: true,
+            }
+            .into(),
+        );
+        for query_ast in &query_asts {
+            build_query(
+                query_ast,
+                make_schema(false),
+                &create_default_quickwit_tokenizer_manager(),
+                &[],
+                true,
+            )
+            .unwrap_err();
+
+            let (_query, warmup_info) = build_query(
+                query_ast,
+                make_schema(false),
+                &create_default_quickwit_tokenizer_manager(),
+                &[],
+                false,
+            )
+            .unwrap();
+            assert!(warmup_info.term_dict_fields.is_empty());
+            assert!(warmup_info.term_ranges_grouped_by_field.is_empty());
+        }
+    }
 }
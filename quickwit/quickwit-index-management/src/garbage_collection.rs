@@ -18,11 +18,12 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
-use futures::Future;
+use futures::{Future, TryStreamExt};
 use quickwit_common::{PrettySample, Progress, ServiceStream};
 use quickwit_metastore::{
     ListSplitsQuery, ListSplitsRequestExt, MetastoreServiceStreamSplitsExt, SplitInfo,
@@ -33,10 +34,10 @@ use quickwit_proto::metastore::{
     MetastoreError, MetastoreResult, MetastoreService, MetastoreServiceClient,
 };
 use quickwit_proto::types::{IndexUid, SplitId};
-use quickwit_storage::{BulkDeleteError, Storage};
+use quickwit_storage::{BulkDeleteError, FileMetadata, Storage};
 use thiserror::Error;
 use time::OffsetDateTime;
-use tracing::{error, instrument};
+use tracing::{error, instrument, warn};
 
 /// The maximum number of splits that the GC should delete per attempt.
 const DELETE_SPLITS_BATCH_SIZE: usize = 1000;
@@ -164,6 +165,154 @@ pub async fn run_garbage_collect(
 
     Ok(deleted_splits)
 }
+
+/// Information on the orphan split files cleaned up by the GC.
+#[derive(Debug, Default)]
+pub struct OrphanFilesRemovalInfo {
+    /// The orphan files that have been removed, or that would be removed in dry run mode.
+    pub removed_files: Vec<FileMetadata>,
+    /// The orphan files that were attempted to be removed, but were unsuccessful.
+    pub failed_files: Vec<FileMetadata>,
+}
+
+impl OrphanFilesRemovalInfo {
+    /// Returns the number of bytes reclaimed, or reclaimable in dry run mode.
+    pub fn num_removed_bytes(&self) -> u64 {
+        self.removed_files
+            .iter()
+            .map(|file_metadata| file_metadata.num_bytes)
+            .sum()
+    }
+}
+
+/// Detects the split files of the index storage that are not referenced by the metastore and
+/// removes them.
+///
+/// Such orphan files are left behind, for instance, by an uploader that crashed before staging
+/// its split, or after restoring the metastore from a backup. Only the split files last modified
+/// before `now - grace_period` are considered, so files of storages that do not report a
+/// modification time are never removed.
+///
+/// * `index_uid` - The target index UID.
+/// * `storage - The storage managing the target index.
+/// * `metastore` - The metastore managing the target index.
+/// * `grace_period` - Threshold period after which an unreferenced split file can be safely
+///   deleted.
+/// * `dry_run` - Should this only return a list of affected files without performing deletion.
+/// * `progress` - For reporting progress (useful when called from within a quickwit actor).
+pub async fn run_orphan_files_garbage_collect(
+    index_uid: IndexUid,
+    storage: Arc<dyn Storage>,
+    mut metastore: MetastoreServiceClient,
+    grace_period: Duration,
+    dry_run: bool,
+    progress_opt: Option<&Progress>,
+) -> anyhow::Result<OrphanFilesRemovalInfo> {
+    let grace_period_timestamp =
+        OffsetDateTime::now_utc().unix_timestamp() - grace_period.as_secs() as i64;
+
+    // The storage must be listed before the metastore: splits are staged before being uploaded,
+    // so every split file listed here that is not orphan is guaranteed to be known by the
+    // metastore when we list its splits.
+    let listed_files: Vec<FileMetadata> = protect_future(
+        progress_opt,
+        storage.list(Path::new("")).try_collect::<Vec<_>>(),
+    )
+    .await?;
+    let candidate_files: Vec<FileMetadata> = listed_files
+        .into_iter()
+        .filter(|file_metadata| {
+            is_split_file(&file_metadata.path)
+                && file_metadata
+                    .last_modified_opt
+                    .map(|last_modified| last_modified <= grace_period_timestamp)
+                    .unwrap_or(false)
+        })
+        .collect();
+
+    if candidate_files.is_empty() {
+        return Ok(OrphanFilesRemovalInfo::default());
+    }
+    let list_splits_query = ListSplitsQuery::for_index(index_uid.clone());
+    let list_splits_request = ListSplitsRequest::try_from_list_splits_query(list_splits_query)?;
+    let split_ids: HashSet<String> =
+        protect_future(progress_opt, metastore.list_splits(list_splits_request))
+            .await?
+            .collect_splits_metadata()
+            .await?
+            .into_iter()
+            .map(|split_metadata| split_metadata.split_id)
+            .collect();
+
+    let orphan_files: Vec<FileMetadata> = candidate_files
+        .into_iter()
+        .filter(|file_metadata| {
+            let split_id = file_metadata
+                .path
+                .file_stem()
+                .and_then(OsStr::to_str)
+                .unwrap_or_default();
+            !split_ids.contains(split_id)
+        })
+        .collect();
+
+    if dry_run || orphan_files.is_empty() {
+        return Ok(OrphanFilesRemovalInfo {
+            removed_files: orphan_files,
+            failed_files: Vec::new(),
+        });
+    }
+    let orphan_paths: Vec<&Path> = orphan_files
+        .iter()
+        .map(|file_metadata| file_metadata.path.as_path())
+        .collect();
+    warn!(
+        index_id = index_uid.index_id(),
+        num_orphan_files = orphan_files.len(),
+        "deleting orphan split file(s) {:?}",
+        PrettySample::new(&orphan_paths, 5),
+    );
+    let mut removal_info = OrphanFilesRemovalInfo::default();
+
+    for orphan_files_batch in orphan_files.chunks(DELETE_SPLITS_BATCH_SIZE) {
+        let batch_paths: Vec<&Path> = orphan_files_batch
+            .iter()
+            .map(|file_metadata| file_metadata.path.as_path())
+            .collect();
+        let delete_result = protect_future(progress_opt, storage.bulk_delete(&batch_paths)).await;
+
+        if let Some(progress) = progress_opt {
+            progress.record_progress();
+        }
+        let Err(bulk_delete_error) = delete_result else {
+            removal_info
+                .removed_files
+                .extend_from_slice(orphan_files_batch);
+            continue;
+        };
+        let success_paths: HashSet<&PathBuf> = bulk_delete_error.successes.iter().collect();
+
+        for file_metadata in orphan_files_batch {
+            if success_paths.contains(&file_metadata.path) {
+                removal_info.removed_files.push(file_metadata.clone());
+            } else {
+                removal_info.failed_files.push(file_metadata.clone());
+            }
+        }
+        error!(
+            error=?bulk_delete_error.error,
+            index_id=index_uid.index_id(),
+            "failed to delete orphan split file(s) from storage",
+        );
+    }
+    Ok(removal_info)
+}
+
+/// Returns whether the path designates a split file located at the root of the index storage.
+fn is_split_file(path: &Path) -> bool {
+    path.parent() == Some(Path::new("")) && path.extension() == Some(OsStr::new("split"))
+}
+
 #[instrument(skip(storage, metastore, progress_opt))]
 /// Removes any splits marked for deletion which haven't been
 /// updated after `updated_before_timestamp` in batches of 1000 splits.
@@ -348,9 +497,11 @@ pub async fn delete_splits_from_storage_and_metastore(
 
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
     use std::time::Duration;
 
     use itertools::Itertools;
+    use quickwit_common::uri::Uri;
     use quickwit_common::ServiceStream;
     use quickwit_config::IndexConfig;
     use quickwit_metastore::{
@@ -360,7 +511,7 @@ mod tests {
     use quickwit_proto::metastore::{CreateIndexRequest, EntityKind, StageSplitsRequest};
     use quickwit_proto::types::IndexUid;
     use quickwit_storage::{
-        storage_for_test, BulkDeleteError, DeleteFailure, MockStorage, PutPayload,
+        storage_for_test, BulkDeleteError, DeleteFailure, LocalFileStorage, MockStorage, PutPayload,
     };
 
     use super::*;
@@ -589,6 +740,123 @@ mod tests {
         .unwrap();
     }
 
+    #[tokio::test]
+    async fn test_run_orphan_files_gc() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let index_uri = Uri::from_str(&temp_dir.path().display().to_string()).unwrap();
+        let storage: Arc<dyn Storage> = Arc::new(LocalFileStorage::from_uri(&index_uri).unwrap());
+        let mut metastore = metastore_for_test();
+
+        let index_id = "test-run-orphan-files-gc--index";
+        let index_config = IndexConfig::for_test(index_id, index_uri.as_str());
+        let create_index_request = CreateIndexRequest::try_from_index_config(index_config).unwrap();
+        let index_uid: IndexUid = metastore
+            .create_index(create_index_request)
+            .await
+            .unwrap()
+            .index_uid
+            .into();
+
+        let split_metadata = SplitMetadata {
+            split_id: "known".to_string(),
+            index_uid: index_uid.clone(),
+            ..Default::default()
+        };
+        let stage_splits_request =
+            StageSplitsRequest::try_from_split_metadata(index_uid.clone(), split_metadata).unwrap();
+        metastore.stage_splits(stage_splits_request).await.unwrap();
+
+        for path in [
+            "known.split",
+            "orphan.split",
+            "orphan.json",
+            "nested/orphan.split",
+        ] {
+            let payload: Box<dyn PutPayload> = Box::new(b"split".to_vec());
+            storage.put(Path::new(path), payload).await.unwrap();
+        }
+        // The orphan file is too recent to be garbage collected.
+        let removal_info = run_orphan_files_garbage_collect(
+            index_uid.clone(),
+            storage.clone(),
+            metastore.clone(),
+            Duration::from_secs(3_600),
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(removal_info.removed_files.is_empty());
+        assert!(removal_info.failed_files.is_empty());
+
+        let removal_info = run_orphan_files_garbage_collect(
+            index_uid.clone(),
+            storage.clone(),
+            metastore.clone(),
+            Duration::ZERO,
+            true,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(removal_info.removed_files.len(), 1);
+        assert_eq!(
+            removal_info.removed_files[0].path,
+            Path::new("orphan.split")
+        );
+        assert_eq!(removal_info.num_removed_bytes(), 5);
+        assert!(storage.exists(Path::new("orphan.split")).await.unwrap());
+
+        let removal_info = run_orphan_files_garbage_collect(
+            index_uid.clone(),
+            storage.clone(),
+            metastore.clone(),
+            Duration::ZERO,
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(removal_info.removed_files.len(), 1);
+        assert_eq!(
+            removal_info.removed_files[0].path,
+            Path::new("orphan.split")
+        );
+        assert!(removal_info.failed_files.is_empty());
+
+        assert!(!storage.exists(Path::new("orphan.split")).await.unwrap());
+        assert!(storage.exists(Path::new("known.split")).await.unwrap());
+        assert!(storage.exists(Path::new("orphan.json")).await.unwrap());
+        assert!(storage
+            .exists(Path::new("nested/orphan.split"))
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_run_orphan_files_gc_ignores_files_without_modification_time() {
+        let storage = storage_for_test();
+        storage
+            .put(Path::new("orphan.split"), Box::new(b"split".to_vec()))
+            .await
+            .unwrap();
+        // The metastore is not called since there is no candidate file.
+        let metastore = MetastoreServiceClient::mock();
+
+        let removal_info = run_orphan_files_garbage_collect(
+            IndexUid::new_with_random_ulid("test-run-orphan-files-gc--index"),
+            storage.clone(),
+            MetastoreServiceClient::from(metastore),
+            Duration::ZERO,
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(removal_info.removed_files.is_empty());
+        assert!(storage.exists(Path::new("orphan.split")).await.unwrap());
+    }
+
     #[tokio::test]
     async fn test_delete_splits_from_storage_and_metastore_happy_path() {
         let storage = storage_for_test();
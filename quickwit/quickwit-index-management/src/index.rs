@@ -26,12 +26,12 @@ use quickwit_indexing::check_source_connectivity;
 use quickwit_metastore::{
     AddSourceRequestExt, CreateIndexRequestExt, IndexMetadata, IndexMetadataResponseExt,
     ListSplitsQuery, ListSplitsRequestExt, MetastoreServiceStreamSplitsExt, SplitInfo,
-    SplitMetadata, SplitState,
+    SplitMetadata, SplitState, UpdateIndexRequestExt,
 };
 use quickwit_proto::metastore::{
     AddSourceRequest, CreateIndexRequest, DeleteIndexRequest, EntityKind, IndexMetadataRequest,
     ListSplitsRequest, MarkSplitsForDeletionRequest, MetastoreError, MetastoreService,
-    MetastoreServiceClient, ResetSourceCheckpointRequest,
+    MetastoreServiceClient, ResetSourceCheckpointRequest, UpdateIndexRequest,
 };
 use quickwit_proto::types::{IndexUid, SplitId};
 use quickwit_proto::{ServiceError, ServiceErrorCode};
@@ -40,8 +40,8 @@ use thiserror::Error;
 use tracing::{error, info};
 
 use crate::garbage_collection::{
-    delete_splits_from_storage_and_metastore, run_garbage_collect, DeleteSplitsError,
-    SplitRemovalInfo,
+    delete_splits_from_storage_and_metastore, run_garbage_collect,
+    run_orphan_files_garbage_collect, DeleteSplitsError, OrphanFilesRemovalInfo, SplitRemovalInfo,
 };
 
 #[derive(Error, Debug)]
@@ -151,6 +151,59 @@ impl IndexService {
         Ok(index_metadata)
     }
 
+    /// Updates the index specified with `index_id` from `IndexConfig`.
+    ///
+    /// Only the search settings, the retention policy, and the indexing settings of an index can
+    /// be updated. The indexing pipelines of the index are restarted to pick up the new indexing
+    /// settings.
+    pub async fn update_index(
+        &mut self,
+        index_id: &str,
+        index_config: IndexConfig,
+    ) -> Result<IndexMetadata, IndexServiceError> {
+        if index_config.index_id != index_id {
+            return Err(IndexServiceError::InvalidConfig(anyhow::anyhow!(
+                "index ID `{}` in the index config does not match index ID `{index_id}`",
+                index_config.index_id
+            )));
+        }
+        let index_metadata_request = IndexMetadataRequest::for_index_id(index_id.to_string());
+        let index_metadata = self
+            .metastore
+            .index_metadata(index_metadata_request)
+            .await?
+            .deserialize_index_metadata()?;
+        let current_index_config = index_metadata.index_config();
+
+        if current_index_config.index_uri != index_config.index_uri {
+            return Err(IndexServiceError::OperationNotAllowed(format!(
+                "index URI of index `{index_id}` cannot be updated: current URI is `{}`",
+                current_index_config.index_uri
+            )));
+        }
+        let doc_mapping = current_index_config
+            .doc_mapping
+            .evolve(index_config.doc_mapping)
+            .map_err(|error| {
+                IndexServiceError::OperationNotAllowed(format!(
+                    "doc mapping of index `{index_id}` cannot be updated: {error:#}"
+                ))
+            })?;
+        let update_index_request = UpdateIndexRequest::try_from_updates(
+            index_metadata.index_uid,
+            &doc_mapping,
+            &index_config.search_settings,
+            &index_config.retention_policy,
+            &index_config.indexing_settings,
+        )?;
+        let updated_index_metadata = self
+            .metastore
+            .update_index(update_index_request)
+            .await?
+            .deserialize_index_metadata()?;
+        Ok(updated_index_metadata)
+    }
+
     /// Deletes the index specified with `index_id`.
     /// This is equivalent to running `rm -rf <index path>` for a local index or
     /// `aws s3 rm --recursive <index path>` for a remote Amazon S3 index.
@@ -268,6 +321,45 @@ impl IndexService {
         Ok(deleted_entries)
     }
 
+    /// Detects the split files of the index storage that are not referenced by the metastore and
+    /// removes them.
+    ///
+    /// * `index_id` - The target index Id.
+    /// * `grace_period` - Threshold period after which an unreferenced split file can be garbage
+    ///   collected.
+    /// * `dry_run` - Should this only return a list of affected files without performing deletion.
+    pub async fn garbage_collect_orphan_files(
+        &mut self,
+        index_id: &str,
+        grace_period: Duration,
+        dry_run: bool,
+    ) -> anyhow::Result<OrphanFilesRemovalInfo> {
+        let index_metadata_request = IndexMetadataRequest::for_index_id(index_id.to_string());
+        let index_metadata = self
+            .metastore
+            .index_metadata(index_metadata_request)
+            .await?
+            .deserialize_index_metadata()?;
+        let index_uid = index_metadata.index_uid.clone();
+        let index_config = index_metadata.into_index_config();
+        let storage = self
+            .storage_resolver
+            .resolve(&index_config.index_uri)
+            .await?;
+
+        let removal_info = run_orphan_files_garbage_collect(
+            index_uid,
+            storage,
+            self.metastore.clone(),
+            grace_period,
+            dry_run,
+            None,
+        )
+        .await?;
+
+        Ok(removal_info)
+    }
+
     /// Clears the index by applying the following actions:
     /// - mark all splits for deletion in the metastore.
     /// - delete the files of all splits marked for deletion using garbage collection.
@@ -420,7 +512,7 @@ pub async fn validate_storage_uri(
 mod tests {
 
     use quickwit_common::uri::Uri;
-    use quickwit_config::IndexConfig;
+    use quickwit_config::{IndexConfig, RetentionPolicy};
     use quickwit_metastore::{
         metastore_for_test, MetastoreServiceExt, SplitMetadata, StageSplitsRequestExt,
     };
@@ -468,6 +560,65 @@ mod tests {
         assert!(index_metadata_0.index_uid != index_metadata_1.index_uid);
     }
 
+    #[tokio::test]
+    async fn test_update_index() {
+        let mut metastore = metastore_for_test();
+        let storage_resolver = StorageResolver::for_test();
+        let mut index_service = IndexService::new(metastore.clone(), storage_resolver);
+        let index_id = "test-index";
+        let index_uri = "ram://indexes/test-index";
+        let index_config = IndexConfig::for_test(index_id, index_uri);
+        let index_metadata = index_service
+            .create_index(index_config.clone(), false)
+            .await
+            .unwrap();
+
+        let mut updated_index_config = index_config.clone();
+        updated_index_config.search_settings.default_search_fields = vec!["owner".to_string()];
+        updated_index_config.retention_policy = Some(RetentionPolicy::new(
+            "1 day".to_string(),
+            "hourly".to_string(),
+        ));
+        updated_index_config.indexing_settings.commit_timeout_secs = 5;
+
+        let updated_index_metadata = index_service
+            .update_index(index_id, updated_index_config.clone())
+            .await
+            .unwrap();
+        assert_eq!(updated_index_metadata.index_uid, index_metadata.index_uid);
+        assert_eq!(updated_index_metadata.index_config, updated_index_config);
+
+        let index_metadata = metastore
+            .index_metadata(IndexMetadataRequest::for_index_id(index_id.to_string()))
+            .await
+            .unwrap()
+            .deserialize_index_metadata()
+            .unwrap();
+        assert_eq!(index_metadata.index_config, updated_index_config);
+
+        let error = index_service
+            .update_index("other-index", updated_index_config.clone())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, IndexServiceError::InvalidConfig(_)));
+
+        let mut invalid_index_config = updated_index_config.clone();
+        invalid_index_config.index_uri = Uri::for_test("ram://indexes/other-index");
+        let error = index_service
+            .update_index(index_id, invalid_index_config)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, IndexServiceError::OperationNotAllowed(_)));
+
+        let mut invalid_index_config = updated_index_config;
+        invalid_index_config.doc_mapping.store_source = false;
+        let error = index_service
+            .update_index(index_id, invalid_index_config)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, IndexServiceError::OperationNotAllowed(_)));
+    }
+
     #[tokio::test]
     async fn test_delete_index() {
         let mut metastore = metastore_for_test();
@@ -20,5 +20,7 @@
 mod garbage_collection;
 mod index;
 
-pub use garbage_collection::run_garbage_collect;
+pub use garbage_collection::{
+    run_garbage_collect, run_orphan_files_garbage_collect, OrphanFilesRemovalInfo,
+};
 pub use index::{clear_cache_directory, validate_storage_uri, IndexService, IndexServiceError};
@@ -33,7 +33,7 @@ use quickwit_opentelemetry::otlp::{
 };
 use serde::Serialize;
 use serde_json::Value as JsonValue;
-use tantivy::schema::{Field, Value};
+use tantivy::schema::{Field, FieldType, Schema, Value};
 use tantivy::{DateTime, TantivyDocument};
 use thiserror::Error;
 use tokio::runtime::Handle;
@@ -117,6 +117,65 @@ impl From<FromUtf8Error> for DocProcessorError {
     }
 }
 
+/// Converts a CSV value to the type of the field it is mapped to. CSV values are always strings,
+/// so numbers and booleans have to be parsed before being handed to the doc mapper. Values that
+/// fail to parse are left as strings and rejected by the doc mapper.
+fn coerce_csv_value(field_type_opt: Option<&FieldType>, value: String) -> JsonValue {
+    let coerced_value_opt = match field_type_opt {
+        Some(FieldType::U64(_)) => value.parse::<u64>().ok().map(JsonValue::from),
+        Some(FieldType::I64(_)) => value.parse::<i64>().ok().map(JsonValue::from),
+        Some(FieldType::F64(_)) => value
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(JsonValue::Number),
+        Some(FieldType::Bool(_)) => value.parse::<bool>().ok().map(JsonValue::Bool),
+        _ => None,
+    };
+    coerced_value_opt.unwrap_or(JsonValue::String(value))
+}
+
+/// Parses a CSV record, which the sources convert into a JSON object keyed by the field names of
+/// the CSV header. The string values are coerced to the types of the fields of `schema_opt`, if
+/// provided.
+fn csv_record_to_json_obj(
+    raw_doc: &[u8],
+    schema_opt: Option<&Schema>,
+) -> Result<JsonObject, DocProcessorError> {
+    let json_obj = match serde_json::from_slice::<JsonValue>(raw_doc)? {
+        JsonValue::Object(json_obj) => json_obj,
+        JsonValue::Array(values) => {
+            return Err(DocProcessorError::Parsing(format!(
+                "CSV record has {} fields, which does not match the CSV header",
+                values.len()
+            )));
+        }
+        _ => {
+            return Err(DocProcessorError::Parsing(
+                "CSV record must be a JSON object".to_string(),
+            ));
+        }
+    };
+    let Some(schema) = schema_opt else {
+        return Ok(json_obj);
+    };
+    let coerced_json_obj = json_obj
+        .into_iter()
+        .map(|(field_name, value)| {
+            let JsonValue::String(value) = value else {
+                return (field_name, value);
+            };
+            let field_type_opt = schema
+                .get_field(&field_name)
+                .ok()
+                .map(|field| schema.get_field_entry(field).field_type());
+            let coerced_value = coerce_csv_value(field_type_opt, value);
+            (field_name, coerced_value)
+        })
+        .collect();
+    Ok(coerced_json_obj)
+}
+
 #[cfg(feature = "vrl")]
 fn try_into_vrl_doc(
     input_format: SourceInputFormat,
@@ -132,6 +191,19 @@ fn try_into_vrl_doc(
             map.insert(key, value);
             VrlValue::Object(map)
         }
+        SourceInputFormat::Csv => {
+            // The values are handed to the VRL program as strings: the program is in charge of
+            // converting them.
+            let json_obj = csv_record_to_json_obj(&raw_doc, None)?;
+            let map = json_obj
+                .into_iter()
+                .filter_map(|(key, value)| match value {
+                    JsonValue::String(value) => Some((key, VrlValue::Bytes(Bytes::from(value)))),
+                    _ => None,
+                })
+                .collect();
+            VrlValue::Object(map)
+        }
         SourceInputFormat::OtlpTraceJson | SourceInputFormat::OtlpTraceProtobuf => {
             panic!("OTP log or trace data does not support VRL transforms")
         }
@@ -144,6 +216,7 @@ fn try_into_json_docs(
     input_format: SourceInputFormat,
     raw_doc: Bytes,
     num_bytes: usize,
+    schema: &Schema,
 ) -> JsonDocIterator {
     match input_format {
         SourceInputFormat::Json => {
@@ -168,6 +241,11 @@ fn try_into_json_docs(
             });
             JsonDocIterator::from(json_doc_result)
         }
+        SourceInputFormat::Csv => {
+            let json_doc_result = csv_record_to_json_obj(&raw_doc, Some(schema))
+                .map(|json_obj| JsonDoc::new(json_obj, num_bytes));
+            JsonDocIterator::from(json_doc_result)
+        }
     }
 }
 
@@ -176,10 +254,11 @@ fn parse_raw_doc(
     input_format: SourceInputFormat,
     raw_doc: Bytes,
     num_bytes: usize,
+    schema: &Schema,
     vrl_program_opt: Option<&mut VrlProgram>,
 ) -> JsonDocIterator {
     let Some(vrl_program) = vrl_program_opt else {
-        return try_into_json_docs(input_format, raw_doc, num_bytes);
+        return try_into_json_docs(input_format, raw_doc, num_bytes, schema);
     };
     let json_doc_result = try_into_vrl_doc(input_format, raw_doc, num_bytes)
         .and_then(|vrl_doc| vrl_program.transform_doc(vrl_doc))
@@ -193,9 +272,10 @@ fn parse_raw_doc(
     input_format: SourceInputFormat,
     raw_doc: Bytes,
     num_bytes: usize,
+    schema: &Schema,
     _vrl_program_opt: Option<&mut VrlProgram>,
 ) -> JsonDocIterator {
-    try_into_json_docs(input_format, raw_doc, num_bytes)
+    try_into_json_docs(input_format, raw_doc, num_bytes, schema)
 }
 
 enum JsonDocIterator {
@@ -345,6 +425,7 @@ pub struct DocProcessor {
     #[cfg(feature = "vrl")]
     transform_opt: Option<VrlProgram>,
     input_format: SourceInputFormat,
+    schema: Schema,
 }
 
 impl DocProcessor {
@@ -357,6 +438,7 @@ impl DocProcessor {
         input_format: SourceInputFormat,
     ) -> anyhow::Result<Self> {
         let timestamp_field_opt = extract_timestamp_field(&*doc_mapper)?;
+        let schema = doc_mapper.schema();
         if cfg!(not(feature = "vrl")) && transform_config_opt.is_some() {
             bail!("VRL is not enabled. please recompile with the `vrl` feature")
         }
@@ -371,6 +453,7 @@ impl DocProcessor {
                 .map(VrlProgram::try_from_transform_config)
                 .transpose()?,
             input_format,
+            schema,
         };
         Ok(doc_processor)
     }
@@ -403,7 +486,13 @@ impl DocProcessor {
         #[cfg(not(feature = "vrl"))]
         let transform_opt: Option<&mut VrlProgram> = None;
 
-        for json_doc_result in parse_raw_doc(self.input_format, raw_doc, num_bytes, transform_opt) {
+        for json_doc_result in parse_raw_doc(
+            self.input_format,
+            raw_doc,
+            num_bytes,
+            &self.schema,
+            transform_opt,
+        ) {
             let processed_doc_result =
                 json_doc_result.and_then(|json_doc| self.process_json_doc(json_doc));
 
@@ -505,6 +594,7 @@ impl Handler<RawDocBatch> for DocProcessor {
             return Ok(());
         }
         let mut processed_docs: Vec<ProcessedDoc> = Vec::with_capacity(raw_doc_batch.docs.len());
+
         for raw_doc in raw_doc_batch.docs {
             let _protected_zone_guard = ctx.protect_zone();
             self.process_raw_doc(raw_doc, &mut processed_docs);
@@ -649,6 +739,103 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_doc_processor_with_csv_input() {
+        let universe = Universe::with_accelerated_time();
+        let doc_mapper = Arc::new(default_doc_mapper_for_test());
+        let (indexer_mailbox, indexer_inbox) = universe.create_test_mailbox();
+        let doc_processor = DocProcessor::try_new(
+            "my-index".to_string(),
+            "my-source".to_string(),
+            doc_mapper.clone(),
+            indexer_mailbox,
+            None,
+            SourceInputFormat::Csv,
+        )
+        .unwrap();
+        let (doc_processor_mailbox, doc_processor_handle) =
+            universe.spawn_builder().spawn(doc_processor);
+        doc_processor_mailbox
+            .send_message(RawDocBatch::for_test(
+                &[
+                    r#"{"body":"happy, using CSV","isImportant":"true","response_date":"2021-12-19T16:39:59+00:00","response_payload":"YWJj","response_time":"2","timestamp":"1628837062"}"#,
+                    r#"{"body":"happy2","response_payload":"YWJj","response_time":"13","timestamp":"1628837062"}"#,
+                    r#"["happy3","1628837062"]"#, // missing fields
+                ],
+                0..3,
+            ))
+            .await
+            .unwrap();
+        doc_processor_mailbox
+            .send_message(RawDocBatch::for_test(
+                &[r#"{"body":"happy4","isImportant":"false","timestamp":"1628837063"}"#],
+                3..4,
+            ))
+            .await
+            .unwrap();
+        let counters = doc_processor_handle
+            .process_pending_and_observe()
+            .await
+            .state;
+        assert_eq!(counters.num_doc_parsing_errors.load(Ordering::Relaxed), 1);
+        assert_eq!(counters.num_valid_docs.load(Ordering::Relaxed), 3);
+
+        let mut batches: Vec<ProcessedDocBatch> = indexer_inbox.drain_for_test_typed();
+        assert_eq!(batches.len(), 2);
+        let batch = batches.remove(0);
+        assert_eq!(batch.docs.len(), 2);
+        assert_eq!(
+            batch.checkpoint_delta,
+            SourceCheckpointDelta::from_range(0..3)
+        );
+        let schema = doc_mapper.schema();
+        let NamedFieldDocument(named_field_doc_map) = batch.docs[0].doc.to_named_doc(&schema);
+        let doc_json = JsonValue::Object(doc_mapper.doc_to_json(named_field_doc_map).unwrap());
+        assert_eq!(doc_json["body"], "happy, using CSV");
+        assert_eq!(doc_json["timestamp"], 1628837062);
+        assert_eq!(doc_json["response_time"], 2.0);
+        assert_eq!(doc_json["isImportant"], true);
+
+        let NamedFieldDocument(named_field_doc_map) = batch.docs[1].doc.to_named_doc(&schema);
+        let doc_json = JsonValue::Object(doc_mapper.doc_to_json(named_field_doc_map).unwrap());
+        assert_eq!(doc_json["body"], "happy2");
+        assert!(doc_json.get("response_date").is_none());
+
+        let NamedFieldDocument(named_field_doc_map) = batches[0].docs[0].doc.to_named_doc(&schema);
+        let doc_json = JsonValue::Object(doc_mapper.doc_to_json(named_field_doc_map).unwrap());
+        assert_eq!(doc_json["body"], "happy4");
+        assert_eq!(doc_json["timestamp"], 1628837063);
+        assert_eq!(doc_json["isImportant"], false);
+        universe.assert_quit().await;
+    }
+
+    #[test]
+    fn test_csv_record_to_json_obj() {
+        let doc_mapper = default_doc_mapper_for_test();
+        let schema = doc_mapper.schema();
+        let raw_doc = br#"{"body":"12","isImportant":"true","response_time":"1.5","unknown":"3"}"#;
+
+        let json_obj = csv_record_to_json_obj(raw_doc, Some(&schema)).unwrap();
+        assert_eq!(
+            JsonValue::Object(json_obj),
+            serde_json::json!({
+                "body": "12",
+                "isImportant": true,
+                "response_time": 1.5,
+                "unknown": "3"
+            })
+        );
+        let json_obj = csv_record_to_json_obj(raw_doc, None).unwrap();
+        assert_eq!(json_obj["isImportant"], "true");
+
+        let json_obj =
+            csv_record_to_json_obj(br#"{"isImportant":"maybe"}"#, Some(&schema)).unwrap();
+        assert_eq!(json_obj["isImportant"], "maybe");
+
+        let error = csv_record_to_json_obj(br#"["a","b"]"#, Some(&schema)).unwrap_err();
+        assert!(matches!(error, DocProcessorError::Parsing(_)));
+    }
+
     const DOCMAPPER_WITH_PARTITION_JSON: &str = r#"
         {
             "tag_fields": ["tenant"],
@@ -101,6 +101,7 @@ struct IndexerState {
     schema: Schema,
     tokenizer_manager: TokenizerManager,
     max_num_partitions: NonZeroU32,
+    doc_mapping_version: u64,
     index_settings: IndexSettings,
     cooperative_indexing_permits: Option<Arc<Semaphore>>,
 }
@@ -131,6 +132,7 @@ impl IndexerState {
             self.pipeline_id.clone(),
             partition_id,
             last_delete_opstamp,
+            self.doc_mapping_version,
             self.indexing_directory.clone(),
             index_builder,
             io_controls,
@@ -548,6 +550,7 @@ impl Indexer {
                 tokenizer_manager: tokenizer_manager.tantivy_manager().clone(),
                 index_settings,
                 max_num_partitions: doc_mapper.max_num_partitions(),
+                doc_mapping_version: doc_mapper.doc_mapping_version(),
                 cooperative_indexing_permits,
             },
             index_serializer_mailbox,
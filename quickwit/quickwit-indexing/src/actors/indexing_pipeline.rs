@@ -24,8 +24,8 @@ use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use quickwit_actors::{
-    Actor, ActorContext, ActorExitStatus, ActorHandle, Handler, Health, Mailbox, QueueCapacity,
-    Supervisable, HEARTBEAT,
+    Actor, ActorContext, ActorExitStatus, ActorHandle, ForgetRestart, Handler, Health, Mailbox,
+    QueueCapacity, RestartPolicy, Supervisable, SupervisorMetrics, HEARTBEAT,
 };
 use quickwit_common::pubsub::EventBroker;
 use quickwit_common::temp_dir::TempDirectory;
@@ -120,6 +120,8 @@ pub struct IndexingPipeline {
     params: IndexingPipelineParams,
     previous_generations_statistics: IndexingStatistics,
     statistics: IndexingStatistics,
+    // Restarts of the pipeline accounted for by the restart policy.
+    restart_metrics: SupervisorMetrics,
     handles_opt: Option<IndexingPipelineHandles>,
     // Killswitch used for the actors in the pipeline. This is not the supervisor killswitch.
     kill_switch: KillSwitch,
@@ -134,7 +136,10 @@ impl Actor for IndexingPipeline {
     type ObservableState = IndexingStatistics;
 
     fn observable_state(&self) -> Self::ObservableState {
-        self.statistics.clone()
+        self.statistics
+            .clone()
+            .set_num_recent_restarts(self.restart_metrics.num_recent_restarts)
+            .set_is_crash_looping(self.restart_metrics.is_crash_looping)
     }
 
     fn name(&self) -> String {
@@ -167,6 +172,7 @@ impl IndexingPipeline {
             handles_opt: None,
             kill_switch: KillSwitch::default(),
             statistics: IndexingStatistics::default(),
+            restart_metrics: SupervisorMetrics::default(),
             shard_ids: Default::default(),
         }
     }
@@ -289,9 +295,7 @@ impl IndexingPipeline {
             Health::Healthy => {}
             Health::FailureOrUnhealthy => {
                 self.terminate().await;
-                let first_retry_delay = wait_duration_before_retry(0);
-                ctx.schedule_self_msg(first_retry_delay, Spawn { retry_count: 0 })
-                    .await;
+                self.schedule_respawn(ctx).await?;
             }
             Health::Success => {
                 return Err(ActorExitStatus::Success);
@@ -300,6 +304,43 @@ impl IndexingPipeline {
         Ok(())
     }
 
+    /// Schedules a respawn of the pipeline after the backoff of the restart policy, or gives up
+    /// on the pipeline if it was restarted too many times within the restart window.
+    async fn schedule_respawn(&mut self, ctx: &ActorContext<Self>) -> Result<(), ActorExitStatus> {
+        let restart_policy = self.params.restart_policy;
+        let num_recent_restarts = self.restart_metrics.num_recent_restarts;
+
+        let Some(restart_delay) = self.restart_metrics.on_failure(&restart_policy) else {
+            error!(
+                pipeline_id=?self.params.pipeline_id,
+                num_recent_restarts=num_recent_restarts,
+                restart_window=?restart_policy.restart_window,
+                "indexing pipeline is crash-looping, giving up"
+            );
+            return Err(ActorExitStatus::from(anyhow::anyhow!(
+                "indexing pipeline was restarted {} times within {:?}, giving up",
+                num_recent_restarts,
+                restart_policy.restart_window
+            )));
+        };
+        if self.restart_metrics.is_crash_looping {
+            error!(
+                pipeline_id=?self.params.pipeline_id,
+                num_recent_restarts=num_recent_restarts,
+                restart_delay=?restart_delay,
+                "indexing pipeline is crash-looping"
+            );
+        }
+        self.restart_metrics.on_restart();
+        ctx.observe(self);
+
+        ctx.schedule_self_msg(restart_delay, Spawn { retry_count: 0 })
+            .await;
+        ctx.schedule_self_msg(restart_policy.restart_window, ForgetRestart)
+            .await;
+        Ok(())
+    }
+
     // TODO this should return an error saying whether we can retry or not.
     #[instrument(
         name="spawn_pipeline",
@@ -516,6 +557,21 @@ impl Handler<SuperviseLoop> for IndexingPipeline {
     }
 }
 
+#[async_trait]
+impl Handler<ForgetRestart> for IndexingPipeline {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        _forget_restart: ForgetRestart,
+        ctx: &ActorContext<Self>,
+    ) -> Result<(), ActorExitStatus> {
+        self.restart_metrics.forget_restart();
+        ctx.observe(self);
+        Ok(())
+    }
+}
+
 #[async_trait]
 impl Handler<Spawn> for IndexingPipeline {
     type Reply = ();
@@ -536,6 +592,9 @@ impl Handler<Spawn> for IndexingPipeline {
                 info!(error = ?spawn_error, "could not spawn pipeline, index might have been deleted");
                 return Err(ActorExitStatus::Success);
             }
+            // Spawn errors are not accounted for by the restart policy: they are usually caused by
+            // an unavailable metastore, and we do not want to give up on the pipeline during an
+            // outage.
             let retry_delay = wait_duration_before_retry(spawn.retry_count + 1);
             error!(error = ?spawn_error, retry_count = spawn.retry_count, retry_delay = ?retry_delay, "error while spawning indexing pipeline, retrying after some time");
             ctx.schedule_self_msg(
@@ -602,6 +661,7 @@ pub struct IndexingPipelineParams {
     pub ingester_pool: IngesterPool,
     pub queues_dir_path: PathBuf,
 
+    pub restart_policy: RestartPolicy,
     pub event_broker: EventBroker,
 }
 
@@ -618,7 +678,7 @@ mod tests {
     use quickwit_metastore::checkpoint::IndexCheckpointDelta;
     use quickwit_metastore::{IndexMetadata, PublishSplitsRequestExt};
     use quickwit_proto::metastore::{
-        EmptyResponse, IndexMetadataResponse, LastDeleteOpstampResponse, MetastoreError,
+        EmptyResponse, EntityKind, IndexMetadataResponse, LastDeleteOpstampResponse, MetastoreError,
     };
     use quickwit_proto::types::{IndexUid, PipelineUid};
     use quickwit_storage::RamStorage;
@@ -723,6 +783,7 @@ mod tests {
             max_concurrent_split_uploads_index: 4,
             max_concurrent_split_uploads_merge: 5,
             cooperative_indexing_permits: None,
+            restart_policy: RestartPolicy::default(),
             merge_planner_mailbox,
             event_broker,
         };
@@ -746,6 +807,77 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_indexing_pipeline_spawn_errors_do_not_count_as_restarts() {
+        let universe = Universe::with_accelerated_time();
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        let mut num_fails = 3;
+        mock_metastore.expect_index_metadata().returning(move |_| {
+            if num_fails == 0 {
+                return Err(MetastoreError::NotFound(EntityKind::Index {
+                    index_id: "test-index".to_string(),
+                }));
+            }
+            num_fails -= 1;
+            Err(MetastoreError::Connection {
+                message: "MetastoreError Alarm".to_string(),
+            })
+        });
+        let pipeline_id = IndexingPipelineId {
+            index_uid: "test-index:11111111111111111111111111".to_string().into(),
+            source_id: "test-source".to_string(),
+            node_id: "test-node".to_string(),
+            pipeline_uid: PipelineUid::from_u128(0u128),
+        };
+        let source_config = SourceConfig {
+            source_id: "test-source".to_string(),
+            max_num_pipelines_per_indexer: NonZeroUsize::new(1).unwrap(),
+            desired_num_pipelines: NonZeroUsize::new(1).unwrap(),
+            enabled: true,
+            source_params: SourceParams::Void(VoidSourceParams),
+            transform_config: None,
+            input_format: SourceInputFormat::Json,
+        };
+        let storage = Arc::new(RamStorage::default());
+        let split_store = IndexingSplitStore::create_without_local_store_for_test(storage.clone());
+        let (merge_planner_mailbox, _) = universe.create_test_mailbox();
+        let restart_policy = RestartPolicy {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(1),
+            restart_window: Duration::from_secs(3600),
+            max_restarts_opt: Some(2),
+        };
+        let pipeline_params = IndexingPipelineParams {
+            pipeline_id,
+            doc_mapper: Arc::new(default_doc_mapper_for_test()),
+            source_config,
+            source_storage_resolver: StorageResolver::for_test(),
+            indexing_directory: TempDirectory::for_test(),
+            indexing_settings: IndexingSettings::for_test(),
+            ingester_pool: IngesterPool::default(),
+            metastore: MetastoreServiceClient::from(mock_metastore),
+            queues_dir_path: PathBuf::from("./queues"),
+            storage,
+            split_store,
+            merge_policy: default_merge_policy(),
+            max_concurrent_split_uploads_index: 4,
+            max_concurrent_split_uploads_merge: 5,
+            cooperative_indexing_permits: None,
+            restart_policy,
+            merge_planner_mailbox,
+            event_broker: Default::default(),
+        };
+        let pipeline = IndexingPipeline::new(pipeline_params);
+        let (_pipeline_mailbox, pipeline_handle) = universe.spawn_builder().spawn(pipeline);
+        let (pipeline_exit_status, pipeline_statistics) = pipeline_handle.join().await;
+        // The index is eventually reported as deleted, which terminates the pipeline.
+        assert!(pipeline_exit_status.is_success());
+        assert_eq!(pipeline_statistics.num_spawn_attempts, 4);
+        assert_eq!(pipeline_statistics.num_recent_restarts, 0);
+        assert!(!pipeline_statistics.is_crash_looping);
+        universe.quit().await;
+    }
+
     #[tokio::test]
     async fn test_indexing_pipeline_simple() -> anyhow::Result<()> {
         let mut metastore = MetastoreServiceClient::mock();
@@ -822,6 +954,7 @@ mod tests {
             max_concurrent_split_uploads_index: 4,
             max_concurrent_split_uploads_merge: 5,
             cooperative_indexing_permits: None,
+            restart_policy: RestartPolicy::default(),
             merge_planner_mailbox,
             event_broker: Default::default(),
         };
@@ -904,6 +1037,7 @@ mod tests {
             max_concurrent_split_uploads_index: 4,
             max_concurrent_split_uploads_merge: 5,
             cooperative_indexing_permits: None,
+            restart_policy: RestartPolicy::default(),
             merge_planner_mailbox: merge_planner_mailbox.clone(),
             event_broker: Default::default(),
         };
@@ -925,6 +1059,8 @@ mod tests {
                 .process_pending_and_observe()
                 .await;
             if obs.generation == 2 {
+                assert_eq!(obs.num_recent_restarts, 1);
+                assert!(!obs.is_crash_looping);
                 assert_eq!(merge_pipeline_handler.check_health(true), Health::Healthy);
                 universe.quit().await;
                 return;
@@ -1025,6 +1161,7 @@ mod tests {
             max_concurrent_split_uploads_index: 4,
             max_concurrent_split_uploads_merge: 5,
             cooperative_indexing_permits: None,
+            restart_policy: RestartPolicy::default(),
             merge_planner_mailbox,
             event_broker: Default::default(),
         };
@@ -29,14 +29,15 @@ use futures::future::try_join_all;
 use itertools::Itertools;
 use quickwit_actors::{
     Actor, ActorContext, ActorExitStatus, ActorHandle, ActorState, Handler, Healthz, Mailbox,
-    Observation,
+    Observation, RestartPolicy,
 };
 use quickwit_cluster::Cluster;
 use quickwit_common::fs::get_cache_directory_path;
 use quickwit_common::pubsub::EventBroker;
 use quickwit_common::temp_dir;
 use quickwit_config::{
-    build_doc_mapper, IndexConfig, IndexerConfig, SourceConfig, INGEST_API_SOURCE_ID,
+    build_doc_mapper, IndexConfig, IndexerConfig, IndexingSettings, SourceConfig,
+    INGEST_API_SOURCE_ID,
 };
 use quickwit_ingest::{
     DropQueueRequest, IngestApiService, IngesterPool, ListQueuesRequest, QUEUES_DIR_NAME,
@@ -70,6 +71,9 @@ pub struct IndexingServiceCounters {
     pub num_running_pipelines: usize,
     pub num_successful_pipelines: usize,
     pub num_failed_pipelines: usize,
+    /// Number of running pipelines that failed again shortly after being restarted.
+    #[serde(default)]
+    pub num_crash_looping_pipelines: usize,
     pub num_running_merge_pipelines: usize,
     pub num_deleted_queues: usize,
     pub num_delete_queue_failures: usize,
@@ -99,6 +103,9 @@ impl<'a> From<&'a IndexingPipelineId> for MergePipelineId {
 struct MergePipelineHandle {
     mailbox: Mailbox<MergePlanner>,
     handle: ActorHandle<MergePipeline>,
+    // The indexing settings the merge pipeline was spawned with. When they change, the merge
+    // pipeline is respawned so that it picks up the new merge policy.
+    indexing_settings: IndexingSettings,
 }
 
 struct PipelineHandle {
@@ -128,6 +135,7 @@ pub struct IndexingService {
     max_concurrent_split_uploads: usize,
     merge_pipeline_handles: HashMap<MergePipelineId, MergePipelineHandle>,
     cooperative_indexing_permits: Option<Arc<Semaphore>>,
+    pipeline_restart_policy: RestartPolicy,
     event_broker: EventBroker,
 }
 
@@ -171,6 +179,12 @@ impl IndexingService {
         } else {
             None
         };
+        let pipeline_restart_policy = RestartPolicy {
+            initial_backoff: indexer_config.pipeline_restart_policy.initial_backoff(),
+            max_backoff: indexer_config.pipeline_restart_policy.max_backoff(),
+            restart_window: indexer_config.pipeline_restart_policy.restart_window(),
+            max_restarts_opt: indexer_config.pipeline_restart_policy.max_restarts,
+        };
         Ok(IndexingService {
             node_id,
             indexing_root_directory,
@@ -186,6 +200,7 @@ impl IndexingService {
             max_concurrent_split_uploads: indexer_config.max_concurrent_split_uploads,
             merge_pipeline_handles: HashMap::new(),
             cooperative_indexing_permits,
+            pipeline_restart_policy,
             event_broker,
         })
     }
@@ -302,7 +317,11 @@ impl IndexingService {
         };
 
         let merge_planner_mailbox = self
-            .get_or_create_merge_pipeline(merge_pipeline_params, ctx)
+            .get_or_create_merge_pipeline(
+                merge_pipeline_params,
+                index_config.indexing_settings.clone(),
+                ctx,
+            )
             .await?;
 
         // The concurrent uploads budget is split in 2: 1/2 for the indexing pipeline, 1/2 for the
@@ -333,6 +352,7 @@ impl IndexingService {
             queues_dir_path: self.queue_dir_path.clone(),
             source_storage_resolver: self.storage_resolver.clone(),
 
+            restart_policy: self.pipeline_restart_policy,
             event_broker: self.event_broker.clone(),
         };
         let pipeline = IndexingPipeline::new(pipeline_params);
@@ -381,11 +401,11 @@ impl IndexingService {
                         false
                     }
                     ActorState::Failure => {
-                        // This should never happen: Indexing Pipelines are not supposed to fail,
-                        // and are themselves in charge of supervising the pipeline actors.
+                        // Indexing pipelines supervise their own actors and only fail once they
+                        // exhausted the restarts allowed by the pipeline restart policy.
                         error!(
                             pipeline_uid=%pipeline_uid,
-                            "Indexing pipeline exited with failure. This should never happen."
+                            "Indexing pipeline exited with failure."
                         );
                         self.counters.num_failed_pipelines += 1;
                         self.counters.num_running_pipelines -= 1;
@@ -439,19 +459,37 @@ impl IndexingService {
         self.cluster
             .update_self_node_pipeline_metrics(&pipeline_metrics)
             .await;
+
+        self.counters.num_crash_looping_pipelines = self
+            .indexing_pipelines
+            .values()
+            .filter(|pipeline_handle| pipeline_handle.handle.last_observation().is_crash_looping)
+            .count();
         Ok(())
     }
 
     async fn get_or_create_merge_pipeline(
         &mut self,
         merge_pipeline_params: MergePipelineParams,
+        indexing_settings: IndexingSettings,
         ctx: &ActorContext<Self>,
     ) -> Result<Mailbox<MergePlanner>, IndexingError> {
         let merge_pipeline_id = MergePipelineId::from(&merge_pipeline_params.pipeline_id);
         if let Some(merge_pipeline_mailbox_handle) =
             self.merge_pipeline_handles.get(&merge_pipeline_id)
         {
-            return Ok(merge_pipeline_mailbox_handle.mailbox.clone());
+            if merge_pipeline_mailbox_handle.indexing_settings == indexing_settings {
+                return Ok(merge_pipeline_mailbox_handle.mailbox.clone());
+            }
+            // The indexing settings of the index were updated since the merge pipeline was
+            // spawned, so we replace it with a new one.
+            info!(
+                index_id=%merge_pipeline_id.index_uid.index_id(),
+                source_id=%merge_pipeline_id.source_id,
+                "indexing settings have changed, respawning merge pipeline"
+            );
+            let merge_pipeline_handle = self.detach_merge_pipeline(&merge_pipeline_id).await?;
+            merge_pipeline_handle.kill().await;
         }
         let merge_pipeline = MergePipeline::new(merge_pipeline_params, ctx.spawn_ctx());
         let merge_planner_mailbox = merge_pipeline.merge_planner_mailbox().clone();
@@ -459,6 +497,7 @@ impl IndexingService {
         let merge_pipeline_mailbox_handle = MergePipelineHandle {
             mailbox: merge_planner_mailbox.clone(),
             handle: pipeline_handle,
+            indexing_settings,
         };
         self.merge_pipeline_handles
             .insert(merge_pipeline_id, merge_pipeline_mailbox_handle);
@@ -868,12 +907,12 @@ mod tests {
     use quickwit_ingest::{init_ingest_api, CreateQueueIfNotExistsRequest};
     use quickwit_metastore::{
         metastore_for_test, AddSourceRequestExt, CreateIndexRequestExt,
-        ListIndexesMetadataResponseExt,
+        ListIndexesMetadataResponseExt, UpdateIndexRequestExt,
     };
     use quickwit_proto::indexing::IndexingTask;
     use quickwit_proto::metastore::{
         AddSourceRequest, CreateIndexRequest, DeleteIndexRequest, IndexMetadataResponse,
-        ListIndexesMetadataResponse,
+        ListIndexesMetadataResponse, UpdateIndexRequest,
     };
 
     use super::*;
@@ -1298,6 +1337,103 @@ mod tests {
         universe.assert_quit().await;
     }
 
+    #[tokio::test]
+    async fn test_indexing_service_respawn_merge_pipeline_on_indexing_settings_update() {
+        quickwit_common::setup_logging_for_tests();
+        let transport = ChannelTransport::default();
+        let cluster = create_cluster_for_test(Vec::new(), &["indexer"], &transport, true)
+            .await
+            .unwrap();
+        let mut metastore = metastore_for_test();
+
+        let index_id = append_random_suffix("test-indexing-service");
+        let index_uri = format!("ram:///indexes/{index_id}");
+        let index_config = IndexConfig::for_test(&index_id, &index_uri);
+
+        let create_index_request =
+            CreateIndexRequest::try_from_index_config(index_config.clone()).unwrap();
+        let index_uid: IndexUid = metastore
+            .create_index(create_index_request)
+            .await
+            .unwrap()
+            .index_uid
+            .into();
+        let source_config = SourceConfig::for_test("test-source", SourceParams::void());
+        let add_source_request =
+            AddSourceRequest::try_from_source_config(index_uid.clone(), source_config).unwrap();
+        metastore.add_source(add_source_request).await.unwrap();
+
+        let universe = Universe::new();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let (indexing_service, indexing_service_handle) = spawn_indexing_service_for_test(
+            temp_dir.path(),
+            &universe,
+            metastore.clone(),
+            cluster.clone(),
+        )
+        .await;
+
+        let indexing_tasks = vec![IndexingTask {
+            index_uid: index_uid.to_string(),
+            source_id: "test-source".to_string(),
+            shard_ids: Vec::new(),
+            pipeline_uid: Some(PipelineUid::from_u128(0u128)),
+        }];
+        indexing_service
+            .ask_for_res(ApplyIndexingPlanRequest { indexing_tasks })
+            .await
+            .unwrap();
+        let observation = indexing_service_handle.observe().await;
+        assert_eq!(observation.num_running_pipelines, 1);
+        assert_eq!(observation.num_running_merge_pipelines, 1);
+
+        let merge_pipeline_id = universe
+            .get_one::<MergePipeline>()
+            .unwrap()
+            .actor_instance_id()
+            .to_string();
+
+        let indexing_settings = IndexingSettings {
+            commit_timeout_secs: 5,
+            ..index_config.indexing_settings.clone()
+        };
+        let update_index_request = UpdateIndexRequest::try_from_updates(
+            index_uid.clone(),
+            &index_config.doc_mapping,
+            &index_config.search_settings,
+            &index_config.retention_policy,
+            &indexing_settings,
+        )
+        .unwrap();
+        metastore.update_index(update_index_request).await.unwrap();
+
+        // The control plane restarts the pipeline by assigning it a new pipeline UID.
+        let indexing_tasks = vec![IndexingTask {
+            index_uid: index_uid.to_string(),
+            source_id: "test-source".to_string(),
+            shard_ids: Vec::new(),
+            pipeline_uid: Some(PipelineUid::from_u128(1u128)),
+        }];
+        indexing_service
+            .ask_for_res(ApplyIndexingPlanRequest { indexing_tasks })
+            .await
+            .unwrap();
+        let observation = indexing_service_handle.observe().await;
+        assert_eq!(observation.num_running_pipelines, 1);
+        assert_eq!(observation.num_running_merge_pipelines, 1);
+
+        let merge_pipeline_ids: Vec<String> = universe
+            .get::<MergePipeline>()
+            .iter()
+            .map(|mailbox| mailbox.actor_instance_id().to_string())
+            .collect();
+        assert!(merge_pipeline_ids
+            .iter()
+            .any(|new_merge_pipeline_id| *new_merge_pipeline_id != merge_pipeline_id));
+
+        universe.quit().await;
+    }
+
     #[tokio::test]
     async fn test_indexing_service_shut_down_merge_pipeline_when_no_indexing_pipeline() {
         quickwit_common::setup_logging_for_tests();
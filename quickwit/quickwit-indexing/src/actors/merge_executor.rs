@@ -23,7 +23,7 @@ use std::path::Path;
 use std::sync::Arc;
 use std::time::Instant;
 
-use anyhow::{anyhow, Context};
+use anyhow::{anyhow, bail, Context};
 use async_trait::async_trait;
 use fail::fail_point;
 use itertools::Itertools;
@@ -43,6 +43,7 @@ use quickwit_proto::types::PipelineUid;
 use quickwit_query::get_quickwit_fastfield_normalizer_manager;
 use quickwit_query::query_ast::QueryAst;
 use tantivy::directory::{Advice, DirectoryClone, MmapDirectory, RamDirectory};
+use tantivy::schema::Schema;
 use tantivy::tokenizer::TokenizerManager;
 use tantivy::{DateTime, Directory, Index, IndexMeta, IndexWriter, SegmentId, SegmentReader};
 use tokio::runtime::Handle;
@@ -148,14 +149,40 @@ impl Handler<MergeScratch> for MergeExecutor {
     }
 }
 
+/// Combines the metas of the splits to merge into a single meta.
+///
+/// Splits created with different versions of the doc mapping of an index have different schemas.
+/// Doc mapping updates only append fields to the schema, so the schemas of the older splits are
+/// prefixes of the schema of the most recent split, which is used for the merged split.
 fn combine_index_meta(mut index_metas: Vec<IndexMeta>) -> anyhow::Result<IndexMeta> {
-    let mut union_index_meta = index_metas.pop().with_context(|| "only one IndexMeta")?;
+    let union_index_meta_position = index_metas
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, index_meta)| index_meta.schema.fields().count())
+        .map(|(position, _)| position)
+        .with_context(|| "only one IndexMeta")?;
+    let mut union_index_meta = index_metas.swap_remove(union_index_meta_position);
     for index_meta in index_metas {
+        check_schema_is_prefix(&index_meta.schema, &union_index_meta.schema)?;
         union_index_meta.segments.extend(index_meta.segments);
     }
     Ok(union_index_meta)
 }
 
+fn check_schema_is_prefix(schema: &Schema, union_schema: &Schema) -> anyhow::Result<()> {
+    for ((_, field_entry), (_, union_field_entry)) in schema.fields().zip(union_schema.fields()) {
+        if field_entry != union_field_entry {
+            bail!(
+                "failed to merge splits with incompatible schemas: field `{}` differs from field \
+                 `{}`",
+                field_entry.name(),
+                union_field_entry.name()
+            );
+        }
+    }
+    Ok(())
+}
+
 fn open_split_directories(
     // Directories containing the splits to merge
     tantivy_dirs: &[Box<dyn Directory>],
@@ -259,6 +286,7 @@ pub fn merge_split_attrs(
         uncompressed_docs_size_in_bytes,
         delete_opstamp,
         num_merge_ops: max_merge_ops(splits) + 1,
+        doc_mapping_version: max_doc_mapping_version(splits),
     }
 }
 
@@ -270,6 +298,14 @@ fn max_merge_ops(splits: &[SplitMetadata]) -> usize {
         .unwrap_or(0)
 }
 
+fn max_doc_mapping_version(splits: &[SplitMetadata]) -> u64 {
+    splits
+        .iter()
+        .map(|split| split.doc_mapping_version)
+        .max()
+        .unwrap_or(0)
+}
+
 impl MergeExecutor {
     pub fn new(
         pipeline_id: IndexingPipelineId,
@@ -446,6 +482,7 @@ impl MergeExecutor {
                 uncompressed_docs_size_in_bytes,
                 delete_opstamp: last_delete_opstamp,
                 num_merge_ops: split.num_merge_ops,
+                doc_mapping_version: split.doc_mapping_version,
             },
             index: merged_index,
             split_scratch_directory: merge_scratch_directory,
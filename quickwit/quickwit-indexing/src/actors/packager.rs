@@ -527,6 +527,7 @@ mod tests {
                 replaced_split_ids: Vec::new(),
                 delete_opstamp: 0,
                 num_merge_ops: 0,
+                doc_mapping_version: 0,
             },
             index,
             split_scratch_directory,
@@ -562,6 +562,7 @@ mod tests {
                         split_id: "test-split".to_string(),
                         delete_opstamp: 10,
                         num_merge_ops: 0,
+                        doc_mapping_version: 0,
                     },
                     serialized_split_fields: Vec::new(),
                     split_scratch_directory,
@@ -674,6 +675,7 @@ mod tests {
                 ],
                 delete_opstamp: 0,
                 num_merge_ops: 0,
+                doc_mapping_version: 0,
             },
             serialized_split_fields: Vec::new(),
             split_scratch_directory: split_scratch_directory_1,
@@ -698,6 +700,7 @@ mod tests {
                 ],
                 delete_opstamp: 0,
                 num_merge_ops: 0,
+                doc_mapping_version: 0,
             },
             serialized_split_fields: Vec::new(),
             split_scratch_directory: split_scratch_directory_2,
@@ -816,6 +819,7 @@ mod tests {
                         split_id: "test-split".to_string(),
                         delete_opstamp: 10,
                         num_merge_ops: 0,
+                        doc_mapping_version: 0,
                     },
                     serialized_split_fields: Vec::new(),
                     split_scratch_directory,
@@ -995,6 +999,7 @@ mod tests {
                         split_id: SPLIT_ULID_STR.to_string(),
                         delete_opstamp: 10,
                         num_merge_ops: 0,
+                        doc_mapping_version: 0,
                     },
                     serialized_split_fields: Vec::new(),
                     split_scratch_directory,
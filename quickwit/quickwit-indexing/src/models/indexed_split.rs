@@ -81,6 +81,7 @@ impl IndexedSplitBuilder {
         pipeline_id: IndexingPipelineId,
         partition_id: u64,
         last_delete_opstamp: u64,
+        doc_mapping_version: u64,
         scratch_directory: TempDirectory,
         index_builder: IndexBuilder,
         io_controls: IoControls,
@@ -110,6 +111,7 @@ impl IndexedSplitBuilder {
                 time_range: None,
                 delete_opstamp: last_delete_opstamp,
                 num_merge_ops: 0,
+                doc_mapping_version,
             },
             index_writer,
             split_scratch_directory,
@@ -130,6 +132,7 @@ impl IndexedSplitBuilder {
             uncompressed_docs_size_in_bytes=%self.split_attrs.uncompressed_docs_size_in_bytes,
             delete_opstamp=%self.split_attrs.delete_opstamp,
             num_merge_ops=%self.split_attrs.num_merge_ops,
+            doc_mapping_version=%self.split_attrs.doc_mapping_version,
         )
     )]
     pub fn finalize(self) -> anyhow::Result<IndexedSplit> {
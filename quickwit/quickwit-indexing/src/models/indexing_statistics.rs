@@ -51,6 +51,10 @@ pub struct IndexingStatistics {
     pub generation: usize,
     /// Number of successive pipeline spawn attempts.
     pub num_spawn_attempts: usize,
+    /// Number of pipeline restarts that occurred within the restart window.
+    pub num_recent_restarts: usize,
+    /// True if the pipeline failed again shortly after being restarted.
+    pub is_crash_looping: bool,
     // Pipeline metrics.
     pub pipeline_metrics_opt: Option<PipelineMetrics>,
     // List of shard ids.
@@ -90,4 +94,14 @@ impl IndexingStatistics {
         self.generation = generation;
         self
     }
+
+    pub fn set_num_recent_restarts(mut self, num_recent_restarts: usize) -> Self {
+        self.num_recent_restarts = num_recent_restarts;
+        self
+    }
+
+    pub fn set_is_crash_looping(mut self, is_crash_looping: bool) -> Self {
+        self.is_crash_looping = is_crash_looping;
+        self
+    }
 }
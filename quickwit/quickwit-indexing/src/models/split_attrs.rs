@@ -63,6 +63,9 @@ pub struct SplitAttrs {
 
     // Number of merge operation the split has been through so far.
     pub num_merge_ops: usize,
+
+    /// Version of the doc mapping used to create the split.
+    pub doc_mapping_version: u64,
 }
 
 impl fmt::Debug for SplitAttrs {
@@ -78,6 +81,7 @@ impl fmt::Debug for SplitAttrs {
             )
             .field("num_docs", &self.num_docs)
             .field("num_merge_ops", &self.num_merge_ops)
+            .field("doc_mapping_version", &self.doc_mapping_version)
             .finish()
     }
 }
@@ -109,5 +113,6 @@ pub fn create_split_metadata(
         footer_offsets,
         delete_opstamp: split_attrs.delete_opstamp,
         num_merge_ops: split_attrs.num_merge_ops,
+        doc_mapping_version: split_attrs.doc_mapping_version,
     }
 }
@@ -17,22 +17,27 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::VecDeque;
+use std::ffi::OsStr;
 use std::fmt;
-use std::ops::Range;
 use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Context;
+use async_compression::tokio::bufread::{GzipDecoder, ZstdDecoder};
 use async_trait::async_trait;
 use bytes::Bytes;
+use csv_core::ReadRecordResult;
 use quickwit_actors::{ActorExitStatus, Mailbox};
 use quickwit_common::uri::Uri;
-use quickwit_config::FileSourceParams;
-use quickwit_metastore::checkpoint::{PartitionId, SourceCheckpoint};
+use quickwit_config::{FileSourceParams, SourceInputFormat};
+use quickwit_metastore::checkpoint::{PartitionId, SourceCheckpoint, SourceCheckpointDelta};
 use quickwit_proto::types::Position;
+use quickwit_storage::Storage;
 use serde::Serialize;
-use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use serde_json::Value as JsonValue;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, BufReader};
 use tracing::info;
 
 use crate::actors::DocProcessor;
@@ -42,6 +47,395 @@ use crate::source::{Source, SourceContext, SourceRuntimeArgs, TypedSourceFactory
 /// Number of bytes after which a new batch is cut.
 pub(crate) const BATCH_NUM_BYTES_LIMIT: u64 = 500_000u64;
 
+const GZIP_MAGIC_BYTES: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC_BYTES: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Compression of the file read by the file source.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum FileCompression {
+    Uncompressed,
+    Gzip,
+    Zstd,
+}
+
+impl FileCompression {
+    fn from_extension(filepath: &Path) -> Option<Self> {
+        match filepath.extension().and_then(OsStr::to_str)? {
+            "gz" | "gzip" => Some(Self::Gzip),
+            "zst" | "zstd" => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+
+    fn from_magic_bytes(first_bytes: &[u8]) -> Self {
+        if first_bytes.starts_with(&GZIP_MAGIC_BYTES) {
+            Self::Gzip
+        } else if first_bytes.starts_with(&ZSTD_MAGIC_BYTES) {
+            Self::Zstd
+        } else {
+            Self::Uncompressed
+        }
+    }
+
+    fn decompress(
+        self,
+        reader: Box<dyn AsyncRead + Send + Unpin>,
+    ) -> Box<dyn AsyncRead + Send + Unpin> {
+        match self {
+            Self::Uncompressed => reader,
+            Self::Gzip => {
+                let mut decoder = GzipDecoder::new(BufReader::new(reader));
+                decoder.multiple_members(true);
+                Box::new(decoder)
+            }
+            Self::Zstd => {
+                let mut decoder = ZstdDecoder::new(BufReader::new(reader));
+                decoder.multiple_members(true);
+                Box::new(decoder)
+            }
+        }
+    }
+}
+
+/// Fields of a CSV record, as raw bytes.
+type CsvRecord = Vec<Vec<u8>>;
+
+/// Incremental CSV parser. The parser is fed the file line by line but keeps its state across
+/// lines, so that quoted values spanning several lines end up in a single record.
+struct CsvRecordParser {
+    reader: csv_core::Reader,
+    output: Vec<u8>,
+    output_len: usize,
+    ends: Vec<usize>,
+    ends_len: usize,
+}
+
+impl Default for CsvRecordParser {
+    fn default() -> Self {
+        Self {
+            reader: csv_core::Reader::new(),
+            output: vec![0; 1024],
+            output_len: 0,
+            ends: vec![0; 32],
+            ends_len: 0,
+        }
+    }
+}
+
+impl CsvRecordParser {
+    /// Returns whether the parser holds the beginning of a record that is not complete yet.
+    fn has_partial_record(&self) -> bool {
+        self.output_len > 0 || self.ends_len > 0
+    }
+
+    /// Feeds `input` to the parser and appends the records it completes to `records`. An empty
+    /// input marks the end of the file and flushes the last record.
+    fn feed(&mut self, mut input: &[u8], records: &mut VecDeque<CsvRecord>) {
+        let is_eof = input.is_empty();
+        loop {
+            let (result, num_bytes_read, num_bytes_written, num_ends_written) =
+                self.reader.read_record(
+                    input,
+                    &mut self.output[self.output_len..],
+                    &mut self.ends[self.ends_len..],
+                );
+            input = &input[num_bytes_read..];
+            self.output_len += num_bytes_written;
+            self.ends_len += num_ends_written;
+
+            match result {
+                ReadRecordResult::InputEmpty | ReadRecordResult::End => return,
+                ReadRecordResult::OutputFull => {
+                    let new_len = self.output.len() * 2;
+                    self.output.resize(new_len, 0);
+                }
+                ReadRecordResult::OutputEndsFull => {
+                    let new_len = self.ends.len() * 2;
+                    self.ends.resize(new_len, 0);
+                }
+                ReadRecordResult::Record => {
+                    records.push_back(self.take_record());
+                    // Feeding an empty input would signal the end of the file to the parser.
+                    if input.is_empty() && !is_eof {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    fn take_record(&mut self) -> CsvRecord {
+        let mut record = Vec::with_capacity(self.ends_len);
+        let mut start = 0;
+
+        for &end in &self.ends[..self.ends_len] {
+            record.push(self.output[start..end].to_vec());
+            start = end;
+        }
+        self.output_len = 0;
+        self.ends_len = 0;
+        record
+    }
+}
+
+/// Validates the header of a CSV file and returns its field names.
+fn parse_csv_header(record: CsvRecord) -> anyhow::Result<Vec<String>> {
+    let mut field_names: Vec<String> = Vec::with_capacity(record.len());
+
+    for field in record {
+        let field_name = String::from_utf8(field).context("CSV header is not valid UTF-8")?;
+
+        if field_name.is_empty() {
+            anyhow::bail!("CSV header contains an empty field name");
+        }
+        if field_names.contains(&field_name) {
+            anyhow::bail!("CSV header contains field `{field_name}` more than once");
+        }
+        field_names.push(field_name);
+    }
+    Ok(field_names)
+}
+
+/// Converts a CSV record into a JSON object keyed by the field names of the header. Empty values
+/// are omitted and the other values are left as strings: the doc processor converts them to the
+/// types of the doc mapping. Records that do not have as many fields as the header are converted
+/// into a JSON array, which the doc processor rejects.
+fn csv_record_to_json_doc(field_names: &[String], record: CsvRecord) -> Bytes {
+    let to_json_string =
+        |value: &[u8]| JsonValue::String(String::from_utf8_lossy(value).into_owned());
+
+    let json_value = if record.len() == field_names.len() {
+        let json_obj = field_names
+            .iter()
+            .zip(record)
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(field_name, value)| (field_name.clone(), to_json_string(&value)))
+            .collect();
+        JsonValue::Object(json_obj)
+    } else {
+        JsonValue::Array(record.iter().map(|value| to_json_string(value)).collect())
+    };
+    serde_json::to_vec(&json_value)
+        .expect("JSON value should be serializable")
+        .into()
+}
+
+/// State of the CSV parsing of a file.
+#[derive(Default)]
+struct CsvState {
+    parser: CsvRecordParser,
+    field_names_opt: Option<Vec<String>>,
+    // Records parsed but not emitted yet.
+    records: VecDeque<CsvRecord>,
+}
+
+impl CsvState {
+    /// Reads the header of the CSV file and returns the number of bytes read. The field names
+    /// are left unset if the file is empty.
+    async fn read_header(
+        &mut self,
+        reader: &mut (impl AsyncBufRead + Unpin),
+    ) -> anyhow::Result<u64> {
+        let mut num_bytes = 0;
+
+        while self.records.is_empty() {
+            let line = read_line(reader).await?;
+            num_bytes += line.len() as u64;
+            self.parser.feed(&line, &mut self.records);
+
+            if line.is_empty() {
+                break;
+            }
+        }
+        if let Some(header_record) = self.records.pop_front() {
+            self.field_names_opt = Some(parse_csv_header(header_record)?);
+        }
+        Ok(num_bytes)
+    }
+
+    /// Reads the header of a CSV file that is resumed from a checkpoint. The records that follow
+    /// the header were already indexed and are discarded.
+    async fn read_header_on_resume(
+        &mut self,
+        reader: &mut (impl AsyncBufRead + Unpin),
+    ) -> anyhow::Result<u64> {
+        let num_bytes = self.read_header(reader).await?;
+        self.parser = CsvRecordParser::default();
+        self.records.clear();
+        Ok(num_bytes)
+    }
+}
+
+/// Documents read from a file by [`DocFileReader::read_batch`].
+#[derive(Debug, Default)]
+pub(crate) struct LinesBatch {
+    /// Documents to send to the doc processor. CSV records are converted into JSON objects.
+    pub docs: Vec<Bytes>,
+    /// Number of bytes read from the file, including the CSV header if it was read.
+    pub num_bytes: u64,
+    /// Number of lines read from the file, or number of records for CSV files.
+    pub num_lines: u64,
+    pub reached_eof: bool,
+}
+
+/// Reads the documents of a file, one document per line or per CSV record, and decompresses the
+/// file on the fly if needed.
+pub(crate) struct DocFileReader {
+    reader: BufReader<Box<dyn AsyncRead + Send + Unpin>>,
+    csv_state_opt: Option<CsvState>,
+}
+
+impl DocFileReader {
+    /// Reads the documents from the standard input.
+    pub async fn from_stdin(is_csv: bool) -> anyhow::Result<Self> {
+        let mut stdin_reader = BufReader::new(tokio::io::stdin());
+        let compression = FileCompression::from_magic_bytes(stdin_reader.fill_buf().await?);
+        Ok(Self {
+            reader: BufReader::new(compression.decompress(Box::new(stdin_reader))),
+            csv_state_opt: is_csv.then(CsvState::default),
+        })
+    }
+
+    /// Opens the file located at `path` in `storage` and skips its first `offset` bytes. The
+    /// offset is expressed in decompressed bytes for compressed files.
+    pub async fn open(
+        storage: &dyn Storage,
+        path: &Path,
+        offset: usize,
+        is_csv: bool,
+    ) -> anyhow::Result<Self> {
+        let file_num_bytes = storage.file_num_bytes(path).await?;
+        let file_size = usize::try_from(file_num_bytes).with_context(|| {
+            format!(
+                "file `{}` is too large to be read ({file_num_bytes} bytes)",
+                path.display()
+            )
+        })?;
+        let compression = if let Some(compression) = FileCompression::from_extension(path) {
+            compression
+        } else if file_size == 0 {
+            FileCompression::Uncompressed
+        } else {
+            let first_bytes = storage
+                .get_slice(path, 0..file_size.min(ZSTD_MAGIC_BYTES.len()))
+                .await?;
+            FileCompression::from_magic_bytes(first_bytes.as_slice())
+        };
+        let mut csv_state_opt = is_csv.then(CsvState::default);
+
+        let reader = if compression == FileCompression::Uncompressed {
+            if let Some(csv_state) = csv_state_opt.as_mut().filter(|_| offset > 0) {
+                let header_stream = storage.get_slice_stream(path, 0..file_size).await?;
+                let mut header_reader = BufReader::new(header_stream);
+                csv_state.read_header_on_resume(&mut header_reader).await?;
+            }
+            let file_stream = storage.get_slice_stream(path, offset..file_size).await?;
+            BufReader::new(file_stream)
+        } else {
+            // Offsets are expressed in decompressed bytes: we need to decompress the file from its
+            // beginning and skip the bytes that were already indexed.
+            let file_stream = storage.get_slice_stream(path, 0..file_size).await?;
+            let mut reader = BufReader::new(compression.decompress(file_stream));
+            let mut num_bytes_to_skip = offset as u64;
+            if let Some(csv_state) = csv_state_opt.as_mut().filter(|_| offset > 0) {
+                let num_header_bytes = csv_state.read_header_on_resume(&mut reader).await?;
+                num_bytes_to_skip = num_bytes_to_skip.saturating_sub(num_header_bytes);
+            }
+            let num_bytes_skipped = tokio::io::copy(
+                &mut (&mut reader).take(num_bytes_to_skip),
+                &mut tokio::io::sink(),
+            )
+            .await?;
+            if num_bytes_skipped < num_bytes_to_skip {
+                anyhow::bail!(
+                    "file `{}` is shorter than its checkpoint position ({offset} bytes)",
+                    path.display()
+                );
+            }
+            reader
+        };
+        Ok(Self {
+            reader,
+            csv_state_opt,
+        })
+    }
+
+    /// Reads lines until `num_bytes_limit` bytes have been read or the end of the file is
+    /// reached.
+    pub async fn read_batch(
+        &mut self,
+        num_bytes_limit: u64,
+        ctx: &SourceContext,
+    ) -> anyhow::Result<LinesBatch> {
+        if self.csv_state_opt.is_some() {
+            return self.read_csv_batch(num_bytes_limit, ctx).await;
+        }
+        let mut lines_batch = LinesBatch::default();
+
+        while lines_batch.num_bytes < num_bytes_limit {
+            // guard the zone in case of slow read, such as reading from someone
+            // typing to stdin
+            let doc_line = ctx.protect_future(read_line(&mut self.reader)).await?;
+            if doc_line.is_empty() {
+                lines_batch.reached_eof = true;
+                break;
+            }
+            lines_batch.num_bytes += doc_line.len() as u64;
+            lines_batch.num_lines += 1;
+            lines_batch.docs.push(doc_line);
+        }
+        Ok(lines_batch)
+    }
+
+    /// Reads CSV records until `num_bytes_limit` bytes have been read or the end of the file is
+    /// reached. Batches are only cut at record boundaries. Fails if the header of the file is
+    /// invalid.
+    async fn read_csv_batch(
+        &mut self,
+        num_bytes_limit: u64,
+        ctx: &SourceContext,
+    ) -> anyhow::Result<LinesBatch> {
+        let csv_state = self
+            .csv_state_opt
+            .as_mut()
+            .expect("the file should be a CSV file");
+        let mut lines_batch = LinesBatch::default();
+
+        if csv_state.field_names_opt.is_none() {
+            lines_batch.num_bytes += ctx
+                .protect_future(csv_state.read_header(&mut self.reader))
+                .await?;
+
+            if csv_state.field_names_opt.is_none() {
+                lines_batch.reached_eof = true;
+                return Ok(lines_batch);
+            }
+        }
+        while lines_batch.num_bytes < num_bytes_limit || csv_state.parser.has_partial_record() {
+            let line = ctx.protect_future(read_line(&mut self.reader)).await?;
+            lines_batch.num_bytes += line.len() as u64;
+            csv_state.parser.feed(&line, &mut csv_state.records);
+
+            if line.is_empty() {
+                lines_batch.reached_eof = true;
+                break;
+            }
+        }
+        let field_names = csv_state
+            .field_names_opt
+            .as_deref()
+            .expect("the CSV header should have been read");
+
+        for record in csv_state.records.drain(..) {
+            lines_batch.num_lines += 1;
+            lines_batch
+                .docs
+                .push(csv_record_to_json_doc(field_names, record));
+        }
+        Ok(lines_batch)
+    }
+}
+
 #[derive(Default, Clone, Debug, Eq, PartialEq, Serialize)]
 pub struct FileSourceCounters {
     pub previous_offset: u64,
@@ -53,7 +447,7 @@ pub struct FileSource {
     source_id: String,
     params: FileSourceParams,
     counters: FileSourceCounters,
-    reader: BufReader<Box<dyn AsyncRead + Send + Unpin>>,
+    reader: DocFileReader,
 }
 
 impl fmt::Debug for FileSource {
@@ -70,34 +464,20 @@ impl Source for FileSource {
         ctx: &SourceContext,
     ) -> Result<Duration, ActorExitStatus> {
         // We collect batches of documents before sending them to the indexer.
-        let limit_num_bytes = self.counters.previous_offset + BATCH_NUM_BYTES_LIMIT;
-        let mut reached_eof = false;
-        let mut doc_batch = RawDocBatch::default();
-        while self.counters.current_offset < limit_num_bytes {
-            let mut doc_line = String::new();
-            // guard the zone in case of slow read, such as reading from someone
-            // typing to stdin
-            let num_bytes = ctx
-                .protect_future(self.reader.read_line(&mut doc_line))
-                .await
-                .map_err(anyhow::Error::from)?;
-            if num_bytes == 0 {
-                reached_eof = true;
-                break;
-            }
-            doc_batch.docs.push(Bytes::from(doc_line));
-            self.counters.current_offset += num_bytes as u64;
-            self.counters.num_lines_processed += 1;
-        }
-        if !doc_batch.docs.is_empty() {
+        let lines_batch = self.reader.read_batch(BATCH_NUM_BYTES_LIMIT, ctx).await?;
+        self.counters.current_offset += lines_batch.num_bytes;
+        self.counters.num_lines_processed += lines_batch.num_lines;
+
+        if !lines_batch.docs.is_empty() {
+            let mut checkpoint_delta = SourceCheckpointDelta::default();
+
             if let Some(filepath) = &self.params.filepath {
                 let filepath_str = filepath
                     .to_str()
                     .context("path is invalid utf-8")?
                     .to_string();
                 let partition_id = PartitionId::from(filepath_str);
-                doc_batch
-                    .checkpoint_delta
+                checkpoint_delta
                     .record_partition_delta(
                         partition_id,
                         Position::offset(self.counters.previous_offset),
@@ -106,9 +486,10 @@ impl Source for FileSource {
                     .unwrap();
             }
             self.counters.previous_offset = self.counters.current_offset;
+            let doc_batch = RawDocBatch::new(lines_batch.docs, checkpoint_delta, false);
             ctx.send_message(doc_processor_mailbox, doc_batch).await?;
         }
-        if reached_eof {
+        if lines_batch.reached_eof {
             info!("EOF");
             ctx.send_exit_with_success(doc_processor_mailbox).await?;
             return Err(ActorExitStatus::Success);
@@ -138,8 +519,9 @@ impl TypedSourceFactory for FileSourceFactory {
         params: FileSourceParams,
         checkpoint: SourceCheckpoint,
     ) -> anyhow::Result<FileSource> {
+        let is_csv = ctx.source_config.input_format == SourceInputFormat::Csv;
         let mut offset = 0;
-        let reader: Box<dyn AsyncRead + Send + Unpin> = if let Some(filepath) = &params.filepath {
+        let reader = if let Some(filepath) = &params.filepath {
             let partition_id = PartitionId::from(filepath.to_string_lossy().to_string());
             offset = checkpoint
                 .position_for_partition(&partition_id)
@@ -151,19 +533,10 @@ impl TypedSourceFactory for FileSourceFactory {
                 .unwrap_or(0);
             let (dir_uri, file_name) = dir_and_filename(filepath)?;
             let storage = ctx.storage_resolver.resolve(&dir_uri).await?;
-            let file_size = storage.file_num_bytes(file_name).await?.try_into().unwrap();
-            storage
-                .get_slice_stream(
-                    file_name,
-                    Range {
-                        start: offset,
-                        end: file_size,
-                    },
-                )
-                .await?
+            DocFileReader::open(&*storage, file_name, offset, is_csv).await?
         } else {
             // We cannot use the checkpoint.
-            Box::new(tokio::io::stdin())
+            DocFileReader::from_stdin(is_csv).await?
         };
         let file_source = FileSource {
             source_id: ctx.source_id().to_string(),
@@ -172,13 +545,19 @@ impl TypedSourceFactory for FileSourceFactory {
                 current_offset: offset as u64,
                 num_lines_processed: 0,
             },
-            reader: BufReader::new(reader),
+            reader,
             params,
         };
         Ok(file_source)
     }
 }
 
+async fn read_line<R: AsyncBufRead + Unpin>(reader: &mut R) -> std::io::Result<Bytes> {
+    let mut line = String::new();
+    reader.read_line(&mut line).await?;
+    Ok(Bytes::from(line))
+}
+
 pub(crate) fn dir_and_filename(filepath: &Path) -> anyhow::Result<(Uri, &Path)> {
     let dir_uri: Uri = filepath
         .parent()
@@ -411,4 +790,326 @@ mod tests {
         let indexer_messages: Vec<RawDocBatch> = doc_processor_inbox.drain_for_test_typed();
         assert!(&indexer_messages[0].docs[0].starts_with(b"2\n"));
     }
+
+    async fn gzip_encode(payload: &[u8]) -> Vec<u8> {
+        use async_compression::tokio::write::GzipEncoder;
+        use tokio::io::AsyncWriteExt;
+
+        let mut encoder = GzipEncoder::new(Vec::new());
+        encoder.write_all(payload).await.unwrap();
+        encoder.shutdown().await.unwrap();
+        encoder.into_inner()
+    }
+
+    async fn run_file_source(
+        filepath: &Path,
+        input_format: SourceInputFormat,
+        checkpoint: SourceCheckpoint,
+    ) -> (serde_json::Value, Vec<RawDocBatch>) {
+        let universe = Universe::with_accelerated_time();
+        let (doc_processor_mailbox, doc_processor_inbox) = universe.create_test_mailbox();
+        let params = FileSourceParams::file(filepath);
+        let source_config = SourceConfig {
+            source_id: "test-file-source".to_string(),
+            desired_num_pipelines: NonZeroUsize::new(1).unwrap(),
+            max_num_pipelines_per_indexer: NonZeroUsize::new(1).unwrap(),
+            enabled: true,
+            source_params: SourceParams::File(params.clone()),
+            transform_config: None,
+            input_format,
+        };
+        let metastore = metastore_for_test();
+        let source = FileSourceFactory::typed_create_source(
+            SourceRuntimeArgs::for_test(
+                IndexUid::new_with_random_ulid("test-index"),
+                source_config,
+                metastore,
+                PathBuf::from("./queues"),
+            ),
+            params,
+            checkpoint,
+        )
+        .await
+        .unwrap();
+        let file_source_actor = SourceActor {
+            source: Box::new(source),
+            doc_processor_mailbox,
+        };
+        let (_file_source_mailbox, file_source_handle) =
+            universe.spawn_builder().spawn(file_source_actor);
+        let (actor_termination, counters) = file_source_handle.join().await;
+        assert!(actor_termination.is_success());
+        let doc_batches: Vec<RawDocBatch> = doc_processor_inbox.drain_for_test_typed();
+        universe.assert_quit().await;
+        (counters, doc_batches)
+    }
+
+    #[test]
+    fn test_file_compression_detection() {
+        assert_eq!(
+            FileCompression::from_extension(Path::new("data/logs.json.gz")),
+            Some(FileCompression::Gzip)
+        );
+        assert_eq!(
+            FileCompression::from_extension(Path::new("data/logs.csv.zst")),
+            Some(FileCompression::Zstd)
+        );
+        assert_eq!(
+            FileCompression::from_extension(Path::new("data/logs.ndjson")),
+            None
+        );
+        assert_eq!(
+            FileCompression::from_magic_bytes(&[0x1f, 0x8b, 0x08, 0x00]),
+            FileCompression::Gzip
+        );
+        assert_eq!(
+            FileCompression::from_magic_bytes(&[0x28, 0xb5, 0x2f, 0xfd]),
+            FileCompression::Zstd
+        );
+        assert_eq!(
+            FileCompression::from_magic_bytes(b"{\"bo"),
+            FileCompression::Uncompressed
+        );
+        assert_eq!(
+            FileCompression::from_magic_bytes(b""),
+            FileCompression::Uncompressed
+        );
+    }
+
+    #[tokio::test]
+    async fn test_file_source_gzip() {
+        let mut payload = Vec::new();
+        for i in 0..100 {
+            payload.extend_from_slice(format!("{{\"body\": \"{i}\"}}\n").as_bytes());
+        }
+        let mut temp_file = tempfile::Builder::new()
+            .suffix(".json.gz")
+            .tempfile()
+            .unwrap();
+        temp_file.write_all(&gzip_encode(&payload).await).unwrap();
+        temp_file.flush().unwrap();
+
+        let (counters, doc_batches) = run_file_source(
+            temp_file.path(),
+            SourceInputFormat::Json,
+            SourceCheckpoint::default(),
+        )
+        .await;
+        assert_eq!(
+            counters,
+            serde_json::json!({
+                "previous_offset": payload.len() as u64,
+                "current_offset": payload.len() as u64,
+                "num_lines_processed": 100u64
+            })
+        );
+        assert_eq!(doc_batches.len(), 1);
+        assert_eq!(doc_batches[0].docs.len(), 100);
+        assert_eq!(&doc_batches[0].docs[0][..], b"{\"body\": \"0\"}\n");
+    }
+
+    #[tokio::test]
+    async fn test_file_source_zstd_resume_from_checkpoint() {
+        let mut payload = Vec::new();
+        for i in 0..100 {
+            payload.extend_from_slice(format!("{i}\n").as_bytes());
+        }
+        // No extension: the compression is detected from the magic bytes.
+        let mut temp_file = tempfile::NamedTempFile::new().unwrap();
+        temp_file
+            .write_all(&zstd::encode_all(&payload[..], 3).unwrap())
+            .unwrap();
+        temp_file.flush().unwrap();
+        let temp_file_path = temp_file.path().canonicalize().unwrap();
+
+        let mut checkpoint = SourceCheckpoint::default();
+        let partition_id = PartitionId::from(temp_file_path.to_string_lossy().to_string());
+        let checkpoint_delta = SourceCheckpointDelta::from_partition_delta(
+            partition_id,
+            Position::offset(0u64),
+            Position::offset(4u64),
+        )
+        .unwrap();
+        checkpoint.try_apply_delta(checkpoint_delta).unwrap();
+
+        let (counters, doc_batches) =
+            run_file_source(&temp_file_path, SourceInputFormat::PlainText, checkpoint).await;
+        assert_eq!(
+            counters,
+            serde_json::json!({
+                "previous_offset": 290u64,
+                "current_offset": 290u64,
+                "num_lines_processed": 98u64
+            })
+        );
+        assert_eq!(&doc_batches[0].docs[0][..], b"2\n");
+    }
+
+    #[tokio::test]
+    async fn test_file_source_csv() {
+        let payload = b"name,age\nalice,41\nbob,42\ncarol,43\n";
+        for compressed in [false, true] {
+            let mut temp_file = tempfile::Builder::new()
+                .suffix(if compressed { ".csv.gz" } else { ".csv" })
+                .tempfile()
+                .unwrap();
+            if compressed {
+                temp_file.write_all(&gzip_encode(payload).await).unwrap();
+            } else {
+                temp_file.write_all(payload).unwrap();
+            }
+            temp_file.flush().unwrap();
+            let temp_file_path = temp_file.path().canonicalize().unwrap();
+
+            let (counters, doc_batches) = run_file_source(
+                &temp_file_path,
+                SourceInputFormat::Csv,
+                SourceCheckpoint::default(),
+            )
+            .await;
+            assert_eq!(
+                counters,
+                serde_json::json!({
+                    "previous_offset": payload.len() as u64,
+                    "current_offset": payload.len() as u64,
+                    "num_lines_processed": 3u64
+                })
+            );
+            assert_eq!(doc_batches.len(), 1);
+            let docs: Vec<&[u8]> = doc_batches[0].docs.iter().map(|doc| &doc[..]).collect();
+            assert_eq!(
+                docs,
+                [
+                    &br#"{"age":"41","name":"alice"}"#[..],
+                    br#"{"age":"42","name":"bob"}"#,
+                    br#"{"age":"43","name":"carol"}"#
+                ]
+            );
+
+            // The header and the first record have already been indexed.
+            let mut checkpoint = SourceCheckpoint::default();
+            let partition_id = PartitionId::from(temp_file_path.to_string_lossy().to_string());
+            let checkpoint_delta = SourceCheckpointDelta::from_partition_delta(
+                partition_id,
+                Position::offset(0u64),
+                Position::offset(18u64),
+            )
+            .unwrap();
+            checkpoint.try_apply_delta(checkpoint_delta).unwrap();
+
+            let (counters, doc_batches) =
+                run_file_source(&temp_file_path, SourceInputFormat::Csv, checkpoint).await;
+            assert_eq!(
+                counters,
+                serde_json::json!({
+                    "previous_offset": payload.len() as u64,
+                    "current_offset": payload.len() as u64,
+                    "num_lines_processed": 2u64
+                })
+            );
+            assert_eq!(doc_batches.len(), 1);
+            let docs: Vec<&[u8]> = doc_batches[0].docs.iter().map(|doc| &doc[..]).collect();
+            assert_eq!(
+                docs,
+                [
+                    &br#"{"age":"42","name":"bob"}"#[..],
+                    br#"{"age":"43","name":"carol"}"#
+                ]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_source_csv_multiline_records() {
+        let payload = b"name,bio\nalice,\"likes\n\nrust\"\nbob\n\"carol\",\"says \"\"hi\"\"\"";
+        let mut temp_file = tempfile::Builder::new().suffix(".csv").tempfile().unwrap();
+        temp_file.write_all(payload).unwrap();
+        temp_file.flush().unwrap();
+        let temp_file_path = temp_file.path().canonicalize().unwrap();
+
+        let (counters, doc_batches) = run_file_source(
+            &temp_file_path,
+            SourceInputFormat::Csv,
+            SourceCheckpoint::default(),
+        )
+        .await;
+        assert_eq!(
+            counters,
+            serde_json::json!({
+                "previous_offset": payload.len() as u64,
+                "current_offset": payload.len() as u64,
+                "num_lines_processed": 3u64
+            })
+        );
+        assert_eq!(doc_batches.len(), 1);
+        let docs: Vec<&[u8]> = doc_batches[0].docs.iter().map(|doc| &doc[..]).collect();
+        assert_eq!(
+            docs,
+            [
+                &br#"{"bio":"likes\n\nrust","name":"alice"}"#[..],
+                br#"["bob"]"#,
+                br#"{"bio":"says \"hi\"","name":"carol"}"#
+            ]
+        );
+    }
+
+    #[test]
+    fn test_csv_record_parser_batch_boundaries() {
+        let mut parser = CsvRecordParser::default();
+        let mut records = VecDeque::new();
+
+        parser.feed(b"alice,\"likes\n", &mut records);
+        assert!(records.is_empty());
+        assert!(parser.has_partial_record());
+
+        parser.feed(b"rust\"\n", &mut records);
+        assert_eq!(
+            records.pop_front().unwrap(),
+            [b"alice".to_vec(), b"likes\nrust".to_vec()]
+        );
+        assert!(!parser.has_partial_record());
+
+        let long_value = "a".repeat(5_000);
+        parser.feed(format!("{long_value},b\n").as_bytes(), &mut records);
+        assert_eq!(
+            records.pop_front().unwrap(),
+            [long_value.into_bytes(), b"b".to_vec()]
+        );
+        parser.feed(b"no,newline", &mut records);
+        assert!(records.is_empty());
+
+        parser.feed(b"", &mut records);
+        assert_eq!(
+            records.pop_front().unwrap(),
+            [b"no".to_vec(), b"newline".to_vec()]
+        );
+        assert!(records.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_csv_state_read_header() {
+        let mut csv_state = CsvState::default();
+        let num_bytes = csv_state
+            .read_header(&mut &b"name,age\nalice,41\n"[..])
+            .await
+            .unwrap();
+        assert_eq!(num_bytes, 9);
+        assert_eq!(
+            csv_state.field_names_opt.unwrap(),
+            ["name".to_string(), "age".to_string()]
+        );
+
+        let mut csv_state = CsvState::default();
+        let num_bytes = csv_state.read_header(&mut &b""[..]).await.unwrap();
+        assert_eq!(num_bytes, 0);
+        assert!(csv_state.field_names_opt.is_none());
+
+        for invalid_header in [&b"name,,age\n"[..], b"name,age,name\n"] {
+            let mut csv_state = CsvState::default();
+            csv_state
+                .read_header(&mut &invalid_header[..])
+                .await
+                .unwrap_err();
+        }
+    }
 }
@@ -69,6 +69,7 @@ mod kinesis;
 #[cfg(feature = "pulsar")]
 mod pulsar_source;
 mod source_factory;
+mod storage_prefix_source;
 mod vec_source;
 mod void_source;
 
@@ -80,6 +81,7 @@ use async_trait::async_trait;
 use bytes::Bytes;
 use bytesize::ByteSize;
 pub use file_source::{FileSource, FileSourceFactory};
+use futures::TryStreamExt;
 #[cfg(feature = "gcp-pubsub")]
 pub use gcp_pubsub_source::{GcpPubSubSource, GcpPubSubSourceFactory};
 #[cfg(feature = "kafka")]
@@ -101,12 +103,14 @@ use quickwit_proto::types::{IndexUid, PipelineUid, ShardId};
 use quickwit_storage::StorageResolver;
 use serde_json::Value as JsonValue;
 pub use source_factory::{SourceFactory, SourceLoader, TypedSourceFactory};
+pub use storage_prefix_source::{StoragePrefixSource, StoragePrefixSourceFactory};
 use tokio::runtime::Handle;
 use tracing::error;
 pub use vec_source::{VecSource, VecSourceFactory};
 pub use void_source::{VoidSource, VoidSourceFactory};
 
 use self::file_source::dir_and_filename;
+use self::storage_prefix_source::split_prefix_uri;
 use crate::actors::DocProcessor;
 use crate::models::RawDocBatch;
 use crate::source::ingest::IngestSourceFactory;
@@ -394,6 +398,7 @@ pub fn quickwit_supported_sources() -> &'static SourceLoader {
         source_factory.add_source("kinesis", KinesisSourceFactory);
         #[cfg(feature = "pulsar")]
         source_factory.add_source("pulsar", PulsarSourceFactory);
+        source_factory.add_source("storage_prefix", StoragePrefixSourceFactory);
         source_factory.add_source("vec", VecSourceFactory);
         source_factory.add_source("void", VoidSourceFactory);
         source_factory
@@ -413,6 +418,12 @@ pub async fn check_source_connectivity(
             }
             Ok(())
         }
+        SourceParams::StoragePrefix(params) => {
+            let (storage_uri, prefix) = split_prefix_uri(&params.prefix_uri);
+            let storage = storage_resolver.resolve(&storage_uri).await?;
+            storage.list(&prefix).try_next().await?;
+            Ok(())
+        }
         #[allow(unused_variables)]
         SourceParams::Kafka(params) => {
             #[cfg(not(feature = "kafka"))]
@@ -0,0 +1,500 @@
+// Copyright (C) 2024 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+use std::collections::VecDeque;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use quickwit_actors::{ActorExitStatus, Mailbox};
+use quickwit_common::uri::Uri;
+use quickwit_config::{SourceInputFormat, StoragePrefixSourceParams};
+use quickwit_metastore::checkpoint::{PartitionId, SourceCheckpoint, SourceCheckpointDelta};
+use quickwit_proto::types::Position;
+use quickwit_storage::{FileMetadata, Storage};
+use serde::Serialize;
+use tracing::info;
+
+use super::file_source::{DocFileReader, BATCH_NUM_BYTES_LIMIT};
+use crate::actors::DocProcessor;
+use crate::models::RawDocBatch;
+use crate::source::{Source, SourceContext, SourceRuntimeArgs, TypedSourceFactory};
+
+#[derive(Default, Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct StoragePrefixSourceCounters {
+    pub num_files_processed: u64,
+    pub num_lines_processed: u64,
+    pub num_bytes_processed: u64,
+}
+
+struct CurrentFile {
+    partition_id: PartitionId,
+    reader: DocFileReader,
+    offset: u64,
+}
+
+/// Ingests all the files located under a prefix of a storage, one file after the other, in
+/// lexicographic order. Each file is tracked as a separate partition of the source checkpoint,
+/// identified by its URI: the position of a file is its offset in (decompressed) bytes, and
+/// becomes `Eof` once the file is entirely ingested.
+pub struct StoragePrefixSource {
+    source_id: String,
+    storage: Arc<dyn Storage>,
+    prefix: PathBuf,
+    is_csv: bool,
+    polling_interval_opt: Option<Duration>,
+    // Checkpoint of the source, including the positions of the batches emitted but not yet
+    // published.
+    checkpoint: SourceCheckpoint,
+    pending_files: VecDeque<PathBuf>,
+    current_file_opt: Option<CurrentFile>,
+    counters: StoragePrefixSourceCounters,
+}
+
+impl fmt::Debug for StoragePrefixSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "StoragePrefixSource {{ source_id: {} }}", self.source_id)
+    }
+}
+
+impl StoragePrefixSource {
+    fn partition_id(&self, path: &Path) -> anyhow::Result<PartitionId> {
+        let file_uri = self.storage.uri().join(path)?;
+        Ok(PartitionId::from(file_uri.into_string()))
+    }
+
+    /// Lists the files located under the prefix that have not been entirely ingested yet.
+    async fn list_pending_files(&mut self, ctx: &SourceContext) -> anyhow::Result<()> {
+        let files: Vec<FileMetadata> = ctx
+            .protect_future(self.storage.list(&self.prefix).try_collect())
+            .await?;
+
+        for file in files {
+            let partition_id = self.partition_id(&file.path)?;
+            let is_ingested = self
+                .checkpoint
+                .position_for_partition(&partition_id)
+                .map(Position::is_eof)
+                .unwrap_or(false);
+            if !is_ingested {
+                self.pending_files.push_back(file.path);
+            }
+        }
+        Ok(())
+    }
+
+    async fn open_file(&self, path: PathBuf, ctx: &SourceContext) -> anyhow::Result<CurrentFile> {
+        let partition_id = self.partition_id(&path)?;
+        let offset = self
+            .checkpoint
+            .position_for_partition(&partition_id)
+            .map(|position| {
+                position
+                    .as_usize()
+                    .expect("file offset should be stored as usize")
+            })
+            .unwrap_or(0);
+        info!(path=%path.display(), offset=offset, "opening file");
+        let reader = ctx
+            .protect_future(DocFileReader::open(
+                &*self.storage,
+                &path,
+                offset,
+                self.is_csv,
+            ))
+            .await?;
+        Ok(CurrentFile {
+            partition_id,
+            reader,
+            offset: offset as u64,
+        })
+    }
+}
+
+#[async_trait]
+impl Source for StoragePrefixSource {
+    async fn emit_batches(
+        &mut self,
+        doc_processor_mailbox: &Mailbox<DocProcessor>,
+        ctx: &SourceContext,
+    ) -> Result<Duration, ActorExitStatus> {
+        if self.current_file_opt.is_none() {
+            if self.pending_files.is_empty() {
+                self.list_pending_files(ctx).await?;
+            }
+            let Some(path) = self.pending_files.pop_front() else {
+                if let Some(polling_interval) = self.polling_interval_opt {
+                    return Ok(polling_interval);
+                }
+                info!("all files ingested");
+                ctx.send_exit_with_success(doc_processor_mailbox).await?;
+                return Err(ActorExitStatus::Success);
+            };
+            self.current_file_opt = Some(self.open_file(path, ctx).await?);
+        }
+        let current_file = self
+            .current_file_opt
+            .as_mut()
+            .expect("a file should be open");
+        let lines_batch = current_file
+            .reader
+            .read_batch(BATCH_NUM_BYTES_LIMIT, ctx)
+            .await?;
+
+        let from_position = Position::offset(current_file.offset);
+        current_file.offset += lines_batch.num_bytes;
+        let to_position = if lines_batch.reached_eof {
+            Position::eof(current_file.offset)
+        } else {
+            Position::offset(current_file.offset)
+        };
+        self.counters.num_lines_processed += lines_batch.num_lines;
+        self.counters.num_bytes_processed += lines_batch.num_bytes;
+
+        if from_position != to_position {
+            // When the end of the file is reached, we emit a batch even if it is empty so that the
+            // file is marked as entirely ingested in the checkpoint.
+            let checkpoint_delta = SourceCheckpointDelta::from_partition_delta(
+                current_file.partition_id.clone(),
+                from_position,
+                to_position,
+            )
+            .context("failed to create checkpoint delta")?;
+            self.checkpoint
+                .try_apply_delta(checkpoint_delta.clone())
+                .context("failed to apply checkpoint delta")?;
+            let doc_batch = RawDocBatch::new(lines_batch.docs, checkpoint_delta, false);
+            ctx.send_message(doc_processor_mailbox, doc_batch).await?;
+        }
+        if lines_batch.reached_eof {
+            self.counters.num_files_processed += 1;
+            self.current_file_opt = None;
+        }
+        Ok(Duration::default())
+    }
+
+    fn name(&self) -> String {
+        format!("StoragePrefixSource{{source_id={}}}", self.source_id)
+    }
+
+    fn observable_state(&self) -> serde_json::Value {
+        serde_json::to_value(&self.counters).unwrap()
+    }
+}
+
+pub struct StoragePrefixSourceFactory;
+
+#[async_trait]
+impl TypedSourceFactory for StoragePrefixSourceFactory {
+    type Source = StoragePrefixSource;
+    type Params = StoragePrefixSourceParams;
+
+    async fn typed_create_source(
+        ctx: Arc<SourceRuntimeArgs>,
+        params: StoragePrefixSourceParams,
+        checkpoint: SourceCheckpoint,
+    ) -> anyhow::Result<StoragePrefixSource> {
+        let (storage_uri, prefix) = split_prefix_uri(&params.prefix_uri);
+        let storage = ctx.storage_resolver.resolve(&storage_uri).await?;
+        let polling_interval_opt = params
+            .polling_interval_secs
+            .map(|polling_interval_secs| Duration::from_secs(polling_interval_secs.get()));
+        let storage_prefix_source = StoragePrefixSource {
+            source_id: ctx.source_id().to_string(),
+            storage,
+            prefix,
+            is_csv: ctx.source_config.input_format == SourceInputFormat::Csv,
+            polling_interval_opt,
+            checkpoint,
+            pending_files: VecDeque::new(),
+            current_file_opt: None,
+            counters: StoragePrefixSourceCounters::default(),
+        };
+        Ok(storage_prefix_source)
+    }
+}
+
+/// Splits a prefix URI into the URI of the storage to resolve and the prefix of the paths to
+/// list within that storage. A prefix URI ending with `/` designates a whole "directory".
+pub(crate) fn split_prefix_uri(prefix_uri: &Uri) -> (Uri, PathBuf) {
+    if prefix_uri.as_str().ends_with('/') {
+        return (prefix_uri.clone(), PathBuf::new());
+    }
+    match (prefix_uri.parent(), prefix_uri.file_name()) {
+        (Some(parent_uri), Some(file_name)) => (parent_uri, file_name.to_path_buf()),
+        _ => (prefix_uri.clone(), PathBuf::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::{NonZeroU64, NonZeroUsize};
+    use std::str::FromStr;
+
+    use async_compression::tokio::write::GzipEncoder;
+    use quickwit_actors::{Observation, Universe};
+    use quickwit_config::{SourceConfig, SourceParams};
+    use quickwit_metastore::metastore_for_test;
+    use quickwit_proto::types::IndexUid;
+    use tokio::io::AsyncWriteExt;
+
+    use super::*;
+    use crate::source::SourceActor;
+
+    async fn gzip_encode(payload: &[u8]) -> Vec<u8> {
+        let mut encoder = GzipEncoder::new(Vec::new());
+        encoder.write_all(payload).await.unwrap();
+        encoder.shutdown().await.unwrap();
+        encoder.into_inner()
+    }
+
+    fn source_args_for_test(
+        params: &StoragePrefixSourceParams,
+        input_format: SourceInputFormat,
+    ) -> Arc<SourceRuntimeArgs> {
+        let source_config = SourceConfig {
+            source_id: "test-storage-prefix-source".to_string(),
+            desired_num_pipelines: NonZeroUsize::new(1).unwrap(),
+            max_num_pipelines_per_indexer: NonZeroUsize::new(1).unwrap(),
+            enabled: true,
+            source_params: SourceParams::StoragePrefix(params.clone()),
+            transform_config: None,
+            input_format,
+        };
+        SourceRuntimeArgs::for_test(
+            IndexUid::new_with_random_ulid("test-index"),
+            source_config,
+            metastore_for_test(),
+            PathBuf::from("./queues"),
+        )
+    }
+
+    async fn run_storage_prefix_source(
+        params: StoragePrefixSourceParams,
+        input_format: SourceInputFormat,
+        checkpoint: SourceCheckpoint,
+    ) -> (serde_json::Value, Vec<RawDocBatch>) {
+        let universe = Universe::with_accelerated_time();
+        let (doc_processor_mailbox, doc_processor_inbox) = universe.create_test_mailbox();
+        let source = StoragePrefixSourceFactory::typed_create_source(
+            source_args_for_test(&params, input_format),
+            params,
+            checkpoint,
+        )
+        .await
+        .unwrap();
+        let source_actor = SourceActor {
+            source: Box::new(source),
+            doc_processor_mailbox,
+        };
+        let (_source_mailbox, source_handle) = universe.spawn_builder().spawn(source_actor);
+        let (actor_termination, counters) = source_handle.join().await;
+        assert!(actor_termination.is_success());
+        let doc_batches: Vec<RawDocBatch> = doc_processor_inbox.drain_for_test_typed();
+        universe.assert_quit().await;
+        (counters, doc_batches)
+    }
+
+    fn collect_docs(doc_batches: &[RawDocBatch]) -> Vec<String> {
+        doc_batches
+            .iter()
+            .flat_map(|doc_batch| doc_batch.docs.iter())
+            .map(|doc| String::from_utf8(doc.to_vec()).unwrap())
+            .collect()
+    }
+
+    fn collect_checkpoint(doc_batches: &[RawDocBatch]) -> SourceCheckpoint {
+        let mut checkpoint = SourceCheckpoint::default();
+        for doc_batch in doc_batches {
+            checkpoint
+                .try_apply_delta(doc_batch.checkpoint_delta.clone())
+                .unwrap();
+        }
+        checkpoint
+    }
+
+    fn file_partition_id(dir_path: &Path, file_name: &str) -> PartitionId {
+        let file_uri = Uri::from_str(&format!("file://{}", dir_path.display()))
+            .unwrap()
+            .join(file_name)
+            .unwrap();
+        PartitionId::from(file_uri.into_string())
+    }
+
+    #[test]
+    fn test_split_prefix_uri() {
+        let (storage_uri, prefix) = split_prefix_uri(&Uri::for_test("s3://bucket/logs/2024-"));
+        assert_eq!(storage_uri, "s3://bucket/logs");
+        assert_eq!(prefix, Path::new("2024-"));
+
+        let (storage_uri, prefix) = split_prefix_uri(&Uri::for_test("s3://bucket/logs/"));
+        assert_eq!(storage_uri, "s3://bucket/logs/");
+        assert_eq!(prefix, Path::new(""));
+
+        let (storage_uri, prefix) = split_prefix_uri(&Uri::for_test("s3://bucket"));
+        assert_eq!(storage_uri, "s3://bucket");
+        assert_eq!(prefix, Path::new(""));
+    }
+
+    #[tokio::test]
+    async fn test_storage_prefix_source() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir_path = temp_dir.path().canonicalize().unwrap();
+        tokio::fs::write(dir_path.join("logs-1.json"), b"{\"id\": 1}\n{\"id\": 2}\n")
+            .await
+            .unwrap();
+        tokio::fs::write(
+            dir_path.join("logs-2.json.gz"),
+            gzip_encode(b"{\"id\": 3}\n").await,
+        )
+        .await
+        .unwrap();
+        tokio::fs::write(dir_path.join("other.json"), b"{\"id\": 4}\n")
+            .await
+            .unwrap();
+
+        let params = StoragePrefixSourceParams {
+            prefix_uri: Uri::from_str(&format!("file://{}/logs-", dir_path.display())).unwrap(),
+            polling_interval_secs: None,
+        };
+        let (counters, doc_batches) =
+            run_storage_prefix_source(params, SourceInputFormat::Json, SourceCheckpoint::default())
+                .await;
+        assert_eq!(
+            counters,
+            serde_json::json!({
+                "num_files_processed": 2u64,
+                "num_lines_processed": 3u64,
+                "num_bytes_processed": 30u64,
+            })
+        );
+        assert_eq!(
+            collect_docs(&doc_batches),
+            ["{\"id\": 1}\n", "{\"id\": 2}\n", "{\"id\": 3}\n"]
+        );
+        let checkpoint = collect_checkpoint(&doc_batches);
+        assert_eq!(
+            checkpoint.position_for_partition(&file_partition_id(&dir_path, "logs-1.json")),
+            Some(&Position::eof(20u64))
+        );
+        assert_eq!(
+            checkpoint.position_for_partition(&file_partition_id(&dir_path, "logs-2.json.gz")),
+            Some(&Position::eof(10u64))
+        );
+        assert_eq!(checkpoint.num_partitions(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_storage_prefix_source_resume_from_checkpoint() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir_path = temp_dir.path().canonicalize().unwrap();
+        tokio::fs::write(dir_path.join("a.csv"), b"id,body\n1,foo\n")
+            .await
+            .unwrap();
+        tokio::fs::write(dir_path.join("b.csv"), b"id,body\n2,bar\n3,baz\n")
+            .await
+            .unwrap();
+
+        // `a.csv` is entirely ingested and the first record of `b.csv` is already indexed.
+        let mut checkpoint = SourceCheckpoint::default();
+        for (file_name, to_position) in [
+            ("a.csv", Position::eof(14u64)),
+            ("b.csv", Position::offset(14u64)),
+        ] {
+            let checkpoint_delta = SourceCheckpointDelta::from_partition_delta(
+                file_partition_id(&dir_path, file_name),
+                Position::Beginning,
+                to_position,
+            )
+            .unwrap();
+            checkpoint.try_apply_delta(checkpoint_delta).unwrap();
+        }
+        let params = StoragePrefixSourceParams {
+            prefix_uri: Uri::from_str(&format!("file://{}/", dir_path.display())).unwrap(),
+            polling_interval_secs: None,
+        };
+        let (counters, doc_batches) =
+            run_storage_prefix_source(params, SourceInputFormat::Csv, checkpoint).await;
+        assert_eq!(
+            counters,
+            serde_json::json!({
+                "num_files_processed": 1u64,
+                "num_lines_processed": 1u64,
+                "num_bytes_processed": 6u64,
+            })
+        );
+        assert_eq!(collect_docs(&doc_batches), [r#"{"body":"baz","id":"3"}"#]);
+        let checkpoint = collect_checkpoint(&doc_batches);
+        assert_eq!(
+            checkpoint.position_for_partition(&file_partition_id(&dir_path, "b.csv")),
+            Some(&Position::eof(20u64))
+        );
+        assert_eq!(checkpoint.num_partitions(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_storage_prefix_source_polling() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir_path = temp_dir.path().canonicalize().unwrap();
+        tokio::fs::write(dir_path.join("logs-1.json"), b"{\"id\": 1}\n")
+            .await
+            .unwrap();
+
+        let params = StoragePrefixSourceParams {
+            prefix_uri: Uri::from_str(&format!("file://{}/logs-", dir_path.display())).unwrap(),
+            polling_interval_secs: Some(NonZeroU64::new(30).unwrap()),
+        };
+        let universe = Universe::with_accelerated_time();
+        let (doc_processor_mailbox, doc_processor_inbox) = universe.create_test_mailbox();
+        let source = StoragePrefixSourceFactory::typed_create_source(
+            source_args_for_test(&params, SourceInputFormat::Json),
+            params,
+            SourceCheckpoint::default(),
+        )
+        .await
+        .unwrap();
+        let source_actor = SourceActor {
+            source: Box::new(source),
+            doc_processor_mailbox,
+        };
+        let (_source_mailbox, source_handle) = universe.spawn_builder().spawn(source_actor);
+        universe.sleep(Duration::from_secs(10)).await;
+
+        let Observation { state, .. } = source_handle.process_pending_and_observe().await;
+        assert_eq!(state["num_files_processed"], 1);
+
+        tokio::fs::write(dir_path.join("logs-2.json"), b"{\"id\": 2}\n")
+            .await
+            .unwrap();
+        universe.sleep(Duration::from_secs(60)).await;
+
+        let Observation { state, .. } = source_handle.process_pending_and_observe().await;
+        assert_eq!(state["num_files_processed"], 2);
+
+        let doc_batches: Vec<RawDocBatch> = doc_processor_inbox.drain_for_test_typed();
+        assert_eq!(
+            collect_docs(&doc_batches),
+            ["{\"id\": 1}\n", "{\"id\": 2}\n"]
+        );
+        universe.assert_quit().await;
+    }
+}
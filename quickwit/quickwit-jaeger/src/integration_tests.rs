@@ -35,6 +35,10 @@ use quickwit_ingest::{
 };
 use quickwit_metastore::{AddSourceRequestExt, CreateIndexRequestExt, FileBackedMetastore};
 use quickwit_opentelemetry::otlp::{make_resource_spans_for_test, OtlpGrpcTracesService};
+use quickwit_proto::jaeger::api_v2::{
+    KeyValue as JaegerKeyValue, Log as JaegerLog, Process as JaegerProcess, Span as JaegerSpan,
+    SpanRef as JaegerSpanRef, SpanRefType as JaegerSpanRefType, ValueType,
+};
 use quickwit_proto::jaeger::storage::v1::span_reader_plugin_server::SpanReaderPlugin;
 use quickwit_proto::jaeger::storage::v1::{
     FindTraceIDsRequest, GetOperationsRequest, GetServicesRequest, GetTraceRequest, Operation,
@@ -45,6 +49,15 @@ use quickwit_proto::metastore::{
 };
 use quickwit_proto::opentelemetry::proto::collector::trace::v1::trace_service_server::TraceService;
 use quickwit_proto::opentelemetry::proto::collector::trace::v1::ExportTraceServiceRequest;
+use quickwit_proto::opentelemetry::proto::common::v1::any_value::Value as OtlpAnyValueValue;
+use quickwit_proto::opentelemetry::proto::common::v1::{
+    AnyValue as OtlpAnyValue, InstrumentationScope, KeyValue as OtlpKeyValue,
+};
+use quickwit_proto::opentelemetry::proto::resource::v1::Resource;
+use quickwit_proto::opentelemetry::proto::trace::v1::span::{Event as OtlpEvent, Link as OtlpLink};
+use quickwit_proto::opentelemetry::proto::trace::v1::{
+    ResourceSpans, ScopeSpans, Span as OtlpSpan, Status as OtlpStatus,
+};
 use quickwit_proto::types::{IndexUid, PipelineUid};
 use quickwit_search::{
     start_searcher_service, SearchJobPlacer, SearchService, SearchServiceClient, SearcherContext,
@@ -52,6 +65,7 @@ use quickwit_search::{
 };
 use quickwit_storage::StorageResolver;
 use tempfile::TempDir;
+use time::OffsetDateTime;
 use tokio_stream::StreamExt;
 
 use crate::JaegerService;
@@ -292,10 +306,256 @@ async fn test_otel_jaeger_integration() {
         assert_eq!(process.tags[0].key, "tags");
         assert_eq!(process.tags[0].v_str, r#"["foo"]"#);
     }
+    {
+        // Test that `GetTrace` returns the spans of an OTLP trace without loss of information.
+        // Timestamps are truncated to the second to rule out any precision loss on the way.
+        let now_secs = OffsetDateTime::now_utc().unix_timestamp() as u64;
+        let start_timestamp_nanos = (now_secs - 2) * 1_000_000_000;
+        let end_timestamp_nanos = (now_secs - 1) * 1_000_000_000;
+
+        let export_trace_request = ExportTraceServiceRequest {
+            resource_spans: make_resource_spans_for_fidelity_test(
+                start_timestamp_nanos,
+                end_timestamp_nanos,
+            ),
+        };
+        traces_service
+            .export(tonic::Request::new(export_trace_request))
+            .await
+            .unwrap();
+
+        let get_trace_request = GetTraceRequest {
+            trace_id: [6; 16].to_vec(),
+        };
+        let mut span_stream = jaeger_service
+            .get_trace(tonic::Request::new(get_trace_request))
+            .await
+            .unwrap()
+            .into_inner();
+        let mut spans = Vec::new();
+
+        while let Some(spans_chunk) = span_stream.next().await {
+            spans.extend(spans_chunk.unwrap().spans);
+        }
+        spans.sort_by(|left, right| left.span_id.cmp(&right.span_id));
+
+        let expected_spans = make_expected_jaeger_spans_for_fidelity_test(
+            start_timestamp_nanos,
+            end_timestamp_nanos,
+        );
+        assert_eq!(spans, expected_spans);
+    }
     _indexer_handle.quit().await;
     universe.assert_quit().await;
 }
 
+fn otlp_string_attribute(key: &str, value: &str) -> OtlpKeyValue {
+    OtlpKeyValue {
+        key: key.to_string(),
+        value: Some(OtlpAnyValue {
+            value: Some(OtlpAnyValueValue::StringValue(value.to_string())),
+        }),
+    }
+}
+
+fn jaeger_string_tag(key: &str, value: &str) -> JaegerKeyValue {
+    JaegerKeyValue {
+        key: key.to_string(),
+        v_type: ValueType::String as i32,
+        v_str: value.to_string(),
+        ..Default::default()
+    }
+}
+
+fn jaeger_int64_tag(key: &str, value: i64) -> JaegerKeyValue {
+    JaegerKeyValue {
+        key: key.to_string(),
+        v_type: ValueType::Int64 as i32,
+        v_int64: value,
+        ..Default::default()
+    }
+}
+
+/// Makes a trace with a server span calling a client span that exercises every field of the
+/// OTLP span model.
+fn make_resource_spans_for_fidelity_test(
+    start_timestamp_nanos: u64,
+    end_timestamp_nanos: u64,
+) -> Vec<ResourceSpans> {
+    let server_span = OtlpSpan {
+        trace_id: vec![6; 16],
+        span_id: vec![6; 8],
+        parent_span_id: Vec::new(),
+        trace_state: String::new(),
+        name: "GET /checkout".to_string(),
+        kind: 2, // Server
+        start_time_unix_nano: start_timestamp_nanos,
+        end_time_unix_nano: end_timestamp_nanos,
+        attributes: Vec::new(),
+        dropped_attributes_count: 0,
+        events: Vec::new(),
+        dropped_events_count: 0,
+        links: Vec::new(),
+        dropped_links_count: 0,
+        status: None,
+    };
+    let client_span = OtlpSpan {
+        trace_id: vec![6; 16],
+        span_id: vec![7; 8],
+        parent_span_id: vec![6; 8],
+        trace_state: "key1=value1".to_string(),
+        name: "charge_card".to_string(),
+        kind: 3, // Client
+        start_time_unix_nano: start_timestamp_nanos,
+        end_time_unix_nano: end_timestamp_nanos,
+        attributes: vec![otlp_string_attribute("http.method", "POST")],
+        dropped_attributes_count: 3,
+        events: vec![OtlpEvent {
+            time_unix_nano: start_timestamp_nanos + 500_000_000,
+            name: "retry".to_string(),
+            attributes: vec![OtlpKeyValue {
+                key: "attempt".to_string(),
+                value: Some(OtlpAnyValue {
+                    value: Some(OtlpAnyValueValue::IntValue(2)),
+                }),
+            }],
+            dropped_attributes_count: 0,
+        }],
+        dropped_events_count: 4,
+        links: vec![OtlpLink {
+            trace_id: vec![8; 16],
+            span_id: vec![9; 8],
+            trace_state: String::new(),
+            attributes: Vec::new(),
+            dropped_attributes_count: 0,
+        }],
+        dropped_links_count: 5,
+        status: Some(OtlpStatus {
+            code: 2,
+            message: "Timeout.".to_string(),
+        }),
+    };
+    let scope_spans = vec![ScopeSpans {
+        scope: Some(InstrumentationScope {
+            name: "checkout-instrumentation".to_string(),
+            version: "1.0.0".to_string(),
+            attributes: Vec::new(),
+            dropped_attributes_count: 2,
+        }),
+        spans: vec![server_span, client_span],
+        schema_url: String::new(),
+    }];
+    let resource_spans = ResourceSpans {
+        resource: Some(Resource {
+            attributes: vec![
+                otlp_string_attribute("service.name", "checkout"),
+                otlp_string_attribute("host.name", "host-1"),
+            ],
+            dropped_attributes_count: 1,
+        }),
+        scope_spans,
+        schema_url: String::new(),
+    };
+    vec![resource_spans]
+}
+
+fn make_expected_jaeger_spans_for_fidelity_test(
+    start_timestamp_nanos: u64,
+    end_timestamp_nanos: u64,
+) -> Vec<JaegerSpan> {
+    let start_time = prost_types::Timestamp {
+        seconds: (start_timestamp_nanos / 1_000_000_000) as i64,
+        nanos: 0,
+    };
+    let duration = prost_types::Duration {
+        seconds: ((end_timestamp_nanos - start_timestamp_nanos) / 1_000_000_000) as i64,
+        nanos: 0,
+    };
+    let process = JaegerProcess {
+        service_name: "checkout".to_string(),
+        tags: vec![jaeger_string_tag("host.name", "host-1")],
+    };
+    let server_span = JaegerSpan {
+        trace_id: vec![6; 16],
+        span_id: vec![6; 8],
+        operation_name: "GET /checkout".to_string(),
+        references: Vec::new(),
+        flags: 1,
+        start_time: Some(start_time.clone()),
+        duration: Some(duration.clone()),
+        tags: vec![
+            jaeger_string_tag("span.kind", "server"),
+            jaeger_string_tag("otel.scope.name", "checkout-instrumentation"),
+            jaeger_string_tag("otel.scope.version", "1.0.0"),
+        ],
+        logs: Vec::new(),
+        process: Some(process.clone()),
+        process_id: String::new(),
+        warnings: vec![
+            "1 resource attributes were dropped".to_string(),
+            "2 scope attributes were dropped".to_string(),
+        ],
+    };
+    let client_span = JaegerSpan {
+        trace_id: vec![6; 16],
+        span_id: vec![7; 8],
+        operation_name: "charge_card".to_string(),
+        references: vec![
+            JaegerSpanRef {
+                trace_id: vec![6; 16],
+                span_id: vec![6; 8],
+                ref_type: JaegerSpanRefType::ChildOf as i32,
+            },
+            JaegerSpanRef {
+                trace_id: vec![8; 16],
+                span_id: vec![9; 8],
+                ref_type: JaegerSpanRefType::FollowsFrom as i32,
+            },
+        ],
+        flags: 1,
+        start_time: Some(start_time.clone()),
+        duration: Some(duration),
+        tags: vec![
+            jaeger_string_tag("http.method", "POST"),
+            jaeger_int64_tag("otel.dropped_attributes_count", 3),
+            jaeger_int64_tag("otel.dropped_events_count", 4),
+            jaeger_int64_tag("otel.dropped_links_count", 5),
+            jaeger_string_tag("span.kind", "client"),
+            jaeger_string_tag("otel.status_code", "ERROR"),
+            jaeger_string_tag("otel.status_description", "Timeout."),
+            JaegerKeyValue {
+                key: "error".to_string(),
+                v_type: ValueType::Bool as i32,
+                v_bool: true,
+                ..Default::default()
+            },
+            jaeger_string_tag("otel.scope.name", "checkout-instrumentation"),
+            jaeger_string_tag("otel.scope.version", "1.0.0"),
+            jaeger_string_tag("w3c.tracestate", "key1=value1"),
+        ],
+        logs: vec![JaegerLog {
+            timestamp: Some(prost_types::Timestamp {
+                seconds: start_time.seconds,
+                nanos: 500_000_000,
+            }),
+            fields: vec![
+                jaeger_int64_tag("attempt", 2),
+                jaeger_string_tag("event", "retry"),
+            ],
+        }],
+        process: Some(process),
+        process_id: String::new(),
+        warnings: vec![
+            "1 resource attributes were dropped".to_string(),
+            "2 scope attributes were dropped".to_string(),
+            "3 span attributes were dropped".to_string(),
+            "4 events were dropped".to_string(),
+            "5 links were dropped".to_string(),
+        ],
+    };
+    vec![server_span, client_span]
+}
+
 async fn cluster_for_test() -> Cluster {
     let transport = ChannelTransport::default();
     create_cluster_for_test(
@@ -34,17 +34,20 @@ use quickwit_opentelemetry::otlp::{
     TraceId, OTEL_TRACES_INDEX_ID,
 };
 use quickwit_proto::jaeger::api_v2::{
-    KeyValue as JaegerKeyValue, Log as JaegerLog, Process as JaegerProcess, Span as JaegerSpan,
-    SpanRef as JaegerSpanRef, SpanRefType as JaegerSpanRefType, ValueType,
+    DependencyLink, KeyValue as JaegerKeyValue, Log as JaegerLog, Process as JaegerProcess,
+    Span as JaegerSpan, SpanRef as JaegerSpanRef, SpanRefType as JaegerSpanRefType, ValueType,
 };
+use quickwit_proto::jaeger::storage::v1::dependencies_reader_plugin_server::DependenciesReaderPlugin;
 use quickwit_proto::jaeger::storage::v1::span_reader_plugin_server::SpanReaderPlugin;
 use quickwit_proto::jaeger::storage::v1::{
-    FindTraceIDsRequest, FindTraceIDsResponse, FindTracesRequest, GetOperationsRequest,
-    GetOperationsResponse, GetServicesRequest, GetServicesResponse, GetTraceRequest, Operation,
-    SpansResponseChunk, TraceQueryParameters,
+    FindTraceIDsRequest, FindTraceIDsResponse, FindTracesRequest, GetDependenciesRequest,
+    GetDependenciesResponse, GetOperationsRequest, GetOperationsResponse, GetServicesRequest,
+    GetServicesResponse, GetTraceRequest, Operation, SpansResponseChunk, TraceQueryParameters,
 };
 use quickwit_proto::opentelemetry::proto::trace::v1::status::StatusCode as OtlpStatusCode;
-use quickwit_proto::search::{CountHits, ListTermsRequest, SearchRequest};
+use quickwit_proto::search::{
+    CountHits, ListTermsRequest, PartialHit, SearchRequest, SortField, SortOrder,
+};
 use quickwit_query::query_ast::{BoolQuery, QueryAst, RangeQuery, TermQuery};
 use quickwit_search::{FindTraceIdsCollector, SearchService};
 use serde::Deserialize;
@@ -73,12 +76,16 @@ type JaegerResult<T> = Result<T, Status>;
 
 type SpanStream = ReceiverStream<Result<SpansResponseChunk, Status>>;
 
+/// Jaeger span flag indicating that the trace was sampled.
+const JAEGER_SAMPLED_FLAG: u32 = 1;
+
 #[derive(Clone)]
 pub struct JaegerService {
     search_service: Arc<dyn SearchService>,
     lookback_period_secs: i64,
     max_trace_duration_secs: i64,
     max_fetch_spans: u64,
+    max_dependency_spans: u64,
 }
 
 impl JaegerService {
@@ -88,6 +95,7 @@ impl JaegerService {
             lookback_period_secs: config.lookback_period().as_secs() as i64,
             max_trace_duration_secs: config.max_trace_duration().as_secs() as i64,
             max_fetch_spans: config.max_fetch_spans.get(),
+            max_dependency_spans: config.max_dependency_spans.get(),
         }
     }
 
@@ -244,6 +252,105 @@ impl JaegerService {
         Ok(response)
     }
 
+    /// Computes the links between services (parent service -> child service, number of calls)
+    /// from the spans that started within the requested time window. The spans are fetched in
+    /// pages of `max_fetch_spans` spans, sorted by start timestamp. At most `max_dependency_spans`
+    /// spans are scanned: beyond that, the call counts only cover the first spans of the window.
+    #[instrument("get_dependencies", skip_all, fields(num_spans=Empty, num_pages=Empty, num_links=Empty, truncated=Empty))]
+    pub async fn get_dependencies_for_indexes(
+        &self,
+        request: GetDependenciesRequest,
+        index_id_patterns: Vec<String>,
+    ) -> JaegerResult<GetDependenciesResponse> {
+        debug!(request=?request, index_ids=?index_id_patterns, "`get_dependencies` request");
+
+        let end_timestamp_secs = request
+            .end_time
+            .map(|end_time| end_time.seconds)
+            .unwrap_or_else(|| OffsetDateTime::now_utc().unix_timestamp());
+        let start_timestamp_secs = request
+            .start_time
+            .map(|start_time| start_time.seconds)
+            .unwrap_or(end_timestamp_secs.saturating_sub(self.lookback_period_secs));
+
+        if start_timestamp_secs > end_timestamp_secs {
+            return Err(Status::invalid_argument(
+                "Start time must be less than or equal to end time.",
+            ));
+        }
+        let query_ast = serde_json::to_string(&QueryAst::MatchAll)
+            .map_err(|err| Status::internal(err.to_string()))?;
+        let sort_field = SortField {
+            field_name: "span_start_timestamp_nanos".to_string(),
+            sort_order: SortOrder::Asc as i32,
+            sort_datetime_format: None,
+        };
+        let mut dependency_links_builder = DependencyLinksBuilder::default();
+        let mut search_after: Option<PartialHit> = None;
+        let mut num_pages = 0;
+        let mut num_scanned_spans: u64 = 0;
+        let mut truncated = false;
+
+        loop {
+            let max_hits = self
+                .max_fetch_spans
+                .min(self.max_dependency_spans - num_scanned_spans);
+            let search_request = SearchRequest {
+                index_id_patterns: index_id_patterns.clone(),
+                query_ast: query_ast.clone(),
+                start_timestamp: Some(start_timestamp_secs),
+                end_timestamp: Some(end_timestamp_secs),
+                max_hits,
+                sort_fields: vec![sort_field.clone()],
+                search_after,
+                count_hits: CountHits::Underestimate.into(),
+                ..Default::default()
+            };
+            let search_response = self.search_service.root_search(search_request).await?;
+            num_pages += 1;
+
+            for hit in &search_response.hits {
+                let span: QwSpanDependencyFields = json_deserialize(&hit.json, "span")?;
+                dependency_links_builder.add_span(span);
+            }
+            let num_hits = search_response.hits.len() as u64;
+            num_scanned_spans += num_hits;
+
+            if num_hits < max_hits {
+                break;
+            }
+            if num_scanned_spans >= self.max_dependency_spans {
+                truncated = true;
+                warn!(
+                    max_dependency_spans=%self.max_dependency_spans,
+                    "reached the maximum number of spans scanned to compute the dependencies, the \
+                     call counts are partial"
+                );
+                break;
+            }
+            search_after = search_response
+                .hits
+                .last()
+                .and_then(|hit| hit.partial_hit.clone());
+
+            if search_after.is_none() {
+                break;
+            }
+        }
+        let num_spans = dependency_links_builder.num_spans();
+        let dependencies = dependency_links_builder.build();
+
+        let current_span = RuntimeSpan::current();
+        current_span.record("num_spans", num_spans);
+        current_span.record("num_pages", num_pages);
+        current_span.record("num_links", dependencies.len());
+        current_span.record("truncated", truncated);
+
+        debug!(dependencies=?dependencies, "`get_dependencies` response");
+        let response = GetDependenciesResponse { dependencies };
+        Ok(response)
+    }
+
     #[instrument("find_trace_ids", skip_all fields(service_name=%trace_query.service_name, operation_name=%trace_query.operation_name))]
     async fn find_trace_ids(
         &self,
@@ -547,6 +654,104 @@ impl SpanReaderPlugin for JaegerService {
     }
 }
 
+#[async_trait]
+impl DependenciesReaderPlugin for JaegerService {
+    async fn get_dependencies(
+        &self,
+        request: Request<GetDependenciesRequest>,
+    ) -> Result<Response<GetDependenciesResponse>, Status> {
+        let index_id_patterns =
+            extract_otel_traces_index_id_patterns_from_metadata(request.metadata())?;
+        metrics!(
+            self.get_dependencies_for_indexes(request.into_inner(), index_id_patterns)
+                .await,
+            [get_dependencies, OTEL_TRACES_INDEX_ID]
+        );
+    }
+}
+
+/// Subset of the span fields required to compute the dependency links between services.
+#[derive(Debug, Deserialize)]
+struct QwSpanDependencyFields {
+    trace_id: TraceId,
+    span_id: SpanId,
+    #[serde(default)]
+    parent_span_id: Option<SpanId>,
+    service_name: String,
+}
+
+/// Counts the calls between services: a call from a parent service to a child service is a span
+/// of the child service whose parent span belongs to the parent service. Like in Jaeger, calls
+/// within the same service are ignored, and so are spans whose parent span is missing.
+///
+/// The spans are added one page at a time, so only the fields needed to resolve the parent spans
+/// are kept, with the service names interned.
+#[derive(Default)]
+struct DependencyLinksBuilder {
+    service_names: Vec<String>,
+    service_ords: HashMap<String, u32>,
+    span_service_ords: HashMap<(TraceId, SpanId), u32>,
+    /// Parent span and service ordinal of the child spans.
+    child_spans: Vec<(TraceId, SpanId, u32)>,
+}
+
+impl DependencyLinksBuilder {
+    fn add_span(&mut self, span: QwSpanDependencyFields) {
+        let service_ord = match self.service_ords.get(&span.service_name) {
+            Some(service_ord) => *service_ord,
+            None => {
+                let service_ord = self.service_names.len() as u32;
+                self.service_ords
+                    .insert(span.service_name.clone(), service_ord);
+                self.service_names.push(span.service_name);
+                service_ord
+            }
+        };
+        self.span_service_ords
+            .insert((span.trace_id, span.span_id), service_ord);
+
+        if let Some(parent_span_id) = span.parent_span_id {
+            self.child_spans
+                .push((span.trace_id, parent_span_id, service_ord));
+        }
+    }
+
+    fn num_spans(&self) -> usize {
+        self.span_service_ords.len()
+    }
+
+    fn build(self) -> Vec<DependencyLink> {
+        let mut call_counts: HashMap<(u32, u32), u64> = HashMap::new();
+
+        for (trace_id, parent_span_id, child_service_ord) in self.child_spans {
+            let Some(parent_service_ord) = self.span_service_ords.get(&(trace_id, parent_span_id))
+            else {
+                continue;
+            };
+            if *parent_service_ord == child_service_ord {
+                continue;
+            }
+            *call_counts
+                .entry((*parent_service_ord, child_service_ord))
+                .or_default() += 1;
+        }
+        call_counts
+            .into_iter()
+            .map(
+                |((parent_service_ord, child_service_ord), call_count)| DependencyLink {
+                    parent: self.service_names[parent_service_ord as usize].clone(),
+                    child: self.service_names[child_service_ord as usize].clone(),
+                    call_count,
+                    source: String::new(),
+                },
+            )
+            .sorted_unstable_by(|left, right| {
+                (&left.parent, &left.child).cmp(&(&right.parent, &right.child))
+            })
+            .collect()
+    }
+}
+
 fn extract_term(term_bytes: &[u8]) -> String {
     tantivy::Term::wrap(term_bytes)
         .value()
@@ -749,6 +954,8 @@ fn qw_span_to_jaeger_span(qw_span_json: &str) -> Result<JaegerSpan, Status> {
         qw_span.span_start_timestamp_nanos,
         qw_span.span_end_timestamp_nanos,
     ));
+    let warnings = make_dropped_count_warnings(&qw_span);
+
     qw_span.resource_attributes.remove("service.name");
     let process = Some(JaegerProcess {
         service_name: qw_span.service_name,
@@ -769,6 +976,8 @@ fn qw_span_to_jaeger_span(qw_span_json: &str) -> Result<JaegerSpan, Status> {
     );
     inject_span_kind_tag(&mut tags, qw_span.span_kind);
     inject_span_status_tags(&mut tags, qw_span.span_status);
+    inject_scope_tags(&mut tags, qw_span.scope_name, qw_span.scope_version);
+    inject_trace_state_tag(&mut tags, qw_span.trace_state);
 
     let references =
         otlp_links_to_jaeger_references(&qw_span.trace_id, qw_span.parent_span_id, qw_span.links)?;
@@ -778,14 +987,18 @@ fn qw_span_to_jaeger_span(qw_span_json: &str) -> Result<JaegerSpan, Status> {
         span_id: qw_span.span_id.to_vec(),
         operation_name: qw_span.span_name,
         references,
-        flags: 0, // TODO
+        // OTLP spans do not carry the trace flags, but spans that reach Quickwit were necessarily
+        // sampled.
+        flags: JAEGER_SAMPLED_FLAG,
         start_time,
         duration,
         tags,
         logs,
         process,
-        process_id: "".to_string(), // TODO
-        warnings: Vec::new(),       // TODO
+        // The process is embedded in the span, so no process ID is needed to resolve it: process
+        // IDs only make sense within a batch or a trace that holds a process map.
+        process_id: String::new(),
+        warnings,
     };
     Ok(span)
 }
@@ -840,6 +1053,69 @@ fn inject_dropped_count_tags(
     }
 }
 
+/// Surfaces the attributes, events, and links dropped by the OpenTelemetry SDK as span warnings
+/// so that Jaeger UI flags the span as incomplete.
+fn make_dropped_count_warnings(qw_span: &QwSpan) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    for (dropped_count, label) in [
+        (
+            qw_span.resource_dropped_attributes_count,
+            "resource attributes",
+        ),
+        (qw_span.scope_dropped_attributes_count, "scope attributes"),
+        (qw_span.span_dropped_attributes_count, "span attributes"),
+        (qw_span.span_dropped_events_count, "events"),
+        (qw_span.span_dropped_links_count, "links"),
+    ] {
+        if dropped_count > 0 {
+            warnings.push(format!("{dropped_count} {label} were dropped"));
+        }
+    }
+    warnings
+}
+
+/// Injects instrumentation scope tags.
+/// <https://opentelemetry.io/docs/specs/otel/trace/sdk_exporters/jaeger/#instrumentationscope>
+fn inject_scope_tags(
+    tags: &mut Vec<JaegerKeyValue>,
+    scope_name_opt: Option<String>,
+    scope_version_opt: Option<String>,
+) {
+    for (key, value_opt) in [
+        ("otel.scope.name", scope_name_opt),
+        ("otel.scope.version", scope_version_opt),
+    ] {
+        if let Some(value) = value_opt.filter(|value| !value.is_empty()) {
+            tags.push(JaegerKeyValue {
+                key: key.to_string(),
+                v_type: ValueType::String as i32,
+                v_str: value,
+                v_bool: false,
+                v_int64: 0,
+                v_float64: 0.0,
+                v_binary: Vec::new(),
+            });
+        }
+    }
+}
+
+/// Injects the W3C trace state tag, which is how the OpenTelemetry Collector translates the trace
+/// state of a span to Jaeger.
+fn inject_trace_state_tag(tags: &mut Vec<JaegerKeyValue>, trace_state_opt: Option<String>) {
+    if let Some(trace_state) = trace_state_opt.filter(|trace_state| !trace_state.is_empty()) {
+        tags.push(JaegerKeyValue {
+            key: "w3c.tracestate".to_string(),
+            v_type: ValueType::String as i32,
+            v_str: trace_state,
+            v_bool: false,
+            v_int64: 0,
+            v_float64: 0.0,
+            v_binary: Vec::new(),
+        });
+    }
+}
+
 /// Injects span kind tag.
 /// <https://opentelemetry.io/docs/specs/otel/trace/sdk_exporters/jaeger/#spankind>
 fn inject_span_kind_tag(tags: &mut Vec<JaegerKeyValue>, span_kind_id: u32) {
@@ -972,6 +1248,9 @@ fn otlp_attributes_to_jaeger_tags(
         };
         tags.push(tag);
     }
+    // Attributes are stored in a hash map, so we sort the tags to return them in a deterministic
+    // order, which also lets clients compare the tags of two processes.
+    tags.sort_unstable_by(|left, right| left.key.cmp(&right.key));
     Ok(tags)
 }
 
@@ -1070,6 +1349,8 @@ where T: Deserialize<'a> {
 
 #[cfg(test)]
 mod tests {
+    use std::num::NonZeroU64;
+
     use quickwit_opentelemetry::otlp::{OtelSignal, OTEL_TRACES_INDEX_ID_PATTERN};
     use quickwit_proto::jaeger::api_v2::ValueType;
     use quickwit_search::{encode_term_for_test, MockSearchService, QuickwitAggregations};
@@ -2203,7 +2484,7 @@ mod tests {
                 }
             ]
         );
-        assert_eq!(jaeger_span.flags, 0);
+        assert_eq!(jaeger_span.flags, 1);
         assert_eq!(
             jaeger_span.start_time.unwrap(),
             WellKnownTimestamp {
@@ -2293,6 +2574,33 @@ mod tests {
                     v_float64: 0.0,
                     v_binary: Vec::new()
                 },
+                JaegerKeyValue {
+                    key: "otel.scope.name".to_string(),
+                    v_type: 0,
+                    v_str: "vector.dev".to_string(),
+                    v_bool: false,
+                    v_int64: 0,
+                    v_float64: 0.0,
+                    v_binary: Vec::new()
+                },
+                JaegerKeyValue {
+                    key: "otel.scope.version".to_string(),
+                    v_type: 0,
+                    v_str: "1.0.0".to_string(),
+                    v_bool: false,
+                    v_int64: 0,
+                    v_float64: 0.0,
+                    v_binary: Vec::new()
+                },
+                JaegerKeyValue {
+                    key: "w3c.tracestate".to_string(),
+                    v_type: 0,
+                    v_str: "key1=value1,key2=value2".to_string(),
+                    v_bool: false,
+                    v_int64: 0,
+                    v_float64: 0.0,
+                    v_binary: Vec::new()
+                },
             ]
         );
         assert_eq!(
@@ -2348,7 +2656,17 @@ mod tests {
                 }]
             }
         );
-        assert!(jaeger_span.warnings.is_empty());
+        assert_eq!(
+            jaeger_span.warnings,
+            [
+                "1 resource attributes were dropped",
+                "2 scope attributes were dropped",
+                "3 span attributes were dropped",
+                "4 events were dropped",
+                "5 links were dropped",
+            ]
+        );
+        assert_eq!(jaeger_span.process_id, "");
     }
 
     #[test]
@@ -2481,4 +2799,275 @@ mod tests {
         let response = jaeger.get_services(request).await.unwrap().into_inner();
         assert_eq!(response.services, &["service1", "service2", "service3"]);
     }
+
+    fn make_span_json_for_test(
+        trace_id: u8,
+        span_id: u8,
+        parent_span_id_opt: Option<u8>,
+        service_name: &str,
+    ) -> String {
+        let mut span_json = json!({
+            "trace_id": TraceId::new([trace_id; 16]),
+            "span_id": SpanId::new([span_id; 8]),
+            "service_name": service_name,
+            "span_name": "span",
+            "span_start_timestamp_nanos": 0,
+        });
+        if let Some(parent_span_id) = parent_span_id_opt {
+            span_json["parent_span_id"] = json!(SpanId::new([parent_span_id; 8]));
+        }
+        span_json.to_string()
+    }
+
+    #[test]
+    fn test_dependency_links_builder() {
+        let spans: Vec<QwSpanDependencyFields> = [
+            // Trace 1: frontend -> backend -> database, plus a call within the backend.
+            make_span_json_for_test(1, 1, None, "frontend"),
+            make_span_json_for_test(1, 2, Some(1), "backend"),
+            make_span_json_for_test(1, 3, Some(2), "backend"),
+            make_span_json_for_test(1, 4, Some(3), "database"),
+            // Trace 2: frontend -> backend twice, and a span whose parent is missing.
+            make_span_json_for_test(2, 1, None, "frontend"),
+            make_span_json_for_test(2, 2, Some(1), "backend"),
+            make_span_json_for_test(2, 3, Some(1), "backend"),
+            make_span_json_for_test(2, 4, Some(5), "database"),
+            // Trace 3: a child span added before its parent span.
+            make_span_json_for_test(3, 2, Some(1), "backend"),
+            make_span_json_for_test(3, 1, None, "frontend"),
+        ]
+        .iter()
+        .map(|span_json| serde_json::from_str(span_json).unwrap())
+        .collect();
+
+        let mut dependency_links_builder = DependencyLinksBuilder::default();
+
+        for span in spans {
+            dependency_links_builder.add_span(span);
+        }
+        assert_eq!(dependency_links_builder.num_spans(), 10);
+
+        let dependencies = dependency_links_builder.build();
+        assert_eq!(
+            dependencies,
+            [
+                DependencyLink {
+                    parent: "backend".to_string(),
+                    child: "database".to_string(),
+                    call_count: 1,
+                    source: String::new(),
+                },
+                DependencyLink {
+                    parent: "frontend".to_string(),
+                    child: "backend".to_string(),
+                    call_count: 4,
+                    source: String::new(),
+                },
+            ]
+        );
+        assert!(DependencyLinksBuilder::default().build().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_dependencies() {
+        let mut service = MockSearchService::new();
+        service
+            .expect_root_search()
+            .withf(|req| {
+                req.index_id_patterns == vec![OTEL_TRACES_INDEX_ID_PATTERN]
+                    && req.query_ast == r#"{"type":"match_all"}"#
+                    && req.start_timestamp == Some(1_000)
+                    && req.end_timestamp == Some(2_000)
+                    && req.max_hits == JaegerConfig::default().max_fetch_spans.get()
+                    && req.sort_fields[0].field_name == "span_start_timestamp_nanos"
+                    && req.search_after.is_none()
+            })
+            .return_once(|_| {
+                let hits = [
+                    make_span_json_for_test(1, 1, None, "frontend"),
+                    make_span_json_for_test(1, 2, Some(1), "backend"),
+                ]
+                .into_iter()
+                .map(|json| quickwit_proto::search::Hit {
+                    json,
+                    ..Default::default()
+                })
+                .collect();
+                Ok(quickwit_proto::search::SearchResponse {
+                    num_hits: 2,
+                    hits,
+                    ..Default::default()
+                })
+            });
+
+        let service = Arc::new(service);
+        let jaeger = JaegerService::new(JaegerConfig::default(), service);
+
+        let request = tonic::Request::new(GetDependenciesRequest {
+            start_time: Some(WellKnownTimestamp {
+                seconds: 1_000,
+                nanos: 0,
+            }),
+            end_time: Some(WellKnownTimestamp {
+                seconds: 2_000,
+                nanos: 0,
+            }),
+        });
+        let response = jaeger.get_dependencies(request).await.unwrap().into_inner();
+        assert_eq!(
+            response.dependencies,
+            [DependencyLink {
+                parent: "frontend".to_string(),
+                child: "backend".to_string(),
+                call_count: 1,
+                source: String::new(),
+            }]
+        );
+
+        let request = tonic::Request::new(GetDependenciesRequest {
+            start_time: Some(WellKnownTimestamp {
+                seconds: 2_000,
+                nanos: 0,
+            }),
+            end_time: Some(WellKnownTimestamp {
+                seconds: 1_000,
+                nanos: 0,
+            }),
+        });
+        let status = jaeger.get_dependencies(request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_get_dependencies_paginates_through_window() {
+        let make_hit = |json: String, doc_id: u32| quickwit_proto::search::Hit {
+            json,
+            partial_hit: Some(PartialHit {
+                split_id: "split".to_string(),
+                doc_id,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let mut service = MockSearchService::new();
+        service
+            .expect_root_search()
+            .withf(|req| req.max_hits == 2 && req.search_after.is_none())
+            .return_once(move |_| {
+                let hits = vec![
+                    make_hit(make_span_json_for_test(1, 1, None, "frontend"), 0),
+                    make_hit(make_span_json_for_test(2, 1, None, "frontend"), 1),
+                ];
+                Ok(quickwit_proto::search::SearchResponse {
+                    num_hits: 3,
+                    hits,
+                    ..Default::default()
+                })
+            });
+        service
+            .expect_root_search()
+            .withf(|req| {
+                req.max_hits == 2
+                    && req
+                        .search_after
+                        .as_ref()
+                        .map(|partial_hit| partial_hit.doc_id)
+                        == Some(1)
+            })
+            .return_once(move |_| {
+                // The parent span of this span was returned by the first page.
+                let hits = vec![make_hit(
+                    make_span_json_for_test(1, 2, Some(1), "backend"),
+                    2,
+                )];
+                Ok(quickwit_proto::search::SearchResponse {
+                    num_hits: 3,
+                    hits,
+                    ..Default::default()
+                })
+            });
+
+        let jaeger_config = JaegerConfig {
+            max_fetch_spans: NonZeroU64::new(2).unwrap(),
+            ..Default::default()
+        };
+        let jaeger = JaegerService::new(jaeger_config, Arc::new(service));
+
+        let request = tonic::Request::new(GetDependenciesRequest {
+            start_time: Some(WellKnownTimestamp {
+                seconds: 1_000,
+                nanos: 0,
+            }),
+            end_time: Some(WellKnownTimestamp {
+                seconds: 2_000,
+                nanos: 0,
+            }),
+        });
+        let response = jaeger.get_dependencies(request).await.unwrap().into_inner();
+        assert_eq!(
+            response.dependencies,
+            [DependencyLink {
+                parent: "frontend".to_string(),
+                child: "backend".to_string(),
+                call_count: 1,
+                source: String::new(),
+            }]
+        );
+    }
+    #[tokio::test]
+    async fn test_get_dependencies_stops_at_max_dependency_spans() {
+        let make_hit = |json: String, doc_id: u32| quickwit_proto::search::Hit {
+            json,
+            partial_hit: Some(PartialHit {
+                split_id: "split".to_string(),
+                doc_id,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let mut service = MockSearchService::new();
+        service
+            .expect_root_search()
+            .withf(|req| req.max_hits == 2 && req.search_after.is_none())
+            .times(1)
+            .returning(move |_| {
+                let hits = vec![
+                    make_hit(make_span_json_for_test(1, 1, None, "frontend"), 0),
+                    make_hit(make_span_json_for_test(1, 2, Some(1), "backend"), 1),
+                ];
+                Ok(quickwit_proto::search::SearchResponse {
+                    num_hits: 3,
+                    hits,
+                    ..Default::default()
+                })
+            });
+
+        let jaeger_config = JaegerConfig {
+            max_fetch_spans: NonZeroU64::new(2).unwrap(),
+            max_dependency_spans: NonZeroU64::new(2).unwrap(),
+            ..Default::default()
+        };
+        let jaeger = JaegerService::new(jaeger_config, Arc::new(service));
+
+        let request = tonic::Request::new(GetDependenciesRequest {
+            start_time: Some(WellKnownTimestamp {
+                seconds: 1_000,
+                nanos: 0,
+            }),
+            end_time: Some(WellKnownTimestamp {
+                seconds: 2_000,
+                nanos: 0,
+            }),
+        });
+        let response = jaeger.get_dependencies(request).await.unwrap().into_inner();
+        assert_eq!(
+            response.dependencies,
+            [DependencyLink {
+                parent: "frontend".to_string(),
+                child: "backend".to_string(),
+                call_count: 1,
+                source: String::new(),
+            }]
+        );
+    }
 }
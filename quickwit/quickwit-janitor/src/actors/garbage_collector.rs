@@ -26,8 +26,8 @@ use futures::{stream, StreamExt};
 use itertools::Itertools;
 use quickwit_actors::{Actor, ActorContext, Handler};
 use quickwit_common::shared_consts::DELETION_GRACE_PERIOD;
-use quickwit_index_management::run_garbage_collect;
-use quickwit_metastore::ListIndexesMetadataResponseExt;
+use quickwit_index_management::{run_garbage_collect, run_orphan_files_garbage_collect};
+use quickwit_metastore::{IndexMetadata, ListIndexesMetadataResponseExt};
 use quickwit_proto::metastore::{
     ListIndexesMetadataRequest, MetastoreService, MetastoreServiceClient,
 };
@@ -44,6 +44,18 @@ const STAGED_GRACE_PERIOD: Duration = Duration::from_secs(60 * 60 * 24); // 24 h
 
 const MAX_CONCURRENT_GC_TASKS: usize = if cfg!(test) { 2 } else { 10 };
 
+/// Finding orphan split files requires listing the whole index storage, so we look for them much
+/// less often than for the other splits to garbage collect.
+const ORPHAN_FILES_RUN_INTERVAL: Duration = Duration::from_secs(60 * 60 * 24); // 24 hours
+
+/// Split files that are not referenced by the metastore are deleted once they are older than this
+/// grace period.
+const ORPHAN_FILES_GRACE_PERIOD: Duration = if cfg!(test) {
+    Duration::ZERO
+} else {
+    Duration::from_secs(60 * 60 * 24) // 24 hours
+};
+
 #[derive(Clone, Debug, Default, Serialize)]
 pub struct GarbageCollectorCounters {
     /// The number of passes the garbage collector has performed.
@@ -60,11 +72,26 @@ pub struct GarbageCollectorCounters {
     pub num_failed_storage_resolution: usize,
     /// The number of splits that were unable to be removed.
     pub num_failed_splits: usize,
+    /// The number of passes looking for orphan split files the garbage collector has performed.
+    pub num_orphan_files_passes: usize,
+    /// The number of deleted orphan split files.
+    pub num_deleted_orphan_files: usize,
+    /// The number of bytes of orphan split files deleted.
+    pub num_deleted_orphan_bytes: usize,
+    /// The number of orphan split files that were unable to be removed.
+    pub num_failed_orphan_files: usize,
+    /// The number of failed passes looking for orphan split files on an index.
+    pub num_failed_orphan_files_run_on_index: usize,
+    /// The number of failed storage resolutions while looking for orphan split files.
+    pub num_failed_orphan_files_storage_resolution: usize,
 }
 
 #[derive(Debug)]
 struct Loop;
 
+#[derive(Debug)]
+struct OrphanFilesLoop;
+
 /// An actor for collecting garbage periodically from an index.
 pub struct GarbageCollector {
     metastore: MetastoreServiceClient,
@@ -81,24 +108,30 @@ impl GarbageCollector {
         }
     }
 
-    /// Gc Loop handler logic.
-    /// Should not return an error to prevent the actor from crashing.
-    async fn handle_inner(&mut self, ctx: &ActorContext<Self>) {
-        info!("garbage-collect-operation");
-        self.counters.num_passes += 1;
-
-        let indexes = match self
+    async fn list_indexes(&mut self) -> Option<Vec<IndexMetadata>> {
+        match self
             .metastore
             .list_indexes_metadata(ListIndexesMetadataRequest::all())
             .await
             .and_then(|list_indexes_metadata_response| {
                 list_indexes_metadata_response.deserialize_indexes_metadata()
             }) {
-            Ok(metadatas) => metadatas,
+            Ok(metadatas) => Some(metadatas),
             Err(error) => {
                 error!(error=?error, "failed to list indexes from the metastore");
-                return;
+                None
             }
+        }
+    }
+
+    /// Gc Loop handler logic.
+    /// Should not return an error to prevent the actor from crashing.
+    async fn handle_inner(&mut self, ctx: &ActorContext<Self>) {
+        info!("garbage-collect-operation");
+        self.counters.num_passes += 1;
+
+        let Some(indexes) = self.list_indexes().await else {
+            return;
         };
         info!(index_ids=%indexes.iter().map(|im| im.index_id()).join(", "), "garbage collecting indexes");
 
@@ -166,6 +199,68 @@ impl GarbageCollector {
             }
         }
     }
+
+    /// Orphan files loop handler logic.
+    /// Should not return an error to prevent the actor from crashing.
+    async fn handle_orphan_files_inner(&mut self, ctx: &ActorContext<Self>) {
+        info!("garbage-collect-orphan-files-operation");
+        self.counters.num_orphan_files_passes += 1;
+
+        let Some(indexes) = self.list_indexes().await else {
+            return;
+        };
+        let mut gc_futures = stream::iter(indexes)
+            .map(|index| {
+                let metastore = self.metastore.clone();
+                let storage_resolver = self.storage_resolver.clone();
+                async move {
+                    let storage = match storage_resolver.resolve(index.index_uri()).await {
+                        Ok(storage) => storage,
+                        Err(error) => {
+                            error!(index=%index.index_id(), error=?error, "failed to resolve the index storage Uri");
+                            return None;
+                        }
+                    };
+                    let index_uid = index.index_uid;
+                    let gc_res = run_orphan_files_garbage_collect(
+                        index_uid.clone(),
+                        storage,
+                        metastore,
+                        ORPHAN_FILES_GRACE_PERIOD,
+                        false,
+                        Some(ctx.progress()),
+                    )
+                    .await;
+                    Some((index_uid, gc_res))
+                }
+            })
+            .buffer_unordered(MAX_CONCURRENT_GC_TASKS);
+
+        while let Some(gc_future_res) = gc_futures.next().await {
+            let Some((index_uid, gc_res)) = gc_future_res else {
+                self.counters.num_failed_orphan_files_storage_resolution += 1;
+                continue;
+            };
+            let removal_info = match gc_res {
+                Ok(removal_info) => removal_info,
+                Err(error) => {
+                    self.counters.num_failed_orphan_files_run_on_index += 1;
+                    error!(index_id=%index_uid.index_id(), error=?error, "failed to garbage collect orphan files of index");
+                    continue;
+                }
+            };
+            if !removal_info.removed_files.is_empty() {
+                info!(
+                    index_id=%index_uid.index_id(),
+                    num_deleted_orphan_files=removal_info.removed_files.len(),
+                    "Janitor deleted orphan split files.",
+                );
+            }
+            self.counters.num_deleted_orphan_files += removal_info.removed_files.len();
+            self.counters.num_deleted_orphan_bytes += removal_info.num_removed_bytes() as usize;
+            self.counters.num_failed_orphan_files += removal_info.failed_files.len();
+        }
+    }
 }
 
 #[async_trait]
@@ -185,6 +280,8 @@ impl Actor for GarbageCollector {
         ctx: &ActorContext<Self>,
     ) -> Result<(), quickwit_actors::ActorExitStatus> {
         self.handle(Loop, ctx).await?;
+        ctx.schedule_self_msg(ORPHAN_FILES_RUN_INTERVAL, OrphanFilesLoop)
+            .await;
         Ok(())
     }
 }
@@ -204,6 +301,22 @@ impl Handler<Loop> for GarbageCollector {
     }
 }
 
+#[async_trait]
+impl Handler<OrphanFilesLoop> for GarbageCollector {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        _: OrphanFilesLoop,
+        ctx: &ActorContext<Self>,
+    ) -> Result<(), quickwit_actors::ActorExitStatus> {
+        self.handle_orphan_files_inner(ctx).await;
+        ctx.schedule_self_msg(ORPHAN_FILES_RUN_INTERVAL, OrphanFilesLoop)
+            .await;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::ops::Bound;
@@ -214,8 +327,7 @@ mod tests {
     use quickwit_common::shared_consts::DELETION_GRACE_PERIOD;
     use quickwit_common::ServiceStream;
     use quickwit_metastore::{
-        IndexMetadata, ListSplitsRequestExt, ListSplitsResponseExt, Split, SplitMetadata,
-        SplitState,
+        ListSplitsRequestExt, ListSplitsResponseExt, Split, SplitMetadata, SplitState,
     };
     use quickwit_proto::metastore::{
         EmptyResponse, ListIndexesMetadataResponse, ListSplitsResponse, MetastoreError,
@@ -754,4 +866,63 @@ mod tests {
         assert_eq!(counters.num_failed_splits, 2);
         universe.assert_quit().await;
     }
+
+    #[tokio::test]
+    async fn test_garbage_collect_orphan_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let index_uri = format!("file://{}", temp_dir.path().display());
+        for file_name in ["known.split", "orphan.split"] {
+            tokio::fs::write(temp_dir.path().join(file_name), b"split")
+                .await
+                .unwrap();
+        }
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .times(2)
+            .returning(move |_list_indexes_request| {
+                let indexes_metadata = vec![IndexMetadata::for_test("test-index", &index_uri)];
+                Ok(
+                    ListIndexesMetadataResponse::try_from_indexes_metadata(indexes_metadata)
+                        .unwrap(),
+                )
+            });
+        mock_metastore
+            .expect_list_splits()
+            .times(3)
+            .returning(|list_splits_request| {
+                let query = list_splits_request.deserialize_list_splits_query().unwrap();
+                // Only the orphan files pass lists the splits in all states.
+                let splits = if query.split_states.is_empty() {
+                    make_splits(&["known"], SplitState::Published)
+                } else {
+                    Vec::new()
+                };
+                let splits = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits)]))
+            });
+        let garbage_collect_actor = GarbageCollector::new(
+            MetastoreServiceClient::from(mock_metastore),
+            StorageResolver::unconfigured(),
+        );
+        let universe = Universe::with_accelerated_time();
+        let (mailbox, handle) = universe.spawn_builder().spawn(garbage_collect_actor);
+
+        let counters = handle.process_pending_and_observe().await.state;
+        assert_eq!(counters.num_passes, 1);
+        assert_eq!(counters.num_orphan_files_passes, 0);
+
+        mailbox.ask(OrphanFilesLoop).await.unwrap();
+        let counters = handle.process_pending_and_observe().await.state;
+        assert_eq!(counters.num_orphan_files_passes, 1);
+        assert_eq!(counters.num_deleted_orphan_files, 1);
+        assert_eq!(counters.num_deleted_orphan_bytes, 5);
+        assert_eq!(counters.num_failed_orphan_files, 0);
+        assert_eq!(counters.num_failed_orphan_files_run_on_index, 0);
+        assert_eq!(counters.num_failed_orphan_files_storage_resolution, 0);
+
+        assert!(temp_dir.path().join("known.split").exists());
+        assert!(!temp_dir.path().join("orphan.split").exists());
+        universe.assert_quit().await;
+    }
 }
@@ -30,7 +30,10 @@ use std::ops::Bound;
 
 use itertools::Itertools;
 use quickwit_common::PrettySample;
-use quickwit_config::{SourceConfig, INGEST_V2_SOURCE_ID};
+use quickwit_config::{
+    DocMapping, IndexingSettings, RetentionPolicy, SearchSettings, SourceConfig,
+    INGEST_V2_SOURCE_ID,
+};
 use quickwit_proto::metastore::{
     AcquireShardsSubrequest, AcquireShardsSubresponse, DeleteQuery, DeleteShardsSubrequest,
     DeleteTask, EntityKind, ListShardsSubrequest, ListShardsSubresponse, MetastoreError,
@@ -477,6 +480,23 @@ impl FileBackedIndex {
         Ok(())
     }
 
+    /// Updates the doc mapping, search settings, retention policy, and indexing settings of the
+    /// index. Returns whether a mutation occurred.
+    pub(crate) fn update_index_config(
+        &mut self,
+        doc_mapping: DocMapping,
+        search_settings: SearchSettings,
+        retention_policy_opt: Option<RetentionPolicy>,
+        indexing_settings: IndexingSettings,
+    ) -> MetastoreResult<bool> {
+        self.metadata.update_index_config(
+            doc_mapping,
+            search_settings,
+            retention_policy_opt,
+            indexing_settings,
+        )
+    }
+
     /// Enables or disables a source. Returns whether a mutation occurred.
     pub(crate) fn toggle_source(&mut self, source_id: &str, enable: bool) -> MetastoreResult<bool> {
         self.metadata.toggle_source(source_id, enable)
@@ -47,7 +47,8 @@ use quickwit_proto::metastore::{
     ListStaleSplitsRequest, MarkSplitsForDeletionRequest, MetastoreError, MetastoreResult,
     MetastoreService, MetastoreServiceStream, OpenShardsRequest, OpenShardsResponse,
     OpenShardsSubrequest, PublishSplitsRequest, ResetSourceCheckpointRequest, StageSplitsRequest,
-    ToggleSourceRequest, UpdateSplitsDeleteOpstampRequest, UpdateSplitsDeleteOpstampResponse,
+    ToggleSourceRequest, UpdateIndexRequest, UpdateSplitsDeleteOpstampRequest,
+    UpdateSplitsDeleteOpstampResponse,
 };
 use quickwit_proto::types::IndexUid;
 use quickwit_storage::Storage;
@@ -65,7 +66,8 @@ use self::store_operations::{
 use super::{
     AddSourceRequestExt, CreateIndexRequestExt, IndexMetadataResponseExt,
     ListIndexesMetadataResponseExt, ListSplitsRequestExt, ListSplitsResponseExt,
-    PublishSplitsRequestExt, StageSplitsRequestExt, STREAM_SPLITS_CHUNK_SIZE,
+    PublishSplitsRequestExt, StageSplitsRequestExt, UpdateIndexRequestExt,
+    STREAM_SPLITS_CHUNK_SIZE,
 };
 use crate::checkpoint::IndexCheckpointDelta;
 use crate::{IndexMetadata, ListSplitsQuery, MetastoreServiceExt, Split, SplitState};
@@ -684,6 +686,37 @@ impl MetastoreService for FileBackedMetastore {
         Ok(response)
     }
 
+    async fn update_index(
+        &mut self,
+        request: UpdateIndexRequest,
+    ) -> MetastoreResult<IndexMetadataResponse> {
+        let doc_mapping = request.deserialize_doc_mapping()?;
+        let search_settings = request.deserialize_search_settings()?;
+        let retention_policy_opt = request.deserialize_retention_policy()?;
+        let indexing_settings = request.deserialize_indexing_settings()?;
+        let index_uid: IndexUid = request.index_uid.into();
+
+        let index_metadata = self
+            .mutate(index_uid, |index| {
+                let mutation_occurred = index.update_index_config(
+                    doc_mapping,
+                    search_settings,
+                    retention_policy_opt,
+                    indexing_settings,
+                )?;
+                let index_metadata = index.metadata().clone();
+
+                if mutation_occurred {
+                    Ok(MutationOccurred::Yes(index_metadata))
+                } else {
+                    Ok(MutationOccurred::No(index_metadata))
+                }
+            })
+            .await?;
+        let response = IndexMetadataResponse::try_from_index_metadata(index_metadata)?;
+        Ok(response)
+    }
+
     async fn list_indexes_metadata(
         &mut self,
         request: ListIndexesMetadataRequest,
@@ -23,7 +23,10 @@ use std::collections::hash_map::Entry;
 use std::collections::{BTreeMap, HashMap};
 
 use quickwit_common::uri::Uri;
-use quickwit_config::{IndexConfig, SourceConfig, TestableForRegression};
+use quickwit_config::{
+    DocMapping, IndexConfig, IndexingSettings, RetentionPolicy, SearchSettings, SourceConfig,
+    TestableForRegression,
+};
 use quickwit_proto::metastore::{EntityKind, MetastoreError, MetastoreResult};
 use quickwit_proto::types::{IndexUid, Position, SourceId};
 use serde::{Deserialize, Serialize};
@@ -114,6 +117,51 @@ impl IndexMetadata {
         }
     }
 
+    /// Updates the mutable settings of the index config: the doc mapping, the search settings,
+    /// the retention policy, and the indexing settings. The doc mapping can only be evolved by
+    /// appending new field mappings and tag fields. Returns whether the index was modified (true).
+    pub(crate) fn update_index_config(
+        &mut self,
+        doc_mapping: DocMapping,
+        search_settings: SearchSettings,
+        retention_policy_opt: Option<RetentionPolicy>,
+        indexing_settings: IndexingSettings,
+    ) -> MetastoreResult<bool> {
+        let doc_mapping = self
+            .index_config
+            .doc_mapping
+            .evolve(doc_mapping)
+            .map_err(|error| MetastoreError::InvalidArgument {
+                message: format!(
+                    "failed to update doc mapping of index `{}`: {error:#}",
+                    self.index_id()
+                ),
+            })?;
+        if self.index_config.doc_mapping == doc_mapping
+            && self.index_config.search_settings == search_settings
+            && self.index_config.retention_policy == retention_policy_opt
+            && self.index_config.indexing_settings == indexing_settings
+        {
+            return Ok(false);
+        }
+        let mut index_config = self.index_config.clone();
+        index_config.doc_mapping = doc_mapping;
+        index_config.search_settings = search_settings;
+        index_config.retention_policy = retention_policy_opt;
+        index_config.indexing_settings = indexing_settings;
+
+        index_config
+            .validate()
+            .map_err(|error| MetastoreError::InvalidArgument {
+                message: format!(
+                    "failed to update index config of index `{}`: {error:#}",
+                    self.index_id()
+                ),
+            })?;
+        self.index_config = index_config;
+        Ok(true)
+    }
+
     pub(crate) fn toggle_source(&mut self, source_id: &str, enable: bool) -> MetastoreResult<bool> {
         let Some(source_config) = self.sources.get_mut(source_id) else {
             return Err(MetastoreError::NotFound(EntityKind::Source {
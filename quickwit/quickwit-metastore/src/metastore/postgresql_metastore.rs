@@ -44,7 +44,7 @@ use quickwit_proto::metastore::{
     ListSplitsRequest, ListSplitsResponse, ListStaleSplitsRequest, MarkSplitsForDeletionRequest,
     MetastoreError, MetastoreResult, MetastoreService, MetastoreServiceClient,
     MetastoreServiceStream, OpenShardsRequest, OpenShardsResponse, PublishSplitsRequest,
-    ResetSourceCheckpointRequest, StageSplitsRequest, ToggleSourceRequest,
+    ResetSourceCheckpointRequest, StageSplitsRequest, ToggleSourceRequest, UpdateIndexRequest,
     UpdateSplitsDeleteOpstampRequest, UpdateSplitsDeleteOpstampResponse,
 };
 use quickwit_proto::types::IndexUid;
@@ -68,7 +68,7 @@ use crate::{
     AddSourceRequestExt, CreateIndexRequestExt, IndexMetadata, IndexMetadataResponseExt,
     ListIndexesMetadataResponseExt, ListSplitsQuery, ListSplitsRequestExt, ListSplitsResponseExt,
     MetastoreFactory, MetastoreResolverError, MetastoreServiceExt, Split, SplitMaturity,
-    SplitMetadata, SplitState, StageSplitsRequestExt,
+    SplitMetadata, SplitState, StageSplitsRequestExt, UpdateIndexRequestExt,
 };
 
 static MIGRATOR: Migrator = sqlx::migrate!("migrations/postgresql");
@@ -434,7 +434,7 @@ async fn mutate_index_metadata<E, M: FnOnce(&mut IndexMetadata) -> Result<bool,
     tx: &mut Transaction<'_, Postgres>,
     index_uid: IndexUid,
     mutate_fn: M,
-) -> MetastoreResult<bool>
+) -> MetastoreResult<IndexMetadata>
 where
     MetastoreError: From<E>,
 {
@@ -447,7 +447,7 @@ where
     }
     let mutation_occurred = mutate_fn(&mut index_metadata)?;
     if !mutation_occurred {
-        return Ok(mutation_occurred);
+        return Ok(index_metadata);
     }
     let index_metadata_json = serde_json::to_string(&index_metadata).map_err(|error| {
         MetastoreError::JsonSerializeError {
@@ -471,7 +471,7 @@ where
             index_id: index_id.to_string(),
         }));
     }
-    Ok(mutation_occurred)
+    Ok(index_metadata)
 }
 
 #[async_trait]
@@ -1047,6 +1047,31 @@ impl MetastoreService for PostgresqlMetastore {
         Ok(response)
     }
 
+    #[instrument(skip_all, fields(index_id=request.index_uid))]
+    async fn update_index(
+        &mut self,
+        request: UpdateIndexRequest,
+    ) -> MetastoreResult<IndexMetadataResponse> {
+        let doc_mapping = request.deserialize_doc_mapping()?;
+        let search_settings = request.deserialize_search_settings()?;
+        let retention_policy_opt = request.deserialize_retention_policy()?;
+        let indexing_settings = request.deserialize_indexing_settings()?;
+        let index_uid: IndexUid = request.index_uid.into();
+
+        let updated_index_metadata = run_with_tx!(self.connection_pool, tx, {
+            mutate_index_metadata(tx, index_uid, |index_metadata| {
+                index_metadata.update_index_config(
+                    doc_mapping,
+                    search_settings,
+                    retention_policy_opt,
+                    indexing_settings,
+                )
+            })
+            .await
+        })?;
+        IndexMetadataResponse::try_from_index_metadata(updated_index_metadata)
+    }
+
     #[instrument(skip(self))]
     async fn add_source(&mut self, request: AddSourceRequest) -> MetastoreResult<EmptyResponse> {
         let source_config = request.deserialize_source_config()?;
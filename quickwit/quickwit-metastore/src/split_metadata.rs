@@ -132,6 +132,10 @@ pub struct SplitMetadata {
     /// Number of merge operations that was involved to create
     /// this split.
     pub num_merge_ops: usize,
+
+    /// Version of the doc mapping used to create this split. Splits created with different
+    /// versions of the doc mapping of an index may have different schemas.
+    pub doc_mapping_version: u64,
 }
 impl fmt::Debug for SplitMetadata {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -175,6 +179,7 @@ impl fmt::Debug for SplitMetadata {
         debug_struct.field("footer_offsets", &self.footer_offsets);
         debug_struct.field("delete_opstamp", &self.delete_opstamp);
         debug_struct.field("num_merge_ops", &self.num_merge_ops);
+        debug_struct.field("doc_mapping_version", &self.doc_mapping_version);
         debug_struct.finish()
     }
 }
@@ -280,6 +285,7 @@ impl quickwit_config::TestableForRegression for SplitMetadata {
             tags: ["234".to_string(), "aaa".to_string()].into_iter().collect(),
             footer_offsets: 1000..2000,
             num_merge_ops: 3,
+            doc_mapping_version: 1,
         }
     }
 
@@ -422,16 +428,17 @@ mod tests {
             footer_offsets: 0..1024,
             delete_opstamp: 0,
             num_merge_ops: 0,
+            doc_mapping_version: 0,
         };
 
-        let expected_output = "SplitMetadata { split_id: \"split-1\", index_uid: \
-                               IndexUid(\"00000000-0000-0000-0000-000000000000:\
-                               00000000000000000000000000\"), partition_id: 0, source_id: \
-                               \"source-1\", node_id: \"node-1\", num_docs: 100, \
-                               uncompressed_docs_size_in_bytes: 1024, time_range: Some(0..=100), \
-                               create_timestamp: 1629867600, maturity: Mature, tags: \
-                               \"{\\\"🐱\\\", \\\"😻\\\", \\\"😼\\\", \\\"😿\\\", and 1 more}\", \
-                               footer_offsets: 0..1024, delete_opstamp: 0, num_merge_ops: 0 }";
+        let expected_output =
+            "SplitMetadata { split_id: \"split-1\", index_uid: \
+             IndexUid(\"00000000-0000-0000-0000-000000000000:00000000000000000000000000\"), \
+             partition_id: 0, source_id: \"source-1\", node_id: \"node-1\", num_docs: 100, \
+             uncompressed_docs_size_in_bytes: 1024, time_range: Some(0..=100), create_timestamp: \
+             1629867600, maturity: Mature, tags: \"{\\\"🐱\\\", \\\"😻\\\", \\\"😼\\\", \
+             \\\"😿\\\", and 1 more}\", footer_offsets: 0..1024, delete_opstamp: 0, \
+             num_merge_ops: 0, doc_mapping_version: 0 }";
 
         assert_eq!(format!("{:?}", split_metadata), expected_output);
     }
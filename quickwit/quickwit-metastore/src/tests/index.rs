@@ -22,14 +22,16 @@
 //  - create_index
 //  - index_exists
 //  - index_metadata
+//  - update_index
 //  - list_indexes
 //  - delete_index
 
 use quickwit_common::rand::append_random_suffix;
-use quickwit_config::IndexConfig;
+use quickwit_config::{IndexConfig, IndexingSettings, RetentionPolicy, SearchSettings};
 use quickwit_proto::metastore::{
     CreateIndexRequest, DeleteIndexRequest, EntityKind, IndexMetadataRequest,
     ListIndexesMetadataRequest, MetastoreError, MetastoreService, StageSplitsRequest,
+    UpdateIndexRequest,
 };
 use quickwit_proto::types::IndexUid;
 
@@ -37,7 +39,7 @@ use super::DefaultForTest;
 use crate::tests::cleanup_index;
 use crate::{
     CreateIndexRequestExt, IndexMetadataResponseExt, ListIndexesMetadataResponseExt,
-    MetastoreServiceExt, SplitMetadata, StageSplitsRequestExt,
+    MetastoreServiceExt, SplitMetadata, StageSplitsRequestExt, UpdateIndexRequestExt,
 };
 
 pub async fn test_metastore_create_index<
@@ -169,6 +171,197 @@ pub async fn test_metastore_index_metadata<
     cleanup_index(&mut metastore, index_uid).await;
 }
 
+pub async fn test_metastore_update_index<MetastoreToTest: MetastoreServiceExt + DefaultForTest>() {
+    let mut metastore = MetastoreToTest::default_for_test().await;
+
+    let index_id = append_random_suffix("test-update-index");
+    let index_uri = format!("ram:///indexes/{index_id}");
+    let index_config = IndexConfig::for_test(&index_id, &index_uri);
+
+    let create_index_request =
+        CreateIndexRequest::try_from_index_config(index_config.clone()).unwrap();
+    let index_uid: IndexUid = metastore
+        .create_index(create_index_request)
+        .await
+        .unwrap()
+        .index_uid
+        .into();
+
+    let new_search_settings = SearchSettings {
+        default_search_fields: vec!["body".to_string(), "owner".to_string()],
+    };
+    let new_retention_policy_opt = Some(RetentionPolicy::new(
+        "90 days".to_string(),
+        "daily".to_string(),
+    ));
+    let new_indexing_settings = IndexingSettings {
+        commit_timeout_secs: 5,
+        ..index_config.indexing_settings.clone()
+    };
+    let update_index_request = UpdateIndexRequest::try_from_updates(
+        index_uid.clone(),
+        &index_config.doc_mapping,
+        &new_search_settings,
+        &new_retention_policy_opt,
+        &new_indexing_settings,
+    )
+    .unwrap();
+    let updated_index_metadata = metastore
+        .update_index(update_index_request)
+        .await
+        .unwrap()
+        .deserialize_index_metadata()
+        .unwrap();
+
+    assert_eq!(
+        updated_index_metadata.index_config.search_settings,
+        new_search_settings
+    );
+    assert_eq!(
+        updated_index_metadata.index_config.retention_policy,
+        new_retention_policy_opt
+    );
+    assert_eq!(
+        updated_index_metadata.index_config.indexing_settings,
+        new_indexing_settings
+    );
+
+    let index_metadata = metastore
+        .index_metadata(IndexMetadataRequest::for_index_id(index_id.to_string()))
+        .await
+        .unwrap()
+        .deserialize_index_metadata()
+        .unwrap();
+    assert_eq!(index_metadata, updated_index_metadata);
+
+    // Updating the index with the same settings is a no-op.
+    let update_index_request = UpdateIndexRequest::try_from_updates(
+        index_uid.clone(),
+        &index_config.doc_mapping,
+        &new_search_settings,
+        &new_retention_policy_opt,
+        &new_indexing_settings,
+    )
+    .unwrap();
+    let index_metadata = metastore
+        .update_index(update_index_request)
+        .await
+        .unwrap()
+        .deserialize_index_metadata()
+        .unwrap();
+    assert_eq!(index_metadata, updated_index_metadata);
+
+    // Removing the retention policy.
+    let update_index_request = UpdateIndexRequest::try_from_updates(
+        index_uid.clone(),
+        &index_config.doc_mapping,
+        &new_search_settings,
+        &None,
+        &new_indexing_settings,
+    )
+    .unwrap();
+    let index_metadata = metastore
+        .update_index(update_index_request)
+        .await
+        .unwrap()
+        .deserialize_index_metadata()
+        .unwrap();
+    assert!(index_metadata.index_config.retention_policy.is_none());
+
+    // Invalid retention policies are rejected and leave the index untouched.
+    let invalid_retention_policy_opt =
+        Some(RetentionPolicy::new("foo".to_string(), "daily".to_string()));
+    let update_index_request = UpdateIndexRequest::try_from_updates(
+        index_uid.clone(),
+        &index_config.doc_mapping,
+        &new_search_settings,
+        &invalid_retention_policy_opt,
+        &new_indexing_settings,
+    )
+    .unwrap();
+    let error = metastore
+        .update_index(update_index_request)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, MetastoreError::InvalidArgument { .. }));
+
+    let index_metadata = metastore
+        .index_metadata(IndexMetadataRequest::for_index_id(index_id.to_string()))
+        .await
+        .unwrap()
+        .deserialize_index_metadata()
+        .unwrap();
+    assert!(index_metadata.index_config.retention_policy.is_none());
+
+    // Appending a field mapping and a tag field bumps the doc mapping version.
+    let mut new_doc_mapping = index_config.doc_mapping.clone();
+    new_doc_mapping.field_mappings.push(
+        serde_json::from_str(r#"{"name": "new_field", "type": "u64", "fast": true}"#).unwrap(),
+    );
+    new_doc_mapping.tag_fields.insert("new_field".to_string());
+
+    let update_index_request = UpdateIndexRequest::try_from_updates(
+        index_uid.clone(),
+        &new_doc_mapping,
+        &new_search_settings,
+        &None,
+        &new_indexing_settings,
+    )
+    .unwrap();
+    let index_metadata = metastore
+        .update_index(update_index_request)
+        .await
+        .unwrap()
+        .deserialize_index_metadata()
+        .unwrap();
+    let updated_doc_mapping = &index_metadata.index_config.doc_mapping;
+    assert_eq!(updated_doc_mapping.doc_mapping_version, 1);
+    assert_eq!(
+        updated_doc_mapping.field_mappings,
+        new_doc_mapping.field_mappings
+    );
+    assert_eq!(updated_doc_mapping.tag_fields, new_doc_mapping.tag_fields);
+    assert_eq!(
+        updated_doc_mapping.field_mapping_versions.get("new_field"),
+        Some(&1)
+    );
+
+    // Removing a field mapping is rejected.
+    let update_index_request = UpdateIndexRequest::try_from_updates(
+        index_uid.clone(),
+        &index_config.doc_mapping,
+        &new_search_settings,
+        &None,
+        &new_indexing_settings,
+    )
+    .unwrap();
+    let error = metastore
+        .update_index(update_index_request)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, MetastoreError::InvalidArgument { .. }));
+
+    // Updating an index that does not exist fails.
+    let update_index_request = UpdateIndexRequest::try_from_updates(
+        IndexUid::new_with_random_ulid(&index_id),
+        &index_config.doc_mapping,
+        &new_search_settings,
+        &None,
+        &new_indexing_settings,
+    )
+    .unwrap();
+    let error = metastore
+        .update_index(update_index_request)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        MetastoreError::NotFound(EntityKind::Index { .. })
+    ));
+
+    cleanup_index(&mut metastore, index_uid).await;
+}
+
 pub async fn test_metastore_list_all_indexes<
     MetastoreToTest: MetastoreServiceExt + DefaultForTest,
 >() {
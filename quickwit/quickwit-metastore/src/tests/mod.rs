@@ -179,6 +179,7 @@ macro_rules! metastore_test_suite {
             //  - create_index
             //  - index_exists
             //  - index_metadata
+            //  - update_index
             //  - list_indexes
             //  - delete_index
 
@@ -209,6 +210,12 @@ macro_rules! metastore_test_suite {
                 $crate::tests::index::test_metastore_index_metadata::<$metastore_type>().await;
             }
 
+            #[tokio::test]
+            async fn test_metastore_update_index() {
+                let _ = tracing_subscriber::fmt::try_init();
+                $crate::tests::index::test_metastore_update_index::<$metastore_type>().await;
+            }
+
             #[tokio::test]
             async fn test_metastore_list_indexes() {
                 let _ = tracing_subscriber::fmt::try_init();
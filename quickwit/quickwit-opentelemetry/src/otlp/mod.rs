@@ -28,6 +28,7 @@ use serde_json::{Number as JsonNumber, Value as JsonValue};
 
 mod logs;
 mod metrics;
+mod otel_metrics;
 mod span_id;
 #[cfg(any(test, feature = "testsuite"))]
 mod test_utils;
@@ -35,6 +36,9 @@ mod trace_id;
 mod traces;
 
 pub use logs::{OtlpGrpcLogsService, OTEL_LOGS_INDEX_ID};
+pub use otel_metrics::{
+    MetricDataPoint, MetricType, OtlpGrpcMetricsService, OTEL_METRICS_INDEX_ID,
+};
 pub use span_id::{SpanId, TryFromSpanIdError};
 #[cfg(any(test, feature = "testsuite"))]
 pub use test_utils::make_resource_spans_for_test;
@@ -48,6 +52,7 @@ pub use traces::{
 
 pub enum OtelSignal {
     Logs,
+    Metrics,
     Traces,
 }
 
@@ -55,6 +60,7 @@ impl OtelSignal {
     pub fn header_name(&self) -> &'static str {
         match self {
             OtelSignal::Logs => "qw-otel-logs-index",
+            OtelSignal::Metrics => "qw-otel-metrics-index",
             OtelSignal::Traces => "qw-otel-traces-index",
         }
     }
@@ -62,6 +68,7 @@ impl OtelSignal {
     pub fn default_index_id(&self) -> &'static str {
         match self {
             OtelSignal::Logs => OTEL_LOGS_INDEX_ID,
+            OtelSignal::Metrics => OTEL_METRICS_INDEX_ID,
             OtelSignal::Traces => OTEL_TRACES_INDEX_ID,
         }
     }
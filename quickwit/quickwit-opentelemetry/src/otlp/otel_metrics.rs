@@ -0,0 +1,792 @@
+// Copyright (C) 2024 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use quickwit_common::uri::Uri;
+use quickwit_config::{load_index_config_from_user_config, ConfigFormat, IndexConfig};
+use quickwit_ingest::{
+    CommitType, DocBatch, DocBatchBuilder, IngestRequest, IngestService, IngestServiceClient,
+};
+use quickwit_proto::opentelemetry::proto::collector::metrics::v1::metrics_service_server::MetricsService;
+use quickwit_proto::opentelemetry::proto::collector::metrics::v1::{
+    ExportMetricsPartialSuccess, ExportMetricsServiceRequest, ExportMetricsServiceResponse,
+};
+use quickwit_proto::opentelemetry::proto::metrics::v1::metric::Data as OtlpMetricData;
+use quickwit_proto::opentelemetry::proto::metrics::v1::number_data_point::Value as OtlpNumberValue;
+use quickwit_proto::opentelemetry::proto::metrics::v1::{
+    AggregationTemporality as OtlpAggregationTemporality,
+    HistogramDataPoint as OtlpHistogramDataPoint, NumberDataPoint as OtlpNumberDataPoint,
+};
+use quickwit_proto::types::IndexId;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use tonic::{Request, Response, Status};
+use tracing::field::Empty;
+use tracing::{error, instrument, Span as RuntimeSpan};
+
+use super::{extract_otel_index_id_from_metadata, is_zero, OtelSignal};
+use crate::otlp::extract_attributes;
+use crate::otlp::metrics::OTLP_SERVICE_METRICS;
+
+pub const OTEL_METRICS_INDEX_ID: &str = "otel-metrics-v0";
+
+const OTEL_METRICS_INDEX_CONFIG: &str = r#"
+version: 0.7
+
+index_id: ${INDEX_ID}
+
+doc_mapping:
+  mode: strict
+  field_mappings:
+    - name: timestamp_nanos
+      type: datetime
+      input_formats: [unix_timestamp]
+      output_format: unix_timestamp_nanos
+      indexed: false
+      fast: true
+      fast_precision: milliseconds
+    - name: start_timestamp_nanos
+      type: datetime
+      input_formats: [unix_timestamp]
+      output_format: unix_timestamp_nanos
+      indexed: false
+    - name: service_name
+      type: text
+      tokenizer: raw
+      fast: true
+    - name: metric_name
+      type: text
+      tokenizer: raw
+      fast: true
+    - name: metric_type
+      type: text
+      tokenizer: raw
+      fast: true
+    - name: description
+      type: text
+      indexed: false
+    - name: unit
+      type: text
+      tokenizer: raw
+      fast: true
+    - name: attributes
+      type: json
+      tokenizer: raw
+      fast: true
+    - name: resource_attributes
+      type: json
+      tokenizer: raw
+      fast: true
+    - name: resource_dropped_attributes_count
+      type: u64
+      indexed: false
+    - name: scope_name
+      type: text
+      indexed: false
+    - name: scope_version
+      type: text
+      indexed: false
+    - name: flags
+      type: u64
+      indexed: false
+    - name: value
+      type: f64
+      fast: true
+    - name: is_monotonic
+      type: bool
+      fast: true
+    - name: aggregation_temporality
+      type: text
+      tokenizer: raw
+      fast: true
+    - name: count
+      type: u64
+      fast: true
+    - name: sum
+      type: f64
+      fast: true
+    - name: min
+      type: f64
+      fast: true
+    - name: max
+      type: f64
+      fast: true
+    - name: bucket_counts
+      type: array<u64>
+      indexed: false
+    - name: explicit_bounds
+      type: array<f64>
+      indexed: false
+
+  timestamp_field: timestamp_nanos
+
+indexing_settings:
+  commit_timeout_secs: 5
+
+search_settings:
+  default_search_fields: [metric_name]
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricType {
+    Gauge,
+    Sum,
+    Histogram,
+}
+
+/// A single metric data point. Gauges, sums, and histograms are flattened into one row per data
+/// point.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricDataPoint {
+    pub timestamp_nanos: u64,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_timestamp_nanos: Option<u64>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub service_name: String,
+    pub metric_name: String,
+    pub metric_type: MetricType,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub attributes: HashMap<String, JsonValue>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub resource_attributes: HashMap<String, JsonValue>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_zero")]
+    pub resource_dropped_attributes_count: u32,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope_name: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope_version: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_zero")]
+    pub flags: u32,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<f64>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_monotonic: Option<bool>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aggregation_temporality: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<u64>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sum: Option<f64>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub bucket_counts: Vec<u64>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub explicit_bounds: Vec<f64>,
+}
+
+/// Metric-level fields shared by all the data points of a metric.
+struct MetricContext<'a> {
+    service_name: &'a str,
+    metric_name: &'a str,
+    description: Option<&'a String>,
+    unit: Option<&'a String>,
+    resource_attributes: &'a HashMap<String, JsonValue>,
+    resource_dropped_attributes_count: u32,
+    scope_name: Option<&'a String>,
+    scope_version: Option<&'a String>,
+}
+
+impl MetricContext<'_> {
+    fn new_data_point(
+        &self,
+        metric_type: MetricType,
+        timestamp_nanos: u64,
+        start_timestamp_nanos: u64,
+        attributes: HashMap<String, JsonValue>,
+        flags: u32,
+    ) -> MetricDataPoint {
+        MetricDataPoint {
+            timestamp_nanos,
+            start_timestamp_nanos: Some(start_timestamp_nanos).filter(|nanos| *nanos != 0),
+            service_name: self.service_name.to_string(),
+            metric_name: self.metric_name.to_string(),
+            metric_type,
+            description: self.description.cloned(),
+            unit: self.unit.cloned(),
+            attributes,
+            resource_attributes: self.resource_attributes.clone(),
+            resource_dropped_attributes_count: self.resource_dropped_attributes_count,
+            scope_name: self.scope_name.cloned(),
+            scope_version: self.scope_version.cloned(),
+            flags,
+            value: None,
+            is_monotonic: None,
+            aggregation_temporality: None,
+            count: None,
+            sum: None,
+            min: None,
+            max: None,
+            bucket_counts: Vec::new(),
+            explicit_bounds: Vec::new(),
+        }
+    }
+
+    fn number_data_point(
+        &self,
+        metric_type: MetricType,
+        data_point: OtlpNumberDataPoint,
+    ) -> MetricDataPoint {
+        let value = match data_point.value {
+            Some(OtlpNumberValue::AsDouble(value)) => Some(value),
+            Some(OtlpNumberValue::AsInt(value)) => Some(value as f64),
+            None => None,
+        };
+        let mut metric_data_point = self.new_data_point(
+            metric_type,
+            data_point.time_unix_nano,
+            data_point.start_time_unix_nano,
+            extract_attributes(data_point.attributes),
+            data_point.flags,
+        );
+        metric_data_point.value = value;
+        metric_data_point
+    }
+
+    fn histogram_data_point(&self, data_point: OtlpHistogramDataPoint) -> MetricDataPoint {
+        let mut metric_data_point = self.new_data_point(
+            MetricType::Histogram,
+            data_point.time_unix_nano,
+            data_point.start_time_unix_nano,
+            extract_attributes(data_point.attributes),
+            data_point.flags,
+        );
+        metric_data_point.count = Some(data_point.count);
+        metric_data_point.sum = data_point.sum;
+        metric_data_point.min = data_point.min;
+        metric_data_point.max = data_point.max;
+        metric_data_point.bucket_counts = data_point.bucket_counts;
+        metric_data_point.explicit_bounds = data_point.explicit_bounds;
+        metric_data_point
+    }
+}
+
+fn aggregation_temporality_name(aggregation_temporality: i32) -> Option<String> {
+    match OtlpAggregationTemporality::from_i32(aggregation_temporality)? {
+        OtlpAggregationTemporality::Unspecified => None,
+        OtlpAggregationTemporality::Delta => Some("delta".to_string()),
+        OtlpAggregationTemporality::Cumulative => Some("cumulative".to_string()),
+    }
+}
+
+struct ParsedMetrics {
+    doc_batch: DocBatch,
+    num_data_points: u64,
+    num_parse_errors: u64,
+    error_message: String,
+}
+
+#[derive(Clone)]
+pub struct OtlpGrpcMetricsService {
+    ingest_service: IngestServiceClient,
+}
+
+impl OtlpGrpcMetricsService {
+    pub fn new(ingest_service: IngestServiceClient) -> Self {
+        Self { ingest_service }
+    }
+
+    pub fn index_config(default_index_root_uri: &Uri) -> anyhow::Result<IndexConfig> {
+        let index_config_str =
+            OTEL_METRICS_INDEX_CONFIG.replace("${INDEX_ID}", OTEL_METRICS_INDEX_ID);
+        let index_config = load_index_config_from_user_config(
+            ConfigFormat::Yaml,
+            index_config_str.as_bytes(),
+            default_index_root_uri,
+        )?;
+        Ok(index_config)
+    }
+
+    async fn export_inner(
+        &mut self,
+        request: ExportMetricsServiceRequest,
+        index_id: IndexId,
+        labels: [&str; 4],
+    ) -> Result<ExportMetricsServiceResponse, Status> {
+        let ParsedMetrics {
+            doc_batch,
+            num_data_points,
+            num_parse_errors,
+            error_message,
+        } = tokio::task::spawn_blocking({
+            let parent_span = RuntimeSpan::current();
+            || Self::parse_metrics(request, parent_span, index_id)
+        })
+        .await
+        .map_err(|join_error| {
+            error!(error=?join_error, "failed to parse metric data points");
+            Status::internal("failed to parse metric data points")
+        })??;
+        if num_data_points == num_parse_errors {
+            return Err(tonic::Status::internal(error_message));
+        }
+        let num_bytes = doc_batch.num_bytes() as u64;
+        self.store_metrics(doc_batch).await?;
+
+        OTLP_SERVICE_METRICS
+            .ingested_data_points_total
+            .with_label_values(labels)
+            .inc_by(num_data_points - num_parse_errors);
+        OTLP_SERVICE_METRICS
+            .ingested_bytes_total
+            .with_label_values(labels)
+            .inc_by(num_bytes);
+
+        let response = ExportMetricsServiceResponse {
+            // `rejected_data_points=0` and `error_message=""` is consided a "full" success.
+            partial_success: Some(ExportMetricsPartialSuccess {
+                rejected_data_points: num_parse_errors as i64,
+                error_message,
+            }),
+        };
+        Ok(response)
+    }
+
+    /// Flattens the gauges, sums, and histograms of the request into one row per data point. Data
+    /// points without a timestamp and data points of unsupported metric types (exponential
+    /// histograms and summaries) are rejected.
+    #[instrument(skip_all, parent = parent_span, fields(num_data_points = Empty, num_bytes = Empty, num_parse_errors = Empty))]
+    fn parse_metrics(
+        request: ExportMetricsServiceRequest,
+        parent_span: RuntimeSpan,
+        index_id: IndexId,
+    ) -> Result<ParsedMetrics, Status> {
+        let mut data_points = Vec::new();
+        let mut num_data_points = 0;
+        let mut num_parse_errors = 0;
+        let mut error_message = String::new();
+
+        for resource_metrics in request.resource_metrics {
+            let mut resource_attributes = extract_attributes(
+                resource_metrics
+                    .resource
+                    .clone()
+                    .map(|rsrc| rsrc.attributes)
+                    .unwrap_or_else(Vec::new),
+            );
+            let resource_dropped_attributes_count = resource_metrics
+                .resource
+                .map(|rsrc| rsrc.dropped_attributes_count)
+                .unwrap_or(0);
+
+            let service_name = match resource_attributes.remove("service.name") {
+                Some(JsonValue::String(value)) => value.to_string(),
+                _ => "unknown_service".to_string(),
+            };
+            for scope_metrics in resource_metrics.scope_metrics {
+                let scope_name = scope_metrics
+                    .scope
+                    .as_ref()
+                    .map(|scope| &scope.name)
+                    .filter(|name| !name.is_empty());
+                let scope_version = scope_metrics
+                    .scope
+                    .as_ref()
+                    .map(|scope| &scope.version)
+                    .filter(|version| !version.is_empty());
+
+                for metric in scope_metrics.metrics {
+                    let metric_context = MetricContext {
+                        service_name: &service_name,
+                        metric_name: &metric.name,
+                        description: Some(&metric.description).filter(|desc| !desc.is_empty()),
+                        unit: Some(&metric.unit).filter(|unit| !unit.is_empty()),
+                        resource_attributes: &resource_attributes,
+                        resource_dropped_attributes_count,
+                        scope_name,
+                        scope_version,
+                    };
+                    let metric_data_points: Vec<MetricDataPoint> = match metric.data {
+                        Some(OtlpMetricData::Gauge(gauge)) => gauge
+                            .data_points
+                            .into_iter()
+                            .map(|data_point| {
+                                metric_context.number_data_point(MetricType::Gauge, data_point)
+                            })
+                            .collect(),
+                        Some(OtlpMetricData::Sum(sum)) => {
+                            let aggregation_temporality =
+                                aggregation_temporality_name(sum.aggregation_temporality);
+                            sum.data_points
+                                .into_iter()
+                                .map(|data_point| {
+                                    let mut metric_data_point = metric_context
+                                        .number_data_point(MetricType::Sum, data_point);
+                                    metric_data_point.is_monotonic = Some(sum.is_monotonic);
+                                    metric_data_point.aggregation_temporality =
+                                        aggregation_temporality.clone();
+                                    metric_data_point
+                                })
+                                .collect()
+                        }
+                        Some(OtlpMetricData::Histogram(histogram)) => {
+                            let aggregation_temporality =
+                                aggregation_temporality_name(histogram.aggregation_temporality);
+                            histogram
+                                .data_points
+                                .into_iter()
+                                .map(|data_point| {
+                                    let mut metric_data_point =
+                                        metric_context.histogram_data_point(data_point);
+                                    metric_data_point.aggregation_temporality =
+                                        aggregation_temporality.clone();
+                                    metric_data_point
+                                })
+                                .collect()
+                        }
+                        Some(OtlpMetricData::ExponentialHistogram(exponential_histogram)) => {
+                            let num_rejected = exponential_histogram.data_points.len() as u64;
+                            num_data_points += num_rejected;
+                            num_parse_errors += num_rejected;
+                            error_message = format!(
+                                "exponential histograms are not supported (metric `{}`)",
+                                metric.name
+                            );
+                            continue;
+                        }
+                        Some(OtlpMetricData::Summary(summary)) => {
+                            let num_rejected = summary.data_points.len() as u64;
+                            num_data_points += num_rejected;
+                            num_parse_errors += num_rejected;
+                            error_message =
+                                format!("summaries are not supported (metric `{}`)", metric.name);
+                            continue;
+                        }
+                        None => continue,
+                    };
+                    for metric_data_point in metric_data_points {
+                        num_data_points += 1;
+
+                        if metric_data_point.timestamp_nanos == 0 {
+                            num_parse_errors += 1;
+                            error_message = format!(
+                                "data point of metric `{}` is missing a timestamp",
+                                metric_data_point.metric_name
+                            );
+                            continue;
+                        }
+                        data_points.push(metric_data_point);
+                    }
+                }
+            }
+        }
+        data_points.sort_by(|left, right| {
+            left.service_name
+                .cmp(&right.service_name)
+                .then_with(|| left.metric_name.cmp(&right.metric_name))
+                .then(left.timestamp_nanos.cmp(&right.timestamp_nanos))
+        });
+        let mut doc_batch = DocBatchBuilder::new(index_id).json_writer();
+        for data_point in data_points {
+            if let Err(error) = doc_batch.ingest_doc(&data_point) {
+                error!(error=?error, "failed to JSON serialize metric data point");
+                error_message = format!("failed to JSON serialize metric data point: {error:?}");
+                num_parse_errors += 1;
+            }
+        }
+        let doc_batch = doc_batch.build();
+        let current_span = RuntimeSpan::current();
+        current_span.record("num_data_points", num_data_points);
+        current_span.record("num_bytes", doc_batch.num_bytes());
+        current_span.record("num_parse_errors", num_parse_errors);
+
+        let parsed_metrics = ParsedMetrics {
+            doc_batch,
+            num_data_points,
+            num_parse_errors,
+            error_message,
+        };
+        Ok(parsed_metrics)
+    }
+
+    #[instrument(skip_all, fields(num_bytes = doc_batch.num_bytes()))]
+    async fn store_metrics(&mut self, doc_batch: DocBatch) -> Result<(), tonic::Status> {
+        let ingest_request = IngestRequest {
+            doc_batches: vec![doc_batch],
+            commit: CommitType::Auto.into(),
+        };
+        self.ingest_service.ingest(ingest_request).await?;
+        Ok(())
+    }
+
+    async fn export_instrumented(
+        &mut self,
+        request: ExportMetricsServiceRequest,
+        index_id: IndexId,
+    ) -> Result<ExportMetricsServiceResponse, Status> {
+        let start = std::time::Instant::now();
+
+        let labels = ["metrics", &index_id, "grpc", "protobuf"];
+
+        OTLP_SERVICE_METRICS
+            .requests_total
+            .with_label_values(labels)
+            .inc();
+        let (export_res, is_error) =
+            match self.export_inner(request, index_id.clone(), labels).await {
+                ok @ Ok(_) => (ok, "false"),
+                err @ Err(_) => {
+                    OTLP_SERVICE_METRICS
+                        .request_errors_total
+                        .with_label_values(labels)
+                        .inc();
+                    (err, "true")
+                }
+            };
+        let elapsed = start.elapsed().as_secs_f64();
+        let labels = ["metrics", &index_id, "grpc", "protobuf", is_error];
+        OTLP_SERVICE_METRICS
+            .request_duration_seconds
+            .with_label_values(labels)
+            .observe(elapsed);
+
+        export_res
+    }
+}
+
+#[async_trait]
+impl MetricsService for OtlpGrpcMetricsService {
+    #[instrument(name = "ingest_metrics", skip_all)]
+    async fn export(
+        &self,
+        request: Request<ExportMetricsServiceRequest>,
+    ) -> Result<Response<ExportMetricsServiceResponse>, Status> {
+        let index_id =
+            extract_otel_index_id_from_metadata(request.metadata(), &OtelSignal::Metrics)?;
+        let request = request.into_inner();
+        self.clone()
+            .export_instrumented(request, index_id)
+            .await
+            .map(Response::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_ingest::DocCommand;
+    use quickwit_metastore::{metastore_for_test, CreateIndexRequestExt};
+    use quickwit_proto::metastore::{CreateIndexRequest, MetastoreService};
+    use quickwit_proto::opentelemetry::proto::common::v1::any_value::Value as OtlpAnyValueValue;
+    use quickwit_proto::opentelemetry::proto::common::v1::{AnyValue, KeyValue};
+    use quickwit_proto::opentelemetry::proto::metrics::v1::{
+        ExponentialHistogram, Gauge, Histogram, Metric, ResourceMetrics, ScopeMetrics, Sum,
+    };
+    use quickwit_proto::opentelemetry::proto::resource::v1::Resource;
+
+    use super::*;
+
+    #[test]
+    fn test_index_config_is_valid() {
+        let index_config =
+            OtlpGrpcMetricsService::index_config(&Uri::for_test("ram:///indexes")).unwrap();
+        assert_eq!(index_config.index_id, OTEL_METRICS_INDEX_ID);
+    }
+
+    #[tokio::test]
+    async fn test_create_index() {
+        let mut metastore = metastore_for_test();
+        let index_config =
+            OtlpGrpcMetricsService::index_config(&Uri::for_test("ram:///indexes")).unwrap();
+        let create_index_request = CreateIndexRequest::try_from_index_config(index_config).unwrap();
+        metastore.create_index(create_index_request).await.unwrap();
+    }
+
+    fn make_key_value(key: &str, value: OtlpAnyValueValue) -> KeyValue {
+        KeyValue {
+            key: key.to_string(),
+            value: Some(AnyValue { value: Some(value) }),
+        }
+    }
+
+    fn make_number_data_point(time_unix_nano: u64, value: OtlpNumberValue) -> OtlpNumberDataPoint {
+        OtlpNumberDataPoint {
+            attributes: vec![make_key_value(
+                "host",
+                OtlpAnyValueValue::StringValue("host-1".to_string()),
+            )],
+            start_time_unix_nano: 0,
+            time_unix_nano,
+            exemplars: Vec::new(),
+            flags: 0,
+            value: Some(value),
+        }
+    }
+
+    fn make_metric(name: &str, data: OtlpMetricData) -> Metric {
+        Metric {
+            name: name.to_string(),
+            description: String::new(),
+            unit: "1".to_string(),
+            data: Some(data),
+        }
+    }
+
+    #[test]
+    fn test_parse_metrics() {
+        let gauge = make_metric(
+            "cpu.usage",
+            OtlpMetricData::Gauge(Gauge {
+                data_points: vec![
+                    make_number_data_point(2_000, OtlpNumberValue::AsDouble(0.5)),
+                    make_number_data_point(1_000, OtlpNumberValue::AsDouble(0.25)),
+                ],
+            }),
+        );
+        let sum = make_metric(
+            "http.requests",
+            OtlpMetricData::Sum(Sum {
+                data_points: vec![
+                    make_number_data_point(1_000, OtlpNumberValue::AsInt(42)),
+                    // Data points without a timestamp are rejected.
+                    make_number_data_point(0, OtlpNumberValue::AsInt(43)),
+                ],
+                aggregation_temporality: OtlpAggregationTemporality::Cumulative as i32,
+                is_monotonic: true,
+            }),
+        );
+        let histogram = make_metric(
+            "http.duration",
+            OtlpMetricData::Histogram(Histogram {
+                data_points: vec![OtlpHistogramDataPoint {
+                    attributes: Vec::new(),
+                    start_time_unix_nano: 500,
+                    time_unix_nano: 1_000,
+                    count: 3,
+                    sum: Some(6.0),
+                    bucket_counts: vec![1, 2],
+                    explicit_bounds: vec![2.5],
+                    exemplars: Vec::new(),
+                    flags: 0,
+                    min: Some(1.0),
+                    max: Some(3.0),
+                }],
+                aggregation_temporality: OtlpAggregationTemporality::Delta as i32,
+            }),
+        );
+        let exponential_histogram = make_metric(
+            "http.size",
+            OtlpMetricData::ExponentialHistogram(ExponentialHistogram {
+                data_points: vec![Default::default()],
+                aggregation_temporality: OtlpAggregationTemporality::Delta as i32,
+            }),
+        );
+        let request = ExportMetricsServiceRequest {
+            resource_metrics: vec![ResourceMetrics {
+                resource: Some(Resource {
+                    attributes: vec![make_key_value(
+                        "service.name",
+                        OtlpAnyValueValue::StringValue("quickwit".to_string()),
+                    )],
+                    dropped_attributes_count: 0,
+                }),
+                scope_metrics: vec![ScopeMetrics {
+                    scope: None,
+                    metrics: vec![gauge, sum, histogram, exponential_histogram],
+                    schema_url: String::new(),
+                }],
+                schema_url: String::new(),
+            }],
+        };
+        let ParsedMetrics {
+            doc_batch,
+            num_data_points,
+            num_parse_errors,
+            error_message,
+        } = OtlpGrpcMetricsService::parse_metrics(
+            request,
+            RuntimeSpan::current(),
+            OTEL_METRICS_INDEX_ID.to_string(),
+        )
+        .unwrap();
+        assert_eq!(num_data_points, 6);
+        assert_eq!(num_parse_errors, 2);
+        assert!(!error_message.is_empty());
+
+        let data_points: Vec<MetricDataPoint> = doc_batch
+            .iter()
+            .map(|doc_command| {
+                let DocCommand::Ingest { payload } = doc_command else {
+                    panic!("expected an ingest command");
+                };
+                serde_json::from_slice(&payload).unwrap()
+            })
+            .collect();
+        assert_eq!(data_points.len(), 4);
+
+        assert_eq!(data_points[0].metric_name, "cpu.usage");
+        assert_eq!(data_points[0].metric_type, MetricType::Gauge);
+        assert_eq!(data_points[0].timestamp_nanos, 1_000);
+        assert_eq!(data_points[0].service_name, "quickwit");
+        assert_eq!(data_points[0].value, Some(0.25));
+        assert_eq!(data_points[0].unit.as_deref(), Some("1"));
+        assert_eq!(data_points[0].attributes["host"], "host-1");
+        assert!(data_points[0].resource_attributes.is_empty());
+
+        assert_eq!(data_points[1].metric_name, "cpu.usage");
+        assert_eq!(data_points[1].timestamp_nanos, 2_000);
+
+        assert_eq!(data_points[2].metric_name, "http.duration");
+        assert_eq!(data_points[2].metric_type, MetricType::Histogram);
+        assert_eq!(data_points[2].start_timestamp_nanos, Some(500));
+        assert_eq!(data_points[2].count, Some(3));
+        assert_eq!(data_points[2].sum, Some(6.0));
+        assert_eq!(data_points[2].bucket_counts, vec![1, 2]);
+        assert_eq!(data_points[2].explicit_bounds, vec![2.5]);
+        assert_eq!(
+            data_points[2].aggregation_temporality.as_deref(),
+            Some("delta")
+        );
+
+        assert_eq!(data_points[3].metric_name, "http.requests");
+        assert_eq!(data_points[3].metric_type, MetricType::Sum);
+        assert_eq!(data_points[3].value, Some(42.0));
+        assert_eq!(data_points[3].is_monotonic, Some(true));
+        assert_eq!(
+            data_points[3].aggregation_temporality.as_deref(),
+            Some("cumulative")
+        );
+    }
+}
@@ -131,11 +131,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .enum_attribute(".", "#[serde(rename_all=\"snake_case\")]")
         .type_attribute(".", "#[derive(Serialize, Deserialize, utoipa::ToSchema)]")
         .type_attribute("PartialHit", "#[derive(Eq, Hash)]")
-        .type_attribute("PartialHit.sort_value", "#[derive(Copy)]")
         .type_attribute("SearchRequest", "#[derive(Eq, Hash)]")
         .type_attribute("ListFieldSerialized", "#[derive(Eq)]")
         .type_attribute("SortByValue", "#[derive(Ord, PartialOrd)]")
         .type_attribute("SortField", "#[derive(Eq, Hash)]")
+        .type_attribute("SnippetOptions", "#[derive(Eq, Hash)]")
+        .type_attribute("CollapseOptions", "#[derive(Eq, Hash)]")
         .out_dir("src/codegen/quickwit")
         .compile_with_config(prost_config, &["protos/quickwit/search.proto"], &["protos"])?;
 
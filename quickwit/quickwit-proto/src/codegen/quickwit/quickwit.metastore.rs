@@ -19,6 +19,21 @@ pub struct CreateIndexResponse {
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateIndexRequest {
+    #[prost(string, tag = "1")]
+    pub index_uid: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub search_settings_json: ::prost::alloc::string::String,
+    #[prost(string, optional, tag = "3")]
+    pub retention_policy_json: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, tag = "4")]
+    pub indexing_settings_json: ::prost::alloc::string::String,
+    #[prost(string, tag = "5")]
+    pub doc_mapping_json: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListIndexesMetadataRequest {
     #[prost(string, repeated, tag = "2")]
     pub index_id_patterns: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
@@ -406,6 +421,7 @@ pub enum SourceType {
     Pulsar = 9,
     Vec = 10,
     Void = 11,
+    StoragePrefix = 12,
 }
 impl SourceType {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -426,6 +442,7 @@ impl SourceType {
             SourceType::Pulsar => "SOURCE_TYPE_PULSAR",
             SourceType::Vec => "SOURCE_TYPE_VEC",
             SourceType::Void => "SOURCE_TYPE_VOID",
+            SourceType::StoragePrefix => "SOURCE_TYPE_STORAGE_PREFIX",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -443,6 +460,7 @@ impl SourceType {
             "SOURCE_TYPE_PULSAR" => Some(Self::Pulsar),
             "SOURCE_TYPE_VEC" => Some(Self::Vec),
             "SOURCE_TYPE_VOID" => Some(Self::Void),
+            "SOURCE_TYPE_STORAGE_PREFIX" => Some(Self::StoragePrefix),
             _ => None,
         }
     }
@@ -462,6 +480,11 @@ impl PrometheusLabels<1> for IndexMetadataRequest {
         OwnedPrometheusLabels::new([std::borrow::Cow::Borrowed("index_metadata")])
     }
 }
+impl PrometheusLabels<1> for UpdateIndexRequest {
+    fn labels(&self) -> OwnedPrometheusLabels<1usize> {
+        OwnedPrometheusLabels::new([std::borrow::Cow::Borrowed("update_index")])
+    }
+}
 impl PrometheusLabels<1> for ListIndexesMetadataRequest {
     fn labels(&self) -> OwnedPrometheusLabels<1usize> {
         OwnedPrometheusLabels::new([std::borrow::Cow::Borrowed("list_indexes_metadata")])
@@ -587,6 +610,12 @@ pub trait MetastoreService: std::fmt::Debug + dyn_clone::DynClone + Send + Sync
         &mut self,
         request: IndexMetadataRequest,
     ) -> crate::metastore::MetastoreResult<IndexMetadataResponse>;
+    /// Updates the mutable settings of an index (search settings, retention policy and indexing settings) and
+    /// returns the updated `IndexMetadata`.
+    async fn update_index(
+        &mut self,
+        request: UpdateIndexRequest,
+    ) -> crate::metastore::MetastoreResult<IndexMetadataResponse>;
     /// Gets an indexes metadatas.
     async fn list_indexes_metadata(
         &mut self,
@@ -796,6 +825,12 @@ impl MetastoreService for MetastoreServiceClient {
     ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
         self.inner.index_metadata(request).await
     }
+    async fn update_index(
+        &mut self,
+        request: UpdateIndexRequest,
+    ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
+        self.inner.update_index(request).await
+    }
     async fn list_indexes_metadata(
         &mut self,
         request: ListIndexesMetadataRequest,
@@ -944,6 +979,12 @@ pub mod metastore_service_mock {
         ) -> crate::metastore::MetastoreResult<super::IndexMetadataResponse> {
             self.inner.lock().await.index_metadata(request).await
         }
+        async fn update_index(
+            &mut self,
+            request: super::UpdateIndexRequest,
+        ) -> crate::metastore::MetastoreResult<super::IndexMetadataResponse> {
+            self.inner.lock().await.update_index(request).await
+        }
         async fn list_indexes_metadata(
             &mut self,
             request: super::ListIndexesMetadataRequest,
@@ -1119,6 +1160,22 @@ impl tower::Service<IndexMetadataRequest> for Box<dyn MetastoreService> {
         Box::pin(fut)
     }
 }
+impl tower::Service<UpdateIndexRequest> for Box<dyn MetastoreService> {
+    type Response = IndexMetadataResponse;
+    type Error = crate::metastore::MetastoreError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: UpdateIndexRequest) -> Self::Future {
+        let mut svc = self.clone();
+        let fut = async move { svc.update_index(request).await };
+        Box::pin(fut)
+    }
+}
 impl tower::Service<ListIndexesMetadataRequest> for Box<dyn MetastoreService> {
     type Response = ListIndexesMetadataResponse;
     type Error = crate::metastore::MetastoreError;
@@ -1453,6 +1510,11 @@ struct MetastoreServiceTowerServiceStack {
         IndexMetadataResponse,
         crate::metastore::MetastoreError,
     >,
+    update_index_svc: quickwit_common::tower::BoxService<
+        UpdateIndexRequest,
+        IndexMetadataResponse,
+        crate::metastore::MetastoreError,
+    >,
     list_indexes_metadata_svc: quickwit_common::tower::BoxService<
         ListIndexesMetadataRequest,
         ListIndexesMetadataResponse,
@@ -1560,6 +1622,7 @@ impl Clone for MetastoreServiceTowerServiceStack {
             inner: self.inner.clone(),
             create_index_svc: self.create_index_svc.clone(),
             index_metadata_svc: self.index_metadata_svc.clone(),
+            update_index_svc: self.update_index_svc.clone(),
             list_indexes_metadata_svc: self.list_indexes_metadata_svc.clone(),
             delete_index_svc: self.delete_index_svc.clone(),
             list_splits_svc: self.list_splits_svc.clone(),
@@ -1599,6 +1662,12 @@ impl MetastoreService for MetastoreServiceTowerServiceStack {
     ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
         self.index_metadata_svc.ready().await?.call(request).await
     }
+    async fn update_index(
+        &mut self,
+        request: UpdateIndexRequest,
+    ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
+        self.update_index_svc.ready().await?.call(request).await
+    }
     async fn list_indexes_metadata(
         &mut self,
         request: ListIndexesMetadataRequest,
@@ -1746,6 +1815,16 @@ type IndexMetadataLayer = quickwit_common::tower::BoxLayer<
     IndexMetadataResponse,
     crate::metastore::MetastoreError,
 >;
+type UpdateIndexLayer = quickwit_common::tower::BoxLayer<
+    quickwit_common::tower::BoxService<
+        UpdateIndexRequest,
+        IndexMetadataResponse,
+        crate::metastore::MetastoreError,
+    >,
+    UpdateIndexRequest,
+    IndexMetadataResponse,
+    crate::metastore::MetastoreError,
+>;
 type ListIndexesMetadataLayer = quickwit_common::tower::BoxLayer<
     quickwit_common::tower::BoxService<
         ListIndexesMetadataRequest,
@@ -1950,6 +2029,7 @@ type ListShardsLayer = quickwit_common::tower::BoxLayer<
 pub struct MetastoreServiceTowerLayerStack {
     create_index_layers: Vec<CreateIndexLayer>,
     index_metadata_layers: Vec<IndexMetadataLayer>,
+    update_index_layers: Vec<UpdateIndexLayer>,
     list_indexes_metadata_layers: Vec<ListIndexesMetadataLayer>,
     delete_index_layers: Vec<DeleteIndexLayer>,
     list_splits_layers: Vec<ListSplitsLayer>,
@@ -2024,6 +2104,31 @@ impl MetastoreServiceTowerLayerStack {
                 crate::metastore::MetastoreError,
             >,
         >>::Service as tower::Service<IndexMetadataRequest>>::Future: Send + 'static,
+        L: tower::Layer<
+                quickwit_common::tower::BoxService<
+                    UpdateIndexRequest,
+                    IndexMetadataResponse,
+                    crate::metastore::MetastoreError,
+                >,
+            > + Clone + Send + Sync + 'static,
+        <L as tower::Layer<
+            quickwit_common::tower::BoxService<
+                UpdateIndexRequest,
+                IndexMetadataResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >>::Service: tower::Service<
+                UpdateIndexRequest,
+                Response = IndexMetadataResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <<L as tower::Layer<
+            quickwit_common::tower::BoxService<
+                UpdateIndexRequest,
+                IndexMetadataResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >>::Service as tower::Service<UpdateIndexRequest>>::Future: Send + 'static,
         L: tower::Layer<
                 quickwit_common::tower::BoxService<
                     ListIndexesMetadataRequest,
@@ -2537,6 +2642,8 @@ impl MetastoreServiceTowerLayerStack {
             .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
         self.index_metadata_layers
             .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self.update_index_layers
+            .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
         self.list_indexes_metadata_layers
             .push(quickwit_common::tower::BoxLayer::new(layer.clone()));
         self.delete_index_layers
@@ -2617,6 +2724,25 @@ impl MetastoreServiceTowerLayerStack {
         self.index_metadata_layers.push(quickwit_common::tower::BoxLayer::new(layer));
         self
     }
+    pub fn stack_update_index_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<
+                quickwit_common::tower::BoxService<
+                    UpdateIndexRequest,
+                    IndexMetadataResponse,
+                    crate::metastore::MetastoreError,
+                >,
+            > + Send + Sync + 'static,
+        L::Service: tower::Service<
+                UpdateIndexRequest,
+                Response = IndexMetadataResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<UpdateIndexRequest>>::Future: Send + 'static,
+    {
+        self.update_index_layers.push(quickwit_common::tower::BoxLayer::new(layer));
+        self
+    }
     pub fn stack_list_indexes_metadata_layer<L>(mut self, layer: L) -> Self
     where
         L: tower::Layer<
@@ -3073,6 +3199,14 @@ impl MetastoreServiceTowerLayerStack {
                 quickwit_common::tower::BoxService::new(boxed_instance.clone()),
                 |svc, layer| layer.layer(svc),
             );
+        let update_index_svc = self
+            .update_index_layers
+            .into_iter()
+            .rev()
+            .fold(
+                quickwit_common::tower::BoxService::new(boxed_instance.clone()),
+                |svc, layer| layer.layer(svc),
+            );
         let list_indexes_metadata_svc = self
             .list_indexes_metadata_layers
             .into_iter()
@@ -3237,6 +3371,7 @@ impl MetastoreServiceTowerLayerStack {
             inner: boxed_instance.clone(),
             create_index_svc,
             index_metadata_svc,
+            update_index_svc,
             list_indexes_metadata_svc,
             delete_index_svc,
             list_splits_svc,
@@ -3345,6 +3480,12 @@ where
             Error = crate::metastore::MetastoreError,
             Future = BoxFuture<IndexMetadataResponse, crate::metastore::MetastoreError>,
         >
+        + tower::Service<
+            UpdateIndexRequest,
+            Response = IndexMetadataResponse,
+            Error = crate::metastore::MetastoreError,
+            Future = BoxFuture<IndexMetadataResponse, crate::metastore::MetastoreError>,
+        >
         + tower::Service<
             ListIndexesMetadataRequest,
             Response = ListIndexesMetadataResponse,
@@ -3490,6 +3631,12 @@ where
     ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
         self.call(request).await
     }
+    async fn update_index(
+        &mut self,
+        request: UpdateIndexRequest,
+    ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
+        self.call(request).await
+    }
     async fn list_indexes_metadata(
         &mut self,
         request: ListIndexesMetadataRequest,
@@ -3677,6 +3824,16 @@ where
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
     }
+    async fn update_index(
+        &mut self,
+        request: UpdateIndexRequest,
+    ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
+        self.inner
+            .update_index(request)
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|error| error.into())
+    }
     async fn list_indexes_metadata(
         &mut self,
         request: ListIndexesMetadataRequest,
@@ -3934,6 +4091,17 @@ for MetastoreServiceGrpcServerAdapter {
             .map(tonic::Response::new)
             .map_err(|error| error.into())
     }
+    async fn update_index(
+        &self,
+        request: tonic::Request<UpdateIndexRequest>,
+    ) -> Result<tonic::Response<IndexMetadataResponse>, tonic::Status> {
+        self.inner
+            .clone()
+            .update_index(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(|error| error.into())
+    }
     async fn list_indexes_metadata(
         &self,
         request: tonic::Request<ListIndexesMetadataRequest>,
@@ -4351,6 +4519,35 @@ pub mod metastore_service_grpc_client {
                 );
             self.inner.unary(req, path, codec).await
         }
+        /// Updates the mutable settings of an index (search settings, retention policy and indexing settings) and
+        /// returns the updated `IndexMetadata`.
+        pub async fn update_index(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateIndexRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::IndexMetadataResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.metastore.MetastoreService/UpdateIndex",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new("quickwit.metastore.MetastoreService", "UpdateIndex"),
+                );
+            self.inner.unary(req, path, codec).await
+        }
         /// Gets an indexes metadatas.
         pub async fn list_indexes_metadata(
             &mut self,
@@ -4958,6 +5155,15 @@ pub mod metastore_service_grpc_server {
             tonic::Response<super::IndexMetadataResponse>,
             tonic::Status,
         >;
+        /// Updates the mutable settings of an index (search settings, retention policy and indexing settings) and
+        /// returns the updated `IndexMetadata`.
+        async fn update_index(
+            &self,
+            request: tonic::Request<super::UpdateIndexRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::IndexMetadataResponse>,
+            tonic::Status,
+        >;
         /// Gets an indexes metadatas.
         async fn list_indexes_metadata(
             &self,
@@ -5317,6 +5523,52 @@ pub mod metastore_service_grpc_server {
                     };
                     Box::pin(fut)
                 }
+                "/quickwit.metastore.MetastoreService/UpdateIndex" => {
+                    #[allow(non_camel_case_types)]
+                    struct UpdateIndexSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
+                    impl<
+                        T: MetastoreServiceGrpc,
+                    > tonic::server::UnaryService<super::UpdateIndexRequest>
+                    for UpdateIndexSvc<T> {
+                        type Response = super::IndexMetadataResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UpdateIndexRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).update_index(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = UpdateIndexSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/quickwit.metastore.MetastoreService/ListIndexesMetadata" => {
                     #[allow(non_camel_case_types)]
                     struct ListIndexesMetadataSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
@@ -184,6 +184,54 @@ pub struct SearchRequest {
     pub search_after: ::core::option::Option<PartialHit>,
     #[prost(enumeration = "CountHits", tag = "17")]
     pub count_hits: i32,
+    /// If set, leaves stop searching the remaining splits once this duration has elapsed
+    /// and the root returns the partial results collected so far.
+    #[prost(uint64, optional, tag = "18")]
+    pub timeout_millis: ::core::option::Option<u64>,
+    /// If set, the response contains a profile of the search: the rewritten query and a
+    /// per-split breakdown of where the time and the bytes were spent.
+    #[prost(bool, tag = "19")]
+    pub profile: bool,
+    /// Options of the snippets extracted on the `snippet_fields`.
+    #[prost(message, optional, tag = "20")]
+    pub snippet_options: ::core::option::Option<SnippetOptions>,
+    /// If set, hits are grouped by the value of a fast field and only the best hit of each group
+    /// is returned. `max_hits` and `start_offset` then apply to the groups.
+    #[prost(message, optional, tag = "21")]
+    pub collapse: ::core::option::Option<CollapseOptions>,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[derive(Eq, Hash)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CollapseOptions {
+    /// Fast text field (with the `raw` tokenizer) on which the hits are grouped. Documents without
+    /// a value form a group of their own.
+    #[prost(string, tag = "1")]
+    pub field_name: ::prost::alloc::string::String,
+    /// Number of best hits of each group returned as the inner hits of the group. No inner hits
+    /// are returned if 0.
+    #[prost(uint32, tag = "2")]
+    pub max_inner_hits: u32,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[derive(Eq, Hash)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SnippetOptions {
+    /// Maximum number of characters of a fragment. Defaults to 150.
+    #[prost(uint32, optional, tag = "1")]
+    pub fragment_size: ::core::option::Option<u32>,
+    /// Maximum number of fragments returned per field value. Defaults to 1. If 0, the whole field
+    /// value is returned as a single fragment.
+    #[prost(uint32, optional, tag = "2")]
+    pub num_fragments: ::core::option::Option<u32>,
+    /// Tag inserted before the highlighted terms. Defaults to `<b>`.
+    #[prost(string, optional, tag = "3")]
+    pub pre_tag: ::core::option::Option<::prost::alloc::string::String>,
+    /// Tag inserted after the highlighted terms. Defaults to `</b>`.
+    #[prost(string, optional, tag = "4")]
+    pub post_tag: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[derive(Eq, Hash)]
@@ -223,6 +271,79 @@ pub struct SearchResponse {
     /// Scroll Id (only set if scroll_secs was set in the request)
     #[prost(string, optional, tag = "6")]
     pub scroll_id: ::core::option::Option<::prost::alloc::string::String>,
+    /// True if the search timed out and the response only contains partial results.
+    #[prost(bool, tag = "7")]
+    pub timed_out: bool,
+    /// Ids of the splits that were not searched because the search timed out.
+    #[prost(string, repeated, tag = "8")]
+    pub skipped_split_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Profile of the search (only set if profile was set in the request).
+    #[prost(message, optional, tag = "9")]
+    pub profile: ::core::option::Option<SearchProfile>,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SearchProfile {
+    /// JSON serialized query AST, as rewritten by the root and sent to the leaves.
+    #[prost(string, tag = "1")]
+    pub query_ast: ::prost::alloc::string::String,
+    /// Sums of the corresponding metrics of the splits.
+    #[prost(uint64, tag = "2")]
+    pub num_bytes_fetched: u64,
+    #[prost(uint64, tag = "3")]
+    pub num_cache_hits: u64,
+    #[prost(uint64, tag = "4")]
+    pub num_cache_misses: u64,
+    #[prost(uint64, tag = "5")]
+    pub num_docs_matched: u64,
+    #[prost(message, repeated, tag = "6")]
+    pub split_profiles: ::prost::alloc::vec::Vec<SplitSearchProfile>,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SplitSearchProfile {
+    #[prost(string, tag = "1")]
+    pub split_id: ::prost::alloc::string::String,
+    /// True if the result was served by the leaf search cache. In that case, only the
+    /// number of matched docs is set.
+    #[prost(bool, tag = "2")]
+    pub leaf_search_cache_hit: bool,
+    /// Debug representation of the tantivy query executed on the split.
+    #[prost(string, tag = "3")]
+    pub tantivy_query: ::prost::alloc::string::String,
+    /// Time spent opening the split, including fetching its footer and hotcache.
+    #[prost(uint64, tag = "4")]
+    pub open_index_micros: u64,
+    /// Time spent in the warmup phase, and in each of its steps. The steps run concurrently.
+    #[prost(uint64, tag = "5")]
+    pub warmup_micros: u64,
+    #[prost(uint64, tag = "6")]
+    pub warm_up_terms_micros: u64,
+    #[prost(uint64, tag = "7")]
+    pub warm_up_term_ranges_micros: u64,
+    #[prost(uint64, tag = "8")]
+    pub warm_up_term_dicts_micros: u64,
+    #[prost(uint64, tag = "9")]
+    pub warm_up_fastfields_micros: u64,
+    #[prost(uint64, tag = "10")]
+    pub warm_up_fieldnorms_micros: u64,
+    #[prost(uint64, tag = "11")]
+    pub warm_up_postings_micros: u64,
+    /// Time spent running the collector on the search thread pool.
+    #[prost(uint64, tag = "12")]
+    pub collection_micros: u64,
+    #[prost(uint64, tag = "13")]
+    pub num_docs_matched: u64,
+    /// Number of bytes fetched from the storage (or the split cache) and number of
+    /// requests served or not by the fast fields cache.
+    #[prost(uint64, tag = "14")]
+    pub num_bytes_fetched: u64,
+    #[prost(uint64, tag = "15")]
+    pub num_cache_hits: u64,
+    #[prost(uint64, tag = "16")]
+    pub num_cache_misses: u64,
 }
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -322,6 +443,9 @@ pub struct Hit {
     /// The index id of the hit
     #[prost(string, tag = "4")]
     pub index_id: ::prost::alloc::string::String,
+    /// The best hits of the group of the hit, if the hits were collapsed with inner hits.
+    #[prost(message, repeated, tag = "5")]
+    pub inner_hits: ::prost::alloc::vec::Vec<Hit>,
 }
 /// A partial hit, is a hit for which we have not fetch the content yet.
 /// Instead, it holds a document_uri which is enough information to
@@ -343,10 +467,17 @@ pub struct Hit {
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PartialHit {
+    /// Deprecated, replaced by `sort_values`. Still populated with the first two sort values so that
+    /// nodes running a previous version can merge and paginate the hits. Only read when
+    /// `sort_values` is empty, that is, when the hit was sent by such a node.
     #[prost(message, optional, tag = "10")]
     pub sort_value: ::core::option::Option<SortByValue>,
     #[prost(message, optional, tag = "11")]
     pub sort_value2: ::core::option::Option<SortByValue>,
+    /// Values of the sorting keys, in the order of the sort fields. `_doc` and `_shard_doc`
+    /// sort fields have no value.
+    #[prost(message, repeated, tag = "12")]
+    pub sort_values: ::prost::alloc::vec::Vec<SortByValue>,
     #[prost(string, tag = "2")]
     pub split_id: ::prost::alloc::string::String,
     /// (segment_ord, doc) form a tantivy DocAddress, which is sufficient to identify a document
@@ -356,13 +487,18 @@ pub struct PartialHit {
     /// The DocId identifies a unique document at the scale of a tantivy segment.
     #[prost(uint32, tag = "4")]
     pub doc_id: u32,
+    /// Value of the collapse field of the document, if the hits are collapsed and the document
+    /// has a value. For the hits of a `top_hits` aggregation, the key of the `terms` bucket the
+    /// document belongs to, if any.
+    #[prost(string, optional, tag = "21")]
+    pub collapse_value: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[derive(Ord, PartialOrd)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SortByValue {
-    #[prost(oneof = "sort_by_value::SortValue", tags = "1, 2, 3, 4")]
+    #[prost(oneof = "sort_by_value::SortValue", tags = "1, 2, 3, 4, 5")]
     pub sort_value: ::core::option::Option<sort_by_value::SortValue>,
 }
 /// Nested message and enum types in `SortByValue`.
@@ -380,6 +516,8 @@ pub mod sort_by_value {
         F64(f64),
         #[prost(bool, tag = "4")]
         Boolean(bool),
+        #[prost(string, tag = "5")]
+        Str(::prost::alloc::string::String),
     }
 }
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
@@ -404,6 +542,37 @@ pub struct LeafSearchResponse {
     pub intermediate_aggregation_result: ::core::option::Option<
         ::prost::alloc::vec::Vec<u8>,
     >,
+    /// Ids of the splits that were not searched because the search timed out.
+    /// Skipped splits are not counted in `num_attempted_splits`.
+    #[prost(string, repeated, tag = "7")]
+    pub skipped_split_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Profiles of the splits searched (only set if profile was set in the request).
+    #[prost(message, repeated, tag = "8")]
+    pub split_profiles: ::prost::alloc::vec::Vec<SplitSearchProfile>,
+    /// Results of the individual splits that the root can cache and reuse for subsequent requests.
+    /// Only set for aggregation requests that fully cover the time range of the splits.
+    #[prost(message, repeated, tag = "9")]
+    pub split_aggregation_results: ::prost::alloc::vec::Vec<SplitAggregationResult>,
+    /// Best hits of the groups of a `top_hits` aggregation, if any. The hits of a group are
+    /// contiguous, sorted, and share the same `collapse_value`.
+    #[prost(message, repeated, tag = "10")]
+    pub top_hits: ::prost::alloc::vec::Vec<PartialHit>,
+}
+/// Result of an aggregation request on a single split.
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SplitAggregationResult {
+    #[prost(string, tag = "1")]
+    pub split_id: ::prost::alloc::string::String,
+    /// Number of documents of the split matched by the query.
+    #[prost(uint64, tag = "2")]
+    pub num_hits: u64,
+    /// postcard serialized intermediate aggregation_result.
+    #[prost(bytes = "vec", optional, tag = "3")]
+    pub intermediate_aggregation_result: ::core::option::Option<
+        ::prost::alloc::vec::Vec<u8>,
+    >,
 }
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -413,6 +582,8 @@ pub struct SnippetRequest {
     pub snippet_fields: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
     #[prost(string, tag = "2")]
     pub query_ast_resolved: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "3")]
+    pub snippet_options: ::core::option::Option<SnippetOptions>,
 }
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -27,6 +27,7 @@ use std::convert::Infallible;
 pub enum ServiceErrorCode {
     AlreadyExists,
     BadRequest,
+    Forbidden,
     Internal,
     MethodNotAllowed,
     NotFound,
@@ -34,6 +35,7 @@ pub enum ServiceErrorCode {
     NotSupportedYet,
     RateLimited,
     Timeout,
+    Unauthenticated,
     Unavailable,
     UnsupportedMediaType,
 }
@@ -43,12 +45,14 @@ impl ServiceErrorCode {
         match self {
             ServiceErrorCode::AlreadyExists => tonic::Code::AlreadyExists,
             ServiceErrorCode::BadRequest => tonic::Code::InvalidArgument,
+            ServiceErrorCode::Forbidden => tonic::Code::PermissionDenied,
             ServiceErrorCode::Internal => tonic::Code::Internal,
             ServiceErrorCode::MethodNotAllowed => tonic::Code::InvalidArgument,
             ServiceErrorCode::NotFound => tonic::Code::NotFound,
             ServiceErrorCode::NotSupportedYet => tonic::Code::Unimplemented,
             ServiceErrorCode::RateLimited => tonic::Code::ResourceExhausted,
             ServiceErrorCode::Timeout => tonic::Code::DeadlineExceeded,
+            ServiceErrorCode::Unauthenticated => tonic::Code::Unauthenticated,
             ServiceErrorCode::Unavailable => tonic::Code::Unavailable,
             ServiceErrorCode::UnsupportedMediaType => tonic::Code::InvalidArgument,
         }
@@ -57,11 +61,13 @@ impl ServiceErrorCode {
         match self {
             ServiceErrorCode::AlreadyExists => http::StatusCode::BAD_REQUEST,
             ServiceErrorCode::BadRequest => http::StatusCode::BAD_REQUEST,
+            ServiceErrorCode::Forbidden => http::StatusCode::FORBIDDEN,
             ServiceErrorCode::Internal => http::StatusCode::INTERNAL_SERVER_ERROR,
             ServiceErrorCode::MethodNotAllowed => http::StatusCode::METHOD_NOT_ALLOWED,
             ServiceErrorCode::NotFound => http::StatusCode::NOT_FOUND,
             ServiceErrorCode::NotSupportedYet => http::StatusCode::NOT_IMPLEMENTED,
             ServiceErrorCode::RateLimited => http::StatusCode::TOO_MANY_REQUESTS,
+            ServiceErrorCode::Unauthenticated => http::StatusCode::UNAUTHORIZED,
             ServiceErrorCode::Unavailable => http::StatusCode::SERVICE_UNAVAILABLE,
             ServiceErrorCode::UnsupportedMediaType => http::StatusCode::UNSUPPORTED_MEDIA_TYPE,
             ServiceErrorCode::Timeout => http::StatusCode::REQUEST_TIMEOUT,
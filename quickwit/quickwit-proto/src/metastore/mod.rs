@@ -210,6 +210,7 @@ impl SourceType {
             SourceType::Kinesis => "kinesis",
             SourceType::Nats => "nats",
             SourceType::Pulsar => "pulsar",
+            SourceType::StoragePrefix => "storage_prefix",
             SourceType::Unspecified => "unspecified",
             SourceType::Vec => "vec",
             SourceType::Void => "void",
@@ -55,7 +55,6 @@ impl fmt::Display for SplitSearchError {
 }
 
 impl Eq for SortByValue {}
-impl Copy for SortByValue {}
 impl From<SortValue> for SortByValue {
     fn from(sort_value: SortValue) -> Self {
         SortByValue {
@@ -85,6 +84,7 @@ impl SortByValue {
                 }
             }
             Some(SortValue::Boolean(b)) => Bool(b),
+            Some(SortValue::Str(text)) => String(text),
             None => Null,
         }
     }
@@ -106,18 +106,10 @@ impl SortByValue {
                     return None;
                 }
             }
-            // Strings that can be converted to a number are accepted.
-            // Some clients (like JS clients) can't easily handle large integers
-            // without losing precision, so we accept them as strings.
-            String(value) => {
-                if let Ok(number) = value.parse::<i64>() {
-                    Some(SortValue::I64(number))
-                } else if let Ok(number) = value.parse::<u64>() {
-                    Some(SortValue::U64(number))
-                } else {
-                    return None;
-                }
-            }
+            // Strings can also be integers sent as strings, see `SortValue::parse_integer`. Since
+            // the type of the sort field is unknown here, they are converted later on, when
+            // sorting on a numeric field.
+            String(value) => Some(SortValue::Str(value)),
             Array(_) | Object(_) => return None,
         };
         Some(SortByValue { sort_value })
@@ -130,15 +122,14 @@ impl SortByValue {
 // This is terrible because this means Eq, PartialEq are not really in line with Ord's
 // implementation. if in presence of NaN.
 impl Eq for SortValue {}
-impl Copy for SortValue {}
 
 impl Ord for SortValue {
     fn cmp(&self, other: &Self) -> Ordering {
         // We make sure to end up with a total order.
-        match (*self, *other) {
+        match (self, other) {
             // Same types.
-            (SortValue::U64(left), SortValue::U64(right)) => left.cmp(&right),
-            (SortValue::I64(left), SortValue::I64(right)) => left.cmp(&right),
+            (SortValue::U64(left), SortValue::U64(right)) => left.cmp(right),
+            (SortValue::I64(left), SortValue::I64(right)) => left.cmp(right),
             (SortValue::F64(left), SortValue::F64(right)) => {
                 if left.is_nan() {
                     if right.is_nan() {
@@ -149,27 +140,30 @@ impl Ord for SortValue {
                 } else if right.is_nan() {
                     Ordering::Greater
                 } else {
-                    left.partial_cmp(&right).unwrap_or(Ordering::Less)
+                    left.partial_cmp(right).unwrap_or(Ordering::Less)
                 }
             }
-            (SortValue::Boolean(left), SortValue::Boolean(right)) => left.cmp(&right),
+            (SortValue::Boolean(left), SortValue::Boolean(right)) => left.cmp(right),
+            (SortValue::Str(left), SortValue::Str(right)) => left.cmp(right),
             // We half the logic by making sure we keep
             // the "stronger" type on the left.
+            // Strings are greater than any other type.
+            (SortValue::Str(_), _) => Ordering::Greater,
             (SortValue::U64(left), SortValue::I64(right)) => {
-                if left > i64::MAX as u64 {
+                if *left > i64::MAX as u64 {
                     return Ordering::Greater;
                 }
-                (left as i64).cmp(&right)
+                (*left as i64).cmp(right)
             }
             (SortValue::F64(left), _) if left.is_nan() => Ordering::Less,
             (SortValue::F64(left), SortValue::U64(right)) => {
-                left.partial_cmp(&(right as f64)).unwrap_or(Ordering::Less)
+                left.partial_cmp(&(*right as f64)).unwrap_or(Ordering::Less)
             }
             (SortValue::F64(left), SortValue::I64(right)) => {
-                left.partial_cmp(&(right as f64)).unwrap_or(Ordering::Less)
+                left.partial_cmp(&(*right as f64)).unwrap_or(Ordering::Less)
             }
-            (SortValue::Boolean(left), right) => SortValue::U64(left as u64).cmp(&right),
-            (left, right) => right.cmp(&left).reverse(),
+            (SortValue::Boolean(left), right) => SortValue::U64(*left as u64).cmp(right),
+            (left, right) => right.cmp(left).reverse(),
         }
     }
 }
@@ -200,23 +194,38 @@ impl std::hash::Hash for SortValue {
                 3u8.hash(state);
                 b.hash(state);
             }
+            SortValue::Str(text) => {
+                4u8.hash(state);
+                text.hash(state);
+            }
         }
     }
 }
 
 impl SortValue {
+    /// Parses an integer sent as a string, which is how some clients (like JS clients) send large
+    /// integers to avoid losing precision.
+    pub fn parse_integer(text: &str) -> Option<Self> {
+        if let Ok(number) = text.parse::<i64>() {
+            Some(SortValue::I64(number))
+        } else if let Ok(number) = text.parse::<u64>() {
+            Some(SortValue::U64(number))
+        } else {
+            None
+        }
+    }
+
     /// Where multiple variant could represent the same logical value, convert to a canonical form.
     ///
     /// For number, we prefer to represent them, in order, as i64, then as u64 and finaly as f64.
     pub fn normalize(&self) -> Self {
         match self {
-            SortValue::I64(_) => *self,
-            SortValue::Boolean(_) => *self,
+            SortValue::I64(_) | SortValue::Boolean(_) | SortValue::Str(_) => self.clone(),
             SortValue::U64(number) => {
                 if let Ok(number) = (*number).try_into() {
                     SortValue::I64(number)
                 } else {
-                    *self
+                    self.clone()
                 }
             }
             SortValue::F64(number) => {
@@ -229,7 +238,7 @@ impl SortValue {
                         return SortValue::U64(number as u64);
                     }
                 }
-                *self
+                self.clone()
             }
         }
     }
@@ -237,11 +246,123 @@ impl SortValue {
 
 impl PartialHit {
     /// Helper to get access to the 1st sort value
-    pub fn sort_value(&self) -> Option<SortValue> {
-        if let Some(sort_value) = self.sort_value {
-            sort_value.sort_value
-        } else {
-            None
+    pub fn sort_value(&self) -> Option<&SortValue> {
+        self.sort_values.first()?.sort_value.as_ref()
+    }
+}
+
+/// Keeps the deprecated `PartialHit::sort_value` and `PartialHit::sort_value2` fields in sync with
+/// `PartialHit::sort_values` on the wire, so that nodes running different versions can search
+/// together during a rolling upgrade. Nodes predating `sort_values` only know about the first two
+/// sort values.
+pub trait LegacySortValues {
+    /// Populates the deprecated fields of the partial hits before sending the message.
+    fn set_legacy_sort_values(&mut self);
+
+    /// Populates `sort_values` from the deprecated fields of the partial hits sent by a node
+    /// predating `sort_values`, after receiving the message.
+    fn upgrade_legacy_sort_values(&mut self);
+}
+
+impl LegacySortValues for PartialHit {
+    fn set_legacy_sort_values(&mut self) {
+        self.sort_value = self.sort_values.first().cloned();
+        self.sort_value2 = self.sort_values.get(1).cloned();
+    }
+
+    fn upgrade_legacy_sort_values(&mut self) {
+        if !self.sort_values.is_empty() {
+            return;
+        }
+        let sort_value_opt = self.sort_value.take();
+        let sort_value2_opt = self.sort_value2.take();
+
+        if sort_value_opt.is_none() && sort_value2_opt.is_none() {
+            return;
+        }
+        self.sort_values
+            .push(sort_value_opt.unwrap_or(SortByValue { sort_value: None }));
+
+        if let Some(sort_value2) = sort_value2_opt {
+            self.sort_values.push(sort_value2);
+        }
+    }
+}
+
+impl LegacySortValues for SearchRequest {
+    fn set_legacy_sort_values(&mut self) {
+        if let Some(search_after) = self.search_after.as_mut() {
+            search_after.set_legacy_sort_values();
+        }
+    }
+
+    fn upgrade_legacy_sort_values(&mut self) {
+        if let Some(search_after) = self.search_after.as_mut() {
+            search_after.upgrade_legacy_sort_values();
+        }
+    }
+}
+
+impl LegacySortValues for LeafSearchRequest {
+    fn set_legacy_sort_values(&mut self) {
+        if let Some(search_request) = self.search_request.as_mut() {
+            search_request.set_legacy_sort_values();
+        }
+    }
+
+    fn upgrade_legacy_sort_values(&mut self) {
+        if let Some(search_request) = self.search_request.as_mut() {
+            search_request.upgrade_legacy_sort_values();
+        }
+    }
+}
+
+impl LegacySortValues for LeafSearchResponse {
+    fn set_legacy_sort_values(&mut self) {
+        for partial_hit in &mut self.partial_hits {
+            partial_hit.set_legacy_sort_values();
+        }
+    }
+
+    fn upgrade_legacy_sort_values(&mut self) {
+        for partial_hit in &mut self.partial_hits {
+            partial_hit.upgrade_legacy_sort_values();
+        }
+    }
+}
+
+impl LegacySortValues for FetchDocsRequest {
+    fn set_legacy_sort_values(&mut self) {
+        for partial_hit in &mut self.partial_hits {
+            partial_hit.set_legacy_sort_values();
+        }
+    }
+
+    fn upgrade_legacy_sort_values(&mut self) {
+        for partial_hit in &mut self.partial_hits {
+            partial_hit.upgrade_legacy_sort_values();
+        }
+    }
+}
+
+impl LegacySortValues for FetchDocsResponse {
+    fn set_legacy_sort_values(&mut self) {
+        for partial_hit in self
+            .hits
+            .iter_mut()
+            .flat_map(|hit| hit.partial_hit.as_mut())
+        {
+            partial_hit.set_legacy_sort_values();
+        }
+    }
+
+    fn upgrade_legacy_sort_values(&mut self) {
+        for partial_hit in self
+            .hits
+            .iter_mut()
+            .flat_map(|hit| hit.partial_hit.as_mut())
+        {
+            partial_hit.upgrade_legacy_sort_values();
         }
     }
 }
@@ -290,3 +411,58 @@ fn read_split_fields_from_zstd<R: Read>(reader: R) -> io::Result<ListFields> {
 
     Ok(serialized_list_fields)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_hit_legacy_sort_values() {
+        let mut partial_hit = PartialHit {
+            sort_value: None,
+            sort_value2: None,
+            sort_values: vec![
+                SortValue::U64(1).into(),
+                SortValue::Str("foo".to_string()).into(),
+                SortValue::I64(-1).into(),
+            ],
+            split_id: "split".to_string(),
+            segment_ord: 1,
+            doc_id: 2,
+            collapse_value: None,
+        };
+        partial_hit.set_legacy_sort_values();
+        assert_eq!(partial_hit.sort_value, Some(SortValue::U64(1).into()));
+        assert_eq!(
+            partial_hit.sort_value2,
+            Some(SortValue::Str("foo".to_string()).into())
+        );
+
+        // `sort_values` prevails over the deprecated fields.
+        let expected_sort_values = partial_hit.sort_values.clone();
+        partial_hit.upgrade_legacy_sort_values();
+        assert_eq!(partial_hit.sort_values, expected_sort_values);
+
+        // Hits sent by a node predating `sort_values`.
+        partial_hit.sort_values.clear();
+        partial_hit.sort_value = None;
+        partial_hit.sort_value2 = Some(SortValue::I64(3).into());
+        partial_hit.upgrade_legacy_sort_values();
+        assert_eq!(
+            partial_hit.sort_values,
+            vec![SortByValue { sort_value: None }, SortValue::I64(3).into()]
+        );
+
+        partial_hit.sort_values.clear();
+        partial_hit.sort_value = Some(SortValue::I64(3).into());
+        partial_hit.sort_value2 = None;
+        partial_hit.upgrade_legacy_sort_values();
+        assert_eq!(partial_hit.sort_values, vec![SortValue::I64(3).into()]);
+
+        partial_hit.sort_values.clear();
+        partial_hit.sort_value = None;
+        partial_hit.sort_value2 = None;
+        partial_hit.upgrade_legacy_sort_values();
+        assert!(partial_hit.sort_values.is_empty());
+    }
+}
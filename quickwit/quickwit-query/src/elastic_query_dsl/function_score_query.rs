@@ -0,0 +1,102 @@
+// Copyright (C) 2024 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use serde::Deserialize;
+
+use crate::elastic_query_dsl::{ConvertableToQueryAst, ElasticQueryDslInner};
+use crate::not_nan_f32::NotNaNf32;
+use crate::query_ast::QueryAst;
+
+/// Only the constant scaling of the score of the underlying query is supported,
+/// through `boost` and `weight`.
+///
+/// # Unsupported features
+/// - functions
+/// - score_mode, boost_mode
+/// - max_boost, min_score
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct FunctionScoreQuery {
+    #[serde(default)]
+    query: Option<Box<ElasticQueryDslInner>>,
+    #[serde(default)]
+    boost: Option<NotNaNf32>,
+    #[serde(default)]
+    weight: Option<NotNaNf32>,
+}
+
+impl ConvertableToQueryAst for FunctionScoreQuery {
+    fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
+        let query_ast = if let Some(query) = self.query {
+            query.convert_to_query_ast()?
+        } else {
+            QueryAst::MatchAll
+        };
+        Ok(query_ast.boost(self.weight).boost(self.boost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query_ast;
+
+    #[test]
+    fn test_function_score_query() {
+        let function_score_query: FunctionScoreQuery = serde_json::from_str(
+            r#"{
+                "query": { "term": { "user.id": "kimchy" } },
+                "boost": 2.0,
+                "weight": 3.0
+            }"#,
+        )
+        .unwrap();
+        let query_ast = function_score_query.convert_to_query_ast().unwrap();
+        assert_eq!(
+            query_ast,
+            QueryAst::Boost {
+                underlying: Box::new(
+                    query_ast::TermQuery {
+                        field: "user.id".to_string(),
+                        value: "kimchy".to_string(),
+                    }
+                    .into()
+                ),
+                boost: NotNaNf32::try_from(6.0f32).unwrap(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_function_score_query_without_query() {
+        let function_score_query: FunctionScoreQuery = serde_json::from_str("{}").unwrap();
+        assert_eq!(
+            function_score_query.convert_to_query_ast().unwrap(),
+            QueryAst::MatchAll
+        );
+    }
+
+    #[test]
+    fn test_function_score_query_with_functions() {
+        serde_json::from_str::<FunctionScoreQuery>(
+            r#"{"query": {"match_all": {}}, "functions": [{"weight": 2.0}]}"#,
+        )
+        .unwrap_err();
+    }
+}
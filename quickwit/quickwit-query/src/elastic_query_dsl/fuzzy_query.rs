@@ -0,0 +1,234 @@
+// Copyright (C) 2024 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use anyhow::Context;
+use serde::Deserialize;
+
+use crate::elastic_query_dsl::one_field_map::OneFieldMap;
+use crate::elastic_query_dsl::{ConvertableToQueryAst, StringOrStructForSerialization};
+use crate::not_nan_f32::NotNaNf32;
+use crate::query_ast::{self, QueryAst, MAX_FUZZY_DISTANCE};
+
+const DEFAULT_AUTO_FUZZINESS_LOW: usize = 3;
+const DEFAULT_AUTO_FUZZINESS_HIGH: usize = 6;
+
+/// # Unsupported features
+/// - max_expansions
+/// - prefix_length
+/// - rewrite
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(from = "OneFieldMap<StringOrStructForSerialization<FuzzyQueryParams>>")]
+pub(crate) struct FuzzyQuery {
+    pub field: String,
+    pub params: FuzzyQueryParams,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct FuzzyQueryParams {
+    value: String,
+    #[serde(default)]
+    fuzziness: Fuzziness,
+    #[serde(default)]
+    transpositions: Option<bool>,
+    #[serde(default)]
+    boost: Option<NotNaNf32>,
+}
+
+impl From<OneFieldMap<StringOrStructForSerialization<FuzzyQueryParams>>> for FuzzyQuery {
+    fn from(one_field_map: OneFieldMap<StringOrStructForSerialization<FuzzyQueryParams>>) -> Self {
+        FuzzyQuery {
+            field: one_field_map.field,
+            params: one_field_map.value.inner,
+        }
+    }
+}
+
+impl From<String> for FuzzyQueryParams {
+    fn from(value: String) -> FuzzyQueryParams {
+        FuzzyQueryParams {
+            value,
+            fuzziness: Fuzziness::default(),
+            transpositions: None,
+            boost: None,
+        }
+    }
+}
+
+/// Maximum edit distance allowed, as expressed in Elasticsearch.
+///
+/// `AUTO:low,high` picks a distance of 0 for values shorter than `low` characters,
+/// 1 for values shorter than `high` characters, and 2 otherwise.
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(try_from = "FuzzinessForSerialization")]
+pub(crate) enum Fuzziness {
+    Distance(u8),
+    Auto { low: usize, high: usize },
+}
+
+impl Default for Fuzziness {
+    fn default() -> Self {
+        Fuzziness::Auto {
+            low: DEFAULT_AUTO_FUZZINESS_LOW,
+            high: DEFAULT_AUTO_FUZZINESS_HIGH,
+        }
+    }
+}
+
+impl Fuzziness {
+    fn distance(&self, value: &str) -> u8 {
+        match *self {
+            Fuzziness::Distance(distance) => distance,
+            Fuzziness::Auto { low, high } => {
+                let num_chars = value.chars().count();
+                if num_chars < low {
+                    0
+                } else if num_chars < high {
+                    1
+                } else {
+                    2
+                }
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FuzzinessForSerialization {
+    Int(u64),
+    Str(String),
+}
+
+impl TryFrom<FuzzinessForSerialization> for Fuzziness {
+    type Error = anyhow::Error;
+
+    fn try_from(fuzziness: FuzzinessForSerialization) -> anyhow::Result<Fuzziness> {
+        let fuzziness_str = match fuzziness {
+            FuzzinessForSerialization::Int(distance) => distance.to_string(),
+            FuzzinessForSerialization::Str(fuzziness_str) => fuzziness_str,
+        };
+        if let Some(auto_params) = fuzziness_str.strip_prefix("AUTO") {
+            if auto_params.is_empty() {
+                return Ok(Fuzziness::default());
+            }
+            let (low_str, high_str) = auto_params
+                .strip_prefix(':')
+                .and_then(|auto_params| auto_params.split_once(','))
+                .with_context(|| format!("invalid fuzziness `{fuzziness_str}`"))?;
+            let low: usize = low_str
+                .parse()
+                .with_context(|| format!("invalid fuzziness `{fuzziness_str}`"))?;
+            let high: usize = high_str
+                .parse()
+                .with_context(|| format!("invalid fuzziness `{fuzziness_str}`"))?;
+            if low > high {
+                anyhow::bail!("invalid fuzziness `{fuzziness_str}`: low must be lower than high");
+            }
+            return Ok(Fuzziness::Auto { low, high });
+        }
+        let distance: u8 = fuzziness_str
+            .parse()
+            .ok()
+            .filter(|distance| *distance <= MAX_FUZZY_DISTANCE)
+            .with_context(|| {
+                format!(
+                    "invalid fuzziness `{fuzziness_str}`: expected `AUTO` or a distance between 0 \
+                     and {MAX_FUZZY_DISTANCE}"
+                )
+            })?;
+        Ok(Fuzziness::Distance(distance))
+    }
+}
+
+impl ConvertableToQueryAst for FuzzyQuery {
+    fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
+        let FuzzyQueryParams {
+            value,
+            fuzziness,
+            transpositions,
+            boost,
+        } = self.params;
+        let distance = fuzziness.distance(&value);
+        let fuzzy_ast: QueryAst = query_ast::FuzzyQuery {
+            field: self.field,
+            value,
+            distance,
+            transpositions: transpositions.unwrap_or(true),
+        }
+        .into();
+        Ok(fuzzy_ast.boost(boost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_query_deserialization() {
+        let fuzzy_query: FuzzyQuery = serde_json::from_str(r#"{"user.id": "ki"}"#).unwrap();
+        assert_eq!(&fuzzy_query.field, "user.id");
+        assert_eq!(fuzzy_query.params.fuzziness, Fuzziness::default());
+
+        let fuzzy_query: FuzzyQuery = serde_json::from_str(
+            r#"{"user.id": {"value": "ki", "fuzziness": 1, "transpositions": false}}"#,
+        )
+        .unwrap();
+        assert_eq!(fuzzy_query.params.fuzziness, Fuzziness::Distance(1));
+        assert_eq!(fuzzy_query.params.transpositions, Some(false));
+
+        let fuzzy_query: FuzzyQuery =
+            serde_json::from_str(r#"{"user.id": {"value": "ki", "fuzziness": "AUTO:2,4"}}"#)
+                .unwrap();
+        assert_eq!(
+            fuzzy_query.params.fuzziness,
+            Fuzziness::Auto { low: 2, high: 4 }
+        );
+
+        serde_json::from_str::<FuzzyQuery>(r#"{"user.id": {"value": "ki", "fuzziness": 3}}"#)
+            .unwrap_err();
+        serde_json::from_str::<FuzzyQuery>(r#"{"user.id": {"value": "ki", "fuzziness": "AUTO:"}}"#)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_fuzziness_auto_distance() {
+        let fuzziness = Fuzziness::default();
+        assert_eq!(fuzziness.distance("ki"), 0);
+        assert_eq!(fuzziness.distance("kimc"), 1);
+        assert_eq!(fuzziness.distance("kimchy"), 2);
+        assert_eq!(Fuzziness::Distance(1).distance("kimchy"), 1);
+    }
+
+    #[test]
+    fn test_fuzzy_query_convert_to_query_ast() {
+        let fuzzy_query: FuzzyQuery = serde_json::from_str(r#"{"user.id": "kimchy"}"#).unwrap();
+        let query_ast = fuzzy_query.convert_to_query_ast().unwrap();
+        assert_eq!(
+            query_ast,
+            QueryAst::Fuzzy(query_ast::FuzzyQuery {
+                field: "user.id".to_string(),
+                value: "kimchy".to_string(),
+                distance: 2,
+                transpositions: true,
+            })
+        );
+    }
+}
@@ -0,0 +1,65 @@
+// Copyright (C) 2024 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use serde::Deserialize;
+
+use crate::elastic_query_dsl::ConvertableToQueryAst;
+use crate::not_nan_f32::NotNaNf32;
+use crate::query_ast::QueryAst;
+
+/// Quickwit documents do not have an `_id` yet, so `ids` queries are parsed but rejected as
+/// unsupported. Converting them to a query matching nothing would match every document once
+/// negated.
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct IdsQuery {
+    #[serde(rename = "values")]
+    _values: Vec<String>,
+    #[serde(default, rename = "boost")]
+    _boost: Option<NotNaNf32>,
+}
+
+impl ConvertableToQueryAst for IdsQuery {
+    fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
+        anyhow::bail!("`ids` queries are not supported: documents do not have an `_id`")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elastic_query_dsl::ElasticQueryDslInner;
+
+    #[test]
+    fn test_ids_query() {
+        let ids_query: IdsQuery = serde_json::from_str(r#"{"values": ["1", "4"]}"#).unwrap();
+        assert_eq!(ids_query._values, vec!["1".to_string(), "4".to_string()]);
+        let error = ids_query.convert_to_query_ast().unwrap_err();
+        assert!(error.to_string().contains("not supported"));
+    }
+
+    #[test]
+    fn test_ids_query_under_must_not() {
+        let query_dsl: ElasticQueryDslInner =
+            serde_json::from_str(r#"{"bool": {"must_not": [{"ids": {"values": ["1"]}}]}}"#)
+                .unwrap();
+        let error = query_dsl.convert_to_query_ast().unwrap_err();
+        assert!(error.to_string().contains("not supported"));
+    }
+}
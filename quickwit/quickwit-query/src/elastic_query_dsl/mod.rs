@@ -21,17 +21,23 @@ use serde::{Deserialize, Serialize};
 
 mod bool_query;
 mod exists_query;
+mod function_score_query;
+mod fuzzy_query;
+mod ids_query;
 mod match_bool_prefix;
 mod match_phrase_query;
 mod match_query;
 mod multi_match;
 mod one_field_map;
 mod phrase_prefix_query;
+mod prefix_query;
 mod query_string_query;
 mod range_query;
+mod regexp_query;
 mod string_or_struct;
 mod term_query;
 mod terms_query;
+mod wildcard_query;
 
 use bool_query::BoolQuery;
 pub use one_field_map::OneFieldMap;
@@ -42,11 +48,17 @@ pub(crate) use string_or_struct::StringOrStructForSerialization;
 use term_query::TermQuery;
 
 use crate::elastic_query_dsl::exists_query::ExistsQuery;
+use crate::elastic_query_dsl::function_score_query::FunctionScoreQuery;
+use crate::elastic_query_dsl::fuzzy_query::FuzzyQuery;
+use crate::elastic_query_dsl::ids_query::IdsQuery;
 use crate::elastic_query_dsl::match_bool_prefix::MatchBoolPrefixQuery;
 use crate::elastic_query_dsl::match_phrase_query::MatchPhraseQuery;
 use crate::elastic_query_dsl::match_query::MatchQuery;
 use crate::elastic_query_dsl::multi_match::MultiMatchQuery;
+use crate::elastic_query_dsl::prefix_query::PrefixQuery;
+use crate::elastic_query_dsl::regexp_query::RegexpQuery;
 use crate::elastic_query_dsl::terms_query::TermsQuery;
+use crate::elastic_query_dsl::wildcard_query::WildcardQuery;
 use crate::not_nan_f32::NotNaNf32;
 use crate::query_ast::QueryAst;
 
@@ -79,6 +91,12 @@ pub(crate) enum ElasticQueryDslInner {
     MultiMatch(MultiMatchQuery),
     Range(RangeQuery),
     Exists(ExistsQuery),
+    Prefix(PrefixQuery),
+    Wildcard(WildcardQuery),
+    Regexp(RegexpQuery),
+    Fuzzy(FuzzyQuery),
+    Ids(IdsQuery),
+    FunctionScore(FunctionScoreQuery),
 }
 
 #[derive(Deserialize, Debug, Eq, PartialEq, Clone)]
@@ -126,6 +144,14 @@ impl ConvertableToQueryAst for ElasticQueryDslInner {
             Self::Match(match_query) => match_query.convert_to_query_ast(),
             Self::Exists(exists_query) => exists_query.convert_to_query_ast(),
             Self::MultiMatch(multi_match_query) => multi_match_query.convert_to_query_ast(),
+            Self::Prefix(prefix_query) => prefix_query.convert_to_query_ast(),
+            Self::Wildcard(wildcard_query) => wildcard_query.convert_to_query_ast(),
+            Self::Regexp(regexp_query) => regexp_query.convert_to_query_ast(),
+            Self::Fuzzy(fuzzy_query) => fuzzy_query.convert_to_query_ast(),
+            Self::Ids(ids_query) => ids_query.convert_to_query_ast(),
+            Self::FunctionScore(function_score_query) => {
+                function_score_query.convert_to_query_ast()
+            }
         }
     }
 }
@@ -151,4 +177,28 @@ mod tests {
             &term_query_from_field_value("product_id", "61809")
         );
     }
+
+    #[test]
+    fn test_query_dsl_deserialize_term_level_queries() {
+        let query_dsl_json = r#"{
+            "bool": {
+                "should": [
+                    { "prefix": { "user.id": "ki" } },
+                    { "wildcard": { "user.id": { "value": "ki*" } } },
+                    { "regexp": { "user.id": "k.*y" } },
+                    { "fuzzy": { "user.id": { "value": "kimchy", "fuzziness": 1 } } },
+                    { "function_score": { "query": { "match_all": {} }, "boost": 2.0 } }
+                ]
+            }
+        }"#;
+        let query_dsl: ElasticQueryDsl = serde_json::from_str(query_dsl_json).unwrap();
+        let QueryAst::Bool(bool_query) = QueryAst::try_from(query_dsl).unwrap() else {
+            panic!()
+        };
+        assert!(matches!(bool_query.should[0], QueryAst::Wildcard(_)));
+        assert!(matches!(bool_query.should[1], QueryAst::Wildcard(_)));
+        assert!(matches!(bool_query.should[2], QueryAst::Regex(_)));
+        assert!(matches!(bool_query.should[3], QueryAst::Fuzzy(_)));
+        assert!(matches!(bool_query.should[4], QueryAst::Boost { .. }));
+    }
 }
@@ -0,0 +1,116 @@
+// Copyright (C) 2024 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use serde::Deserialize;
+
+use crate::elastic_query_dsl::one_field_map::OneFieldMap;
+use crate::elastic_query_dsl::{ConvertableToQueryAst, StringOrStructForSerialization};
+use crate::not_nan_f32::NotNaNf32;
+use crate::query_ast::{self, QueryAst};
+
+/// `PrefixQuery` matches documents containing a term starting with the given prefix.
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(from = "OneFieldMap<StringOrStructForSerialization<PrefixQueryParams>>")]
+pub(crate) struct PrefixQuery {
+    pub field: String,
+    pub params: PrefixQueryParams,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct PrefixQueryParams {
+    value: String,
+    #[serde(default)]
+    boost: Option<NotNaNf32>,
+}
+
+impl From<OneFieldMap<StringOrStructForSerialization<PrefixQueryParams>>> for PrefixQuery {
+    fn from(one_field_map: OneFieldMap<StringOrStructForSerialization<PrefixQueryParams>>) -> Self {
+        PrefixQuery {
+            field: one_field_map.field,
+            params: one_field_map.value.inner,
+        }
+    }
+}
+
+impl From<String> for PrefixQueryParams {
+    fn from(value: String) -> PrefixQueryParams {
+        PrefixQueryParams { value, boost: None }
+    }
+}
+
+/// Escapes the wildcard special characters, so that the value is matched literally.
+fn escape_wildcard_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 1);
+    for c in value.chars() {
+        if matches!(c, '*' | '?' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+impl ConvertableToQueryAst for PrefixQuery {
+    fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
+        let PrefixQueryParams { value, boost } = self.params;
+        let mut wildcard_value = escape_wildcard_value(&value);
+        wildcard_value.push('*');
+        let wildcard_ast: QueryAst = query_ast::WildcardQuery {
+            field: self.field,
+            value: wildcard_value,
+        }
+        .into();
+        Ok(wildcard_ast.boost(boost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_query_deserialization() {
+        let prefix_query: PrefixQuery =
+            serde_json::from_str(r#"{"user.id": {"value": "ki", "boost": 2.0}}"#).unwrap();
+        assert_eq!(&prefix_query.field, "user.id");
+        assert_eq!(&prefix_query.params.value, "ki");
+        assert_eq!(
+            prefix_query.params.boost,
+            Some(NotNaNf32::try_from(2.0f32).unwrap())
+        );
+
+        let prefix_query: PrefixQuery = serde_json::from_str(r#"{"user.id": "ki"}"#).unwrap();
+        assert_eq!(&prefix_query.params.value, "ki");
+        assert!(prefix_query.params.boost.is_none());
+    }
+
+    #[test]
+    fn test_prefix_query_convert_to_query_ast() {
+        let prefix_query: PrefixQuery = serde_json::from_str(r#"{"user.id": "k*?"}"#).unwrap();
+        let query_ast = prefix_query.convert_to_query_ast().unwrap();
+        assert_eq!(
+            query_ast,
+            QueryAst::Wildcard(query_ast::WildcardQuery {
+                field: "user.id".to_string(),
+                value: "k\\*\\?*".to_string(),
+            })
+        );
+    }
+}
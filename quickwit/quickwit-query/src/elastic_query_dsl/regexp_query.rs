@@ -0,0 +1,102 @@
+// Copyright (C) 2024 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use serde::Deserialize;
+
+use crate::elastic_query_dsl::one_field_map::OneFieldMap;
+use crate::elastic_query_dsl::{ConvertableToQueryAst, StringOrStructForSerialization};
+use crate::not_nan_f32::NotNaNf32;
+use crate::query_ast::{self, QueryAst};
+
+/// # Unsupported features
+/// - flags
+/// - case_insensitive
+/// - max_determinized_states
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(from = "OneFieldMap<StringOrStructForSerialization<RegexpQueryParams>>")]
+pub(crate) struct RegexpQuery {
+    pub field: String,
+    pub params: RegexpQueryParams,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct RegexpQueryParams {
+    value: String,
+    #[serde(default)]
+    boost: Option<NotNaNf32>,
+}
+
+impl From<OneFieldMap<StringOrStructForSerialization<RegexpQueryParams>>> for RegexpQuery {
+    fn from(one_field_map: OneFieldMap<StringOrStructForSerialization<RegexpQueryParams>>) -> Self {
+        RegexpQuery {
+            field: one_field_map.field,
+            params: one_field_map.value.inner,
+        }
+    }
+}
+
+impl From<String> for RegexpQueryParams {
+    fn from(value: String) -> RegexpQueryParams {
+        RegexpQueryParams { value, boost: None }
+    }
+}
+
+impl ConvertableToQueryAst for RegexpQuery {
+    fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
+        let RegexpQueryParams { value, boost } = self.params;
+        let regex_ast: QueryAst = query_ast::RegexQuery {
+            field: self.field,
+            regex: value,
+        }
+        .into();
+        Ok(regex_ast.boost(boost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regexp_query_convert_to_query_ast() {
+        let regexp_query: RegexpQuery =
+            serde_json::from_str(r#"{"user.id": {"value": "k.*y", "boost": 1.5}}"#).unwrap();
+        let query_ast = regexp_query.convert_to_query_ast().unwrap();
+        assert_eq!(
+            query_ast,
+            QueryAst::Boost {
+                underlying: Box::new(QueryAst::Regex(query_ast::RegexQuery {
+                    field: "user.id".to_string(),
+                    regex: "k.*y".to_string(),
+                })),
+                boost: NotNaNf32::try_from(1.5f32).unwrap(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_regexp_query_unsupported_param() {
+        let error = serde_json::from_str::<RegexpQuery>(
+            r#"{"user.id": {"value": "k.*y", "flags": "ALL"}}"#,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("unknown field `flags`"));
+    }
+}
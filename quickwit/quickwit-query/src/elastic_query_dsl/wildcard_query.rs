@@ -0,0 +1,173 @@
+// Copyright (C) 2024 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use anyhow::bail;
+use serde::Deserialize;
+
+use crate::elastic_query_dsl::one_field_map::OneFieldMap;
+use crate::elastic_query_dsl::{ConvertableToQueryAst, StringOrStructForSerialization};
+use crate::not_nan_f32::NotNaNf32;
+use crate::query_ast::{self, escape_regex_literal, QueryAst};
+
+/// Patterns consisting of a literal followed by a single `*` are run as prefix queries. Any other
+/// pattern (`ki*bana`, `*bana`, `k?bana`, ...) is converted into a regular expression matched
+/// against the whole indexed terms.
+///
+/// # Unsupported features
+/// - case_insensitive
+/// - rewrite
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(from = "OneFieldMap<StringOrStructForSerialization<WildcardQueryParams>>")]
+pub(crate) struct WildcardQuery {
+    pub field: String,
+    pub params: WildcardQueryParams,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct WildcardQueryParams {
+    // Elasticsearch accepts `wildcard` as an alias for `value`.
+    #[serde(alias = "wildcard")]
+    value: String,
+    #[serde(default)]
+    boost: Option<NotNaNf32>,
+}
+
+impl From<OneFieldMap<StringOrStructForSerialization<WildcardQueryParams>>> for WildcardQuery {
+    fn from(
+        one_field_map: OneFieldMap<StringOrStructForSerialization<WildcardQueryParams>>,
+    ) -> Self {
+        WildcardQuery {
+            field: one_field_map.field,
+            params: one_field_map.value.inner,
+        }
+    }
+}
+
+impl From<String> for WildcardQueryParams {
+    fn from(value: String) -> WildcardQueryParams {
+        WildcardQueryParams { value, boost: None }
+    }
+}
+
+/// Converts an Elasticsearch wildcard pattern into an equivalent regular expression. `*` matches
+/// any sequence of characters, `?` matches any single character, and `\` escapes the following
+/// character.
+///
+/// Returns `None` if the pattern is a literal followed by a single final `*`.
+fn wildcard_pattern_to_regex(pattern: &str) -> anyhow::Result<Option<String>> {
+    let mut regex = String::with_capacity(pattern.len() * 2);
+    let mut literal = String::new();
+    let mut chars = pattern.chars().peekable();
+    let mut is_prefix_pattern = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                let Some(escaped_char) = chars.next() else {
+                    bail!("wildcard pattern `{pattern}` ends with an unescaped `\\`");
+                };
+                literal.push(escaped_char);
+            }
+            '*' | '?' => {
+                is_prefix_pattern = c == '*' && chars.peek().is_none() && regex.is_empty();
+                regex.push_str(&escape_regex_literal(&literal));
+                literal.clear();
+                regex.push_str(if c == '*' { ".*" } else { "." });
+            }
+            _ => literal.push(c),
+        }
+    }
+    if is_prefix_pattern {
+        return Ok(None);
+    }
+    regex.push_str(&escape_regex_literal(&literal));
+    Ok(Some(regex))
+}
+
+impl ConvertableToQueryAst for WildcardQuery {
+    fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
+        let WildcardQueryParams { value, boost } = self.params;
+        let query_ast: QueryAst = match wildcard_pattern_to_regex(&value)? {
+            Some(regex) => query_ast::RegexQuery {
+                field: self.field,
+                regex,
+            }
+            .into(),
+            None => query_ast::WildcardQuery {
+                field: self.field,
+                value,
+            }
+            .into(),
+        };
+        Ok(query_ast.boost(boost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcard_query_convert_to_query_ast() {
+        let wildcard_query: WildcardQuery =
+            serde_json::from_str(r#"{"user.id": {"wildcard": "ki*"}}"#).unwrap();
+        let query_ast = wildcard_query.convert_to_query_ast().unwrap();
+        assert_eq!(
+            query_ast,
+            QueryAst::Wildcard(query_ast::WildcardQuery {
+                field: "user.id".to_string(),
+                value: "ki*".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_wildcard_query_convert_to_regex_query_ast() {
+        for (pattern, expected_regex) in [
+            ("ki*bana", "ki.*bana"),
+            ("*bana", ".*bana"),
+            ("k?bana", "k.bana"),
+            ("kibana", "kibana"),
+            ("ki**", "ki.*.*"),
+            ("k.b*n?", "k\\.b.*n."),
+            ("k\\*bana?", "k\\*bana."),
+        ] {
+            let wildcard_query: WildcardQuery =
+                serde_json::from_value(serde_json::json!({"user.id": {"value": pattern}})).unwrap();
+            let query_ast = wildcard_query.convert_to_query_ast().unwrap();
+            assert_eq!(
+                query_ast,
+                QueryAst::Regex(query_ast::RegexQuery {
+                    field: "user.id".to_string(),
+                    regex: expected_regex.to_string(),
+                }),
+                "unexpected query AST for pattern `{pattern}`"
+            );
+        }
+    }
+
+    #[test]
+    fn test_wildcard_query_with_trailing_escape_is_rejected() {
+        let wildcard_query: WildcardQuery =
+            serde_json::from_str(r#"{"user.id": {"value": "kibana\\"}}"#).unwrap();
+        let error = wildcard_query.convert_to_query_ast().unwrap_err();
+        assert!(error.to_string().contains("unescaped"));
+    }
+}
@@ -0,0 +1,248 @@
+// Copyright (C) 2024 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use tantivy::json_utils::JsonTermWriter;
+use tantivy::schema::{Field, FieldType, Schema as TantivySchema};
+use tantivy::Term;
+
+use super::{BuildTantivyAst, QueryAst};
+use crate::query_ast::TantivyQueryAst;
+use crate::tokenizers::TokenizerManager;
+use crate::{find_field_or_hit_dynamic, InvalidQuery};
+
+/// Maximum edit distance supported by fuzzy queries.
+pub const MAX_FUZZY_DISTANCE: u8 = 2;
+
+fn default_transpositions() -> bool {
+    true
+}
+
+/// A Fuzzy query matches all of the documents containing a term within
+/// a given Levenshtein distance of `value`.
+///
+/// `value` goes through the normalizer of the field tokenizer, and must
+/// result in a single term.
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize, Clone)]
+pub struct FuzzyQuery {
+    pub field: String,
+    pub value: String,
+    /// Maximum edit distance. Must be lower or equal to 2.
+    pub distance: u8,
+    /// If true, the transposition of two adjacent characters counts as a single edit.
+    #[serde(default = "default_transpositions")]
+    pub transpositions: bool,
+}
+
+impl From<FuzzyQuery> for QueryAst {
+    fn from(fuzzy_query: FuzzyQuery) -> Self {
+        Self::Fuzzy(fuzzy_query)
+    }
+}
+
+impl FuzzyQuery {
+    #[cfg(test)]
+    pub fn from_field_value(field: impl ToString, value: impl ToString, distance: u8) -> Self {
+        Self {
+            field: field.to_string(),
+            value: value.to_string(),
+            distance,
+            transpositions: true,
+        }
+    }
+
+    /// Returns the targeted field, and the term to run the fuzzy query with.
+    ///
+    /// For json fields, the term is prefixed by the json path, so that the query only matches
+    /// terms of the targeted path.
+    pub fn extract_term(
+        &self,
+        schema: &TantivySchema,
+        tokenizer_manager: &TokenizerManager,
+    ) -> Result<(Field, Term), InvalidQuery> {
+        let (field, field_entry, json_path) = find_field_or_hit_dynamic(&self.field, schema)?;
+        let (text_field_indexing_opt, json_options_opt) = match field_entry.field_type() {
+            FieldType::Str(ref text_options) => (text_options.get_indexing_options(), None),
+            FieldType::JsonObject(ref json_options) => {
+                (json_options.get_text_indexing_options(), Some(json_options))
+            }
+            _ => {
+                return Err(InvalidQuery::SchemaError(
+                    "trying to run a Fuzzy query on a non-text field".to_string(),
+                ))
+            }
+        };
+        let text_field_indexing = text_field_indexing_opt.ok_or_else(|| {
+            InvalidQuery::SchemaError(format!(
+                "field {} is not full-text searchable",
+                field_entry.name()
+            ))
+        })?;
+        let tokenizer_name = text_field_indexing.tokenizer();
+        let mut normalizer = tokenizer_manager
+            .get_normalizer(tokenizer_name)
+            .with_context(|| format!("no tokenizer named `{}` is registered", tokenizer_name))?;
+        let mut token_stream = normalizer.token_stream(&self.value);
+        let mut tokens = Vec::new();
+        token_stream.process(&mut |token| {
+            tokens.push(token.text.clone());
+        });
+        let token = tokens.pop().context("fuzzy query generated no term")?;
+        if !tokens.is_empty() {
+            return Err(anyhow::anyhow!("fuzzy query generated more than one term").into());
+        }
+        let Some(json_options) = json_options_opt else {
+            return Ok((field, Term::from_field_text(field, &token)));
+        };
+        let mut term = Term::with_capacity(100);
+        let mut json_term_writer = JsonTermWriter::from_field_and_json_path(
+            field,
+            json_path,
+            json_options.is_expand_dots_enabled(),
+            &mut term,
+        );
+        json_term_writer.set_str(&token);
+        Ok((field, term))
+    }
+}
+
+impl BuildTantivyAst for FuzzyQuery {
+    fn build_tantivy_ast_impl(
+        &self,
+        schema: &TantivySchema,
+        tokenizer_manager: &TokenizerManager,
+        _search_fields: &[String],
+        _with_validation: bool,
+    ) -> Result<TantivyQueryAst, InvalidQuery> {
+        if self.distance > MAX_FUZZY_DISTANCE {
+            return Err(anyhow::anyhow!(
+                "fuzzy query distance must be lower or equal to {MAX_FUZZY_DISTANCE}, got {}",
+                self.distance
+            )
+            .into());
+        }
+        let (_, term) = self.extract_term(schema, tokenizer_manager)?;
+        let fuzzy_query =
+            tantivy::query::FuzzyTermQuery::new(term, self.distance, self.transpositions);
+        Ok(fuzzy_query.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::schema::{Schema as TantivySchema, INDEXED, TEXT};
+
+    use super::FuzzyQuery;
+    use crate::query_ast::BuildTantivyAst;
+    use crate::{create_default_quickwit_tokenizer_manager, InvalidQuery};
+
+    #[test]
+    fn test_fuzzy_query_extract_term() {
+        let mut schema_builder = TantivySchema::builder();
+        let body_field = schema_builder.add_text_field("body", TEXT);
+        let schema = schema_builder.build();
+        let tokenizer_manager = create_default_quickwit_tokenizer_manager();
+
+        let fuzzy_query = FuzzyQuery::from_field_value("body", "QuickWit", 1);
+        let (field, term) = fuzzy_query
+            .extract_term(&schema, &tokenizer_manager)
+            .unwrap();
+        assert_eq!(field, body_field);
+        assert_eq!(term.value().as_str(), Some("quickwit"));
+
+        let fuzzy_query = FuzzyQuery::from_field_value("body", "quick wit", 1);
+        fuzzy_query
+            .extract_term(&schema, &tokenizer_manager)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_fuzzy_query_distance_too_large() {
+        let mut schema_builder = TantivySchema::builder();
+        schema_builder.add_text_field("body", TEXT);
+        let schema = schema_builder.build();
+
+        let fuzzy_query = FuzzyQuery::from_field_value("body", "quickwit", 3);
+        let error = fuzzy_query
+            .build_tantivy_ast_call(
+                &schema,
+                &create_default_quickwit_tokenizer_manager(),
+                &[],
+                true,
+            )
+            .unwrap_err();
+        assert!(matches!(error, InvalidQuery::Other(_)));
+    }
+
+    #[test]
+    fn test_fuzzy_query_on_json_field() {
+        let mut schema_builder = TantivySchema::builder();
+        let attributes_field = schema_builder.add_json_field("attributes", TEXT);
+        let schema = schema_builder.build();
+        let tokenizer_manager = create_default_quickwit_tokenizer_manager();
+
+        let fuzzy_query = FuzzyQuery::from_field_value("attributes.service", "QuickWit", 1);
+        let (field, term) = fuzzy_query
+            .extract_term(&schema, &tokenizer_manager)
+            .unwrap();
+        assert_eq!(field, attributes_field);
+        assert_eq!(term.serialized_value_bytes(), b"service\x00squickwit");
+
+        fuzzy_query
+            .build_tantivy_ast_call(&schema, &tokenizer_manager, &[], true)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_fuzzy_query_on_dynamic_field() {
+        let mut schema_builder = TantivySchema::builder();
+        let dynamic_field = schema_builder.add_json_field("_dynamic", TEXT);
+        let schema = schema_builder.build();
+        let tokenizer_manager = create_default_quickwit_tokenizer_manager();
+
+        let fuzzy_query = FuzzyQuery::from_field_value("service.name", "quickwit", 2);
+        let (field, term) = fuzzy_query
+            .extract_term(&schema, &tokenizer_manager)
+            .unwrap();
+        assert_eq!(field, dynamic_field);
+        assert_eq!(
+            term.serialized_value_bytes(),
+            b"service\x01name\x00squickwit"
+        );
+    }
+
+    #[test]
+    fn test_fuzzy_query_on_non_text_field() {
+        let mut schema_builder = TantivySchema::builder();
+        schema_builder.add_u64_field("count", INDEXED);
+        let schema = schema_builder.build();
+
+        let fuzzy_query = FuzzyQuery::from_field_value("count", "1", 1);
+        let error = fuzzy_query
+            .build_tantivy_ast_call(
+                &schema,
+                &create_default_quickwit_tokenizer_manager(),
+                &[],
+                true,
+            )
+            .unwrap_err();
+        assert!(matches!(error, InvalidQuery::SchemaError(_)));
+    }
+}
@@ -26,8 +26,10 @@ use crate::tokenizers::TokenizerManager;
 mod bool_query;
 mod field_presence;
 mod full_text_query;
+mod fuzzy_query;
 mod phrase_prefix_query;
 mod range_query;
+mod regex_query;
 mod tantivy_query_ast;
 mod term_query;
 mod term_set_query;
@@ -39,8 +41,11 @@ mod wildcard_query;
 pub use bool_query::BoolQuery;
 pub use field_presence::FieldPresenceQuery;
 pub use full_text_query::{FullTextMode, FullTextParams, FullTextQuery};
+pub use fuzzy_query::{FuzzyQuery, MAX_FUZZY_DISTANCE};
 pub use phrase_prefix_query::PhrasePrefixQuery;
 pub use range_query::RangeQuery;
+pub(crate) use regex_query::escape_regex_literal;
+pub use regex_query::RegexQuery;
 use tantivy_query_ast::TantivyQueryAst;
 pub use term_query::TermQuery;
 pub use term_set_query::TermSetQuery;
@@ -63,6 +68,8 @@ pub enum QueryAst {
     Range(RangeQuery),
     UserInput(UserInputQuery),
     Wildcard(WildcardQuery),
+    Regex(RegexQuery),
+    Fuzzy(FuzzyQuery),
     MatchAll,
     MatchNone,
     Boost {
@@ -103,7 +110,9 @@ impl QueryAst {
             | ast @ QueryAst::MatchNone
             | ast @ QueryAst::FieldPresence(_)
             | ast @ QueryAst::Range(_)
-            | ast @ QueryAst::Wildcard(_) => Ok(ast),
+            | ast @ QueryAst::Wildcard(_)
+            | ast @ QueryAst::Regex(_)
+            | ast @ QueryAst::Fuzzy(_) => Ok(ast),
             QueryAst::UserInput(user_text_query) => {
                 user_text_query.parse_user_query(default_search_fields)
             }
@@ -247,6 +256,18 @@ impl BuildTantivyAst for QueryAst {
                 search_fields,
                 with_validation,
             ),
+            QueryAst::Regex(regex) => regex.build_tantivy_ast_call(
+                schema,
+                tokenizer_manager,
+                search_fields,
+                with_validation,
+            ),
+            QueryAst::Fuzzy(fuzzy) => fuzzy.build_tantivy_ast_call(
+                schema,
+                tokenizer_manager,
+                search_fields,
+                with_validation,
+            ),
         }
     }
 }
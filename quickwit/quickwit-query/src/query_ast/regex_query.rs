@@ -0,0 +1,210 @@
+// Copyright (C) 2024 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use tantivy::json_utils::JsonTermWriter;
+use tantivy::schema::{Field, FieldType, Schema as TantivySchema};
+use tantivy::Term;
+
+use super::{BuildTantivyAst, QueryAst};
+use crate::query_ast::TantivyQueryAst;
+use crate::tokenizers::TokenizerManager;
+use crate::{find_field_or_hit_dynamic, InvalidQuery};
+
+/// A Regex query matches all of the documents containing a term
+/// that matches the regular expression `regex`.
+///
+/// The regular expression must match the entire term. It is applied on the terms
+/// as they were indexed, and is therefore not processed by the field tokenizer.
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize, Clone)]
+pub struct RegexQuery {
+    pub field: String,
+    pub regex: String,
+}
+
+impl From<RegexQuery> for QueryAst {
+    fn from(regex_query: RegexQuery) -> Self {
+        Self::Regex(regex_query)
+    }
+}
+
+impl RegexQuery {
+    #[cfg(test)]
+    pub fn from_field_value(field: impl ToString, regex: impl ToString) -> Self {
+        Self {
+            field: field.to_string(),
+            regex: regex.to_string(),
+        }
+    }
+}
+
+/// Escapes a literal so that it can be used as the prefix of a regular expression.
+///
+/// Control characters, such as the json path separators, are written as hex escape sequences.
+pub(crate) fn escape_regex_literal(literal: &str) -> String {
+    let mut escaped = String::with_capacity(literal.len());
+    for c in literal.chars() {
+        if c.is_ascii_control() {
+            escaped.push_str(&format!("\\x{:02X}", c as u32));
+            continue;
+        }
+        if "\\.+*?()|[]{}^$#&-~".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+impl RegexQuery {
+    /// Returns the targeted field, and the regular expression to run on its term dictionary.
+    ///
+    /// For json fields, the regular expression is prefixed by the json path, so
+    /// that it only matches terms of the targeted path.
+    pub fn to_field_and_regex(
+        &self,
+        schema: &TantivySchema,
+    ) -> Result<(Field, String), InvalidQuery> {
+        let (field, field_entry, json_path) = find_field_or_hit_dynamic(&self.field, schema)?;
+        match field_entry.field_type() {
+            FieldType::Str(ref text_options) => {
+                text_options.get_indexing_options().ok_or_else(|| {
+                    InvalidQuery::SchemaError(format!(
+                        "field {} is not full-text searchable",
+                        field_entry.name()
+                    ))
+                })?;
+                Ok((field, self.regex.clone()))
+            }
+            FieldType::JsonObject(json_options) => {
+                json_options.get_text_indexing_options().ok_or_else(|| {
+                    InvalidQuery::SchemaError(format!(
+                        "field {} is not full-text searchable",
+                        field_entry.name()
+                    ))
+                })?;
+                let mut term = Term::with_capacity(100);
+                let mut json_term_writer = JsonTermWriter::from_field_and_json_path(
+                    field,
+                    json_path,
+                    json_options.is_expand_dots_enabled(),
+                    &mut term,
+                );
+                json_term_writer.set_str("");
+                let path_prefix =
+                    std::str::from_utf8(json_term_writer.term().serialized_value_bytes())
+                        .context("json path is not valid utf-8")?;
+                let regex = format!("{}(?:{})", escape_regex_literal(path_prefix), self.regex);
+                Ok((field, regex))
+            }
+            _ => Err(InvalidQuery::SchemaError(
+                "trying to run a Regex query on a non-text field".to_string(),
+            )),
+        }
+    }
+}
+
+impl BuildTantivyAst for RegexQuery {
+    fn build_tantivy_ast_impl(
+        &self,
+        schema: &TantivySchema,
+        _tokenizer_manager: &TokenizerManager,
+        _search_fields: &[String],
+        _with_validation: bool,
+    ) -> Result<TantivyQueryAst, InvalidQuery> {
+        let (field, regex) = self.to_field_and_regex(schema)?;
+        let regex_query =
+            tantivy::query::RegexQuery::from_pattern(&regex, field).map_err(|error| {
+                InvalidQuery::Other(anyhow::anyhow!(
+                    "invalid regular expression `{}`: {error}",
+                    self.regex
+                ))
+            })?;
+        Ok(regex_query.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::schema::{Schema as TantivySchema, INDEXED, TEXT};
+
+    use super::{escape_regex_literal, RegexQuery};
+    use crate::query_ast::BuildTantivyAst;
+    use crate::{create_default_quickwit_tokenizer_manager, InvalidQuery};
+
+    #[test]
+    fn test_escape_regex_literal() {
+        assert_eq!(escape_regex_literal("service"), "service");
+        assert_eq!(escape_regex_literal("k8s.pod"), "k8s\\.pod");
+        assert_eq!(escape_regex_literal("attr\u{0}s"), "attr\\x00s");
+    }
+
+    #[test]
+    fn test_regex_query_to_field_and_regex() {
+        let mut schema_builder = TantivySchema::builder();
+        schema_builder.add_text_field("body", TEXT);
+        schema_builder.add_json_field("attributes", TEXT);
+        let schema = schema_builder.build();
+
+        let regex_query = RegexQuery::from_field_value("body", "qu.*t");
+        let (_, regex) = regex_query.to_field_and_regex(&schema).unwrap();
+        assert_eq!(regex, "qu.*t");
+
+        let regex_query = RegexQuery::from_field_value("attributes.service", "qu.*t");
+        let (_, regex) = regex_query.to_field_and_regex(&schema).unwrap();
+        assert_eq!(regex, "service\\x00s(?:qu.*t)");
+    }
+
+    #[test]
+    fn test_regex_query_on_non_text_field() {
+        let mut schema_builder = TantivySchema::builder();
+        schema_builder.add_u64_field("count", INDEXED);
+        let schema = schema_builder.build();
+
+        let regex_query = RegexQuery::from_field_value("count", "1.*");
+        let error = regex_query
+            .build_tantivy_ast_call(
+                &schema,
+                &create_default_quickwit_tokenizer_manager(),
+                &[],
+                true,
+            )
+            .unwrap_err();
+        assert!(matches!(error, InvalidQuery::SchemaError(_)));
+    }
+
+    #[test]
+    fn test_regex_query_invalid_regex() {
+        let mut schema_builder = TantivySchema::builder();
+        schema_builder.add_text_field("body", TEXT);
+        let schema = schema_builder.build();
+
+        let regex_query = RegexQuery::from_field_value("body", "qu(ick");
+        let error = regex_query
+            .build_tantivy_ast_call(
+                &schema,
+                &create_default_quickwit_tokenizer_manager(),
+                &[],
+                true,
+            )
+            .unwrap_err();
+        assert!(matches!(error, InvalidQuery::Other(_)));
+    }
+}
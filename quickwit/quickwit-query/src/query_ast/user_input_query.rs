@@ -31,6 +31,7 @@ use crate::not_nan_f32::NotNaNf32;
 use crate::query_ast::tantivy_query_ast::TantivyQueryAst;
 use crate::query_ast::{
     self, BuildTantivyAst, FieldPresenceQuery, FullTextMode, FullTextParams, QueryAst,
+    MAX_FUZZY_DISTANCE,
 };
 use crate::tokenizers::TokenizerManager;
 use crate::{BooleanOperand, InvalidQuery, JsonLiteral};
@@ -207,6 +208,39 @@ fn is_wildcard(phrase: &str) -> bool {
         .is_break()
 }
 
+/// Characters that need to be escaped in the user query language.
+const RESERVED_CHARS: &[char] = &[
+    '+', '^', '`', ':', '{', '}', '"', '\'', '[', ']', '(', ')', '~', '!', '\\', '*', ' ',
+];
+
+/// Extracts the regular expression out of a term of the form `/regex/`.
+///
+/// Reserved characters of the query language have to be escaped in the regular expression.
+/// Those escapes are removed, while all other escape sequences are left to the regular
+/// expression. For instance, `/qu.\*t/` results in the regular expression `qu.*t`.
+fn extract_regex(phrase: &str) -> Option<String> {
+    let regex = phrase.strip_prefix('/')?.strip_suffix('/')?;
+    if regex.is_empty() {
+        return None;
+    }
+    let mut unescaped = String::with_capacity(regex.len());
+    let mut chars = regex.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(next_c) = chars
+                .peek()
+                .filter(|next_c| RESERVED_CHARS.contains(next_c))
+            {
+                unescaped.push(*next_c);
+                chars.next();
+                continue;
+            }
+        }
+        unescaped.push(c);
+    }
+    Some(unescaped)
+}
+
 fn convert_user_input_literal(
     user_input_literal: UserInputLiteral,
     default_search_fields: &[String],
@@ -242,10 +276,36 @@ fn convert_user_input_literal(
         zero_terms_query: crate::MatchAllOrNone::MatchNone,
     };
     let wildcard = delimiter == Delimiter::None && is_wildcard(&phrase);
+    let regex_opt = if delimiter == Delimiter::None {
+        extract_regex(&phrase)
+    } else {
+        None
+    };
+    // Outside of phrases, the slop operator is interpreted as a fuzzy distance, e.g. `quikwit~1`.
+    let fuzzy = delimiter == Delimiter::None && slop > 0;
+    if fuzzy && slop > MAX_FUZZY_DISTANCE as u32 {
+        anyhow::bail!(
+            "fuzzy query distance must be lower or equal to {MAX_FUZZY_DISTANCE}, got {slop}"
+        );
+    }
     let mut phrase_queries: Vec<QueryAst> = field_names
         .into_iter()
         .map(|field_name| {
-            if prefix {
+            if let Some(regex) = &regex_opt {
+                query_ast::RegexQuery {
+                    field: field_name,
+                    regex: regex.clone(),
+                }
+                .into()
+            } else if fuzzy {
+                query_ast::FuzzyQuery {
+                    field: field_name,
+                    value: phrase.clone(),
+                    distance: slop as u8,
+                    transpositions: true,
+                }
+                .into()
+            } else if prefix {
                 query_ast::PhrasePrefixQuery {
                     field: field_name,
                     phrase: phrase.clone(),
@@ -284,6 +344,7 @@ fn convert_user_input_literal(
 
 #[cfg(test)]
 mod tests {
+    use super::extract_regex;
     use crate::query_ast::{
         BoolQuery, BuildTantivyAst, FullTextMode, FullTextQuery, QueryAst, UserInputQuery,
     };
@@ -392,6 +453,59 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_user_input_query_regex() {
+        let ast = UserInputQuery {
+            user_text: "field:/qu.\\*t/".to_string(),
+            default_fields: None,
+            default_operator: BooleanOperand::And,
+        }
+        .parse_user_query(&[])
+        .unwrap();
+        let QueryAst::Regex(regex_query) = ast else {
+            panic!()
+        };
+        assert_eq!(&regex_query.field, "field");
+        assert_eq!(&regex_query.regex, "qu.*t");
+    }
+
+    #[test]
+    fn test_extract_regex() {
+        assert_eq!(extract_regex("hello"), None);
+        assert_eq!(extract_regex("//"), None);
+        assert_eq!(extract_regex("/hel+o/").unwrap(), "hel+o");
+        assert_eq!(extract_regex("/hel\\+o/").unwrap(), "hel+o");
+        assert_eq!(extract_regex("/hel\\.o/").unwrap(), "hel\\.o");
+        assert_eq!(extract_regex("/\\(a\\|b\\)\\*/").unwrap(), "(a\\|b)*");
+    }
+
+    #[test]
+    fn test_user_input_query_fuzzy() {
+        let ast = UserInputQuery {
+            user_text: "field:quikwit~1".to_string(),
+            default_fields: None,
+            default_operator: BooleanOperand::And,
+        }
+        .parse_user_query(&[])
+        .unwrap();
+        let QueryAst::Fuzzy(fuzzy_query) = ast else {
+            panic!()
+        };
+        assert_eq!(&fuzzy_query.field, "field");
+        assert_eq!(&fuzzy_query.value, "quikwit");
+        assert_eq!(fuzzy_query.distance, 1);
+        assert!(fuzzy_query.transpositions);
+
+        let error = UserInputQuery {
+            user_text: "field:quikwit~3".to_string(),
+            default_fields: None,
+            default_operator: BooleanOperand::And,
+        }
+        .parse_user_query(&[])
+        .unwrap_err();
+        assert!(error.to_string().contains("fuzzy query distance"));
+    }
+
     #[test]
     fn test_user_input_query_override_default_fields() {
         let ast = UserInputQuery {
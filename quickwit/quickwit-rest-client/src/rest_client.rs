@@ -352,6 +352,30 @@ impl<'a> IndexClient<'a> {
         Ok(index_metadata)
     }
 
+    pub async fn update(
+        &self,
+        index_id: &str,
+        index_config: impl ToString,
+        config_format: ConfigFormat,
+    ) -> Result<IndexMetadata, Error> {
+        let header_map = header_from_config_format(config_format);
+        let body = Bytes::from(index_config.to_string());
+        let path = format!("indexes/{index_id}");
+        let response = self
+            .transport
+            .send::<()>(
+                Method::PUT,
+                &path,
+                Some(header_map),
+                None,
+                Some(body),
+                self.timeout,
+            )
+            .await?;
+        let index_metadata = response.deserialize().await?;
+        Ok(index_metadata)
+    }
+
     pub async fn list(&self) -> Result<Vec<IndexMetadata>, Error> {
         let response = self
             .transport
@@ -702,6 +726,10 @@ mod test {
             aggregations: None,
             elapsed_time_micros: 100,
             errors: Vec::new(),
+            timed_out: false,
+            skipped_split_ids: Vec::new(),
+            profile: None,
+            inner_hits: None,
         };
         Mock::given(method("POST"))
             .and(path("/api/v1/my-index/search"))
@@ -918,6 +946,25 @@ mod test {
             index_metadata
         );
 
+        // PUT update index
+        Mock::given(method("PUT"))
+            .and(path("/api/v1/indexes/test-index"))
+            .and(header(CONTENT_TYPE.as_str(), "application/yaml"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK).set_body_json(index_metadata.clone()),
+            )
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        assert_eq!(
+            qw_client
+                .indexes()
+                .update("test-index", "", ConfigFormat::Yaml)
+                .await
+                .unwrap(),
+            index_metadata
+        );
+
         // PUT clear index
         Mock::given(method("PUT"))
             .and(path("/api/v1/indexes/my-index/clear"))
@@ -0,0 +1,484 @@
+// Copyright (C) 2024 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//! Asynchronous searches.
+//!
+//! An async search runs in the background on the node it was submitted to. Its state, including
+//! the results merged so far, is stored in the searchers' KV store (see
+//! [`ClusterClient::put_kv`]), so that it can be polled from any node of the cluster.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use quickwit_common::shared_consts::DELETION_GRACE_PERIOD;
+use quickwit_proto::metastore::MetastoreServiceClient;
+use quickwit_proto::search::{SearchRequest, SearchResponse};
+use serde::{Deserialize, Serialize};
+use tantivy::time::OffsetDateTime;
+use tokio::sync::watch;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+use ulid::Ulid;
+
+use crate::root::root_search_with_progress;
+use crate::service::SearcherContext;
+use crate::{ClusterClient, SearchError};
+
+/// Number of async searches kept by the async search namespace of the KV store of a searcher. The
+/// contexts of async searches are not stored along with the scroll contexts, so that a burst of
+/// scrolls cannot evict the running searches.
+pub(crate) const ASYNC_SEARCH_CAPACITY: usize = 1_000;
+
+pub(crate) const ASYNC_SEARCH_KEY_PREFIX: &[u8] = b"async_search:";
+
+/// Duration for which an async search and its results are kept if the request does not specify
+/// one.
+pub const DEFAULT_ASYNC_SEARCH_KEEP_ALIVE: Duration = Duration::from_secs(5 * 60);
+
+/// An async search is aborted when its keep alive expires. Like for scroll contexts, the search
+/// has to be over before the splits it targets can be garbage collected.
+const MAX_ASYNC_SEARCH_KEEP_ALIVE: Duration =
+    Duration::from_secs(DELETION_GRACE_PERIOD.as_secs() - 60 * 2);
+
+/// State of an async search, as returned by the async search API.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AsyncSearchResponse {
+    /// ID of the async search, used to poll its results and to delete it.
+    pub id: String,
+    /// Index IDs or patterns targeted by the search, against which the callers polling or
+    /// deleting the search are authorized.
+    pub index_id_patterns: Vec<String>,
+    /// Whether the search is still running.
+    pub is_running: bool,
+    /// Whether `response` only covers the leaf responses received so far.
+    pub is_partial: bool,
+    /// Unix timestamp (in seconds) at which the search was submitted.
+    pub start_timestamp: i64,
+    /// Unix timestamp (in seconds) after which the search and its results are discarded.
+    pub expiration_timestamp: i64,
+    /// Search response. While the search is running, it only carries the number of hits and the
+    /// aggregations merged so far.
+    pub response: Option<SearchResponse>,
+    /// Error that made the search fail.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum AsyncSearchStatus {
+    Running {
+        partial_response_opt: Option<SearchResponse>,
+    },
+    Succeeded {
+        response: SearchResponse,
+    },
+    Failed {
+        error: String,
+    },
+    // Tombstone left by the deletion of the search, so that the node running it stops
+    // publishing its results.
+    Deleted,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct AsyncSearchContext {
+    index_id_patterns: Vec<String>,
+    start_timestamp: i64,
+    expiration_timestamp: i64,
+    status: AsyncSearchStatus,
+}
+
+impl AsyncSearchContext {
+    fn ttl(&self) -> Duration {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        Duration::from_secs(self.expiration_timestamp.saturating_sub(now).max(0) as u64)
+    }
+
+    fn into_response(self, async_search_id: Ulid) -> AsyncSearchResponse {
+        let (is_running, response, error) = match self.status {
+            AsyncSearchStatus::Running {
+                partial_response_opt,
+            } => (true, partial_response_opt, None),
+            AsyncSearchStatus::Succeeded { response } => (false, Some(response), None),
+            AsyncSearchStatus::Failed { error } => (false, None, Some(error)),
+            AsyncSearchStatus::Deleted => (false, None, None),
+        };
+        AsyncSearchResponse {
+            id: async_search_id.to_string(),
+            index_id_patterns: self.index_id_patterns,
+            is_running,
+            is_partial: is_running,
+            start_timestamp: self.start_timestamp,
+            expiration_timestamp: self.expiration_timestamp,
+            response,
+            error,
+        }
+    }
+}
+
+/// Async searches running on this node, which can be cancelled when they get deleted.
+#[derive(Clone, Default)]
+pub(crate) struct RunningAsyncSearches {
+    cancellation_tokens: Arc<Mutex<HashMap<Ulid, CancellationToken>>>,
+}
+
+impl RunningAsyncSearches {
+    fn register(&self, async_search_id: Ulid) -> CancellationToken {
+        let cancellation_token = CancellationToken::new();
+        self.cancellation_tokens
+            .lock()
+            .unwrap()
+            .insert(async_search_id, cancellation_token.clone());
+        cancellation_token
+    }
+
+    fn unregister(&self, async_search_id: Ulid) {
+        self.cancellation_tokens
+            .lock()
+            .unwrap()
+            .remove(&async_search_id);
+    }
+
+    fn cancel(&self, async_search_id: Ulid) {
+        if let Some(cancellation_token) = self
+            .cancellation_tokens
+            .lock()
+            .unwrap()
+            .remove(&async_search_id)
+        {
+            cancellation_token.cancel();
+        }
+    }
+}
+
+fn parse_async_search_id(async_search_id: &str) -> crate::Result<Ulid> {
+    Ulid::from_string(async_search_id).map_err(|_| {
+        SearchError::InvalidArgument(format!("invalid async search ID: `{async_search_id}`"))
+    })
+}
+
+fn async_search_key(async_search_id: Ulid) -> Vec<u8> {
+    let mut key = ASYNC_SEARCH_KEY_PREFIX.to_vec();
+    key.extend_from_slice(async_search_id.to_string().as_bytes());
+    key
+}
+
+async fn load_async_search_context(
+    cluster_client: &ClusterClient,
+    async_search_id: Ulid,
+) -> crate::Result<AsyncSearchContext> {
+    let payload = cluster_client
+        .get_kv(&async_search_key(async_search_id))
+        .await
+        .ok_or_else(|| SearchError::AsyncSearchNotFound(async_search_id.to_string()))?;
+    let async_search_context: AsyncSearchContext = serde_json::from_slice(&payload)
+        .map_err(|_| SearchError::Internal("corrupted async search context".to_string()))?;
+    if let AsyncSearchStatus::Deleted = async_search_context.status {
+        return Err(SearchError::AsyncSearchNotFound(
+            async_search_id.to_string(),
+        ));
+    }
+    Ok(async_search_context)
+}
+
+async fn store_async_search_context(
+    cluster_client: &ClusterClient,
+    async_search_id: Ulid,
+    async_search_context: &AsyncSearchContext,
+) {
+    let payload = serde_json::to_vec(async_search_context).unwrap();
+    cluster_client
+        .put_kv(
+            &async_search_key(async_search_id),
+            &payload,
+            async_search_context.ttl(),
+        )
+        .await;
+}
+
+/// Returns true if the async search was explicitly deleted.
+async fn is_async_search_deleted(cluster_client: &ClusterClient, async_search_id: Ulid) -> bool {
+    let payload_opt = cluster_client
+        .get_kv(&async_search_key(async_search_id))
+        .await;
+    is_deleted_async_search_payload(payload_opt.as_deref())
+}
+
+/// A context that is missing, for instance because it got evicted from the KV store or because
+/// the nodes holding it are unreachable, does not count as deleted: the search keeps running and
+/// stores its context again.
+fn is_deleted_async_search_payload(payload_opt: Option<&[u8]>) -> bool {
+    let Some(payload) = payload_opt else {
+        return false;
+    };
+    matches!(
+        serde_json::from_slice::<AsyncSearchContext>(payload),
+        Ok(AsyncSearchContext {
+            status: AsyncSearchStatus::Deleted,
+            ..
+        })
+    )
+}
+
+/// Starts an async search in the background and returns its ID.
+pub(crate) async fn submit_async_search(
+    searcher_context: Arc<SearcherContext>,
+    search_request: SearchRequest,
+    keep_alive: Duration,
+    metastore: MetastoreServiceClient,
+    cluster_client: ClusterClient,
+    running_async_searches: &RunningAsyncSearches,
+) -> crate::Result<AsyncSearchResponse> {
+    if search_request.scroll_ttl_secs.is_some() {
+        return Err(SearchError::InvalidArgument(
+            "async searches do not support scrolling".to_string(),
+        ));
+    }
+    if keep_alive.is_zero() || keep_alive > MAX_ASYNC_SEARCH_KEEP_ALIVE {
+        return Err(SearchError::InvalidArgument(format!(
+            "async search keep alive must be positive and at most {} seconds",
+            MAX_ASYNC_SEARCH_KEEP_ALIVE.as_secs()
+        )));
+    }
+    let async_search_id = Ulid::new();
+    let start_timestamp = OffsetDateTime::now_utc().unix_timestamp();
+    let async_search_context = AsyncSearchContext {
+        index_id_patterns: search_request.index_id_patterns.clone(),
+        start_timestamp,
+        expiration_timestamp: start_timestamp + keep_alive.as_secs() as i64,
+        status: AsyncSearchStatus::Running {
+            partial_response_opt: None,
+        },
+    };
+    store_async_search_context(&cluster_client, async_search_id, &async_search_context).await;
+
+    let cancellation_token = running_async_searches.register(async_search_id);
+    let running_async_searches = running_async_searches.clone();
+    let async_search_response = AsyncSearchResponse {
+        id: async_search_id.to_string(),
+        index_id_patterns: async_search_context.index_id_patterns.clone(),
+        is_running: true,
+        is_partial: true,
+        start_timestamp: async_search_context.start_timestamp,
+        expiration_timestamp: async_search_context.expiration_timestamp,
+        response: None,
+        error: None,
+    };
+    info!(async_search_id=%async_search_id, "submitted async search");
+    tokio::spawn(async move {
+        run_async_search(
+            async_search_id,
+            async_search_context,
+            searcher_context,
+            search_request,
+            keep_alive,
+            metastore,
+            cluster_client,
+            cancellation_token,
+        )
+        .await;
+        running_async_searches.unregister(async_search_id);
+    });
+    Ok(async_search_response)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_async_search(
+    async_search_id: Ulid,
+    mut async_search_context: AsyncSearchContext,
+    searcher_context: Arc<SearcherContext>,
+    search_request: SearchRequest,
+    keep_alive: Duration,
+    metastore: MetastoreServiceClient,
+    cluster_client: ClusterClient,
+    cancellation_token: CancellationToken,
+) {
+    let start_instant = Instant::now();
+    let (progress_tx, mut progress_rx) = watch::channel(None);
+    let search_fut = root_search_with_progress(
+        &searcher_context,
+        search_request,
+        metastore,
+        &cluster_client,
+        Some(&progress_tx),
+    );
+    tokio::pin!(search_fut);
+
+    let search_result = loop {
+        tokio::select! {
+            search_result = &mut search_fut => break search_result,
+            _ = cancellation_token.cancelled() => {
+                // Dropping the search future cancels the in-flight leaf requests.
+                info!(async_search_id=%async_search_id, "async search cancelled");
+                return;
+            }
+            _ = tokio::time::sleep_until(start_instant + keep_alive) => {
+                // The search and its results expire at the same time, so there is nothing
+                // left to store.
+                warn!(async_search_id=%async_search_id, "async search expired before completion");
+                return;
+            }
+            Ok(()) = progress_rx.changed() => {
+                let partial_response_opt = progress_rx.borrow_and_update().clone();
+                // The search may have been deleted from another node, which cannot cancel it.
+                if is_async_search_deleted(&cluster_client, async_search_id).await {
+                    return;
+                }
+                async_search_context.status = AsyncSearchStatus::Running {
+                    partial_response_opt: partial_response_opt.map(|mut partial_response| {
+                        partial_response.elapsed_time_micros =
+                            start_instant.elapsed().as_micros() as u64;
+                        partial_response
+                    }),
+                };
+                store_async_search_context(
+                    &cluster_client,
+                    async_search_id,
+                    &async_search_context,
+                )
+                .await;
+            }
+        }
+    };
+    if is_async_search_deleted(&cluster_client, async_search_id).await {
+        return;
+    }
+    async_search_context.status = match search_result {
+        Ok(search_response) => AsyncSearchStatus::Succeeded {
+            response: search_response,
+        },
+        Err(search_error) => AsyncSearchStatus::Failed {
+            error: search_error.to_string(),
+        },
+    };
+    store_async_search_context(&cluster_client, async_search_id, &async_search_context).await;
+}
+
+/// Returns the current state of an async search.
+pub(crate) async fn get_async_search(
+    async_search_id: &str,
+    cluster_client: &ClusterClient,
+) -> crate::Result<AsyncSearchResponse> {
+    let async_search_id = parse_async_search_id(async_search_id)?;
+    let async_search_context = load_async_search_context(cluster_client, async_search_id).await?;
+    Ok(async_search_context.into_response(async_search_id))
+}
+
+/// Deletes an async search and its results, cancelling the search if it is still running.
+pub(crate) async fn delete_async_search(
+    async_search_id: &str,
+    cluster_client: &ClusterClient,
+    running_async_searches: &RunningAsyncSearches,
+) -> crate::Result<()> {
+    let async_search_id = parse_async_search_id(async_search_id)?;
+    let mut async_search_context =
+        load_async_search_context(cluster_client, async_search_id).await?;
+    async_search_context.status = AsyncSearchStatus::Deleted;
+    store_async_search_context(cluster_client, async_search_id, &async_search_context).await;
+    running_async_searches.cancel(async_search_id);
+    info!(async_search_id=%async_search_id, "deleted async search");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_async_search_context_serde_and_into_response() {
+        let async_search_id = Ulid::new();
+        let async_search_context = AsyncSearchContext {
+            index_id_patterns: vec!["my-index".to_string()],
+            start_timestamp: 1_000,
+            expiration_timestamp: 1_300,
+            status: AsyncSearchStatus::Running {
+                partial_response_opt: Some(SearchResponse {
+                    num_hits: 12,
+                    ..Default::default()
+                }),
+            },
+        };
+        let payload = serde_json::to_vec(&async_search_context).unwrap();
+        let async_search_context: AsyncSearchContext = serde_json::from_slice(&payload).unwrap();
+        let async_search_response = async_search_context.into_response(async_search_id);
+        assert_eq!(async_search_response.id, async_search_id.to_string());
+        assert_eq!(async_search_response.index_id_patterns, ["my-index"]);
+        assert!(async_search_response.is_running);
+        assert!(async_search_response.is_partial);
+        assert_eq!(async_search_response.expiration_timestamp, 1_300);
+        assert_eq!(async_search_response.response.unwrap().num_hits, 12);
+
+        let async_search_context = AsyncSearchContext {
+            index_id_patterns: vec!["my-index".to_string()],
+            start_timestamp: 1_000,
+            expiration_timestamp: 1_300,
+            status: AsyncSearchStatus::Failed {
+                error: "boom".to_string(),
+            },
+        };
+        let async_search_response = async_search_context.into_response(async_search_id);
+        assert!(!async_search_response.is_running);
+        assert!(!async_search_response.is_partial);
+        assert!(async_search_response.response.is_none());
+        assert_eq!(async_search_response.error.as_deref(), Some("boom"));
+        // The context expired a long time ago.
+        assert_eq!(
+            AsyncSearchContext {
+                index_id_patterns: vec!["my-index".to_string()],
+                start_timestamp: 1_000,
+                expiration_timestamp: 1_300,
+                status: AsyncSearchStatus::Deleted,
+            }
+            .ttl(),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn test_is_deleted_async_search_payload() {
+        assert!(!is_deleted_async_search_payload(None));
+
+        let mut async_search_context = AsyncSearchContext {
+            index_id_patterns: vec!["my-index".to_string()],
+            start_timestamp: 1_000,
+            expiration_timestamp: 1_300,
+            status: AsyncSearchStatus::Running {
+                partial_response_opt: None,
+            },
+        };
+        let payload = serde_json::to_vec(&async_search_context).unwrap();
+        assert!(!is_deleted_async_search_payload(Some(&payload)));
+
+        async_search_context.status = AsyncSearchStatus::Deleted;
+        let payload = serde_json::to_vec(&async_search_context).unwrap();
+        assert!(is_deleted_async_search_payload(Some(&payload)));
+    }
+
+    #[test]
+    fn test_parse_async_search_id() {
+        let async_search_id = Ulid::new();
+        assert_eq!(
+            parse_async_search_id(&async_search_id.to_string()).unwrap(),
+            async_search_id
+        );
+        let error = parse_async_search_id("not-an-id").unwrap_err();
+        assert!(matches!(error, SearchError::InvalidArgument(_)));
+    }
+}
@@ -25,27 +25,37 @@ use std::time::Duration;
 use bytesize::ByteSize;
 use futures::{StreamExt, TryStreamExt};
 use http::Uri;
+use quickwit_common::tower::GrpcTimeout;
 use quickwit_proto::search::{
-    GetKvRequest, LeafSearchStreamResponse, PutKvRequest, ReportSplitsRequest,
+    GetKvRequest, LeafSearchStreamResponse, LegacySortValues, PutKvRequest, ReportSplitsRequest,
 };
 use quickwit_proto::tonic::codegen::InterceptedService;
 use quickwit_proto::tonic::transport::{Channel, Endpoint};
 use quickwit_proto::tonic::Request;
 use quickwit_proto::{tonic, SpanContextInterceptor};
 use tokio_stream::wrappers::UnboundedReceiverStream;
-use tower::timeout::Timeout;
 use tracing::{info_span, warn, Instrument};
 
 use crate::error::parse_grpc_error;
 use crate::SearchService;
 
+/// Time given past the timeout of a search request to the node serving it to return its partial
+/// results, before the gRPC call is aborted.
+const SEARCH_TIMEOUT_GRACE_PERIOD: Duration = Duration::from_secs(2);
+
+/// Returns the deadline of the gRPC call serving a search request with a timeout of
+/// `timeout_millis`. The deadline overrides the default timeout of the channel.
+fn grpc_timeout(timeout_millis: u64) -> Duration {
+    Duration::from_millis(timeout_millis) + SEARCH_TIMEOUT_GRACE_PERIOD
+}
+
 /// Impl is an enumeration that meant to manage Quickwit's search service client types.
 #[derive(Clone)]
 enum SearchServiceClientImpl {
     Local(Arc<dyn SearchService>),
     Grpc(
         quickwit_proto::search::search_service_client::SearchServiceClient<
-            InterceptedService<Timeout<Channel>, SpanContextInterceptor>,
+            InterceptedService<GrpcTimeout<Channel>, SpanContextInterceptor>,
         >,
     ),
 }
@@ -76,7 +86,7 @@ impl SearchServiceClient {
     /// Create a search service client instance given a gRPC client and gRPC address.
     pub fn from_grpc_client(
         client: quickwit_proto::search::search_service_client::SearchServiceClient<
-            InterceptedService<Timeout<Channel>, SpanContextInterceptor>,
+            InterceptedService<GrpcTimeout<Channel>, SpanContextInterceptor>,
         >,
         grpc_addr: SocketAddr,
     ) -> Self {
@@ -108,11 +118,16 @@ impl SearchServiceClient {
     /// Perform root search.
     pub async fn root_search(
         &mut self,
-        request: quickwit_proto::search::SearchRequest,
+        mut request: quickwit_proto::search::SearchRequest,
     ) -> crate::Result<quickwit_proto::search::SearchResponse> {
         match &mut self.client_impl {
             SearchServiceClientImpl::Grpc(grpc_client) => {
-                let tonic_request = Request::new(request);
+                request.set_legacy_sort_values();
+                let timeout_opt = request.timeout_millis.map(grpc_timeout);
+                let mut tonic_request = Request::new(request);
+                if let Some(timeout) = timeout_opt {
+                    tonic_request.set_timeout(timeout);
+                }
                 let tonic_response = grpc_client
                     .root_search(tonic_request)
                     .await
@@ -126,16 +141,27 @@ impl SearchServiceClient {
     /// Perform leaf search.
     pub async fn leaf_search(
         &mut self,
-        request: quickwit_proto::search::LeafSearchRequest,
+        mut request: quickwit_proto::search::LeafSearchRequest,
     ) -> crate::Result<quickwit_proto::search::LeafSearchResponse> {
         match &mut self.client_impl {
             SearchServiceClientImpl::Grpc(grpc_client) => {
-                let tonic_request = Request::new(request);
+                request.set_legacy_sort_values();
+                let timeout_opt = request
+                    .search_request
+                    .as_ref()
+                    .and_then(|search_request| search_request.timeout_millis)
+                    .map(grpc_timeout);
+                let mut tonic_request = Request::new(request);
+                if let Some(timeout) = timeout_opt {
+                    tonic_request.set_timeout(timeout);
+                }
                 let tonic_response = grpc_client
                     .leaf_search(tonic_request)
                     .await
                     .map_err(|tonic_error| parse_grpc_error(&tonic_error))?;
-                Ok(tonic_response.into_inner())
+                let mut leaf_search_response = tonic_response.into_inner();
+                leaf_search_response.upgrade_legacy_sort_values();
+                Ok(leaf_search_response)
             }
             SearchServiceClientImpl::Local(service) => service.leaf_search(request).await,
         }
@@ -216,16 +242,19 @@ impl SearchServiceClient {
     /// Perform fetch docs.
     pub async fn fetch_docs(
         &mut self,
-        request: quickwit_proto::search::FetchDocsRequest,
+        mut request: quickwit_proto::search::FetchDocsRequest,
     ) -> crate::Result<quickwit_proto::search::FetchDocsResponse> {
         match &mut self.client_impl {
             SearchServiceClientImpl::Grpc(grpc_client) => {
+                request.set_legacy_sort_values();
                 let tonic_request = Request::new(request);
                 let tonic_response = grpc_client
                     .fetch_docs(tonic_request)
                     .await
                     .map_err(|tonic_error| parse_grpc_error(&tonic_error))?;
-                Ok(tonic_response.into_inner())
+                let mut fetch_docs_response = tonic_response.into_inner();
+                fetch_docs_response.upgrade_legacy_sort_values();
+                Ok(fetch_docs_response)
             }
             SearchServiceClientImpl::Local(service) => service.fetch_docs(request).await,
         }
@@ -312,8 +341,8 @@ impl SearchServiceClient {
 }
 
 /// Creates a [`SearchServiceClient`] from a socket address.
-/// The underlying channel connects lazily and is set up to time out after 5 seconds. It reconnects
-/// automatically should the connection be dropped.
+/// The underlying channel connects lazily and is set up to time out after 5 seconds, unless the
+/// search request carries a timeout. It reconnects automatically should the connection be dropped.
 pub fn create_search_client_from_grpc_addr(
     grpc_addr: SocketAddr,
     max_message_size: ByteSize,
@@ -325,14 +354,14 @@ pub fn create_search_client_from_grpc_addr(
         .build()
         .expect("The URI should be well-formed.");
     let channel = Endpoint::from(uri).connect_lazy();
-    let timeout_channel = Timeout::new(channel, Duration::from_secs(5));
+    let timeout_channel = GrpcTimeout::new(channel, Duration::from_secs(5));
     create_search_client_from_channel(grpc_addr, timeout_channel, max_message_size)
 }
 
 /// Creates a [`SearchServiceClient`] from a pre-established connection (channel).
 pub fn create_search_client_from_channel(
     grpc_addr: SocketAddr,
-    channel: Timeout<Channel>,
+    channel: GrpcTimeout<Channel>,
     max_message_size: ByteSize,
 ) -> SearchServiceClient {
     let client =
@@ -301,6 +301,26 @@ fn merge_leaf_search_response(
             + right_response.num_attempted_splits,
         failed_splits: right_response.failed_splits,
         partial_hits: left_response.partial_hits,
+        skipped_split_ids: left_response
+            .skipped_split_ids
+            .into_iter()
+            .chain(right_response.skipped_split_ids)
+            .collect(),
+        split_profiles: left_response
+            .split_profiles
+            .into_iter()
+            .chain(right_response.split_profiles)
+            .collect(),
+        split_aggregation_results: left_response
+            .split_aggregation_results
+            .into_iter()
+            .chain(right_response.split_aggregation_results)
+            .collect(),
+        top_hits: left_response
+            .top_hits
+            .into_iter()
+            .chain(right_response.top_hits)
+            .collect(),
     })
 }
 
@@ -362,11 +382,12 @@ mod tests {
 
     fn mock_partial_hit(split_id: &str, sort_value: u64, doc_id: u32) -> PartialHit {
         PartialHit {
-            sort_value: Some(SortValue::U64(sort_value).into()),
-            sort_value2: None,
+            sort_values: vec![SortValue::U64(sort_value).into()],
             split_id: split_id.to_string(),
             segment_ord: 1,
             doc_id,
+            collapse_value: None,
+            ..Default::default()
         }
     }
 
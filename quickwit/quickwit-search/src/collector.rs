@@ -19,26 +19,31 @@
 
 use std::borrow::Cow;
 use std::cmp::Ordering;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::io;
+use std::sync::Arc;
 
 use itertools::Itertools;
-use quickwit_common::binary_heap::{SortKeyMapper, TopK};
+use quickwit_common::binary_heap::{top_k, SortKeyMapper, TopK};
 use quickwit_doc_mapper::{DocMapper, WarmupInfo};
 use quickwit_proto::search::{
-    LeafSearchResponse, PartialHit, SearchRequest, SortByValue, SortOrder, SortValue,
-    SplitSearchError,
+    CollapseOptions, LeafSearchResponse, PartialHit, SearchRequest, SortByValue, SortField,
+    SortOrder, SortValue, SplitAggregationResult, SplitSearchError, SplitSearchProfile,
 };
 use serde::Deserialize;
 use tantivy::aggregation::agg_req::{get_fast_field_names, Aggregations};
 use tantivy::aggregation::intermediate_agg_result::IntermediateAggregationResults;
 use tantivy::aggregation::{AggregationLimits, AggregationSegmentCollector};
 use tantivy::collector::{Collector, SegmentCollector};
-use tantivy::columnar::{ColumnType, MonotonicallyMappableToU64};
+use tantivy::columnar::{ColumnType, MonotonicallyMappableToU64, StrColumn};
 use tantivy::fastfield::Column;
 use tantivy::{DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
 
 use crate::filters::{create_timestamp_filter_builder, TimestampFilter, TimestampFilterBuilder};
 use crate::find_trace_ids_collector::{FindTraceIdsCollector, FindTraceIdsSegmentCollector, Span};
+use crate::partial_aggregation_cache::extend_split_aggregation_results;
+use crate::top_hits::{parse_aggregation_request, TopHitsAggregation};
 use crate::GlobalDocAddress;
 
 #[derive(Clone, Debug)]
@@ -54,53 +59,79 @@ pub(crate) enum SortByComponent {
         order: SortOrder,
     },
 }
-impl From<SortByComponent> for SortByPair {
+impl From<SortByComponent> for SortBy {
     fn from(value: SortByComponent) -> Self {
         Self {
-            first: value,
-            second: None,
+            components: vec![value],
         }
     }
 }
+
+/// The criteria used to sort the hits, by decreasing priority.
 #[derive(Clone)]
-pub(crate) struct SortByPair {
-    first: SortByComponent,
-    second: Option<SortByComponent>,
+pub(crate) struct SortBy {
+    components: Vec<SortByComponent>,
 }
-impl SortByPair {
-    pub fn sort_orders(&self) -> (SortOrder, SortOrder) {
-        (
-            self.first.sort_order(),
-            self.second
-                .as_ref()
-                .map(|sort_by| sort_by.sort_order())
-                .unwrap_or(SortOrder::Desc),
-        )
+impl SortBy {
+    /// Returns the sort orders of the components yielding a sort value, that is, all the
+    /// components but `DocId`. Those are the sort values found in `PartialHit::sort_values`.
+    fn value_sort_orders(&self) -> Arc<[SortOrder]> {
+        self.components
+            .iter()
+            .filter(|component| !matches!(component, SortByComponent::DocId { .. }))
+            .map(SortByComponent::sort_order)
+            .collect()
+    }
+
+    /// Returns the order used to break ties with the document address.
+    fn address_sort_order(&self) -> SortOrder {
+        self.components
+            .first()
+            .map(SortByComponent::sort_order)
+            .unwrap_or(SortOrder::Desc)
+    }
+
+    fn hit_sorting_mapper(&self) -> HitSortingMapper {
+        HitSortingMapper {
+            sort_orders: self.value_sort_orders(),
+            address_sort_order: self.address_sort_order(),
+        }
+    }
+
+    pub fn requires_scoring(&self) -> bool {
+        self.components
+            .iter()
+            .any(SortByComponent::requires_scoring)
     }
 }
 impl SortByComponent {
+    /// Returns `None` for `DocId`, which does not yield any sort value.
     fn to_sorting_field_extractor_component(
         &self,
         segment_reader: &SegmentReader,
-    ) -> tantivy::Result<SortingFieldExtractorComponent> {
+    ) -> tantivy::Result<Option<SortingFieldExtractorComponent>> {
         match self {
-            SortByComponent::DocId { .. } => Ok(SortingFieldExtractorComponent::DocId),
+            SortByComponent::DocId { .. } => Ok(None),
             SortByComponent::FastField { field_name, .. } => {
-                let sort_column_opt: Option<(Column<u64>, ColumnType)> =
-                    segment_reader.fast_fields().u64_lenient(field_name)?;
-                let (sort_column, column_type) = sort_column_opt.unwrap_or_else(|| {
-                    (
-                        Column::build_empty_column(segment_reader.max_doc()),
-                        ColumnType::U64,
-                    )
-                });
-                let sort_field_type = SortFieldType::try_from(column_type)?;
-                Ok(SortingFieldExtractorComponent::FastField {
-                    sort_column,
-                    sort_field_type,
-                })
+                let fast_fields = segment_reader.fast_fields();
+                if let Some((sort_column, column_type)) = fast_fields.u64_lenient(field_name)? {
+                    let sort_field_type = SortFieldType::try_from(column_type)?;
+                    return Ok(Some(SortingFieldExtractorComponent::FastField {
+                        sort_column,
+                        sort_field_type,
+                    }));
+                }
+                if let Some(str_column) = fast_fields.str(field_name)? {
+                    return Ok(Some(SortingFieldExtractorComponent::StrFastField {
+                        str_column,
+                    }));
+                }
+                Ok(Some(SortingFieldExtractorComponent::FastField {
+                    sort_column: Column::build_empty_column(segment_reader.max_doc()),
+                    sort_field_type: SortFieldType::U64,
+                }))
             }
-            SortByComponent::Score { .. } => Ok(SortingFieldExtractorComponent::Score),
+            SortByComponent::Score { .. } => Ok(Some(SortingFieldExtractorComponent::Score)),
         }
     }
     pub fn requires_scoring(&self) -> bool {
@@ -137,77 +168,138 @@ enum SortFieldType {
     Bool,
 }
 
-/// The `SortingFieldExtractor` is used to extract a score, which can either be a true score,
-/// a value from a fast field, or nothing (sort by DocId).
+impl SortFieldType {
+    fn sort_value(&self, fast_field_value: u64) -> SortValue {
+        match self {
+            SortFieldType::U64 => SortValue::U64(fast_field_value),
+            SortFieldType::I64 => SortValue::I64(i64::from_u64(fast_field_value)),
+            SortFieldType::F64 => SortValue::F64(f64::from_u64(fast_field_value)),
+            SortFieldType::DateTime => SortValue::I64(i64::from_u64(fast_field_value)),
+            SortFieldType::Bool => SortValue::Boolean(fast_field_value != 0u64),
+        }
+    }
+}
+
+/// The `SortingFieldExtractorComponent` is used to extract a sort key, which can either be a
+/// true score, a value from a fast field, or the term ordinal of a string fast field.
+///
+/// Sort keys are `u64` preserving the order of the values they represent, within a segment.
 enum SortingFieldExtractorComponent {
-    /// If undefined, we simply sort by DocIds.
-    DocId,
     FastField {
         sort_column: Column<u64>,
         sort_field_type: SortFieldType,
     },
+    /// Term ordinals follow the lexicographic order of the terms, but they are only meaningful
+    /// within a segment.
+    StrFastField {
+        str_column: StrColumn,
+    },
     Score,
 }
 
 impl SortingFieldExtractorComponent {
-    /// Returns the sort value for the given element
+    /// Returns the sort key for the given element
     ///
     /// The function returns None if the sort key is a fast field, for which we have no value
-    /// for the given doc_id, or we sort by DocId.
-    fn extract_typed_sort_value_opt(&self, doc_id: DocId, score: Score) -> Option<SortValue> {
-        let map_fast_field_to_value = |fast_field_value, field_type| match field_type {
-            SortFieldType::U64 => SortValue::U64(fast_field_value),
-            SortFieldType::I64 => SortValue::I64(i64::from_u64(fast_field_value)),
-            SortFieldType::F64 => SortValue::F64(f64::from_u64(fast_field_value)),
-            SortFieldType::DateTime => SortValue::I64(i64::from_u64(fast_field_value)),
-            SortFieldType::Bool => SortValue::Boolean(fast_field_value != 0u64),
+    /// for the given doc_id.
+    #[inline]
+    fn extract_sort_key(&self, doc_id: DocId, score: Score) -> Option<u64> {
+        match self {
+            SortingFieldExtractorComponent::FastField { sort_column, .. } => {
+                sort_column.first(doc_id)
+            }
+            SortingFieldExtractorComponent::StrFastField { str_column } => {
+                str_column.term_ords(doc_id).next()
+            }
+            SortingFieldExtractorComponent::Score => Some((score as f64).to_u64()),
+        }
+    }
+
+    /// Converts a sort key into a sort value, which can be compared across segments and splits.
+    fn sort_value(&self, sort_key: u64) -> tantivy::Result<SortValue> {
+        let sort_value = match self {
+            SortingFieldExtractorComponent::FastField {
+                sort_field_type, ..
+            } => sort_field_type.sort_value(sort_key),
+            SortingFieldExtractorComponent::StrFastField { str_column } => {
+                let mut term = String::new();
+                str_column.ord_to_str(sort_key, &mut term)?;
+                SortValue::Str(term)
+            }
+            SortingFieldExtractorComponent::Score => SortValue::F64(f64::from_u64(sort_key)),
         };
+        Ok(sort_value)
+    }
 
+    /// Converts a sort key into a value that can be compared with the search after value
+    /// returned by [`Self::segment_search_after_value`].
+    #[inline]
+    fn search_after_comparable_value(&self, sort_key: u64) -> SortValue {
         match self {
-            SortingFieldExtractorComponent::DocId => None,
             SortingFieldExtractorComponent::FastField {
-                sort_column,
-                sort_field_type,
-                ..
-            } => sort_column
-                .first(doc_id)
-                .map(|field_val| map_fast_field_to_value(field_val, *sort_field_type)),
-            SortingFieldExtractorComponent::Score { .. } => Some(SortValue::F64(score as f64)),
+                sort_field_type, ..
+            } => sort_field_type.sort_value(sort_key),
+            SortingFieldExtractorComponent::StrFastField { .. } => SortValue::U64(2 * sort_key + 1),
+            SortingFieldExtractorComponent::Score => SortValue::F64(f64::from_u64(sort_key)),
         }
     }
-}
 
-impl From<SortingFieldExtractorComponent> for SortingFieldExtractorPair {
-    fn from(value: SortingFieldExtractorComponent) -> Self {
-        Self {
-            first: value,
-            second: None,
-        }
+    /// Resolves a search after value for the segment.
+    ///
+    /// Comparing strings for every document would be expensive, so for string fast fields, the
+    /// search after term is located in the segment term dictionary instead: it becomes
+    /// `2 * term_ord + 1` if the term exists, and `2 * num_lower_terms` otherwise. Document term
+    /// ordinals are then mapped to `2 * term_ord + 1`.
+    ///
+    /// For other fields, integers sent as strings are parsed.
+    fn segment_search_after_value(
+        &self,
+        search_after_value: Option<&SortValue>,
+    ) -> tantivy::Result<Option<SortValue>> {
+        let SortingFieldExtractorComponent::StrFastField { str_column } = self else {
+            let segment_search_after_value = match search_after_value {
+                Some(SortValue::Str(text)) => {
+                    SortValue::parse_integer(text).unwrap_or_else(|| SortValue::Str(text.clone()))
+                }
+                Some(sort_value) => sort_value.clone(),
+                None => return Ok(None),
+            };
+            return Ok(Some(segment_search_after_value));
+        };
+        let segment_search_after_value = match search_after_value {
+            Some(SortValue::Str(term)) => {
+                let (num_lower_terms, term_exists) = locate_term(str_column, term)?;
+                if term_exists {
+                    2 * num_lower_terms + 1
+                } else {
+                    2 * num_lower_terms
+                }
+            }
+            // Strings are greater than any other type.
+            Some(_) => 0,
+            None => return Ok(None),
+        };
+        Ok(Some(SortValue::U64(segment_search_after_value)))
     }
 }
 
-pub(crate) struct SortingFieldExtractorPair {
-    first: SortingFieldExtractorComponent,
-    second: Option<SortingFieldExtractorComponent>,
-}
-
-impl SortingFieldExtractorPair {
-    /// Returns the list of sort values for the given element
-    ///
-    /// See also [`SortingFieldExtractorComponent::extract_typed_sort_value_opt`] for more
-    /// information.
-    fn extract_typed_sort_value(
-        &self,
-        doc_id: DocId,
-        score: Score,
-    ) -> (Option<SortValue>, Option<SortValue>) {
-        let first = self.first.extract_typed_sort_value_opt(doc_id, score);
-        let second = self
-            .second
-            .as_ref()
-            .and_then(|second| second.extract_typed_sort_value_opt(doc_id, score));
-        (first, second)
+/// Returns the number of terms of the column dictionary lower than `term`, and whether `term`
+/// belongs to the dictionary.
+fn locate_term(str_column: &StrColumn, term: &str) -> io::Result<(u64, bool)> {
+    let mut buffer = String::new();
+    let mut low = 0u64;
+    let mut high = str_column.num_terms() as u64;
+    while low < high {
+        let mid = low + (high - low) / 2;
+        buffer.clear();
+        str_column.ord_to_str(mid, &mut buffer)?;
+        match buffer.as_str().cmp(term) {
+            Ordering::Less => low = mid + 1,
+            Ordering::Equal => return Ok((mid, true)),
+            Ordering::Greater => high = mid,
+        }
     }
+    Ok((low, false))
 }
 
 impl TryFrom<ColumnType> for SortFieldType {
@@ -228,66 +320,42 @@ impl TryFrom<ColumnType> for SortFieldType {
     }
 }
 
-/// Takes a user-defined sorting criteria and resolves it to a
-/// segment specific `SortingFieldExtractorPair`.
-fn get_score_extractor(
-    sort_by: &SortByPair,
+/// Takes a user-defined sorting criteria and resolves it to the segment specific
+/// `SortingFieldExtractorComponent`s, one per component yielding a sort value.
+fn get_score_extractors(
+    sort_by: &SortBy,
     segment_reader: &SegmentReader,
-) -> tantivy::Result<SortingFieldExtractorPair> {
-    Ok(SortingFieldExtractorPair {
-        first: sort_by
-            .first
-            .to_sorting_field_extractor_component(segment_reader)?,
-        second: sort_by
-            .second
-            .as_ref()
-            .map(|first| first.to_sorting_field_extractor_component(segment_reader))
-            .transpose()?,
-    })
-}
-
-/// PartialHitHeapItem order is the inverse of the natural order
-/// so that we actually have a min-heap.
-#[derive(Clone, Copy, Debug)]
-struct PartialHitHeapItem {
-    sort_value_opt1: Option<u64>,
-    sort_value_opt2: Option<u64>,
-    doc_id: DocId,
-}
-
-impl PartialOrd for PartialHitHeapItem {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+) -> tantivy::Result<Vec<SortingFieldExtractorComponent>> {
+    let mut score_extractors = Vec::with_capacity(sort_by.components.len());
+    for component in &sort_by.components {
+        if let Some(score_extractor) =
+            component.to_sorting_field_extractor_component(segment_reader)?
+        {
+            score_extractors.push(score_extractor);
+        }
     }
+    Ok(score_extractors)
 }
 
-impl Ord for PartialHitHeapItem {
-    #[inline]
-    fn cmp(&self, other: &Self) -> Ordering {
-        let by_sorting_field1 = other.sort_value_opt1.cmp(&self.sort_value_opt1);
-        let by_sorting_field2 = other.sort_value_opt2.cmp(&self.sort_value_opt2);
-
-        let lazy_order_by_doc_id = || {
-            self.doc_id
-                .partial_cmp(&other.doc_id)
-                .unwrap_or(Ordering::Equal)
-        };
-
-        // In case of a tie on the feature, we sort by ascending `DocId`.
-        by_sorting_field1
-            .then_with(|| by_sorting_field2)
-            .then_with(lazy_order_by_doc_id)
+/// Maps a sort key to a key whose natural order is the requested order.
+///
+/// This is an involution, so it also maps the key back.
+#[inline]
+fn order_sort_key(sort_key: u64, sort_order: SortOrder) -> u64 {
+    match sort_order {
+        SortOrder::Desc => sort_key,
+        SortOrder::Asc => u64::MAX - sort_key,
     }
 }
 
-impl PartialEq for PartialHitHeapItem {
-    fn eq(&self, other: &Self) -> bool {
-        self.cmp(other) == Ordering::Equal
+#[inline]
+fn order_doc_id(doc_id: DocId, sort_order: SortOrder) -> DocId {
+    match sort_order {
+        SortOrder::Desc => doc_id,
+        SortOrder::Asc => DocId::MAX - doc_id,
     }
 }
 
-impl Eq for PartialHitHeapItem {}
-
 enum AggregationSegmentCollectors {
     FindTraceIdsSegmentCollector(Box<FindTraceIdsSegmentCollector>),
     TantivyAggregationSegmentCollector(AggregationSegmentCollector),
@@ -297,57 +365,251 @@ enum AggregationSegmentCollectors {
 pub struct QuickwitSegmentCollector {
     num_hits: u64,
     split_id: String,
-    score_extractor: SortingFieldExtractorPair,
+    score_extractors: Vec<SortingFieldExtractorComponent>,
+    sort_orders: Arc<[SortOrder]>,
+    address_sort_order: SortOrder,
+    // Avoids allocating the sort keys of the documents which don't make it to the top K.
+    sort_keys_buffer: Vec<Option<u64>>,
     // PartialHits in this heap don't contain a split_id yet.
-    top_k_hits: TopK<SegmentPartialHit, SegmentPartialHitSortingKey, HitSortingMapper>,
+    top_k_hits: TopK<SegmentPartialHit, SegmentPartialHit, HitSortingMapper>,
     segment_ord: u32,
     timestamp_filter_opt: Option<TimestampFilter>,
     aggregation: Option<AggregationSegmentCollectors>,
     search_after: Option<PartialHit>,
+    segment_search_after_values: Vec<Option<SortValue>>,
     split_search_after_order: Ordering,
+    // If set, the hits are collected in `collapse` instead of `top_k_hits`, whose length is then
+    // the number of groups to return.
+    collapse: Option<SegmentCollapse>,
+    top_hits: Option<SegmentTopHits>,
 }
 
-impl QuickwitSegmentCollector {
+/// Collects the top hits of each group of documents sharing the same collapse value within a
+/// segment. Groups are identified by the term ordinal of their value.
+struct SegmentCollapse {
+    // `None` if the collapse field has no string column in the segment, in which case all the
+    // documents belong to the group without value.
+    str_column_opt: Option<StrColumn>,
+    top_k_hits: CollapsedTopK<Option<u64>, SegmentPartialHit, SegmentPartialHit, HitSortingMapper>,
+}
+
+/// Collects the top hits of a `top_hits` aggregation within a segment, either of all the
+/// documents or of each term of the field of its parent `terms` aggregation. Groups are then
+/// identified by the term ordinal of their term.
+struct SegmentTopHits {
+    score_extractors: Vec<SortingFieldExtractorComponent>,
+    sort_orders: Arc<[SortOrder]>,
+    address_sort_order: SortOrder,
+    terms_opt: Option<SegmentTopHitsTerms>,
+    top_k_hits: CollapsedTopK<Option<u64>, SegmentPartialHit, SegmentPartialHit, HitSortingMapper>,
+}
+
+struct SegmentTopHitsTerms {
+    // `None` if the field has no string column in the segment, in which case no document belongs
+    // to any term.
+    str_column_opt: Option<StrColumn>,
+    doc_counts: HashMap<u64, u64>,
+    segment_size_opt: Option<usize>,
+}
+
+impl SegmentTopHits {
+    fn for_segment(
+        top_hits_aggregation: &TopHitsAggregation,
+        segment_reader: &SegmentReader,
+    ) -> tantivy::Result<Self> {
+        let sort_by = sort_by_from_sort_fields(&top_hits_aggregation.sort_fields);
+        let score_extractors = get_score_extractors(&sort_by, segment_reader)?;
+        let sort_key_mapper = sort_by.hit_sorting_mapper();
+        let terms_opt = match &top_hits_aggregation.terms_opt {
+            Some(terms) => Some(SegmentTopHitsTerms {
+                str_column_opt: segment_reader.fast_fields().str(&terms.field_name)?,
+                doc_counts: HashMap::new(),
+                segment_size_opt: terms.segment_size_opt,
+            }),
+            None => None,
+        };
+        Ok(SegmentTopHits {
+            score_extractors,
+            sort_orders: sort_key_mapper.sort_orders.clone(),
+            address_sort_order: sort_key_mapper.address_sort_order,
+            terms_opt,
+            top_k_hits: CollapsedTopK::new(
+                top_hits_aggregation.num_hits_per_group(),
+                sort_key_mapper,
+            ),
+        })
+    }
+
     #[inline]
-    fn collect_top_k(&mut self, doc_id: DocId, score: Score) {
-        let (sort_value, sort_value2) =
-            self.score_extractor.extract_typed_sort_value(doc_id, score);
+    fn collect(&mut self, doc_id: DocId, score: Score) {
+        let sort_keys = self
+            .score_extractors
+            .iter()
+            .zip(&*self.sort_orders)
+            .map(|(score_extractor, sort_order)| {
+                score_extractor
+                    .extract_sort_key(doc_id, score)
+                    .map(|sort_key| order_sort_key(sort_key, *sort_order))
+            })
+            .collect();
+        let hit = SegmentPartialHit {
+            sort_keys,
+            doc_key: order_doc_id(doc_id, self.address_sort_order),
+            doc_id,
+        };
+        let Some(terms) = &mut self.terms_opt else {
+            self.top_k_hits.add_entry(None, hit);
+            return;
+        };
+        let Some(str_column) = &terms.str_column_opt else {
+            return;
+        };
+        // As in the `terms` aggregation, a document belongs to the buckets of all its terms.
+        for term_ord in str_column.term_ords(doc_id) {
+            *terms.doc_counts.entry(term_ord).or_default() += 1;
+            self.top_k_hits.add_entry(Some(term_ord), hit.clone());
+        }
+    }
 
-        if let Some(search_after) = &self.search_after {
-            let search_after_value1 = search_after.sort_value.and_then(|v| v.sort_value);
-            let search_after_value2 = search_after.sort_value2.and_then(|v| v.sort_value);
-            let orders = &self.top_k_hits.sort_key_mapper;
-            let mut cmp_result = orders
-                .order1
-                .compare_opt(&sort_value, &search_after_value1)
-                .then_with(|| {
-                    orders
-                        .order2
-                        .compare_opt(&sort_value2, &search_after_value2)
-                });
-            if !search_after.split_id.is_empty() {
-                // TODO actually it's not first, it should be what's in _shard_doc then first then
-                // default
-                let order = orders.order1;
-                cmp_result = cmp_result
-                    .then(self.split_search_after_order)
-                    .then_with(|| order.compare(&self.segment_ord, &search_after.segment_ord))
-                    .then_with(|| order.compare(&doc_id, &search_after.doc_id))
+    fn harvest(
+        self,
+        split_id: &str,
+        segment_ord: SegmentOrdinal,
+    ) -> tantivy::Result<Vec<PartialHit>> {
+        // The `terms` aggregation only keeps the terms with the highest doc counts of each
+        // segment. Ties are all kept, so that the terms kept are a superset of the terms kept by
+        // the aggregation, whatever the way it breaks ties.
+        let min_doc_count = match &self.terms_opt {
+            Some(SegmentTopHitsTerms {
+                doc_counts,
+                segment_size_opt: Some(segment_size),
+                ..
+            }) if doc_counts.len() > *segment_size => {
+                let mut sorted_doc_counts: Vec<u64> = doc_counts.values().copied().collect();
+                sorted_doc_counts.sort_unstable_by(|left, right| right.cmp(left));
+                sorted_doc_counts[*segment_size - 1]
             }
+            _ => 0,
+        };
+        let num_groups = self.top_k_hits.groups.len();
+        let mut partial_hits = Vec::new();
+
+        for (term_ord_opt, segment_partial_hits) in self.top_k_hits.finalize(num_groups) {
+            let term_opt = match (&self.terms_opt, term_ord_opt) {
+                (Some(terms), Some(term_ord)) => {
+                    if terms.doc_counts[&term_ord] < min_doc_count {
+                        continue;
+                    }
+                    let str_column = terms
+                        .str_column_opt
+                        .as_ref()
+                        .expect("term ordinals should come from the string column");
+                    let mut term = String::new();
+                    str_column.ord_to_str(term_ord, &mut term)?;
+                    Some(term)
+                }
+                _ => None,
+            };
+            for segment_partial_hit in segment_partial_hits {
+                let mut partial_hit = segment_partial_hit.into_partial_hit(
+                    &self.score_extractors,
+                    &self.sort_orders,
+                    split_id.to_string(),
+                    segment_ord,
+                )?;
+                partial_hit.collapse_value = term_opt.clone();
+                partial_hits.push(partial_hit);
+            }
+        }
+        Ok(partial_hits)
+    }
+}
 
-            if cmp_result != Ordering::Less {
-                return;
+impl QuickwitSegmentCollector {
+    #[inline]
+    fn collect_top_k(&mut self, doc_id: DocId, score: Score) {
+        if self.top_k_hits.max_len() == 0 {
+            return;
+        }
+        self.sort_keys_buffer.clear();
+        for score_extractor in &self.score_extractors {
+            self.sort_keys_buffer
+                .push(score_extractor.extract_sort_key(doc_id, score));
+        }
+        if !self.is_after_search_after(doc_id) {
+            return;
+        }
+        for (sort_key_opt, sort_order) in self.sort_keys_buffer.iter_mut().zip(&*self.sort_orders) {
+            if let Some(sort_key) = sort_key_opt {
+                *sort_key = order_sort_key(*sort_key, *sort_order);
             }
         }
+        let doc_key = order_doc_id(doc_id, self.address_sort_order);
 
+        if let Some(collapse) = &mut self.collapse {
+            let term_ord_opt = collapse
+                .str_column_opt
+                .as_ref()
+                .and_then(|str_column| str_column.term_ords(doc_id).next());
+            let hit = SegmentPartialHit {
+                sort_keys: self.sort_keys_buffer.clone(),
+                doc_key,
+                doc_id,
+            };
+            collapse.top_k_hits.add_entry(term_ord_opt, hit);
+            return;
+        }
+        if self.top_k_hits.at_capacity() {
+            if let Some(worst_hit) = self.top_k_hits.peek_worst() {
+                let cmp_result = (&self.sort_keys_buffer[..], doc_key)
+                    .cmp(&(&worst_hit.sort_keys[..], worst_hit.doc_key));
+                if cmp_result != Ordering::Greater {
+                    return;
+                }
+            }
+        }
         let hit = SegmentPartialHit {
-            sort_value: sort_value.map(Into::into),
-            sort_value2: sort_value2.map(Into::into),
+            sort_keys: self.sort_keys_buffer.clone(),
+            doc_key,
             doc_id,
         };
         self.top_k_hits.add_entry(hit);
     }
 
+    /// Returns true if the document comes after the search after hit, if any. Expects the
+    /// document sort keys to be in `sort_keys_buffer`.
+    #[inline]
+    fn is_after_search_after(&self, doc_id: DocId) -> bool {
+        let Some(search_after) = &self.search_after else {
+            return true;
+        };
+        let mut cmp_result = Ordering::Equal;
+        for (((score_extractor, sort_order), sort_key_opt), search_after_value) in self
+            .score_extractors
+            .iter()
+            .zip(&*self.sort_orders)
+            .zip(&self.sort_keys_buffer)
+            .zip(&self.segment_search_after_values)
+        {
+            let sort_value = sort_key_opt
+                .map(|sort_key| score_extractor.search_after_comparable_value(sort_key));
+            cmp_result = sort_order.compare_opt(&sort_value, search_after_value);
+            if cmp_result != Ordering::Equal {
+                break;
+            }
+        }
+        if !search_after.split_id.is_empty() {
+            // TODO actually it's not first, it should be what's in _shard_doc then first then
+            // default
+            let order = self.address_sort_order;
+            cmp_result = cmp_result
+                .then(self.split_search_after_order)
+                .then_with(|| order.compare(&self.segment_ord, &search_after.segment_ord))
+                .then_with(|| order.compare(&doc_id, &search_after.doc_id))
+        }
+        cmp_result == Ordering::Less
+    }
+
     #[inline]
     fn accept_document(&self, doc_id: DocId) -> bool {
         if let Some(ref timestamp_filter) = self.timestamp_filter_opt {
@@ -357,26 +619,44 @@ impl QuickwitSegmentCollector {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+/// A hit within a segment.
+///
+/// The sort keys and the doc ID are mapped according to their sort order, so that the natural
+/// order of `SegmentPartialHit` is the requested order: the greater, the better. Missing values
+/// always come last, whatever the sort order.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 struct SegmentPartialHit {
-    sort_value: Option<SortValue>,
-    sort_value2: Option<SortValue>,
+    sort_keys: Vec<Option<u64>>,
+    doc_key: DocId,
     doc_id: DocId,
 }
 
 impl SegmentPartialHit {
-    fn into_partial_hit(self, split_id: String, segment_ord: SegmentOrdinal) -> PartialHit {
-        PartialHit {
-            sort_value: self.sort_value.map(|sort_value| SortByValue {
-                sort_value: Some(sort_value),
-            }),
-            sort_value2: self.sort_value2.map(|sort_value| SortByValue {
-                sort_value: Some(sort_value),
-            }),
+    fn into_partial_hit(
+        self,
+        score_extractors: &[SortingFieldExtractorComponent],
+        sort_orders: &[SortOrder],
+        split_id: String,
+        segment_ord: SegmentOrdinal,
+    ) -> tantivy::Result<PartialHit> {
+        let mut sort_values = Vec::with_capacity(self.sort_keys.len());
+        for ((score_extractor, sort_order), sort_key_opt) in
+            score_extractors.iter().zip(sort_orders).zip(self.sort_keys)
+        {
+            let sort_value = sort_key_opt
+                .map(|sort_key| score_extractor.sort_value(order_sort_key(sort_key, *sort_order)))
+                .transpose()?;
+            sort_values.push(SortByValue { sort_value });
+        }
+        Ok(PartialHit {
+            sort_value: None,
+            sort_value2: None,
+            sort_values,
             doc_id: self.doc_id,
             split_id,
             segment_ord,
-        }
+            collapse_value: None,
+        })
     }
 }
 
@@ -392,6 +672,10 @@ impl SegmentCollector for QuickwitSegmentCollector {
         self.num_hits += 1;
         self.collect_top_k(doc_id, score);
 
+        if let Some(top_hits) = &mut self.top_hits {
+            top_hits.collect(doc_id, score);
+        }
+
         match self.aggregation.as_mut() {
             Some(AggregationSegmentCollectors::FindTraceIdsSegmentCollector(collector)) => {
                 collector.collect(doc_id, score)
@@ -404,14 +688,44 @@ impl SegmentCollector for QuickwitSegmentCollector {
     }
 
     fn harvest(self) -> Self::Fruit {
-        let partial_hits: Vec<PartialHit> = self
-            .top_k_hits
-            .finalize()
-            .into_iter()
-            .map(|segment_partial_hit: SegmentPartialHit| {
-                segment_partial_hit.into_partial_hit(self.split_id.clone(), self.segment_ord)
-            })
-            .collect();
+        let partial_hits: Vec<PartialHit> = if let Some(collapse) = self.collapse {
+            let num_groups = self.top_k_hits.max_len();
+            let mut partial_hits = Vec::new();
+            for (term_ord_opt, segment_partial_hits) in collapse.top_k_hits.finalize(num_groups) {
+                let collapse_value = match (&collapse.str_column_opt, term_ord_opt) {
+                    (Some(str_column), Some(term_ord)) => {
+                        let mut collapse_value = String::new();
+                        str_column.ord_to_str(term_ord, &mut collapse_value)?;
+                        Some(collapse_value)
+                    }
+                    _ => None,
+                };
+                for segment_partial_hit in segment_partial_hits {
+                    let mut partial_hit = segment_partial_hit.into_partial_hit(
+                        &self.score_extractors,
+                        &self.sort_orders,
+                        self.split_id.clone(),
+                        self.segment_ord,
+                    )?;
+                    partial_hit.collapse_value = collapse_value.clone();
+                    partial_hits.push(partial_hit);
+                }
+            }
+            partial_hits
+        } else {
+            self.top_k_hits
+                .finalize()
+                .into_iter()
+                .map(|segment_partial_hit: SegmentPartialHit| {
+                    segment_partial_hit.into_partial_hit(
+                        &self.score_extractors,
+                        &self.sort_orders,
+                        self.split_id.clone(),
+                        self.segment_ord,
+                    )
+                })
+                .collect::<tantivy::Result<_>>()?
+        };
 
         let intermediate_aggregation_result = match self.aggregation {
             Some(AggregationSegmentCollectors::FindTraceIdsSegmentCollector(collector)) => {
@@ -427,12 +741,20 @@ impl SegmentCollector for QuickwitSegmentCollector {
             }
             None => None,
         };
+        let top_hits = match self.top_hits {
+            Some(top_hits) => top_hits.harvest(&self.split_id, self.segment_ord)?,
+            None => Vec::new(),
+        };
         Ok(LeafSearchResponse {
             intermediate_aggregation_result,
             num_hits: self.num_hits,
             partial_hits,
             failed_splits: Vec::new(),
             num_attempted_splits: 1,
+            skipped_split_ids: Vec::new(),
+            split_profiles: Vec::new(),
+            split_aggregation_results: Vec::new(),
+            top_hits,
         })
     }
 }
@@ -507,13 +829,13 @@ impl QuickwitIncrementalAggregations {
                         if let Some(last_elem) = first.last() {
                             let timestamp = last_elem.span_timestamp.into_timestamp_nanos();
                             return Some(PartialHit {
-                                sort_value: Some(SortByValue {
-                                    sort_value: Some(SortValue::I64(timestamp)),
-                                }),
+                                sort_value: None,
                                 sort_value2: None,
+                                sort_values: vec![SortValue::I64(timestamp).into()],
                                 split_id: String::new(),
                                 segment_ord: 0,
                                 doc_id: 0,
+                                collapse_value: None,
                             });
                         }
                     }
@@ -556,19 +878,20 @@ pub(crate) struct QuickwitCollector {
     pub split_id: String,
     pub start_offset: usize,
     pub max_hits: usize,
-    pub sort_by: SortByPair,
+    pub sort_by: SortBy,
     timestamp_filter_builder_opt: Option<TimestampFilterBuilder>,
     pub aggregation: Option<QuickwitAggregations>,
     pub aggregation_limits: AggregationLimits,
     search_after: Option<PartialHit>,
+    collapse: Option<CollapseOptions>,
+    top_hits: Option<TopHitsAggregation>,
 }
 
 impl QuickwitCollector {
     pub fn fast_field_names(&self) -> HashSet<String> {
         let mut fast_field_names = HashSet::default();
-        self.sort_by.first.add_fast_field(&mut fast_field_names);
-        if let Some(sort_by_second) = &self.sort_by.second {
-            sort_by_second.add_fast_field(&mut fast_field_names);
+        for sort_by_component in &self.sort_by.components {
+            sort_by_component.add_fast_field(&mut fast_field_names);
         }
         if let Some(aggregations) = &self.aggregation {
             fast_field_names.extend(aggregations.fast_field_names());
@@ -576,9 +899,74 @@ impl QuickwitCollector {
         if let Some(timestamp_filter_builder) = &self.timestamp_filter_builder_opt {
             fast_field_names.insert(timestamp_filter_builder.timestamp_field_name.clone());
         }
+        if let Some(collapse) = &self.collapse {
+            fast_field_names.insert(collapse.field_name.clone());
+        }
+        if let Some(top_hits) = &self.top_hits {
+            for sort_by_component in &sort_by_from_sort_fields(&top_hits.sort_fields).components {
+                sort_by_component.add_fast_field(&mut fast_field_names);
+            }
+            if let Some(terms) = &top_hits.terms_opt {
+                fast_field_names.insert(terms.field_name.clone());
+            }
+        }
         fast_field_names
     }
 
+    /// Returns the number of hits kept for each group of collapsed hits, or `None` if the hits
+    /// are not collapsed.
+    fn max_hits_per_group(&self) -> Option<usize> {
+        self.collapse
+            .as_ref()
+            .map(|collapse| (collapse.max_inner_hits as usize).max(1))
+    }
+
+    /// Drops the hits before `start_offset`. If the hits are collapsed, `start_offset` is a
+    /// number of groups.
+    fn drain_start_offset(&self, partial_hits: &mut Vec<PartialHit>) {
+        if self.start_offset == 0 {
+            return;
+        }
+        let num_hits_to_drain = if self.collapse.is_some() {
+            (0..partial_hits.len())
+                .filter(|&position| {
+                    position == 0
+                        || partial_hits[position].collapse_value
+                            != partial_hits[position - 1].collapse_value
+                })
+                .nth(self.start_offset)
+                .unwrap_or(partial_hits.len())
+        } else {
+            self.start_offset.min(partial_hits.len())
+        };
+        partial_hits.drain(0..num_hits_to_drain);
+    }
+
+    /// Returns an empty collection of the top hits of the groups of the `top_hits` aggregation, if
+    /// any.
+    fn top_hits_collapsed_top_k(
+        &self,
+    ) -> Option<CollapsedTopK<Option<String>, PartialHit, PartialHitSortingKey, HitSortingMapper>>
+    {
+        self.top_hits.as_ref().map(|top_hits| {
+            let sort_key_mapper =
+                sort_by_from_sort_fields(&top_hits.sort_fields).hit_sorting_mapper();
+            CollapsedTopK::new(top_hits.num_hits_per_group(), sort_key_mapper)
+        })
+    }
+
+    /// Merges the top hits of the groups of the `top_hits` aggregation. The merged groups are
+    /// all kept, the root selects the ones to return from the aggregation result.
+    fn merge_top_hits(&self, top_hits: impl Iterator<Item = PartialHit>) -> Vec<PartialHit> {
+        let Some(mut collapsed_top_k_hits) = self.top_hits_collapsed_top_k() else {
+            return Vec::new();
+        };
+        for partial_hit in top_hits {
+            collapsed_top_k_hits.add_entry(partial_hit.collapse_value.clone(), partial_hit);
+        }
+        collapsed_top_k_hits.finalize_all_flat()
+    }
+
     pub fn warmup_info(&self) -> WarmupInfo {
         WarmupInfo {
             fast_field_names: self.fast_field_names(),
@@ -622,12 +1010,42 @@ impl Collector for QuickwitCollector {
             ),
             None => None,
         };
-        let score_extractor = get_score_extractor(&self.sort_by, segment_reader)?;
-        let (order1, order2) = self.sort_by.sort_orders();
-        let sort_key_mapper = HitSortingMapper { order1, order2 };
+        let score_extractors = get_score_extractors(&self.sort_by, segment_reader)?;
+        let sort_key_mapper = self.sort_by.hit_sorting_mapper();
+        let sort_orders = sort_key_mapper.sort_orders.clone();
+        let address_sort_order = sort_key_mapper.address_sort_order;
+        let mut segment_search_after_values = Vec::new();
+        if let Some(search_after) = &self.search_after {
+            for (position, score_extractor) in score_extractors.iter().enumerate() {
+                let search_after_value = search_after
+                    .sort_values
+                    .get(position)
+                    .and_then(|sort_by_value| sort_by_value.sort_value.as_ref());
+                segment_search_after_values
+                    .push(score_extractor.segment_search_after_value(search_after_value)?);
+            }
+        }
+        let collapse = if let Some(max_hits_per_group) = self.max_hits_per_group() {
+            let field_name = &self
+                .collapse
+                .as_ref()
+                .expect("collapse should be set")
+                .field_name;
+            Some(SegmentCollapse {
+                str_column_opt: segment_reader.fast_fields().str(field_name)?,
+                top_k_hits: CollapsedTopK::new(max_hits_per_group, sort_key_mapper.clone()),
+            })
+        } else {
+            None
+        };
+        let top_hits = self
+            .top_hits
+            .as_ref()
+            .map(|top_hits| SegmentTopHits::for_segment(top_hits, segment_reader))
+            .transpose()?;
         let split_search_after_order = if let Some(search_after) = &self.search_after {
             if !search_after.split_id.is_empty() {
-                order1.compare(&self.split_id, &search_after.split_id)
+                address_sort_order.compare(&self.split_id, &search_after.split_id)
             } else {
                 // so we don't reject document based on their split_id if we don't have one in
                 // search_after
@@ -640,13 +1058,19 @@ impl Collector for QuickwitCollector {
         Ok(QuickwitSegmentCollector {
             num_hits: 0u64,
             split_id: self.split_id.clone(),
-            score_extractor,
+            sort_keys_buffer: Vec::with_capacity(score_extractors.len()),
+            score_extractors,
+            sort_orders,
+            address_sort_order,
             top_k_hits: TopK::new(leaf_max_hits, sort_key_mapper),
             segment_ord,
             timestamp_filter_opt,
             aggregation,
             search_after: self.search_after.clone(),
+            segment_search_after_values,
             split_search_after_order,
+            collapse,
+            top_hits,
         })
     }
 
@@ -654,44 +1078,40 @@ impl Collector for QuickwitCollector {
         // We do not need BM25 scoring in Quickwit if it is not opted-in.
         // By returning false, we inform tantivy that it does not need to decompress
         // term frequencies.
-        self.sort_by.first.requires_scoring()
-            || self
-                .sort_by
-                .second
-                .as_ref()
-                .map(|sort_by| sort_by.requires_scoring())
-                .unwrap_or(false)
+        self.sort_by.requires_scoring()
+            || self.top_hits.as_ref().is_some_and(|top_hits| {
+                sort_by_from_sort_fields(&top_hits.sort_fields).requires_scoring()
+            })
     }
 
     fn merge_fruits(
         &self,
         segment_fruits: Vec<tantivy::Result<LeafSearchResponse>>,
     ) -> tantivy::Result<Self::Fruit> {
-        let segment_fruits: tantivy::Result<Vec<LeafSearchResponse>> =
-            segment_fruits.into_iter().collect();
+        let mut segment_fruits: Vec<LeafSearchResponse> =
+            segment_fruits.into_iter().collect::<tantivy::Result<_>>()?;
+        let top_hits = self.merge_top_hits(
+            segment_fruits
+                .iter_mut()
+                .flat_map(|segment_fruit| std::mem::take(&mut segment_fruit.top_hits)),
+        );
         // We want the hits in [start_offset..start_offset + max_hits).
         // All leaves will return their top [0..start_offset + max_hits) documents.
         // We compute the overall [0..start_offset + max_hits) documents ...
         let num_hits = self.start_offset + self.max_hits;
-        let (sort_order1, sort_order2) = self.sort_by.sort_orders();
+        // If the hits are collapsed, this is a number of groups.
         let mut merged_leaf_response = merge_leaf_responses(
             &self.aggregation,
-            segment_fruits?,
-            sort_order1,
-            sort_order2,
+            segment_fruits,
+            self.sort_by.hit_sorting_mapper(),
             num_hits,
+            self.max_hits_per_group(),
         )?;
+        merged_leaf_response.top_hits = top_hits;
         // ... and drop the first [..start_offsets) hits.
         // note that self.start_offset is 0 when merging from leaf_search, and is only set when
         // merging from root_search, so as to remove the firsts elements only once.
-        merged_leaf_response
-            .partial_hits
-            .drain(
-                0..self
-                    .start_offset
-                    .min(merged_leaf_response.partial_hits.len()),
-            )
-            .count(); //< we just use count as a way to consume the entire iterator.
+        self.drain_start_offset(&mut merged_leaf_response.partial_hits);
         Ok(merged_leaf_response)
     }
 }
@@ -748,9 +1168,9 @@ fn merge_intermediate_aggregation_result<'a>(
 fn merge_leaf_responses(
     aggregations_opt: &Option<QuickwitAggregations>,
     mut leaf_responses: Vec<LeafSearchResponse>,
-    sort_order1: SortOrder,
-    sort_order2: SortOrder,
+    sort_key_mapper: HitSortingMapper,
     max_hits: usize,
+    max_hits_per_group_opt: Option<usize>,
 ) -> tantivy::Result<LeafSearchResponse> {
     // Optimization: No merging needed if there is only one result.
     if leaf_responses.len() == 1 {
@@ -777,22 +1197,50 @@ fn merge_leaf_responses(
         .flat_map(|leaf_response| leaf_response.failed_splits.iter())
         .cloned()
         .collect_vec();
+    let skipped_split_ids = leaf_responses
+        .iter()
+        .flat_map(|leaf_response| leaf_response.skipped_split_ids.iter())
+        .cloned()
+        .collect_vec();
+    let split_profiles = leaf_responses
+        .iter()
+        .flat_map(|leaf_response| leaf_response.split_profiles.iter())
+        .cloned()
+        .collect_vec();
+    let mut split_aggregation_results = Vec::new();
+    extend_split_aggregation_results(
+        &mut split_aggregation_results,
+        leaf_responses
+            .iter()
+            .flat_map(|leaf_response| leaf_response.split_aggregation_results.iter())
+            .cloned(),
+    );
     let all_partial_hits: Vec<PartialHit> = leaf_responses
         .into_iter()
         .flat_map(|leaf_response| leaf_response.partial_hits)
         .collect();
-    let top_k_partial_hits: Vec<PartialHit> = top_k_partial_hits(
-        all_partial_hits.into_iter(),
-        sort_order1,
-        sort_order2,
-        max_hits,
-    );
+    let top_k_partial_hits: Vec<PartialHit> =
+        if let Some(max_hits_per_group) = max_hits_per_group_opt {
+            top_k_collapsed_partial_hits(
+                all_partial_hits.into_iter(),
+                sort_key_mapper,
+                max_hits,
+                max_hits_per_group,
+            )
+        } else {
+            top_k_partial_hits(all_partial_hits.into_iter(), sort_key_mapper, max_hits)
+        };
     Ok(LeafSearchResponse {
         intermediate_aggregation_result: merged_intermediate_aggregation_result,
         num_hits,
         partial_hits: top_k_partial_hits,
         failed_splits,
         num_attempted_splits,
+        skipped_split_ids,
+        split_profiles,
+        split_aggregation_results,
+        // The top hits are merged by `QuickwitCollector::merge_fruits`.
+        top_hits: Vec::new(),
     })
 }
 
@@ -802,11 +1250,9 @@ fn merge_leaf_responses(
 /// TODO we could possibly optimize the sort away (but I doubt it matters).
 fn top_k_partial_hits(
     partial_hits: impl Iterator<Item = PartialHit>,
-    order1: SortOrder,
-    order2: SortOrder,
+    sort_key_mapper: HitSortingMapper,
     num_hits: usize,
 ) -> Vec<PartialHit> {
-    let sort_key_mapper = HitSortingMapper { order1, order2 };
     let mut top_k_hits = TopK::new(num_hits, sort_key_mapper);
 
     partial_hits.for_each(|hit| top_k_hits.add_entry(hit));
@@ -814,7 +1260,89 @@ fn top_k_partial_hits(
     top_k_hits.finalize()
 }
 
-pub(crate) fn sort_by_from_request(search_request: &SearchRequest) -> SortByPair {
+/// Returns the hits of the top-num_groups groups of hits sharing the same collapse value, keeping
+/// the top-max_hits_per_group hits of each group.
+///
+/// The groups are sorted by their best hit, and the hits of a group are contiguous and sorted.
+fn top_k_collapsed_partial_hits(
+    partial_hits: impl Iterator<Item = PartialHit>,
+    sort_key_mapper: HitSortingMapper,
+    num_groups: usize,
+    max_hits_per_group: usize,
+) -> Vec<PartialHit> {
+    let mut collapsed_top_k_hits = CollapsedTopK::new(max_hits_per_group, sort_key_mapper);
+
+    for partial_hit in partial_hits {
+        collapsed_top_k_hits.add_entry(partial_hit.collapse_value.clone(), partial_hit);
+    }
+    collapsed_top_k_hits.finalize_flat(num_groups)
+}
+
+/// Progressively computes the top hits of each group of hits sharing the same collapse value.
+#[derive(Clone)]
+struct CollapsedTopK<G, T, O: Ord, S> {
+    groups: HashMap<G, TopK<T, O, S>>,
+    max_hits_per_group: usize,
+    sort_key_mapper: S,
+}
+
+impl<G, T, O, S> CollapsedTopK<G, T, O, S>
+where
+    G: Eq + Hash,
+    O: Ord,
+    S: SortKeyMapper<T, Key = O> + Clone,
+{
+    fn new(max_hits_per_group: usize, sort_key_mapper: S) -> Self {
+        CollapsedTopK {
+            groups: HashMap::new(),
+            max_hits_per_group,
+            sort_key_mapper,
+        }
+    }
+
+    fn add_entry(&mut self, group: G, item: T) {
+        let max_hits_per_group = self.max_hits_per_group;
+        let sort_key_mapper = &self.sort_key_mapper;
+        self.groups
+            .entry(group)
+            .or_insert_with(|| TopK::new(max_hits_per_group, sort_key_mapper.clone()))
+            .add_entry(item);
+    }
+
+    /// Returns the top-num_groups groups, ranked by their best hit, along with their sorted hits.
+    fn finalize(self, num_groups: usize) -> Vec<(G, Vec<T>)> {
+        let sort_key_mapper = self.sort_key_mapper;
+        let groups = self
+            .groups
+            .into_iter()
+            .map(|(group, top_k_hits)| (group, top_k_hits.finalize()))
+            .filter(|(_, hits)| !hits.is_empty());
+        top_k(groups, num_groups, |(_, hits)| {
+            sort_key_mapper.get_sort_key(&hits[0])
+        })
+    }
+
+    /// Same as [`Self::finalize`], with the hits of the groups laid out one group after the
+    /// other.
+    fn finalize_flat(self, num_groups: usize) -> Vec<T> {
+        self.finalize(num_groups)
+            .into_iter()
+            .flat_map(|(_, hits)| hits)
+            .collect()
+    }
+
+    /// Same as [`Self::finalize_flat`], keeping all the groups.
+    fn finalize_all_flat(self) -> Vec<T> {
+        let num_groups = self.groups.len();
+        self.finalize_flat(num_groups)
+    }
+}
+
+pub(crate) fn sort_by_from_request(search_request: &SearchRequest) -> SortBy {
+    sort_by_from_sort_fields(&search_request.sort_fields)
+}
+
+fn sort_by_from_sort_fields(sort_fields: &[SortField]) -> SortBy {
     let to_sort_by_component = |field_name: &str, order| {
         if field_name == "_score" {
             SortByComponent::Score { order }
@@ -828,28 +1356,20 @@ pub(crate) fn sort_by_from_request(search_request: &SearchRequest) -> SortByPair
         }
     };
 
-    let num_sort_fields = search_request.sort_fields.len();
-    if num_sort_fields == 0 {
-        SortByComponent::DocId {
+    if sort_fields.is_empty() {
+        return SortByComponent::DocId {
             order: SortOrder::Desc,
         }
-        .into()
-    } else if num_sort_fields == 1 {
-        let sort_field = &search_request.sort_fields[0];
-        let order = SortOrder::from_i32(sort_field.sort_order).unwrap_or(SortOrder::Desc);
-        to_sort_by_component(&sort_field.field_name, order).into()
-    } else if num_sort_fields == 2 {
-        let sort_field1 = &search_request.sort_fields[0];
-        let order1 = SortOrder::from_i32(sort_field1.sort_order).unwrap_or(SortOrder::Desc);
-        let sort_field2 = &search_request.sort_fields[1];
-        let order2 = SortOrder::from_i32(sort_field2.sort_order).unwrap_or(SortOrder::Desc);
-        SortByPair {
-            first: to_sort_by_component(&sort_field1.field_name, order1),
-            second: Some(to_sort_by_component(&sort_field2.field_name, order2)),
-        }
-    } else {
-        panic!("Sort by more than 2 fields is not supported yet.")
+        .into();
     }
+    let components = sort_fields
+        .iter()
+        .map(|sort_field| {
+            let order = SortOrder::from_i32(sort_field.sort_order).unwrap_or(SortOrder::Desc);
+            to_sort_by_component(&sort_field.field_name, order)
+        })
+        .collect();
+    SortBy { components }
 }
 
 /// Builds the QuickwitCollector, in function of the information that was requested by the user.
@@ -859,9 +1379,9 @@ pub(crate) fn make_collector_for_split(
     search_request: &SearchRequest,
     aggregation_limits: AggregationLimits,
 ) -> crate::Result<QuickwitCollector> {
-    let aggregation = match &search_request.aggregation_request {
-        Some(aggregation) => Some(serde_json::from_str(aggregation)?),
-        None => None,
+    let (aggregation, top_hits) = match &search_request.aggregation_request {
+        Some(aggregation_request) => parse_aggregation_request(aggregation_request)?,
+        None => (None, None),
     };
     let timestamp_filter_builder_opt = create_timestamp_filter_builder(
         doc_mapper.timestamp_field_name(),
@@ -878,6 +1398,8 @@ pub(crate) fn make_collector_for_split(
         aggregation,
         aggregation_limits,
         search_after: search_request.search_after.clone(),
+        collapse: search_request.collapse.clone(),
+        top_hits,
     })
 }
 
@@ -886,9 +1408,9 @@ pub(crate) fn make_merge_collector(
     search_request: &SearchRequest,
     aggregation_limits: &AggregationLimits,
 ) -> crate::Result<QuickwitCollector> {
-    let aggregation = match &search_request.aggregation_request {
-        Some(aggregation) => Some(serde_json::from_str(aggregation)?),
-        None => None,
+    let (aggregation, top_hits) = match &search_request.aggregation_request {
+        Some(aggregation_request) => parse_aggregation_request(aggregation_request)?,
+        None => (None, None),
     };
     let sort_by = sort_by_from_request(search_request);
     Ok(QuickwitCollector {
@@ -900,79 +1422,39 @@ pub(crate) fn make_merge_collector(
         aggregation,
         aggregation_limits: aggregation_limits.clone(),
         search_after: search_request.search_after.clone(),
+        collapse: search_request.collapse.clone(),
+        top_hits,
     })
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-struct SegmentPartialHitSortingKey {
-    sort_value: Option<SortValue>,
-    sort_value2: Option<SortValue>,
-    doc_id: DocId,
-    // TODO This should not be there.
-    sort_order: SortOrder,
-    // TODO This should not be there.
-    sort_order2: SortOrder,
-}
-
-impl Ord for SegmentPartialHitSortingKey {
-    fn cmp(&self, other: &SegmentPartialHitSortingKey) -> Ordering {
-        debug_assert_eq!(
-            self.sort_order, other.sort_order,
-            "comparing two PartialHitSortingKey of different ordering"
-        );
-        debug_assert_eq!(
-            self.sort_order2, other.sort_order2,
-            "comparing two PartialHitSortingKey of different ordering"
-        );
-        let order = self
-            .sort_order
-            .compare_opt(&self.sort_value, &other.sort_value);
-        let order2 = self
-            .sort_order2
-            .compare_opt(&self.sort_value2, &other.sort_value2);
-        let order_addr = self.sort_order.compare(&self.doc_id, &other.doc_id);
-        order.then(order2).then(order_addr)
-    }
-}
-
-impl PartialOrd for SegmentPartialHitSortingKey {
-    fn partial_cmp(&self, other: &SegmentPartialHitSortingKey) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
 #[derive(Clone, Debug, PartialEq, Eq)]
 struct PartialHitSortingKey {
-    sort_value: Option<SortValue>,
-    sort_value2: Option<SortValue>,
+    sort_values: Vec<Option<SortValue>>,
     address: GlobalDocAddress,
     // TODO remove this
-    sort_order: SortOrder,
-    sort_order2: SortOrder,
+    sort_orders: Arc<[SortOrder]>,
+    address_sort_order: SortOrder,
 }
 
 impl Ord for PartialHitSortingKey {
     fn cmp(&self, other: &PartialHitSortingKey) -> Ordering {
         assert_eq!(
-            self.sort_order, other.sort_order,
+            self.sort_orders, other.sort_orders,
             "comparing two PartialHitSortingKey of different ordering"
         );
-        assert_eq!(
-            self.sort_order2, other.sort_order2,
-            "comparing two PartialHitSortingKey of different ordering"
-        );
-
-        let order = self
-            .sort_order
-            .compare_opt(&self.sort_value, &other.sort_value);
-
-        let order2 = self
-            .sort_order2
-            .compare_opt(&self.sort_value2, &other.sort_value2);
-
-        let order_addr = self.sort_order.compare(&self.address, &other.address);
-
-        order.then(order2).then(order_addr)
+        for ((sort_order, sort_value), other_sort_value) in self
+            .sort_orders
+            .iter()
+            .zip(&self.sort_values)
+            .zip(&other.sort_values)
+        {
+            let order = sort_order.compare_opt(sort_value, other_sort_value);
+            if order != Ordering::Equal {
+                return order;
+            }
+        }
+        self.address_sort_order
+            .compare(&self.address, &other.address)
     }
 }
 
@@ -984,33 +1466,36 @@ impl PartialOrd for PartialHitSortingKey {
 
 #[derive(Clone)]
 struct HitSortingMapper {
-    order1: SortOrder,
-    order2: SortOrder,
+    /// Sort orders of the sort values, see [`SortBy::value_sort_orders`].
+    sort_orders: Arc<[SortOrder]>,
+    address_sort_order: SortOrder,
 }
 
 impl SortKeyMapper<PartialHit> for HitSortingMapper {
     type Key = PartialHitSortingKey;
     fn get_sort_key(&self, partial_hit: &PartialHit) -> PartialHitSortingKey {
+        let sort_values = (0..self.sort_orders.len())
+            .map(|position| {
+                partial_hit
+                    .sort_values
+                    .get(position)
+                    .and_then(|sort_by_value| sort_by_value.sort_value.clone())
+            })
+            .collect();
         PartialHitSortingKey {
-            sort_value: partial_hit.sort_value.and_then(|v| v.sort_value),
-            sort_value2: partial_hit.sort_value2.and_then(|v| v.sort_value),
+            sort_values,
             address: GlobalDocAddress::from_partial_hit(partial_hit),
-            sort_order: self.order1,
-            sort_order2: self.order2,
+            sort_orders: self.sort_orders.clone(),
+            address_sort_order: self.address_sort_order,
         }
     }
 }
 
 impl SortKeyMapper<SegmentPartialHit> for HitSortingMapper {
-    type Key = SegmentPartialHitSortingKey;
-    fn get_sort_key(&self, partial_hit: &SegmentPartialHit) -> SegmentPartialHitSortingKey {
-        SegmentPartialHitSortingKey {
-            sort_value: partial_hit.sort_value,
-            sort_value2: partial_hit.sort_value2,
-            doc_id: partial_hit.doc_id,
-            sort_order: self.order1,
-            sort_order2: self.order2,
-        }
+    // The sort keys of segment partial hits are already mapped according to the sort orders.
+    type Key = SegmentPartialHit;
+    fn get_sort_key(&self, partial_hit: &SegmentPartialHit) -> SegmentPartialHit {
+        partial_hit.clone()
     }
 }
 
@@ -1019,10 +1504,19 @@ impl SortKeyMapper<SegmentPartialHit> for HitSortingMapper {
 pub(crate) struct IncrementalCollector {
     inner: QuickwitCollector,
     top_k_hits: TopK<PartialHit, PartialHitSortingKey, HitSortingMapper>,
+    // If set, the hits are collected here instead of in `top_k_hits`, whose length is then the
+    // number of groups to return.
+    collapsed_top_k_hits:
+        Option<CollapsedTopK<Option<String>, PartialHit, PartialHitSortingKey, HitSortingMapper>>,
+    top_hits_collapsed_top_k_hits:
+        Option<CollapsedTopK<Option<String>, PartialHit, PartialHitSortingKey, HitSortingMapper>>,
     incremental_aggregation: QuickwitIncrementalAggregations,
     num_hits: u64,
     failed_splits: Vec<SplitSearchError>,
     num_attempted_splits: u64,
+    skipped_split_ids: Vec<String>,
+    split_profiles: Vec<SplitSearchProfile>,
+    split_aggregation_results: Vec<SplitAggregationResult>,
 }
 
 impl IncrementalCollector {
@@ -1033,15 +1527,22 @@ impl IncrementalCollector {
             .as_ref()
             .map(QuickwitAggregations::maybe_incremental_aggregator)
             .unwrap_or(QuickwitIncrementalAggregations::NoAggregation);
-        let (order1, order2) = inner.sort_by.sort_orders();
-        let sort_key_mapper = HitSortingMapper { order1, order2 };
+        let sort_key_mapper = inner.sort_by.hit_sorting_mapper();
+        let collapsed_top_k_hits = inner.max_hits_per_group().map(|max_hits_per_group| {
+            CollapsedTopK::new(max_hits_per_group, sort_key_mapper.clone())
+        });
         IncrementalCollector {
             top_k_hits: TopK::new(inner.max_hits + inner.start_offset, sort_key_mapper),
+            collapsed_top_k_hits,
+            top_hits_collapsed_top_k_hits: inner.top_hits_collapsed_top_k(),
             inner,
             incremental_aggregation,
             num_hits: 0,
             failed_splits: Vec::new(),
             num_attempted_splits: 0,
+            skipped_split_ids: Vec::new(),
+            split_profiles: Vec::new(),
+            split_aggregation_results: Vec::new(),
         }
     }
 
@@ -1053,12 +1554,34 @@ impl IncrementalCollector {
             failed_splits,
             num_attempted_splits,
             intermediate_aggregation_result,
+            skipped_split_ids,
+            split_profiles,
+            split_aggregation_results,
+            top_hits,
         } = leaf_response;
 
         self.num_hits += num_hits;
-        self.top_k_hits.add_entries(partial_hits.into_iter());
+        if let Some(collapsed_top_k_hits) = &mut self.collapsed_top_k_hits {
+            for partial_hit in partial_hits {
+                collapsed_top_k_hits.add_entry(partial_hit.collapse_value.clone(), partial_hit);
+            }
+        } else {
+            self.top_k_hits.add_entries(partial_hits.into_iter());
+        }
+        if let Some(top_hits_collapsed_top_k_hits) = &mut self.top_hits_collapsed_top_k_hits {
+            for partial_hit in top_hits {
+                top_hits_collapsed_top_k_hits
+                    .add_entry(partial_hit.collapse_value.clone(), partial_hit);
+            }
+        }
         self.failed_splits.extend(failed_splits);
         self.num_attempted_splits += num_attempted_splits;
+        self.skipped_split_ids.extend(skipped_split_ids);
+        self.split_profiles.extend(split_profiles);
+        extend_split_aggregation_results(
+            &mut self.split_aggregation_results,
+            split_aggregation_results,
+        );
         if let Some(intermediate_aggregation_result) = intermediate_aggregation_result {
             self.incremental_aggregation
                 .add(intermediate_aggregation_result)?;
@@ -1071,6 +1594,11 @@ impl IncrementalCollector {
         self.failed_splits.push(split_error)
     }
 
+    /// Add splits that were not searched because the search timed out to the state
+    pub(crate) fn add_skipped_splits(&mut self, skipped_split_ids: Vec<String>) {
+        self.skipped_split_ids.extend(skipped_split_ids)
+    }
+
     /// Get the worst top-hit. Can be used to skip splits if they can't possibly do better.
     ///
     /// Only returns a result if enough hits were recorded already.
@@ -1081,7 +1609,11 @@ impl IncrementalCollector {
                 .virtual_worst_hit()
                 .map(Cow::Owned);
         }
-
+        // A split can hold worse hits belonging to the groups already collected, so splits are
+        // never skipped when the hits are collapsed.
+        if self.collapsed_top_k_hits.is_some() {
+            return None;
+        }
         if self.top_k_hits.at_capacity() {
             self.top_k_hits.peek_worst().map(Cow::Borrowed)
         } else {
@@ -1092,16 +1624,26 @@ impl IncrementalCollector {
     /// Finalize the merge, creating a LeafSearchResponse.
     pub(crate) fn finalize(self) -> tantivy::Result<LeafSearchResponse> {
         let intermediate_aggregation_result = self.incremental_aggregation.finalize()?;
-        let mut partial_hits = self.top_k_hits.finalize();
-        if self.inner.start_offset != 0 {
-            partial_hits.drain(0..self.inner.start_offset.min(partial_hits.len()));
-        }
+        let mut partial_hits = if let Some(collapsed_top_k_hits) = self.collapsed_top_k_hits {
+            collapsed_top_k_hits.finalize_flat(self.top_k_hits.max_len())
+        } else {
+            self.top_k_hits.finalize()
+        };
+        self.inner.drain_start_offset(&mut partial_hits);
+        let top_hits = self
+            .top_hits_collapsed_top_k_hits
+            .map(CollapsedTopK::finalize_all_flat)
+            .unwrap_or_default();
         Ok(LeafSearchResponse {
             num_hits: self.num_hits,
             partial_hits,
             failed_splits: self.failed_splits,
             num_attempted_splits: self.num_attempted_splits,
             intermediate_aggregation_result,
+            skipped_split_ids: self.skipped_split_ids,
+            split_profiles: self.split_profiles,
+            split_aggregation_results: self.split_aggregation_results,
+            top_hits,
         })
     }
 }
@@ -1109,37 +1651,40 @@ impl IncrementalCollector {
 #[cfg(test)]
 mod tests {
     use std::cmp::Ordering;
+    use std::collections::HashSet;
 
     use quickwit_proto::search::{
-        LeafSearchResponse, PartialHit, SearchRequest, SortByValue, SortField, SortOrder,
-        SortValue, SplitSearchError,
+        CollapseOptions, LeafSearchResponse, PartialHit, SearchRequest, SortByValue, SortField,
+        SortOrder, SortValue, SplitSearchError,
     };
     use tantivy::collector::Collector;
     use tantivy::TantivyDocument;
 
-    use super::{make_merge_collector, IncrementalCollector, PartialHitHeapItem};
+    use super::{
+        make_merge_collector, sort_by_from_request, IncrementalCollector, SegmentPartialHit,
+    };
     use crate::collector::top_k_partial_hits;
 
     #[test]
-    fn test_partial_hit_ordered_by_sorting_field() {
-        let lesser_score = PartialHitHeapItem {
+    fn test_segment_partial_hit_ordered_by_sorting_field() {
+        let lesser_score = SegmentPartialHit {
+            sort_keys: vec![Some(1u64), None],
+            doc_key: 1u32,
             doc_id: 1u32,
-            sort_value_opt1: Some(1u64),
-            sort_value_opt2: None,
         };
-        let higher_score = PartialHitHeapItem {
-            sort_value_opt1: Some(2u64),
-            sort_value_opt2: None,
+        let higher_score = SegmentPartialHit {
+            sort_keys: vec![Some(2u64), None],
+            doc_key: 1u32,
             doc_id: 1u32,
         };
-        assert_eq!(lesser_score.cmp(&higher_score), Ordering::Greater);
+        assert_eq!(lesser_score.cmp(&higher_score), Ordering::Less);
     }
     #[test]
-    fn test_partial_hit_ordered_by_sorting_field_2() {
-        let get_el = |val1, val2, docid| PartialHitHeapItem {
+    fn test_segment_partial_hit_ordered_by_sorting_field_2() {
+        let get_el = |val1, val2, docid| SegmentPartialHit {
+            sort_keys: vec![val1, val2],
+            doc_key: docid,
             doc_id: docid,
-            sort_value_opt1: val1,
-            sort_value_opt2: val2,
         };
         let mut data = vec![
             get_el(Some(1u64), None, 1u32),
@@ -1149,7 +1694,7 @@ mod tests {
             get_el(None, Some(1u64), 1u32),
             get_el(None, None, 1u32),
         ];
-        data.sort();
+        data.sort_by(|left, right| right.cmp(left));
         assert_eq!(
             data,
             vec![
@@ -1166,17 +1711,17 @@ mod tests {
     #[test]
     fn test_merge_partial_hits_no_tie() {
         let make_doc = |sort_value: u64| PartialHit {
-            sort_value: Some(SortValue::U64(sort_value).into()),
-            sort_value2: None,
+            sort_values: vec![SortValue::U64(sort_value).into()],
             split_id: "split1".to_string(),
             segment_ord: 0u32,
             doc_id: 0u32,
+            collapse_value: None,
+            ..Default::default()
         };
         assert_eq!(
             top_k_partial_hits(
                 vec![make_doc(1u64), make_doc(3u64), make_doc(2u64),].into_iter(),
-                SortOrder::Asc,
-                SortOrder::Asc,
+                sort_by_from_request(&make_request(2, "-sort1")).hit_sorting_mapper(),
                 2
             ),
             vec![make_doc(1), make_doc(2)]
@@ -1186,11 +1731,12 @@ mod tests {
     #[test]
     fn test_merge_partial_hits_with_tie() {
         let make_hit_given_split_id = |split_id: u64| PartialHit {
-            sort_value: Some(SortValue::U64(0u64).into()),
-            sort_value2: None,
+            sort_values: vec![SortValue::U64(0u64).into()],
             split_id: format!("split_{split_id}"),
             segment_ord: 0u32,
             doc_id: 0u32,
+            collapse_value: None,
+            ..Default::default()
         };
         assert_eq!(
             &top_k_partial_hits(
@@ -1200,8 +1746,7 @@ mod tests {
                     make_hit_given_split_id(2u64),
                 ]
                 .into_iter(),
-                SortOrder::Desc,
-                SortOrder::Desc,
+                sort_by_from_request(&make_request(2, "sort1")).hit_sorting_mapper(),
                 2
             ),
             &[make_hit_given_split_id(3), make_hit_given_split_id(2)]
@@ -1214,8 +1759,7 @@ mod tests {
                     make_hit_given_split_id(2u64),
                 ]
                 .into_iter(),
-                SortOrder::Asc,
-                SortOrder::Asc,
+                sort_by_from_request(&make_request(2, "-sort1")).hit_sorting_mapper(),
                 2
             ),
             &[make_hit_given_split_id(1), make_hit_given_split_id(2)]
@@ -1524,12 +2068,16 @@ mod tests {
                 split_id: "fake_split_id".to_string(),
                 segment_ord: 0,
                 doc_id: *doc_id as u32,
-                sort_value: Some(SortByValue {
-                    sort_value: val1.map(SortValue::U64),
-                }),
-                sort_value2: Some(SortByValue {
-                    sort_value: val2.map(SortValue::U64),
-                }),
+                sort_values: vec![
+                    SortByValue {
+                        sort_value: val1.map(SortValue::U64),
+                    },
+                    SortByValue {
+                        sort_value: val2.map(SortValue::U64),
+                    },
+                ],
+                collapse_value: None,
+                ..Default::default()
             })
             .collect::<Vec<_>>();
         // we eliminte based on sort value
@@ -1576,8 +2124,9 @@ mod tests {
                 split_id: "fake_split_id2".to_string(),
                 segment_ord: 0,
                 doc_id: 5,
-                sort_value: None,
-                sort_value2: None,
+                sort_values: Vec::new(),
+                collapse_value: None,
+                ..Default::default()
             };
             let request = SearchRequest {
                 max_hits: 1000,
@@ -1636,6 +2185,134 @@ mod tests {
         }
     }
 
+    fn make_index_with_string_field() -> tantivy::Index {
+        use tantivy::indexer::UserOperation;
+        use tantivy::schema::{NumericOptions, Schema, TextOptions};
+        use tantivy::Index;
+
+        let dataset: Vec<(Option<&str>, u64, i64)> = vec![
+            (Some("api"), 1, 10),
+            (Some("web"), 2, 20),
+            (Some("api"), 2, 30),
+            (Some("db"), 1, 40),
+            (None, 3, 50),
+            (Some("web"), 2, 10),
+            (Some("api"), 1, 20),
+            (Some("db"), 3, 60),
+        ];
+        let mut schema_builder = Schema::builder();
+        let service_field =
+            schema_builder.add_text_field("service", TextOptions::default().set_fast(None));
+        let severity_field =
+            schema_builder.add_u64_field("severity", NumericOptions::default().set_fast());
+        let ts_field = schema_builder.add_i64_field("ts", NumericOptions::default().set_fast());
+        let schema = schema_builder.build();
+
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer(50_000_000).unwrap();
+        index_writer
+            .run(
+                dataset
+                    .into_iter()
+                    .map(|(service, severity, ts)| {
+                        let mut doc = TantivyDocument::new();
+                        if let Some(service) = service {
+                            doc.add_text(service_field, service);
+                        }
+                        doc.add_u64(severity_field, severity);
+                        doc.add_i64(ts_field, ts);
+                        doc
+                    })
+                    .map(UserOperation::Add),
+            )
+            .unwrap();
+        index_writer.commit().unwrap();
+        index
+    }
+
+    #[test]
+    fn test_sort_by_string_fast_field_and_search_after() {
+        let index = make_index_with_string_field();
+        let reader = index.reader().unwrap();
+        let searcher = reader.searcher();
+
+        let search = |request: &SearchRequest| {
+            let collector = super::make_collector_for_split(
+                "fake_split_id".to_string(),
+                &MockDocMapper,
+                request,
+                Default::default(),
+            )
+            .unwrap();
+            searcher
+                .search(&tantivy::query::AllQuery, &collector)
+                .unwrap()
+        };
+        // Documents without a value come last, whatever the sort order.
+        let expected_doc_ids = vec![2u32, 6, 0, 7, 3, 1, 5, 4];
+
+        let request = make_request(10, "-service,severity,ts");
+        let response = search(&request);
+        let doc_ids: Vec<u32> = response.partial_hits.iter().map(|hit| hit.doc_id).collect();
+        assert_eq!(doc_ids, expected_doc_ids);
+        assert_eq!(
+            response.partial_hits[0].sort_values,
+            vec![
+                SortValue::Str("api".to_string()).into(),
+                SortValue::U64(2).into(),
+                SortValue::I64(30).into(),
+            ]
+        );
+        assert_eq!(
+            response.partial_hits[7].sort_values,
+            vec![
+                SortByValue { sort_value: None },
+                SortValue::U64(3).into(),
+                SortValue::I64(50).into(),
+            ]
+        );
+
+        // Paginates through the hits with search after.
+        let mut doc_ids = Vec::new();
+        let mut search_after: Option<PartialHit> = None;
+        loop {
+            let request = SearchRequest {
+                search_after: search_after.clone(),
+                ..make_request(3, "-service,severity,ts")
+            };
+            let response = search(&request);
+            if response.partial_hits.is_empty() {
+                break;
+            }
+            doc_ids.extend(response.partial_hits.iter().map(|hit| hit.doc_id));
+            search_after = response.partial_hits.last().cloned();
+        }
+        assert_eq!(doc_ids, expected_doc_ids);
+
+        // The search after term does not need to exist in the segment.
+        let request = SearchRequest {
+            search_after: Some(PartialHit {
+                sort_values: vec![SortValue::Str("c".to_string()).into()],
+                ..Default::default()
+            }),
+            ..make_request(10, "-service")
+        };
+        let response = search(&request);
+        let doc_ids: HashSet<u32> = response.partial_hits.iter().map(|hit| hit.doc_id).collect();
+        assert_eq!(doc_ids, HashSet::from_iter([1, 3, 4, 5, 7]));
+
+        let request = SearchRequest {
+            search_after: Some(PartialHit {
+                sort_values: vec![SortValue::Str("db".to_string()).into()],
+                ..Default::default()
+            }),
+            ..make_request(10, "service")
+        };
+        let response = search(&request);
+        let doc_ids: HashSet<u32> = response.partial_hits.iter().map(|hit| hit.doc_id).collect();
+        assert_eq!(doc_ids, HashSet::from_iter([0, 2, 4, 6]));
+    }
+
     fn merge_collector_equal_results(
         request: &SearchRequest,
         results: Vec<LeafSearchResponse>,
@@ -1676,12 +2353,17 @@ mod tests {
                     split_id: "1".to_string(),
                     segment_ord: 0,
                     doc_id: 123,
-                    sort_value: Some(SortValue::I64(1234).into()),
-                    sort_value2: None,
+                    sort_values: vec![SortValue::I64(1234).into()],
+                    collapse_value: None,
+                    ..Default::default()
                 }],
                 failed_splits: Vec::new(),
                 num_attempted_splits: 3,
                 intermediate_aggregation_result: None,
+                skipped_split_ids: Vec::new(),
+                split_profiles: Vec::new(),
+                split_aggregation_results: Vec::new(),
+                top_hits: Vec::new(),
             }],
         );
 
@@ -1693,12 +2375,17 @@ mod tests {
                     split_id: "1".to_string(),
                     segment_ord: 0,
                     doc_id: 123,
-                    sort_value: Some(SortValue::I64(1234).into()),
-                    sort_value2: None,
+                    sort_values: vec![SortValue::I64(1234).into()],
+                    collapse_value: None,
+                    ..Default::default()
                 }],
                 failed_splits: Vec::new(),
                 num_attempted_splits: 3,
-                intermediate_aggregation_result: None
+                intermediate_aggregation_result: None,
+                skipped_split_ids: Vec::new(),
+                split_profiles: Vec::new(),
+                split_aggregation_results: Vec::new(),
+                top_hits: Vec::new(),
             }
         );
 
@@ -1722,20 +2409,26 @@ mod tests {
                             split_id: "1".to_string(),
                             segment_ord: 0,
                             doc_id: 123,
-                            sort_value: Some(SortValue::I64(1234).into()),
-                            sort_value2: None,
+                            sort_values: vec![SortValue::I64(1234).into()],
+                            collapse_value: None,
+                            ..Default::default()
                         },
                         PartialHit {
                             split_id: "1".to_string(),
                             segment_ord: 0,
                             doc_id: 125,
-                            sort_value: Some(SortValue::I64(1236).into()),
-                            sort_value2: None,
+                            sort_values: vec![SortValue::I64(1236).into()],
+                            collapse_value: None,
+                            ..Default::default()
                         },
                     ],
                     failed_splits: Vec::new(),
                     num_attempted_splits: 3,
                     intermediate_aggregation_result: None,
+                    skipped_split_ids: Vec::new(),
+                    split_profiles: Vec::new(),
+                    split_aggregation_results: Vec::new(),
+                    top_hits: Vec::new(),
                 },
                 LeafSearchResponse {
                     num_hits: 10,
@@ -1743,8 +2436,9 @@ mod tests {
                         split_id: "2".to_string(),
                         segment_ord: 0,
                         doc_id: 3,
-                        sort_value: Some(SortValue::I64(1235).into()),
-                        sort_value2: None,
+                        sort_values: vec![SortValue::I64(1235).into()],
+                        collapse_value: None,
+                        ..Default::default()
                     }],
                     failed_splits: vec![SplitSearchError {
                         error: "fake error".to_string(),
@@ -1753,6 +2447,10 @@ mod tests {
                     }],
                     num_attempted_splits: 2,
                     intermediate_aggregation_result: None,
+                    skipped_split_ids: Vec::new(),
+                    split_profiles: Vec::new(),
+                    split_aggregation_results: Vec::new(),
+                    top_hits: Vec::new(),
                 },
             ],
         );
@@ -1766,15 +2464,17 @@ mod tests {
                         split_id: "1".to_string(),
                         segment_ord: 0,
                         doc_id: 125,
-                        sort_value: Some(SortValue::I64(1236).into()),
-                        sort_value2: None,
+                        sort_values: vec![SortValue::I64(1236).into()],
+                        collapse_value: None,
+                        ..Default::default()
                     },
                     PartialHit {
                         split_id: "2".to_string(),
                         segment_ord: 0,
                         doc_id: 3,
-                        sort_value: Some(SortValue::I64(1235).into()),
-                        sort_value2: None,
+                        sort_values: vec![SortValue::I64(1235).into()],
+                        collapse_value: None,
+                        ..Default::default()
                     },
                 ],
                 failed_splits: vec![SplitSearchError {
@@ -1783,7 +2483,11 @@ mod tests {
                     retryable_error: true,
                 }],
                 num_attempted_splits: 5,
-                intermediate_aggregation_result: None
+                intermediate_aggregation_result: None,
+                skipped_split_ids: Vec::new(),
+                split_profiles: Vec::new(),
+                split_aggregation_results: Vec::new(),
+                top_hits: Vec::new(),
             }
         );
 
@@ -1808,20 +2512,26 @@ mod tests {
                             split_id: "1".to_string(),
                             segment_ord: 0,
                             doc_id: 123,
-                            sort_value: Some(SortValue::I64(1234).into()),
-                            sort_value2: None,
+                            sort_values: vec![SortValue::I64(1234).into()],
+                            collapse_value: None,
+                            ..Default::default()
                         },
                         PartialHit {
                             split_id: "1".to_string(),
                             segment_ord: 0,
                             doc_id: 125,
-                            sort_value: Some(SortValue::I64(1236).into()),
-                            sort_value2: None,
+                            sort_values: vec![SortValue::I64(1236).into()],
+                            collapse_value: None,
+                            ..Default::default()
                         },
                     ],
                     failed_splits: Vec::new(),
                     num_attempted_splits: 3,
                     intermediate_aggregation_result: None,
+                    skipped_split_ids: Vec::new(),
+                    split_profiles: Vec::new(),
+                    split_aggregation_results: Vec::new(),
+                    top_hits: Vec::new(),
                 },
                 LeafSearchResponse {
                     num_hits: 10,
@@ -1829,8 +2539,9 @@ mod tests {
                         split_id: "2".to_string(),
                         segment_ord: 0,
                         doc_id: 3,
-                        sort_value: Some(SortValue::I64(1235).into()),
-                        sort_value2: None,
+                        sort_values: vec![SortValue::I64(1235).into()],
+                        collapse_value: None,
+                        ..Default::default()
                     }],
                     failed_splits: vec![SplitSearchError {
                         error: "fake error".to_string(),
@@ -1839,6 +2550,10 @@ mod tests {
                     }],
                     num_attempted_splits: 2,
                     intermediate_aggregation_result: None,
+                    skipped_split_ids: Vec::new(),
+                    split_profiles: Vec::new(),
+                    split_aggregation_results: Vec::new(),
+                    top_hits: Vec::new(),
                 },
             ],
         );
@@ -1852,15 +2567,17 @@ mod tests {
                         split_id: "1".to_string(),
                         segment_ord: 0,
                         doc_id: 123,
-                        sort_value: Some(SortValue::I64(1234).into()),
-                        sort_value2: None,
+                        sort_values: vec![SortValue::I64(1234).into()],
+                        collapse_value: None,
+                        ..Default::default()
                     },
                     PartialHit {
                         split_id: "2".to_string(),
                         segment_ord: 0,
                         doc_id: 3,
-                        sort_value: Some(SortValue::I64(1235).into()),
-                        sort_value2: None,
+                        sort_values: vec![SortValue::I64(1235).into()],
+                        collapse_value: None,
+                        ..Default::default()
                     },
                 ],
                 failed_splits: vec![SplitSearchError {
@@ -1869,9 +2586,241 @@ mod tests {
                     retryable_error: true,
                 }],
                 num_attempted_splits: 5,
-                intermediate_aggregation_result: None
+                intermediate_aggregation_result: None,
+                skipped_split_ids: Vec::new(),
+                split_profiles: Vec::new(),
+                split_aggregation_results: Vec::new(),
+                top_hits: Vec::new(),
             }
         );
         // TODO would be nice to test aggregation too.
     }
+
+    #[test]
+    fn test_collapse_on_string_fast_field() {
+        let index = make_index_with_string_field();
+        let reader = index.reader().unwrap();
+        let searcher = reader.searcher();
+
+        let search = |max_hits: u64, max_inner_hits: u32| {
+            let request = SearchRequest {
+                collapse: Some(CollapseOptions {
+                    field_name: "service".to_string(),
+                    max_inner_hits,
+                }),
+                ..make_request(max_hits, "ts")
+            };
+            let collector = super::make_collector_for_split(
+                "fake_split_id".to_string(),
+                &MockDocMapper,
+                &request,
+                Default::default(),
+            )
+            .unwrap();
+            let response = searcher
+                .search(&tantivy::query::AllQuery, &collector)
+                .unwrap();
+            response
+                .partial_hits
+                .into_iter()
+                .map(|hit| (hit.doc_id, hit.collapse_value))
+                .collect::<Vec<_>>()
+        };
+        let db = || Some("db".to_string());
+        let api = || Some("api".to_string());
+        let web = || Some("web".to_string());
+
+        // Groups are ranked by their best hit, and documents without a value form a group.
+        assert_eq!(
+            search(10, 0),
+            vec![(7, db()), (4, None), (2, api()), (1, web())]
+        );
+        assert_eq!(
+            search(10, 2),
+            vec![
+                (7, db()),
+                (3, db()),
+                (4, None),
+                (2, api()),
+                (6, api()),
+                (1, web()),
+                (5, web()),
+            ]
+        );
+        assert_eq!(search(2, 2), vec![(7, db()), (3, db()), (4, None)]);
+        assert!(search(0, 2).is_empty());
+    }
+
+    #[test]
+    fn test_top_hits_aggregation_on_string_fast_field() {
+        let index = make_index_with_string_field();
+        let reader = index.reader().unwrap();
+        let searcher = reader.searcher();
+
+        let search = |aggregation_request: &str| {
+            let request = SearchRequest {
+                aggregation_request: Some(aggregation_request.to_string()),
+                ..make_request(0, "")
+            };
+            let collector = super::make_collector_for_split(
+                "fake_split_id".to_string(),
+                &MockDocMapper,
+                &request,
+                Default::default(),
+            )
+            .unwrap();
+            let response = searcher
+                .search(&tantivy::query::AllQuery, &collector)
+                .unwrap();
+            assert!(response.partial_hits.is_empty());
+            response
+        };
+        let top_hits = |response: &LeafSearchResponse| {
+            response
+                .top_hits
+                .iter()
+                .map(|hit| (hit.doc_id, hit.collapse_value.clone()))
+                .collect::<Vec<_>>()
+        };
+        let db = || Some("db".to_string());
+        let api = || Some("api".to_string());
+        let web = || Some("web".to_string());
+
+        let response = search(r#"{"latest": {"top_hits": {"size": 3, "sort": {"ts": "desc"}}}}"#);
+        assert_eq!(top_hits(&response), vec![(7, None), (4, None), (3, None)]);
+        assert_eq!(
+            response.top_hits[0].sort_values,
+            vec![SortValue::I64(60).into()]
+        );
+        assert!(response.intermediate_aggregation_result.is_none());
+
+        // Documents without a value do not belong to any bucket.
+        let response = search(
+            r#"{"services": {"terms": {"field": "service"},
+                "aggs": {"latest": {"top_hits": {"size": 2, "sort": [{"ts": {"order": "desc"}}]}}}}}"#,
+        );
+        assert_eq!(
+            top_hits(&response),
+            vec![
+                (7, db()),
+                (3, db()),
+                (2, api()),
+                (6, api()),
+                (1, web()),
+                (5, web()),
+            ]
+        );
+        assert!(response.intermediate_aggregation_result.is_some());
+
+        // Only the terms with the highest doc counts are kept, `api` has 3 documents.
+        let response = search(
+            r#"{"services": {"terms": {"field": "service", "size": 1, "segment_size": 1},
+                "aggs": {"latest": {"top_hits": {"size": 2, "sort": [{"ts": "desc"}]}}}}}"#,
+        );
+        assert_eq!(top_hits(&response), vec![(2, api()), (6, api())]);
+    }
+
+    #[test]
+    fn test_merge_top_hits() {
+        let make_hit = |split_id: &str, doc_id: u32, ts: i64, term: &str| PartialHit {
+            sort_values: vec![SortValue::I64(ts).into()],
+            split_id: split_id.to_string(),
+            segment_ord: 0,
+            doc_id,
+            collapse_value: Some(term.to_string()),
+            ..Default::default()
+        };
+        let make_leaf_response = |top_hits: Vec<PartialHit>| LeafSearchResponse {
+            num_hits: top_hits.len() as u64,
+            top_hits,
+            num_attempted_splits: 1,
+            ..Default::default()
+        };
+        let request = SearchRequest {
+            aggregation_request: Some(
+                r#"{"services": {"terms": {"field": "service"},
+                    "aggs": {"latest": {"top_hits": {"size": 2, "sort": {"ts": "desc"}}}}}}"#
+                    .to_string(),
+            ),
+            ..make_request(0, "")
+        };
+        let result = merge_collector_equal_results(
+            &request,
+            vec![
+                make_leaf_response(vec![
+                    make_hit("split1", 0, 60, "db"),
+                    make_hit("split1", 1, 40, "db"),
+                    make_hit("split1", 2, 30, "api"),
+                ]),
+                make_leaf_response(vec![
+                    make_hit("split2", 0, 50, "db"),
+                    make_hit("split2", 1, 35, "api"),
+                    make_hit("split2", 2, 5, "web"),
+                ]),
+            ],
+        );
+        assert!(result.partial_hits.is_empty());
+        // All the groups are kept, the root picks the ones of the buckets returned.
+        assert_eq!(
+            result.top_hits,
+            vec![
+                make_hit("split1", 0, 60, "db"),
+                make_hit("split2", 0, 50, "db"),
+                make_hit("split2", 1, 35, "api"),
+                make_hit("split1", 2, 30, "api"),
+                make_hit("split2", 2, 5, "web"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_collapsed_hits() {
+        let make_hit = |split_id: &str, doc_id: u32, ts: i64, collapse_value: &str| PartialHit {
+            sort_values: vec![SortValue::I64(ts).into()],
+            split_id: split_id.to_string(),
+            segment_ord: 0,
+            doc_id,
+            collapse_value: Some(collapse_value.to_string()),
+            ..Default::default()
+        };
+        let make_leaf_response = |partial_hits: Vec<PartialHit>| LeafSearchResponse {
+            num_hits: partial_hits.len() as u64,
+            partial_hits,
+            num_attempted_splits: 1,
+            ..Default::default()
+        };
+        let request = SearchRequest {
+            start_offset: 1,
+            collapse: Some(CollapseOptions {
+                field_name: "service".to_string(),
+                max_inner_hits: 2,
+            }),
+            ..make_request(2, "ts")
+        };
+        let result = merge_collector_equal_results(
+            &request,
+            vec![
+                make_leaf_response(vec![
+                    make_hit("split1", 0, 60, "db"),
+                    make_hit("split1", 1, 40, "db"),
+                    make_hit("split1", 2, 30, "api"),
+                ]),
+                make_leaf_response(vec![
+                    make_hit("split2", 0, 50, "db"),
+                    make_hit("split2", 1, 35, "api"),
+                    make_hit("split2", 2, 5, "web"),
+                ]),
+            ],
+        );
+        assert_eq!(result.num_hits, 6);
+        // The first group, `db`, is skipped.
+        assert_eq!(
+            result.partial_hits,
+            vec![
+                make_hit("split2", 1, 35, "api"),
+                make_hit("split1", 2, 30, "api"),
+                make_hit("split2", 2, 5, "web"),
+            ]
+        );
+    }
 }
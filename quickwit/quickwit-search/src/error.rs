@@ -30,6 +30,8 @@ use tokio::task::JoinError;
 #[allow(missing_docs)]
 #[derive(Error, Debug, Serialize, Deserialize, Clone)]
 pub enum SearchError {
+    #[error("could not find async search `{0}`")]
+    AsyncSearchNotFound(String),
     #[error("could not find indexes matching the IDs `{index_ids:?}`")]
     IndexesNotFound { index_ids: Vec<String> },
     #[error("internal error: `{0}`")]
@@ -47,6 +49,7 @@ pub enum SearchError {
 impl ServiceError for SearchError {
     fn error_code(&self) -> ServiceErrorCode {
         match self {
+            SearchError::AsyncSearchNotFound(_) => ServiceErrorCode::NotFound,
             SearchError::IndexesNotFound { .. } => ServiceErrorCode::NotFound,
             SearchError::Internal(_) => ServiceErrorCode::Internal,
             SearchError::InvalidAggregationRequest(_) => ServiceErrorCode::BadRequest,
@@ -17,6 +17,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::cmp::Reverse;
 use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
@@ -27,10 +28,13 @@ use quickwit_doc_mapper::DocMapper;
 use quickwit_proto::search::{
     FetchDocsResponse, PartialHit, SnippetRequest, SplitIdAndFooterOffsets,
 };
+use quickwit_query::find_field_or_hit_dynamic;
 use quickwit_storage::Storage;
 use tantivy::query::Query;
-use tantivy::schema::{Document as DocumentTrait, Field, OwnedValue, TantivyDocument, Value};
-use tantivy::{ReloadPolicy, Score, Searcher, SnippetGenerator, Term};
+use tantivy::schema::{
+    Document as DocumentTrait, Field, FieldType, OwnedValue, Schema, TantivyDocument, Value,
+};
+use tantivy::{ReloadPolicy, Score, Searcher, Snippet, SnippetGenerator, Term};
 use tracing::{error, Instrument};
 
 use crate::leaf::open_index_with_caches;
@@ -38,6 +42,13 @@ use crate::service::SearcherContext;
 use crate::{convert_document_to_json_string, GlobalDocAddress};
 
 const SNIPPET_MAX_NUM_CHARS: usize = 150;
+/// Fragment size used to highlight whole field values. The snippet generator adds it to text
+/// offsets, so it cannot be `usize::MAX`.
+const WHOLE_FIELD_FRAGMENT_SIZE: usize = u32::MAX as usize;
+/// Snippet field standing for all the fields snippets can be extracted from.
+pub(crate) const SNIPPET_FIELDS_WILDCARD: &str = "*";
+const DEFAULT_SNIPPET_PRE_TAG: &str = "<b>";
+const DEFAULT_SNIPPET_POST_TAG: &str = "</b>";
 
 /// Given a list of global doc address, fetches all the documents and
 /// returns them as a hashmap.
@@ -189,7 +200,9 @@ async fn fetch_docs_in_split(
         .try_into()?;
     let searcher = Arc::new(index_reader.searcher());
     let fields_snippet_generator_opt = if let Some(snippet_request) = snippet_request_opt {
-        Some(create_fields_snippet_generator(&searcher, doc_mapper.clone(), snippet_request).await?)
+        let fields_snippet_generator =
+            create_fields_snippet_generator(&searcher, doc_mapper.clone(), snippet_request).await?;
+        Some(Arc::new(fields_snippet_generator))
     } else {
         None
     };
@@ -228,15 +241,7 @@ async fn fetch_docs_in_split(
                 ));
             }
 
-            let mut snippets = HashMap::new();
-            for (field, field_values) in doc.get_sorted_field_values() {
-                let field_name = moved_searcher.schema().get_field_name(field);
-                if let Some(values) = fields_snippet_generator_clone
-                    .snippets_from_field_values(field_name, field_values)
-                {
-                    snippets.insert(field_name, values);
-                }
-            }
+            let snippets = fields_snippet_generator_clone.snippets_from_doc(&doc);
             let snippet_json = serde_json::to_string(&snippets)?;
             Ok((
                 global_doc_addr,
@@ -257,35 +262,84 @@ async fn fetch_docs_in_split(
 
 // A struct to hold the snippet generators associated to
 // the snippet fields from a search request.
-#[derive(Clone)]
 struct FieldsSnippetGenerator {
-    field_generators: Arc<HashMap<String, SnippetGenerator>>,
+    field_generators: HashMap<String, FieldSnippetGenerator>,
+    fragment_size: usize,
+    num_fragments: usize,
+    pre_tag: String,
+    post_tag: String,
+}
+
+// The snippet generator of a snippet field, which is either a text field or a path within a json
+// field.
+struct FieldSnippetGenerator {
+    field: Field,
+    // Empty if the snippet field is not a json path.
+    json_path: String,
+    snippet_generator: SnippetGenerator,
 }
 
 impl FieldsSnippetGenerator {
-    // Returns the  snippets from fields values.
-    fn snippets_from_field_values(
+    // Returns the snippets of the snippet fields present in the document.
+    fn snippets_from_doc(&self, doc: &TantivyDocument) -> HashMap<&str, Vec<String>> {
+        let sorted_field_values = doc.get_sorted_field_values();
+        let mut snippets = HashMap::new();
+        for (snippet_field_name, field_generator) in &self.field_generators {
+            let Some((_, field_values)) = sorted_field_values
+                .iter()
+                .find(|(field, _)| *field == field_generator.field)
+            else {
+                continue;
+            };
+            let mut texts = Vec::new();
+            for field_value in field_values {
+                collect_texts_at_json_path(field_value, &field_generator.json_path, &mut texts);
+            }
+            if texts.is_empty() && !field_generator.json_path.is_empty() {
+                continue;
+            }
+            let mut fragments = Vec::new();
+            for text in texts {
+                self.highlight_text(&field_generator.snippet_generator, text, &mut fragments);
+            }
+            snippets.insert(snippet_field_name.as_str(), fragments);
+        }
+        snippets
+    }
+
+    // Appends the best highlighted fragments of `text` to `fragments`.
+    fn highlight_text(
         &self,
-        field_name: &str,
-        field_values: Vec<&OwnedValue>,
-    ) -> Option<Vec<String>> {
-        if let Some(snippet_generator) = self.field_generators.get(field_name) {
-            let values = field_values
-                .into_iter()
-                .filter_map(|value| {
-                    value.as_str().and_then(|text| {
-                        let snippet = snippet_generator.snippet(text);
-                        match snippet.is_empty() {
-                            false => Some(snippet.to_html()),
-                            _ => None,
-                        }
-                    })
-                })
-                .collect();
-            Some(values)
-        } else {
-            None
+        snippet_generator: &SnippetGenerator,
+        text: &str,
+        fragments: &mut Vec<String>,
+    ) {
+        if self.num_fragments <= 1 {
+            let snippet = snippet_generator.snippet(text);
+            if !snippet.is_empty() {
+                fragments.push(self.snippet_to_html(snippet));
+            }
+            return;
         }
+        let mut snippets: Vec<Snippet> = split_text_into_chunks(text, self.fragment_size)
+            .into_iter()
+            .map(|chunk| snippet_generator.snippet(chunk))
+            .filter(|snippet| !snippet.is_empty())
+            .collect();
+        // The sort is stable: among the fragments with the same number of highlighted terms,
+        // the first ones in the text come first.
+        snippets.sort_by_key(|snippet| Reverse(snippet.highlighted().len()));
+        fragments.extend(
+            snippets
+                .into_iter()
+                .take(self.num_fragments)
+                .map(|snippet| self.snippet_to_html(snippet)),
+        );
+    }
+
+    fn snippet_to_html(&self, mut snippet: Snippet) -> String {
+        snippet.set_snippet_prefix_postfix(&self.pre_tag, &self.post_tag);
+        snippet.to_html()
     }
 
     fn is_empty(&self) -> bool {
@@ -293,6 +347,67 @@ impl FieldsSnippetGenerator {
     }
 }
 
+// Collects the texts found at `json_path` in `value`. Objects keys may contain dots, so they are
+// matched against a prefix of the path rather than against a single path segment.
+fn collect_texts_at_json_path<'a>(
+    value: &'a OwnedValue,
+    json_path: &str,
+    texts: &mut Vec<&'a str>,
+) {
+    if let Some(array_iter) = value.as_array() {
+        for element in array_iter {
+            collect_texts_at_json_path(element, json_path, texts);
+        }
+        return;
+    }
+    if json_path.is_empty() {
+        if let Some(text) = value.as_str() {
+            texts.push(text);
+        }
+        return;
+    }
+    let Some(object_iter) = value.as_object() else {
+        return;
+    };
+    for (key, child_value) in object_iter {
+        let Some(path_suffix) = json_path.strip_prefix(key) else {
+            continue;
+        };
+        if path_suffix.is_empty() {
+            collect_texts_at_json_path(child_value, "", texts);
+        } else if let Some(child_json_path) = path_suffix.strip_prefix('.') {
+            collect_texts_at_json_path(child_value, child_json_path, texts);
+        }
+    }
+}
+
+// Splits `text` into chunks of at most `chunk_size` bytes, avoiding to cut words whenever
+// possible.
+fn split_text_into_chunks(mut text: &str, chunk_size: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    while text.len() > chunk_size {
+        let mut chunk_end = chunk_size;
+        while !text.is_char_boundary(chunk_end) {
+            chunk_end -= 1;
+        }
+        if let Some(whitespace_pos) = text[..chunk_end].rfind(char::is_whitespace) {
+            if whitespace_pos > 0 {
+                chunk_end = whitespace_pos;
+            }
+        }
+        if chunk_end == 0 {
+            // The chunk size is smaller than the first character.
+            chunk_end = text.chars().next().map(char::len_utf8).unwrap_or_default();
+        }
+        chunks.push(&text[..chunk_end]);
+        text = text[chunk_end..].trim_start();
+    }
+    if !text.is_empty() {
+        chunks.push(text);
+    }
+    chunks
+}
+
 // Creates FieldsSnippetGenerator.
 async fn create_fields_snippet_generator(
     searcher: &Searcher,
@@ -303,23 +418,89 @@ async fn create_fields_snippet_generator(
     let query_ast_resolved = serde_json::from_str(&snippet_request.query_ast_resolved)
         .context("failed to deserialize QueryAst")?;
     let (query, _) = doc_mapper.query(schema.clone(), &query_ast_resolved, false)?;
-    let mut snippet_generators = HashMap::new();
-    for field_name in &snippet_request.snippet_fields {
-        let field = schema.get_field(field_name)?;
-        let snippet_generator = create_snippet_generator(searcher, &query, field).await?;
-        snippet_generators.insert(field_name.clone(), snippet_generator);
+    let snippet_options = snippet_request.snippet_options.clone().unwrap_or_default();
+    // As in Elasticsearch, 0 fragments means that the whole field values are highlighted.
+    let (fragment_size, num_fragments) = match snippet_options.num_fragments {
+        Some(0) => (WHOLE_FIELD_FRAGMENT_SIZE, 1),
+        num_fragments_opt => (
+            snippet_options
+                .fragment_size
+                .map(|fragment_size| fragment_size as usize)
+                .unwrap_or(SNIPPET_MAX_NUM_CHARS),
+            num_fragments_opt.unwrap_or(1) as usize,
+        ),
+    };
+    let mut field_generators = HashMap::new();
+    for field_name in expand_snippet_fields(&snippet_request.snippet_fields, schema) {
+        // Splits created with an older doc mapping may not hold the requested field.
+        let Ok((field, _, json_path)) = find_field_or_hit_dynamic(&field_name, schema) else {
+            continue;
+        };
+        let snippet_generator =
+            create_snippet_generator(searcher, &query, field, json_path, fragment_size).await?;
+        let field_generator = FieldSnippetGenerator {
+            field,
+            json_path: json_path.to_string(),
+            snippet_generator,
+        };
+        field_generators.insert(field_name, field_generator);
     }
 
     Ok(FieldsSnippetGenerator {
-        field_generators: Arc::new(snippet_generators),
+        field_generators,
+        fragment_size,
+        num_fragments,
+        pre_tag: snippet_options
+            .pre_tag
+            .unwrap_or_else(|| DEFAULT_SNIPPET_PRE_TAG.to_string()),
+        post_tag: snippet_options
+            .post_tag
+            .unwrap_or_else(|| DEFAULT_SNIPPET_POST_TAG.to_string()),
     })
 }
 
+// Replaces the `*` snippet field with the text fields of the split that are stored and indexed.
+// The paths of json fields cannot be listed, so they have to be requested explicitly.
+fn expand_snippet_fields(snippet_fields: &[String], schema: &Schema) -> Vec<String> {
+    let mut expanded_snippet_fields = Vec::with_capacity(snippet_fields.len());
+    for field_name in snippet_fields {
+        if field_name != SNIPPET_FIELDS_WILDCARD {
+            expanded_snippet_fields.push(field_name.clone());
+            continue;
+        }
+        for (_, field_entry) in schema.fields() {
+            if let FieldType::Str(text_options) = field_entry.field_type() {
+                if text_options.is_stored() && text_options.get_indexing_options().is_some() {
+                    expanded_snippet_fields.push(field_entry.name().to_string());
+                }
+            }
+        }
+    }
+    expanded_snippet_fields.into_iter().unique().collect()
+}
+
+// Returns the text of `term` if it belongs to `json_path`. An empty `json_path` designates terms
+// of a plain text field.
+fn term_text(term: &Term, json_path: &str) -> Option<String> {
+    let value = term.value();
+    if json_path.is_empty() {
+        return value.as_str().map(str::to_string);
+    }
+    let (term_json_path, json_value) = value.as_json()?;
+    // The segments of the json path of a term are separated by `\x01`.
+    if term_json_path.replace('\u{1}', ".") != json_path {
+        return None;
+    }
+    json_value.as_str().map(str::to_string)
+}
+
 // Creates a snippet generator associated to a field.
 async fn create_snippet_generator(
     searcher: &Searcher,
     query: &dyn Query,
     field: Field,
+    json_path: &str,
+    fragment_size: usize,
 ) -> anyhow::Result<SnippetGenerator> {
     let mut terms: Vec<&Term> = Vec::new();
     // TODO ok with termset?
@@ -330,14 +511,13 @@ async fn create_snippet_generator(
     });
     let mut terms_text: BTreeMap<String, f32> = BTreeMap::default();
     for term in terms {
-        let value = term.value();
-        let Some(term_str) = value.as_str() else {
+        let Some(term_str) = term_text(term, json_path) else {
             continue;
         };
         let doc_freq = searcher.doc_freq_async(term).await?;
         if doc_freq > 0 {
             let score = 1.0 / (1.0 + doc_freq as Score);
-            terms_text.insert(term_str.to_string(), score);
+            terms_text.insert(term_str, score);
         }
     }
     let tokenizer = searcher.index().tokenizer_for_field(field)?;
@@ -345,6 +525,6 @@ async fn create_snippet_generator(
         terms_text,
         tokenizer,
         field,
-        SNIPPET_MAX_NUM_CHARS,
+        fragment_size,
     ))
 }
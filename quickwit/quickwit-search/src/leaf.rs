@@ -18,8 +18,10 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use anyhow::Context;
 use futures::future::try_join_all;
@@ -28,22 +30,29 @@ use quickwit_directories::{CachingDirectory, HotDirectory, StorageDirectory};
 use quickwit_doc_mapper::{DocMapper, TermRange, WarmupInfo};
 use quickwit_proto::search::{
     CountHits, LeafSearchResponse, PartialHit, SearchRequest, SortOrder, SortValue,
-    SplitIdAndFooterOffsets, SplitSearchError,
+    SplitIdAndFooterOffsets, SplitSearchError, SplitSearchProfile,
 };
 use quickwit_query::query_ast::QueryAst;
 use quickwit_query::tokenizers::TokenizerManager;
 use quickwit_storage::{
-    wrap_storage_with_cache, BundleStorage, MemorySizedCache, OwnedBytes, SplitCache, Storage,
+    wrap_storage_with_cache, wrap_storage_with_cache_and_stats, BundleStorage, MemorySizedCache,
+    OwnedBytes, SplitCache, Storage, StorageCacheStats,
 };
 use tantivy::directory::FileSlice;
 use tantivy::fastfield::FastFieldReaders;
 use tantivy::schema::Field;
 use tantivy::{Index, ReloadPolicy, Searcher, Term};
+use tokio::task::{JoinError, JoinHandle};
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
 use tracing::*;
 
 use crate::collector::{make_collector_for_split, make_merge_collector, IncrementalCollector};
+use crate::partial_aggregation_cache::{
+    is_split_aggregation_result_cacheable, split_aggregation_result_to_cache,
+};
 use crate::service::SearcherContext;
-use crate::SearchError;
+use crate::{run_unless_cancelled, SearchError};
 
 #[instrument(skip_all)]
 async fn get_split_footer_from_cache_or_fetch(
@@ -126,14 +135,43 @@ pub(crate) async fn open_index_with_caches(
     split_and_footer_offsets: &SplitIdAndFooterOffsets,
     tokenizer_manager: Option<&TokenizerManager>,
     ephemeral_unbounded_cache: bool,
+) -> anyhow::Result<Index> {
+    open_index_with_caches_and_stats(
+        searcher_context,
+        index_storage,
+        split_and_footer_offsets,
+        tokenizer_manager,
+        ephemeral_unbounded_cache,
+        None,
+    )
+    .await
+}
+
+/// Same as [`open_index_with_caches`], but records the reads going through the fast fields cache
+/// in `cache_stats_opt`, if set.
+async fn open_index_with_caches_and_stats(
+    searcher_context: &SearcherContext,
+    index_storage: Arc<dyn Storage>,
+    split_and_footer_offsets: &SplitIdAndFooterOffsets,
+    tokenizer_manager: Option<&TokenizerManager>,
+    ephemeral_unbounded_cache: bool,
+    cache_stats_opt: Option<Arc<StorageCacheStats>>,
 ) -> anyhow::Result<Index> {
     let (hotcache_bytes, bundle_storage) =
         open_split_bundle(searcher_context, index_storage, split_and_footer_offsets).await?;
 
-    let bundle_storage_with_cache = wrap_storage_with_cache(
-        searcher_context.fast_fields_cache.clone(),
-        Arc::new(bundle_storage),
-    );
+    let bundle_storage_with_cache = if let Some(cache_stats) = cache_stats_opt {
+        wrap_storage_with_cache_and_stats(
+            searcher_context.fast_fields_cache.clone(),
+            Arc::new(bundle_storage),
+            cache_stats,
+        )
+    } else {
+        wrap_storage_with_cache(
+            searcher_context.fast_fields_cache.clone(),
+            Arc::new(bundle_storage),
+        )
+    };
     let directory = StorageDirectory::new(bundle_storage_with_cache);
 
     let hot_directory = if ephemeral_unbounded_cache {
@@ -171,25 +209,33 @@ pub(crate) async fn open_index_with_caches(
 /// This is e.g. required for term aggregation, since we don't know in advance which terms are going
 /// to be hit.
 #[instrument(skip_all)]
-pub(crate) async fn warmup(searcher: &Searcher, warmup_info: &WarmupInfo) -> anyhow::Result<()> {
+pub(crate) async fn warmup(
+    searcher: &Searcher,
+    warmup_info: &WarmupInfo,
+) -> anyhow::Result<WarmupDurations> {
     debug!(warmup_info=?warmup_info);
-    let warm_up_terms_future = warm_up_terms(searcher, &warmup_info.terms_grouped_by_field)
+    let warm_up_terms_future = timed(warm_up_terms(searcher, &warmup_info.terms_grouped_by_field))
         .instrument(debug_span!("warm_up_terms"));
-    let warm_up_term_ranges_future =
-        warm_up_term_ranges(searcher, &warmup_info.term_ranges_grouped_by_field)
-            .instrument(debug_span!("warm_up_term_ranges"));
-    let warm_up_term_dict_future =
-        warm_up_term_dict_fields(searcher, &warmup_info.term_dict_fields)
-            .instrument(debug_span!("warm_up_term_dicts"));
-    let warm_up_fastfields_future = warm_up_fastfields(searcher, &warmup_info.fast_field_names)
-        .instrument(debug_span!("warm_up_fastfields"));
-    let warm_up_fieldnorms_future = warm_up_fieldnorms(searcher, warmup_info.field_norms)
+    let warm_up_term_ranges_future = timed(warm_up_term_ranges(
+        searcher,
+        &warmup_info.term_ranges_grouped_by_field,
+    ))
+    .instrument(debug_span!("warm_up_term_ranges"));
+    let warm_up_term_dict_future = timed(warm_up_term_dict_fields(
+        searcher,
+        &warmup_info.term_dict_fields,
+    ))
+    .instrument(debug_span!("warm_up_term_dicts"));
+    let warm_up_fastfields_future =
+        timed(warm_up_fastfields(searcher, &warmup_info.fast_field_names))
+            .instrument(debug_span!("warm_up_fastfields"));
+    let warm_up_fieldnorms_future = timed(warm_up_fieldnorms(searcher, warmup_info.field_norms))
         .instrument(debug_span!("warm_up_fieldnorms"));
     // TODO merge warm_up_postings into warm_up_term_dict_fields
-    let warm_up_postings_future = warm_up_postings(searcher, &warmup_info.term_dict_fields)
+    let warm_up_postings_future = timed(warm_up_postings(searcher, &warmup_info.term_dict_fields))
         .instrument(debug_span!("warm_up_postings"));
 
-    tokio::try_join!(
+    let (terms, term_ranges, fastfields, term_dicts, fieldnorms, postings) = tokio::try_join!(
         warm_up_terms_future,
         warm_up_term_ranges_future,
         warm_up_fastfields_future,
@@ -198,7 +244,32 @@ pub(crate) async fn warmup(searcher: &Searcher, warmup_info: &WarmupInfo) -> any
         warm_up_postings_future,
     )?;
 
-    Ok(())
+    Ok(WarmupDurations {
+        terms,
+        term_ranges,
+        term_dicts,
+        fastfields,
+        fieldnorms,
+        postings,
+    })
+}
+
+/// Time spent in each step of the warmup. The steps run concurrently.
+#[derive(Debug, Default)]
+pub(crate) struct WarmupDurations {
+    terms: Duration,
+    term_ranges: Duration,
+    term_dicts: Duration,
+    fastfields: Duration,
+    fieldnorms: Duration,
+    postings: Duration,
+}
+
+/// Runs a warmup step and returns the time it took.
+async fn timed(future: impl Future<Output = anyhow::Result<()>>) -> anyhow::Result<Duration> {
+    let start_instant = Instant::now();
+    future.await?;
+    Ok(start_instant.elapsed())
 }
 
 async fn warm_up_term_dict_fields(
@@ -326,6 +397,9 @@ async fn warm_up_fieldnorms(searcher: &Searcher, requires_scoring: bool) -> anyh
 }
 
 /// Apply a leaf search on a single split.
+///
+/// The search stops downloading data and does not schedule any work on the search thread pool
+/// once `cancellation_token` is cancelled.
 #[instrument(skip_all, fields(split_id = split.split_id))]
 async fn leaf_search_single_split(
     searcher_context: &SearcherContext,
@@ -333,24 +407,41 @@ async fn leaf_search_single_split(
     storage: Arc<dyn Storage>,
     split: SplitIdAndFooterOffsets,
     doc_mapper: Arc<dyn DocMapper>,
+    cancellation_token: &CancellationToken,
 ) -> crate::Result<LeafSearchResponse> {
     rewrite_request(&mut search_request, &split);
-    if let Some(cached_answer) = searcher_context
+    if let Some(mut cached_answer) = searcher_context
         .leaf_search_cache
         .get(split.clone(), search_request.clone())
     {
+        if search_request.profile {
+            cached_answer.split_profiles = vec![SplitSearchProfile {
+                split_id: split.split_id.clone(),
+                leaf_search_cache_hit: true,
+                num_docs_matched: cached_answer.num_hits,
+                ..Default::default()
+            }];
+        }
         return Ok(cached_answer);
     }
 
     let split_id = split.split_id.to_string();
-    let index = open_index_with_caches(
-        searcher_context,
-        storage,
-        &split,
-        Some(doc_mapper.tokenizer_manager()),
-        true,
+    let cache_stats_opt: Option<Arc<StorageCacheStats>> =
+        search_request.profile.then(Default::default);
+    let open_index_start_instant = Instant::now();
+    let index = run_unless_cancelled(
+        open_index_with_caches_and_stats(
+            searcher_context,
+            storage,
+            &split,
+            Some(doc_mapper.tokenizer_manager()),
+            true,
+            cache_stats_opt.clone(),
+        ),
+        cancellation_token,
     )
-    .await?;
+    .await??;
+    let open_index_duration = open_index_start_instant.elapsed();
     let split_schema = index.schema();
 
     let quickwit_collector = make_collector_for_split(
@@ -368,27 +459,123 @@ async fn leaf_search_single_split(
         .try_into()?;
     let searcher = reader.searcher();
 
+    let tantivy_query_opt: Option<String> = search_request.profile.then(|| format!("{query:?}"));
+
+    if let Some(term) = single_term_to_count(&search_request, &query_ast, &warmup_info) {
+        // Splits do not have deleted documents, so the number of matching documents is the
+        // document frequency of the term. It only requires reading the term dictionary.
+        let count_start_instant = Instant::now();
+        let num_hits =
+            run_unless_cancelled(searcher.doc_freq_async(&term), cancellation_token).await??;
+        let mut leaf_search_response = LeafSearchResponse {
+            num_hits,
+            num_attempted_splits: 1,
+            ..Default::default()
+        };
+        searcher_context
+            .leaf_search_cache
+            .put(split, search_request, leaf_search_response.clone());
+
+        if let Some(tantivy_query) = tantivy_query_opt {
+            leaf_search_response.split_profiles = vec![SplitSearchProfile {
+                split_id,
+                tantivy_query,
+                open_index_micros: open_index_duration.as_micros() as u64,
+                collection_micros: count_start_instant.elapsed().as_micros() as u64,
+                num_docs_matched: num_hits,
+                ..Default::default()
+            }];
+        }
+        return Ok(leaf_search_response);
+    }
+
     let collector_warmup_info = quickwit_collector.warmup_info();
     warmup_info.merge(collector_warmup_info);
     warmup_info.simplify();
 
-    warmup(&searcher, &warmup_info).await?;
+    let warmup_start_instant = Instant::now();
+    let warmup_durations =
+        run_unless_cancelled(warmup(&searcher, &warmup_info), cancellation_token).await??;
+    let warmup_duration = warmup_start_instant.elapsed();
     let span = info_span!("tantivy_search");
-    let leaf_search_response = crate::run_cpu_intensive(move || {
-        let _span_guard = span.enter();
-        searcher.search(&query, &quickwit_collector)
-    })
-    .await
-    .map_err(|_| {
-        crate::SearchError::Internal(format!("leaf search panicked. split={split_id}"))
-    })??;
+    // Dropping the `run_cpu_intensive` future on cancellation prevents the search from being
+    // scheduled if it is still waiting for a thread of the search thread pool.
+    let (leaf_search_response_res, collection_duration) = run_unless_cancelled(
+        crate::run_cpu_intensive(move || {
+            let _span_guard = span.enter();
+            let collection_start_instant = std::time::Instant::now();
+            let leaf_search_response_res = searcher.search(&query, &quickwit_collector);
+            (leaf_search_response_res, collection_start_instant.elapsed())
+        }),
+        cancellation_token,
+    )
+    .await?
+    .map_err(|_| crate::SearchError::Internal(format!("leaf search panicked. split={split_id}")))?;
+    let mut leaf_search_response = leaf_search_response_res?;
 
     searcher_context
         .leaf_search_cache
         .put(split, search_request, leaf_search_response.clone());
+
+    if let (Some(tantivy_query), Some(cache_stats)) = (tantivy_query_opt, cache_stats_opt) {
+        leaf_search_response.split_profiles = vec![SplitSearchProfile {
+            split_id,
+            leaf_search_cache_hit: false,
+            tantivy_query,
+            open_index_micros: open_index_duration.as_micros() as u64,
+            warmup_micros: warmup_duration.as_micros() as u64,
+            warm_up_terms_micros: warmup_durations.terms.as_micros() as u64,
+            warm_up_term_ranges_micros: warmup_durations.term_ranges.as_micros() as u64,
+            warm_up_term_dicts_micros: warmup_durations.term_dicts.as_micros() as u64,
+            warm_up_fastfields_micros: warmup_durations.fastfields.as_micros() as u64,
+            warm_up_fieldnorms_micros: warmup_durations.fieldnorms.as_micros() as u64,
+            warm_up_postings_micros: warmup_durations.postings.as_micros() as u64,
+            collection_micros: collection_duration.as_micros() as u64,
+            num_docs_matched: leaf_search_response.num_hits,
+            num_bytes_fetched: cache_stats.num_bytes_fetched(),
+            num_cache_hits: cache_stats.num_hits(),
+            num_cache_misses: cache_stats.num_misses(),
+        }];
+    }
     Ok(leaf_search_response)
 }
 
+/// Returns the term whose document frequency is the number of documents of the split matching the
+/// request, if the request only counts the documents matching a single term.
+///
+/// `search_request` is expected to have been rewritten by [`rewrite_request`] already, so that the
+/// time bounds are only set if they do not cover the entire split.
+fn single_term_to_count(
+    search_request: &SearchRequest,
+    query_ast: &QueryAst,
+    warmup_info: &WarmupInfo,
+) -> Option<Term> {
+    if search_request.max_hits > 0
+        || search_request.aggregation_request.is_some()
+        || search_request.start_timestamp.is_some()
+        || search_request.end_timestamp.is_some()
+    {
+        return None;
+    }
+    // A term query uses the raw tokenizer, so it matches exactly the documents containing the
+    // single term found in the warmup info.
+    if !matches!(query_ast, QueryAst::Term(_))
+        || !warmup_info.term_dict_fields.is_empty()
+        || !warmup_info.term_ranges_grouped_by_field.is_empty()
+    {
+        return None;
+    }
+    let mut terms = warmup_info
+        .terms_grouped_by_field
+        .values()
+        .flat_map(|terms| terms.keys());
+    let term = terms.next()?;
+    if terms.next().is_some() {
+        return None;
+    }
+    Some(term.clone())
+}
+
 /// Rewrite a request removing parts which incure additional download or computation with no
 /// effect.
 ///
@@ -397,6 +584,7 @@ async fn leaf_search_single_split(
 fn rewrite_request(search_request: &mut SearchRequest, split: &SplitIdAndFooterOffsets) {
     if search_request.max_hits == 0 {
         search_request.sort_fields = vec![];
+        search_request.collapse = None;
     }
     rewrite_start_end_time_bounds(
         &mut search_request.start_timestamp,
@@ -522,7 +710,7 @@ impl CanSplitDoBetter {
             CanSplitDoBetter::SplitIdHigher(split_id) => *split_id = Some(hit.split_id.clone()),
             CanSplitDoBetter::SplitTimestampHigher(timestamp)
             | CanSplitDoBetter::FindTraceIdsAggregation(timestamp) => {
-                if let Some(SortValue::I64(timestamp_ns)) = hit.sort_value() {
+                if let Some(&SortValue::I64(timestamp_ns)) = hit.sort_value() {
                     // if we get a timestamp of, says 1.5s, we need to check up to 2s to make
                     // sure we don't throw away something like 1.2s, so we should round up while
                     // dividing.
@@ -530,7 +718,7 @@ impl CanSplitDoBetter {
                 }
             }
             CanSplitDoBetter::SplitTimestampLower(timestamp) => {
-                if let Some(SortValue::I64(timestamp_ns)) = hit.sort_value() {
+                if let Some(&SortValue::I64(timestamp_ns)) = hit.sort_value() {
                     // if we get a timestamp of, says 1.5s, we need to check down to 1s to make
                     // sure we don't throw away something like 1.7s, so we should truncate,
                     // which is the default behavior of division
@@ -542,12 +730,47 @@ impl CanSplitDoBetter {
     }
 }
 
+/// Splits currently being searched, along with the tokens used to cancel their search.
+///
+/// Once a split returns, the worst hit of the top-k may improve and some of the splits still
+/// running may no longer be able to produce better hits. Cancelling them frees the search
+/// thread pool and the download bandwidth they would otherwise consume.
+#[derive(Default)]
+struct RunningSplits {
+    splits: HashMap<String, (SplitIdAndFooterOffsets, CancellationToken)>,
+}
+
+impl RunningSplits {
+    fn insert(&mut self, split: SplitIdAndFooterOffsets, cancellation_token: CancellationToken) {
+        self.splits
+            .insert(split.split_id.clone(), (split, cancellation_token));
+    }
+
+    fn remove(&mut self, split_id: &str) {
+        self.splits.remove(split_id);
+    }
+
+    /// Cancels and forgets the splits that can no longer produce better hits than the ones
+    /// already collected.
+    fn cancel_splits_that_cannot_be_better(&mut self, split_filter: &CanSplitDoBetter) {
+        self.splits.retain(|_, (split, cancellation_token)| {
+            if split_filter.can_be_better(split) {
+                return true;
+            }
+            cancellation_token.cancel();
+            false
+        });
+    }
+}
+
 /// `leaf` step of search.
 ///
 /// The leaf search collects all kind of information, and returns a set of
 /// [PartialHit](quickwit_proto::search::PartialHit) candidates. The root will be in
 /// charge to consolidate, identify the actual final top hits to display, and
 /// fetch the actual documents to convert the partial hits into actual Hits.
+///
+/// Cancelling `cancellation_token` stops the searches of all the splits that are still running.
 #[instrument(skip_all, fields(index = ?request.index_id_patterns))]
 pub async fn leaf_search(
     searcher_context: Arc<SearcherContext>,
@@ -555,6 +778,7 @@ pub async fn leaf_search(
     index_storage: Arc<dyn Storage>,
     mut splits: Vec<SplitIdAndFooterOffsets>,
     doc_mapper: Arc<dyn DocMapper>,
+    cancellation_token: CancellationToken,
 ) -> Result<LeafSearchResponse, SearchError> {
     info!(splits_num = splits.len(), split_offsets = ?PrettySample::new(&splits, 5));
 
@@ -574,14 +798,42 @@ pub async fn leaf_search(
 
     let split_filter = Arc::new(Mutex::new(split_filter));
     let incremental_merge_collector = Arc::new(Mutex::new(incremental_merge_collector));
+    // Running splits can only be pruned if we are not required to search all of them.
+    let running_splits_opt: Option<Arc<Mutex<RunningSplits>>> =
+        (!run_all_splits).then(Default::default);
 
-    let mut leaf_search_single_split_futures: Vec<_> = Vec::with_capacity(splits.len());
+    // Splits that are not searched before the deadline are skipped, and reported as such in the
+    // response.
+    let deadline_opt: Option<Instant> = request
+        .timeout_millis
+        .map(|timeout_millis| Instant::now() + Duration::from_millis(timeout_millis));
+    let mut skipped_split_ids: Vec<String> = Vec::new();
+
+    let mut leaf_search_single_split_futures: Vec<(String, JoinHandle<()>)> =
+        Vec::with_capacity(splits.len());
 
     for split in splits {
-        let leaf_split_search_permit = searcher_context.leaf_search_split_semaphore
+        if cancellation_token.is_cancelled() {
+            break;
+        }
+        if deadline_opt.map_or(false, |deadline| deadline <= Instant::now()) {
+            skipped_split_ids.push(split.split_id);
+            continue;
+        }
+        let permit_fut = searcher_context
+            .leaf_search_split_semaphore
             .clone()
-            .acquire_owned()
-            .await
+            .acquire_owned();
+        let permit_res = if let Some(deadline) = deadline_opt {
+            let Ok(permit_res) = tokio::time::timeout_at(deadline, permit_fut).await else {
+                skipped_split_ids.push(split.split_id);
+                continue;
+            };
+            permit_res
+        } else {
+            permit_fut.await
+        };
+        let leaf_split_search_permit = permit_res
             .expect("Failed to acquire permit. This should never happen! Please, report on https://github.com/quickwit-oss/quickwit/issues.");
 
         let mut request = (*request).clone();
@@ -595,7 +847,15 @@ pub async fn leaf_search(
             request.sort_fields.clear();
         }
 
-        leaf_search_single_split_futures.push(tokio::spawn(
+        let split_id = split.split_id.clone();
+        let split_cancellation_token = cancellation_token.child_token();
+        if let Some(running_splits) = &running_splits_opt {
+            running_splits
+                .lock()
+                .unwrap()
+                .insert(split.clone(), split_cancellation_token.clone());
+        }
+        let leaf_search_single_split_future = tokio::spawn(
             leaf_search_single_split_wrapper(
                 request,
                 searcher_context.clone(),
@@ -603,17 +863,41 @@ pub async fn leaf_search(
                 doc_mapper.clone(),
                 split,
                 split_filter.clone(),
+                running_splits_opt.clone(),
                 incremental_merge_collector.clone(),
                 leaf_split_search_permit,
+                split_cancellation_token,
             )
             .in_current_span(),
-        ));
+        );
+        leaf_search_single_split_futures.push((split_id, leaf_search_single_split_future));
     }
 
-    // TODO we could cancel running splits when !run_all_splits and the running split can no longer
-    // give better results after some other split answered.
-    let split_search_results: Vec<Result<(), _>> =
-        futures::future::join_all(leaf_search_single_split_futures).await;
+    let mut split_search_results: Vec<(String, Result<(), JoinError>)> =
+        Vec::with_capacity(leaf_search_single_split_futures.len());
+
+    for (split_id, mut leaf_search_single_split_future) in leaf_search_single_split_futures {
+        let Some(deadline) = deadline_opt else {
+            split_search_results.push((split_id, leaf_search_single_split_future.await));
+            continue;
+        };
+        match tokio::time::timeout_at(deadline, &mut leaf_search_single_split_future).await {
+            Ok(split_search_result) => split_search_results.push((split_id, split_search_result)),
+            Err(_elapsed) => {
+                // The deadline has passed: we stop searching this split. The split may have
+                // completed in the meantime, in which case its result was already collected.
+                leaf_search_single_split_future.abort();
+                match leaf_search_single_split_future.await {
+                    Err(join_error) if join_error.is_cancelled() => {
+                        skipped_split_ids.push(split_id);
+                    }
+                    split_search_result => {
+                        split_search_results.push((split_id, split_search_result))
+                    }
+                }
+            }
+        }
+    }
 
     // we can't use unwrap_or_clone because mutexes aren't Clone
     let mut incremental_merge_collector = match Arc::try_unwrap(incremental_merge_collector) {
@@ -621,18 +905,23 @@ pub async fn leaf_search(
         Err(filter_merger) => filter_merger.lock().unwrap().clone(),
     };
 
-    for result in split_search_results {
+    for (split_id, result) in split_search_results {
         // splits that did not panic were already added to the collector
         if let Err(e) = result {
             incremental_merge_collector.add_failed_split(SplitSearchError {
-                // we could reasonably add a wrapper to the JoinHandle to give us the
-                // split_id anyway
-                split_id: "unknown".to_string(),
+                split_id,
                 error: format!("{}", SearchError::from(e)),
                 retryable_error: true,
             })
         }
     }
+    if !skipped_split_ids.is_empty() {
+        warn!(
+            num_skipped_splits = skipped_split_ids.len(),
+            "leaf search timed out before searching all splits"
+        );
+        incremental_merge_collector.add_skipped_splits(skipped_split_ids);
+    }
 
     crate::run_cpu_intensive(|| incremental_merge_collector.finalize().map_err(Into::into))
         .instrument(info_span!("incremental_merge_finalize"))
@@ -648,32 +937,56 @@ async fn leaf_search_single_split_wrapper(
     doc_mapper: Arc<dyn DocMapper>,
     split: SplitIdAndFooterOffsets,
     split_filter: Arc<Mutex<CanSplitDoBetter>>,
+    running_splits_opt: Option<Arc<Mutex<RunningSplits>>>,
     incremental_merge_collector: Arc<Mutex<IncrementalCollector>>,
     leaf_split_search_permit: tokio::sync::OwnedSemaphorePermit,
+    cancellation_token: CancellationToken,
 ) {
     crate::SEARCH_METRICS.leaf_searches_splits_total.inc();
     let timer = crate::SEARCH_METRICS
         .leaf_search_split_duration_secs
         .start_timer();
+    // The root caches the results of the splits fully covered by aggregation requests.
+    let is_split_result_cacheable = searcher_context
+        .searcher_config
+        .enable_partial_aggregation_cache
+        && is_split_aggregation_result_cacheable(&request, &split);
     let leaf_search_single_split_res = leaf_search_single_split(
         &searcher_context,
         request,
         index_storage,
         split.clone(),
         doc_mapper,
+        &cancellation_token,
     )
     .await;
 
     // We explicitly drop it, to highlight it to the reader
     std::mem::drop(leaf_split_search_permit);
 
+    if let Some(running_splits) = &running_splits_opt {
+        running_splits.lock().unwrap().remove(&split.split_id);
+    }
     if leaf_search_single_split_res.is_ok() {
         timer.observe_duration();
+    } else if cancellation_token.is_cancelled() {
+        // The split could no longer produce better hits or the request was abandoned: it is
+        // neither a hit contributor nor a failure.
+        crate::SEARCH_METRICS
+            .leaf_search_splits_cancelled_total
+            .inc();
+        return;
     }
 
     let mut locked_incremental_merge_collector = incremental_merge_collector.lock().unwrap();
     match leaf_search_single_split_res {
-        Ok(split_search_res) => {
+        Ok(mut split_search_res) => {
+            if is_split_result_cacheable {
+                split_search_res.split_aggregation_results =
+                    split_aggregation_result_to_cache(&split, &split_search_res)
+                        .into_iter()
+                        .collect();
+            }
             if let Err(err) = locked_incremental_merge_collector.add_split(split_search_res) {
                 locked_incremental_merge_collector.add_failed_split(SplitSearchError {
                     split_id: split.split_id.clone(),
@@ -689,9 +1002,14 @@ async fn leaf_search_single_split_wrapper(
         }),
     }
     if let Some(last_hit) = locked_incremental_merge_collector.peek_worst_hit() {
-        split_filter
-            .lock()
-            .unwrap()
-            .record_new_worst_hit(last_hit.as_ref());
+        let mut locked_split_filter = split_filter.lock().unwrap();
+        locked_split_filter.record_new_worst_hit(last_hit.as_ref());
+
+        if let Some(running_splits) = &running_splits_opt {
+            running_splits
+                .lock()
+                .unwrap()
+                .cancel_splits_that_cannot_be_better(&locked_split_filter);
+        }
     }
 }
@@ -104,6 +104,10 @@ impl CacheKey {
         // it doesn't matter whether or not we count all hits at the scale of a
         // single split: either we did process it and got everything, or we didn't.
         search_request.count_hits = CountHits::CountAll.into();
+        // the timeout only decides whether a split gets searched at all, not its result.
+        search_request.timeout_millis = None;
+        // profiles are attached to the responses after they are cached.
+        search_request.profile = false;
 
         CacheKey {
             split_id: split_info.split_id,
@@ -244,16 +248,29 @@ mod tests {
             partial_hits: vec![PartialHit {
                 doc_id: 1,
                 segment_ord: 0,
-                sort_value: Some(SortValue::U64(0u64).into()),
-                sort_value2: None,
+                sort_values: vec![SortValue::U64(0u64).into()],
                 split_id: "split_1".to_string(),
+                collapse_value: None,
+                ..Default::default()
             }],
+            skipped_split_ids: Vec::new(),
+            split_profiles: Vec::new(),
+            split_aggregation_results: Vec::new(),
+            top_hits: Vec::new(),
         };
 
         assert!(cache.get(split_1.clone(), query_1.clone()).is_none());
 
         cache.put(split_1.clone(), query_1.clone(), result.clone());
         assert_eq!(cache.get(split_1.clone(), query_1.clone()).unwrap(), result);
+        let query_1_with_timeout = SearchRequest {
+            timeout_millis: Some(1_000),
+            ..query_1.clone()
+        };
+        assert_eq!(
+            cache.get(split_1.clone(), query_1_with_timeout).unwrap(),
+            result
+        );
         assert!(cache.get(split_2, query_1).is_none());
         assert!(cache.get(split_1, query_2).is_none());
     }
@@ -330,10 +347,15 @@ mod tests {
             partial_hits: vec![PartialHit {
                 doc_id: 1,
                 segment_ord: 0,
-                sort_value: Some(SortValue::U64(0).into()),
-                sort_value2: None,
+                sort_values: vec![SortValue::U64(0).into()],
                 split_id: "split_1".to_string(),
+                collapse_value: None,
+                ..Default::default()
             }],
+            skipped_split_ids: Vec::new(),
+            split_profiles: Vec::new(),
+            split_aggregation_results: Vec::new(),
+            top_hits: Vec::new(),
         };
 
         // for split_1, 1 and 1bis cover different timestamp ranges
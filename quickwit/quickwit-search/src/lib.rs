@@ -22,6 +22,7 @@
 #![allow(clippy::bool_assert_comparison)]
 #![deny(clippy::disallowed_methods)]
 
+mod async_search;
 mod client;
 mod cluster_client;
 mod collector;
@@ -34,6 +35,7 @@ mod leaf_cache;
 mod list_fields;
 mod list_fields_cache;
 mod list_terms;
+mod partial_aggregation_cache;
 mod retry;
 mod root;
 mod scroll_context;
@@ -42,6 +44,7 @@ mod search_response_rest;
 mod search_stream;
 mod service;
 mod thread_pool;
+mod top_hits;
 
 mod metrics;
 
@@ -58,6 +61,7 @@ use tantivy::schema::NamedFieldDocument;
 /// Refer to this as `crate::Result<T>`.
 pub type Result<T> = std::result::Result<T, SearchError>;
 
+use std::future::Future;
 use std::net::{Ipv4Addr, SocketAddr};
 use std::sync::Arc;
 
@@ -73,7 +77,9 @@ use quickwit_proto::types::IndexUid;
 use quickwit_storage::StorageResolver;
 pub use service::SearcherContext;
 use tantivy::DocAddress;
+use tokio_util::sync::CancellationToken;
 
+pub use crate::async_search::{AsyncSearchResponse, DEFAULT_ASYNC_SEARCH_KEEP_ALIVE};
 pub use crate::client::{
     create_search_client_from_channel, create_search_client_from_grpc_addr, SearchServiceClient,
 };
@@ -83,7 +89,7 @@ use crate::fetch_docs::fetch_docs;
 use crate::leaf::leaf_search;
 pub use crate::root::{jobs_to_leaf_requests, root_search, IndexMetasForLeafSearch, SearchJob};
 pub use crate::search_job_placer::{Job, SearchJobPlacer};
-pub use crate::search_response_rest::SearchResponseRest;
+pub use crate::search_response_rest::{AsyncSearchResponseRest, SearchResponseRest};
 pub use crate::search_stream::root_search_stream;
 pub use crate::service::{MockSearchService, SearchService, SearchServiceImpl};
 use crate::thread_pool::run_cpu_intensive;
@@ -229,6 +235,21 @@ pub async fn start_searcher_service(
     Ok(search_service)
 }
 
+/// Drives `future` to completion, unless `cancellation_token` gets cancelled first, in which case
+/// the future is dropped and an error is returned.
+pub(crate) async fn run_unless_cancelled<F: Future>(
+    future: F,
+    cancellation_token: &CancellationToken,
+) -> crate::Result<F::Output> {
+    tokio::select! {
+        biased;
+        _ = cancellation_token.cancelled() => {
+            Err(SearchError::Internal("search was cancelled".to_string()))
+        }
+        output = future => Ok(output),
+    }
+}
+
 /// Performs a search on the current node.
 /// See also `[distributed_search]`.
 pub async fn single_node_search(
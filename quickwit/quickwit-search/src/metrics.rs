@@ -27,6 +27,8 @@ use quickwit_common::metrics::{
 pub struct SearchMetrics {
     pub leaf_searches_splits_total: IntCounter,
     pub leaf_search_split_duration_secs: Histogram,
+    pub leaf_search_splits_cancelled_total: IntCounter,
+    pub root_partial_aggregation_cache_hits_total: IntCounter,
     pub active_search_threads_count: IntGauge,
 }
 
@@ -44,6 +46,19 @@ impl Default for SearchMetrics {
                  starts after the semaphore is obtained.",
                 "quickwit_search",
             ),
+            leaf_search_splits_cancelled_total: new_counter(
+                "leaf_search_splits_cancelled_total",
+                "Number of leaf searches (count of splits) cancelled before completion, either \
+                 because they could no longer produce better hits or because the request was \
+                 abandoned.",
+                "quickwit_search",
+            ),
+            root_partial_aggregation_cache_hits_total: new_counter(
+                "root_partial_aggregation_cache_hits_total",
+                "Number of splits whose aggregation results were served from the root partial \
+                 aggregation cache instead of being searched.",
+                "quickwit_search",
+            ),
             active_search_threads_count: new_gauge(
                 "active_search_threads_count",
                 "Number of threads in use in the CPU thread pool",
@@ -0,0 +1,434 @@
+// Copyright (C) 2024 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//! Root-level cache of the per-split results of aggregation requests.
+//!
+//! Dashboards re-issue the same aggregation request over and over with a sliding time window.
+//! The splits fully covered by the time range of such a request yield the same result every time,
+//! so the root caches their results and only searches the splits at the edges of the window and
+//! the splits published since the last refresh. The results are stored in the searchers' KV
+//! store, in a namespace of their own, so that they are shared across the cluster.
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use prost::Message;
+use quickwit_config::SearcherConfig;
+use quickwit_proto::search::{
+    CountHits, LeafSearchResponse, SearchRequest, SplitAggregationResult, SplitIdAndFooterOffsets,
+};
+use tracing::debug;
+
+use crate::top_hits::may_contain_top_hits_aggregation;
+use crate::ClusterClient;
+
+/// Duration for which the split results of a request are kept after the request was last run.
+const PARTIAL_AGGREGATION_CACHE_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// Entries larger than this are not stored: fetching them from another node would not be much
+/// cheaper than running the search.
+const MAX_PARTIAL_AGGREGATION_CACHE_ENTRY_NUM_BYTES: usize = 4_000_000;
+
+/// Leaves do not return the results of splits larger than this for caching, so that a
+/// high-cardinality aggregation does not double the size of the leaf responses.
+const MAX_SPLIT_AGGREGATION_RESULT_NUM_BYTES: usize = 1_000_000;
+
+/// Loading the cache entry is skipped past this delay, and the splits are searched instead.
+const PARTIAL_AGGREGATION_CACHE_LOAD_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Number of entries kept by the partial aggregation cache namespace of the KV store of a searcher.
+pub(crate) const PARTIAL_AGGREGATION_CACHE_CAPACITY: usize = 100;
+
+pub(crate) const PARTIAL_AGGREGATION_CACHE_KEY_PREFIX: &[u8] = b"partial_aggregation_cache:";
+
+fn is_request_cacheable(search_request: &SearchRequest) -> bool {
+    // The split results do not hold the hits of `top_hits` aggregations.
+    search_request
+        .aggregation_request
+        .as_deref()
+        .is_some_and(|aggregation_request| !may_contain_top_hits_aggregation(aggregation_request))
+        && search_request.max_hits == 0
+        && search_request.scroll_ttl_secs.is_none()
+}
+
+/// Returns true if the result of `search_request` on `split` can be cached and reused by
+/// subsequent requests that only differ by their time range, that is, if the time range of the
+/// request fully covers the split.
+pub(crate) fn is_split_aggregation_result_cacheable(
+    search_request: &SearchRequest,
+    split: &SplitIdAndFooterOffsets,
+) -> bool {
+    if !is_request_cacheable(search_request) {
+        return false;
+    }
+    match (split.timestamp_start, split.timestamp_end) {
+        // The start of the request is inclusive and its end exclusive, whereas both bounds of the
+        // split are inclusive.
+        (Some(split_start), Some(split_end)) => {
+            search_request
+                .start_timestamp
+                .map_or(true, |start_timestamp| start_timestamp <= split_start)
+                && search_request
+                    .end_timestamp
+                    .map_or(true, |end_timestamp| end_timestamp > split_end)
+        }
+        _ => search_request.start_timestamp.is_none() && search_request.end_timestamp.is_none(),
+    }
+}
+
+/// Returns the result of a cacheable split to return along with the leaf response, so that the
+/// root can cache it, unless the result is too large.
+pub(crate) fn split_aggregation_result_to_cache(
+    split: &SplitIdAndFooterOffsets,
+    split_response: &LeafSearchResponse,
+) -> Option<SplitAggregationResult> {
+    let intermediate_aggregation_result = split_response.intermediate_aggregation_result.as_ref();
+    if intermediate_aggregation_result.map_or(0, Vec::len) > MAX_SPLIT_AGGREGATION_RESULT_NUM_BYTES
+    {
+        return None;
+    }
+    Some(SplitAggregationResult {
+        split_id: split.split_id.clone(),
+        num_hits: split_response.num_hits,
+        intermediate_aggregation_result: intermediate_aggregation_result.cloned(),
+    })
+}
+
+/// Appends `split_results` to `dst` until their total size reaches the maximum size of a cache
+/// entry. The split results beyond that could not be stored anyway.
+pub(crate) fn extend_split_aggregation_results(
+    dst: &mut Vec<SplitAggregationResult>,
+    split_results: impl IntoIterator<Item = SplitAggregationResult>,
+) {
+    let mut num_bytes: usize = dst.iter().map(split_aggregation_result_num_bytes).sum();
+
+    for split_result in split_results {
+        num_bytes += split_aggregation_result_num_bytes(&split_result);
+
+        if num_bytes > MAX_PARTIAL_AGGREGATION_CACHE_ENTRY_NUM_BYTES {
+            return;
+        }
+        dst.push(split_result);
+    }
+}
+
+fn split_aggregation_result_num_bytes(split_result: &SplitAggregationResult) -> usize {
+    split_result
+        .intermediate_aggregation_result
+        .as_ref()
+        .map_or(0, Vec::len)
+}
+
+/// Builds the key of the cache entry of `search_request`. The time range is not part of the key
+/// since the entry only holds the results of splits fully covered by the requests.
+fn partial_aggregation_cache_key(search_request: &SearchRequest) -> Vec<u8> {
+    let mut search_request = search_request.clone();
+    search_request.start_timestamp = None;
+    search_request.end_timestamp = None;
+    // See the `LeafSearchCache` key for the rationale behind the fields below.
+    search_request.count_hits = CountHits::CountAll.into();
+    search_request.timeout_millis = None;
+    search_request.profile = false;
+    search_request.sort_fields.clear();
+
+    let mut key = PARTIAL_AGGREGATION_CACHE_KEY_PREFIX.to_vec();
+    key.extend(search_request.encode_to_vec());
+    key
+}
+
+/// The split results of an aggregation request, as loaded from the cluster KV store.
+pub(crate) struct PartialAggregationCache {
+    key: Vec<u8>,
+    split_results: HashMap<String, SplitAggregationResult>,
+    is_modified: bool,
+}
+
+impl PartialAggregationCache {
+    /// Loads the cached split results of `search_request`. Returns `None` if the cache is
+    /// disabled, if the results of the request cannot be cached, or if the entry could not be
+    /// loaded in time.
+    pub async fn load(
+        searcher_config: &SearcherConfig,
+        search_request: &SearchRequest,
+        cluster_client: &ClusterClient,
+    ) -> Option<PartialAggregationCache> {
+        if !searcher_config.enable_partial_aggregation_cache
+            || !is_request_cacheable(search_request)
+        {
+            return None;
+        }
+        let key = partial_aggregation_cache_key(search_request);
+        let get_kv_fut = cluster_client.get_kv(&key);
+        let Ok(payload_opt) =
+            tokio::time::timeout(PARTIAL_AGGREGATION_CACHE_LOAD_TIMEOUT, get_kv_fut).await
+        else {
+            debug!("loading partial aggregation cache entry timed out");
+            return None;
+        };
+        let split_results: Vec<SplitAggregationResult> = match payload_opt {
+            Some(payload) => postcard::from_bytes(&payload).unwrap_or_else(|error| {
+                debug!(error=?error, "failed to deserialize partial aggregation cache entry");
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+        let split_results = split_results
+            .into_iter()
+            .map(|split_result| (split_result.split_id.clone(), split_result))
+            .collect();
+        Some(PartialAggregationCache {
+            key,
+            split_results,
+            is_modified: false,
+        })
+    }
+
+    /// Returns the cached result of `search_request` on `split`, as a leaf response.
+    pub fn get(
+        &self,
+        search_request: &SearchRequest,
+        split: &SplitIdAndFooterOffsets,
+    ) -> Option<LeafSearchResponse> {
+        if !is_split_aggregation_result_cacheable(search_request, split) {
+            return None;
+        }
+        let split_result = self.split_results.get(&split.split_id)?;
+        Some(LeafSearchResponse {
+            num_hits: split_result.num_hits,
+            intermediate_aggregation_result: split_result.intermediate_aggregation_result.clone(),
+            num_attempted_splits: 1,
+            ..Default::default()
+        })
+    }
+
+    /// Adds the split results returned by the leaves to the cache.
+    pub fn insert(&mut self, split_results: Vec<SplitAggregationResult>) {
+        for split_result in split_results {
+            self.split_results
+                .insert(split_result.split_id.clone(), split_result);
+            self.is_modified = true;
+        }
+    }
+
+    /// Evicts the results of the splits that were not part of the request, because they were
+    /// merged, deleted, or left the time window of the request.
+    pub fn retain_splits(&mut self, split_ids: &HashSet<&str>) {
+        let num_split_results = self.split_results.len();
+        self.split_results
+            .retain(|split_id, _| split_ids.contains(split_id.as_str()));
+        self.is_modified |= self.split_results.len() != num_split_results;
+    }
+
+    /// Stores the cache back in the cluster KV store, if it was modified.
+    pub async fn store(self, cluster_client: ClusterClient) {
+        if !self.is_modified {
+            return;
+        }
+        let split_results: Vec<SplitAggregationResult> = self.split_results.into_values().collect();
+        let payload = match postcard::to_allocvec(&split_results) {
+            Ok(payload) => payload,
+            Err(error) => {
+                debug!(error=?error, "failed to serialize partial aggregation cache entry");
+                return;
+            }
+        };
+        if payload.len() > MAX_PARTIAL_AGGREGATION_CACHE_ENTRY_NUM_BYTES {
+            debug!(
+                num_bytes = payload.len(),
+                "partial aggregation cache entry is too large to be stored"
+            );
+            return;
+        }
+        cluster_client
+            .put_kv(&self.key, &payload, PARTIAL_AGGREGATION_CACHE_TTL)
+            .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(split_id: &str, time_range: Option<(i64, i64)>) -> SplitIdAndFooterOffsets {
+        SplitIdAndFooterOffsets {
+            split_id: split_id.to_string(),
+            timestamp_start: time_range.map(|(start, _)| start),
+            timestamp_end: time_range.map(|(_, end)| end),
+            ..Default::default()
+        }
+    }
+
+    fn aggregation_request(time_range: Option<(i64, i64)>) -> SearchRequest {
+        SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            aggregation_request: Some(r#"{"count": {"value_count": {"field": "ts"}}}"#.to_string()),
+            max_hits: 0,
+            start_timestamp: time_range.map(|(start, _)| start),
+            end_timestamp: time_range.map(|(_, end)| end),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_is_split_aggregation_result_cacheable() {
+        let search_request = aggregation_request(Some((10, 20)));
+        assert!(is_split_aggregation_result_cacheable(
+            &search_request,
+            &split("split", Some((10, 19)))
+        ));
+        assert!(!is_split_aggregation_result_cacheable(
+            &search_request,
+            &split("split", Some((10, 20)))
+        ));
+        assert!(!is_split_aggregation_result_cacheable(
+            &search_request,
+            &split("split", Some((9, 15)))
+        ));
+        assert!(!is_split_aggregation_result_cacheable(
+            &search_request,
+            &split("split", None)
+        ));
+        let search_request = aggregation_request(None);
+        assert!(is_split_aggregation_result_cacheable(
+            &search_request,
+            &split("split", Some((10, 20)))
+        ));
+        assert!(is_split_aggregation_result_cacheable(
+            &search_request,
+            &split("split", None)
+        ));
+        let search_request = SearchRequest {
+            max_hits: 10,
+            ..aggregation_request(None)
+        };
+        assert!(!is_split_aggregation_result_cacheable(
+            &search_request,
+            &split("split", None)
+        ));
+        let search_request = SearchRequest {
+            aggregation_request: None,
+            ..aggregation_request(None)
+        };
+        assert!(!is_split_aggregation_result_cacheable(
+            &search_request,
+            &split("split", None)
+        ));
+        let search_request = SearchRequest {
+            aggregation_request: Some(r#"{"latest": {"top_hits": {"size": 1}}}"#.to_string()),
+            ..aggregation_request(None)
+        };
+        assert!(!is_split_aggregation_result_cacheable(
+            &search_request,
+            &split("split", None)
+        ));
+    }
+
+    #[test]
+    fn test_partial_aggregation_cache_key_ignores_time_range() {
+        assert_eq!(
+            partial_aggregation_cache_key(&aggregation_request(Some((10, 20)))),
+            partial_aggregation_cache_key(&aggregation_request(Some((15, 30))))
+        );
+        let other_search_request = SearchRequest {
+            aggregation_request: Some(r#"{"count": {"value_count": {"field": "id"}}}"#.to_string()),
+            ..aggregation_request(Some((10, 20)))
+        };
+        assert_ne!(
+            partial_aggregation_cache_key(&aggregation_request(Some((10, 20)))),
+            partial_aggregation_cache_key(&other_search_request)
+        );
+    }
+
+    #[test]
+    fn test_partial_aggregation_cache() {
+        let search_request = aggregation_request(Some((10, 30)));
+        let mut cache = PartialAggregationCache {
+            key: partial_aggregation_cache_key(&search_request),
+            split_results: HashMap::new(),
+            is_modified: false,
+        };
+        let split_1 = split("split_1", Some((10, 19)));
+        let split_2 = split("split_2", Some((20, 29)));
+        assert!(cache.get(&search_request, &split_1).is_none());
+
+        cache.insert(vec![
+            SplitAggregationResult {
+                split_id: "split_1".to_string(),
+                num_hits: 3,
+                intermediate_aggregation_result: Some(b"split_1".to_vec()),
+            },
+            SplitAggregationResult {
+                split_id: "split_2".to_string(),
+                num_hits: 5,
+                intermediate_aggregation_result: Some(b"split_2".to_vec()),
+            },
+        ]);
+        assert!(cache.is_modified);
+
+        let leaf_response = cache.get(&search_request, &split_1).unwrap();
+        assert_eq!(leaf_response.num_hits, 3);
+        assert_eq!(leaf_response.num_attempted_splits, 1);
+        assert_eq!(
+            leaf_response.intermediate_aggregation_result.as_deref(),
+            Some(&b"split_1"[..])
+        );
+        // The time range of the request no longer covers the second split.
+        let search_request = aggregation_request(Some((10, 25)));
+        assert!(cache.get(&search_request, &split_2).is_none());
+
+        cache.is_modified = false;
+        cache.retain_splits(&HashSet::from(["split_1", "split_2"]));
+        assert!(!cache.is_modified);
+        cache.retain_splits(&HashSet::from(["split_2"]));
+        assert!(cache.is_modified);
+        assert!(cache.get(&search_request, &split_1).is_none());
+    }
+    #[test]
+    fn test_split_aggregation_results_size_limits() {
+        let split_response = |num_bytes: usize| LeafSearchResponse {
+            num_hits: 1,
+            intermediate_aggregation_result: Some(vec![0; num_bytes]),
+            ..Default::default()
+        };
+        let split_1 = split("split_1", None);
+        let split_result =
+            split_aggregation_result_to_cache(&split_1, &split_response(1_000)).unwrap();
+        assert_eq!(split_result.split_id, "split_1");
+        assert_eq!(split_result.num_hits, 1);
+        assert!(split_aggregation_result_to_cache(
+            &split_1,
+            &split_response(MAX_SPLIT_AGGREGATION_RESULT_NUM_BYTES + 1)
+        )
+        .is_none());
+
+        let split_results: Vec<SplitAggregationResult> = (0..5)
+            .map(|split_ord| {
+                split_aggregation_result_to_cache(
+                    &split(&format!("split_{split_ord}"), None),
+                    &split_response(MAX_SPLIT_AGGREGATION_RESULT_NUM_BYTES),
+                )
+                .unwrap()
+            })
+            .collect();
+        let mut dst = Vec::new();
+        extend_split_aggregation_results(&mut dst, split_results.clone());
+        assert_eq!(dst.len(), 4);
+        extend_split_aggregation_results(&mut dst, split_results);
+        assert_eq!(dst.len(), 4);
+    }
+}
@@ -17,11 +17,15 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::cmp::Reverse;
 use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::ops::RangeInclusive;
 use std::time::Duration;
 
 use anyhow::Context;
 use futures::future::try_join_all;
+use futures::stream::{FuturesUnordered, StreamExt};
 use itertools::Itertools;
 use quickwit_common::shared_consts::{DELETION_GRACE_PERIOD, SCROLL_BATCH_LEN};
 use quickwit_common::uri::Uri;
@@ -35,30 +39,37 @@ use quickwit_proto::metastore::{
 };
 use quickwit_proto::search::{
     FetchDocsRequest, FetchDocsResponse, Hit, LeafHit, LeafSearchRequest, LeafSearchResponse,
-    PartialHit, SearchRequest, SearchResponse, SnippetRequest, SortDatetimeFormat, SortField,
-    SortValue, SplitIdAndFooterOffsets,
+    PartialHit, SearchProfile, SearchRequest, SearchResponse, SnippetRequest, SortDatetimeFormat,
+    SortField, SortValue, SplitIdAndFooterOffsets, SplitSearchProfile,
 };
 use quickwit_proto::types::{IndexUid, SplitId};
+use quickwit_query::find_field_or_hit_dynamic;
 use quickwit_query::query_ast::{
     BoolQuery, QueryAst, QueryAstVisitor, RangeQuery, TermQuery, TermSetQuery,
 };
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
 use tantivy::aggregation::agg_result::AggregationResults;
 use tantivy::aggregation::intermediate_agg_result::IntermediateAggregationResults;
 use tantivy::collector::Collector;
 use tantivy::schema::{FieldEntry, FieldType, Schema};
 use tantivy::TantivyError;
-use tracing::{debug, error, info, info_span, instrument};
+use tokio::sync::watch;
+use tokio::time::Instant;
+use tracing::{debug, error, info, info_span, instrument, warn};
 
 use crate::cluster_client::ClusterClient;
 use crate::collector::{make_merge_collector, QuickwitAggregations};
+use crate::fetch_docs::SNIPPET_FIELDS_WILDCARD;
 use crate::find_trace_ids_collector::Span;
+use crate::partial_aggregation_cache::PartialAggregationCache;
 use crate::scroll_context::{ScrollContext, ScrollKeyAndStartOffset};
 use crate::search_job_placer::Job;
 use crate::service::SearcherContext;
+use crate::top_hits::{parse_aggregation_request, TopHitsAggregation};
 use crate::{
-    extract_split_and_footer_offsets, list_relevant_splits, SearchError, SearchJobPlacer,
-    SearchServiceClient,
+    extract_split_and_footer_offsets, list_relevant_splits, GlobalDocAddress, SearchError,
+    SearchJobPlacer, SearchServiceClient,
 };
 
 /// Maximum accepted scroll TTL.
@@ -66,12 +77,20 @@ const MAX_SCROLL_TTL: Duration = Duration::from_secs(DELETION_GRACE_PERIOD.as_se
 
 const SORT_DOC_FIELD_NAMES: &[&str] = &["_shard_doc", "_doc"];
 
+/// Time given to leaves past the search deadline to return their partial results.
+const LEAF_SEARCH_TIMEOUT_GRACE_PERIOD: Duration = Duration::from_millis(500);
+
+/// Channel on which a root search publishes its partial results. The documents are only fetched
+/// at the end of the search, so partial results only carry the hit count and the aggregations.
+pub(crate) type SearchProgressSender = watch::Sender<Option<SearchResponse>>;
+
 /// SearchJob to be assigned to search clients by the [`SearchJobPlacer`].
 #[derive(Debug, Clone, PartialEq)]
 pub struct SearchJob {
     /// The index UID.
     pub index_uid: IndexUid,
     cost: usize,
+    affinity_miss_cost: usize,
     /// The split ID and footer offsets of the split.
     pub offsets: SplitIdAndFooterOffsets,
 }
@@ -82,6 +101,7 @@ impl SearchJob {
         SearchJob {
             index_uid: IndexUid::from("test-index:0"),
             cost,
+            affinity_miss_cost: 0,
             offsets: SplitIdAndFooterOffsets {
                 split_id: split_id.to_string(),
                 ..Default::default()
@@ -101,6 +121,7 @@ impl<'a> From<&'a SplitMetadata> for SearchJob {
         SearchJob {
             index_uid: split_metadata.index_uid.clone(),
             cost: compute_split_cost(split_metadata),
+            affinity_miss_cost: compute_split_affinity_miss_cost(split_metadata),
             offsets: extract_split_and_footer_offsets(split_metadata),
         }
     }
@@ -114,6 +135,10 @@ impl Job for SearchJob {
     fn cost(&self) -> usize {
         self.cost
     }
+
+    fn affinity_miss_cost(&self) -> usize {
+        self.affinity_miss_cost
+    }
 }
 
 pub struct FetchDocsJob {
@@ -181,6 +206,7 @@ fn validate_request_and_build_metadata(
     let mut query_ast_resolved_opt: Option<QueryAst> = None;
     let mut timestamp_field_opt: Option<String> = None;
     let mut sort_fields_is_datetime: HashMap<String, bool> = HashMap::new();
+    let top_hits_opt = top_hits_aggregation(search_request)?;
 
     for index_metadata in indexes_metadata {
         let doc_mapper = build_doc_mapper(
@@ -235,6 +261,10 @@ fn validate_request_and_build_metadata(
             &mut sort_fields_is_datetime,
         )?;
 
+        if let Some(top_hits) = &top_hits_opt {
+            validate_top_hits_aggregation(&schema, top_hits, &mut sort_fields_is_datetime)?;
+        }
+
         // Validates the query by effectively building it against the current schema.
         doc_mapper.query(doc_mapper.schema(), &query_ast_resolved_for_index, true)?;
 
@@ -302,9 +332,22 @@ fn validate_requested_snippet_fields(
     snippet_fields: &[String],
 ) -> anyhow::Result<()> {
     for field_name in snippet_fields {
-        let field_entry = schema
-            .get_field(field_name)
-            .map(|field| schema.get_field_entry(field))?;
+        // The wildcard is expanded by the leaves, into the fields of the splits they search.
+        if field_name == SNIPPET_FIELDS_WILDCARD {
+            continue;
+        }
+        // Snippets can also be extracted on the text values found at a path of a json field or
+        // of the dynamic field.
+        let (field_entry, json_path) = match schema.get_field(field_name) {
+            Ok(field) => (schema.get_field_entry(field), ""),
+            Err(field_not_found_error) => {
+                let Ok((_, field_entry, json_path)) = find_field_or_hit_dynamic(field_name, schema)
+                else {
+                    return Err(field_not_found_error.into());
+                };
+                (field_entry, json_path)
+            }
+        };
         match field_entry.field_type() {
             FieldType::Str(text_options) => {
                 if !text_options.is_stored() {
@@ -314,6 +357,20 @@ fn validate_requested_snippet_fields(
                     ));
                 }
             }
+            FieldType::JsonObject(json_options) if !json_path.is_empty() => {
+                if !json_options.is_stored() {
+                    return Err(anyhow::anyhow!(
+                        "the snippet field `{}` must be stored",
+                        field_name
+                    ));
+                }
+                if json_options.get_text_indexing_options().is_none() {
+                    return Err(anyhow::anyhow!(
+                        "the snippet field `{}` must be indexed",
+                        field_name
+                    ));
+                }
+            }
             other => {
                 return Err(anyhow::anyhow!(
                     "the snippet field `{}` must be of type `Str`, got `{}`",
@@ -351,12 +408,15 @@ fn simplify_search_request_for_scroll_api(req: &SearchRequest) -> crate::Result<
         scroll_ttl_secs: None,
         search_after: None,
         count_hits: req.count_hits,
+        timeout_millis: None,
+        profile: false,
+        snippet_options: None,
+        collapse: None,
     })
 }
 
 /// Validates sort fields and search after values.
-/// - validate sort fields length.
-/// - search after values must be set for all sort fields.
+/// - search after values must be set for all sort fields but `_doc`.
 fn validate_sort_by_fields_and_search_after(
     sort_fields: &[SortField],
     search_after: &Option<PartialHit>,
@@ -364,20 +424,11 @@ fn validate_sort_by_fields_and_search_after(
     if sort_fields.is_empty() {
         return Ok(());
     }
-    if sort_fields.len() > 2 {
-        return Err(SearchError::InvalidArgument(format!(
-            "sort by field must be up to 2 fields, got {}",
-            sort_fields.len()
-        )));
-    }
     let Some(search_after_partial_hit) = search_after.as_ref() else {
         return Ok(());
     };
 
-    let sort_fields_without_doc_count = sort_fields
-        .iter()
-        .filter(|sort_field| !SORT_DOC_FIELD_NAMES.contains(&sort_field.field_name.as_str()))
-        .count();
+    let sort_fields_without_doc_count = sort_fields_without_doc(sort_fields).count();
     let has_doc_sort_field = sort_fields_without_doc_count != sort_fields.len();
     if has_doc_sort_field && search_after_partial_hit.split_id.is_empty() {
         return Err(SearchError::InvalidArgument(
@@ -387,20 +438,10 @@ fn validate_sort_by_fields_and_search_after(
         ));
     }
 
-    let mut search_after_sort_value_count = 0;
     // TODO: we could validate if the search after sort value types of consistent with the sort
     // field types.
-    if let Some(sort_by_value) = search_after_partial_hit.sort_value.as_ref() {
-        sort_by_value.sort_value.context("sort value must be set")?;
-        search_after_sort_value_count += 1;
-    }
-    if let Some(sort_by_value_2) = search_after_partial_hit.sort_value2.as_ref() {
-        sort_by_value_2
-            .sort_value
-            .context("sort value must be set")?;
-        search_after_sort_value_count += 1;
-    }
-    if search_after_sort_value_count != sort_fields_without_doc_count {
+    // A missing sort value is valid: it matches the hits without a value for the sort field.
+    if search_after_partial_hit.sort_values.len() != sort_fields_without_doc_count {
         return Err(SearchError::InvalidArgument(format!(
             "`search_after` must have the same number of sort values as sort by fields {:?}",
             sort_fields
@@ -412,6 +453,14 @@ fn validate_sort_by_fields_and_search_after(
     Ok(())
 }
 
+/// Returns the sort fields yielding a sort value, that is, all the sort fields but `_doc`. Those
+/// are the sort fields matching `PartialHit::sort_values`.
+fn sort_fields_without_doc(sort_fields: &[SortField]) -> impl Iterator<Item = &SortField> {
+    sort_fields
+        .iter()
+        .filter(|sort_field| !SORT_DOC_FIELD_NAMES.contains(&sort_field.field_name.as_str()))
+}
+
 fn get_sort_by_field_entry<'a>(
     field_name: &str,
     schema: &'a Schema,
@@ -435,11 +484,6 @@ fn validate_sort_by_field_type(
     has_timestamp_format: bool,
 ) -> crate::Result<()> {
     let field_name = sort_by_field_entry.name();
-    if matches!(sort_by_field_entry.field_type(), FieldType::Str(_)) {
-        return Err(SearchError::InvalidArgument(format!(
-            "sort by field on type text is currently not supported `{field_name}`"
-        )));
-    }
     if !sort_by_field_entry.is_fast() {
         return Err(SearchError::InvalidArgument(format!(
             "sort by field must be a fast field, please add the fast property to your field \
@@ -455,6 +499,63 @@ fn validate_sort_by_field_type(
     Ok(())
 }
 
+/// Returns whether the field is a fast text field, or `None` if the field does not exist.
+fn is_fast_text_field(schema: &Schema, field_name: &str) -> Option<bool> {
+    let dynamic_field_opt = schema.get_field(DYNAMIC_FIELD_NAME).ok();
+    let (field, _json_path) = schema.find_field_with_default(field_name, dynamic_field_opt)?;
+    let field_entry = schema.get_field_entry(field);
+    let is_text_field = matches!(
+        field_entry.field_type(),
+        FieldType::Str(_) | FieldType::JsonObject(_)
+    );
+    Some(is_text_field && field_entry.is_fast())
+}
+
+/// Validates that hits can be collapsed on the given field, that is, a fast text field.
+fn validate_collapse_field(schema: &Schema, field_name: &str) -> crate::Result<()> {
+    let is_fast_text_field = is_fast_text_field(schema, field_name).ok_or_else(|| {
+        SearchError::InvalidArgument(format!("unknown field used in `collapse`: {field_name}"))
+    })?;
+    if !is_fast_text_field {
+        return Err(SearchError::InvalidArgument(format!(
+            "collapse field must be a fast text field, and the field `{field_name}` is not"
+        )));
+    }
+    Ok(())
+}
+
+/// Validates the sort fields of a `top_hits` aggregation and the field of its parent `terms`
+/// aggregation, if any, which must be a fast text field.
+fn validate_top_hits_aggregation(
+    schema: &Schema,
+    top_hits: &TopHitsAggregation,
+    sort_fields_is_datetime: &mut HashMap<String, bool>,
+) -> crate::Result<()> {
+    validate_sort_field_types(schema, &top_hits.sort_fields, sort_fields_is_datetime)?;
+
+    if let Some(terms) = &top_hits.terms_opt {
+        if is_fast_text_field(schema, &terms.field_name) == Some(false) {
+            return Err(SearchError::InvalidAggregationRequest(format!(
+                "the parent `terms` aggregation of a `top_hits` aggregation must be on a fast \
+                 text field, and the field `{}` is not",
+                terms.field_name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the `top_hits` aggregation of the request, if any.
+fn top_hits_aggregation(
+    search_request: &SearchRequest,
+) -> crate::Result<Option<TopHitsAggregation>> {
+    let Some(aggregation_request) = &search_request.aggregation_request else {
+        return Ok(None);
+    };
+    let (_aggregations_opt, top_hits_opt) = parse_aggregation_request(aggregation_request)?;
+    Ok(top_hits_opt)
+}
+
 fn validate_request(
     schema: &Schema,
     timestamp_field_name: &Option<&str>,
@@ -472,13 +573,29 @@ fn validate_request(
 
     validate_requested_snippet_fields(schema, &search_request.snippet_fields)?;
 
-    if let Some(agg) = search_request.aggregation_request.as_ref() {
-        let _aggs: QuickwitAggregations = serde_json::from_str(agg).map_err(|_err| {
-            let err = serde_json::from_str::<tantivy::aggregation::agg_req::Aggregations>(agg)
-                .unwrap_err();
-            SearchError::InvalidAggregationRequest(err.to_string())
-        })?;
-    };
+    if let Some(snippet_options) = &search_request.snippet_options {
+        // A number of fragments of 0 is valid: the whole field values are highlighted.
+        if snippet_options.fragment_size == Some(0) {
+            return Err(SearchError::InvalidArgument(
+                "snippet fragment size must be strictly positive".to_string(),
+            ));
+        }
+    }
+
+    if let Some(collapse) = &search_request.collapse {
+        validate_collapse_field(schema, &collapse.field_name)?;
+        if search_request.scroll_ttl_secs.is_some() {
+            return Err(SearchError::InvalidArgument(
+                "collapse cannot be used in a scroll context".to_string(),
+            ));
+        }
+    }
+
+    if top_hits_aggregation(search_request)?.is_some() && search_request.scroll_ttl_secs.is_some() {
+        return Err(SearchError::InvalidArgument(
+            "`top_hits` aggregations cannot be used in a scroll context".to_string(),
+        ));
+    }
 
     if search_request.start_offset > 10_000 {
         return Err(SearchError::InvalidArgument(format!(
@@ -517,7 +634,9 @@ async fn search_partial_hits_phase_with_scroll(
     indexes_metas_for_leaf_search: &IndexesMetasForLeafSearch,
     mut search_request: SearchRequest,
     split_metadatas: &[SplitMetadata],
+    timestamp_field_opt: Option<&str>,
     cluster_client: &ClusterClient,
+    progress_tx_opt: Option<&SearchProgressSender>,
 ) -> crate::Result<(LeafSearchResponse, Option<ScrollKeyAndStartOffset>)> {
     let scroll_ttl_opt = get_scroll_ttl_duration(&search_request)?;
 
@@ -528,12 +647,17 @@ async fn search_partial_hits_phase_with_scroll(
         // We increase max hits to add populate the scroll cache.
         search_request.max_hits = SCROLL_BATCH_LEN as u64;
         search_request.scroll_ttl_secs = None;
+        // The scroll context caches the hits of the first page, so partial results would leak
+        // into the following pages. Scroll searches always run to completion.
+        search_request.timeout_millis = None;
         let mut leaf_search_resp = search_partial_hits_phase(
             searcher_context,
             indexes_metas_for_leaf_search,
             &search_request,
             split_metadatas,
+            timestamp_field_opt,
             cluster_client,
+            progress_tx_opt,
         )
         .await?;
         let cached_partial_hits = leaf_search_resp.partial_hits.clone();
@@ -569,7 +693,9 @@ async fn search_partial_hits_phase_with_scroll(
             indexes_metas_for_leaf_search,
             &search_request,
             split_metadatas,
+            timestamp_field_opt,
             cluster_client,
+            progress_tx_opt,
         )
         .await?;
         Ok((leaf_search_resp, None))
@@ -582,23 +708,171 @@ pub(crate) async fn search_partial_hits_phase(
     indexes_metas_for_leaf_search: &IndexesMetasForLeafSearch,
     search_request: &SearchRequest,
     split_metadatas: &[SplitMetadata],
+    timestamp_field_opt: Option<&str>,
     cluster_client: &ClusterClient,
+    progress_tx_opt: Option<&SearchProgressSender>,
 ) -> crate::Result<LeafSearchResponse> {
-    let jobs: Vec<SearchJob> = split_metadatas.iter().map(SearchJob::from).collect();
+    // The splits whose results are cached are not searched again.
+    let mut partial_aggregation_cache_opt = PartialAggregationCache::load(
+        &searcher_context.searcher_config,
+        search_request,
+        cluster_client,
+    )
+    .await;
+    let mut cached_leaf_search_responses: Vec<LeafSearchResponse> = Vec::new();
+    // Neither are the splits whose number of matching documents is known from their metadata.
+    let metadata_count_query_ast_opt = metadata_count_query_ast(search_request)?;
+    let mut metadata_count_leaf_search_responses: Vec<LeafSearchResponse> = Vec::new();
+    let mut jobs: Vec<SearchJob> = Vec::with_capacity(split_metadatas.len());
+    for split_metadata in split_metadatas {
+        let num_docs_opt = metadata_count_query_ast_opt.as_ref().and_then(|query_ast| {
+            count_from_split_metadata(
+                search_request,
+                query_ast,
+                timestamp_field_opt,
+                split_metadata,
+            )
+        });
+        if let Some(num_docs) = num_docs_opt {
+            metadata_count_leaf_search_responses.push(LeafSearchResponse {
+                num_hits: num_docs,
+                num_attempted_splits: 1,
+                ..Default::default()
+            });
+            continue;
+        }
+        let job = SearchJob::from(split_metadata);
+        let cached_leaf_search_response_opt = partial_aggregation_cache_opt
+            .as_ref()
+            .and_then(|cache| cache.get(search_request, &job.offsets));
+        if let Some(cached_leaf_search_response) = cached_leaf_search_response_opt {
+            cached_leaf_search_responses.push(cached_leaf_search_response);
+        } else {
+            jobs.push(job);
+        }
+    }
+    crate::SEARCH_METRICS
+        .root_partial_aggregation_cache_hits_total
+        .inc_by(cached_leaf_search_responses.len() as u64);
+    debug!(
+        num_splits = metadata_count_leaf_search_responses.len(),
+        "Counted documents from split metadata."
+    );
+    cached_leaf_search_responses.extend(metadata_count_leaf_search_responses);
+    let split_costs: HashMap<String, usize> = jobs
+        .iter()
+        .map(|job| (job.split_id().to_string(), job.cost()))
+        .collect();
     let assigned_leaf_search_jobs = cluster_client
         .search_job_placer
         .assign_jobs(jobs, &HashSet::default())
         .await?;
+    // Leaves return their partial results at the deadline. We give them a bit of slack to
+    // answer before considering all the splits they were assigned as skipped.
+    let leaf_deadline_opt: Option<Instant> = search_request.timeout_millis.map(|timeout_millis| {
+        Instant::now() + Duration::from_millis(timeout_millis) + LEAF_SEARCH_TIMEOUT_GRACE_PERIOD
+    });
     let mut leaf_request_tasks = Vec::new();
     for (client, client_jobs) in assigned_leaf_search_jobs {
         let leaf_requests =
             jobs_to_leaf_requests(search_request, indexes_metas_for_leaf_search, client_jobs)?;
         for leaf_request in leaf_requests {
-            leaf_request_tasks.push(cluster_client.leaf_search(leaf_request, client.clone()));
+            let split_ids: Vec<String> = leaf_request
+                .split_offsets
+                .iter()
+                .map(|split_offsets| split_offsets.split_id.clone())
+                .collect();
+            let leaf_request_cost: usize = split_ids
+                .iter()
+                .filter_map(|split_id| split_costs.get(split_id))
+                .sum();
+            let grpc_addr = client.grpc_addr();
+            let search_job_placer = &cluster_client.search_job_placer;
+            let leaf_search_fut = cluster_client.leaf_search(leaf_request, client.clone());
+            leaf_request_tasks.push(async move {
+                let start_instant = Instant::now();
+                let leaf_search_result = match leaf_deadline_opt {
+                    Some(leaf_deadline) => tokio::time::timeout_at(leaf_deadline, leaf_search_fut)
+                        .await
+                        .unwrap_or_else(|_elapsed| {
+                            warn!(
+                                num_skipped_splits = split_ids.len(),
+                                "leaf search did not answer before the deadline"
+                            );
+                            Ok(LeafSearchResponse {
+                                skipped_split_ids: split_ids,
+                                ..Default::default()
+                            })
+                        }),
+                    None => leaf_search_fut.await,
+                };
+                // The observed latencies are fed back to the job placer, which steers jobs away
+                // from slow nodes. Failed and timed out requests are recorded too, so that a
+                // node that errors or hangs is not treated as fast.
+                search_job_placer.record_node_latency(
+                    grpc_addr,
+                    leaf_request_cost,
+                    start_instant.elapsed(),
+                );
+                leaf_search_result
+            });
         }
     }
-    let leaf_search_responses: Vec<LeafSearchResponse> = try_join_all(leaf_request_tasks).await?;
+    let mut leaf_search_responses: Vec<LeafSearchResponse> = match progress_tx_opt {
+        Some(progress_tx) => {
+            collect_leaf_search_responses_with_progress(
+                searcher_context,
+                search_request,
+                cached_leaf_search_responses,
+                leaf_request_tasks,
+                progress_tx,
+            )
+            .await?
+        }
+        None => {
+            let mut leaf_search_responses = try_join_all(leaf_request_tasks).await?;
+            leaf_search_responses.extend(cached_leaf_search_responses);
+            leaf_search_responses
+        }
+    };
+    if let Some(mut partial_aggregation_cache) = partial_aggregation_cache_opt.take() {
+        for leaf_search_response in &mut leaf_search_responses {
+            partial_aggregation_cache.insert(std::mem::take(
+                &mut leaf_search_response.split_aggregation_results,
+            ));
+        }
+        let split_ids: HashSet<&str> = split_metadatas
+            .iter()
+            .map(|split_metadata| split_metadata.split_id())
+            .collect();
+        partial_aggregation_cache.retain_splits(&split_ids);
+        // The cache is stored in the background, so as not to delay the response.
+        tokio::spawn(partial_aggregation_cache.store(cluster_client.clone()));
+    }
+    let leaf_search_response =
+        merge_leaf_search_responses(searcher_context, search_request, leaf_search_responses)
+            .await?;
+    debug!(
+        num_hits = leaf_search_response.num_hits,
+        failed_splits = ?leaf_search_response.failed_splits,
+        num_attempted_splits = leaf_search_response.num_attempted_splits,
+        num_skipped_splits = leaf_search_response.skipped_split_ids.len(),
+        has_intermediate_aggregation_result = leaf_search_response.intermediate_aggregation_result.is_some(),
+        "Merged leaf search response."
+    );
+    if !leaf_search_response.failed_splits.is_empty() {
+        error!(failed_splits = ?leaf_search_response.failed_splits, "leaf search response contains at least one failed split");
+        let errors: String = leaf_search_response.failed_splits.iter().join(", ");
+        return Err(SearchError::Internal(errors));
+    }
+    Ok(leaf_search_response)
+}
 
+async fn merge_leaf_search_responses(
+    searcher_context: &SearcherContext,
+    search_request: &SearchRequest,
+    leaf_search_responses: Vec<LeafSearchResponse>,
+) -> crate::Result<LeafSearchResponse> {
     // Creates a collector which merges responses into one
     let merge_collector =
         make_merge_collector(search_request, &searcher_context.get_aggregation_limits())?;
@@ -617,21 +891,65 @@ pub(crate) async fn search_partial_hits_phase(
     .await
     .context("failed to merge leaf search responses")?
     .map_err(|error: TantivyError| crate::SearchError::Internal(error.to_string()))?;
-    debug!(
-        num_hits = leaf_search_response.num_hits,
-        failed_splits = ?leaf_search_response.failed_splits,
-        num_attempted_splits = leaf_search_response.num_attempted_splits,
-        has_intermediate_aggregation_result = leaf_search_response.intermediate_aggregation_result.is_some(),
-        "Merged leaf search response."
-    );
-    if !leaf_search_response.failed_splits.is_empty() {
-        error!(failed_splits = ?leaf_search_response.failed_splits, "leaf search response contains at least one failed split");
-        let errors: String = leaf_search_response.failed_splits.iter().join(", ");
-        return Err(SearchError::Internal(errors));
-    }
     Ok(leaf_search_response)
 }
 
+/// Awaits the leaf requests in the order they complete, and publishes the results merged so far
+/// after each of them. The responses already available, typically coming from a cache, are
+/// merged first.
+async fn collect_leaf_search_responses_with_progress<F>(
+    searcher_context: &SearcherContext,
+    search_request: &SearchRequest,
+    mut leaf_search_responses: Vec<LeafSearchResponse>,
+    leaf_request_tasks: Vec<F>,
+    progress_tx: &SearchProgressSender,
+) -> crate::Result<Vec<LeafSearchResponse>>
+where
+    F: Future<Output = crate::Result<LeafSearchResponse>>,
+{
+    leaf_search_responses.reserve(leaf_request_tasks.len());
+    let mut leaf_request_tasks: FuturesUnordered<F> = leaf_request_tasks.into_iter().collect();
+    let mut merged_leaf_search_response_opt: Option<LeafSearchResponse> =
+        if leaf_search_responses.is_empty() {
+            None
+        } else {
+            let merged_leaf_search_response = merge_leaf_search_responses(
+                searcher_context,
+                search_request,
+                leaf_search_responses.clone(),
+            )
+            .await?;
+            Some(merged_leaf_search_response)
+        };
+
+    while let Some(leaf_search_result) = leaf_request_tasks.next().await {
+        let leaf_search_response = leaf_search_result?;
+        let mut responses_to_merge = vec![leaf_search_response.clone()];
+        responses_to_merge.extend(merged_leaf_search_response_opt.take());
+        let merged_leaf_search_response =
+            merge_leaf_search_responses(searcher_context, search_request, responses_to_merge)
+                .await?;
+        let aggregation = finalize_aggregation_if_any(
+            search_request,
+            merged_leaf_search_response
+                .intermediate_aggregation_result
+                .clone(),
+            searcher_context,
+        )?;
+        // The documents are only fetched once all the leaves have answered, so partial responses
+        // only carry the hit count and the aggregations.
+        let partial_search_response = SearchResponse {
+            num_hits: merged_leaf_search_response.num_hits,
+            aggregation,
+            ..Default::default()
+        };
+        progress_tx.send_replace(Some(partial_search_response));
+        merged_leaf_search_response_opt = Some(merged_leaf_search_response);
+        leaf_search_responses.push(leaf_search_response);
+    }
+    Ok(leaf_search_responses)
+}
+
 pub(crate) fn get_snippet_request(search_request: &SearchRequest) -> Option<SnippetRequest> {
     if search_request.snippet_fields.is_empty() {
         return None;
@@ -639,6 +957,7 @@ pub(crate) fn get_snippet_request(search_request: &SearchRequest) -> Option<Snip
     Some(SnippetRequest {
         snippet_fields: search_request.snippet_fields.clone(),
         query_ast_resolved: search_request.query_ast.clone(),
+        snippet_options: search_request.snippet_options.clone(),
     })
 }
 
@@ -700,19 +1019,17 @@ pub(crate) async fn fetch_docs_phase(
             )
         })
         .collect();
-    let mut sort_field_iter = search_request.sort_fields.iter();
-    let sort_field_1_datetime_format_opt: Option<SortDatetimeFormat> =
-        get_sort_field_datetime_format(sort_field_iter.next())?;
-    let sort_field_2_datetime_format_opt: Option<SortDatetimeFormat> =
-        get_sort_field_datetime_format(sort_field_iter.next())?;
+    let sort_fields_datetime_format_opt: Vec<Option<SortDatetimeFormat>> =
+        sort_fields_without_doc(&search_request.sort_fields)
+            .map(get_sort_field_datetime_format)
+            .try_collect()?;
     let mut hits_with_position: Vec<(usize, Hit)> = leaf_hits
         .map(|leaf_hit| {
             build_hit_with_position(
                 leaf_hit,
                 &split_id_to_index_id_map,
                 &hit_order,
-                &sort_field_1_datetime_format_opt,
-                &sort_field_2_datetime_format_opt,
+                &sort_fields_datetime_format_opt,
             )
         })
         .try_collect()?;
@@ -730,8 +1047,7 @@ fn build_hit_with_position(
     mut leaf_hit: LeafHit,
     split_id_to_index_id_map: &HashMap<&SplitId, &str>,
     hit_order: &HashMap<(String, u32, u32), usize>,
-    sort_field_1_datetime_format_opt: &Option<SortDatetimeFormat>,
-    sort_field_2_datetime_format_opt: &Option<SortDatetimeFormat>,
+    sort_fields_datetime_format_opt: &[Option<SortDatetimeFormat>],
 ) -> crate::Result<(usize, Hit)> {
     let partial_hit_ref = leaf_hit
         .partial_hit
@@ -742,22 +1058,16 @@ fn build_hit_with_position(
         partial_hit_ref.segment_ord,
         partial_hit_ref.doc_id,
     );
-    let sort_value_opt = partial_hit_ref
-        .sort_value
-        .as_mut()
-        .and_then(|sort_field| sort_field.sort_value.as_mut());
-    if let Some(sort_by_value) = sort_value_opt {
-        if let Some(output_datetime_format) = &sort_field_1_datetime_format_opt {
-            convert_sort_datetime_value(sort_by_value, *output_datetime_format)?;
-        }
-    }
-    let sort_value_2_opt = partial_hit_ref
-        .sort_value2
-        .as_mut()
-        .and_then(|sort_field| sort_field.sort_value.as_mut());
-    if let Some(sort_by_value) = sort_value_2_opt {
-        if let Some(output_datetime_format) = &sort_field_2_datetime_format_opt {
-            convert_sort_datetime_value(sort_by_value, *output_datetime_format)?;
+    for (sort_by_value, sort_field_datetime_format_opt) in partial_hit_ref
+        .sort_values
+        .iter_mut()
+        .zip(sort_fields_datetime_format_opt)
+    {
+        if let (Some(sort_value), Some(output_datetime_format)) = (
+            sort_by_value.sort_value.as_mut(),
+            sort_field_datetime_format_opt,
+        ) {
+            convert_sort_datetime_value(sort_value, *output_datetime_format)?;
         }
     }
     let position = *hit_order.get(&key).expect("hit order must be present");
@@ -773,20 +1083,51 @@ fn build_hit_with_position(
             partial_hit: leaf_hit.partial_hit,
             snippet: leaf_hit.leaf_snippet_json,
             index_id,
+            inner_hits: Vec::new(),
         },
     ))
 }
 
+/// Keeps the best hit of each group of collapsed hits. The hits of a group are expected to be
+/// contiguous and sorted, which is how the collector returns them.
+///
+/// If `with_inner_hits` is true, the hits of each group are returned as the inner hits of its best
+/// hit.
+fn collapse_hits(hits: Vec<Hit>, with_inner_hits: bool) -> Vec<Hit> {
+    let collapse_value = |hit: &Hit| {
+        hit.partial_hit
+            .as_ref()
+            .and_then(|partial_hit| partial_hit.collapse_value.clone())
+    };
+    let mut collapsed_hits: Vec<Hit> = Vec::new();
+
+    for hit in hits {
+        if let Some(group_hit) = collapsed_hits.last_mut() {
+            if collapse_value(group_hit) == collapse_value(&hit) {
+                if with_inner_hits {
+                    group_hit.inner_hits.push(hit);
+                }
+                continue;
+            }
+        }
+        let mut group_hit = hit;
+        if with_inner_hits {
+            let inner_hit = group_hit.clone();
+            group_hit.inner_hits.push(inner_hit);
+        }
+        collapsed_hits.push(group_hit);
+    }
+    collapsed_hits
+}
+
 fn get_sort_field_datetime_format(
-    sort_field: Option<&SortField>,
+    sort_field: &SortField,
 ) -> crate::Result<Option<SortDatetimeFormat>> {
-    if let Some(sort_field) = sort_field {
-        if let Some(sort_field_datetime_format_int) = &sort_field.sort_datetime_format {
-            let sort_field_datetime_format =
-                SortDatetimeFormat::from_i32(*sort_field_datetime_format_int)
-                    .context("invalid sort datetime format")?;
-            return Ok(Some(sort_field_datetime_format));
-        }
+    if let Some(sort_field_datetime_format_int) = &sort_field.sort_datetime_format {
+        let sort_field_datetime_format =
+            SortDatetimeFormat::from_i32(*sort_field_datetime_format_int)
+                .context("invalid sort datetime format")?;
+        return Ok(Some(sort_field_datetime_format));
     }
     Ok(None)
 }
@@ -802,7 +1143,10 @@ async fn root_search_aux(
     indexes_metas_for_leaf_search: &IndexesMetasForLeafSearch,
     search_request: SearchRequest,
     split_metadatas: Vec<SplitMetadata>,
+    timestamp_field_opt: Option<&str>,
+    sort_fields_is_datetime: &HashMap<String, bool>,
     cluster_client: &ClusterClient,
+    progress_tx_opt: Option<&SearchProgressSender>,
 ) -> crate::Result<SearchResponse> {
     debug!(split_metadatas = ?PrettySample::new(&split_metadatas, 5));
     let (first_phase_result, scroll_key_and_start_offset_opt): (
@@ -813,7 +1157,9 @@ async fn root_search_aux(
         indexes_metas_for_leaf_search,
         search_request.clone(),
         &split_metadatas[..],
+        timestamp_field_opt,
         cluster_client,
+        progress_tx_opt,
     )
     .await?;
 
@@ -825,12 +1171,41 @@ async fn root_search_aux(
         cluster_client,
     )
     .await?;
+    let hits = if let Some(collapse) = &search_request.collapse {
+        collapse_hits(hits, collapse.max_inner_hits > 0)
+    } else {
+        hits
+    };
 
-    let aggregation_result_json_opt = finalize_aggregation_if_any(
+    let mut aggregation_result_json_opt = finalize_aggregation_if_any(
         &search_request,
         first_phase_result.intermediate_aggregation_result,
         searcher_context,
     )?;
+    if let Some(top_hits_aggregation) = top_hits_aggregation(&search_request)? {
+        let aggregation_result_json = finalize_top_hits_aggregation(
+            &top_hits_aggregation,
+            first_phase_result.top_hits,
+            first_phase_result.num_hits,
+            aggregation_result_json_opt,
+            indexes_metas_for_leaf_search,
+            &split_metadatas[..],
+            &search_request,
+            sort_fields_is_datetime,
+            cluster_client,
+        )
+        .await?;
+        aggregation_result_json_opt = Some(aggregation_result_json);
+    }
+
+    let profile_opt = if search_request.profile {
+        Some(build_search_profile(
+            &search_request,
+            first_phase_result.split_profiles,
+        ))
+    } else {
+        None
+    };
 
     Ok(SearchResponse {
         aggregation: aggregation_result_json_opt,
@@ -841,7 +1216,205 @@ async fn root_search_aux(
         scroll_id: scroll_key_and_start_offset_opt
             .as_ref()
             .map(ToString::to_string),
+        timed_out: !first_phase_result.skipped_split_ids.is_empty(),
+        skipped_split_ids: first_phase_result.skipped_split_ids,
+        profile: profile_opt,
+    })
+}
+
+/// Fetches the documents of the hits of the `top_hits` aggregation and adds them to the
+/// aggregation result, either at the top level or in each bucket of its parent `terms`
+/// aggregation.
+#[allow(clippy::too_many_arguments)]
+async fn finalize_top_hits_aggregation(
+    top_hits_aggregation: &TopHitsAggregation,
+    top_hits: Vec<PartialHit>,
+    num_hits: u64,
+    aggregation_result_json_opt: Option<String>,
+    indexes_metas_for_leaf_search: &IndexesMetasForLeafSearch,
+    split_metadatas: &[SplitMetadata],
+    search_request: &SearchRequest,
+    sort_fields_is_datetime: &HashMap<String, bool>,
+    cluster_client: &ClusterClient,
+) -> crate::Result<String> {
+    let mut aggregation_result: JsonMap<String, JsonValue> = match aggregation_result_json_opt {
+        Some(aggregation_result_json) => serde_json::from_str(&aggregation_result_json)?,
+        None => JsonMap::new(),
+    };
+    // The hits of a group are contiguous and sorted.
+    let mut top_hits_per_group: HashMap<Option<String>, Vec<PartialHit>> = HashMap::new();
+    for partial_hit in top_hits {
+        top_hits_per_group
+            .entry(partial_hit.collapse_value.clone())
+            .or_default()
+            .push(partial_hit);
+    }
+    // The groups returned, along with their number of documents.
+    let groups: Vec<(Option<String>, u64)> = match &top_hits_aggregation.terms_opt {
+        Some(terms) => terms_buckets(&mut aggregation_result, &terms.name)
+            .map(|bucket| {
+                let doc_count = bucket.get("doc_count").and_then(JsonValue::as_u64);
+                (bucket_key(bucket), doc_count.unwrap_or_default())
+            })
+            .collect(),
+        None => vec![(None, num_hits)],
+    };
+    let mut partial_hits_to_fetch: Vec<PartialHit> = Vec::new();
+    let mut addresses_to_fetch: HashSet<GlobalDocAddress> = HashSet::new();
+
+    for (group, _) in &groups {
+        let Some(group_top_hits) = top_hits_per_group.get_mut(group) else {
+            continue;
+        };
+        group_top_hits.drain(..top_hits_aggregation.from.min(group_top_hits.len()));
+        group_top_hits.truncate(top_hits_aggregation.size);
+
+        for partial_hit in group_top_hits.iter() {
+            // A document belongs to several groups if it has several terms.
+            if addresses_to_fetch.insert(GlobalDocAddress::from_partial_hit(partial_hit)) {
+                partial_hits_to_fetch.push(partial_hit.clone());
+            }
+        }
+    }
+    let sort_fields = top_hits_aggregation
+        .sort_fields
+        .iter()
+        .map(|sort_field| {
+            let is_datetime = sort_fields_is_datetime
+                .get(&sort_field.field_name)
+                .copied()
+                .unwrap_or(false);
+            SortField {
+                // Datetime sort values are returned in milliseconds, as in Elasticsearch.
+                sort_datetime_format: is_datetime
+                    .then_some(SortDatetimeFormat::UnixTimestampMillis as i32),
+                ..sort_field.clone()
+            }
+        })
+        .collect();
+    let fetch_docs_search_request = SearchRequest {
+        sort_fields,
+        snippet_fields: Vec::new(),
+        ..search_request.clone()
+    };
+    let hits: HashMap<GlobalDocAddress, Hit> = fetch_docs_phase(
+        indexes_metas_for_leaf_search,
+        &partial_hits_to_fetch,
+        split_metadatas,
+        &fetch_docs_search_request,
+        cluster_client,
+    )
+    .await?
+    .into_iter()
+    .filter_map(|hit| {
+        let address = GlobalDocAddress::from_partial_hit(hit.partial_hit.as_ref()?);
+        Some((address, hit))
     })
+    .collect();
+
+    let mut top_hits_results: HashMap<Option<String>, JsonValue> = HashMap::new();
+    for (group, doc_count) in groups {
+        let group_top_hits = top_hits_per_group.remove(&group).unwrap_or_default();
+        let hits_json = group_top_hits
+            .iter()
+            .filter_map(|partial_hit| hits.get(&GlobalDocAddress::from_partial_hit(partial_hit)))
+            .map(top_hit_json)
+            .collect::<crate::Result<Vec<JsonValue>>>()?;
+        let top_hits_result = json!({
+            "hits": {
+                "total": {"value": doc_count, "relation": "eq"},
+                "max_score": null,
+                "hits": hits_json,
+            }
+        });
+        top_hits_results.insert(group, top_hits_result);
+    }
+    match &top_hits_aggregation.terms_opt {
+        Some(terms) => {
+            for bucket in terms_buckets(&mut aggregation_result, &terms.name) {
+                if let Some(top_hits_result) = top_hits_results.remove(&bucket_key(bucket)) {
+                    bucket.insert(top_hits_aggregation.name.clone(), top_hits_result);
+                }
+            }
+        }
+        None => {
+            if let Some(top_hits_result) = top_hits_results.remove(&None) {
+                aggregation_result.insert(top_hits_aggregation.name.clone(), top_hits_result);
+            }
+        }
+    }
+    let aggregation_result_json = serde_json::to_string(&aggregation_result)?;
+    Ok(aggregation_result_json)
+}
+
+/// Returns the buckets of the `terms` aggregation with the given name in the aggregation result.
+fn terms_buckets<'a>(
+    aggregation_result: &'a mut JsonMap<String, JsonValue>,
+    terms_name: &str,
+) -> impl Iterator<Item = &'a mut JsonMap<String, JsonValue>> {
+    aggregation_result
+        .get_mut(terms_name)
+        .and_then(|terms_result| terms_result.get_mut("buckets"))
+        .and_then(JsonValue::as_array_mut)
+        .into_iter()
+        .flatten()
+        .filter_map(JsonValue::as_object_mut)
+}
+
+/// Returns the key of a `terms` bucket as a string, the way it is found in
+/// `PartialHit::collapse_value`.
+fn bucket_key(bucket: &JsonMap<String, JsonValue>) -> Option<String> {
+    match bucket.get("key")? {
+        JsonValue::String(key) => Some(key.clone()),
+        key => Some(key.to_string()),
+    }
+}
+
+/// Formats a hit of a `top_hits` aggregation the way Elasticsearch does.
+fn top_hit_json(hit: &Hit) -> crate::Result<JsonValue> {
+    let source: JsonValue = serde_json::from_str(&hit.json)?;
+    let sort: Vec<JsonValue> = hit
+        .partial_hit
+        .iter()
+        .flat_map(|partial_hit| partial_hit.sort_values.iter())
+        .map(|sort_value| sort_value.clone().into_json())
+        .collect();
+    let mut top_hit_json = json!({
+        "_index": hit.index_id,
+        "_score": null,
+        "_source": source,
+    });
+    if !sort.is_empty() {
+        top_hit_json["sort"] = JsonValue::Array(sort);
+    }
+    Ok(top_hit_json)
+}
+
+/// Aggregates the profiles of the splits searched into the profile of the search.
+fn build_search_profile(
+    search_request: &SearchRequest,
+    mut split_profiles: Vec<SplitSearchProfile>,
+) -> SearchProfile {
+    // The splits that took the longest come first.
+    split_profiles.sort_unstable_by_key(|split_profile| {
+        Reverse(
+            split_profile.open_index_micros
+                + split_profile.warmup_micros
+                + split_profile.collection_micros,
+        )
+    });
+    let mut search_profile = SearchProfile {
+        query_ast: search_request.query_ast.clone(),
+        ..Default::default()
+    };
+    for split_profile in &split_profiles {
+        search_profile.num_bytes_fetched += split_profile.num_bytes_fetched;
+        search_profile.num_cache_hits += split_profile.num_cache_hits;
+        search_profile.num_cache_misses += split_profile.num_cache_misses;
+        search_profile.num_docs_matched += split_profile.num_docs_matched;
+    }
+    search_profile.split_profiles = split_profiles;
+    search_profile
 }
 
 fn finalize_aggregation(
@@ -874,7 +1447,9 @@ fn finalize_aggregation_if_any(
     let Some(aggregations_json) = search_request.aggregation_request.as_ref() else {
         return Ok(None);
     };
-    let aggregations: QuickwitAggregations = serde_json::from_str(aggregations_json)?;
+    let (Some(aggregations), _top_hits_opt) = parse_aggregation_request(aggregations_json)? else {
+        return Ok(None);
+    };
     let Some(intermediate_result_bytes) = intermediate_aggregation_result_bytes_opt else {
         return Ok(None);
     };
@@ -933,13 +1508,32 @@ pub fn check_all_index_metadata_found(
 /// 4. Builds the response with docs and returns.
 #[instrument(skip_all)]
 pub async fn root_search(
+    searcher_context: &SearcherContext,
+    search_request: SearchRequest,
+    metastore: MetastoreServiceClient,
+    cluster_client: &ClusterClient,
+) -> crate::Result<SearchResponse> {
+    root_search_with_progress(
+        searcher_context,
+        search_request,
+        metastore,
+        cluster_client,
+        None,
+    )
+    .await
+}
+
+/// Same as [`root_search`], but publishes the partial results of the search (hit count and
+/// aggregations) on `progress_tx_opt` as the leaf responses come in.
+pub(crate) async fn root_search_with_progress(
     searcher_context: &SearcherContext,
     mut search_request: SearchRequest,
     mut metastore: MetastoreServiceClient,
     cluster_client: &ClusterClient,
+    progress_tx_opt: Option<&SearchProgressSender>,
 ) -> crate::Result<SearchResponse> {
     info!(searcher_context = ?searcher_context, search_request = ?search_request);
-    let start_instant = tokio::time::Instant::now();
+    let start_instant = Instant::now();
     let list_indexes_metadatas_request = ListIndexesMetadataRequest {
         index_id_patterns: search_request.index_id_patterns.clone(),
     };
@@ -959,7 +1553,10 @@ pub async fn root_search(
             &HashMap::default(),
             search_request,
             Vec::new(),
+            None,
+            &HashMap::default(),
             cluster_client,
+            progress_tx_opt,
         )
         .await?;
         search_response.elapsed_time_micros = start_instant.elapsed().as_micros() as u64;
@@ -1001,12 +1598,21 @@ pub async fn root_search(
     )
     .await?;
 
+    if let Some(timeout_millis) = search_request.timeout_millis {
+        // The leaves are only given the time left after the metastore calls above.
+        let elapsed_millis = start_instant.elapsed().as_millis() as u64;
+        search_request.timeout_millis = Some(timeout_millis.saturating_sub(elapsed_millis));
+    }
+
     let mut search_response = root_search_aux(
         searcher_context,
         &request_metadata.indexes_meta_for_leaf_search,
         search_request,
         split_metadatas,
+        request_metadata.timestamp_field_opt.as_deref(),
+        &request_metadata.sort_fields_is_datetime,
         cluster_client,
+        progress_tx_opt,
     )
     .await?;
 
@@ -1032,16 +1638,9 @@ fn convert_search_after_datetime_values(
         }
     }
     if let Some(partial_hit) = search_request.search_after.as_mut() {
-        let search_after_values = [
-            partial_hit.sort_value.as_mut(),
-            partial_hit.sort_value2.as_mut(),
-        ];
-        for (sort_field, search_after_value_opt) in
-            search_request.sort_fields.iter().zip(search_after_values)
+        for (sort_field, search_after_sort_by_value) in
+            sort_fields_without_doc(&search_request.sort_fields).zip(&mut partial_hit.sort_values)
         {
-            let Some(search_after_sort_by_value) = search_after_value_opt else {
-                continue;
-            };
             let Some(search_after_sort_value) = search_after_sort_by_value.sort_value.as_mut()
             else {
                 continue;
@@ -1049,6 +1648,11 @@ fn convert_search_after_datetime_values(
             let Some(datetime_format_int) = sort_field.sort_datetime_format else {
                 continue;
             };
+            if let SortValue::Str(text) = search_after_sort_value {
+                if let Some(sort_value) = SortValue::parse_integer(text) {
+                    *search_after_sort_value = sort_value;
+                }
+            }
             let input_datetime_format = SortDatetimeFormat::from_i32(datetime_format_int)
                 .context("invalid sort datetime format")?;
             convert_sort_datetime_value_into_nanos(search_after_sort_value, input_datetime_format)?;
@@ -1263,6 +1867,123 @@ impl<'a, 'b> QueryAstVisitor<'b> for ExtractTimestampRange<'a> {
     }
 }
 
+/// Returns the query AST of the request if the request only counts documents, and if its query
+/// matches all the documents of a split whose time range it covers. This is the case of
+/// `match_all` queries and of queries made only of ranges on the timestamp field.
+fn metadata_count_query_ast(search_request: &SearchRequest) -> crate::Result<Option<QueryAst>> {
+    if search_request.max_hits > 0 || search_request.aggregation_request.is_some() {
+        return Ok(None);
+    }
+    let query_ast: QueryAst = serde_json::from_str(&search_request.query_ast)
+        .map_err(|err| SearchError::InvalidQuery(err.to_string()))?;
+    if !matches!(
+        query_ast,
+        QueryAst::MatchAll | QueryAst::Range(_) | QueryAst::Bool(_)
+    ) {
+        return Ok(None);
+    }
+    Ok(Some(query_ast))
+}
+
+/// Returns the number of documents of the split matching the request, if it can be derived from
+/// the split metadata without searching the split.
+fn count_from_split_metadata(
+    search_request: &SearchRequest,
+    query_ast: &QueryAst,
+    timestamp_field_opt: Option<&str>,
+    split_metadata: &SplitMetadata,
+) -> Option<u64> {
+    let mut timestamp_ranges: Vec<&RangeQuery> = Vec::new();
+    if !collect_timestamp_ranges(query_ast, timestamp_field_opt, &mut timestamp_ranges) {
+        return None;
+    }
+    let is_time_filtered = search_request.start_timestamp.is_some()
+        || search_request.end_timestamp.is_some()
+        || !timestamp_ranges.is_empty();
+    if is_time_filtered {
+        let time_range = split_metadata.time_range.as_ref()?;
+        // The request start timestamp is inclusive and its end timestamp exclusive.
+        let start_timestamp_covers_split = search_request
+            .start_timestamp
+            .map_or(true, |start_timestamp| {
+                start_timestamp <= *time_range.start()
+            });
+        let end_timestamp_covers_split = search_request
+            .end_timestamp
+            .map_or(true, |end_timestamp| end_timestamp > *time_range.end());
+        if !start_timestamp_covers_split
+            || !end_timestamp_covers_split
+            || !timestamp_ranges
+                .iter()
+                .all(|range_query| range_covers_time_range(range_query, time_range))
+        {
+            return None;
+        }
+    }
+    Some(split_metadata.num_docs as u64)
+}
+
+/// Collects the ranges on the timestamp field of a query AST, returning `false` if the query AST
+/// does not match all the documents within those ranges.
+fn collect_timestamp_ranges<'a>(
+    query_ast: &'a QueryAst,
+    timestamp_field_opt: Option<&str>,
+    timestamp_ranges: &mut Vec<&'a RangeQuery>,
+) -> bool {
+    match query_ast {
+        QueryAst::MatchAll => true,
+        QueryAst::Range(range_query) if Some(range_query.field.as_str()) == timestamp_field_opt => {
+            timestamp_ranges.push(range_query);
+            true
+        }
+        QueryAst::Bool(bool_query) => {
+            bool_query.should.is_empty()
+                && bool_query.must_not.is_empty()
+                && !(bool_query.must.is_empty() && bool_query.filter.is_empty())
+                && bool_query
+                    .must
+                    .iter()
+                    .chain(bool_query.filter.iter())
+                    .all(|ast| collect_timestamp_ranges(ast, timestamp_field_opt, timestamp_ranges))
+        }
+        _ => false,
+    }
+}
+
+/// Returns true if all the timestamps within the time range of a split, expressed in seconds, are
+/// within the range of the range query.
+fn range_covers_time_range(range_query: &RangeQuery, time_range: &RangeInclusive<i64>) -> bool {
+    use std::ops::Bound;
+
+    use quickwit_query::InterpretUserInput;
+
+    let to_timestamp_nanos = |json_literal: &quickwit_query::JsonLiteral| {
+        tantivy::DateTime::interpret_json(json_literal)
+            .map(|datetime| datetime.into_timestamp_nanos())
+    };
+    // The timestamps of the split are within `[split_start_nanos..split_end_nanos)`.
+    let split_start_nanos = time_range.start().saturating_mul(1_000_000_000);
+    let split_end_nanos = time_range
+        .end()
+        .saturating_add(1)
+        .saturating_mul(1_000_000_000);
+    let lower_bound_covers_split = match &range_query.lower_bound {
+        Bound::Included(lower_bound) => to_timestamp_nanos(lower_bound)
+            .map_or(false, |lower_bound| lower_bound <= split_start_nanos),
+        Bound::Excluded(lower_bound) => to_timestamp_nanos(lower_bound)
+            .map_or(false, |lower_bound| lower_bound < split_start_nanos),
+        Bound::Unbounded => true,
+    };
+    let upper_bound_covers_split = match &range_query.upper_bound {
+        Bound::Included(upper_bound) => to_timestamp_nanos(upper_bound)
+            .map_or(false, |upper_bound| upper_bound >= split_end_nanos - 1),
+        Bound::Excluded(upper_bound) => to_timestamp_nanos(upper_bound)
+            .map_or(false, |upper_bound| upper_bound >= split_end_nanos),
+        Bound::Unbounded => true,
+    };
+    lower_bound_covers_split && upper_bound_covers_split
+}
+
 async fn assign_client_fetch_docs_jobs(
     partial_hits: &[PartialHit],
     split_metadatas: &[SplitMetadata],
@@ -1316,10 +2037,36 @@ async fn assign_client_fetch_docs_jobs(
     Ok(assigned_jobs)
 }
 
+/// Number of documents adding one unit to the cost of searching a split.
+const NUM_DOCS_PER_COST_UNIT: u64 = 100_000;
+
+/// Number of bytes of uncompressed documents adding one unit to the cost of searching a split.
+const NUM_DOCS_BYTES_PER_COST_UNIT: u64 = 100_000_000;
+
+/// Number of bytes to download adding one unit to the cost of searching a split that is not
+/// cached on the node searching it.
+const NUM_DOWNLOADED_BYTES_PER_COST_UNIT: u64 = 1_000_000;
+
 // Measure the cost associated to searching in a given split metadata.
-fn compute_split_cost(_split_metadata: &SplitMetadata) -> usize {
-    // TODO: Have a smarter cost, by smoothing the number of docs.
-    1
+fn compute_split_cost(split_metadata: &SplitMetadata) -> usize {
+    // Opening a split has a fixed cost, on top of which the cost of running the query grows with
+    // the number of documents and the amount of data they hold.
+    let num_docs_cost = split_metadata.num_docs as u64 / NUM_DOCS_PER_COST_UNIT;
+    let docs_size_cost =
+        split_metadata.uncompressed_docs_size_in_bytes / NUM_DOCS_BYTES_PER_COST_UNIT;
+    (1 + num_docs_cost + docs_size_cost) as usize
+}
+
+// Measure the additional cost of searching a split on a node other than the one with the highest
+// affinity with the split. The cost assumes that only the node with the highest affinity has the
+// split cached, which the placer does not actually know.
+fn compute_split_affinity_miss_cost(split_metadata: &SplitMetadata) -> usize {
+    // At the very least, the footer and the hotcache of the split must be downloaded.
+    let footer_num_bytes = split_metadata
+        .footer_offsets
+        .end
+        .saturating_sub(split_metadata.footer_offsets.start);
+    (footer_num_bytes / NUM_DOWNLOADED_BYTES_PER_COST_UNIT) as usize
 }
 
 /// Builds a list of [`LeafSearchRequest`], one per index, from a list of [`SearchJob`].
@@ -1403,24 +2150,196 @@ mod tests {
         ScrollRequest, SortByValue, SortOrder, SortValue, SplitSearchError,
     };
     use quickwit_query::query_ast::{qast_helper, qast_json_helper, query_ast_from_user_text};
-    use tantivy::schema::{FAST, STORED, TEXT};
+    use tantivy::schema::{FAST, STORED, STRING, TEXT};
 
     use super::*;
     use crate::{searcher_pool_for_test, MockSearchService};
 
-    #[track_caller]
-    fn check_snippet_fields_validation(snippet_fields: &[String]) -> anyhow::Result<()> {
-        let mut schema_builder = Schema::builder();
-        schema_builder.add_text_field("title", TEXT);
-        schema_builder.add_text_field("desc", TEXT | STORED);
-        schema_builder.add_ip_addr_field("ip", FAST | STORED);
-        let schema = schema_builder.build();
-        validate_requested_snippet_fields(&schema, snippet_fields)
+    #[track_caller]
+    fn check_snippet_fields_validation(snippet_fields: &[String]) -> anyhow::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        schema_builder.add_text_field("desc", TEXT | STORED);
+        schema_builder.add_ip_addr_field("ip", FAST | STORED);
+        schema_builder.add_json_field("attributes", TEXT | STORED);
+        schema_builder.add_json_field("resource", STORED);
+        let schema = schema_builder.build();
+        validate_requested_snippet_fields(&schema, snippet_fields)
+    }
+
+    #[test]
+    fn test_compute_split_cost() {
+        let small_split = SplitMetadata {
+            num_docs: 1_000,
+            uncompressed_docs_size_in_bytes: 1_000_000,
+            footer_offsets: 0..100_000,
+            ..Default::default()
+        };
+        assert_eq!(compute_split_cost(&small_split), 1);
+        assert_eq!(compute_split_affinity_miss_cost(&small_split), 0);
+
+        let large_split = SplitMetadata {
+            num_docs: 10_000_000,
+            uncompressed_docs_size_in_bytes: 5_000_000_000,
+            footer_offsets: 1_000_000..5_000_000,
+            ..Default::default()
+        };
+        assert_eq!(compute_split_cost(&large_split), 1 + 100 + 50);
+        assert_eq!(compute_split_affinity_miss_cost(&large_split), 4);
+
+        let search_job = SearchJob::from(&large_split);
+        assert_eq!(search_job.cost(), 151);
+        assert_eq!(search_job.affinity_miss_cost(), 4);
+    }
+
+    #[test]
+    fn test_count_from_split_metadata() {
+        use std::ops::Bound;
+
+        use quickwit_query::JsonLiteral;
+
+        let split_metadata = SplitMetadata {
+            num_docs: 100,
+            time_range: Some(100..=199),
+            ..Default::default()
+        };
+        let count = |search_request: &SearchRequest| {
+            let query_ast = metadata_count_query_ast(search_request).unwrap()?;
+            count_from_split_metadata(search_request, &query_ast, Some("ts"), &split_metadata)
+        };
+        let time_range_ast = |lower_bound: &str, upper_bound: Bound<&str>| {
+            let to_json_literal = |bound: &str| JsonLiteral::String(bound.to_string());
+            let upper_bound = match upper_bound {
+                Bound::Included(upper_bound) => Bound::Included(to_json_literal(upper_bound)),
+                Bound::Excluded(upper_bound) => Bound::Excluded(to_json_literal(upper_bound)),
+                Bound::Unbounded => Bound::Unbounded,
+            };
+            QueryAst::Range(RangeQuery {
+                field: "ts".to_string(),
+                lower_bound: Bound::Included(to_json_literal(lower_bound)),
+                upper_bound,
+            })
+        };
+        let search_request = |query_ast: QueryAst| SearchRequest {
+            query_ast: serde_json::to_string(&query_ast).unwrap(),
+            ..Default::default()
+        };
+        assert_eq!(count(&search_request(QueryAst::MatchAll)), Some(100));
+        assert_eq!(
+            count(&SearchRequest {
+                max_hits: 10,
+                ..search_request(QueryAst::MatchAll)
+            }),
+            None
+        );
+        assert_eq!(
+            count(&SearchRequest {
+                aggregation_request: Some(
+                    r#"{"count": {"value_count": {"field": "ts"}}}"#.to_string()
+                ),
+                ..search_request(QueryAst::MatchAll)
+            }),
+            None
+        );
+        assert_eq!(
+            count(&SearchRequest {
+                start_timestamp: Some(100),
+                end_timestamp: Some(200),
+                ..search_request(QueryAst::MatchAll)
+            }),
+            Some(100)
+        );
+        assert_eq!(
+            count(&SearchRequest {
+                start_timestamp: Some(101),
+                ..search_request(QueryAst::MatchAll)
+            }),
+            None
+        );
+        assert_eq!(
+            count(&SearchRequest {
+                end_timestamp: Some(199),
+                ..search_request(QueryAst::MatchAll)
+            }),
+            None
+        );
+        // 100s and 200s.
+        let covering_time_range_ast = time_range_ast(
+            "1970-01-01T00:01:40Z",
+            Bound::Excluded("1970-01-01T00:03:20Z"),
+        );
+        assert_eq!(
+            count(&search_request(covering_time_range_ast.clone())),
+            Some(100)
+        );
+        // The split can hold documents between 199s and 200s.
+        assert_eq!(
+            count(&search_request(time_range_ast(
+                "1970-01-01T00:01:40Z",
+                Bound::Included("1970-01-01T00:03:19Z"),
+            ))),
+            None
+        );
+        assert_eq!(
+            count(&search_request(time_range_ast(
+                "1970-01-01T00:01:40.5Z",
+                Bound::Unbounded,
+            ))),
+            None
+        );
+        assert_eq!(
+            count(&search_request(QueryAst::Bool(BoolQuery {
+                must: vec![QueryAst::MatchAll],
+                filter: vec![covering_time_range_ast.clone()],
+                ..Default::default()
+            }))),
+            Some(100)
+        );
+        assert_eq!(
+            count(&search_request(QueryAst::Bool(BoolQuery {
+                should: vec![covering_time_range_ast.clone()],
+                ..Default::default()
+            }))),
+            None
+        );
+        assert_eq!(
+            count(&search_request(QueryAst::Range(RangeQuery {
+                field: "other_field".to_string(),
+                lower_bound: Bound::Unbounded,
+                upper_bound: Bound::Unbounded,
+            }))),
+            None
+        );
+        assert_eq!(
+            count(&search_request(
+                TermQuery {
+                    field: "ts".to_string(),
+                    value: "1970-01-01T00:01:40Z".to_string(),
+                }
+                .into()
+            )),
+            None
+        );
+        let split_metadata_without_time_range = SplitMetadata {
+            num_docs: 100,
+            ..Default::default()
+        };
+        let search_request = search_request(covering_time_range_ast);
+        assert_eq!(
+            count_from_split_metadata(
+                &search_request,
+                &metadata_count_query_ast(&search_request).unwrap().unwrap(),
+                Some("ts"),
+                &split_metadata_without_time_range
+            ),
+            None
+        );
     }
 
     #[test]
     fn test_validate_requested_snippet_fields() {
         check_snippet_fields_validation(&["desc".to_string()]).unwrap();
+        check_snippet_fields_validation(&["*".to_string()]).unwrap();
         let field_not_stored_err =
             check_snippet_fields_validation(&["title".to_string()]).unwrap_err();
         assert_eq!(
@@ -1439,6 +2358,19 @@ mod tests {
             field_is_not_text_err.to_string(),
             "the snippet field `ip` must be of type `Str`, got `IpAddr`"
         );
+        check_snippet_fields_validation(&["attributes.message".to_string()]).unwrap();
+        let json_root_err =
+            check_snippet_fields_validation(&["attributes".to_string()]).unwrap_err();
+        assert_eq!(
+            json_root_err.to_string(),
+            "the snippet field `attributes` must be of type `Str`, got `Json`"
+        );
+        let json_field_not_indexed_err =
+            check_snippet_fields_validation(&["resource.service".to_string()]).unwrap_err();
+        assert_eq!(
+            json_field_not_indexed_err.to_string(),
+            "the snippet field `resource.service` must be indexed"
+        );
     }
 
     #[test]
@@ -1890,15 +2822,12 @@ mod tests {
             },
         ];
         let partial_hit = PartialHit {
-            sort_value: Some(SortByValue {
-                sort_value: Some(SortValue::U64(1)),
-            }),
-            sort_value2: Some(SortByValue {
-                sort_value: Some(SortValue::U64(2)),
-            }),
+            sort_values: vec![SortValue::U64(1).into(), SortValue::U64(2).into()],
             split_id: "".to_string(),
             segment_ord: 0,
             doc_id: 0,
+            collapse_value: None,
+            ..Default::default()
         };
         validate_sort_by_fields_and_search_after(&sort_fields, &Some(partial_hit)).unwrap();
     }
@@ -1918,13 +2847,12 @@ mod tests {
             },
         ];
         let partial_hit = PartialHit {
-            sort_value: Some(SortByValue {
-                sort_value: Some(SortValue::U64(1)),
-            }),
-            sort_value2: None,
+            sort_values: vec![SortValue::U64(1).into()],
             split_id: "split1".to_string(),
             segment_ord: 1,
             doc_id: 1,
+            collapse_value: None,
+            ..Default::default()
         };
         validate_sort_by_fields_and_search_after(&sort_fields, &Some(partial_hit)).unwrap();
     }
@@ -1936,6 +2864,7 @@ mod tests {
         let id_field = schema_builder.add_u64_field("id", FAST);
         let no_fast_field = schema_builder.add_u64_field("no_fast", STORED);
         let text_field = schema_builder.add_text_field("text", STORED);
+        let text_fast_field = schema_builder.add_text_field("text_fast", STRING | FAST);
         let schema = schema_builder.build();
         {
             let sort_by_field_entry = schema.get_field_entry(timestamp_field);
@@ -1963,10 +2892,21 @@ mod tests {
         }
         {
             let sort_by_field_entry = schema.get_field_entry(text_field);
+            let error = validate_sort_by_field_type(sort_by_field_entry, false).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "Invalid argument: sort by field must be a fast field, please add the fast \
+                 property to your field `text`"
+            );
+        }
+        {
+            let sort_by_field_entry = schema.get_field_entry(text_fast_field);
+            validate_sort_by_field_type(sort_by_field_entry, false).unwrap();
             let error = validate_sort_by_field_type(sort_by_field_entry, true).unwrap_err();
             assert_eq!(
                 error.to_string(),
-                "Invalid argument: sort by field on type text is currently not supported `text`"
+                "Invalid argument: sort by field with a timestamp format must be a datetime field \
+                 and the field `text_fast` is not"
             );
         }
     }
@@ -1987,13 +2927,12 @@ mod tests {
             },
         ];
         let partial_hit = PartialHit {
-            sort_value: Some(SortByValue {
-                sort_value: Some(SortValue::U64(1)),
-            }),
-            sort_value2: None,
+            sort_values: vec![SortValue::U64(1).into()],
             split_id: "split1".to_string(),
             segment_ord: 1,
             doc_id: 1,
+            collapse_value: None,
+            ..Default::default()
         };
         let error =
             validate_sort_by_fields_and_search_after(&sort_fields, &Some(partial_hit)).unwrap_err();
@@ -2020,13 +2959,12 @@ mod tests {
             },
         ];
         let partial_hit = PartialHit {
-            sort_value: Some(SortByValue {
-                sort_value: Some(SortValue::U64(1)),
-            }),
-            sort_value2: None,
+            sort_values: vec![SortValue::U64(1).into()],
             split_id: "".to_string(),
             segment_ord: 1,
             doc_id: 1,
+            collapse_value: None,
+            ..Default::default()
         };
         let error =
             validate_sort_by_fields_and_search_after(&sort_fields, &Some(partial_hit)).unwrap_err();
@@ -2053,13 +2991,12 @@ mod tests {
             },
         ];
         let partial_hit = PartialHit {
-            sort_value: Some(SortByValue {
-                sort_value: Some(SortValue::U64(1)),
-            }),
-            sort_value2: None,
+            sort_values: vec![SortValue::U64(1).into()],
             split_id: "split1".to_string(),
             segment_ord: 1,
             doc_id: 1,
+            collapse_value: None,
+            ..Default::default()
         };
         let error =
             validate_sort_by_fields_and_search_after(&sort_fields, &Some(partial_hit)).unwrap_err();
@@ -2086,18 +3023,17 @@ mod tests {
     }
 
     #[test]
-    fn test_validate_sort_by_fields_and_search_after_invalid_3() {
-        // 3 sort fields is not possible.
+    fn test_validate_sort_by_fields_and_search_after_with_3_sort_fields() {
         let sort_fields = vec![
             SortField {
-                field_name: "timestamp".to_string(),
+                field_name: "service".to_string(),
                 sort_order: 0,
-                sort_datetime_format: Some(SortDatetimeFormat::UnixTimestampMillis as i32),
+                sort_datetime_format: None,
             },
             SortField {
-                field_name: "timestamp".to_string(),
+                field_name: "severity".to_string(),
                 sort_order: 0,
-                sort_datetime_format: Some(SortDatetimeFormat::UnixTimestampMillis as i32),
+                sort_datetime_format: None,
             },
             SortField {
                 field_name: "timestamp".to_string(),
@@ -2105,11 +3041,20 @@ mod tests {
                 sort_datetime_format: Some(SortDatetimeFormat::UnixTimestampMillis as i32),
             },
         ];
-        let error = validate_sort_by_fields_and_search_after(&sort_fields, &None).unwrap_err();
-        assert_eq!(
-            error.to_string(),
-            "Invalid argument: sort by field must be up to 2 fields, got 3"
-        );
+        validate_sort_by_fields_and_search_after(&sort_fields, &None).unwrap();
+        let partial_hit = PartialHit {
+            sort_values: vec![
+                SortValue::Str("api".to_string()).into(),
+                SortByValue { sort_value: None },
+                SortValue::U64(1).into(),
+            ],
+            split_id: "".to_string(),
+            segment_ord: 0,
+            doc_id: 0,
+            collapse_value: None,
+            ..Default::default()
+        };
+        validate_sort_by_fields_and_search_after(&sort_fields, &Some(partial_hit)).unwrap();
     }
 
     fn mock_partial_hit(
@@ -2118,11 +3063,12 @@ mod tests {
         doc_id: u32,
     ) -> quickwit_proto::search::PartialHit {
         quickwit_proto::search::PartialHit {
-            sort_value: Some(SortValue::U64(sort_value).into()),
-            sort_value2: None,
+            sort_values: vec![SortValue::U64(sort_value).into()],
             split_id: split_id.to_string(),
             segment_ord: 1,
             doc_id,
+            collapse_value: None,
+            ..Default::default()
         }
     }
 
@@ -2132,11 +3078,14 @@ mod tests {
         doc_id: u32,
     ) -> quickwit_proto::search::PartialHit {
         quickwit_proto::search::PartialHit {
-            sort_value: sort_value.map(|sort_value| SortValue::U64(sort_value).into()),
-            sort_value2: None,
+            sort_values: vec![SortByValue {
+                sort_value: sort_value.map(SortValue::U64),
+            }],
             split_id: split_id.to_string(),
             segment_ord: 1,
             doc_id,
+            collapse_value: None,
+            ..Default::default()
         }
     }
 
@@ -2327,6 +3276,186 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_root_search_top_hits_aggregation() -> anyhow::Result<()> {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            query_ast: qast_json_helper("test", &["body"]),
+            max_hits: 0,
+            aggregation_request: Some(
+                r#"{"latest": {"top_hits": {"from": 1, "size": 1}}}"#.to_string(),
+            ),
+            ..Default::default()
+        };
+        let mut metastore = MetastoreServiceClient::mock();
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        let index_uid = index_metadata.index_uid.clone();
+        metastore
+            .expect_list_indexes_metadata()
+            .returning(move |_index_ids_query| {
+                Ok(ListIndexesMetadataResponse::try_from_indexes_metadata(vec![
+                    index_metadata.clone()
+                ])
+                .unwrap())
+            });
+        metastore
+            .expect_list_splits()
+            .returning(move |_list_splits_request| {
+                let splits = vec![MockSplitBuilder::new("split1")
+                    .with_index_uid(&index_uid)
+                    .build()];
+                let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits_response)]))
+            });
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_leaf_search().returning(
+            |_leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                Ok(quickwit_proto::search::LeafSearchResponse {
+                    num_hits: 3,
+                    num_attempted_splits: 1,
+                    top_hits: vec![
+                        mock_partial_hit("split1", 3, 3),
+                        mock_partial_hit("split1", 2, 2),
+                        mock_partial_hit("split1", 1, 1),
+                    ],
+                    ..Default::default()
+                })
+            },
+        );
+        mock_search_service.expect_fetch_docs().times(1).returning(
+            |fetch_docs_req: quickwit_proto::search::FetchDocsRequest| {
+                Ok(quickwit_proto::search::FetchDocsResponse {
+                    hits: get_doc_for_fetch_req(fetch_docs_req),
+                })
+            },
+        );
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer.clone());
+
+        let search_response = root_search(
+            &SearcherContext::for_test(),
+            search_request,
+            MetastoreServiceClient::from(metastore),
+            &cluster_client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(search_response.num_hits, 3);
+        assert!(search_response.hits.is_empty());
+
+        // The hits are sorted by doc ID in descending order by default.
+        let aggregation_result: serde_json::Value =
+            serde_json::from_str(&search_response.aggregation.unwrap()).unwrap();
+        assert_eq!(
+            aggregation_result,
+            serde_json::json!({
+                "latest": {
+                    "hits": {
+                        "total": {"value": 3, "relation": "eq"},
+                        "max_score": null,
+                        "hits": [{
+                            "_index": "test-index",
+                            "_score": null,
+                            "_source": {
+                                "title": ["2"],
+                                "body": ["test 1"],
+                                "url": ["http://127.0.0.1/1"]
+                            }
+                        }]
+                    }
+                }
+            })
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_root_search_timeout_returns_partial_results() -> anyhow::Result<()> {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            query_ast: qast_json_helper("test", &["body"]),
+            max_hits: 10,
+            timeout_millis: Some(10_000),
+            ..Default::default()
+        };
+        let mut metastore = MetastoreServiceClient::mock();
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        let index_uid = index_metadata.index_uid.clone();
+        metastore
+            .expect_list_indexes_metadata()
+            .returning(move |_index_ids_query| {
+                Ok(ListIndexesMetadataResponse::try_from_indexes_metadata(vec![
+                    index_metadata.clone()
+                ])
+                .unwrap())
+            });
+        metastore
+            .expect_list_splits()
+            .returning(move |_list_splits_request| {
+                let splits = vec![
+                    MockSplitBuilder::new("split1")
+                        .with_index_uid(&index_uid)
+                        .build(),
+                    MockSplitBuilder::new("split2")
+                        .with_index_uid(&index_uid)
+                        .build(),
+                ];
+                let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits_response)]))
+            });
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_leaf_search().returning(
+            |leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                let timeout_millis = leaf_search_req
+                    .search_request
+                    .unwrap()
+                    .timeout_millis
+                    .unwrap();
+                assert!(timeout_millis <= 10_000);
+                // The leaf ran out of time before searching `split2`.
+                Ok(quickwit_proto::search::LeafSearchResponse {
+                    num_hits: 2,
+                    partial_hits: vec![
+                        mock_partial_hit("split1", 2, 1),
+                        mock_partial_hit("split1", 1, 2),
+                    ],
+                    num_attempted_splits: 1,
+                    skipped_split_ids: vec!["split2".to_string()],
+                    ..Default::default()
+                })
+            },
+        );
+        mock_search_service.expect_fetch_docs().returning(
+            |fetch_docs_req: quickwit_proto::search::FetchDocsRequest| {
+                Ok(quickwit_proto::search::FetchDocsResponse {
+                    hits: get_doc_for_fetch_req(fetch_docs_req),
+                })
+            },
+        );
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer.clone());
+
+        let searcher_context = SearcherContext::for_test();
+        let search_response = root_search(
+            &searcher_context,
+            search_request,
+            MetastoreServiceClient::from(metastore),
+            &cluster_client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(search_response.num_hits, 2);
+        assert_eq!(search_response.hits.len(), 2);
+        assert!(search_response.timed_out);
+        assert_eq!(
+            search_response.skipped_split_ids,
+            vec!["split2".to_string()]
+        );
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_root_search_multiple_splits() -> anyhow::Result<()> {
         let search_request = quickwit_proto::search::SearchRequest {
@@ -2462,18 +3591,20 @@ mod tests {
                     num_hits: 2,
                     partial_hits: vec![
                         quickwit_proto::search::PartialHit {
-                            sort_value: Some(SortValue::U64(2u64).into()),
-                            sort_value2: None,
+                            sort_values: vec![SortValue::U64(2u64).into()],
                             split_id: "split1".to_string(),
                             segment_ord: 0,
                             doc_id: 0,
+                            collapse_value: None,
+                            ..Default::default()
                         },
                         quickwit_proto::search::PartialHit {
-                            sort_value: None,
-                            sort_value2: None,
+                            sort_values: vec![SortByValue { sort_value: None }],
                             split_id: "split1".to_string(),
                             segment_ord: 0,
                             doc_id: 1,
+                            collapse_value: None,
+                            ..Default::default()
                         },
                     ],
                     failed_splits: Vec::new(),
@@ -2496,25 +3627,28 @@ mod tests {
                     num_hits: 3,
                     partial_hits: vec![
                         quickwit_proto::search::PartialHit {
-                            sort_value: Some(SortValue::I64(-1i64).into()),
-                            sort_value2: None,
+                            sort_values: vec![SortValue::I64(-1i64).into()],
                             split_id: "split2".to_string(),
                             segment_ord: 0,
                             doc_id: 1,
+                            collapse_value: None,
+                            ..Default::default()
                         },
                         quickwit_proto::search::PartialHit {
-                            sort_value: Some(SortValue::I64(1i64).into()),
-                            sort_value2: None,
+                            sort_values: vec![SortValue::I64(1i64).into()],
                             split_id: "split2".to_string(),
                             segment_ord: 0,
                             doc_id: 0,
+                            collapse_value: None,
+                            ..Default::default()
                         },
                         quickwit_proto::search::PartialHit {
-                            sort_value: None,
-                            sort_value2: None,
+                            sort_values: vec![SortByValue { sort_value: None }],
                             split_id: "split2".to_string(),
                             segment_ord: 0,
                             doc_id: 2,
+                            collapse_value: None,
+                            ..Default::default()
                         },
                     ],
                     failed_splits: Vec::new(),
@@ -2552,8 +3686,9 @@ mod tests {
                 split_id: "split2".to_string(),
                 segment_ord: 0,
                 doc_id: 1,
-                sort_value: Some(SortValue::I64(-1i64).into()),
-                sort_value2: None,
+                sort_values: vec![SortValue::I64(-1i64).into()],
+                collapse_value: None,
+                ..Default::default()
             }
         );
         assert_eq!(
@@ -2562,8 +3697,9 @@ mod tests {
                 split_id: "split2".to_string(),
                 segment_ord: 0,
                 doc_id: 0,
-                sort_value: Some(SortValue::I64(1i64).into()),
-                sort_value2: None,
+                sort_values: vec![SortValue::I64(1i64).into()],
+                collapse_value: None,
+                ..Default::default()
             }
         );
         assert_eq!(
@@ -2572,8 +3708,9 @@ mod tests {
                 split_id: "split1".to_string(),
                 segment_ord: 0,
                 doc_id: 0,
-                sort_value: Some(SortValue::U64(2u64).into()),
-                sort_value2: None,
+                sort_values: vec![SortValue::U64(2u64).into()],
+                collapse_value: None,
+                ..Default::default()
             }
         );
         assert_eq!(
@@ -2582,8 +3719,9 @@ mod tests {
                 split_id: "split1".to_string(),
                 segment_ord: 0,
                 doc_id: 1,
-                sort_value: None,
-                sort_value2: None,
+                sort_values: vec![SortByValue { sort_value: None }],
+                collapse_value: None,
+                ..Default::default()
             }
         );
         assert_eq!(
@@ -2592,8 +3730,9 @@ mod tests {
                 split_id: "split2".to_string(),
                 segment_ord: 0,
                 doc_id: 2,
-                sort_value: None,
-                sort_value2: None,
+                sort_values: vec![SortByValue { sort_value: None }],
+                collapse_value: None,
+                ..Default::default()
             }
         );
         Ok(())
@@ -2643,18 +3782,20 @@ mod tests {
                     num_hits: 2,
                     partial_hits: vec![
                         quickwit_proto::search::PartialHit {
-                            sort_value: Some(SortValue::U64(2u64).into()),
-                            sort_value2: None,
+                            sort_values: vec![SortValue::U64(2u64).into()],
                             split_id: "split1".to_string(),
                             segment_ord: 0,
                             doc_id: 0,
+                            collapse_value: None,
+                            ..Default::default()
                         },
                         quickwit_proto::search::PartialHit {
-                            sort_value: None,
-                            sort_value2: None,
+                            sort_values: vec![SortByValue { sort_value: None }],
                             split_id: "split1".to_string(),
                             segment_ord: 0,
                             doc_id: 1,
+                            collapse_value: None,
+                            ..Default::default()
                         },
                     ],
                     failed_splits: Vec::new(),
@@ -2677,25 +3818,28 @@ mod tests {
                     num_hits: 3,
                     partial_hits: vec![
                         quickwit_proto::search::PartialHit {
-                            sort_value: Some(SortValue::I64(1i64).into()),
-                            sort_value2: None,
+                            sort_values: vec![SortValue::I64(1i64).into()],
                             split_id: "split2".to_string(),
                             segment_ord: 0,
                             doc_id: 0,
+                            collapse_value: None,
+                            ..Default::default()
                         },
                         quickwit_proto::search::PartialHit {
-                            sort_value: Some(SortValue::I64(-1i64).into()),
-                            sort_value2: None,
+                            sort_values: vec![SortValue::I64(-1i64).into()],
                             split_id: "split2".to_string(),
                             segment_ord: 0,
                             doc_id: 1,
+                            collapse_value: None,
+                            ..Default::default()
                         },
                         quickwit_proto::search::PartialHit {
-                            sort_value: None,
-                            sort_value2: None,
+                            sort_values: vec![SortByValue { sort_value: None }],
                             split_id: "split2".to_string(),
                             segment_ord: 0,
                             doc_id: 2,
+                            collapse_value: None,
+                            ..Default::default()
                         },
                     ],
                     failed_splits: Vec::new(),
@@ -2733,8 +3877,9 @@ mod tests {
                 split_id: "split1".to_string(),
                 segment_ord: 0,
                 doc_id: 0,
-                sort_value: Some(SortValue::U64(2u64).into()),
-                sort_value2: None,
+                sort_values: vec![SortValue::U64(2u64).into()],
+                collapse_value: None,
+                ..Default::default()
             }
         );
         assert_eq!(
@@ -2743,8 +3888,9 @@ mod tests {
                 split_id: "split2".to_string(),
                 segment_ord: 0,
                 doc_id: 0,
-                sort_value: Some(SortValue::I64(1i64).into()),
-                sort_value2: None,
+                sort_values: vec![SortValue::I64(1i64).into()],
+                collapse_value: None,
+                ..Default::default()
             }
         );
         assert_eq!(
@@ -2753,8 +3899,9 @@ mod tests {
                 split_id: "split2".to_string(),
                 segment_ord: 0,
                 doc_id: 1,
-                sort_value: Some(SortValue::I64(-1i64).into()),
-                sort_value2: None,
+                sort_values: vec![SortValue::I64(-1i64).into()],
+                collapse_value: None,
+                ..Default::default()
             }
         );
         assert_eq!(
@@ -2763,8 +3910,9 @@ mod tests {
                 split_id: "split2".to_string(),
                 segment_ord: 0,
                 doc_id: 2,
-                sort_value: None,
-                sort_value2: None,
+                sort_values: vec![SortByValue { sort_value: None }],
+                collapse_value: None,
+                ..Default::default()
             }
         );
         assert_eq!(
@@ -2773,8 +3921,9 @@ mod tests {
                 split_id: "split1".to_string(),
                 segment_ord: 0,
                 doc_id: 1,
-                sort_value: None,
-                sort_value2: None,
+                sort_values: vec![SortByValue { sort_value: None }],
+                collapse_value: None,
+                ..Default::default()
             }
         );
         Ok(())
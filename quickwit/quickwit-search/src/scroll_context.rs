@@ -114,7 +114,11 @@ impl ScrollContext {
             &self.indexes_metas_for_leaf_search,
             &self.search_request,
             &self.split_metadatas[..],
+            // Scroll requests always fetch hits, so the split metadata is never enough to answer
+            // them.
+            None,
             cluster_client,
+            None,
         )
         .await?;
         self.cached_partial_hits_start_offset = start_offset;
@@ -130,13 +134,17 @@ pub(crate) struct MiniKV {
 
 impl Default for MiniKV {
     fn default() -> MiniKV {
-        MiniKV {
-            ttl_with_cache: Arc::new(RwLock::new(TtlCache::new(SCROLL_BATCH_LEN))),
-        }
+        MiniKV::with_capacity(SCROLL_BATCH_LEN)
     }
 }
 
 impl MiniKV {
+    pub fn with_capacity(capacity: usize) -> MiniKV {
+        MiniKV {
+            ttl_with_cache: Arc::new(RwLock::new(TtlCache::new(capacity))),
+        }
+    }
+
     pub async fn put(&self, key: Vec<u8>, payload: Vec<u8>, ttl: Duration) {
         let mut cache_lock = self.ttl_with_cache.write().await;
         cache_lock.insert(key, payload, ttl);
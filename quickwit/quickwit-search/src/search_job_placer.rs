@@ -22,6 +22,8 @@ use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use anyhow::bail;
 use async_trait::async_trait;
@@ -46,6 +48,17 @@ pub trait Job {
     /// the sum of cost evenly.
     fn cost(&self) -> usize;
 
+    /// Estimation of the additional load incurred when the job does not run on the node with
+    /// the highest affinity with its split.
+    ///
+    /// This is a heuristic based on rendez-vous hashing alone: the placer does not know which
+    /// splits are actually cached on which nodes. Splits are reported to their node of highest
+    /// affinity though, so that node is the most likely to hold the split in its split cache and
+    /// its other caches.
+    fn affinity_miss_cost(&self) -> usize {
+        0
+    }
+
     /// Compares the cost of two jobs in reverse order, breaking ties by split ID.
     fn compare_cost(&self, other: &Self) -> Ordering {
         self.cost()
@@ -55,12 +68,23 @@ pub trait Job {
     }
 }
 
+/// Weight of the latest observation in the moving average of the nodes' latencies.
+const LATENCY_EWMA_ALPHA: f64 = 0.2;
+
+/// Bounds of the factor applied to the load of a node depending on its observed latency, so that
+/// a single slow response cannot starve or flood a node.
+const MIN_LATENCY_FACTOR: f64 = 0.5;
+const MAX_LATENCY_FACTOR: f64 = 4.0;
+
 /// Search job placer.
 /// It assigns jobs to search clients.
 #[derive(Clone, Default)]
 pub struct SearchJobPlacer {
     /// Search clients pool.
     searcher_pool: SearcherPool,
+    /// Exponentially weighted moving average of the time it takes each node to search one unit
+    /// of cost, in seconds.
+    node_latencies: Arc<Mutex<HashMap<SocketAddr, f64>>>,
 }
 
 #[async_trait]
@@ -103,7 +127,67 @@ impl fmt::Debug for SearchJobPlacer {
 impl SearchJobPlacer {
     /// Returns an [`SearchJobPlacer`] from a search service client pool.
     pub fn new(searcher_pool: SearcherPool) -> Self {
-        Self { searcher_pool }
+        Self {
+            searcher_pool,
+            node_latencies: Default::default(),
+        }
+    }
+
+    /// Records the time it took the node at `grpc_addr` to run jobs totalling `cost`.
+    ///
+    /// The observed latencies are used to steer jobs away from slow nodes.
+    pub fn record_node_latency(&self, grpc_addr: SocketAddr, cost: usize, elapsed: Duration) {
+        // The node may have left the pool while running the jobs.
+        if cost == 0 || !self.searcher_pool.contains_key(&grpc_addr) {
+            return;
+        }
+        let secs_per_cost_unit = elapsed.as_secs_f64() / cost as f64;
+        let mut node_latencies = self.node_latencies.lock().unwrap();
+        node_latencies
+            .entry(grpc_addr)
+            .and_modify(|ewma| {
+                *ewma = LATENCY_EWMA_ALPHA * secs_per_cost_unit + (1.0 - LATENCY_EWMA_ALPHA) * *ewma
+            })
+            .or_insert(secs_per_cost_unit);
+    }
+
+    /// Returns the factor by which the load of each node is multiplied: the ratio between the
+    /// node's latency and the average latency of the nodes. Nodes without any observation get a
+    /// factor of 1.
+    ///
+    /// The latencies of the nodes that left the pool are dropped along the way, so that a node
+    /// rejoining the cluster later on starts afresh.
+    fn latency_factors(&self, grpc_addrs: &[SocketAddr]) -> HashMap<SocketAddr, f64> {
+        let mut node_latencies = self.node_latencies.lock().unwrap();
+        node_latencies.retain(|grpc_addr, _| self.searcher_pool.contains_key(grpc_addr));
+
+        let observed_latencies: Vec<(SocketAddr, f64)> = grpc_addrs
+            .iter()
+            .filter_map(|grpc_addr| {
+                node_latencies
+                    .get(grpc_addr)
+                    .map(|latency| (*grpc_addr, *latency))
+            })
+            .collect();
+        if observed_latencies.is_empty() {
+            return HashMap::new();
+        }
+        let mean_latency = observed_latencies
+            .iter()
+            .map(|(_, latency)| latency)
+            .sum::<f64>()
+            / observed_latencies.len() as f64;
+        if mean_latency <= 0.0 {
+            return HashMap::new();
+        }
+        observed_latencies
+            .into_iter()
+            .map(|(grpc_addr, latency)| {
+                let latency_factor =
+                    (latency / mean_latency).clamp(MIN_LATENCY_FACTOR, MAX_LATENCY_FACTOR);
+                (grpc_addr, latency_factor)
+            })
+            .collect()
     }
 }
 
@@ -164,6 +248,7 @@ impl SearchJobPlacer {
                 grpc_addr,
                 client,
                 load: 0,
+                latency_factor: 1.0,
             })
             .collect();
 
@@ -172,6 +257,16 @@ impl SearchJobPlacer {
                 "failed to assign search jobs. there are no available searcher nodes in the pool"
             );
         }
+        let grpc_addrs: Vec<SocketAddr> = candidate_nodes
+            .iter()
+            .map(|candidate_node| candidate_node.grpc_addr)
+            .collect();
+        let latency_factors = self.latency_factors(&grpc_addrs);
+        for candidate_node in &mut candidate_nodes {
+            if let Some(latency_factor) = latency_factors.get(&candidate_node.grpc_addr) {
+                candidate_node.latency_factor = *latency_factor;
+            }
+        }
         jobs.sort_unstable_by(Job::compare_cost);
 
         let mut job_assignments: HashMap<SocketAddr, (SearchServiceClient, Vec<J>)> =
@@ -179,14 +274,24 @@ impl SearchJobPlacer {
 
         for job in jobs {
             sort_by_rendez_vous_hash(&mut candidate_nodes, job.split_id());
-            // Select the least loaded node.
+            // Select the node that would be the least loaded once the job is assigned to it. The
+            // first node has the highest affinity with the split, so running the job on the
+            // second node may incur cache misses.
             let chosen_node_idx = if candidate_nodes.len() >= 2 {
-                usize::from(candidate_nodes[0].load > candidate_nodes[1].load)
+                let affinity_node_load = candidate_nodes[0].projected_load(job.cost());
+                let other_node_load =
+                    candidate_nodes[1].projected_load(job.cost() + job.affinity_miss_cost());
+                usize::from(affinity_node_load > other_node_load)
             } else {
                 0
             };
+            let job_cost = if chosen_node_idx == 0 {
+                job.cost()
+            } else {
+                job.cost() + job.affinity_miss_cost()
+            };
             let chosen_node = &mut candidate_nodes[chosen_node_idx];
-            chosen_node.load += job.cost();
+            chosen_node.load += job_cost;
 
             job_assignments
                 .entry(chosen_node.grpc_addr)
@@ -218,6 +323,15 @@ struct CandidateNodes {
     pub grpc_addr: SocketAddr,
     pub client: SearchServiceClient,
     pub load: usize,
+    pub latency_factor: f64,
+}
+
+impl CandidateNodes {
+    /// Returns the load of the node, weighted by its latency, if a job of cost `job_cost` was
+    /// assigned to it.
+    fn projected_load(&self, job_cost: usize) -> f64 {
+        (self.load + job_cost) as f64 * self.latency_factor
+    }
 }
 
 impl Hash for CandidateNodes {
@@ -236,6 +350,8 @@ impl Eq for CandidateNodes {}
 
 #[cfg(test)]
 mod tests {
+    use quickwit_common::tower::Change;
+
     use super::*;
     use crate::{searcher_pool_for_test, MockSearchService, SearchJob};
 
@@ -322,4 +438,87 @@ mod tests {
             assert_eq!(assigned_jobs, expected_assigned_jobs);
         }
     }
+
+    #[tokio::test]
+    async fn test_search_job_placer_steers_jobs_away_from_slow_nodes() {
+        let searcher_pool = searcher_pool_for_test([
+            ("127.0.0.1:1001", MockSearchService::new()),
+            ("127.0.0.1:1002", MockSearchService::new()),
+        ]);
+        let slow_searcher_addr: SocketAddr = ([127, 0, 0, 1], 1001).into();
+        let fast_searcher_addr: SocketAddr = ([127, 0, 0, 1], 1002).into();
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        search_job_placer.record_node_latency(slow_searcher_addr, 10, Duration::from_secs(3));
+        search_job_placer.record_node_latency(fast_searcher_addr, 10, Duration::from_secs(1));
+
+        let jobs: Vec<SearchJob> = (0..8)
+            .map(|split_ord| SearchJob::for_test(&format!("split{split_ord}"), 1))
+            .collect();
+        let assigned_jobs: HashMap<SocketAddr, Vec<SearchJob>> = search_job_placer
+            .assign_jobs(jobs, &HashSet::default())
+            .await
+            .unwrap()
+            .map(|(client, jobs)| (client.grpc_addr(), jobs))
+            .collect();
+        let num_slow_searcher_jobs = assigned_jobs
+            .get(&slow_searcher_addr)
+            .map(Vec::len)
+            .unwrap_or(0);
+        let num_fast_searcher_jobs = assigned_jobs
+            .get(&fast_searcher_addr)
+            .map(Vec::len)
+            .unwrap_or(0);
+        assert_eq!(num_slow_searcher_jobs + num_fast_searcher_jobs, 8);
+        assert!(num_slow_searcher_jobs < num_fast_searcher_jobs);
+    }
+
+    #[tokio::test]
+    async fn test_search_job_placer_record_node_latency() {
+        let searcher_pool = searcher_pool_for_test([
+            ("127.0.0.1:1001", MockSearchService::new()),
+            ("127.0.0.1:1002", MockSearchService::new()),
+            ("127.0.0.1:1003", MockSearchService::new()),
+        ]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool.clone());
+        let searcher_addr_1: SocketAddr = ([127, 0, 0, 1], 1001).into();
+        let searcher_addr_2: SocketAddr = ([127, 0, 0, 1], 1002).into();
+        let searcher_addr_3: SocketAddr = ([127, 0, 0, 1], 1003).into();
+        let searcher_addrs = [searcher_addr_1, searcher_addr_2, searcher_addr_3];
+
+        assert!(search_job_placer
+            .latency_factors(&searcher_addrs)
+            .is_empty());
+
+        // Jobs with a zero cost are not informative.
+        search_job_placer.record_node_latency(searcher_addr_1, 0, Duration::from_secs(1));
+        assert!(search_job_placer
+            .latency_factors(&searcher_addrs)
+            .is_empty());
+
+        search_job_placer.record_node_latency(searcher_addr_1, 1, Duration::from_secs(1));
+        search_job_placer.record_node_latency(searcher_addr_2, 1, Duration::from_secs(1));
+        search_job_placer.record_node_latency(searcher_addr_2, 1, Duration::from_secs(101));
+
+        let latency_factors = search_job_placer.latency_factors(&searcher_addrs);
+        assert_eq!(latency_factors.len(), 2);
+        assert_eq!(latency_factors[&searcher_addr_1], MIN_LATENCY_FACTOR);
+        // The moving average of the second searcher is 0.2 * 101 + 0.8 * 1 = 21, and the mean
+        // latency is (1 + 21) / 2 = 11.
+        assert!((latency_factors[&searcher_addr_2] - 21.0 / 11.0).abs() < 1e-9);
+
+        // The latencies of the nodes that leave the pool are forgotten.
+        searcher_pool.listen_for_changes(futures::stream::iter([Change::Remove(searcher_addr_2)]));
+        while searcher_pool.contains_key(&searcher_addr_2) {
+            tokio::task::yield_now().await;
+        }
+        let latency_factors = search_job_placer.latency_factors(&searcher_addrs);
+        assert_eq!(latency_factors.len(), 1);
+        assert_eq!(latency_factors[&searcher_addr_1], 1.0);
+        assert_eq!(search_job_placer.node_latencies.lock().unwrap().len(), 1);
+
+        // Nor are the latencies of nodes outside of the pool recorded.
+        let searcher_addr_4: SocketAddr = ([127, 0, 0, 1], 1004).into();
+        search_job_placer.record_node_latency(searcher_addr_4, 1, Duration::from_secs(1));
+        assert_eq!(search_job_placer.node_latencies.lock().unwrap().len(), 1);
+    }
 }
@@ -19,11 +19,12 @@
 
 use std::convert::TryFrom;
 
-use quickwit_common::truncate_str;
-use quickwit_proto::search::SearchResponse;
+use quickwit_common::{is_false, truncate_str};
+use quickwit_proto::search::{SearchProfile, SearchResponse};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 
+use crate::async_search::AsyncSearchResponse;
 use crate::error::SearchError;
 
 /// SearchResponseRest represents the response returned by the REST search API
@@ -39,6 +40,10 @@ pub struct SearchResponseRest {
     #[schema(value_type = Vec<Object>)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub snippets: Option<Vec<JsonValue>>,
+    /// Inner hits of each hit, only returned if the hits are collapsed with inner hits.
+    #[schema(value_type = Vec<Vec<Object>>)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inner_hits: Option<Vec<Vec<JsonValue>>>,
     /// Elapsed time.
     pub elapsed_time_micros: u64,
     /// Search errors.
@@ -47,6 +52,18 @@ pub struct SearchResponseRest {
     #[schema(value_type = Object)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub aggregations: Option<JsonValue>,
+    /// True if the search timed out and the response only contains partial results.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub timed_out: bool,
+    /// Ids of the splits that were not searched because the search timed out.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped_split_ids: Vec<String>,
+    /// Profile of the search, only returned if requested.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<SearchProfile>,
 }
 
 impl TryFrom<SearchResponse> for SearchResponseRest {
@@ -55,15 +72,18 @@ impl TryFrom<SearchResponse> for SearchResponseRest {
     fn try_from(search_response: SearchResponse) -> Result<Self, Self::Error> {
         let mut documents = Vec::with_capacity(search_response.hits.len());
         let mut snippets = Vec::new();
+        let mut inner_hits = Vec::new();
         for hit in search_response.hits {
-            let document: JsonValue = serde_json::from_str(&hit.json).map_err(|err| {
-                SearchError::Internal(format!(
-                    "failed to serialize document `{}` to JSON: `{}`",
-                    truncate_str(&hit.json, 100),
-                    err
-                ))
-            })?;
-            documents.push(document);
+            documents.push(parse_document(&hit.json)?);
+
+            if !hit.inner_hits.is_empty() {
+                let inner_documents: Vec<JsonValue> = hit
+                    .inner_hits
+                    .iter()
+                    .map(|inner_hit| parse_document(&inner_hit.json))
+                    .collect::<Result<_, _>>()?;
+                inner_hits.push(inner_documents);
+            }
 
             if let Some(snippet_json) = hit.snippet {
                 let snippet_opt: JsonValue =
@@ -81,6 +101,11 @@ impl TryFrom<SearchResponse> for SearchResponseRest {
         } else {
             None
         };
+        let inner_hits_opt = if !inner_hits.is_empty() {
+            Some(inner_hits)
+        } else {
+            None
+        };
 
         let aggregations_opt = if let Some(aggregation_json) = search_response.aggregation {
             let aggregation: JsonValue = serde_json::from_str(&aggregation_json)
@@ -94,9 +119,68 @@ impl TryFrom<SearchResponse> for SearchResponseRest {
             num_hits: search_response.num_hits,
             hits: documents,
             snippets: snippet_opt,
+            inner_hits: inner_hits_opt,
             elapsed_time_micros: search_response.elapsed_time_micros,
             errors: search_response.errors,
             aggregations: aggregations_opt,
+            timed_out: search_response.timed_out,
+            skipped_split_ids: search_response.skipped_split_ids,
+            profile: search_response.profile,
+        })
+    }
+}
+
+fn parse_document(document_json: &str) -> Result<JsonValue, SearchError> {
+    serde_json::from_str(document_json).map_err(|err| {
+        SearchError::Internal(format!(
+            "failed to serialize document `{}` to JSON: `{}`",
+            truncate_str(document_json, 100),
+            err
+        ))
+    })
+}
+
+/// AsyncSearchResponseRest represents the state of an async search returned by the REST async
+/// search API.
+#[derive(Serialize, Deserialize, PartialEq, Debug, utoipa::ToSchema)]
+pub struct AsyncSearchResponseRest {
+    /// ID of the async search, used to poll its results and to delete it.
+    pub id: String,
+    /// Whether the search is still running.
+    pub is_running: bool,
+    /// Whether the response only covers the splits searched so far.
+    pub is_partial: bool,
+    /// Unix timestamp (in seconds) at which the search was submitted.
+    pub start_timestamp: i64,
+    /// Unix timestamp (in seconds) after which the search and its results are discarded.
+    pub expiration_timestamp: i64,
+    /// Search response. While the search is running, it only contains the number of hits and
+    /// the aggregations merged so far.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<SearchResponseRest>,
+    /// Error that made the search fail.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl TryFrom<AsyncSearchResponse> for AsyncSearchResponseRest {
+    type Error = SearchError;
+
+    fn try_from(async_search_response: AsyncSearchResponse) -> Result<Self, Self::Error> {
+        let response = async_search_response
+            .response
+            .map(SearchResponseRest::try_from)
+            .transpose()?;
+        Ok(AsyncSearchResponseRest {
+            id: async_search_response.id,
+            is_running: async_search_response.is_running,
+            is_partial: async_search_response.is_partial,
+            start_timestamp: async_search_response.start_timestamp,
+            expiration_timestamp: async_search_response.expiration_timestamp,
+            response,
+            error: async_search_response.error,
         })
     }
 }
@@ -41,11 +41,19 @@ use quickwit_storage::{
 use tantivy::aggregation::AggregationLimits;
 use tokio::sync::Semaphore;
 use tokio_stream::wrappers::UnboundedReceiverStream;
+use tokio_util::sync::CancellationToken;
 
+use crate::async_search::{
+    delete_async_search, get_async_search, submit_async_search, AsyncSearchResponse,
+    RunningAsyncSearches, ASYNC_SEARCH_CAPACITY, ASYNC_SEARCH_KEY_PREFIX,
+};
 use crate::leaf_cache::LeafSearchCache;
 use crate::list_fields::{leaf_list_fields, root_list_fields};
 use crate::list_fields_cache::ListFieldsCache;
 use crate::list_terms::{leaf_list_terms, root_list_terms};
+use crate::partial_aggregation_cache::{
+    PARTIAL_AGGREGATION_CACHE_CAPACITY, PARTIAL_AGGREGATION_CACHE_KEY_PREFIX,
+};
 use crate::root::fetch_docs_phase;
 use crate::scroll_context::{MiniKV, ScrollContext, ScrollKeyAndStartOffset};
 use crate::search_stream::{leaf_search_stream, root_search_stream};
@@ -59,6 +67,9 @@ pub struct SearchServiceImpl {
     cluster_client: ClusterClient,
     searcher_context: Arc<SearcherContext>,
     search_after_cache: MiniKV,
+    partial_aggregation_cache: MiniKV,
+    async_search_contexts: MiniKV,
+    running_async_searches: RunningAsyncSearches,
 }
 
 /// Trait representing a search service.
@@ -124,6 +135,21 @@ pub trait SearchService: 'static + Send + Sync {
     /// Performs a scroll request.
     async fn scroll(&self, scroll_request: ScrollRequest) -> crate::Result<SearchResponse>;
 
+    /// Starts a search in the background and returns its ID right away.
+    /// The search and its results are discarded after `keep_alive`.
+    async fn submit_async_search(
+        &self,
+        search_request: SearchRequest,
+        keep_alive: Duration,
+    ) -> crate::Result<AsyncSearchResponse>;
+
+    /// Returns the state of an async search, with its partial results if it is still running.
+    async fn get_async_search(&self, async_search_id: String)
+        -> crate::Result<AsyncSearchResponse>;
+
+    /// Deletes an async search, cancelling it if it is still running.
+    async fn delete_async_search(&self, async_search_id: String) -> crate::Result<()>;
+
     /// Stores a Key value in the local cache.
     /// This operation is not distributed. The distribution logic lives in
     /// the `ClusterClient`.
@@ -164,6 +190,22 @@ impl SearchServiceImpl {
             cluster_client,
             searcher_context,
             search_after_cache: MiniKV::default(),
+            partial_aggregation_cache: MiniKV::with_capacity(PARTIAL_AGGREGATION_CACHE_CAPACITY),
+            async_search_contexts: MiniKV::with_capacity(ASYNC_SEARCH_CAPACITY),
+            running_async_searches: RunningAsyncSearches::default(),
+        }
+    }
+
+    /// Returns the namespace of the KV store holding `key`. The entries of each namespace are
+    /// evicted independently, so that scroll contexts, async searches, and cached aggregation
+    /// results do not evict one another.
+    fn kv_namespace(&self, key: &[u8]) -> &MiniKV {
+        if key.starts_with(PARTIAL_AGGREGATION_CACHE_KEY_PREFIX) {
+            &self.partial_aggregation_cache
+        } else if key.starts_with(ASYNC_SEARCH_KEY_PREFIX) {
+            &self.async_search_contexts
+        } else {
+            &self.search_after_cache
         }
     }
 }
@@ -200,12 +242,17 @@ impl SearchService for SearchServiceImpl {
         let storage = self.storage_resolver.resolve(&index_uri).await?;
         let doc_mapper = deserialize_doc_mapper(&leaf_search_request.doc_mapper)?;
 
+        // The splits are searched in spawned tasks, which would outlive this future if the root
+        // abandons the request. Dropping the guard stops them.
+        let cancellation_token = CancellationToken::new();
+        let _cancel_on_drop = cancellation_token.clone().drop_guard();
         let leaf_search_response = leaf_search(
             self.searcher_context.clone(),
             search_request,
             storage.clone(),
             leaf_search_request.split_offsets,
             doc_mapper,
+            cancellation_token,
         )
         .await?;
 
@@ -308,15 +355,50 @@ impl SearchService for SearchServiceImpl {
         scroll(scroll_request, &self.cluster_client, &self.searcher_context).await
     }
 
+    async fn submit_async_search(
+        &self,
+        search_request: SearchRequest,
+        keep_alive: Duration,
+    ) -> crate::Result<AsyncSearchResponse> {
+        submit_async_search(
+            self.searcher_context.clone(),
+            search_request,
+            keep_alive,
+            self.metastore.clone(),
+            self.cluster_client.clone(),
+            &self.running_async_searches,
+        )
+        .await
+    }
+
+    async fn get_async_search(
+        &self,
+        async_search_id: String,
+    ) -> crate::Result<AsyncSearchResponse> {
+        get_async_search(&async_search_id, &self.cluster_client).await
+    }
+
+    async fn delete_async_search(&self, async_search_id: String) -> crate::Result<()> {
+        delete_async_search(
+            &async_search_id,
+            &self.cluster_client,
+            &self.running_async_searches,
+        )
+        .await
+    }
+
     async fn put_kv(&self, put_request: PutKvRequest) {
         let ttl = Duration::from_secs(put_request.ttl_secs as u64);
-        self.search_after_cache
+        self.kv_namespace(&put_request.key)
             .put(put_request.key, put_request.payload, ttl)
             .await;
     }
 
     async fn get_kv(&self, get_request: GetKvRequest) -> Option<Vec<u8>> {
-        let payload: Vec<u8> = self.search_after_cache.get(&get_request.key).await?;
+        let payload: Vec<u8> = self
+            .kv_namespace(&get_request.key)
+            .get(&get_request.key)
+            .await?;
         Some(payload)
     }
 
@@ -426,6 +508,9 @@ pub(crate) async fn scroll(
         scroll_id: next_scroll_id.as_ref().map(ToString::to_string),
         errors: Vec::new(),
         aggregation: None,
+        timed_out: false,
+        skipped_split_ids: Vec::new(),
+        profile: None,
     })
 }
 /// [`SearcherContext`] provides a common set of variables
@@ -18,24 +18,29 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
 
 use assert_json_diff::{assert_json_eq, assert_json_include};
 use quickwit_config::SearcherConfig;
 use quickwit_doc_mapper::tag_pruning::extract_tags_from_query;
 use quickwit_doc_mapper::DefaultDocMapper;
 use quickwit_indexing::TestSandbox;
+use quickwit_metastore::{IndexMetadataResponseExt, UpdateIndexRequestExt};
 use quickwit_opentelemetry::otlp::TraceId;
+use quickwit_proto::metastore::{IndexMetadataRequest, UpdateIndexRequest};
 use quickwit_proto::search::{
-    LeafListTermsResponse, ListTermsRequest, SearchRequest, SortByValue, SortField, SortOrder,
-    SortValue,
+    CollapseOptions, Hit, LeafListTermsResponse, ListTermsRequest, PartialHit, SearchRequest,
+    SnippetOptions, SortField, SortOrder, SortValue,
 };
 use quickwit_query::query_ast::{
-    qast_helper, qast_json_helper, query_ast_from_user_text, QueryAst,
+    qast_helper, qast_json_helper, query_ast_from_user_text, QueryAst, TermQuery,
 };
 use serde_json::{json, Value as JsonValue};
 use tantivy::schema::OwnedValue as TantivyValue;
 use tantivy::time::OffsetDateTime;
 use tantivy::Term;
+use tokio_util::sync::CancellationToken;
+use ulid::Ulid;
 
 use super::*;
 use crate::find_trace_ids_collector::Span;
@@ -132,6 +137,72 @@ async fn test_single_node_termset() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_single_node_search_field_appended_after_split_creation() -> anyhow::Result<()> {
+    let index_id = "single-node-appended-field";
+    let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: title
+                type: text
+              - name: body
+                type: text
+        "#;
+    let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &["body"]).await?;
+    let docs = vec![
+        json!({"title": "snoopy", "body": "Snoopy is an anthropomorphic beagle[5] in the comic strip..."}),
+        json!({"title": "beagle", "body": "The beagle is a breed of small scent hound, similar in appearance to the much larger foxhound."}),
+    ];
+    test_sandbox.add_documents(docs).await?;
+
+    // The split created above does not hold the `category` field appended to the doc mapping.
+    let mut metastore = test_sandbox.metastore();
+    let index_metadata = metastore
+        .index_metadata(IndexMetadataRequest::for_index_id(index_id.to_string()))
+        .await?
+        .deserialize_index_metadata()?;
+    let index_config = index_metadata.index_config;
+    let mut doc_mapping = index_config.doc_mapping.clone();
+    doc_mapping.field_mappings.push(serde_json::from_str(
+        r#"{"name": "category", "type": "text"}"#,
+    )?);
+    let update_index_request = UpdateIndexRequest::try_from_updates(
+        test_sandbox.index_uid(),
+        &doc_mapping,
+        &index_config.search_settings,
+        &index_config.retention_policy,
+        &index_config.indexing_settings,
+    )?;
+    metastore.update_index(update_index_request).await?;
+
+    for (user_query, expected_num_hits) in [
+        ("category:dog", 0),
+        ("category:do*", 0),
+        ("category:\"small dog\"*", 0),
+        ("category: IN [dog hound]", 0),
+        ("title:beagle OR category:do*", 1),
+    ] {
+        let search_request = SearchRequest {
+            index_id_patterns: vec![index_id.to_string()],
+            query_ast: qast_json_helper(user_query, &[]),
+            max_hits: 10,
+            ..Default::default()
+        };
+        let single_node_result = single_node_search(
+            search_request,
+            test_sandbox.metastore(),
+            test_sandbox.storage_resolver(),
+        )
+        .await?;
+        assert_eq!(
+            single_node_result.num_hits, expected_num_hits,
+            "unexpected number of hits for query `{user_query}`"
+        );
+        assert!(single_node_result.errors.is_empty());
+    }
+    test_sandbox.assert_quit().await;
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_single_search_with_snippet() -> anyhow::Result<()> {
     let index_id = "single-node-with-snippet";
@@ -178,6 +249,129 @@ async fn test_single_search_with_snippet() -> anyhow::Result<()> {
     let expected_json: JsonValue = json!({"title": [], "body": ["Snoopy is an anthropomorphic <b>beagle</b> in the comic strip"]});
     assert_json_eq!(highlight_json, expected_json);
 
+    // The wildcard stands for all the text fields, and 0 fragments highlights the whole values,
+    // regardless of the fragment size.
+    let search_request = SearchRequest {
+        index_id_patterns: vec![index_id.to_string()],
+        query_ast: qast_json_helper("beagle", &["title", "body"]),
+        snippet_fields: vec!["*".to_string()],
+        snippet_options: Some(SnippetOptions {
+            fragment_size: Some(10),
+            num_fragments: Some(0),
+            ..Default::default()
+        }),
+        max_hits: 2,
+        ..Default::default()
+    };
+    let single_node_result = single_node_search(
+        search_request,
+        test_sandbox.metastore(),
+        test_sandbox.storage_resolver(),
+    )
+    .await?;
+    let highlight_json: JsonValue =
+        serde_json::from_str(single_node_result.hits[1].snippet.as_ref().unwrap())?;
+    let expected_json: JsonValue = json!({"title": [], "body": ["Snoopy is an anthropomorphic <b>beagle</b> in the comic strip"]});
+    assert_json_eq!(highlight_json, expected_json);
+
+    test_sandbox.assert_quit().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_single_search_with_snippet_on_json_fields() -> anyhow::Result<()> {
+    let index_id = "single-node-with-snippet-on-json-fields";
+    let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: title
+                type: text
+              - name: attributes
+                type: json
+                tokenizer: default
+            mode: dynamic
+            dynamic_mapping:
+                tokenizer: default
+        "#;
+    let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &["title"]).await?;
+    let docs = vec![
+        json!({
+            "title": "snoopy",
+            "attributes": {"description": "Snoopy is a beagle."},
+            "owner": {"name": "Charlie Brown", "pets": ["a beagle", "a bird"]}
+        }),
+        json!({
+            "title": "beagle",
+            "attributes": {"description": "The beagle barks at the cat. The cat ignores it. The beagle and another beagle play."}
+        }),
+    ];
+    test_sandbox.add_documents(docs.clone()).await?;
+    let search_request = SearchRequest {
+        index_id_patterns: vec![index_id.to_string()],
+        query_ast: qast_json_helper("attributes.description:beagle OR owner.pets:beagle", &[]),
+        snippet_fields: vec![
+            "attributes.description".to_string(),
+            "owner.pets".to_string(),
+        ],
+        max_hits: 2,
+        ..Default::default()
+    };
+    let single_node_result = single_node_search(
+        search_request.clone(),
+        test_sandbox.metastore(),
+        test_sandbox.storage_resolver(),
+    )
+    .await?;
+    assert_eq!(single_node_result.num_hits, 2);
+
+    let snippets_json: Vec<JsonValue> = single_node_result
+        .hits
+        .iter()
+        .map(|hit| serde_json::from_str(hit.snippet.as_ref().unwrap()).unwrap())
+        .collect();
+    let snoopy_snippet_json = snippets_json
+        .iter()
+        .find(|snippet_json| snippet_json.get("owner.pets").is_some())
+        .unwrap();
+    assert_json_eq!(
+        snoopy_snippet_json,
+        json!({
+            "attributes.description": ["Snoopy is a <b>beagle</b>"],
+            "owner.pets": ["a <b>beagle</b>"],
+        })
+    );
+
+    // Several fragments of a long text, best fragments first.
+    let search_request = SearchRequest {
+        snippet_options: Some(SnippetOptions {
+            fragment_size: Some(30),
+            num_fragments: Some(2),
+            pre_tag: Some("<em>".to_string()),
+            post_tag: Some("</em>".to_string()),
+        }),
+        ..search_request
+    };
+    let single_node_result = single_node_search(
+        search_request,
+        test_sandbox.metastore(),
+        test_sandbox.storage_resolver(),
+    )
+    .await?;
+    let beagle_snippet_json: JsonValue = single_node_result
+        .hits
+        .iter()
+        .map(|hit| serde_json::from_str(hit.snippet.as_ref().unwrap()).unwrap())
+        .find(|snippet_json: &JsonValue| snippet_json.get("owner.pets").is_none())
+        .unwrap();
+    assert_json_eq!(
+        beagle_snippet_json,
+        json!({
+            "attributes.description": [
+                "<em>beagle</em> and another <em>beagle</em>",
+                "The <em>beagle</em> barks at the cat",
+            ],
+        })
+    );
+
     test_sandbox.assert_quit().await;
     Ok(())
 }
@@ -328,7 +522,7 @@ async fn test_single_node_several_splits() -> anyhow::Result<()> {
     assert!(is_sorted(single_node_result.hits.iter().flat_map(|hit| {
         hit.partial_hit.as_ref().map(|partial_hit| {
             (
-                partial_hit.sort_value,
+                partial_hit.sort_value().cloned(),
                 partial_hit.split_id.as_str(),
                 partial_hit.doc_id,
             )
@@ -598,8 +792,8 @@ async fn single_node_search_sort_by_field(
                 .partial_hit
                 .as_ref()
                 .unwrap()
-                .sort_value
-                >= hits[1].partial_hit.as_ref().unwrap().sort_value));
+                .sort_value()
+                >= hits[1].partial_hit.as_ref().unwrap().sort_value()));
             test_sandbox.assert_quit().await;
             Ok(())
         }
@@ -679,10 +873,7 @@ async fn test_sort_bm25() {
                 .into_iter()
                 .map(|hit| {
                     let partial_hit = hit.partial_hit.unwrap();
-                    let Some(SortByValue {
-                        sort_value: Some(SortValue::F64(score)),
-                    }) = partial_hit.sort_value
-                    else {
+                    let Some(&SortValue::F64(score)) = partial_hit.sort_value() else {
                         panic!()
                     };
                     (score as f32, partial_hit.doc_id)
@@ -912,6 +1103,225 @@ async fn test_sort_by_2_field() {
     test_sandbox.assert_quit().await;
 }
 
+#[tokio::test]
+async fn test_sort_by_3_fields_with_string_field_and_search_after() {
+    let index_id = "sort_by_3_fields";
+    let doc_mapping_yaml = r#"
+            mode: dynamic
+            field_mappings:
+              - name: service
+                type: text
+                tokenizer: raw
+                fast: true
+              - name: severity
+                type: u64
+                fast: true
+              - name: ts
+                type: i64
+                fast: true
+            "#;
+    let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &[])
+        .await
+        .unwrap();
+    test_sandbox
+        .add_documents(vec![
+            json!({"id": 0, "service": "web", "severity": 2, "ts": 10}),
+            json!({"id": 1, "service": "api", "severity": 1, "ts": 20}),
+            json!({"id": 2, "severity": 3, "ts": 30}),
+            json!({"id": 3, "service": "db", "severity": 2, "ts": 40}),
+        ])
+        .await
+        .unwrap();
+    test_sandbox
+        .add_documents(vec![
+            json!({"id": 4, "service": "api", "severity": 1, "ts": 5}),
+            json!({"id": 5, "service": "web", "severity": 2, "ts": 50}),
+            json!({"id": 6, "service": "api", "severity": 3, "ts": 60}),
+            json!({"id": 7, "service": "db", "severity": 2, "ts": 10}),
+        ])
+        .await
+        .unwrap();
+    let search_hits = |max_hits: u64, search_after: Option<PartialHit>| {
+        let search_request = SearchRequest {
+            index_id_patterns: vec![index_id.to_string()],
+            query_ast: serde_json::to_string(&QueryAst::MatchAll).unwrap(),
+            max_hits,
+            sort_fields: vec![
+                SortField {
+                    field_name: "service".to_string(),
+                    sort_order: SortOrder::Asc as i32,
+                    sort_datetime_format: None,
+                },
+                SortField {
+                    field_name: "severity".to_string(),
+                    sort_order: SortOrder::Desc as i32,
+                    sort_datetime_format: None,
+                },
+                SortField {
+                    field_name: "ts".to_string(),
+                    sort_order: SortOrder::Asc as i32,
+                    sort_datetime_format: None,
+                },
+            ],
+            search_after,
+            ..Default::default()
+        };
+        let metastore = test_sandbox.metastore();
+        let storage_resolver = test_sandbox.storage_resolver();
+        async move {
+            let search_resp = single_node_search(search_request, metastore, storage_resolver)
+                .await
+                .unwrap();
+            assert_eq!(search_resp.num_hits, 8);
+            search_resp
+                .hits
+                .into_iter()
+                .map(|hit| {
+                    let doc: JsonValue = serde_json::from_str(&hit.json).unwrap();
+                    (doc["id"].as_u64().unwrap(), hit.partial_hit.unwrap())
+                })
+                .collect::<Vec<(u64, PartialHit)>>()
+        }
+    };
+    // Documents without a service come last.
+    let expected_ids = [6, 4, 1, 7, 3, 0, 5, 2];
+    let hits = search_hits(1_000, None).await;
+    let ids: Vec<u64> = hits.iter().map(|(id, _)| *id).collect();
+    assert_eq!(ids, expected_ids);
+    assert_eq!(
+        hits[0].1.sort_values,
+        vec![
+            SortValue::Str("api".to_string()).into(),
+            SortValue::U64(3).into(),
+            SortValue::I64(60).into(),
+        ]
+    );
+
+    let mut ids = Vec::new();
+    let mut search_after = None;
+    loop {
+        let hits = search_hits(3, search_after).await;
+        let Some((_, last_partial_hit)) = hits.last() else {
+            break;
+        };
+        search_after = Some(last_partial_hit.clone());
+        ids.extend(hits.iter().map(|(id, _)| *id));
+    }
+    assert_eq!(ids, expected_ids);
+    test_sandbox.assert_quit().await;
+}
+
+#[tokio::test]
+async fn test_single_node_search_collapse() {
+    let index_id = "single-node-search-collapse";
+    let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: service
+                type: text
+                tokenizer: raw
+                fast: true
+              - name: ts
+                type: i64
+                fast: true
+              - name: id
+                type: u64
+            "#;
+    let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &[])
+        .await
+        .unwrap();
+    test_sandbox
+        .add_documents(vec![
+            json!({"id": 0, "service": "api", "ts": 10}),
+            json!({"id": 1, "service": "db", "ts": 60}),
+            json!({"id": 2, "service": "api", "ts": 30}),
+        ])
+        .await
+        .unwrap();
+    test_sandbox
+        .add_documents(vec![
+            json!({"id": 3, "service": "api", "ts": 40}),
+            json!({"id": 4, "ts": 50}),
+            json!({"id": 5, "service": "web", "ts": 20}),
+        ])
+        .await
+        .unwrap();
+    let search = |start_offset: u64, max_inner_hits: u32| {
+        let search_request = SearchRequest {
+            index_id_patterns: vec![index_id.to_string()],
+            query_ast: serde_json::to_string(&QueryAst::MatchAll).unwrap(),
+            max_hits: 3,
+            start_offset,
+            sort_fields: vec![SortField {
+                field_name: "ts".to_string(),
+                sort_order: SortOrder::Desc as i32,
+                sort_datetime_format: None,
+            }],
+            collapse: Some(CollapseOptions {
+                field_name: "service".to_string(),
+                max_inner_hits,
+            }),
+            ..Default::default()
+        };
+        single_node_search(
+            search_request,
+            test_sandbox.metastore(),
+            test_sandbox.storage_resolver(),
+        )
+    };
+    let doc_id = |hit: &Hit| {
+        let doc: JsonValue = serde_json::from_str(&hit.json).unwrap();
+        doc["id"].as_u64().unwrap()
+    };
+    let search_response = search(0, 0).await.unwrap();
+    assert_eq!(search_response.num_hits, 6);
+    let doc_ids: Vec<u64> = search_response.hits.iter().map(doc_id).collect();
+    assert_eq!(doc_ids, [1, 4, 3]);
+    assert!(search_response
+        .hits
+        .iter()
+        .all(|hit| hit.inner_hits.is_empty()));
+
+    let search_response = search(1, 2).await.unwrap();
+    let doc_ids: Vec<u64> = search_response.hits.iter().map(doc_id).collect();
+    assert_eq!(doc_ids, [4, 3, 5]);
+    let inner_doc_ids: Vec<Vec<u64>> = search_response
+        .hits
+        .iter()
+        .map(|hit| hit.inner_hits.iter().map(doc_id).collect())
+        .collect();
+    assert_eq!(inner_doc_ids, [vec![4], vec![3, 2], vec![5]]);
+    assert_eq!(
+        search_response.hits[1]
+            .partial_hit
+            .as_ref()
+            .unwrap()
+            .collapse_value
+            .as_deref(),
+        Some("api")
+    );
+
+    let search_response = single_node_search(
+        SearchRequest {
+            index_id_patterns: vec![index_id.to_string()],
+            query_ast: serde_json::to_string(&QueryAst::MatchAll).unwrap(),
+            max_hits: 3,
+            collapse: Some(CollapseOptions {
+                field_name: "id".to_string(),
+                max_inner_hits: 0,
+            }),
+            ..Default::default()
+        },
+        test_sandbox.metastore(),
+        test_sandbox.storage_resolver(),
+    )
+    .await;
+    assert_eq!(
+        search_response.unwrap_err().to_string(),
+        "Invalid argument: collapse field must be a fast text field, and the field `id` is not"
+    );
+    test_sandbox.assert_quit().await;
+}
+
 #[tokio::test]
 async fn test_single_node_invalid_sorting_with_query() {
     let index_id = "single-node-invalid-sorting";
@@ -939,7 +1349,7 @@ async fn test_single_node_invalid_sorting_with_query() {
         query_ast: qast_json_helper("city", &["description"]),
         max_hits: 15,
         sort_fields: vec![SortField {
-            field_name: "description".to_string(),
+            field_name: "temperature".to_string(),
             sort_order: SortOrder::Desc as i32,
             sort_datetime_format: None,
         }],
@@ -955,7 +1365,8 @@ async fn test_single_node_invalid_sorting_with_query() {
     let error_msg = single_node_response.unwrap_err().to_string();
     assert_eq!(
         error_msg,
-        "Invalid argument: sort by field on type text is currently not supported `description`"
+        "Invalid argument: sort by field must be a fast field, please add the fast property to \
+         your field `temperature`"
     );
     test_sandbox.assert_quit().await;
 }
@@ -1033,6 +1444,444 @@ async fn test_single_node_split_pruning_by_tags() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_single_node_search_timeout_returns_partial_results() -> anyhow::Result<()> {
+    let index_id = "single-node-search-timeout";
+    let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: body
+                type: text
+        "#;
+    let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &["body"]).await?;
+    test_sandbox
+        .add_documents(vec![json!({"body": "hello"})])
+        .await?;
+    test_sandbox
+        .add_documents(vec![json!({"body": "hello world"})])
+        .await?;
+    let search_request = SearchRequest {
+        index_id_patterns: vec![index_id.to_string()],
+        query_ast: qast_json_helper("hello", &["body"]),
+        max_hits: 10,
+        ..Default::default()
+    };
+    let single_node_result = single_node_search(
+        search_request.clone(),
+        test_sandbox.metastore(),
+        test_sandbox.storage_resolver(),
+    )
+    .await?;
+    assert_eq!(single_node_result.num_hits, 2);
+    assert!(!single_node_result.timed_out);
+    assert!(single_node_result.skipped_split_ids.is_empty());
+
+    // With a zero timeout, the deadline is reached before any split is searched.
+    let search_request = SearchRequest {
+        timeout_millis: Some(0),
+        ..search_request
+    };
+    let single_node_result = single_node_search(
+        search_request,
+        test_sandbox.metastore(),
+        test_sandbox.storage_resolver(),
+    )
+    .await?;
+    assert_eq!(single_node_result.num_hits, 0);
+    assert!(single_node_result.hits.is_empty());
+    assert!(single_node_result.timed_out);
+    assert_eq!(single_node_result.skipped_split_ids.len(), 2);
+    test_sandbox.assert_quit().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_leaf_search_cancelled() -> anyhow::Result<()> {
+    let index_id = "leaf-search-cancelled";
+    let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: body
+                type: text
+        "#;
+    let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &["body"]).await?;
+    test_sandbox
+        .add_documents(vec![json!({"body": "hello"})])
+        .await?;
+    test_sandbox
+        .add_documents(vec![json!({"body": "hello world"})])
+        .await?;
+    let splits_offsets: Vec<SplitIdAndFooterOffsets> = test_sandbox
+        .metastore()
+        .list_splits(ListSplitsRequest::try_from_index_uid(test_sandbox.index_uid()).unwrap())
+        .await?
+        .collect_splits()
+        .await?
+        .iter()
+        .map(|split| extract_split_and_footer_offsets(&split.split_metadata))
+        .collect();
+    assert_eq!(splits_offsets.len(), 2);
+
+    let request = Arc::new(SearchRequest {
+        index_id_patterns: vec![index_id.to_string()],
+        query_ast: qast_json_helper("hello", &["body"]),
+        max_hits: 10,
+        ..Default::default()
+    });
+    let searcher_context: Arc<SearcherContext> =
+        Arc::new(SearcherContext::new(SearcherConfig::default(), None));
+    let cancellation_token = CancellationToken::new();
+    cancellation_token.cancel();
+
+    let leaf_search_response = leaf_search(
+        searcher_context,
+        request,
+        test_sandbox.storage(),
+        splits_offsets,
+        test_sandbox.doc_mapper(),
+        cancellation_token,
+    )
+    .await?;
+    // No split is searched once the request is cancelled and none of them is reported as failed.
+    assert_eq!(leaf_search_response.num_attempted_splits, 0);
+    assert_eq!(leaf_search_response.num_hits, 0);
+    assert!(leaf_search_response.partial_hits.is_empty());
+    assert!(leaf_search_response.failed_splits.is_empty());
+    test_sandbox.assert_quit().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_single_node_search_profile() -> anyhow::Result<()> {
+    let index_id = "single-node-search-profile";
+    let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: body
+                type: text
+        "#;
+    let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &["body"]).await?;
+    test_sandbox
+        .add_documents(vec![json!({"body": "hello"}), json!({"body": "bye"})])
+        .await?;
+    test_sandbox
+        .add_documents(vec![json!({"body": "hello world"})])
+        .await?;
+    let search_request = SearchRequest {
+        index_id_patterns: vec![index_id.to_string()],
+        query_ast: qast_json_helper("hello", &[]),
+        max_hits: 10,
+        ..Default::default()
+    };
+    let single_node_result = single_node_search(
+        search_request.clone(),
+        test_sandbox.metastore(),
+        test_sandbox.storage_resolver(),
+    )
+    .await?;
+    assert!(single_node_result.profile.is_none());
+
+    let search_request = SearchRequest {
+        profile: true,
+        ..search_request
+    };
+    let single_node_result = single_node_search(
+        search_request,
+        test_sandbox.metastore(),
+        test_sandbox.storage_resolver(),
+    )
+    .await?;
+    assert_eq!(single_node_result.num_hits, 2);
+    let search_profile = single_node_result.profile.unwrap();
+    // The query AST sent to the leaves has the default search fields resolved.
+    assert!(search_profile.query_ast.contains("body"));
+    assert_eq!(search_profile.num_docs_matched, 2);
+    assert_eq!(search_profile.split_profiles.len(), 2);
+    assert_eq!(
+        search_profile.num_cache_misses,
+        search_profile
+            .split_profiles
+            .iter()
+            .map(|split_profile| split_profile.num_cache_misses)
+            .sum::<u64>()
+    );
+    for split_profile in &search_profile.split_profiles {
+        assert!(!split_profile.leaf_search_cache_hit);
+        assert_eq!(split_profile.num_docs_matched, 1);
+        assert!(!split_profile.tantivy_query.is_empty());
+    }
+    test_sandbox.assert_quit().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_single_node_count_without_searching_splits() -> anyhow::Result<()> {
+    let index_id = "single-node-count-without-searching-splits";
+    let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: body
+                type: text
+              - name: service
+                type: text
+                tokenizer: raw
+              - name: ts
+                type: datetime
+                input_formats:
+                    - "unix_timestamp"
+                fast: true
+            timestamp_field: ts
+        "#;
+    let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &["body"]).await?;
+    test_sandbox
+        .add_documents(vec![
+            json!({"body": "hello", "service": "api", "ts": 10}),
+            json!({"body": "hello", "service": "web", "ts": 20}),
+        ])
+        .await?;
+    test_sandbox
+        .add_documents(vec![
+            json!({"body": "hello", "service": "api", "ts": 30}),
+            json!({"body": "bye", "service": "api", "ts": 40}),
+        ])
+        .await?;
+    let search_service = single_node_search_service(&test_sandbox);
+
+    // The split profiles tell us which splits were actually searched.
+    let search_request = SearchRequest {
+        index_id_patterns: vec![index_id.to_string()],
+        query_ast: serde_json::to_string(&QueryAst::MatchAll)?,
+        max_hits: 0,
+        profile: true,
+        ..Default::default()
+    };
+    let search_response = search_service.root_search(search_request.clone()).await?;
+    assert_eq!(search_response.num_hits, 4);
+    assert!(search_response.profile.unwrap().split_profiles.is_empty());
+
+    // The first split is fully covered by the time range, the second one is not.
+    let search_response = search_service
+        .root_search(SearchRequest {
+            start_timestamp: Some(0),
+            end_timestamp: Some(35),
+            ..search_request.clone()
+        })
+        .await?;
+    assert_eq!(search_response.num_hits, 3);
+    assert_eq!(search_response.profile.unwrap().split_profiles.len(), 1);
+
+    let search_response = search_service
+        .root_search(SearchRequest {
+            query_ast: qast_json_helper("ts:[1970-01-01T00:00:00Z TO 1970-01-01T00:00:35Z}", &[]),
+            ..search_request.clone()
+        })
+        .await?;
+    assert_eq!(search_response.num_hits, 3);
+    assert_eq!(search_response.profile.unwrap().split_profiles.len(), 1);
+
+    // Hits are still searched when requested.
+    let search_response = search_service
+        .root_search(SearchRequest {
+            max_hits: 10,
+            ..search_request.clone()
+        })
+        .await?;
+    assert_eq!(search_response.num_hits, 4);
+    assert_eq!(search_response.hits.len(), 4);
+    assert_eq!(search_response.profile.unwrap().split_profiles.len(), 2);
+
+    // Term queries are counted from the term dictionary, without warming up the postings.
+    let search_response = search_service
+        .root_search(SearchRequest {
+            query_ast: serde_json::to_string(&QueryAst::from(TermQuery {
+                field: "service".to_string(),
+                value: "api".to_string(),
+            }))?,
+            ..search_request
+        })
+        .await?;
+    assert_eq!(search_response.num_hits, 3);
+    let mut split_profiles = search_response.profile.unwrap().split_profiles;
+    split_profiles.sort_by_key(|split_profile| split_profile.num_docs_matched);
+    assert_eq!(split_profiles.len(), 2);
+    assert_eq!(split_profiles[0].num_docs_matched, 1);
+    assert_eq!(split_profiles[1].num_docs_matched, 2);
+    for split_profile in &split_profiles {
+        assert_eq!(split_profile.warmup_micros, 0);
+    }
+    test_sandbox.assert_quit().await;
+    Ok(())
+}
+
+/// Builds the search service of a single node cluster, for the tests that go through the
+/// `SearchService` API rather than `single_node_search`.
+fn single_node_search_service(test_sandbox: &TestSandbox) -> Arc<SearchServiceImpl> {
+    let socket_addr = SocketAddr::new(Ipv4Addr::new(127, 0, 0, 1).into(), 7280u16);
+    let searcher_pool = SearcherPool::default();
+    let cluster_client = ClusterClient::new(SearchJobPlacer::new(searcher_pool.clone()));
+    let searcher_context = Arc::new(SearcherContext::new(SearcherConfig::default(), None));
+    let search_service = Arc::new(SearchServiceImpl::new(
+        test_sandbox.metastore(),
+        test_sandbox.storage_resolver(),
+        cluster_client,
+        searcher_context,
+    ));
+    searcher_pool.insert(
+        socket_addr,
+        SearchServiceClient::from_service(search_service.clone(), socket_addr),
+    );
+    search_service
+}
+
+#[tokio::test]
+async fn test_single_node_async_search() -> anyhow::Result<()> {
+    let index_id = "single-node-async-search";
+    let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: body
+                type: text
+              - name: count
+                type: u64
+                fast: true
+        "#;
+    let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &["body"]).await?;
+    test_sandbox
+        .add_documents(vec![
+            json!({"body": "hello", "count": 1}),
+            json!({"body": "bye", "count": 2}),
+        ])
+        .await?;
+    test_sandbox
+        .add_documents(vec![json!({"body": "hello world", "count": 3})])
+        .await?;
+
+    let search_service = single_node_search_service(&test_sandbox);
+    let search_request = SearchRequest {
+        index_id_patterns: vec![index_id.to_string()],
+        query_ast: qast_json_helper("hello", &[]),
+        max_hits: 10,
+        aggregation_request: Some(r#"{"total": {"sum": {"field": "count"}}}"#.to_string()),
+        ..Default::default()
+    };
+    let async_search_response = search_service
+        .submit_async_search(search_request, Duration::from_secs(60))
+        .await?;
+    assert!(async_search_response.is_running);
+    let async_search_id = async_search_response.id;
+
+    let async_search_response = loop {
+        let async_search_response = search_service
+            .get_async_search(async_search_id.clone())
+            .await?;
+        if !async_search_response.is_running {
+            break async_search_response;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    };
+    assert!(!async_search_response.is_partial);
+    assert!(async_search_response.error.is_none());
+    let search_response = async_search_response.response.unwrap();
+    assert_eq!(search_response.num_hits, 2);
+    assert_eq!(search_response.hits.len(), 2);
+    let aggregation: JsonValue = serde_json::from_str(&search_response.aggregation.unwrap())?;
+    assert_eq!(aggregation["total"]["value"], 4.0);
+
+    search_service
+        .delete_async_search(async_search_id.clone())
+        .await?;
+    let error = search_service
+        .get_async_search(async_search_id)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, SearchError::AsyncSearchNotFound(_)));
+
+    let error = search_service
+        .get_async_search(Ulid::new().to_string())
+        .await
+        .unwrap_err();
+    assert!(matches!(error, SearchError::AsyncSearchNotFound(_)));
+    test_sandbox.assert_quit().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_single_node_search_partial_aggregation_cache() -> anyhow::Result<()> {
+    let index_id = "single-node-search-partial-aggregation-cache";
+    let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: body
+                type: text
+              - name: ts
+                type: datetime
+                input_formats:
+                    - "unix_timestamp"
+                fast: true
+              - name: count
+                type: u64
+                fast: true
+            timestamp_field: ts
+        "#;
+    let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &["body"]).await?;
+    test_sandbox
+        .add_documents(vec![
+            json!({"body": "hello", "ts": 10, "count": 1}),
+            json!({"body": "hello", "ts": 20, "count": 2}),
+        ])
+        .await?;
+    test_sandbox
+        .add_documents(vec![
+            json!({"body": "hello", "ts": 30, "count": 3}),
+            json!({"body": "hello", "ts": 40, "count": 4}),
+        ])
+        .await?;
+    let search_service = single_node_search_service(&test_sandbox);
+
+    // The split profiles tell us which splits were actually searched.
+    let search_request = SearchRequest {
+        index_id_patterns: vec![index_id.to_string()],
+        query_ast: qast_json_helper("hello", &[]),
+        max_hits: 0,
+        aggregation_request: Some(r#"{"total": {"sum": {"field": "count"}}}"#.to_string()),
+        profile: true,
+        ..Default::default()
+    };
+    let search_response = search_service.root_search(search_request.clone()).await?;
+    assert_eq!(search_response.num_hits, 4);
+    assert_eq!(search_response.profile.unwrap().split_profiles.len(), 2);
+    let aggregation: JsonValue = serde_json::from_str(&search_response.aggregation.unwrap())?;
+    assert_eq!(aggregation["total"]["value"], 10.0);
+
+    // Both splits are served from the cache, once it was stored in the background.
+    let mut search_response = search_service.root_search(search_request.clone()).await?;
+    for _ in 0..10 {
+        if search_response
+            .profile
+            .as_ref()
+            .unwrap()
+            .split_profiles
+            .is_empty()
+        {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        search_response = search_service.root_search(search_request.clone()).await?;
+    }
+    assert_eq!(search_response.num_hits, 4);
+    assert!(search_response.profile.unwrap().split_profiles.is_empty());
+    let aggregation: JsonValue = serde_json::from_str(&search_response.aggregation.unwrap())?;
+    assert_eq!(aggregation["total"]["value"], 10.0);
+
+    // The first split is fully covered by the time range, the second one is not.
+    let search_request = SearchRequest {
+        start_timestamp: Some(0),
+        end_timestamp: Some(35),
+        ..search_request
+    };
+    let search_response = search_service.root_search(search_request).await?;
+    assert_eq!(search_response.num_hits, 3);
+    assert_eq!(search_response.profile.unwrap().split_profiles.len(), 1);
+    let aggregation: JsonValue = serde_json::from_str(&search_response.aggregation.unwrap())?;
+    assert_eq!(aggregation["total"]["value"], 6.0);
+    test_sandbox.assert_quit().await;
+    Ok(())
+}
+
 async fn test_search_util(test_sandbox: &TestSandbox, query: &str) -> Vec<u32> {
     let splits = test_sandbox
         .metastore()
@@ -1060,6 +1909,7 @@ async fn test_search_util(test_sandbox: &TestSandbox, query: &str) -> Vec<u32> {
         test_sandbox.storage(),
         splits_offsets,
         test_sandbox.doc_mapper(),
+        CancellationToken::new(),
     )
     .await
     .unwrap();
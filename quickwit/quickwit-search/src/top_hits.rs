@@ -0,0 +1,492 @@
+// Copyright (C) 2024 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use quickwit_proto::search::{SortField, SortOrder};
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use tantivy::aggregation::agg_req::Aggregations;
+
+use crate::collector::QuickwitAggregations;
+use crate::SearchError;
+
+const TOP_HITS: &str = "top_hits";
+
+const SUB_AGGREGATIONS_KEYS: [&str; 2] = ["aggs", "aggregations"];
+
+/// Maximum value of `from + size` of a `top_hits` aggregation. This is the default value of the
+/// `index.max_inner_result_window` setting of Elasticsearch.
+const MAX_TOP_HITS_WINDOW: usize = 100;
+
+/// A `top_hits` aggregation, returning the best hits of the documents matching the query, or of
+/// each bucket of its parent `terms` aggregation.
+///
+/// Tantivy does not support this aggregation, so it is extracted from the aggregation request and
+/// computed by Quickwit: the leaves collect the best hits of each group of documents, the root
+/// merges them and fetches the documents of the hits making it to the final result.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct TopHitsAggregation {
+    pub name: String,
+    /// The parent `terms` aggregation, if the aggregation is not at the top level.
+    pub terms_opt: Option<TopHitsTerms>,
+    pub from: usize,
+    pub size: usize,
+    /// The hits are sorted by doc ID if empty.
+    pub sort_fields: Vec<SortField>,
+}
+
+/// The parent `terms` aggregation of a `top_hits` aggregation.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct TopHitsTerms {
+    pub name: String,
+    pub field_name: String,
+    /// Number of terms a segment keeps the top hits of, the ones with the highest doc counts, as
+    /// the `terms` aggregation only returns those. `None` if the buckets are not ordered by doc
+    /// count, in which case the top hits of all the terms are kept.
+    pub segment_size_opt: Option<usize>,
+}
+
+impl TopHitsAggregation {
+    /// Number of hits to collect for each group of documents.
+    pub fn num_hits_per_group(&self) -> usize {
+        self.from + self.size
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TopHitsParams {
+    #[serde(default)]
+    from: usize,
+    #[serde(default = "default_top_hits_size")]
+    size: usize,
+    #[serde(default)]
+    sort: Option<JsonValue>,
+    // Accepted for compatibility with Elasticsearch clients, but ignored: the hits always carry
+    // their whole source.
+    #[serde(default, rename = "_source")]
+    _source: IgnoredAny,
+    #[serde(default, rename = "stored_fields")]
+    _stored_fields: IgnoredAny,
+}
+
+fn default_top_hits_size() -> usize {
+    3
+}
+
+#[derive(Deserialize)]
+struct TermsParams {
+    field: String,
+    #[serde(default)]
+    size: Option<usize>,
+    #[serde(default, alias = "shard_size", alias = "split_size")]
+    segment_size: Option<usize>,
+    #[serde(default)]
+    order: Option<JsonValue>,
+    #[serde(default)]
+    missing: Option<JsonValue>,
+}
+
+fn invalid_top_hits_error(message: impl ToString) -> SearchError {
+    SearchError::InvalidAggregationRequest(message.to_string())
+}
+
+/// Returns false if the aggregation request does not contain any `top_hits` aggregation, without
+/// parsing it.
+pub(crate) fn may_contain_top_hits_aggregation(aggregation_request: &str) -> bool {
+    aggregation_request.contains(TOP_HITS)
+}
+
+/// Parses an aggregation request, extracting its `top_hits` aggregation, if any, from the
+/// aggregations run by tantivy. Only one `top_hits` aggregation is supported, either at the top
+/// level or as a sub-aggregation of a top level `terms` aggregation.
+pub(crate) fn parse_aggregation_request(
+    aggregation_request: &str,
+) -> crate::Result<(Option<QuickwitAggregations>, Option<TopHitsAggregation>)> {
+    // Most aggregation requests do not contain any `top_hits` aggregation.
+    if !may_contain_top_hits_aggregation(aggregation_request) {
+        let aggregations = parse_quickwit_aggregations(aggregation_request)?;
+        return Ok((Some(aggregations), None));
+    }
+    let mut aggregations_json: JsonMap<String, JsonValue> =
+        serde_json::from_str(aggregation_request).map_err(invalid_top_hits_error)?;
+    let top_hits_opt = extract_top_hits_aggregation(&mut aggregations_json)?;
+
+    if aggregations_json.is_empty() {
+        return Ok((None, top_hits_opt));
+    }
+    let aggregations = parse_quickwit_aggregations(&serde_json::to_string(&aggregations_json)?)?;
+    Ok((Some(aggregations), top_hits_opt))
+}
+
+fn parse_quickwit_aggregations(aggregation_request: &str) -> crate::Result<QuickwitAggregations> {
+    serde_json::from_str(aggregation_request).map_err(|_err| {
+        let err = serde_json::from_str::<Aggregations>(aggregation_request).unwrap_err();
+        SearchError::InvalidAggregationRequest(err.to_string())
+    })
+}
+
+fn extract_top_hits_aggregation(
+    aggregations_json: &mut JsonMap<String, JsonValue>,
+) -> crate::Result<Option<TopHitsAggregation>> {
+    let mut top_hits_aggregations = Vec::new();
+
+    for (name, params_json) in remove_top_hits_aggregations(aggregations_json)? {
+        top_hits_aggregations.push(parse_top_hits_aggregation(name, None, params_json)?);
+    }
+    for (name, aggregation_json) in aggregations_json.iter_mut() {
+        let Some(aggregation_json) = aggregation_json.as_object_mut() else {
+            continue;
+        };
+        let Some(terms_json) = aggregation_json.get("terms").cloned() else {
+            continue;
+        };
+        for sub_aggregations_key in SUB_AGGREGATIONS_KEYS {
+            let Some(JsonValue::Object(sub_aggregations_json)) =
+                aggregation_json.get_mut(sub_aggregations_key)
+            else {
+                continue;
+            };
+            let sub_top_hits = remove_top_hits_aggregations(sub_aggregations_json)?;
+
+            if sub_top_hits.is_empty() {
+                continue;
+            }
+            if sub_aggregations_json.is_empty() {
+                aggregation_json.remove(sub_aggregations_key);
+            }
+            let terms = parse_top_hits_terms(name.clone(), terms_json.clone())?;
+
+            for (sub_name, params_json) in sub_top_hits {
+                top_hits_aggregations.push(parse_top_hits_aggregation(
+                    sub_name,
+                    Some(terms.clone()),
+                    params_json,
+                )?);
+            }
+        }
+    }
+    if contains_top_hits_aggregation(aggregations_json) {
+        return Err(invalid_top_hits_error(
+            "`top_hits` aggregations are only supported at the top level or as a sub-aggregation \
+             of a top level `terms` aggregation",
+        ));
+    }
+    if top_hits_aggregations.len() > 1 {
+        return Err(invalid_top_hits_error(
+            "only one `top_hits` aggregation per request is supported",
+        ));
+    }
+    Ok(top_hits_aggregations.pop())
+}
+
+/// Removes the `top_hits` aggregations from the aggregations and returns their names and
+/// parameters.
+fn remove_top_hits_aggregations(
+    aggregations_json: &mut JsonMap<String, JsonValue>,
+) -> crate::Result<Vec<(String, JsonValue)>> {
+    let top_hits_names: Vec<String> = aggregations_json
+        .iter()
+        .filter(|(_, aggregation_json)| aggregation_json.get(TOP_HITS).is_some())
+        .map(|(name, _)| name.clone())
+        .collect();
+    let mut top_hits_aggregations = Vec::with_capacity(top_hits_names.len());
+
+    for name in top_hits_names {
+        let Some(JsonValue::Object(mut aggregation_json)) = aggregations_json.remove(&name) else {
+            continue;
+        };
+        if aggregation_json.len() > 1 {
+            return Err(invalid_top_hits_error(format!(
+                "`top_hits` aggregation `{name}` cannot have sub-aggregations or other parameters \
+                 than `top_hits`"
+            )));
+        }
+        let params_json = aggregation_json
+            .remove(TOP_HITS)
+            .expect("aggregation should be a `top_hits` aggregation");
+        top_hits_aggregations.push((name, params_json));
+    }
+    Ok(top_hits_aggregations)
+}
+
+fn contains_top_hits_aggregation(aggregations_json: &JsonMap<String, JsonValue>) -> bool {
+    aggregations_json.values().any(|aggregation_json| {
+        if aggregation_json.get(TOP_HITS).is_some() {
+            return true;
+        }
+        SUB_AGGREGATIONS_KEYS.iter().any(|sub_aggregations_key| {
+            matches!(
+                aggregation_json.get(sub_aggregations_key),
+                Some(JsonValue::Object(sub_aggregations_json))
+                    if contains_top_hits_aggregation(sub_aggregations_json)
+            )
+        })
+    })
+}
+
+fn parse_top_hits_aggregation(
+    name: String,
+    terms_opt: Option<TopHitsTerms>,
+    params_json: JsonValue,
+) -> crate::Result<TopHitsAggregation> {
+    let params: TopHitsParams = serde_json::from_value(params_json).map_err(|err| {
+        invalid_top_hits_error(format!("invalid `top_hits` aggregation `{name}`: {err}"))
+    })?;
+    let window_opt = params.from.checked_add(params.size);
+    if window_opt.map_or(true, |window| window > MAX_TOP_HITS_WINDOW) {
+        return Err(invalid_top_hits_error(format!(
+            "`from` + `size` of `top_hits` aggregation `{name}` must be less than or equal to \
+             {MAX_TOP_HITS_WINDOW}"
+        )));
+    }
+    let sort_json_values = match params.sort {
+        Some(JsonValue::Array(sort_json_values)) => sort_json_values,
+        Some(sort_json_value) => vec![sort_json_value],
+        None => Vec::new(),
+    };
+    let sort_fields = sort_json_values
+        .into_iter()
+        .map(parse_sort_field)
+        .collect::<Option<Vec<SortField>>>()
+        .ok_or_else(|| {
+            invalid_top_hits_error(format!(
+                "invalid `sort` of `top_hits` aggregation `{name}`, expected a field name or an \
+                 object such as `{{\"field\": {{\"order\": \"desc\"}}}}`"
+            ))
+        })?;
+    Ok(TopHitsAggregation {
+        name,
+        terms_opt,
+        from: params.from,
+        size: params.size,
+        sort_fields,
+    })
+}
+
+/// Parses a sort field in one of the forms supported by Elasticsearch: `"field"`,
+/// `{"field": "desc"}` or `{"field": {"order": "desc"}}`.
+fn parse_sort_field(sort_json_value: JsonValue) -> Option<SortField> {
+    let (field_name, sort_order) = match sort_json_value {
+        JsonValue::String(field_name) => {
+            // As in Elasticsearch, the score is sorted in descending order by default.
+            let sort_order = if field_name == "_score" {
+                SortOrder::Desc
+            } else {
+                SortOrder::Asc
+            };
+            (field_name, sort_order)
+        }
+        JsonValue::Object(sort_json_object) if sort_json_object.len() == 1 => {
+            let (field_name, order_json_value) = sort_json_object.into_iter().next()?;
+            let order_json_value = match order_json_value {
+                JsonValue::Object(mut order_json_object) if order_json_object.len() == 1 => {
+                    order_json_object.remove("order")?
+                }
+                order_json_value => order_json_value,
+            };
+            let sort_order = match order_json_value.as_str()? {
+                "asc" => SortOrder::Asc,
+                "desc" => SortOrder::Desc,
+                _ => return None,
+            };
+            (field_name, sort_order)
+        }
+        _ => return None,
+    };
+    Some(SortField {
+        field_name,
+        sort_order: sort_order as i32,
+        sort_datetime_format: None,
+    })
+}
+
+fn parse_top_hits_terms(name: String, terms_json: JsonValue) -> crate::Result<TopHitsTerms> {
+    let params: TermsParams = serde_json::from_value(terms_json).map_err(|err| {
+        invalid_top_hits_error(format!("invalid `terms` aggregation `{name}`: {err}"))
+    })?;
+    if params.missing.is_some() {
+        return Err(invalid_top_hits_error(format!(
+            "the `missing` parameter of `terms` aggregation `{name}` is not supported along with \
+             a `top_hits` sub-aggregation"
+        )));
+    }
+    let is_ordered_by_doc_count = match &params.order {
+        None => true,
+        Some(order_json) => *order_json == serde_json::json!({"_count": "desc"}),
+    };
+    // Same defaults as tantivy's `terms` aggregation.
+    let size = params.size.unwrap_or(10);
+    let segment_size_opt = if is_ordered_by_doc_count {
+        Some(
+            params
+                .segment_size
+                .unwrap_or(size.saturating_mul(10))
+                .max(size)
+                .max(1),
+        )
+    } else {
+        None
+    };
+    Ok(TopHitsTerms {
+        name,
+        field_name: params.field,
+        segment_size_opt,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(
+        aggregation_request: &str,
+    ) -> (Option<QuickwitAggregations>, Option<TopHitsAggregation>) {
+        parse_aggregation_request(aggregation_request).unwrap()
+    }
+
+    fn parse_err(aggregation_request: &str) -> String {
+        match parse_aggregation_request(aggregation_request).unwrap_err() {
+            SearchError::InvalidAggregationRequest(message) => message,
+            error => panic!("unexpected error: {error:?}"),
+        }
+    }
+
+    fn sort_field(field_name: &str, sort_order: SortOrder) -> SortField {
+        SortField {
+            field_name: field_name.to_string(),
+            sort_order: sort_order as i32,
+            sort_datetime_format: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_aggregation_request_without_top_hits() {
+        let (aggregations_opt, top_hits_opt) =
+            parse(r#"{"services": {"terms": {"field": "service"}}}"#);
+        assert!(matches!(
+            aggregations_opt,
+            Some(QuickwitAggregations::TantivyAggregations(_))
+        ));
+        assert!(top_hits_opt.is_none());
+    }
+
+    #[test]
+    fn test_parse_top_level_top_hits_aggregation() {
+        let (aggregations_opt, top_hits_opt) =
+            parse(r#"{"latest": {"top_hits": {"sort": [{"ts": "desc"}, "_score", "service"]}}}"#);
+        assert!(aggregations_opt.is_none());
+        assert_eq!(
+            top_hits_opt.unwrap(),
+            TopHitsAggregation {
+                name: "latest".to_string(),
+                terms_opt: None,
+                from: 0,
+                size: 3,
+                sort_fields: vec![
+                    sort_field("ts", SortOrder::Desc),
+                    sort_field("_score", SortOrder::Desc),
+                    sort_field("service", SortOrder::Asc),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_top_hits_sub_aggregation() {
+        let (aggregations_opt, top_hits_opt) = parse(
+            r#"{
+                "services": {
+                    "terms": {"field": "service", "size": 5},
+                    "aggs": {
+                        "latest": {"top_hits": {"from": 1, "size": 2, "sort": {"ts": {"order": "asc"}}}},
+                        "max_ts": {"max": {"field": "ts"}}
+                    }
+                },
+                "count": {"value_count": {"field": "ts"}}
+            }"#,
+        );
+        let Some(QuickwitAggregations::TantivyAggregations(aggregations)) = aggregations_opt else {
+            panic!("expected tantivy aggregations");
+        };
+        assert_eq!(aggregations.len(), 2);
+        assert_eq!(
+            top_hits_opt.unwrap(),
+            TopHitsAggregation {
+                name: "latest".to_string(),
+                terms_opt: Some(TopHitsTerms {
+                    name: "services".to_string(),
+                    field_name: "service".to_string(),
+                    segment_size_opt: Some(50),
+                }),
+                from: 1,
+                size: 2,
+                sort_fields: vec![sort_field("ts", SortOrder::Asc)],
+            }
+        );
+        // `_source` and `stored_fields` are accepted but ignored.
+        let (_, top_hits_opt) = parse(
+            r#"{"latest": {"top_hits": {"size": 1, "_source": false, "stored_fields": ["ts"]}}}"#,
+        );
+        assert_eq!(top_hits_opt.unwrap().size, 1);
+        // The hits of all the terms are kept if the buckets are not ordered by doc count.
+        let (_, top_hits_opt) = parse(
+            r#"{"services": {"terms": {"field": "service", "order": {"_key": "asc"}},
+                "aggs": {"latest": {"top_hits": {}}}}}"#,
+        );
+        assert_eq!(
+            top_hits_opt.unwrap().terms_opt.unwrap().segment_size_opt,
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_top_hits_aggregation() {
+        assert!(parse_err(
+            r#"{"histo": {"histogram": {"field": "ts", "interval": 10},
+                "aggs": {"latest": {"top_hits": {}}}}}"#
+        )
+        .contains("only supported at the top level"));
+        assert!(
+            parse_err(r#"{"latest": {"top_hits": {}}, "oldest": {"top_hits": {}}}"#)
+                .contains("only one `top_hits` aggregation")
+        );
+        assert!(parse_err(r#"{"latest": {"top_hits": {"size": 101}}}"#)
+            .contains("must be less than or equal to 100"));
+        assert!(parse_err(
+            r#"{"latest": {"top_hits": {"from": 18446744073709551615, "size": 1}}}"#
+        )
+        .contains("must be less than or equal to 100"));
+        assert!(parse_err(r#"{"latest": {"top_hits": {"explain": true}}}"#)
+            .contains("invalid `top_hits` aggregation `latest`"));
+        assert!(
+            parse_err(r#"{"latest": {"top_hits": {"sort": [{"ts": "up"}]}}}"#)
+                .contains("invalid `sort`")
+        );
+        assert!(parse_err(
+            r#"{"services": {"terms": {"field": "service", "missing": "none"},
+                "aggs": {"latest": {"top_hits": {}}}}}"#
+        )
+        .contains("`missing`"));
+        assert!(
+            parse_err(r#"{"latest": {"top_hits": {}}, "count": {"unknown": {}}}"#)
+                .contains("unknown")
+        );
+    }
+}
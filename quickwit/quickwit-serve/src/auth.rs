@@ -0,0 +1,881 @@
+// Copyright (C) 2024 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures::future;
+use hyper::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+use hyper::http::HeaderValue;
+use hyper::{Body, Method, Request, Response};
+use itertools::Itertools;
+use percent_encoding::percent_decode_str;
+use quickwit_common::tower::BoxFuture;
+use quickwit_config::{ApiKeyConfig, ApiKeyPermission};
+use quickwit_opentelemetry::otlp::{
+    OtelSignal, OTEL_LOGS_INDEX_ID, OTEL_METRICS_INDEX_ID, OTEL_TRACES_INDEX_ID,
+    OTEL_TRACES_INDEX_ID_PATTERN,
+};
+use quickwit_proto::tonic::service::Interceptor;
+use quickwit_proto::{tonic, ServiceError, ServiceErrorCode};
+use regex::RegexSet;
+use tower::{Layer, Service};
+use tracing::{info, warn};
+use warp::{Filter, Reply};
+
+use crate::json_api_response::make_json_api_response;
+use crate::BodyFormat;
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum AuthError {
+    #[error("missing API key: pass it in the `Authorization` header with the `Bearer` scheme")]
+    MissingApiKey,
+    #[error("invalid API key")]
+    InvalidApiKey,
+    #[error("API key `{api_key_name}` does not grant the `{permission}` permission")]
+    PermissionDenied {
+        api_key_name: String,
+        permission: ApiKeyPermission,
+    },
+    #[error(
+        "API key `{api_key_name}` does not grant the `{permission}` permission on index \
+         `{index_id_pattern}`"
+    )]
+    IndexPermissionDenied {
+        api_key_name: String,
+        permission: ApiKeyPermission,
+        index_id_pattern: String,
+    },
+}
+
+impl ServiceError for AuthError {
+    fn error_code(&self) -> ServiceErrorCode {
+        match self {
+            Self::MissingApiKey | Self::InvalidApiKey => ServiceErrorCode::Unauthenticated,
+            Self::PermissionDenied { .. } | Self::IndexPermissionDenied { .. } => {
+                ServiceErrorCode::Forbidden
+            }
+        }
+    }
+}
+
+struct ApiKey {
+    name: String,
+    key: String,
+    permissions: Vec<ApiKeyPermission>,
+    index_id_patterns: RegexSet,
+}
+
+/// API key a request was authenticated with. [`ApiKeyAuthLayer`] adds it to the extensions of
+/// the request so that the handlers of the requests targeting indexes listed in their body can
+/// check them.
+#[derive(Clone)]
+pub(crate) struct AuthenticatedApiKey(Arc<ApiKey>);
+
+impl AuthenticatedApiKey {
+    pub fn try_new(api_key_config: &ApiKeyConfig) -> anyhow::Result<Self> {
+        let regexes = api_key_config
+            .index_id_patterns
+            .iter()
+            .map(|index_id_pattern| {
+                format!(
+                    "^{}$",
+                    index_id_pattern.split('*').map(regex::escape).join(".*")
+                )
+            });
+        let api_key = ApiKey {
+            name: api_key_config.name.clone(),
+            key: api_key_config.key.clone(),
+            permissions: api_key_config.permissions.clone(),
+            index_id_patterns: RegexSet::new(regexes)?,
+        };
+        Ok(Self(Arc::new(api_key)))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    fn check_permission(&self, permission: ApiKeyPermission) -> Result<(), AuthError> {
+        if self.0.permissions.contains(&ApiKeyPermission::Admin)
+            || self.0.permissions.contains(&permission)
+        {
+            return Ok(());
+        }
+        Err(AuthError::PermissionDenied {
+            api_key_name: self.0.name.clone(),
+            permission,
+        })
+    }
+
+    /// Checks that the key grants `permission` on the indexes targeted by `index_id_patterns`, a
+    /// comma-separated list of index IDs or patterns.
+    fn check_index_permission(
+        &self,
+        permission: ApiKeyPermission,
+        index_id_patterns: &str,
+    ) -> Result<(), AuthError> {
+        self.check_permission(permission)?;
+
+        for index_id_pattern in index_id_patterns.split(',') {
+            // The requested pattern is matched as a literal string, so its `*` chars can only be
+            // matched by the `*` of the patterns of the key: it cannot target more indexes.
+            if !self.0.index_id_patterns.is_match(index_id_pattern) {
+                return Err(AuthError::IndexPermissionDenied {
+                    api_key_name: self.0.name.clone(),
+                    permission,
+                    index_id_pattern: index_id_pattern.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Extracts the API key the request was authenticated with, if any.
+pub(crate) fn api_key_filter(
+) -> impl Filter<Extract = (Option<AuthenticatedApiKey>,), Error = Infallible> + Clone {
+    warp::ext::optional::<AuthenticatedApiKey>()
+}
+
+/// Checks that the API key of a request grants `permission` on `index_id_patterns`. Requests do not
+/// have an API key when authentication is disabled.
+pub(crate) fn check_index_permission(
+    api_key_opt: Option<&AuthenticatedApiKey>,
+    permission: ApiKeyPermission,
+    index_id_patterns: &str,
+) -> Result<(), AuthError> {
+    if let Some(api_key) = api_key_opt {
+        api_key.check_index_permission(permission, index_id_patterns)?;
+    }
+    Ok(())
+}
+
+/// Access a request to the REST API requires.
+#[derive(Debug, Eq, PartialEq)]
+struct RequiredAccess {
+    /// `None` if any valid API key is accepted.
+    permission_opt: Option<ApiKeyPermission>,
+    /// Comma-separated list of the index IDs or patterns the request targets. `None` if the
+    /// request does not target specific indexes, or if its handler checks them.
+    index_id_patterns_opt: Option<String>,
+}
+
+impl RequiredAccess {
+    fn authenticated() -> Self {
+        Self {
+            permission_opt: None,
+            index_id_patterns_opt: None,
+        }
+    }
+
+    fn permission(permission: ApiKeyPermission) -> Self {
+        Self {
+            permission_opt: Some(permission),
+            index_id_patterns_opt: None,
+        }
+    }
+
+    fn index_permission(permission: ApiKeyPermission, index_id_patterns: &str) -> Self {
+        Self {
+            permission_opt: Some(permission),
+            index_id_patterns_opt: Some(index_id_patterns.to_string()),
+        }
+    }
+
+    /// Returns the access a request requires, or `None` for the public routes: UI, OpenAPI docs,
+    /// health checks, and metrics.
+    fn from_request(method: &Method, path: &str) -> Option<Self> {
+        let segments: Vec<String> = path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| percent_decode_str(segment).decode_utf8_lossy().into_owned())
+            .collect();
+        let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+
+        let required_access = match segments.as_slice() {
+            ["api", "v1", api_v1_segments @ ..] => {
+                Self::from_api_v1_request(method, api_v1_segments)
+            }
+            ["debugging", ..] => Self::permission(ApiKeyPermission::Admin),
+            _ => return None,
+        };
+        Some(required_access)
+    }
+
+    fn from_api_v1_request(method: &Method, segments: &[&str]) -> Self {
+        use ApiKeyPermission::*;
+
+        let is_read = method == Method::GET || method == Method::HEAD;
+
+        match segments {
+            ["version"] => Self::authenticated(),
+            ["config"] | ["cluster"] | ["indexing"] => Self::permission(Admin),
+            ["analyze"] => Self::permission(Search),
+            // The handlers check the indexes targeted by the async search.
+            ["async_search", _] => Self::permission(Search),
+            ["indexes"] if is_read => Self::index_permission(Search, "*"),
+            // The handler checks the ID of the created index.
+            ["indexes"] => Self::permission(Admin),
+            ["indexes", index_id, ..] if is_read => Self::index_permission(Search, index_id),
+            ["indexes", index_id, ..] => Self::index_permission(Admin, index_id),
+            ["otlp", "v1", "logs"] => Self::index_permission(Ingest, OTEL_LOGS_INDEX_ID),
+            ["otlp", "v1", "metrics"] => Self::index_permission(Ingest, OTEL_METRICS_INDEX_ID),
+            ["otlp", "v1", "traces"] => Self::index_permission(Ingest, OTEL_TRACES_INDEX_ID),
+            ["_elastic", elastic_segments @ ..] => Self::from_elastic_request(elastic_segments),
+            [index_id_patterns, "search", ..]
+            | [index_id_patterns, "async_search"]
+            | [index_id_patterns, "tail"]
+            | [index_id_patterns, "jaeger", ..] => {
+                Self::index_permission(Search, index_id_patterns)
+            }
+            [index_id, "ingest"] | [index_id, "ingest-v2"] | [index_id, "otlp", ..] => {
+                Self::index_permission(Ingest, index_id)
+            }
+            [index_id, "delete-tasks"] => Self::index_permission(Admin, index_id),
+            _ => Self::index_permission(Admin, "*"),
+        }
+    }
+
+    fn from_elastic_request(segments: &[&str]) -> Self {
+        use ApiKeyPermission::*;
+
+        match segments {
+            [] => Self::authenticated(),
+            ["_search", "scroll"] => Self::permission(Search),
+            // The handlers check the indexes targeted by the actions and searches of the body.
+            ["_msearch"] => Self::permission(Search),
+            ["_bulk"] | [_, "_bulk"] => Self::permission(Ingest),
+            ["_search"] | ["_field_caps"] | ["_mapping"] | ["_stats"] | ["_cat", "indices"] => {
+                Self::index_permission(Search, "*")
+            }
+            ["_cat", "indices", index_id_patterns] => {
+                Self::index_permission(Search, index_id_patterns)
+            }
+            [index_id_patterns, "_delete_by_query"] => {
+                Self::index_permission(Admin, index_id_patterns)
+            }
+            [index_id_patterns, ..] => Self::index_permission(Search, index_id_patterns),
+        }
+    }
+
+    fn is_admin_action(&self, method: &Method) -> bool {
+        self.permission_opt == Some(ApiKeyPermission::Admin)
+            && method != Method::GET
+            && method != Method::HEAD
+    }
+}
+
+/// Layer authenticating the requests to the REST API with the API keys of the node config, and
+/// checking that they grant the access the requests require. Authentication is disabled when no API
+/// key is configured. Admin actions are logged in both cases.
+#[derive(Clone)]
+pub(crate) struct ApiKeyAuthLayer {
+    api_keys: Arc<Vec<AuthenticatedApiKey>>,
+}
+
+impl ApiKeyAuthLayer {
+    pub fn try_new(api_key_configs: &[ApiKeyConfig]) -> anyhow::Result<Self> {
+        let api_keys = api_key_configs
+            .iter()
+            .map(AuthenticatedApiKey::try_new)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self {
+            api_keys: Arc::new(api_keys),
+        })
+    }
+
+    /// Authenticates a request with the value of its `Authorization` header. A header value that
+    /// is not valid ASCII must be passed as an empty string.
+    fn authenticate(
+        &self,
+        authorization_opt: Option<&str>,
+    ) -> Result<Option<AuthenticatedApiKey>, AuthError> {
+        if self.api_keys.is_empty() {
+            return Ok(None);
+        }
+        let Some(authorization) = authorization_opt else {
+            return Err(AuthError::MissingApiKey);
+        };
+        let key = parse_api_key(authorization).ok_or(AuthError::InvalidApiKey)?;
+        let api_key = self
+            .api_keys
+            .iter()
+            .find(|api_key| constant_time_eq(api_key.0.key.as_bytes(), key.as_bytes()))
+            .ok_or(AuthError::InvalidApiKey)?;
+        Ok(Some(api_key.clone()))
+    }
+
+    fn authorize(
+        &self,
+        authorization_opt: Option<&str>,
+        required_access: &RequiredAccess,
+    ) -> Result<Option<AuthenticatedApiKey>, AuthError> {
+        let Some(api_key) = self.authenticate(authorization_opt)? else {
+            return Ok(None);
+        };
+        match (
+            required_access.permission_opt,
+            &required_access.index_id_patterns_opt,
+        ) {
+            (Some(permission), Some(index_id_patterns)) => {
+                api_key.check_index_permission(permission, index_id_patterns)?
+            }
+            (Some(permission), None) => api_key.check_permission(permission)?,
+            (None, _) => {}
+        }
+        Ok(Some(api_key))
+    }
+}
+
+impl<S> Layer<S> for ApiKeyAuthLayer {
+    type Service = ApiKeyAuth<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ApiKeyAuth {
+            inner,
+            auth_layer: self.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct ApiKeyAuth<S> {
+    inner: S,
+    auth_layer: ApiKeyAuthLayer,
+}
+
+impl<S> Service<Request<Body>> for ApiKeyAuth<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>>,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<Body>) -> Self::Future {
+        let Some(required_access) =
+            RequiredAccess::from_request(request.method(), request.uri().path())
+        else {
+            return Box::pin(self.inner.call(request));
+        };
+        let authorization_opt = request
+            .headers()
+            .get(AUTHORIZATION)
+            .map(|authorization| authorization.to_str().unwrap_or_default());
+
+        let api_key_opt = match self
+            .auth_layer
+            .authorize(authorization_opt, &required_access)
+        {
+            Ok(api_key_opt) => api_key_opt,
+            Err(auth_error) => {
+                warn!(
+                    method=%request.method(),
+                    path=%request.uri().path(),
+                    "request rejected: {auth_error}"
+                );
+                return Box::pin(future::ok(auth_error_response(auth_error)));
+            }
+        };
+        let audit_log_opt = if required_access.is_admin_action(request.method()) {
+            let api_key_name = api_key_opt
+                .as_ref()
+                .map(|api_key| api_key.name().to_string());
+            Some((
+                api_key_name,
+                request.method().clone(),
+                request.uri().path().to_string(),
+            ))
+        } else {
+            None
+        };
+        if let Some(api_key) = api_key_opt {
+            request.extensions_mut().insert(api_key);
+        }
+        let response_fut = self.inner.call(request);
+
+        Box::pin(async move {
+            let response = response_fut.await?;
+
+            if let Some((api_key_name_opt, method, path)) = audit_log_opt {
+                info!(
+                    api_key=api_key_name_opt.as_deref().unwrap_or("none"),
+                    method=%method,
+                    path=%path,
+                    status=response.status().as_u16(),
+                    "admin action"
+                );
+            }
+            Ok(response)
+        })
+    }
+}
+
+/// Interceptor applying the checks of [`ApiKeyAuthLayer`] to the requests to the public gRPC
+/// services of the node: the OTLP services and the Jaeger storage plugin. The indexes a request
+/// targets are read from the same metadata key as the services, and default to theirs.
+#[derive(Clone)]
+pub(crate) struct ApiKeyAuthInterceptor {
+    auth_layer: ApiKeyAuthLayer,
+    permission: ApiKeyPermission,
+    index_id_patterns_key: &'static str,
+    default_index_id_patterns: &'static str,
+}
+
+impl ApiKeyAuthInterceptor {
+    /// Requires the `ingest` permission on the index the OTLP service of `otel_signal` ingests
+    /// into.
+    pub fn otlp(auth_layer: ApiKeyAuthLayer, otel_signal: OtelSignal) -> Self {
+        Self {
+            auth_layer,
+            permission: ApiKeyPermission::Ingest,
+            index_id_patterns_key: otel_signal.header_name(),
+            default_index_id_patterns: otel_signal.default_index_id(),
+        }
+    }
+
+    /// Requires the `search` permission on the trace indexes the Jaeger storage plugin reads.
+    pub fn jaeger(auth_layer: ApiKeyAuthLayer) -> Self {
+        Self {
+            auth_layer,
+            permission: ApiKeyPermission::Search,
+            index_id_patterns_key: OtelSignal::Traces.header_name(),
+            default_index_id_patterns: OTEL_TRACES_INDEX_ID_PATTERN,
+        }
+    }
+}
+
+impl Interceptor for ApiKeyAuthInterceptor {
+    fn call(&mut self, request: tonic::Request<()>) -> Result<tonic::Request<()>, tonic::Status> {
+        let metadata = request.metadata();
+        let authorization_opt = metadata
+            .get(AUTHORIZATION.as_str())
+            .map(|authorization| authorization.to_str().unwrap_or_default());
+        let index_id_patterns = metadata
+            .get(self.index_id_patterns_key)
+            .and_then(|index_id_patterns| index_id_patterns.to_str().ok())
+            .unwrap_or(self.default_index_id_patterns);
+        let required_access = RequiredAccess::index_permission(self.permission, index_id_patterns);
+
+        if let Err(auth_error) = self
+            .auth_layer
+            .authorize(authorization_opt, &required_access)
+        {
+            warn!(
+                index_id_patterns=%index_id_patterns,
+                "gRPC request rejected: {auth_error}"
+            );
+            return Err(auth_error.grpc_error());
+        }
+        Ok(request)
+    }
+}
+
+/// Extracts the key of an `Authorization` header value with the `Bearer` or `ApiKey` scheme.
+fn parse_api_key(authorization: &str) -> Option<&str> {
+    let (scheme, key) = authorization.trim().split_once(' ')?;
+
+    if scheme.eq_ignore_ascii_case("bearer") || scheme.eq_ignore_ascii_case("apikey") {
+        return Some(key.trim());
+    }
+    None
+}
+
+/// Compares two keys in a time that does not depend on the position of their first difference.
+fn constant_time_eq(left: &[u8], right: &[u8]) -> bool {
+    left.len() == right.len()
+        && left
+            .iter()
+            .zip(right)
+            .fold(0u8, |acc, (left_byte, right_byte)| {
+                acc | (left_byte ^ right_byte)
+            })
+            == 0
+}
+
+fn auth_error_response(auth_error: AuthError) -> Response<Body> {
+    let is_unauthenticated = matches!(auth_error.error_code(), ServiceErrorCode::Unauthenticated);
+    let mut response =
+        make_json_api_response::<(), _>(Err(auth_error), BodyFormat::default()).into_response();
+
+    if is_unauthenticated {
+        response
+            .headers_mut()
+            .insert(WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::StatusCode;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn api_key_config(
+        name: &str,
+        permissions: &[ApiKeyPermission],
+        index_id_patterns: &[&str],
+    ) -> ApiKeyConfig {
+        ApiKeyConfig {
+            name: name.to_string(),
+            key: format!("{name}-secret-key"),
+            permissions: permissions.to_vec(),
+            index_id_patterns: index_id_patterns
+                .iter()
+                .map(|index_id_pattern| index_id_pattern.to_string())
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_required_access_from_request() {
+        use ApiKeyPermission::*;
+
+        let required_access = |method: Method, path: &str| {
+            RequiredAccess::from_request(&method, path).map(|required_access| {
+                (
+                    required_access.permission_opt,
+                    required_access.index_id_patterns_opt,
+                )
+            })
+        };
+        assert_eq!(required_access(Method::GET, "/"), None);
+        assert_eq!(required_access(Method::GET, "/ui/search"), None);
+        assert_eq!(required_access(Method::GET, "/health/livez"), None);
+        assert_eq!(required_access(Method::GET, "/metrics"), None);
+        assert_eq!(
+            required_access(Method::GET, "/debugging"),
+            Some((Some(Admin), None))
+        );
+        assert_eq!(
+            required_access(Method::GET, "/api/v1/version"),
+            Some((None, None))
+        );
+        assert_eq!(
+            required_access(Method::GET, "/api/v1/config"),
+            Some((Some(Admin), None))
+        );
+        assert_eq!(
+            required_access(Method::GET, "/api/v1/indexes"),
+            Some((Some(Search), Some("*".to_string())))
+        );
+        assert_eq!(
+            required_access(Method::POST, "/api/v1/indexes"),
+            Some((Some(Admin), None))
+        );
+        assert_eq!(
+            required_access(Method::GET, "/api/v1/indexes/logs/describe"),
+            Some((Some(Search), Some("logs".to_string())))
+        );
+        assert_eq!(
+            required_access(Method::DELETE, "/api/v1/indexes/logs"),
+            Some((Some(Admin), Some("logs".to_string())))
+        );
+        assert_eq!(
+            required_access(
+                Method::PUT,
+                "/api/v1/indexes/logs/sources/kafka/reset-checkpoint"
+            ),
+            Some((Some(Admin), Some("logs".to_string())))
+        );
+        assert_eq!(
+            required_access(Method::POST, "/api/v1/logs-*,traces/search"),
+            Some((Some(Search), Some("logs-*,traces".to_string())))
+        );
+        assert_eq!(
+            required_access(Method::POST, "/api/v1/logs%2Ctraces/search/stream"),
+            Some((Some(Search), Some("logs,traces".to_string())))
+        );
+        assert_eq!(
+            required_access(Method::POST, "/api/v1/logs/ingest"),
+            Some((Some(Ingest), Some("logs".to_string())))
+        );
+        assert_eq!(
+            required_access(Method::POST, "/api/v1/otlp/v1/traces"),
+            Some((Some(Ingest), Some(OTEL_TRACES_INDEX_ID.to_string())))
+        );
+        assert_eq!(
+            required_access(Method::GET, "/api/v1/otel-traces-v0_7/jaeger/api/services"),
+            Some((Some(Search), Some("otel-traces-v0_7".to_string())))
+        );
+        assert_eq!(
+            required_access(Method::POST, "/api/v1/logs/delete-tasks"),
+            Some((Some(Admin), Some("logs".to_string())))
+        );
+        assert_eq!(
+            required_access(Method::POST, "/api/v1/_elastic/logs/_search"),
+            Some((Some(Search), Some("logs".to_string())))
+        );
+        assert_eq!(
+            required_access(Method::POST, "/api/v1/_elastic/logs/_delete_by_query"),
+            Some((Some(Admin), Some("logs".to_string())))
+        );
+        assert_eq!(
+            required_access(Method::POST, "/api/v1/_elastic/_bulk"),
+            Some((Some(Ingest), None))
+        );
+        assert_eq!(
+            required_access(Method::POST, "/api/v1/_elastic/logs/_bulk"),
+            Some((Some(Ingest), None))
+        );
+        assert_eq!(
+            required_access(Method::POST, "/api/v1/_elastic/_msearch"),
+            Some((Some(Search), None))
+        );
+        assert_eq!(
+            required_access(Method::GET, "/api/v1/_elastic/_cat/indices"),
+            Some((Some(Search), Some("*".to_string())))
+        );
+        assert_eq!(
+            required_access(Method::GET, "/api/v1/unknown/route"),
+            Some((Some(Admin), Some("*".to_string())))
+        );
+    }
+
+    #[test]
+    fn test_api_key_check_index_permission() {
+        use ApiKeyPermission::*;
+
+        let api_key = AuthenticatedApiKey::try_new(&api_key_config(
+            "logs",
+            &[Search, Ingest],
+            &["logs-*", "audit"],
+        ))
+        .unwrap();
+        api_key.check_index_permission(Search, "logs-app").unwrap();
+        api_key.check_index_permission(Ingest, "audit").unwrap();
+        api_key
+            .check_index_permission(Search, "logs-app,audit")
+            .unwrap();
+        api_key.check_index_permission(Search, "logs-*").unwrap();
+        api_key
+            .check_index_permission(Search, "logs-app-*")
+            .unwrap();
+
+        let error = api_key
+            .check_index_permission(Admin, "logs-app")
+            .unwrap_err();
+        assert!(matches!(error, AuthError::PermissionDenied { .. }));
+
+        for index_id_patterns in ["audit-2024", "logs*", "*", "logs-app,traces", "audi*"] {
+            let error = api_key
+                .check_index_permission(Search, index_id_patterns)
+                .unwrap_err();
+            assert!(matches!(error, AuthError::IndexPermissionDenied { .. }));
+        }
+        let admin_api_key =
+            AuthenticatedApiKey::try_new(&api_key_config("admin", &[Admin], &["*"])).unwrap();
+        admin_api_key.check_index_permission(Ingest, "*").unwrap();
+        admin_api_key
+            .check_index_permission(Search, "logs-*,traces")
+            .unwrap();
+    }
+
+    #[test]
+    fn test_parse_api_key() {
+        assert_eq!(parse_api_key("Bearer secret-key"), Some("secret-key"));
+        assert_eq!(parse_api_key("bearer  secret-key "), Some("secret-key"));
+        assert_eq!(parse_api_key("ApiKey secret-key"), Some("secret-key"));
+        assert_eq!(parse_api_key("Basic dXNlcjpwYXNz"), None);
+        assert_eq!(parse_api_key("secret-key"), None);
+    }
+
+    async fn call_with_api_key(
+        auth_layer: &ApiKeyAuthLayer,
+        method: Method,
+        path: &str,
+        key_opt: Option<&str>,
+    ) -> (StatusCode, String) {
+        let service = auth_layer.layer(tower::service_fn(|request: Request<Body>| async move {
+            let api_key_name = request
+                .extensions()
+                .get::<AuthenticatedApiKey>()
+                .map(|api_key| api_key.name().to_string())
+                .unwrap_or_default();
+            Ok::<_, Infallible>(Response::new(Body::from(api_key_name)))
+        }));
+        let mut request_builder = Request::builder().method(method).uri(path);
+
+        if let Some(key) = key_opt {
+            request_builder = request_builder.header(AUTHORIZATION, format!("Bearer {key}"));
+        }
+        let request = request_builder.body(Body::empty()).unwrap();
+        let response = service.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_api_key_auth_layer() {
+        use ApiKeyPermission::*;
+
+        let auth_layer = ApiKeyAuthLayer::try_new(&[
+            api_key_config("admin", &[Admin], &["*"]),
+            api_key_config("logs", &[Search, Ingest], &["logs-*"]),
+        ])
+        .unwrap();
+
+        let (status, _) = call_with_api_key(&auth_layer, Method::GET, "/health/livez", None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, body) =
+            call_with_api_key(&auth_layer, Method::GET, "/api/v1/version", None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert!(body.contains("missing API key"));
+
+        let (status, body) = call_with_api_key(
+            &auth_layer,
+            Method::GET,
+            "/api/v1/version",
+            Some("unknown-secret-key"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert!(body.contains("invalid API key"));
+
+        let (status, body) = call_with_api_key(
+            &auth_layer,
+            Method::POST,
+            "/api/v1/logs-app/search",
+            Some("logs-secret-key"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "logs");
+
+        let (status, body) = call_with_api_key(
+            &auth_layer,
+            Method::POST,
+            "/api/v1/traces/search",
+            Some("logs-secret-key"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert!(body.contains("on index `traces`"));
+
+        let (status, body) = call_with_api_key(
+            &auth_layer,
+            Method::DELETE,
+            "/api/v1/indexes/logs-app",
+            Some("logs-secret-key"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert!(body.contains("`admin` permission"));
+
+        let (status, body) = call_with_api_key(
+            &auth_layer,
+            Method::DELETE,
+            "/api/v1/indexes/logs-app",
+            Some("admin-secret-key"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "admin");
+    }
+
+    fn call_interceptor(
+        auth_interceptor: &mut ApiKeyAuthInterceptor,
+        key_opt: Option<&str>,
+        index_id_patterns_opt: Option<(&'static str, &str)>,
+    ) -> Result<(), tonic::Status> {
+        let mut request = tonic::Request::new(());
+
+        if let Some(key) = key_opt {
+            request
+                .metadata_mut()
+                .insert("authorization", format!("Bearer {key}").parse().unwrap());
+        }
+        if let Some((index_id_patterns_key, index_id_patterns)) = index_id_patterns_opt {
+            request
+                .metadata_mut()
+                .insert(index_id_patterns_key, index_id_patterns.parse().unwrap());
+        }
+        auth_interceptor.call(request).map(|_| ())
+    }
+
+    #[test]
+    fn test_api_key_auth_interceptor() {
+        use ApiKeyPermission::*;
+
+        let auth_layer = ApiKeyAuthLayer::try_new(&[
+            api_key_config("ingest", &[Ingest], &["otel-logs-*"]),
+            api_key_config("search", &[Search], &["otel-traces-*"]),
+        ])
+        .unwrap();
+        let mut otlp_logs_interceptor =
+            ApiKeyAuthInterceptor::otlp(auth_layer.clone(), OtelSignal::Logs);
+
+        let status = call_interceptor(&mut otlp_logs_interceptor, None, None).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+        assert!(status.message().contains("missing API key"));
+
+        let status = call_interceptor(&mut otlp_logs_interceptor, Some("unknown-secret-key"), None)
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+        assert!(status.message().contains("invalid API key"));
+
+        call_interceptor(&mut otlp_logs_interceptor, Some("ingest-secret-key"), None).unwrap();
+
+        let status = call_interceptor(&mut otlp_logs_interceptor, Some("search-secret-key"), None)
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+        let status = call_interceptor(
+            &mut otlp_logs_interceptor,
+            Some("ingest-secret-key"),
+            Some(("qw-otel-logs-index", "audit")),
+        )
+        .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+        assert!(status.message().contains("on index `audit`"));
+
+        let mut jaeger_interceptor = ApiKeyAuthInterceptor::jaeger(auth_layer);
+        call_interceptor(&mut jaeger_interceptor, Some("search-secret-key"), None).unwrap();
+
+        let status =
+            call_interceptor(&mut jaeger_interceptor, Some("ingest-secret-key"), None).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+        let mut disabled_interceptor =
+            ApiKeyAuthInterceptor::jaeger(ApiKeyAuthLayer::try_new(&[]).unwrap());
+        call_interceptor(&mut disabled_interceptor, None, None).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_api_key_auth_layer_disabled() {
+        let auth_layer = ApiKeyAuthLayer::try_new(&[]).unwrap();
+
+        let (status, body) =
+            call_with_api_key(&auth_layer, Method::DELETE, "/api/v1/indexes/logs", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.is_empty());
+    }
+}
@@ -142,7 +142,29 @@ pub fn post_delete_tasks_handler(
 pub async fn post_delete_request(
     index_id: String,
     delete_request: DeleteQueryRequest,
+    metastore: MetastoreServiceClient,
+) -> Result<DeleteTask, JanitorError> {
+    let query_ast = query_ast_from_user_text(&delete_request.query, Some(Vec::new()))
+        .parse_user_query(&[])
+        .map_err(|err| JanitorError::InvalidDeleteQuery(err.to_string()))?;
+    create_delete_task(
+        metastore,
+        &index_id,
+        query_ast,
+        delete_request.start_timestamp,
+        delete_request.end_timestamp,
+    )
+    .await
+}
+
+/// Resolves and validates the delete query against the doc mapping of the index, then creates
+/// a delete task.
+pub(crate) async fn create_delete_task(
     mut metastore: MetastoreServiceClient,
+    index_id: &str,
+    query_ast: QueryAst,
+    start_timestamp: Option<i64>,
+    end_timestamp: Option<i64>,
 ) -> Result<DeleteTask, JanitorError> {
     let index_metadata_request = IndexMetadataRequest::for_index_id(index_id.to_string());
     let metadata = metastore
@@ -150,16 +172,18 @@ pub async fn post_delete_request(
         .await?
         .deserialize_index_metadata()?;
     let index_uid: IndexUid = metadata.index_uid.clone();
-    let query_ast = query_ast_from_user_text(&delete_request.query, Some(Vec::new()))
-        .parse_user_query(&[])
+    // Queries expressed in the user query language are resolved against the default search
+    // fields of the index.
+    let query_ast = query_ast
+        .parse_user_query(&metadata.index_config.search_settings.default_search_fields)
         .map_err(|err| JanitorError::InvalidDeleteQuery(err.to_string()))?;
     let query_ast_json = serde_json::to_string(&query_ast).map_err(|_err| {
         JanitorError::Internal("failed to serialized delete query ast".to_string())
     })?;
     let delete_query = DeleteQuery {
         index_uid: index_uid.to_string(),
-        start_timestamp: delete_request.start_timestamp,
-        end_timestamp: delete_request.end_timestamp,
+        start_timestamp,
+        end_timestamp,
         query_ast: query_ast_json,
     };
     let index_config = metadata.into_index_config();
@@ -22,7 +22,7 @@ use std::time::Instant;
 
 use bytes::Bytes;
 use hyper::StatusCode;
-use quickwit_config::enable_ingest_v2;
+use quickwit_config::{enable_ingest_v2, ApiKeyPermission};
 use quickwit_ingest::{
     CommitType, DocBatchBuilder, IngestRequest, IngestService, IngestServiceClient,
 };
@@ -31,6 +31,7 @@ use quickwit_proto::types::IndexId;
 use warp::{Filter, Rejection};
 
 use super::bulk_v2::{elastic_bulk_ingest_v2, ElasticBulkResponse};
+use crate::auth::{api_key_filter, check_index_permission, AuthenticatedApiKey};
 use crate::elastic_search_api::filter::{elastic_bulk_filter, elastic_index_bulk_filter};
 use crate::elastic_search_api::make_elastic_api_response;
 use crate::elastic_search_api::model::{BulkAction, ElasticBulkOptions, ElasticSearchError};
@@ -46,9 +47,19 @@ pub fn es_compat_bulk_handler(
     elastic_bulk_filter()
         .and(with_arg(ingest_service))
         .and(with_arg(ingest_router))
-        .then(|body, bulk_options, ingest_service, ingest_router| {
-            elastic_ingest_bulk(None, body, bulk_options, ingest_service, ingest_router)
-        })
+        .and(api_key_filter())
+        .then(
+            |body, bulk_options, ingest_service, ingest_router, api_key_opt| {
+                elastic_ingest_bulk(
+                    None,
+                    body,
+                    bulk_options,
+                    ingest_service,
+                    ingest_router,
+                    api_key_opt,
+                )
+            },
+        )
         .and(extract_format_from_qs())
         .map(make_elastic_api_response)
 }
@@ -61,14 +72,16 @@ pub fn es_compat_index_bulk_handler(
     elastic_index_bulk_filter()
         .and(with_arg(ingest_service))
         .and(with_arg(ingest_router))
+        .and(api_key_filter())
         .then(
-            |index_id, body, bulk_options, ingest_service, ingest_router| {
+            |index_id, body, bulk_options, ingest_service, ingest_router, api_key_opt| {
                 elastic_ingest_bulk(
                     Some(index_id),
                     body,
                     bulk_options,
                     ingest_service,
                     ingest_router,
+                    api_key_opt,
                 )
             },
         )
@@ -82,9 +95,17 @@ async fn elastic_ingest_bulk(
     bulk_options: ElasticBulkOptions,
     mut ingest_service: IngestServiceClient,
     ingest_router: IngestRouterServiceClient,
+    api_key_opt: Option<AuthenticatedApiKey>,
 ) -> Result<ElasticBulkResponse, ElasticSearchError> {
     if enable_ingest_v2() {
-        return elastic_bulk_ingest_v2(default_index_id, body, bulk_options, ingest_router).await;
+        return elastic_bulk_ingest_v2(
+            default_index_id,
+            body,
+            bulk_options,
+            ingest_router,
+            api_key_opt,
+        )
+        .await;
     }
     let now = Instant::now();
     let mut doc_batch_builders = HashMap::new();
@@ -115,6 +136,8 @@ async fn elastic_ingest_bulk(
                     format!("missing required field: `_index` in the line [#{line_number}]."),
                 )
             })?;
+        check_index_permission(api_key_opt.as_ref(), ApiKeyPermission::Ingest, &index_id)?;
+
         let doc_batch_builder = doc_batch_builders
             .entry(index_id.clone())
             .or_insert(DocBatchBuilder::new(index_id));
@@ -146,16 +169,22 @@ mod tests {
     use std::time::Duration;
 
     use hyper::StatusCode;
-    use quickwit_config::{IngestApiConfig, NodeConfig};
+    use quickwit_config::{ApiKeyConfig, ApiKeyPermission, IngestApiConfig, NodeConfig};
     use quickwit_ingest::{FetchRequest, IngestServiceClient, SuggestTruncateRequest};
     use quickwit_proto::ingest::router::IngestRouterServiceClient;
+    use quickwit_proto::metastore::MetastoreServiceClient;
     use quickwit_search::MockSearchService;
 
+    use crate::auth::AuthenticatedApiKey;
     use crate::elastic_search_api::bulk_v2::ElasticBulkResponse;
     use crate::elastic_search_api::elastic_api_handlers;
     use crate::elastic_search_api::model::ElasticSearchError;
     use crate::ingest_api::setup_ingest_service;
 
+    fn metastore_client() -> MetastoreServiceClient {
+        MetastoreServiceClient::from(MetastoreServiceClient::mock())
+    }
+
     #[tokio::test]
     async fn test_bulk_api_returns_404_if_index_id_does_not_exist() {
         let config = Arc::new(NodeConfig::for_test());
@@ -163,8 +192,13 @@ mod tests {
         let (universe, _temp_dir, ingest_service, _) =
             setup_ingest_service(&["my-index"], &IngestApiConfig::default()).await;
         let ingest_router = IngestRouterServiceClient::from(IngestRouterServiceClient::mock());
-        let elastic_api_handlers =
-            elastic_api_handlers(config, search_service, ingest_service, ingest_router);
+        let elastic_api_handlers = elastic_api_handlers(
+            config,
+            search_service,
+            ingest_service,
+            ingest_router,
+            metastore_client(),
+        );
         let payload = r#"
             { "create" : { "_index" : "my-index", "_id" : "1"} }
             {"id": 1, "message": "push"}
@@ -187,8 +221,13 @@ mod tests {
         let (universe, _temp_dir, ingest_service, _) =
             setup_ingest_service(&["my-index-1", "my-index-2"], &IngestApiConfig::default()).await;
         let ingest_router = IngestRouterServiceClient::from(IngestRouterServiceClient::mock());
-        let elastic_api_handlers =
-            elastic_api_handlers(config, search_service, ingest_service, ingest_router);
+        let elastic_api_handlers = elastic_api_handlers(
+            config,
+            search_service,
+            ingest_service,
+            ingest_router,
+            metastore_client(),
+        );
         let payload = r#"
             { "create" : { "_index" : "my-index-1", "_id" : "1"} }
             {"id": 1, "message": "push"}
@@ -208,6 +247,62 @@ mod tests {
         universe.assert_quit().await;
     }
 
+    #[tokio::test]
+    async fn test_bulk_api_checks_index_permissions_of_api_key() {
+        let config = Arc::new(NodeConfig::for_test());
+        let search_service = Arc::new(MockSearchService::new());
+        let (universe, _temp_dir, ingest_service, _) =
+            setup_ingest_service(&["my-index-1", "my-index-2"], &IngestApiConfig::default()).await;
+        let ingest_router = IngestRouterServiceClient::from(IngestRouterServiceClient::mock());
+        let elastic_api_handlers = elastic_api_handlers(
+            config,
+            search_service,
+            ingest_service,
+            ingest_router,
+            metastore_client(),
+        );
+        let api_key = AuthenticatedApiKey::try_new(&ApiKeyConfig {
+            name: "my-index-1-ingest".to_string(),
+            key: "secret-key".to_string(),
+            permissions: vec![ApiKeyPermission::Ingest],
+            index_id_patterns: vec!["my-index-1".to_string()],
+        })
+        .unwrap();
+        // `_index` overrides the index of the path.
+        let payload = r#"
+            { "create" : { "_id" : "1"} }
+            {"id": 1, "message": "push"}
+            { "create" : { "_index" : "my-index-2", "_id" : "1"} }
+            {"id": 1, "message": "push"}"#;
+        let resp = warp::test::request()
+            .path("/_elastic/my-index-1/_bulk")
+            .method("POST")
+            .extension(api_key.clone())
+            .body(payload)
+            .reply(&elastic_api_handlers)
+            .await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        let es_error: ElasticSearchError = serde_json::from_slice(resp.body()).unwrap();
+        assert!(es_error
+            .error
+            .reason
+            .unwrap()
+            .contains("on index `my-index-2`"));
+
+        let payload = r#"
+            { "create" : { "_id" : "1"} }
+            {"id": 1, "message": "push"}"#;
+        let resp = warp::test::request()
+            .path("/_elastic/my-index-1/_bulk")
+            .method("POST")
+            .extension(api_key)
+            .body(payload)
+            .reply(&elastic_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 200);
+        universe.assert_quit().await;
+    }
+
     #[tokio::test]
     async fn test_bulk_api_returns_200_if_payload_has_blank_lines() {
         let config = Arc::new(NodeConfig::for_test());
@@ -215,8 +310,13 @@ mod tests {
         let (universe, _temp_dir, ingest_service, _) =
             setup_ingest_service(&["my-index-1"], &IngestApiConfig::default()).await;
         let ingest_router = IngestRouterServiceClient::from(IngestRouterServiceClient::mock());
-        let elastic_api_handlers =
-            elastic_api_handlers(config, search_service, ingest_service, ingest_router);
+        let elastic_api_handlers = elastic_api_handlers(
+            config,
+            search_service,
+            ingest_service,
+            ingest_router,
+            metastore_client(),
+        );
         let payload = "
             {\"create\": {\"_index\": \"my-index-1\", \"_id\": \"1674834324802805760\"}}
             \u{20}\u{20}\u{20}\u{20}\n
@@ -240,8 +340,13 @@ mod tests {
         let (universe, _temp_dir, ingest_service, _) =
             setup_ingest_service(&["my-index-1", "my-index-2"], &IngestApiConfig::default()).await;
         let ingest_router = IngestRouterServiceClient::from(IngestRouterServiceClient::mock());
-        let elastic_api_handlers =
-            elastic_api_handlers(config, search_service, ingest_service, ingest_router);
+        let elastic_api_handlers = elastic_api_handlers(
+            config,
+            search_service,
+            ingest_service,
+            ingest_router,
+            metastore_client(),
+        );
         let payload = r#"
             { "create" : { "_index" : "my-index-1", "_id" : "1"} }
             {"id": 1, "message": "push"}
@@ -268,8 +373,13 @@ mod tests {
         let (universe, _temp_dir, ingest_service, ingest_service_mailbox) =
             setup_ingest_service(&["my-index-1", "my-index-2"], &IngestApiConfig::default()).await;
         let ingest_router = IngestRouterServiceClient::from(IngestRouterServiceClient::mock());
-        let elastic_api_handlers =
-            elastic_api_handlers(config, search_service, ingest_service, ingest_router);
+        let elastic_api_handlers = elastic_api_handlers(
+            config,
+            search_service,
+            ingest_service,
+            ingest_router,
+            metastore_client(),
+        );
         let payload = r#"
             { "create" : { "_index" : "my-index-1", "_id" : "1"} }
             {"id": 1, "message": "push"}
@@ -347,8 +457,13 @@ mod tests {
         let (universe, _temp_dir, ingest_service, ingest_service_mailbox) =
             setup_ingest_service(&["my-index-1", "my-index-2"], &IngestApiConfig::default()).await;
         let ingest_router = IngestRouterServiceClient::from(IngestRouterServiceClient::mock());
-        let elastic_api_handlers =
-            elastic_api_handlers(config, search_service, ingest_service, ingest_router);
+        let elastic_api_handlers = elastic_api_handlers(
+            config,
+            search_service,
+            ingest_service,
+            ingest_router,
+            metastore_client(),
+        );
         let payload = r#"
             { "create" : { "_index" : "my-index-1", "_id" : "1"} }
             {"id": 1, "message": "push"}
@@ -424,8 +539,13 @@ mod tests {
         let search_service = Arc::new(MockSearchService::new());
         let ingest_service = IngestServiceClient::from(IngestServiceClient::mock());
         let ingest_router = IngestRouterServiceClient::from(IngestRouterServiceClient::mock());
-        let elastic_api_handlers =
-            elastic_api_handlers(config, search_service, ingest_service, ingest_router);
+        let elastic_api_handlers = elastic_api_handlers(
+            config,
+            search_service,
+            ingest_service,
+            ingest_router,
+            metastore_client(),
+        );
         let payload = r#"
             {"create": {"_index": "my-index", "_id": "1"},}
             {"id": 1, "message": "my-doc"}"#;
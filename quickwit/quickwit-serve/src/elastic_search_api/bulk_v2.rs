@@ -21,7 +21,7 @@ use std::time::Instant;
 
 use bytes::Bytes;
 use hyper::StatusCode;
-use quickwit_config::INGEST_V2_SOURCE_ID;
+use quickwit_config::{ApiKeyPermission, INGEST_V2_SOURCE_ID};
 use quickwit_ingest::IngestRequestV2Builder;
 use quickwit_proto::ingest::router::{IngestRouterService, IngestRouterServiceClient};
 use quickwit_proto::ingest::CommitTypeV2;
@@ -29,6 +29,7 @@ use quickwit_proto::types::IndexId;
 use serde::{Deserialize, Serialize};
 use tracing::warn;
 
+use crate::auth::{check_index_permission, AuthenticatedApiKey};
 use crate::elastic_search_api::model::{BulkAction, ElasticBulkOptions, ElasticSearchError};
 use crate::ingest_api::lines;
 
@@ -44,6 +45,7 @@ pub(crate) async fn elastic_bulk_ingest_v2(
     body: Bytes,
     bulk_options: ElasticBulkOptions,
     mut ingest_router: IngestRouterServiceClient,
+    api_key_opt: Option<AuthenticatedApiKey>,
 ) -> Result<ElasticBulkResponse, ElasticSearchError> {
     let now = Instant::now();
     let mut ingest_request_builder = IngestRequestV2Builder::default();
@@ -75,6 +77,8 @@ pub(crate) async fn elastic_bulk_ingest_v2(
                     format!("`_index` field of action on line #{line_no} is missing"),
                 )
             })?;
+        check_index_permission(api_key_opt.as_ref(), ApiKeyPermission::Ingest, &index_id)?;
+
         ingest_request_builder.add_doc(index_id, source);
     }
     let commit_type: CommitTypeV2 = bulk_options.refresh.into();
@@ -120,7 +124,7 @@ mod tests {
         elastic_bulk_filter()
             .and(with_arg(ingest_router))
             .then(|body, bulk_options, ingest_router| {
-                elastic_bulk_ingest_v2(None, body, bulk_options, ingest_router)
+                elastic_bulk_ingest_v2(None, body, bulk_options, ingest_router, None)
             })
             .and(extract_format_from_qs())
             .map(make_elastic_api_response)
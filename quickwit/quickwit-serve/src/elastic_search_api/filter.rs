@@ -24,6 +24,7 @@ use warp::reject::LengthRequired;
 use warp::{Filter, Rejection};
 
 use super::model::{
+    CatIndexQueryParams, CountBody, DeleteByQueryBody, DeleteByQueryParams,
     FieldCapabilityQueryParams, FieldCapabilityRequestBody, MultiSearchQueryParams,
 };
 use crate::elastic_search_api::model::{
@@ -170,6 +171,71 @@ pub(crate) fn elastic_index_search_filter(
         .and(json_or_empty())
 }
 
+#[utoipa::path(get, tag = "Search", path = "/{index}/_count")]
+pub(crate) fn elastic_index_count_filter(
+) -> impl Filter<Extract = (Vec<String>, SearchQueryParams, CountBody), Error = Rejection> + Clone {
+    warp::path!("_elastic" / String / "_count")
+        .and_then(extract_index_id_patterns)
+        .and(warp::get().or(warp::post()).unify())
+        .and(serde_qs::warp::query(serde_qs::Config::default()))
+        .and(json_or_empty())
+}
+
+#[utoipa::path(post, tag = "Delete Tasks", path = "/{index}/_delete_by_query")]
+pub(crate) fn elastic_delete_by_query_filter(
+) -> impl Filter<Extract = (String, DeleteByQueryParams, DeleteByQueryBody), Error = Rejection> + Clone
+{
+    warp::path!("_elastic" / String / "_delete_by_query")
+        .and(warp::post())
+        .and(serde_qs::warp::query(serde_qs::Config::default()))
+        .and(json_or_empty())
+}
+
+#[utoipa::path(head, tag = "Metadata", path = "/{index}")]
+pub(crate) fn elastic_index_exists_filter(
+) -> impl Filter<Extract = (Vec<String>,), Error = Rejection> + Clone {
+    warp::path!("_elastic" / String)
+        .and(warp::head())
+        .and_then(extract_index_id_patterns)
+}
+
+#[utoipa::path(get, tag = "Metadata", path = "/{index}/_mapping")]
+pub(crate) fn elastic_index_mapping_filter(
+) -> impl Filter<Extract = (Vec<String>,), Error = Rejection> + Clone {
+    warp::path!("_elastic" / String / "_mapping")
+        .and(warp::get())
+        .and_then(extract_index_id_patterns)
+        .or(warp::path!("_elastic" / "_mapping")
+            .and(warp::get())
+            .and_then(extract_index_id_patterns_default))
+        .unify()
+}
+
+#[utoipa::path(get, tag = "Metadata", path = "/{index}/_stats")]
+pub(crate) fn elastic_index_stats_filter(
+) -> impl Filter<Extract = (Vec<String>,), Error = Rejection> + Clone {
+    warp::path!("_elastic" / String / "_stats")
+        .and(warp::get())
+        .and_then(extract_index_id_patterns)
+        .or(warp::path!("_elastic" / "_stats")
+            .and(warp::get())
+            .and_then(extract_index_id_patterns_default))
+        .unify()
+}
+
+#[utoipa::path(get, tag = "Metadata", path = "/_cat/indices/{index}")]
+pub(crate) fn elastic_cat_indices_filter(
+) -> impl Filter<Extract = (Vec<String>, CatIndexQueryParams), Error = Rejection> + Clone {
+    warp::path!("_elastic" / "_cat" / "indices" / String)
+        .and(warp::get())
+        .and_then(extract_index_id_patterns)
+        .or(warp::path!("_elastic" / "_cat" / "indices")
+            .and(warp::get())
+            .and_then(extract_index_id_patterns_default))
+        .unify()
+        .and(serde_qs::warp::query(serde_qs::Config::default()))
+}
+
 #[utoipa::path(post, tag = "Search", path = "/_msearch")]
 pub(crate) fn elastic_multi_search_filter(
 ) -> impl Filter<Extract = (Bytes, MultiSearchQueryParams), Error = Rejection> + Clone {
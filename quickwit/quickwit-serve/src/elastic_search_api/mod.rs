@@ -31,10 +31,14 @@ use hyper::StatusCode;
 use quickwit_config::NodeConfig;
 use quickwit_ingest::IngestServiceClient;
 use quickwit_proto::ingest::router::IngestRouterServiceClient;
+use quickwit_proto::metastore::MetastoreServiceClient;
 use quickwit_search::SearchService;
 use rest_handler::{
-    es_compat_cluster_info_handler, es_compat_index_multi_search_handler,
-    es_compat_index_search_handler, es_compat_scroll_handler, es_compat_search_handler,
+    es_compat_cat_indices_handler, es_compat_cluster_info_handler,
+    es_compat_delete_by_query_handler, es_compat_index_count_handler,
+    es_compat_index_exists_handler, es_compat_index_mapping_handler,
+    es_compat_index_multi_search_handler, es_compat_index_search_handler,
+    es_compat_index_stats_handler, es_compat_scroll_handler, es_compat_search_handler,
 };
 use serde::{Deserialize, Serialize};
 use warp::{Filter, Rejection};
@@ -53,10 +57,12 @@ pub fn elastic_api_handlers(
     search_service: Arc<dyn SearchService>,
     ingest_service: IngestServiceClient,
     ingest_router: IngestRouterServiceClient,
+    metastore: MetastoreServiceClient,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     es_compat_cluster_info_handler(node_config, BuildInfo::get())
         .or(es_compat_search_handler(search_service.clone()))
         .or(es_compat_index_search_handler(search_service.clone()))
+        .or(es_compat_index_count_handler(search_service.clone()))
         .or(es_compat_scroll_handler(search_service.clone()))
         .or(es_compat_index_multi_search_handler(search_service.clone()))
         .or(es_compat_index_field_capabilities_handler(
@@ -67,6 +73,11 @@ pub fn elastic_api_handlers(
             ingest_router.clone(),
         ))
         .or(es_compat_index_bulk_handler(ingest_service, ingest_router))
+        .or(es_compat_delete_by_query_handler(metastore.clone()))
+        .or(es_compat_index_exists_handler(metastore.clone()))
+        .or(es_compat_index_mapping_handler(metastore.clone()))
+        .or(es_compat_index_stats_handler(metastore.clone()))
+        .or(es_compat_cat_indices_handler(metastore))
     // Register newly created handlers here.
 }
 
@@ -119,7 +130,10 @@ mod tests {
     use mockall::predicate;
     use quickwit_config::NodeConfig;
     use quickwit_ingest::{IngestApiService, IngestServiceClient};
+    use quickwit_metastore::{IndexMetadata, IndexMetadataResponseExt};
     use quickwit_proto::ingest::router::IngestRouterServiceClient;
+    use quickwit_proto::metastore::{DeleteTask, IndexMetadataResponse, MetastoreServiceClient};
+    use quickwit_proto::search::{CountHits, SearchResponse};
     use quickwit_search::MockSearchService;
     use serde_json::Value as JsonValue;
     use warp::Filter;
@@ -137,6 +151,10 @@ mod tests {
         IngestServiceClient::from_mailbox(ingest_service_mailbox)
     }
 
+    fn metastore_client() -> MetastoreServiceClient {
+        MetastoreServiceClient::from(MetastoreServiceClient::mock())
+    }
+
     #[tokio::test]
     async fn test_msearch_api_return_200_responses() {
         let config = Arc::new(NodeConfig::for_test());
@@ -160,6 +178,7 @@ mod tests {
             Arc::new(mock_search_service),
             ingest_service_client(),
             ingest_router,
+            metastore_client(),
         );
         let msearch_payload = r#"
             {"index":"index-1"}
@@ -210,6 +229,7 @@ mod tests {
             Arc::new(mock_search_service),
             ingest_service_client(),
             ingest_router,
+            metastore_client(),
         );
         let msearch_payload = r#"
             {"index":"index-1"}
@@ -248,6 +268,7 @@ mod tests {
             Arc::new(mock_search_service),
             ingest_service_client(),
             ingest_router,
+            metastore_client(),
         );
         let msearch_payload = r#"
             {"index":"index-1"
@@ -279,6 +300,7 @@ mod tests {
             Arc::new(mock_search_service),
             ingest_service_client(),
             ingest_router,
+            metastore_client(),
         );
         let msearch_payload = r#"
             {"index":"index-1"}
@@ -310,6 +332,7 @@ mod tests {
             Arc::new(mock_search_service),
             ingest_service_client(),
             ingest_router,
+            metastore_client(),
         );
         let msearch_payload = r#"
             {"index":"index-1"}
@@ -340,6 +363,7 @@ mod tests {
             Arc::new(mock_search_service),
             ingest_service_client(),
             ingest_router,
+            metastore_client(),
         );
         let msearch_payload = r#"
             {}
@@ -382,6 +406,7 @@ mod tests {
             Arc::new(mock_search_service),
             ingest_service_client(),
             ingest_router,
+            metastore_client(),
         );
         let msearch_payload = r#"
             {"index": ["index-1", "index-2"]}
@@ -396,6 +421,267 @@ mod tests {
         assert_eq!(resp.status(), 200);
     }
 
+    #[tokio::test]
+    async fn test_count_api() {
+        let config = Arc::new(NodeConfig::for_test());
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .withf(|search_request| {
+                search_request.index_id_patterns == vec!["index-1".to_string()]
+                    && search_request.max_hits == 0
+                    && search_request.count_hits() == CountHits::CountAll
+            })
+            .returning(|_| {
+                Ok(SearchResponse {
+                    num_hits: 42,
+                    ..Default::default()
+                })
+            });
+        let ingest_router = IngestRouterServiceClient::from(IngestRouterServiceClient::mock());
+        let es_search_api_handler = super::elastic_api_handlers(
+            config,
+            Arc::new(mock_search_service),
+            ingest_service_client(),
+            ingest_router,
+            metastore_client(),
+        )
+        .recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/_elastic/index-1/_count")
+            .method("POST")
+            .body(r#"{"query": {"query_string": {"query": "test"}}}"#)
+            .reply(&es_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(resp_json, serde_json::json!({"count": 42}));
+
+        // Unlike the search endpoint, `_count` only accepts a query in its body.
+        let resp = warp::test::request()
+            .path("/_elastic/index-1/_count")
+            .method("POST")
+            .body(r#"{"size": 10}"#)
+            .reply(&es_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+    }
+
+    #[tokio::test]
+    async fn test_delete_by_query_api() {
+        let config = Arc::new(NodeConfig::for_test());
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        mock_metastore.expect_index_metadata().returning(|_| {
+            Ok(
+                IndexMetadataResponse::try_from_index_metadata(IndexMetadata::for_test(
+                    "test-index",
+                    "ram:///indexes/test-index",
+                ))
+                .unwrap(),
+            )
+        });
+        mock_metastore
+            .expect_create_delete_task()
+            .withf(|delete_query| {
+                delete_query.index_uid.starts_with("test-index:")
+                    && delete_query.start_timestamp.is_none()
+                    && delete_query.end_timestamp.is_none()
+            })
+            .return_once(|delete_query| {
+                Ok(DeleteTask {
+                    create_timestamp: 0,
+                    opstamp: 3,
+                    delete_query: Some(delete_query),
+                })
+            });
+        let ingest_router = IngestRouterServiceClient::from(IngestRouterServiceClient::mock());
+        let es_search_api_handler = super::elastic_api_handlers(
+            config,
+            Arc::new(MockSearchService::new()),
+            ingest_service_client(),
+            ingest_router,
+            MetastoreServiceClient::from(mock_metastore),
+        );
+        let resp = warp::test::request()
+            .path("/_elastic/test-index/_delete_by_query")
+            .method("POST")
+            .body(r#"{"query": {"term": {"owner": {"value": "alice"}}}}"#)
+            .reply(&es_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(resp_json, serde_json::json!({"task": "test-index:3"}));
+
+        // Queries on fields that do not exist in the doc mapping are rejected.
+        let resp = warp::test::request()
+            .path("/_elastic/test-index/_delete_by_query")
+            .method("POST")
+            .body(r#"{"query": {"term": {"unknown_field": {"value": "alice"}}}}"#)
+            .reply(&es_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+
+        // A query is required.
+        let resp = warp::test::request()
+            .path("/_elastic/test-index/_delete_by_query")
+            .method("POST")
+            .reply(&es_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+    }
+
+    #[tokio::test]
+    async fn test_index_metadata_apis() {
+        let config = Arc::new(NodeConfig::for_test());
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .returning(|list_indexes_metadata_request| {
+                let indexes_metadata = if list_indexes_metadata_request
+                    .index_id_patterns
+                    .iter()
+                    .any(|index_id_pattern| {
+                        index_id_pattern == "*" || index_id_pattern == "test-index"
+                    }) {
+                    vec![IndexMetadata::for_test(
+                        "test-index",
+                        "ram:///indexes/test-index",
+                    )]
+                } else {
+                    Vec::new()
+                };
+                Ok(
+                    ListIndexesMetadataResponse::try_from_indexes_metadata(indexes_metadata)
+                        .unwrap(),
+                )
+            });
+        mock_metastore.expect_list_splits().returning(|_| {
+            let index_uid = IndexUid::from_parts("test-index", "0");
+            let splits = vec![
+                MockSplitBuilder::new("split-1")
+                    .with_index_uid(&index_uid)
+                    .build(),
+                MockSplitBuilder::new("split-2")
+                    .with_index_uid(&index_uid)
+                    .build(),
+            ];
+            let splits = ListSplitsResponse::try_from_splits(splits).unwrap();
+            Ok(ServiceStream::from(vec![Ok(splits)]))
+        });
+        let ingest_router = IngestRouterServiceClient::from(IngestRouterServiceClient::mock());
+        let es_search_api_handler = super::elastic_api_handlers(
+            config,
+            Arc::new(MockSearchService::new()),
+            ingest_service_client(),
+            ingest_router,
+            MetastoreServiceClient::from(mock_metastore),
+        )
+        .recover(recover_fn);
+        {
+            let resp = warp::test::request()
+                .path("/_elastic/test-index")
+                .method("HEAD")
+                .reply(&es_search_api_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+
+            let resp = warp::test::request()
+                .path("/_elastic/unknown-index")
+                .method("HEAD")
+                .reply(&es_search_api_handler)
+                .await;
+            assert_eq!(resp.status(), 404);
+        }
+        {
+            let resp = warp::test::request()
+                .path("/_elastic/test-index/_mapping")
+                .reply(&es_search_api_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+            let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+            let expected_response_json = serde_json::json!({
+                "test-index": {
+                    "mappings": {
+                        "dynamic": "false",
+                        "properties": {
+                            "timestamp": {"type": "date_nanos"},
+                            "body": {"type": "text"},
+                            "owner": {"type": "keyword"},
+                        }
+                    }
+                }
+            });
+            assert_json_include!(actual: resp_json, expected: expected_response_json);
+        }
+        {
+            let resp = warp::test::request()
+                .path("/_elastic/_cat/indices?format=json")
+                .reply(&es_search_api_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+            let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+            let expected_response_json = serde_json::json!([{
+                "health": "green",
+                "status": "open",
+                "index": "test-index",
+                "docs.count": "20",
+                "docs.deleted": "0",
+                "store.size": "1.6kb",
+                "dataset.size": "512b",
+            }]);
+            assert_json_include!(actual: resp_json, expected: expected_response_json);
+
+            let resp = warp::test::request()
+                .path("/_elastic/_cat/indices/test-index?bytes=b")
+                .reply(&es_search_api_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+            let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+            let expected_response_json = serde_json::json!([{
+                "index": "test-index",
+                "store.size": "1600",
+            }]);
+            assert_json_include!(actual: resp_json, expected: expected_response_json);
+
+            let resp = warp::test::request()
+                .path("/_elastic/_cat/indices?format=yaml")
+                .reply(&es_search_api_handler)
+                .await;
+            assert_eq!(resp.status(), 400);
+        }
+        {
+            let resp = warp::test::request()
+                .path("/_elastic/test-index/_stats")
+                .reply(&es_search_api_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+            let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+            let expected_response_json = serde_json::json!({
+                "_all": {
+                    "primaries": {
+                        "docs": {"count": 20, "deleted": 0},
+                        "store": {"size_in_bytes": 1600}
+                    }
+                },
+                "indices": {
+                    "test-index": {
+                        "total": {
+                            "docs": {"count": 20, "deleted": 0},
+                            "store": {"size_in_bytes": 1600}
+                        }
+                    }
+                }
+            });
+            assert_json_include!(actual: resp_json, expected: expected_response_json);
+
+            let resp = warp::test::request()
+                .path("/_elastic/unknown-index/_stats")
+                .reply(&es_search_api_handler)
+                .await;
+            assert_eq!(resp.status(), 404);
+        }
+    }
+
     #[tokio::test]
     async fn test_es_compat_cluster_info_handler() {
         let build_info = BuildInfo::get();
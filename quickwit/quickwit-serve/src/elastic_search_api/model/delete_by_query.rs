@@ -0,0 +1,48 @@
+// Copyright (C) 2024 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use quickwit_query::{BooleanOperand, ElasticQueryDsl};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeleteByQueryParams {
+    #[serde(default)]
+    pub default_operator: Option<BooleanOperand>,
+    #[serde(default)]
+    pub q: Option<String>,
+    // Delete tasks are always executed asynchronously, this parameter is accepted for
+    // compatibility purposes but ignored.
+    #[serde(default)]
+    pub wait_for_completion: Option<bool>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DeleteByQueryBody {
+    #[serde(default)]
+    pub query: Option<ElasticQueryDsl>,
+}
+
+/// Response returned by Elasticsearch when a `_delete_by_query` request is executed
+/// asynchronously.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct DeleteByQueryResponse {
+    pub task: String,
+}
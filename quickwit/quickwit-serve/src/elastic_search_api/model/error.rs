@@ -20,11 +20,15 @@
 use elasticsearch_dsl::search::ErrorCause;
 use hyper::StatusCode;
 use quickwit_ingest::IngestServiceError;
+use quickwit_janitor::error::JanitorError;
 use quickwit_proto::ingest::IngestV2Error;
+use quickwit_proto::metastore::MetastoreError;
 use quickwit_proto::ServiceError;
 use quickwit_search::SearchError;
 use serde::{Deserialize, Serialize};
 
+use crate::auth::AuthError;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ElasticSearchError {
     #[serde(with = "http_serde::status_code")]
@@ -69,6 +73,26 @@ impl From<SearchError> for ElasticSearchError {
     }
 }
 
+impl From<AuthError> for ElasticSearchError {
+    fn from(auth_error: AuthError) -> Self {
+        let status = auth_error.error_code().to_http_status_code();
+
+        let reason = ErrorCause {
+            reason: Some(auth_error.to_string()),
+            caused_by: None,
+            root_cause: Vec::new(),
+            stack_trace: None,
+            suppressed: Vec::new(),
+            ty: None,
+            additional_details: Default::default(),
+        };
+        ElasticSearchError {
+            status,
+            error: reason,
+        }
+    }
+}
+
 impl From<IngestServiceError> for ElasticSearchError {
     fn from(ingest_service_error: IngestServiceError) -> Self {
         let status = ingest_service_error.error_code().to_http_status_code();
@@ -108,3 +132,43 @@ impl From<IngestV2Error> for ElasticSearchError {
         }
     }
 }
+
+impl From<JanitorError> for ElasticSearchError {
+    fn from(janitor_error: JanitorError) -> Self {
+        let status = janitor_error.error_code().to_http_status_code();
+
+        let reason = ErrorCause {
+            reason: Some(janitor_error.to_string()),
+            caused_by: None,
+            root_cause: Vec::new(),
+            stack_trace: None,
+            suppressed: Vec::new(),
+            ty: None,
+            additional_details: Default::default(),
+        };
+        ElasticSearchError {
+            status,
+            error: reason,
+        }
+    }
+}
+
+impl From<MetastoreError> for ElasticSearchError {
+    fn from(metastore_error: MetastoreError) -> Self {
+        let status = metastore_error.error_code().to_http_status_code();
+
+        let reason = ErrorCause {
+            reason: Some(metastore_error.to_string()),
+            caused_by: None,
+            root_cause: Vec::new(),
+            stack_trace: None,
+            suppressed: Vec::new(),
+            ty: None,
+            additional_details: Default::default(),
+        };
+        ElasticSearchError {
+            status,
+            error: reason,
+        }
+    }
+}
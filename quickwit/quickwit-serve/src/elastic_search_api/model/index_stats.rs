@@ -0,0 +1,265 @@
+// Copyright (C) 2024 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use std::ops::AddAssign;
+
+use quickwit_metastore::{IndexMetadata, SplitMetadata};
+use serde::{Deserialize, Serialize};
+
+/// Statistics of an index computed from the metadata of its published splits.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct IndexStats {
+    pub num_docs: u64,
+    pub size_in_bytes: u64,
+    pub uncompressed_docs_size_in_bytes: u64,
+}
+
+impl IndexStats {
+    pub fn from_splits<'a>(splits: impl IntoIterator<Item = &'a SplitMetadata>) -> Self {
+        let mut index_stats = IndexStats::default();
+
+        for split in splits {
+            index_stats.num_docs += split.num_docs as u64;
+            index_stats.size_in_bytes += split.footer_offsets.end;
+            index_stats.uncompressed_docs_size_in_bytes += split.uncompressed_docs_size_in_bytes;
+        }
+        index_stats
+    }
+}
+
+impl AddAssign for IndexStats {
+    fn add_assign(&mut self, other: IndexStats) {
+        self.num_docs += other.num_docs;
+        self.size_in_bytes += other.size_in_bytes;
+        self.uncompressed_docs_size_in_bytes += other.uncompressed_docs_size_in_bytes;
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CatIndexQueryParams {
+    #[serde(default)]
+    pub bytes: Option<ByteUnit>,
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ByteUnit {
+    B,
+    Kb,
+    Mb,
+    Gb,
+    Tb,
+    Pb,
+}
+
+impl ByteUnit {
+    const ALL: [ByteUnit; 6] = [
+        ByteUnit::B,
+        ByteUnit::Kb,
+        ByteUnit::Mb,
+        ByteUnit::Gb,
+        ByteUnit::Tb,
+        ByteUnit::Pb,
+    ];
+
+    fn num_bytes(&self) -> u64 {
+        1024u64.pow(*self as u32)
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            ByteUnit::B => "b",
+            ByteUnit::Kb => "kb",
+            ByteUnit::Mb => "mb",
+            ByteUnit::Gb => "gb",
+            ByteUnit::Tb => "tb",
+            ByteUnit::Pb => "pb",
+        }
+    }
+}
+
+/// Formats a size the way the `_cat` APIs of Elasticsearch do: as an integer if a unit is
+/// provided, as a human readable string otherwise.
+fn format_size(size_in_bytes: u64, byte_unit_opt: Option<ByteUnit>) -> String {
+    if let Some(byte_unit) = byte_unit_opt {
+        return (size_in_bytes / byte_unit.num_bytes()).to_string();
+    }
+    let byte_unit = ByteUnit::ALL
+        .into_iter()
+        .rev()
+        .find(|byte_unit| size_in_bytes >= byte_unit.num_bytes())
+        .unwrap_or(ByteUnit::B);
+
+    if byte_unit == ByteUnit::B {
+        return format!("{size_in_bytes}b");
+    }
+    let size = size_in_bytes as f64 / byte_unit.num_bytes() as f64;
+    let size_str = format!("{size:.1}");
+    let size_str = size_str.strip_suffix(".0").unwrap_or(&size_str);
+    format!("{size_str}{}", byte_unit.as_str())
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ElasticsearchCatIndexResponse {
+    pub health: &'static str,
+    pub status: &'static str,
+    pub index: String,
+    pub uuid: String,
+    pub pri: String,
+    pub rep: String,
+    #[serde(rename = "docs.count")]
+    pub docs_count: String,
+    #[serde(rename = "docs.deleted")]
+    pub docs_deleted: String,
+    #[serde(rename = "store.size")]
+    pub store_size: String,
+    #[serde(rename = "pri.store.size")]
+    pub pri_store_size: String,
+    #[serde(rename = "dataset.size")]
+    pub dataset_size: String,
+}
+
+impl ElasticsearchCatIndexResponse {
+    pub fn new(
+        index_metadata: &IndexMetadata,
+        index_stats: IndexStats,
+        byte_unit_opt: Option<ByteUnit>,
+    ) -> Self {
+        // Quickwit indexes have no replicas: their splits are stored on object storage.
+        let store_size = format_size(index_stats.size_in_bytes, byte_unit_opt);
+        ElasticsearchCatIndexResponse {
+            health: "green",
+            status: "open",
+            index: index_metadata.index_id().to_string(),
+            uuid: index_metadata.index_uid.incarnation_id().to_string(),
+            pri: "1".to_string(),
+            rep: "0".to_string(),
+            docs_count: index_stats.num_docs.to_string(),
+            docs_deleted: "0".to_string(),
+            store_size: store_size.clone(),
+            pri_store_size: store_size,
+            dataset_size: format_size(index_stats.uncompressed_docs_size_in_bytes, byte_unit_opt),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ElasticsearchStatsResponse {
+    pub _shards: ElasticsearchShardStats,
+    pub _all: ElasticsearchIndexStats,
+    pub indices: BTreeMap<String, ElasticsearchIndexStats>,
+}
+
+impl ElasticsearchStatsResponse {
+    pub fn new(indexes_stats: Vec<(IndexMetadata, IndexStats)>) -> Self {
+        let num_indexes = indexes_stats.len();
+        let mut all_stats = IndexStats::default();
+        let mut indices = BTreeMap::new();
+
+        for (index_metadata, index_stats) in indexes_stats {
+            all_stats += index_stats;
+            let es_index_stats = ElasticsearchIndexStats {
+                uuid: Some(index_metadata.index_uid.incarnation_id().to_string()),
+                primaries: index_stats.into(),
+                total: index_stats.into(),
+            };
+            indices.insert(index_metadata.index_id().to_string(), es_index_stats);
+        }
+        ElasticsearchStatsResponse {
+            _shards: ElasticsearchShardStats {
+                total: num_indexes,
+                successful: num_indexes,
+                failed: 0,
+            },
+            _all: ElasticsearchIndexStats {
+                uuid: None,
+                primaries: all_stats.into(),
+                total: all_stats.into(),
+            },
+            indices,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ElasticsearchShardStats {
+    pub total: usize,
+    pub successful: usize,
+    pub failed: usize,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ElasticsearchIndexStats {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    pub primaries: ElasticsearchStats,
+    pub total: ElasticsearchStats,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ElasticsearchStats {
+    pub docs: ElasticsearchDocStats,
+    pub store: ElasticsearchStoreStats,
+}
+
+impl From<IndexStats> for ElasticsearchStats {
+    fn from(index_stats: IndexStats) -> Self {
+        ElasticsearchStats {
+            docs: ElasticsearchDocStats {
+                count: index_stats.num_docs,
+                deleted: 0,
+            },
+            store: ElasticsearchStoreStats {
+                size_in_bytes: index_stats.size_in_bytes,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ElasticsearchDocStats {
+    pub count: u64,
+    pub deleted: u64,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ElasticsearchStoreStats {
+    pub size_in_bytes: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0, None), "0b");
+        assert_eq!(format_size(1023, None), "1023b");
+        assert_eq!(format_size(1024, None), "1kb");
+        assert_eq!(format_size(1536, None), "1.5kb");
+        assert_eq!(format_size(10 * 1024 * 1024, None), "10mb");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024 / 2, None), "1.5gb");
+        assert_eq!(format_size(1536, Some(ByteUnit::B)), "1536");
+        assert_eq!(format_size(1536, Some(ByteUnit::Kb)), "1");
+        assert_eq!(format_size(1536, Some(ByteUnit::Mb)), "0");
+    }
+}
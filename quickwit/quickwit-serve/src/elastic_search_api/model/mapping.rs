@@ -0,0 +1,142 @@
+// Copyright (C) 2024 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+
+use quickwit_config::DocMapping;
+use quickwit_doc_mapper::{FieldMappingEntry, FieldMappingType, ModeType, QuickwitTextTokenizer};
+use serde::Serialize;
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ElasticsearchIndexMappings {
+    pub mappings: JsonValue,
+}
+
+pub type ElasticsearchMappingsResponse = BTreeMap<String, ElasticsearchIndexMappings>;
+
+/// Translates the doc mapping of an index into an Elasticsearch mapping.
+pub fn convert_to_es_mapping(doc_mapping: &DocMapping) -> ElasticsearchIndexMappings {
+    let dynamic = match doc_mapping.mode.mode_type() {
+        ModeType::Lenient => "false",
+        ModeType::Strict => "strict",
+        ModeType::Dynamic => "true",
+    };
+    let mappings = json!({
+        "dynamic": dynamic,
+        "properties": convert_to_es_properties(&doc_mapping.field_mappings),
+    });
+    ElasticsearchIndexMappings { mappings }
+}
+
+fn convert_to_es_properties(field_mappings: &[FieldMappingEntry]) -> JsonMap<String, JsonValue> {
+    field_mappings
+        .iter()
+        .map(|field_mapping| {
+            (
+                field_mapping.name.clone(),
+                convert_to_es_property(&field_mapping.mapping_type),
+            )
+        })
+        .collect()
+}
+
+fn convert_to_es_property(mapping_type: &FieldMappingType) -> JsonValue {
+    // Types are consistent with the ones returned by the `_field_caps` endpoint. Elasticsearch
+    // has no dedicated array type, so the cardinality of the fields is ignored.
+    let es_type = match mapping_type {
+        FieldMappingType::Text(text_options, _) => {
+            let is_raw = text_options
+                .indexing_options
+                .as_ref()
+                .map(|indexing_options| indexing_options.tokenizer == QuickwitTextTokenizer::raw())
+                .unwrap_or(false);
+            if is_raw {
+                "keyword"
+            } else {
+                "text"
+            }
+        }
+        FieldMappingType::I64(..) | FieldMappingType::U64(..) => "long",
+        FieldMappingType::F64(..) => "double",
+        FieldMappingType::Bool(..) => "boolean",
+        FieldMappingType::IpAddr(..) => "ip",
+        FieldMappingType::DateTime(..) => "date_nanos",
+        FieldMappingType::Bytes(..) => "binary",
+        FieldMappingType::Json(..) => "object",
+        FieldMappingType::Object(object_options) => {
+            return json!({
+                "properties": convert_to_es_properties(&object_options.field_mappings),
+            });
+        }
+    };
+    json!({ "type": es_type })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_to_es_mapping() {
+        let doc_mapping: DocMapping = serde_json::from_str(
+            r#"{
+                "mode": "strict",
+                "field_mappings": [
+                    {"name": "timestamp", "type": "datetime", "fast": true},
+                    {"name": "body", "type": "text"},
+                    {"name": "severity", "type": "text", "tokenizer": "raw"},
+                    {"name": "tags", "type": "array<text>", "tokenizer": "raw"},
+                    {"name": "status_code", "type": "u64"},
+                    {"name": "latency", "type": "f64"},
+                    {"name": "attributes", "type": "json"},
+                    {
+                        "name": "resource",
+                        "type": "object",
+                        "field_mappings": [
+                            {"name": "ip", "type": "ip"},
+                            {"name": "healthy", "type": "bool"}
+                        ]
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let es_mapping = convert_to_es_mapping(&doc_mapping);
+        let expected_mappings = json!({
+            "dynamic": "strict",
+            "properties": {
+                "timestamp": {"type": "date_nanos"},
+                "body": {"type": "text"},
+                "severity": {"type": "keyword"},
+                "tags": {"type": "keyword"},
+                "status_code": {"type": "long"},
+                "latency": {"type": "double"},
+                "attributes": {"type": "object"},
+                "resource": {
+                    "properties": {
+                        "ip": {"type": "ip"},
+                        "healthy": {"type": "boolean"}
+                    }
+                }
+            }
+        });
+        assert_eq!(es_mapping.mappings, expected_mappings);
+    }
+}
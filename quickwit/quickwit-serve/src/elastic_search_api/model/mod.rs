@@ -19,8 +19,12 @@
 
 mod bulk_body;
 mod bulk_query_params;
+mod count;
+mod delete_by_query;
 mod error;
 mod field_capability;
+mod index_stats;
+mod mapping;
 mod multi_search;
 mod scroll;
 mod search_body;
@@ -28,11 +32,17 @@ mod search_query_params;
 
 pub use bulk_body::BulkAction;
 pub use bulk_query_params::ElasticBulkOptions;
+pub use count::{CountBody, CountResponse};
+pub use delete_by_query::{DeleteByQueryBody, DeleteByQueryParams, DeleteByQueryResponse};
 pub use error::ElasticSearchError;
 pub use field_capability::{
     build_list_field_request_for_es_api, convert_to_es_field_capabilities_response,
     FieldCapabilityQueryParams, FieldCapabilityRequestBody, FieldCapabilityResponse,
 };
+pub use index_stats::{
+    CatIndexQueryParams, ElasticsearchCatIndexResponse, ElasticsearchStatsResponse, IndexStats,
+};
+pub use mapping::{convert_to_es_mapping, ElasticsearchMappingsResponse};
 pub use multi_search::{
     MultiSearchHeader, MultiSearchQueryParams, MultiSearchResponse, MultiSearchSingleResponse,
 };
@@ -20,7 +20,7 @@
 use std::collections::BTreeSet;
 use std::fmt;
 
-use quickwit_proto::search::SortOrder;
+use quickwit_proto::search::{CollapseOptions, SnippetOptions, SortOrder};
 use quickwit_query::{ElasticQueryDsl, OneFieldMap};
 use serde::de::{MapAccess, Visitor};
 use serde::{Deserialize, Deserializer, Serialize};
@@ -86,6 +86,103 @@ pub struct SearchBody {
     pub stored_fields: Option<BTreeSet<String>>,
     #[serde(default)]
     pub search_after: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub timeout: Option<String>,
+    #[serde(default)]
+    pub highlight: Option<Highlight>,
+    #[serde(default)]
+    pub collapse: Option<Collapse>,
+}
+
+/// The subset of the Elasticsearch `collapse` options supported by Quickwit.
+///
+/// Inner hits are sorted like the hits: they do not support a `sort` of their own.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Collapse {
+    pub field: String,
+    #[serde(default)]
+    pub inner_hits: Option<InnerHits>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct InnerHits {
+    pub name: String,
+    #[serde(default = "default_inner_hits_size")]
+    pub size: u32,
+}
+
+fn default_inner_hits_size() -> u32 {
+    3
+}
+
+impl Collapse {
+    pub fn collapse_options(&self) -> CollapseOptions {
+        CollapseOptions {
+            field_name: self.field.clone(),
+            max_inner_hits: self
+                .inner_hits
+                .as_ref()
+                .map(|inner_hits| inner_hits.size)
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// The subset of the Elasticsearch `highlight` options supported by Quickwit.
+///
+/// Per-field options are not supported: the options set at the root of `highlight` apply to all
+/// the fields.
+#[derive(Debug, Default, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Highlight {
+    #[serde(deserialize_with = "deserialize_highlight_fields")]
+    pub fields: Vec<String>,
+    #[serde(default)]
+    pub pre_tags: Vec<String>,
+    #[serde(default)]
+    pub post_tags: Vec<String>,
+    #[serde(default)]
+    pub fragment_size: Option<u32>,
+    #[serde(default)]
+    pub number_of_fragments: Option<u32>,
+}
+
+impl Highlight {
+    pub fn snippet_options(&self) -> SnippetOptions {
+        // Elasticsearch cycles through the tags to highlight the different terms with different
+        // tags. We only use the first ones.
+        SnippetOptions {
+            fragment_size: self.fragment_size,
+            num_fragments: self.number_of_fragments,
+            pre_tag: self.pre_tags.first().cloned(),
+            post_tag: self.post_tags.first().cloned(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HighlightFieldsForDeser {
+    Object(serde_json::Map<String, serde_json::Value>),
+    // An array of single field objects, to preserve the order of the fields.
+    Array(Vec<serde_json::Map<String, serde_json::Value>>),
+}
+
+fn deserialize_highlight_fields<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where D: Deserializer<'de> {
+    let field_names = match HighlightFieldsForDeser::deserialize(deserializer)? {
+        HighlightFieldsForDeser::Object(fields) => fields
+            .into_iter()
+            .map(|(field_name, _)| field_name)
+            .collect(),
+        HighlightFieldsForDeser::Array(fields) => fields
+            .into_iter()
+            .flat_map(|fields| fields.into_iter().map(|(field_name, _)| field_name))
+            .collect(),
+    };
+    Ok(field_names)
 }
 
 struct FieldSortVecVisitor;
@@ -266,7 +363,100 @@ mod tests {
         assert!(error_msg.contains("unknown field `term`"));
         assert!(error_msg.contains(
             "expected one of `from`, `size`, `query`, `sort`, `aggs`, `track_total_hits`, \
-             `stored_fields`, `search_after`"
+             `stored_fields`, `search_after`, `timeout`, `highlight`, `collapse`"
         ));
     }
+
+    #[test]
+    fn test_highlight() {
+        let json = r#"
+        {
+            "highlight": {
+                "pre_tags": ["<em>"],
+                "post_tags": ["</em>"],
+                "fragment_size": 50,
+                "number_of_fragments": 3,
+                "fields": {
+                    "attributes.message": {},
+                    "body": {}
+                }
+            }
+        }
+        "#;
+        let search_body: SearchBody = serde_json::from_str(json).unwrap();
+        let highlight = search_body.highlight.unwrap();
+        assert_eq!(highlight.fields, ["attributes.message", "body"]);
+        assert_eq!(
+            highlight.snippet_options(),
+            SnippetOptions {
+                fragment_size: Some(50),
+                num_fragments: Some(3),
+                pre_tag: Some("<em>".to_string()),
+                post_tag: Some("</em>".to_string()),
+            }
+        );
+
+        let json = r#"
+        {
+            "highlight": {
+                "fields": [{"body": {}}, {"title": {}}]
+            }
+        }
+        "#;
+        let search_body: SearchBody = serde_json::from_str(json).unwrap();
+        let highlight = search_body.highlight.unwrap();
+        assert_eq!(highlight.fields, ["body", "title"]);
+        assert_eq!(highlight.snippet_options(), SnippetOptions::default());
+
+        // The wildcard and the whole field highlighting are handled by the searchers.
+        let json = r#"{"highlight": {"number_of_fragments": 0, "fields": {"*": {}}}}"#;
+        let search_body: SearchBody = serde_json::from_str(json).unwrap();
+        let highlight = search_body.highlight.unwrap();
+        assert_eq!(highlight.fields, ["*"]);
+        assert_eq!(highlight.snippet_options().num_fragments, Some(0));
+    }
+
+    #[test]
+    fn test_collapse() {
+        let json = r#"
+        {
+            "collapse": {
+                "field": "service",
+                "inner_hits": {"name": "latest", "size": 5}
+            }
+        }
+        "#;
+        let search_body: SearchBody = serde_json::from_str(json).unwrap();
+        let collapse = search_body.collapse.unwrap();
+        assert_eq!(collapse.inner_hits.as_ref().unwrap().name, "latest");
+        assert_eq!(
+            collapse.collapse_options(),
+            CollapseOptions {
+                field_name: "service".to_string(),
+                max_inner_hits: 5,
+            }
+        );
+
+        let json = r#"{"collapse": {"field": "service", "inner_hits": {"name": "latest"}}}"#;
+        let search_body: SearchBody = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            search_body
+                .collapse
+                .unwrap()
+                .collapse_options()
+                .max_inner_hits,
+            3
+        );
+
+        let json = r#"{"collapse": {"field": "service"}}"#;
+        let search_body: SearchBody = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            search_body
+                .collapse
+                .unwrap()
+                .collapse_options()
+                .max_inner_hits,
+            0
+        );
+    }
 }
@@ -17,23 +17,31 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::str::from_utf8;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use bytes::Bytes;
 use elasticsearch_dsl::search::{Hit as ElasticHit, SearchResponse as ElasticSearchResponse};
-use elasticsearch_dsl::{HitsMetadata, Source, TotalHits, TotalHitsRelation};
+use elasticsearch_dsl::{HitsMetadata, InnerHitsResult, Source, TotalHits, TotalHitsRelation};
 use futures_util::StreamExt;
 use hyper::StatusCode;
 use itertools::Itertools;
 use quickwit_common::truncate_str;
-use quickwit_config::{validate_index_id_pattern, NodeConfig};
+use quickwit_config::{validate_index_id_pattern, ApiKeyPermission, NodeConfig};
+use quickwit_metastore::{
+    IndexMetadata, ListIndexesMetadataResponseExt, ListSplitsQuery, ListSplitsRequestExt,
+    MetastoreServiceStreamSplitsExt, SplitMetadata, SplitState,
+};
+use quickwit_proto::metastore::{
+    ListIndexesMetadataRequest, ListSplitsRequest, MetastoreService, MetastoreServiceClient,
+};
 use quickwit_proto::search::{
     CountHits, ListFieldsResponse, PartialHit, ScrollRequest, SearchResponse, SortByValue,
     SortDatetimeFormat,
 };
+use quickwit_proto::types::IndexUid;
 use quickwit_proto::ServiceErrorCode;
 use quickwit_query::query_ast::{QueryAst, UserInputQuery};
 use quickwit_query::BooleanOperand;
@@ -42,19 +50,24 @@ use serde_json::json;
 use warp::{Filter, Rejection};
 
 use super::filter::{
-    elastic_cluster_info_filter, elastic_field_capabilities_filter,
-    elastic_index_field_capabilities_filter, elastic_index_search_filter,
-    elastic_multi_search_filter, elastic_scroll_filter, elastic_search_filter,
+    elastic_cluster_info_filter, elastic_delete_by_query_filter, elastic_field_capabilities_filter,
+    elastic_index_count_filter, elastic_index_field_capabilities_filter,
+    elastic_index_search_filter, elastic_multi_search_filter, elastic_scroll_filter,
+    elastic_search_filter,
 };
 use super::model::{
-    build_list_field_request_for_es_api, convert_to_es_field_capabilities_response,
+    build_list_field_request_for_es_api, convert_to_es_field_capabilities_response, CountBody,
+    CountResponse, DeleteByQueryBody, DeleteByQueryParams, DeleteByQueryResponse,
     ElasticSearchError, FieldCapabilityQueryParams, FieldCapabilityRequestBody,
     FieldCapabilityResponse, MultiSearchHeader, MultiSearchQueryParams, MultiSearchResponse,
     MultiSearchSingleResponse, ScrollQueryParams, SearchBody, SearchQueryParams,
 };
 use super::{make_elastic_api_response, TrackTotalHits};
+use crate::auth::{api_key_filter, check_index_permission, AuthenticatedApiKey};
+use crate::delete_task_api::create_delete_task;
 use crate::format::BodyFormat;
 use crate::json_api_response::{make_json_api_response, ApiError, JsonApiResponse};
+use crate::search_api::parse_search_timeout;
 use crate::{with_arg, BuildInfo};
 
 /// Elastic compatible cluster info handler.
@@ -119,12 +132,73 @@ pub fn es_compat_index_search_handler(
         .map(|result| make_elastic_api_response(result, BodyFormat::default()))
 }
 
+/// GET or POST _elastic/{index}/_count
+pub fn es_compat_index_count_handler(
+    search_service: Arc<dyn SearchService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_index_count_filter()
+        .and(with_arg(search_service))
+        .then(es_compat_index_count)
+        .map(|result| make_elastic_api_response(result, BodyFormat::default()))
+}
+
+/// POST _elastic/{index}/_delete_by_query
+pub fn es_compat_delete_by_query_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_delete_by_query_filter()
+        .and(with_arg(metastore))
+        .then(es_compat_delete_by_query)
+        .map(|result| make_elastic_api_response(result, BodyFormat::default()))
+}
+
+/// HEAD _elastic/{index}
+pub fn es_compat_index_exists_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_index_exists_filter()
+        .and(with_arg(metastore))
+        .then(es_compat_index_exists)
+        .map(|result| make_elastic_api_response(result, BodyFormat::default()))
+}
+
+/// GET _elastic/{index}/_mapping
+pub fn es_compat_index_mapping_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_index_mapping_filter()
+        .and(with_arg(metastore))
+        .then(es_compat_index_mapping)
+        .map(|result| make_elastic_api_response(result, BodyFormat::default()))
+}
+
+/// GET _elastic/{index}/_stats
+pub fn es_compat_index_stats_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_index_stats_filter()
+        .and(with_arg(metastore))
+        .then(es_compat_index_stats)
+        .map(|result| make_elastic_api_response(result, BodyFormat::default()))
+}
+
+/// GET _elastic/_cat/indices/{index}
+pub fn es_compat_cat_indices_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_cat_indices_filter()
+        .and(with_arg(metastore))
+        .then(es_compat_cat_indices)
+        .map(|result| make_elastic_api_response(result, BodyFormat::default()))
+}
+
 /// POST _elastic/_search
 pub fn es_compat_index_multi_search_handler(
     search_service: Arc<dyn SearchService>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     elastic_multi_search_filter()
         .and(with_arg(search_service))
+        .and(api_key_filter())
         .then(es_compat_index_multi_search)
         .map(|result: Result<MultiSearchResponse, ElasticSearchError>| {
             let status_code = match &result {
@@ -145,11 +219,20 @@ pub fn es_compat_scroll_handler(
         .map(|result| make_elastic_api_response(result, BodyFormat::default()))
 }
 
+/// Options of the conversion of the hits into Elasticsearch hits, derived from the request.
+#[derive(Debug, Default)]
+struct HitConversionOptions {
+    /// Whether the `_shard_doc` address is appended to the sort values of the hits.
+    append_shard_doc: bool,
+    /// Name of the inner hits of collapsed hits.
+    inner_hits_name_opt: Option<String>,
+}
+
 fn build_request_for_es_api(
     index_id_patterns: Vec<String>,
     search_params: SearchQueryParams,
     search_body: SearchBody,
-) -> Result<(quickwit_proto::search::SearchRequest, bool), ElasticSearchError> {
+) -> Result<(quickwit_proto::search::SearchRequest, HitConversionOptions), ElasticSearchError> {
     let default_operator = search_params.default_operator.unwrap_or(BooleanOperand::Or);
     // The query string, if present, takes priority over what can be in the request
     // body.
@@ -198,16 +281,36 @@ fn build_request_for_es_api(
         })
         .take_while_inclusive(|sort_field| !is_doc_field(sort_field))
         .collect();
-    if sort_fields.len() >= 3 {
-        return Err(ElasticSearchError::from(SearchError::InvalidArgument(
-            format!("only up to two sort fields supported at the moment. got {sort_fields:?}"),
-        )));
-    }
 
     let scroll_duration: Option<Duration> = search_params.parse_scroll_ttl()?;
     let scroll_ttl_secs: Option<u32> = scroll_duration.map(|duration| duration.as_secs() as u32);
+    // The query string parameter, if present, takes priority over the request body.
+    let timeout_millis: Option<u64> = search_params
+        .timeout
+        .as_deref()
+        .or(search_body.timeout.as_deref())
+        .map(parse_search_timeout)
+        .transpose()?;
 
-    let has_doc_id_field = sort_fields.iter().any(is_doc_field);
+    let (snippet_fields, snippet_options) = match search_body.highlight {
+        Some(highlight) => {
+            let snippet_options = highlight.snippet_options();
+            (highlight.fields, Some(snippet_options))
+        }
+        None => (Vec::new(), None),
+    };
+
+    let collapse = search_body
+        .collapse
+        .as_ref()
+        .map(|collapse| collapse.collapse_options());
+    let hit_conversion_options = HitConversionOptions {
+        append_shard_doc: sort_fields.iter().any(is_doc_field),
+        inner_hits_name_opt: search_body
+            .collapse
+            .and_then(|collapse| collapse.inner_hits)
+            .map(|inner_hits| inner_hits.name),
+    };
     let search_after = partial_hit_from_search_after_param(search_body.search_after, &sort_fields)?;
 
     Ok((
@@ -220,12 +323,16 @@ fn build_request_for_es_api(
             sort_fields,
             start_timestamp: None,
             end_timestamp: None,
-            snippet_fields: Vec::new(),
+            snippet_fields,
             scroll_ttl_secs,
             search_after,
             count_hits,
+            timeout_millis,
+            profile: false,
+            snippet_options,
+            collapse,
         },
-        has_doc_id_field,
+        hit_conversion_options,
     ))
 }
 
@@ -276,12 +383,7 @@ fn partial_hit_from_search_after_param(
                     "invalid search_after field value, expect bool, number or string".to_string(),
                 )
             })?;
-            // TODO make cleaner once we support Vec
-            if parsed_search_after.sort_value.is_none() {
-                parsed_search_after.sort_value = Some(value);
-            } else {
-                parsed_search_after.sort_value2 = Some(value);
-            }
+            parsed_search_after.sort_values.push(value);
         }
     }
     Ok(Some(parsed_search_after))
@@ -294,16 +396,205 @@ async fn es_compat_index_search(
     search_service: Arc<dyn SearchService>,
 ) -> Result<ElasticSearchResponse, ElasticSearchError> {
     let start_instant = Instant::now();
-    let (search_request, append_shard_doc) =
+    let (search_request, hit_conversion_options) =
         build_request_for_es_api(index_id_patterns, search_params, search_body)?;
     let search_response: SearchResponse = search_service.root_search(search_request).await?;
     let elapsed = start_instant.elapsed();
     let mut search_response_rest: ElasticSearchResponse =
-        convert_to_es_search_response(search_response, append_shard_doc);
+        convert_to_es_search_response(search_response, &hit_conversion_options);
     search_response_rest.took = elapsed.as_millis() as u32;
     Ok(search_response_rest)
 }
 
+async fn es_compat_index_count(
+    index_id_patterns: Vec<String>,
+    search_params: SearchQueryParams,
+    count_body: CountBody,
+    search_service: Arc<dyn SearchService>,
+) -> Result<CountResponse, ElasticSearchError> {
+    let search_body = SearchBody {
+        query: count_body.query,
+        ..Default::default()
+    };
+    let (mut search_request, _hit_conversion_options) =
+        build_request_for_es_api(index_id_patterns, search_params, search_body)?;
+    search_request.max_hits = 0;
+    search_request.start_offset = 0;
+    search_request.count_hits = CountHits::CountAll.into();
+    let search_response: SearchResponse = search_service.root_search(search_request).await?;
+    let count_response = CountResponse {
+        count: search_response.num_hits,
+    };
+    Ok(count_response)
+}
+
+async fn es_compat_delete_by_query(
+    index_id: String,
+    delete_by_query_params: DeleteByQueryParams,
+    delete_by_query_body: DeleteByQueryBody,
+    metastore: MetastoreServiceClient,
+) -> Result<DeleteByQueryResponse, ElasticSearchError> {
+    // The query string, if present, takes priority over what can be in the request
+    // body.
+    let query_ast: QueryAst = if let Some(q) = delete_by_query_params.q {
+        let default_operator = delete_by_query_params
+            .default_operator
+            .unwrap_or(BooleanOperand::Or);
+        UserInputQuery {
+            user_text: q,
+            default_fields: None,
+            default_operator,
+        }
+        .into()
+    } else if let Some(query_dsl) = delete_by_query_body.query {
+        query_dsl
+            .try_into()
+            .map_err(|err: anyhow::Error| SearchError::InvalidQuery(err.to_string()))?
+    } else {
+        return Err(ElasticSearchError::new(
+            StatusCode::BAD_REQUEST,
+            "`_delete_by_query` request must define a query in its body or with the `q` parameter"
+                .to_string(),
+        ));
+    };
+    let delete_task = create_delete_task(metastore, &index_id, query_ast, None, None).await?;
+    // Elasticsearch task IDs are of the form `{node_id}:{task_number}`. Delete tasks are
+    // identified by their index and their opstamp.
+    let delete_by_query_response = DeleteByQueryResponse {
+        task: format!("{index_id}:{}", delete_task.opstamp),
+    };
+    Ok(delete_by_query_response)
+}
+
+/// Lists the metadata of the indexes matching the index ID patterns, or of all the indexes if no
+/// pattern is provided. Returns a not found error if the patterns do not match any index.
+async fn list_indexes_metadata(
+    mut metastore: MetastoreServiceClient,
+    index_id_patterns: Vec<String>,
+) -> Result<Vec<IndexMetadata>, ElasticSearchError> {
+    if index_id_patterns.is_empty() {
+        let indexes_metadata = metastore
+            .list_indexes_metadata(ListIndexesMetadataRequest::all())
+            .await?
+            .deserialize_indexes_metadata()?;
+        return Ok(indexes_metadata);
+    }
+    let list_indexes_metadata_request = ListIndexesMetadataRequest {
+        index_id_patterns: index_id_patterns.clone(),
+    };
+    let indexes_metadata = metastore
+        .list_indexes_metadata(list_indexes_metadata_request)
+        .await?
+        .deserialize_indexes_metadata()?;
+
+    if indexes_metadata.is_empty() {
+        return Err(ElasticSearchError::new(
+            StatusCode::NOT_FOUND,
+            format!("no such index [{}]", index_id_patterns.join(",")),
+        ));
+    }
+    Ok(indexes_metadata)
+}
+
+/// Computes the stats of the indexes from the metadata of their published splits.
+async fn compute_indexes_stats(
+    mut metastore: MetastoreServiceClient,
+    indexes_metadata: Vec<IndexMetadata>,
+) -> Result<Vec<(IndexMetadata, IndexStats)>, ElasticSearchError> {
+    if indexes_metadata.is_empty() {
+        return Ok(Vec::new());
+    }
+    let index_uids: Vec<IndexUid> = indexes_metadata
+        .iter()
+        .map(|index_metadata| index_metadata.index_uid.clone())
+        .collect();
+    let query =
+        ListSplitsQuery::try_from_index_uids(index_uids)?.with_split_state(SplitState::Published);
+    let list_splits_request = ListSplitsRequest::try_from_list_splits_query(query)?;
+    let splits_metadata = metastore
+        .list_splits(list_splits_request)
+        .await?
+        .collect_splits_metadata()
+        .await?;
+    let mut splits_metadata_per_index: HashMap<IndexUid, Vec<SplitMetadata>> = HashMap::new();
+
+    for split_metadata in splits_metadata {
+        splits_metadata_per_index
+            .entry(split_metadata.index_uid.clone())
+            .or_default()
+            .push(split_metadata);
+    }
+    let indexes_stats = indexes_metadata
+        .into_iter()
+        .map(|index_metadata| {
+            let index_stats = splits_metadata_per_index
+                .get(&index_metadata.index_uid)
+                .map(IndexStats::from_splits)
+                .unwrap_or_default();
+            (index_metadata, index_stats)
+        })
+        .collect();
+    Ok(indexes_stats)
+}
+
+async fn es_compat_index_exists(
+    index_id_patterns: Vec<String>,
+    metastore: MetastoreServiceClient,
+) -> Result<(), ElasticSearchError> {
+    list_indexes_metadata(metastore, index_id_patterns).await?;
+    Ok(())
+}
+
+async fn es_compat_index_mapping(
+    index_id_patterns: Vec<String>,
+    metastore: MetastoreServiceClient,
+) -> Result<ElasticsearchMappingsResponse, ElasticSearchError> {
+    let indexes_metadata = list_indexes_metadata(metastore, index_id_patterns).await?;
+    let mappings_response = indexes_metadata
+        .iter()
+        .map(|index_metadata| {
+            (
+                index_metadata.index_id().to_string(),
+                convert_to_es_mapping(&index_metadata.index_config.doc_mapping),
+            )
+        })
+        .collect();
+    Ok(mappings_response)
+}
+
+async fn es_compat_index_stats(
+    index_id_patterns: Vec<String>,
+    metastore: MetastoreServiceClient,
+) -> Result<ElasticsearchStatsResponse, ElasticSearchError> {
+    let indexes_metadata = list_indexes_metadata(metastore.clone(), index_id_patterns).await?;
+    let indexes_stats = compute_indexes_stats(metastore, indexes_metadata).await?;
+    Ok(ElasticsearchStatsResponse::new(indexes_stats))
+}
+
+async fn es_compat_cat_indices(
+    index_id_patterns: Vec<String>,
+    query_params: CatIndexQueryParams,
+    metastore: MetastoreServiceClient,
+) -> Result<Vec<ElasticsearchCatIndexResponse>, ElasticSearchError> {
+    if let Some(format) = &query_params.format {
+        if format != "json" {
+            return Err(ElasticSearchError::new(
+                StatusCode::BAD_REQUEST,
+                format!("`{format}` format is not supported, only `json` is"),
+            ));
+        }
+    }
+    let indexes_metadata = list_indexes_metadata(metastore.clone(), index_id_patterns).await?;
+    let indexes_stats = compute_indexes_stats(metastore, indexes_metadata).await?;
+    let cat_indices_response = indexes_stats
+        .iter()
+        .map(|(index_metadata, index_stats)| {
+            ElasticsearchCatIndexResponse::new(index_metadata, *index_stats, query_params.bytes)
+        })
+        .collect();
+    Ok(cat_indices_response)
+}
+
 async fn es_compat_index_field_capabilities(
     index_id_patterns: Vec<String>,
     search_params: FieldCapabilityQueryParams,
@@ -319,23 +610,51 @@ async fn es_compat_index_field_capabilities(
     Ok(search_response_rest)
 }
 
-fn convert_hit(hit: quickwit_proto::search::Hit, append_shard_doc: bool) -> ElasticHit {
+fn convert_hit(
+    hit: quickwit_proto::search::Hit,
+    hit_conversion_options: &HitConversionOptions,
+) -> ElasticHit {
     let fields: BTreeMap<String, serde_json::Value> =
         serde_json::from_str(&hit.json).unwrap_or_default();
+    // Elasticsearch omits the fields without any highlighted fragment.
+    let highlight = hit
+        .snippet
+        .as_deref()
+        .and_then(|snippet_json| {
+            serde_json::from_str::<BTreeMap<String, Vec<String>>>(snippet_json).ok()
+        })
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(_, fragments)| !fragments.is_empty())
+        .collect();
     let mut sort = Vec::new();
     if let Some(partial_hit) = hit.partial_hit {
-        if let Some(sort_value) = partial_hit.sort_value {
-            sort.push(sort_value.into_json());
-        }
-        if let Some(sort_value2) = partial_hit.sort_value2 {
-            sort.push(sort_value2.into_json());
+        for sort_value in &partial_hit.sort_values {
+            sort.push(sort_value.clone().into_json());
         }
-        if append_shard_doc {
+        if hit_conversion_options.append_shard_doc {
             sort.push(serde_json::Value::String(
                 quickwit_search::GlobalDocAddress::from_partial_hit(&partial_hit).to_string(),
             ));
         }
     }
+    let mut inner_hits = BTreeMap::new();
+    if let Some(inner_hits_name) = &hit_conversion_options.inner_hits_name_opt {
+        let hits = hit
+            .inner_hits
+            .into_iter()
+            .map(|inner_hit| convert_hit(inner_hit, hit_conversion_options))
+            .collect();
+        // The total number of documents of the group is not known, so it is omitted.
+        let inner_hits_result = InnerHitsResult {
+            hits: HitsMetadata {
+                total: None,
+                max_score: None,
+                hits,
+            },
+        };
+        inner_hits.insert(inner_hits_name.clone(), inner_hits_result);
+    }
 
     ElasticHit {
         fields,
@@ -346,8 +665,8 @@ fn convert_hit(hit: quickwit_proto::search::Hit, append_shard_doc: bool) -> Elas
         nested: None,
         source: Source::from_string(hit.json)
             .unwrap_or_else(|_| Source::from_string("{}".to_string()).unwrap()),
-        highlight: Default::default(),
-        inner_hits: Default::default(),
+        highlight,
+        inner_hits,
         matched_queries: Vec::default(),
         sort,
     }
@@ -357,6 +676,7 @@ async fn es_compat_index_multi_search(
     payload: Bytes,
     multi_search_params: MultiSearchQueryParams,
     search_service: Arc<dyn SearchService>,
+    api_key_opt: Option<AuthenticatedApiKey>,
 ) -> Result<MultiSearchResponse, ElasticSearchError> {
     let mut search_requests = Vec::new();
     let str_payload = from_utf8(&payload)
@@ -383,6 +703,7 @@ async fn es_compat_index_multi_search(
                     err
                 ))
             })?;
+            check_index_permission(api_key_opt.as_ref(), ApiKeyPermission::Search, index)?;
         }
         let index_ids_patterns = request_header.index.clone();
         let search_body = payload_lines
@@ -405,10 +726,10 @@ async fn es_compat_index_multi_search(
         search_requests.push(es_request);
     }
     // TODO: forced to do weird referencing to work around https://github.com/rust-lang/rust/issues/100905
-    // otherwise append_shard_doc is captured by ref, and we get lifetime issues
+    // otherwise hit_conversion_options is captured by ref, and we get lifetime issues
     let futures = search_requests
         .into_iter()
-        .map(|(search_request, append_shard_doc)| {
+        .map(|(search_request, hit_conversion_options)| {
             let search_service = &search_service;
             async move {
                 let start_instant = Instant::now();
@@ -416,7 +737,7 @@ async fn es_compat_index_multi_search(
                     search_service.clone().root_search(search_request).await?;
                 let elapsed = start_instant.elapsed();
                 let mut search_response_rest: ElasticSearchResponse =
-                    convert_to_es_search_response(search_response, append_shard_doc);
+                    convert_to_es_search_response(search_response, &hit_conversion_options);
                 search_response_rest.took = elapsed.as_millis() as u32;
                 Ok::<_, ElasticSearchError>(search_response_rest)
             }
@@ -460,19 +781,19 @@ async fn es_scroll(
     let search_response: SearchResponse = search_service.scroll(scroll_request).await?;
     // TODO append_shard_doc depends on the initial request, but we don't have access to it
     let mut search_response_rest: ElasticSearchResponse =
-        convert_to_es_search_response(search_response, false);
+        convert_to_es_search_response(search_response, &HitConversionOptions::default());
     search_response_rest.took = start_instant.elapsed().as_millis() as u32;
     Ok(search_response_rest)
 }
 
 fn convert_to_es_search_response(
     resp: SearchResponse,
-    append_shard_doc: bool,
+    hit_conversion_options: &HitConversionOptions,
 ) -> ElasticSearchResponse {
     let hits: Vec<ElasticHit> = resp
         .hits
         .into_iter()
-        .map(|hit| convert_hit(hit, append_shard_doc))
+        .map(|hit| convert_hit(hit, hit_conversion_options))
         .collect();
     let aggregations: Option<serde_json::Value> = if let Some(aggregation_json) = resp.aggregation {
         serde_json::from_str(&aggregation_json).ok()
@@ -480,7 +801,7 @@ fn convert_to_es_search_response(
         None
     };
     ElasticSearchResponse {
-        timed_out: false,
+        timed_out: resp.timed_out,
         hits: HitsMetadata {
             total: Some(TotalHits {
                 value: resp.num_hits,
@@ -504,8 +825,9 @@ pub(crate) fn str_lines(body: &str) -> impl Iterator<Item = &str> {
 #[cfg(test)]
 mod tests {
     use hyper::StatusCode;
+    use quickwit_proto::search::{Hit, PartialHit, SortByValue, SortField, SortValue};
 
-    use super::partial_hit_from_search_after_param;
+    use super::{convert_hit, partial_hit_from_search_after_param, HitConversionOptions};
 
     #[test]
     fn test_partial_hit_from_search_after_param_invalid_length() {
@@ -551,4 +873,69 @@ mod tests {
              u32}`"
         );
     }
+
+    #[test]
+    fn test_partial_hit_from_search_after_param_multiple_sort_fields() {
+        let search_after = vec![
+            serde_json::json!("api"),
+            serde_json::json!(3),
+            serde_json::json!(null),
+            serde_json::json!("split_id:1:2"),
+        ];
+        let sort_order: Vec<SortField> = ["service", "severity", "host.name", "_shard_doc"]
+            .into_iter()
+            .map(|field_name| SortField {
+                field_name: field_name.to_string(),
+                sort_order: 1,
+                sort_datetime_format: None,
+            })
+            .collect();
+        let partial_hit = partial_hit_from_search_after_param(search_after, &sort_order)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            partial_hit,
+            PartialHit {
+                sort_values: vec![
+                    SortValue::Str("api".to_string()).into(),
+                    SortValue::U64(3).into(),
+                    SortByValue { sort_value: None },
+                ],
+                split_id: "split_id".to_string(),
+                segment_ord: 1,
+                doc_id: 2,
+                collapse_value: None,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_convert_hit_with_inner_hits() {
+        let hit = |json: &str| Hit {
+            json: json.to_string(),
+            partial_hit: None,
+            snippet: None,
+            index_id: "my-index".to_string(),
+            inner_hits: Vec::new(),
+        };
+        let mut group_hit = hit(r#"{"service": "api", "severity": 3}"#);
+        group_hit.inner_hits = vec![
+            hit(r#"{"service": "api", "severity": 3}"#),
+            hit(r#"{"service": "api", "severity": 2}"#),
+        ];
+        let elastic_hit = convert_hit(group_hit.clone(), &HitConversionOptions::default());
+        assert!(elastic_hit.inner_hits.is_empty());
+
+        let hit_conversion_options = HitConversionOptions {
+            append_shard_doc: false,
+            inner_hits_name_opt: Some("latest".to_string()),
+        };
+        let elastic_hit = convert_hit(group_hit, &hit_conversion_options);
+        let inner_hits = &elastic_hit.inner_hits["latest"].hits;
+        assert!(inner_hits.total.is_none());
+        assert_eq!(inner_hits.hits.len(), 2);
+        assert_eq!(inner_hits.hits[1].index, "my-index");
+        assert_eq!(inner_hits.hits[1].fields["severity"], serde_json::json!(2));
+    }
 }
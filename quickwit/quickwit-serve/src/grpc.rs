@@ -22,17 +22,22 @@ use std::net::SocketAddr;
 use std::sync::Arc;
 
 use bytesize::ByteSize;
+use quickwit_common::tls::{bind_tls_incoming, ReloadableTlsConfig, ALPN_H2};
 use quickwit_common::tower::BoxFutureInfaillible;
 use quickwit_config::service::QuickwitService;
+use quickwit_opentelemetry::otlp::OtelSignal;
 use quickwit_proto::indexing::IndexingServiceClient;
+use quickwit_proto::jaeger::storage::v1::dependencies_reader_plugin_server::DependenciesReaderPluginServer;
 use quickwit_proto::jaeger::storage::v1::span_reader_plugin_server::SpanReaderPluginServer;
 use quickwit_proto::opentelemetry::proto::collector::logs::v1::logs_service_server::LogsServiceServer;
+use quickwit_proto::opentelemetry::proto::collector::metrics::v1::metrics_service_server::MetricsServiceServer;
 use quickwit_proto::opentelemetry::proto::collector::trace::v1::trace_service_server::TraceServiceServer;
 use quickwit_proto::search::search_service_server::SearchServiceServer;
-use quickwit_proto::tonic::codegen::CompressionEncoding;
+use quickwit_proto::tonic::codegen::{CompressionEncoding, InterceptedService};
 use quickwit_proto::tonic::transport::Server;
 use tracing::*;
 
+use crate::auth::{ApiKeyAuthInterceptor, ApiKeyAuthLayer};
 use crate::search_api::GrpcSearchAdapter;
 use crate::QuickwitServices;
 
@@ -47,6 +52,12 @@ pub(crate) async fn start_grpc_server(
     let mut enabled_grpc_services = BTreeSet::new();
     let mut server = Server::builder();
 
+    // The public services (OTLP and Jaeger) are authenticated with the API keys of the REST API.
+    // The internal services (metastore, search, control plane, indexing, ingest, etc.) are called
+    // by the other nodes, which do not hold API keys: they stay unauthenticated and must only be
+    // reachable from within the cluster, or be restricted with mutual TLS.
+    let auth_layer = ApiKeyAuthLayer::try_new(&services.node_config.rest_config.api_keys)?;
+
     // Mount gRPC metastore service if `QuickwitService::Metastore` is enabled on node.
     let metastore_grpc_service = if let Some(metastore_server) = &services.metastore_server_opt {
         enabled_grpc_services.insert("metastore");
@@ -119,24 +130,39 @@ pub(crate) async fn start_grpc_server(
         None
     };
     // Mount gRPC OpenTelemetry OTLP services if present.
-    let otlp_trace_grpc_service =
-        if let Some(otlp_traces_service) = services.otlp_traces_service_opt.clone() {
-            enabled_grpc_services.insert("otlp-trace");
-            let trace_service = TraceServiceServer::new(otlp_traces_service)
-                .accept_compressed(CompressionEncoding::Gzip);
-            Some(trace_service)
-        } else {
-            None
-        };
-    let otlp_log_grpc_service =
-        if let Some(otlp_logs_service) = services.otlp_logs_service_opt.clone() {
-            enabled_grpc_services.insert("otlp-log");
-            let logs_service = LogsServiceServer::new(otlp_logs_service)
-                .accept_compressed(CompressionEncoding::Gzip);
-            Some(logs_service)
-        } else {
-            None
-        };
+    let otlp_trace_grpc_service = if let Some(otlp_traces_service) =
+        services.otlp_traces_service_opt.clone()
+    {
+        enabled_grpc_services.insert("otlp-trace");
+        let trace_service = TraceServiceServer::new(otlp_traces_service)
+            .accept_compressed(CompressionEncoding::Gzip);
+        let auth_interceptor = ApiKeyAuthInterceptor::otlp(auth_layer.clone(), OtelSignal::Traces);
+        Some(InterceptedService::new(trace_service, auth_interceptor))
+    } else {
+        None
+    };
+    let otlp_log_grpc_service = if let Some(otlp_logs_service) =
+        services.otlp_logs_service_opt.clone()
+    {
+        enabled_grpc_services.insert("otlp-log");
+        let logs_service =
+            LogsServiceServer::new(otlp_logs_service).accept_compressed(CompressionEncoding::Gzip);
+        let auth_interceptor = ApiKeyAuthInterceptor::otlp(auth_layer.clone(), OtelSignal::Logs);
+        Some(InterceptedService::new(logs_service, auth_interceptor))
+    } else {
+        None
+    };
+    let otlp_metrics_grpc_service = if let Some(otlp_metrics_service) =
+        services.otlp_metrics_service_opt.clone()
+    {
+        enabled_grpc_services.insert("otlp-metrics");
+        let metrics_service = MetricsServiceServer::new(otlp_metrics_service)
+            .accept_compressed(CompressionEncoding::Gzip);
+        let auth_interceptor = ApiKeyAuthInterceptor::otlp(auth_layer.clone(), OtelSignal::Metrics);
+        Some(InterceptedService::new(metrics_service, auth_interceptor))
+    } else {
+        None
+    };
     // Mount gRPC search service if `QuickwitService::Searcher` is enabled on node.
     let search_grpc_service = if services
         .node_config
@@ -154,13 +180,24 @@ pub(crate) async fn start_grpc_server(
         None
     };
 
-    // Mount gRPC jaeger service if present.
-    let jaeger_grpc_service = if let Some(jaeger_service) = services.jaeger_service_opt.clone() {
-        enabled_grpc_services.insert("jaeger");
-        Some(SpanReaderPluginServer::new(jaeger_service))
-    } else {
-        None
-    };
+    // Mount gRPC jaeger services if present.
+    let (jaeger_grpc_service, jaeger_dependencies_grpc_service) =
+        if let Some(jaeger_service) = services.jaeger_service_opt.clone() {
+            enabled_grpc_services.insert("jaeger");
+            let auth_interceptor = ApiKeyAuthInterceptor::jaeger(auth_layer);
+            (
+                Some(SpanReaderPluginServer::with_interceptor(
+                    jaeger_service.clone(),
+                    auth_interceptor.clone(),
+                )),
+                Some(DependenciesReaderPluginServer::with_interceptor(
+                    jaeger_service,
+                    auth_interceptor,
+                )),
+            )
+        } else {
+            (None, None)
+        };
     let server_router = server
         .add_optional_service(control_plane_grpc_service)
         .add_optional_service(indexing_grpc_service)
@@ -168,18 +205,35 @@ pub(crate) async fn start_grpc_server(
         .add_optional_service(ingest_router_grpc_service)
         .add_optional_service(ingester_grpc_service)
         .add_optional_service(jaeger_grpc_service)
+        .add_optional_service(jaeger_dependencies_grpc_service)
         .add_optional_service(metastore_grpc_service)
         .add_optional_service(otlp_log_grpc_service)
+        .add_optional_service(otlp_metrics_grpc_service)
         .add_optional_service(otlp_trace_grpc_service)
         .add_optional_service(search_grpc_service);
 
+    let tls_config_opt = services.node_config.grpc_config.tls.as_ref();
+
     info!(
         enabled_grpc_services=?enabled_grpc_services,
         grpc_listen_addr=?grpc_listen_addr,
+        tls_enabled=tls_config_opt.is_some(),
         "Starting gRPC server listening on {grpc_listen_addr}."
     );
-    let serve_fut = server_router.serve_with_shutdown(grpc_listen_addr, shutdown_signal);
-    let (serve_res, _trigger_res) = tokio::join!(serve_fut, readiness_trigger);
+    let serve_res = if let Some(tls_config) = tls_config_opt {
+        let validate_client = tls_config.validate_client;
+        let server_config = ReloadableTlsConfig::load(tls_config.tls_files(), move |tls_files| {
+            tls_files.server_config(validate_client, &[ALPN_H2])
+        })?;
+        let tls_incoming = bind_tls_incoming(grpc_listen_addr, server_config).await?;
+        let serve_fut = server_router.serve_with_incoming_shutdown(tls_incoming, shutdown_signal);
+        let (serve_res, _trigger_res) = tokio::join!(serve_fut, readiness_trigger);
+        serve_res
+    } else {
+        let serve_fut = server_router.serve_with_shutdown(grpc_listen_addr, shutdown_signal);
+        let (serve_res, _trigger_res) = tokio::join!(serve_fut, readiness_trigger);
+        serve_res
+    };
     serve_res?;
     Ok(())
 }
@@ -23,8 +23,8 @@ use bytes::Bytes;
 use hyper::header::CONTENT_TYPE;
 use quickwit_common::uri::Uri;
 use quickwit_config::{
-    load_source_config_from_user_config, ConfigFormat, NodeConfig, SourceConfig, SourceParams,
-    CLI_INGEST_SOURCE_ID, INGEST_API_SOURCE_ID,
+    load_source_config_from_user_config, ApiKeyPermission, ConfigFormat, NodeConfig, SourceConfig,
+    SourceParams, CLI_INGEST_SOURCE_ID, INGEST_API_SOURCE_ID,
 };
 use quickwit_doc_mapper::{analyze_text, TokenizerConfig};
 use quickwit_index_management::{IndexService, IndexServiceError};
@@ -38,12 +38,14 @@ use quickwit_proto::metastore::{
     MetastoreService, MetastoreServiceClient, ResetSourceCheckpointRequest, ToggleSourceRequest,
 };
 use quickwit_proto::types::IndexUid;
+use quickwit_proto::{ServiceError, ServiceErrorCode};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tracing::info;
 use warp::{Filter, Rejection};
 
+use crate::auth::{api_key_filter, check_index_permission, AuthError, AuthenticatedApiKey};
 use crate::format::extract_format_from_qs;
 use crate::json_api_response::make_json_api_response;
 use crate::simple_list::{from_simple_list, to_simple_list};
@@ -53,6 +55,7 @@ use crate::with_arg;
 #[openapi(
     paths(
         create_index,
+        update_index,
         clear_index,
         delete_index,
         get_indexes_metadatas,
@@ -75,7 +78,11 @@ pub fn index_management_handlers(
     // Indexes handlers.
     get_index_metadata_handler(index_service.metastore())
         .or(get_indexes_metadatas_handler(index_service.metastore()))
-        .or(create_index_handler(index_service.clone(), node_config))
+        .or(create_index_handler(
+            index_service.clone(),
+            node_config.clone(),
+        ))
+        .or(update_index_handler(index_service.clone(), node_config))
         .or(clear_index_handler(index_service.clone()))
         .or(delete_index_handler(index_service.clone()))
         // Splits handlers
@@ -447,6 +454,23 @@ async fn get_indexes_metadatas(
         .and_then(|response| response.deserialize_indexes_metadata())
 }
 
+#[derive(Debug, Error)]
+enum CreateIndexError {
+    #[error(transparent)]
+    Auth(#[from] AuthError),
+    #[error(transparent)]
+    IndexService(#[from] IndexServiceError),
+}
+
+impl ServiceError for CreateIndexError {
+    fn error_code(&self) -> ServiceErrorCode {
+        match self {
+            Self::Auth(error) => error.error_code(),
+            Self::IndexService(error) => error.error_code(),
+        }
+    }
+}
+
 #[derive(Deserialize, utoipa::IntoParams, utoipa::ToSchema)]
 #[into_params(parameter_in = Query)]
 struct CreateIndexQueryParams {
@@ -466,6 +490,7 @@ fn create_index_handler(
         .and(warp::filters::body::bytes())
         .and(with_arg(index_service))
         .and(with_arg(node_config))
+        .and(api_key_filter())
         .then(create_index)
         .and(extract_format_from_qs())
         .map(make_json_api_response)
@@ -491,17 +516,74 @@ async fn create_index(
     index_config_bytes: Bytes,
     mut index_service: IndexService,
     node_config: Arc<NodeConfig>,
-) -> Result<IndexMetadata, IndexServiceError> {
+    api_key_opt: Option<AuthenticatedApiKey>,
+) -> Result<IndexMetadata, CreateIndexError> {
     let index_config = quickwit_config::load_index_config_from_user_config(
         config_format,
         &index_config_bytes,
         &node_config.default_index_root_uri,
     )
     .map_err(IndexServiceError::InvalidConfig)?;
+    check_index_permission(
+        api_key_opt.as_ref(),
+        ApiKeyPermission::Admin,
+        &index_config.index_id,
+    )?;
     info!(index_id = %index_config.index_id, overwrite = create_index_query_params.overwrite, "create-index");
-    index_service
+    let index_metadata = index_service
         .create_index(index_config, create_index_query_params.overwrite)
-        .await
+        .await?;
+    Ok(index_metadata)
+}
+
+fn update_index_handler(
+    index_service: IndexService,
+    node_config: Arc<NodeConfig>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String)
+        .and(warp::put())
+        .and(config_format_filter())
+        .and(warp::body::content_length_limit(1024 * 1024))
+        .and(warp::filters::body::bytes())
+        .and(with_arg(index_service))
+        .and(with_arg(node_config))
+        .then(update_index)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    put,
+    tag = "Indexes",
+    path = "/indexes/{index_id}",
+    request_body = VersionedIndexConfig,
+    responses(
+        // We return `VersionedIndexMetadata` as it's the serialized model view.
+        (status = 200, description = "Successfully updated index.", body = VersionedIndexMetadata)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to update."),
+    )
+)]
+/// Updates the search settings, the retention policy, and the indexing settings of an index.
+///
+/// The request body is the full index config. The index URI and the doc mapping cannot be
+/// updated.
+async fn update_index(
+    index_id: String,
+    config_format: ConfigFormat,
+    index_config_bytes: Bytes,
+    mut index_service: IndexService,
+    node_config: Arc<NodeConfig>,
+) -> Result<IndexMetadata, IndexServiceError> {
+    let index_config = quickwit_config::load_index_config_from_user_config(
+        config_format,
+        &index_config_bytes,
+        &node_config.default_index_root_uri,
+    )
+    .map_err(IndexServiceError::InvalidConfig)?;
+    info!(index_id = %index_id, "update-index");
+    index_service.update_index(&index_id, index_config).await
 }
 
 fn clear_index_handler(
@@ -1600,6 +1682,124 @@ mod tests {
         assert_json_include!(actual: resp_json, expected: expected_response_json);
     }
 
+    #[tokio::test]
+    async fn test_update_index() {
+        let metastore = metastore_for_test();
+        let index_service = IndexService::new(metastore.clone(), StorageResolver::unconfigured());
+        let mut node_config = NodeConfig::for_test();
+        node_config.default_index_root_uri = Uri::for_test("file:///default-index-root-uri");
+        let index_management_handler =
+            super::index_management_handlers(index_service, Arc::new(node_config))
+                .recover(recover_fn);
+        let index_config_yaml = r#"
+            version: 0.7
+            index_id: hdfs-logs
+            doc_mapping:
+              field_mappings:
+                - name: timestamp
+                  type: datetime
+                  fast: true
+                - name: body
+                  type: text
+              timestamp_field: timestamp
+            "#;
+        let resp = warp::test::request()
+            .path("/indexes")
+            .method("POST")
+            .header("content-type", "application/yaml")
+            .body(index_config_yaml)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+
+        let updated_index_config_yaml = format!(
+            r#"{index_config_yaml}
+            search_settings:
+              default_search_fields: [body]
+            retention:
+              period: 1 week
+              schedule: daily
+            indexing_settings:
+              commit_timeout_secs: 5
+            "#
+        );
+        let resp = warp::test::request()
+            .path("/indexes/hdfs-logs")
+            .method("PUT")
+            .header("content-type", "application/yaml")
+            .body(&updated_index_config_yaml)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        let expected_response_json = serde_json::json!({
+            "index_config": {
+                "index_id": "hdfs-logs",
+                "search_settings": {
+                    "default_search_fields": ["body"]
+                },
+                "retention": {
+                    "period": "1 week",
+                    "schedule": "daily"
+                },
+                "indexing_settings": {
+                    "commit_timeout_secs": 5
+                }
+            }
+        });
+        assert_json_include!(actual: resp_json, expected: expected_response_json);
+
+        // Field mappings and tag fields can be appended to the doc mapping.
+        let appended_index_config_yaml = updated_index_config_yaml
+            .replace(
+                "                  type: text\n",
+                "                  type: text\n                - name: status_code\n                  \
+                 type: u64\n",
+            )
+            .replace(
+                "timestamp_field: timestamp\n",
+                "timestamp_field: timestamp\n              tag_fields: [status_code]\n",
+            );
+        let resp = warp::test::request()
+            .path("/indexes/hdfs-logs")
+            .method("PUT")
+            .header("content-type", "application/yaml")
+            .body(&appended_index_config_yaml)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        let expected_response_json = serde_json::json!({
+            "index_config": {
+                "doc_mapping": {
+                    "doc_mapping_version": 1,
+                    "tag_fields": ["status_code"]
+                }
+            }
+        });
+        assert_json_include!(actual: resp_json, expected: expected_response_json);
+
+        // Existing field mappings cannot be modified.
+        let resp = warp::test::request()
+            .path("/indexes/hdfs-logs")
+            .method("PUT")
+            .header("content-type", "application/yaml")
+            .body(updated_index_config_yaml.replace("type: text", "type: u64"))
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 405);
+
+        // The index ID in the config must match the index ID of the path.
+        let resp = warp::test::request()
+            .path("/indexes/other-logs")
+            .method("PUT")
+            .header("content-type", "application/yaml")
+            .body(&updated_index_config_yaml)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+    }
+
     #[tokio::test]
     async fn test_create_index_with_wrong_content_type() {
         let metastore = metastore_for_test();
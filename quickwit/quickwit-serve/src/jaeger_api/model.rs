@@ -23,7 +23,9 @@ use base64::prelude::{Engine, BASE64_STANDARD};
 use hyper::StatusCode;
 use itertools::Itertools;
 use prost_types::{Duration, Timestamp};
-use quickwit_proto::jaeger::api_v2::{KeyValue, Log, Process, Span, SpanRef, ValueType};
+use quickwit_proto::jaeger::api_v2::{
+    DependencyLink, KeyValue, Log, Process, Span, SpanRef, ValueType,
+};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use serde_with::serde_as;
@@ -64,6 +66,17 @@ pub struct TracesSearchQueryParams {
     pub limit: Option<i32>,
 }
 
+#[serde_with::skip_serializing_none]
+#[derive(Clone, Default, Debug, Serialize, Deserialize, utoipa::IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+pub struct DependenciesQueryParams {
+    /// End of the time window in milliseconds since the Unix epoch. Defaults to now.
+    pub end_ts: Option<i64>,
+    /// Duration of the time window in milliseconds.
+    pub lookback: Option<i64>,
+}
+
 // Jaeger Model for UI
 // Source: https://github.com/jaegertracing/jaeger/blob/main/model/json/model.go#L82
 
@@ -90,34 +103,32 @@ impl JaegerTrace {
     }
 
     /// Processes a collection of spans, updating the `process_id` field based on the unique
-    /// `service_name` values. The function uses an accumulator (`acc`) to keep track of
-    /// processed `JaegerProcess` objects and assigns a new key to each unique `service_name` value.
+    /// processes, identified by their `service_name` and `tags` values. The function assigns a new
+    /// key to each unique process and returns the map of processes keyed by these keys.
     /// The logic has been replicated from
     /// https://github.com/jaegertracing/jaeger/blob/995231c42cadd70bce2bbbf02579e33f6e6329c8/model/converter/json/process_hashtable.go#L37
-    /// TODO: use also tags to identify processes.
     fn build_process_map(spans: &mut [JaegerSpan]) -> HashMap<String, JaegerProcess> {
-        let mut service_name_to_process_id: HashMap<String, String> = HashMap::new();
-        let mut process_map: HashMap<String, JaegerProcess> = HashMap::new();
-        let mut process_counter: i32 = 0;
+        let mut processes: Vec<JaegerProcess> = Vec::new();
+
         for span in spans.iter_mut() {
             let Some(current_process) = span.process.as_mut() else {
                 continue;
             };
-            if let Some(process_id) = service_name_to_process_id.get(&current_process.service_name)
-            {
-                span.process_id = Some(process_id.clone());
+            if let Some(process) = processes.iter().find(|process| {
+                process.service_name == current_process.service_name
+                    && process.tags == current_process.tags
+            }) {
+                current_process.key = process.key.clone();
             } else {
-                process_counter += 1;
-                current_process.key = format!("p{}", process_counter);
-                span.process_id = Some(current_process.key.clone());
-                process_map.insert(current_process.key.clone(), current_process.clone());
-                service_name_to_process_id.insert(
-                    current_process.service_name.clone(),
-                    current_process.key.clone(),
-                );
+                current_process.key = format!("p{}", processes.len() + 1);
+                processes.push(current_process.clone());
             }
+            span.process_id = Some(current_process.key.clone());
         }
-        process_map
+        processes
+            .into_iter()
+            .map(|process| (process.key.clone(), process))
+            .collect()
     }
 }
 
@@ -311,6 +322,28 @@ impl From<Process> for JaegerProcess {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JaegerDependencyLink {
+    parent: String,
+    child: String,
+    call_count: u64,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "String::is_empty")]
+    source: String,
+}
+
+impl From<DependencyLink> for JaegerDependencyLink {
+    fn from(dependency_link: DependencyLink) -> Self {
+        Self {
+            parent: dependency_link.parent,
+            child: dependency_link.child,
+            call_count: dependency_link.call_count,
+            source: dependency_link.source,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JaegerError {
     #[serde(with = "http_serde::status_code")]
@@ -344,7 +377,7 @@ fn convert_duration_to_microsecs(duration: Duration) -> i64 {
 mod tests {
     use quickwit_proto::jaeger::api_v2::Log;
 
-    use crate::jaeger_api::model::{build_jaeger_traces, JaegerSpan};
+    use crate::jaeger_api::model::{build_jaeger_traces, JaegerSpan, JaegerTrace};
 
     #[test]
     fn test_convert_grpc_jaeger_spans_into_jaeger_ui_model() {
@@ -360,6 +393,38 @@ mod tests {
         assert_json_diff::assert_json_eq!(expected_jaeger_trace, trace_json);
     }
 
+    #[test]
+    fn test_build_process_map() {
+        let mut jaeger_spans: Vec<JaegerSpan> = ["host-1", "host-2", "host-1"]
+            .into_iter()
+            .map(|host_name| {
+                let span = quickwit_proto::jaeger::api_v2::Span {
+                    process: Some(quickwit_proto::jaeger::api_v2::Process {
+                        service_name: "service-x".to_string(),
+                        tags: vec![quickwit_proto::jaeger::api_v2::KeyValue {
+                            key: "host.name".to_string(),
+                            v_type: 0,
+                            v_str: host_name.to_string(),
+                            ..Default::default()
+                        }],
+                    }),
+                    ..Default::default()
+                };
+                JaegerSpan::try_from(span).unwrap()
+            })
+            .collect();
+        let process_map = JaegerTrace::build_process_map(&mut jaeger_spans);
+        assert_eq!(process_map.len(), 2);
+        assert_eq!(process_map["p1"].tags[0].value, "host-1");
+        assert_eq!(process_map["p2"].tags[0].value, "host-2");
+
+        let process_ids: Vec<&str> = jaeger_spans
+            .iter()
+            .map(|span| span.process_id.as_deref().unwrap())
+            .collect();
+        assert_eq!(process_ids, ["p1", "p2", "p1"]);
+    }
+
     fn get_jaeger_ui_trace_filepath() -> String {
         format!(
             "{}/resources/tests/jaeger_ui_trace.json",
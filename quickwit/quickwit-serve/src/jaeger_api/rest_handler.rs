@@ -22,12 +22,14 @@ use std::time::Instant;
 
 use hyper::StatusCode;
 use itertools::Itertools;
+use prost_types::Timestamp as WellKnownTimestamp;
 use quickwit_jaeger::JaegerService;
 use quickwit_proto::jaeger::storage::v1::{
-    FindTracesRequest, GetOperationsRequest, GetServicesRequest, GetTraceRequest,
-    SpansResponseChunk, TraceQueryParameters,
+    FindTracesRequest, GetDependenciesRequest, GetOperationsRequest, GetServicesRequest,
+    GetTraceRequest, SpansResponseChunk, TraceQueryParameters,
 };
 use quickwit_proto::tonic;
+use time::OffsetDateTime;
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_stream::StreamExt;
 use tracing::error;
@@ -36,8 +38,8 @@ use warp::{Filter, Rejection};
 use super::model::build_jaeger_traces;
 use super::parse_duration::{parse_duration_with_units, to_well_known_timestamp};
 use crate::jaeger_api::model::{
-    JaegerError, JaegerResponseBody, JaegerSpan, JaegerTrace, TracesSearchQueryParams,
-    DEFAULT_NUMBER_OF_TRACES,
+    DependenciesQueryParams, JaegerDependencyLink, JaegerError, JaegerResponseBody, JaegerSpan,
+    JaegerTrace, TracesSearchQueryParams, DEFAULT_NUMBER_OF_TRACES,
 };
 use crate::json_api_response::JsonApiResponse;
 use crate::search_api::extract_index_id_patterns;
@@ -48,7 +50,8 @@ use crate::{require, BodyFormat};
     jaeger_services_handler,
     jaeger_service_operations_handler,
     jaeger_traces_search_handler,
-    jaeger_traces_handler
+    jaeger_traces_handler,
+    jaeger_dependencies_handler
 ))]
 pub(crate) struct JaegerApi;
 
@@ -66,6 +69,7 @@ pub(crate) fn jaeger_api_handlers(
         ))
         .or(jaeger_traces_search_handler(jaeger_service_opt.clone()))
         .or(jaeger_traces_handler(jaeger_service_opt.clone()))
+        .or(jaeger_dependencies_handler(jaeger_service_opt.clone()))
 }
 
 fn jaeger_api_path_filter() -> impl Filter<Extract = (Vec<String>,), Error = Rejection> + Clone {
@@ -170,6 +174,30 @@ pub fn jaeger_traces_handler(
         .map(|result| make_jaeger_api_response(result, BodyFormat::default()))
 }
 
+#[utoipa::path(
+    get,
+    tag = "Jaeger",
+    path = "/{otel-traces-index-id}/jaeger/api/dependencies",
+    responses(
+        (status = 200, description = "Successfully fetched the dependency links between services.", body = JaegerResponseBody )
+    ),
+    params(
+        ("otel-traces-index-id" = String, Path, description = "The name of the index to get dependencies for."),
+        ("endTs" = Option<i64>, Query, description = "The end of the time window in milliseconds since the Unix epoch. Defaults to now."),
+        ("lookback" = Option<i64>, Query, description = "The duration of the time window in milliseconds."),
+    )
+)]
+pub fn jaeger_dependencies_handler(
+    jaeger_service_opt: Option<JaegerService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    jaeger_api_path_filter()
+        .and(warp::path!("dependencies"))
+        .and(serde_qs::warp::query(serde_qs::Config::default()))
+        .and(require(jaeger_service_opt))
+        .then(jaeger_dependencies)
+        .map(|result| make_jaeger_api_response(result, BodyFormat::default()))
+}
+
 async fn jaeger_services(
     index_id_patterns: Vec<String>,
     jaeger_service: JaegerService,
@@ -275,6 +303,53 @@ async fn jaeger_traces_search(
     })
 }
 
+async fn jaeger_dependencies(
+    index_id_patterns: Vec<String>,
+    dependencies_params: DependenciesQueryParams,
+    jaeger_service: JaegerService,
+) -> Result<JaegerResponseBody<Vec<JaegerDependencyLink>>, JaegerError> {
+    let end_time_opt = dependencies_params
+        .end_ts
+        .map(millis_to_well_known_timestamp);
+    let start_time_opt = dependencies_params.lookback.map(|lookback_millis| {
+        let end_millis = dependencies_params
+            .end_ts
+            .unwrap_or_else(|| OffsetDateTime::now_utc().unix_timestamp() * 1_000);
+        millis_to_well_known_timestamp(end_millis.saturating_sub(lookback_millis))
+    });
+    let get_dependencies_request = GetDependenciesRequest {
+        start_time: start_time_opt,
+        end_time: end_time_opt,
+    };
+    let get_dependencies_response = jaeger_service
+        .get_dependencies_for_indexes(get_dependencies_request, index_id_patterns)
+        .await
+        .map_err(|error| {
+            let status = if error.code() == tonic::Code::InvalidArgument {
+                StatusCode::BAD_REQUEST
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+            JaegerError {
+                status,
+                message: format!("failed to fetch dependencies: {}", error.message()),
+            }
+        })?;
+    let dependencies = get_dependencies_response
+        .dependencies
+        .into_iter()
+        .map(JaegerDependencyLink::from)
+        .collect();
+    Ok(JaegerResponseBody { data: dependencies })
+}
+
+fn millis_to_well_known_timestamp(timestamp_millis: i64) -> WellKnownTimestamp {
+    WellKnownTimestamp {
+        seconds: timestamp_millis.div_euclid(1_000),
+        nanos: (timestamp_millis.rem_euclid(1_000) * 1_000_000) as i32,
+    }
+}
+
 async fn collect_and_build_jaeger_spans(
     mut spans_chunk_stream: ReceiverStream<Result<SpansResponseChunk, tonic::Status>>,
 ) -> anyhow::Result<Vec<JaegerSpan>> {
@@ -460,6 +535,9 @@ mod tests {
                     errors: vec![],
                     aggregation: None,
                     scroll_id: None,
+                    timed_out: false,
+                    skipped_split_ids: Vec::new(),
+                    profile: None,
                 })
             });
         let mock_search_service = Arc::new(mock_search_service);
@@ -491,6 +569,9 @@ mod tests {
                     errors: vec![],
                     aggregation: None,
                     scroll_id: None,
+                    timed_out: false,
+                    skipped_split_ids: Vec::new(),
+                    profile: None,
                 })
             });
         let mock_search_service = Arc::new(mock_search_service);
@@ -511,4 +592,60 @@ mod tests {
             .unwrap()
             .is_empty());
     }
+
+    #[tokio::test]
+    async fn test_jaeger_dependencies() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .withf(|req| {
+                req.index_id_patterns == vec![OTEL_TRACES_INDEX_ID.to_string()]
+                    && req.start_timestamp == Some(1_702_352_106)
+                    && req.end_timestamp == Some(1_702_373_706)
+            })
+            .return_once(|_| {
+                let hits = [
+                    r#"{"trace_id":"01010101010101010101010101010101","span_id":"0101010101010101","service_name":"frontend","span_name":"GET /"}"#,
+                    r#"{"trace_id":"01010101010101010101010101010101","span_id":"0202020202020202","parent_span_id":"0101010101010101","service_name":"backend","span_name":"query"}"#,
+                ]
+                .into_iter()
+                .map(|json| quickwit_proto::search::Hit {
+                    json: json.to_string(),
+                    ..Default::default()
+                })
+                .collect();
+                Ok(quickwit_proto::search::SearchResponse {
+                    num_hits: 2,
+                    hits,
+                    ..Default::default()
+                })
+            });
+        let mock_search_service = Arc::new(mock_search_service);
+        let jaeger = JaegerService::new(JaegerConfig::default(), mock_search_service);
+        let jaeger_api_handler = jaeger_api_handlers(Some(jaeger)).recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/otel-traces-v0_7/jaeger/api/dependencies?endTs=1702373706016&lookback=21600000")
+            .reply(&jaeger_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let actual_response_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(
+            actual_response_json,
+            serde_json::json!({
+                "data": [{"parent": "frontend", "child": "backend", "callCount": 1}]
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_jaeger_dependencies_with_invalid_time_window() {
+        let mock_search_service = Arc::new(MockSearchService::new());
+        let jaeger = JaegerService::new(JaegerConfig::default(), mock_search_service);
+        let jaeger_api_handler = jaeger_api_handlers(Some(jaeger)).recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/otel-traces-v0_7/jaeger/api/dependencies?endTs=1702373706016&lookback=-1000000")
+            .reply(&jaeger_api_handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+    }
 }
@@ -17,6 +17,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+mod auth;
 mod build_info;
 mod cluster_api;
 mod debugging_api;
@@ -64,7 +65,7 @@ use quickwit_common::rate_limiter::RateLimiterSettings;
 use quickwit_common::runtimes::RuntimesConfig;
 use quickwit_common::tower::{
     BalanceChannel, BoxFutureInfaillible, BufferLayer, Change, ConstantRate, EstimateRateLayer,
-    EventListenerLayer, RateLimitLayer, RetryLayer, RetryPolicy, SmaRateEstimator,
+    EventListenerLayer, GrpcTimeout, RateLimitLayer, RetryLayer, RetryPolicy, SmaRateEstimator,
 };
 use quickwit_config::service::QuickwitService;
 use quickwit_config::NodeConfig;
@@ -84,7 +85,9 @@ use quickwit_janitor::{start_janitor_service, JanitorService};
 use quickwit_metastore::{
     ControlPlaneMetastore, ListIndexesMetadataResponseExt, MetastoreResolver,
 };
-use quickwit_opentelemetry::otlp::{OtlpGrpcLogsService, OtlpGrpcTracesService};
+use quickwit_opentelemetry::otlp::{
+    OtlpGrpcLogsService, OtlpGrpcMetricsService, OtlpGrpcTracesService,
+};
 use quickwit_proto::control_plane::ControlPlaneServiceClient;
 use quickwit_proto::indexing::{IndexingServiceClient, ShardPositionsUpdate};
 use quickwit_proto::ingest::ingester::IngesterServiceClient;
@@ -101,7 +104,6 @@ use quickwit_search::{
 };
 use quickwit_storage::{SplitCache, StorageResolver};
 use tokio::sync::oneshot;
-use tower::timeout::Timeout;
 use tower::ServiceBuilder;
 use tracing::{debug, error, info, warn};
 use warp::{Filter, Rejection};
@@ -136,6 +138,7 @@ struct QuickwitServices {
     pub janitor_service_opt: Option<Mailbox<JanitorService>>,
     pub jaeger_service_opt: Option<JaegerService>,
     pub otlp_logs_service_opt: Option<OtlpGrpcLogsService>,
+    pub otlp_metrics_service_opt: Option<OtlpGrpcMetricsService>,
     pub otlp_traces_service_opt: Option<OtlpGrpcTracesService>,
     /// We do have a search service even on nodes that are not running `search`.
     /// It is only used to serve the rest API calls and will only execute
@@ -418,10 +421,16 @@ pub async fn serve_quickwit(
         {
             let otel_logs_index_config =
                 OtlpGrpcLogsService::index_config(&node_config.default_index_root_uri)?;
+            let otel_metrics_index_config =
+                OtlpGrpcMetricsService::index_config(&node_config.default_index_root_uri)?;
             let otel_traces_index_config =
                 OtlpGrpcTracesService::index_config(&node_config.default_index_root_uri)?;
 
-            for index_config in [otel_logs_index_config, otel_traces_index_config] {
+            for index_config in [
+                otel_logs_index_config,
+                otel_metrics_index_config,
+                otel_traces_index_config,
+            ] {
                 match index_manager.create_index(index_config, false).await {
                     Ok(_)
                     | Err(IndexServiceError::Metastore(MetastoreError::AlreadyExists(
@@ -520,6 +529,14 @@ pub async fn serve_quickwit(
         None
     };
 
+    let otlp_metrics_service_opt = if node_config.is_service_enabled(QuickwitService::Indexer)
+        && node_config.indexer_config.enable_otlp_endpoint
+    {
+        Some(OtlpGrpcMetricsService::new(ingest_service.clone()))
+    } else {
+        None
+    };
+
     let otlp_traces_service_opt = if node_config.is_service_enabled(QuickwitService::Indexer)
         && node_config.indexer_config.enable_otlp_endpoint
     {
@@ -546,6 +563,7 @@ pub async fn serve_quickwit(
         janitor_service_opt,
         jaeger_service_opt,
         otlp_logs_service_opt,
+        otlp_metrics_service_opt,
         otlp_traces_service_opt,
         search_service,
     });
@@ -748,7 +766,8 @@ async fn setup_searcher(
                             SearchServiceClient::from_service(search_service_clone, grpc_addr);
                         Some(Change::Insert(grpc_addr, search_client))
                     } else {
-                        let timeout_channel = Timeout::new(node.channel(), Duration::from_secs(30));
+                        let timeout_channel =
+                            GrpcTimeout::new(node.channel(), Duration::from_secs(30));
                         let search_client = create_search_client_from_channel(
                             grpc_addr,
                             timeout_channel,
@@ -19,12 +19,17 @@
 
 use bytes::Bytes;
 use quickwit_opentelemetry::otlp::{
-    OtlpGrpcLogsService, OtlpGrpcTracesService, OTEL_LOGS_INDEX_ID, OTEL_TRACES_INDEX_ID,
+    OtlpGrpcLogsService, OtlpGrpcMetricsService, OtlpGrpcTracesService, OTEL_LOGS_INDEX_ID,
+    OTEL_METRICS_INDEX_ID, OTEL_TRACES_INDEX_ID,
 };
 use quickwit_proto::opentelemetry::proto::collector::logs::v1::logs_service_server::LogsService;
 use quickwit_proto::opentelemetry::proto::collector::logs::v1::{
     ExportLogsServiceRequest, ExportLogsServiceResponse,
 };
+use quickwit_proto::opentelemetry::proto::collector::metrics::v1::metrics_service_server::MetricsService;
+use quickwit_proto::opentelemetry::proto::collector::metrics::v1::{
+    ExportMetricsServiceRequest, ExportMetricsServiceResponse,
+};
 use quickwit_proto::opentelemetry::proto::collector::trace::v1::trace_service_server::TraceService;
 use quickwit_proto::opentelemetry::proto::collector::trace::v1::{
     ExportTraceServiceRequest, ExportTraceServiceResponse,
@@ -44,11 +49,14 @@ pub(crate) struct OtlpApi;
 /// Setup OpenTelemetry API handlers.
 pub(crate) fn otlp_ingest_api_handlers(
     otlp_logs_service: Option<OtlpGrpcLogsService>,
+    otlp_metrics_service: Option<OtlpGrpcMetricsService>,
     otlp_traces_service: Option<OtlpGrpcTracesService>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     otlp_default_logs_handler(otlp_logs_service.clone())
+        .or(otlp_default_metrics_handler(otlp_metrics_service.clone()))
         .or(otlp_default_traces_handler(otlp_traces_service.clone()))
         .or(otlp_logs_handler(otlp_logs_service))
+        .or(otlp_metrics_handler(otlp_metrics_service))
         .or(otlp_ingest_traces_handler(otlp_traces_service))
 }
 
@@ -86,6 +94,45 @@ pub(crate) fn otlp_logs_handler(
         .map(make_json_api_response)
 }
 
+pub(crate) fn otlp_default_metrics_handler(
+    otlp_metrics_service: Option<OtlpGrpcMetricsService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    require(otlp_metrics_service)
+        .and(warp::path!("otlp" / "v1" / "metrics"))
+        .and(warp::header::exact_ignore_case(
+            "content-type",
+            "application/x-protobuf",
+        ))
+        .and(warp::post())
+        .and(warp::body::bytes())
+        .then(|otlp_metrics_service, body| async move {
+            otlp_ingest_metrics(
+                otlp_metrics_service,
+                OTEL_METRICS_INDEX_ID.to_string(),
+                body,
+            )
+            .await
+        })
+        .and(with_arg(BodyFormat::default()))
+        .map(make_json_api_response)
+}
+
+pub(crate) fn otlp_metrics_handler(
+    otlp_metrics_service: Option<OtlpGrpcMetricsService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    require(otlp_metrics_service)
+        .and(warp::path!(String / "otlp" / "v1" / "metrics"))
+        .and(warp::header::exact_ignore_case(
+            "content-type",
+            "application/x-protobuf",
+        ))
+        .and(warp::post())
+        .and(warp::body::bytes())
+        .then(otlp_ingest_metrics)
+        .and(with_arg(BodyFormat::default()))
+        .map(make_json_api_response)
+}
+
 pub(crate) fn otlp_default_traces_handler(
     otlp_traces_service: Option<OtlpGrpcTracesService>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
@@ -152,6 +199,20 @@ async fn otlp_ingest_logs(
     Ok(result.into_inner())
 }
 
+async fn otlp_ingest_metrics(
+    otlp_metrics_service: OtlpGrpcMetricsService,
+    _index_id: String, // <- TODO: use index ID when gRPC service supports it.
+    body: Bytes,
+) -> Result<ExportMetricsServiceResponse, OtlpApiError> {
+    let export_metrics_request: ExportMetricsServiceRequest = prost::Message::decode(&body[..])
+        .map_err(|err| OtlpApiError::InvalidPayload(err.to_string()))?;
+    let response = otlp_metrics_service
+        .export(tonic::Request::new(export_metrics_request))
+        .await
+        .map_err(|err| OtlpApiError::Ingest(err.to_string()))?;
+    Ok(response.into_inner())
+}
+
 async fn otlp_ingest_traces(
     otlp_traces_service: OtlpGrpcTracesService,
     _index_id: String, // <- TODO: use index ID when gRPC service supports it.
@@ -171,15 +232,24 @@ mod tests {
     use prost::Message;
     use quickwit_ingest::{CommitType, IngestResponse, IngestServiceClient};
     use quickwit_opentelemetry::otlp::{
-        make_resource_spans_for_test, OtlpGrpcLogsService, OtlpGrpcTracesService,
+        make_resource_spans_for_test, OtlpGrpcLogsService, OtlpGrpcMetricsService,
+        OtlpGrpcTracesService,
     };
     use quickwit_proto::opentelemetry::proto::collector::logs::v1::{
         ExportLogsServiceRequest, ExportLogsServiceResponse,
     };
+    use quickwit_proto::opentelemetry::proto::collector::metrics::v1::{
+        ExportMetricsServiceRequest, ExportMetricsServiceResponse,
+    };
     use quickwit_proto::opentelemetry::proto::collector::trace::v1::{
         ExportTraceServiceRequest, ExportTraceServiceResponse,
     };
     use quickwit_proto::opentelemetry::proto::logs::v1::{LogRecord, ResourceLogs, ScopeLogs};
+    use quickwit_proto::opentelemetry::proto::metrics::v1::metric::Data as MetricData;
+    use quickwit_proto::opentelemetry::proto::metrics::v1::number_data_point::Value as NumberValue;
+    use quickwit_proto::opentelemetry::proto::metrics::v1::{
+        Gauge, Metric, NumberDataPoint, ResourceMetrics, ScopeMetrics,
+    };
     use quickwit_proto::opentelemetry::proto::resource::v1::Resource;
     use warp::Filter;
 
@@ -203,6 +273,7 @@ mod tests {
             });
         let ingest_service_client = IngestServiceClient::from(ingest_service_mock);
         let logs_service = OtlpGrpcLogsService::new(ingest_service_client.clone());
+        let metrics_service = OtlpGrpcMetricsService::new(ingest_service_client.clone());
         let traces_service =
             OtlpGrpcTracesService::new(ingest_service_client, Some(CommitType::Force));
         let export_logs_request = ExportLogsServiceRequest {
@@ -231,8 +302,12 @@ mod tests {
             }],
         };
         let body = export_logs_request.encode_to_vec();
-        let otlp_traces_api_handler =
-            otlp_ingest_api_handlers(Some(logs_service), Some(traces_service)).recover(recover_fn);
+        let otlp_traces_api_handler = otlp_ingest_api_handlers(
+            Some(logs_service),
+            Some(metrics_service),
+            Some(traces_service),
+        )
+        .recover(recover_fn);
         {
             // Test default otlp endpoint
             let resp = warp::test::request()
@@ -277,6 +352,103 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_otlp_ingest_metrics_handler() {
+        let mut ingest_service_mock = IngestServiceClient::mock();
+        ingest_service_mock
+            .expect_ingest()
+            .withf(|request| {
+                request.doc_batches.len() == 1 && request.doc_batches[0].doc_lengths.len() == 2
+            })
+            .returning(|_| {
+                Ok(IngestResponse {
+                    num_docs_for_processing: 2,
+                })
+            });
+        let ingest_service_client = IngestServiceClient::from(ingest_service_mock);
+        let logs_service = OtlpGrpcLogsService::new(ingest_service_client.clone());
+        let metrics_service = OtlpGrpcMetricsService::new(ingest_service_client.clone());
+        let traces_service = OtlpGrpcTracesService::new(ingest_service_client, None);
+        let make_data_point = |value: f64| NumberDataPoint {
+            attributes: vec![],
+            start_time_unix_nano: 0,
+            time_unix_nano: 1704036033047000000,
+            exemplars: vec![],
+            flags: 0,
+            value: Some(NumberValue::AsDouble(value)),
+        };
+        let export_metrics_request = ExportMetricsServiceRequest {
+            resource_metrics: vec![ResourceMetrics {
+                resource: Some(Resource {
+                    attributes: vec![],
+                    dropped_attributes_count: 0,
+                }),
+                scope_metrics: vec![ScopeMetrics {
+                    metrics: vec![Metric {
+                        name: "cpu.usage".to_string(),
+                        description: "".to_string(),
+                        unit: "1".to_string(),
+                        data: Some(MetricData::Gauge(Gauge {
+                            data_points: vec![make_data_point(0.25), make_data_point(0.5)],
+                        })),
+                    }],
+                    scope: None,
+                    schema_url: "".to_string(),
+                }],
+                schema_url: "".to_string(),
+            }],
+        };
+        let body = export_metrics_request.encode_to_vec();
+        let otlp_metrics_api_handler = otlp_ingest_api_handlers(
+            Some(logs_service),
+            Some(metrics_service),
+            Some(traces_service),
+        )
+        .recover(recover_fn);
+        {
+            // Test default otlp endpoint
+            let resp = warp::test::request()
+                .path("/otlp/v1/metrics")
+                .method("POST")
+                .header("content-type", "application/x-protobuf")
+                .body(body.clone())
+                .reply(&otlp_metrics_api_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+            let actual_response: ExportMetricsServiceResponse =
+                serde_json::from_slice(resp.body()).unwrap();
+            assert!(actual_response.partial_success.is_some());
+            assert_eq!(
+                actual_response
+                    .partial_success
+                    .unwrap()
+                    .rejected_data_points,
+                0
+            );
+        }
+        {
+            // Test endpoint with given index ID.
+            let resp = warp::test::request()
+                .path("/otel-metrics-v0/otlp/v1/metrics")
+                .method("POST")
+                .header("content-type", "application/x-protobuf")
+                .body(body)
+                .reply(&otlp_metrics_api_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+            let actual_response: ExportMetricsServiceResponse =
+                serde_json::from_slice(resp.body()).unwrap();
+            assert!(actual_response.partial_success.is_some());
+            assert_eq!(
+                actual_response
+                    .partial_success
+                    .unwrap()
+                    .rejected_data_points,
+                0
+            );
+        }
+    }
+
     #[tokio::test]
     async fn test_otlp_ingest_traces_handler() {
         let mut ingest_service_mock = IngestServiceClient::mock();
@@ -294,14 +466,19 @@ mod tests {
             });
         let ingest_service_client = IngestServiceClient::from(ingest_service_mock);
         let logs_service = OtlpGrpcLogsService::new(ingest_service_client.clone());
+        let metrics_service = OtlpGrpcMetricsService::new(ingest_service_client.clone());
         let traces_service =
             OtlpGrpcTracesService::new(ingest_service_client, Some(CommitType::Force));
         let export_trace_request = ExportTraceServiceRequest {
             resource_spans: make_resource_spans_for_test(),
         };
         let body = export_trace_request.encode_to_vec();
-        let otlp_traces_api_handler =
-            otlp_ingest_api_handlers(Some(logs_service), Some(traces_service)).recover(recover_fn);
+        let otlp_traces_api_handler = otlp_ingest_api_handlers(
+            Some(logs_service),
+            Some(metrics_service),
+            Some(traces_service),
+        )
+        .recover(recover_fn);
         {
             // Test default otlp endpoint
             let resp = warp::test::request()
@@ -22,7 +22,8 @@ use std::sync::Arc;
 
 use hyper::http::HeaderValue;
 use hyper::{http, Method};
-use quickwit_common::tower::BoxFutureInfaillible;
+use quickwit_common::tls::{bind_tls_incoming, ReloadableTlsConfig, ALPN_H2, ALPN_HTTP_1_1};
+use quickwit_common::tower::{BoxFuture, BoxFutureInfaillible};
 use quickwit_proto::ServiceErrorCode;
 use tower::make::Shared;
 use tower::ServiceBuilder;
@@ -32,6 +33,7 @@ use tower_http::cors::CorsLayer;
 use tracing::{error, info};
 use warp::{redirect, Filter, Rejection, Reply};
 
+use crate::auth::ApiKeyAuthLayer;
 use crate::cluster_api::cluster_handler;
 use crate::debugging_api::debugging_handler;
 use crate::delete_task_api::delete_task_api_handlers;
@@ -45,7 +47,9 @@ use crate::json_api_response::{ApiError, JsonApiResponse};
 use crate::metrics_api::metrics_handler;
 use crate::node_info_handler::node_info_handler;
 use crate::otlp_api::otlp_ingest_api_handlers;
-use crate::search_api::{search_get_handler, search_post_handler, search_stream_handler};
+use crate::search_api::{
+    async_search_handlers, search_get_handler, search_post_handler, search_stream_handler,
+};
 use crate::ui_handler::ui_handler;
 use crate::{BodyFormat, BuildInfo, QuickwitServices, RuntimeInfo};
 
@@ -126,6 +130,7 @@ pub(crate) async fn start_rest_server(
     let compression_predicate =
         DefaultPredicate::new().and(SizeAbove::new(MINIMUM_RESPONSE_COMPRESSION_SIZE));
     let cors = build_cors(&quickwit_services.node_config.rest_config.cors_allow_origins);
+    let auth_layer = ApiKeyAuthLayer::try_new(&quickwit_services.node_config.rest_config.api_keys)?;
 
     let service = ServiceBuilder::new()
         .layer(
@@ -134,12 +139,27 @@ pub(crate) async fn start_rest_server(
                 .compress_when(compression_predicate),
         )
         .layer(cors)
+        .layer(auth_layer)
         .service(warp_service);
 
+    let tls_config_opt = quickwit_services.node_config.rest_config.tls.as_ref();
+
     info!(
         rest_listen_addr=?rest_listen_addr,
+        tls_enabled=tls_config_opt.is_some(),
         "Starting REST server listening on {rest_listen_addr}."
     );
+    let server_fut: BoxFuture<(), hyper::Error> = if let Some(tls_config) = tls_config_opt {
+        let validate_client = tls_config.validate_client;
+        let server_config = ReloadableTlsConfig::load(tls_config.tls_files(), move |tls_files| {
+            tls_files.server_config(validate_client, &[ALPN_H2, ALPN_HTTP_1_1])
+        })?;
+        let tls_incoming = bind_tls_incoming(rest_listen_addr, server_config).await?;
+        let tls_acceptor = hyper::server::accept::from_stream(tls_incoming);
+        Box::pin(hyper::Server::builder(tls_acceptor).serve(Shared::new(service)))
+    } else {
+        Box::pin(hyper::Server::bind(&rest_listen_addr).serve(Shared::new(service)))
+    };
 
     // `graceful_shutdown()` seems to be blocking in presence of existing connections.
     // The following approach of dropping the serve supposedly is not bullet proof, but it seems to
@@ -149,7 +169,7 @@ pub(crate) async fn start_rest_server(
     // https://github.com/hyperium/hyper/issues/2386
     let serve_fut = async move {
         tokio::select! {
-             res = hyper::Server::bind(&rest_listen_addr).serve(Shared::new(service)) => { res }
+             res = server_fut => { res }
              _ = shutdown_signal => { Ok(()) }
         }
     };
@@ -180,6 +200,9 @@ fn api_v1_routes(
             .or(search_stream_handler(
                 quickwit_services.search_service.clone(),
             ))
+            .or(async_search_handlers(
+                quickwit_services.search_service.clone(),
+            ))
             .or(ingest_api_handlers(
                 quickwit_services.ingest_router_service.clone(),
                 quickwit_services.ingest_service.clone(),
@@ -187,6 +210,7 @@ fn api_v1_routes(
             ))
             .or(otlp_ingest_api_handlers(
                 quickwit_services.otlp_logs_service_opt.clone(),
+                quickwit_services.otlp_metrics_service_opt.clone(),
                 quickwit_services.otlp_traces_service_opt.clone(),
             ))
             .or(index_management_handlers(
@@ -204,6 +228,7 @@ fn api_v1_routes(
                 quickwit_services.search_service.clone(),
                 quickwit_services.ingest_service.clone(),
                 quickwit_services.ingest_router_service.clone(),
+                quickwit_services.metastore_client.clone(),
             )),
     )
 }
@@ -607,6 +632,7 @@ mod tests {
             ),
             janitor_service_opt: None,
             otlp_logs_service_opt: None,
+            otlp_metrics_service_opt: None,
             otlp_traces_service_opt: None,
             metastore_client,
             metastore_server_opt: None,
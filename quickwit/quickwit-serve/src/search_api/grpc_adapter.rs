@@ -24,8 +24,8 @@ use futures::TryStreamExt;
 use quickwit_proto::error::convert_to_grpc_result;
 use quickwit_proto::search::{
     search_service_server as grpc, GetKvRequest, GetKvResponse, LeafListFieldsRequest,
-    LeafSearchStreamRequest, LeafSearchStreamResponse, ListFieldsRequest, ListFieldsResponse,
-    ReportSplitsRequest, ReportSplitsResponse,
+    LeafSearchStreamRequest, LeafSearchStreamResponse, LegacySortValues, ListFieldsRequest,
+    ListFieldsResponse, ReportSplitsRequest, ReportSplitsResponse,
 };
 use quickwit_proto::{set_parent_span_from_request_metadata, tonic, ServiceError};
 use quickwit_search::SearchService;
@@ -48,7 +48,8 @@ impl grpc::SearchService for GrpcSearchAdapter {
         request: tonic::Request<quickwit_proto::search::SearchRequest>,
     ) -> Result<tonic::Response<quickwit_proto::search::SearchResponse>, tonic::Status> {
         set_parent_span_from_request_metadata(request.metadata());
-        let search_request = request.into_inner();
+        let mut search_request = request.into_inner();
+        search_request.upgrade_legacy_sort_values();
         let search_result = self.0.root_search(search_request).await;
         convert_to_grpc_result(search_result)
     }
@@ -59,8 +60,16 @@ impl grpc::SearchService for GrpcSearchAdapter {
         request: tonic::Request<quickwit_proto::search::LeafSearchRequest>,
     ) -> Result<tonic::Response<quickwit_proto::search::LeafSearchResponse>, tonic::Status> {
         set_parent_span_from_request_metadata(request.metadata());
-        let leaf_search_request = request.into_inner();
-        let leaf_search_result = self.0.leaf_search(leaf_search_request).await;
+        let mut leaf_search_request = request.into_inner();
+        leaf_search_request.upgrade_legacy_sort_values();
+        let leaf_search_result =
+            self.0
+                .leaf_search(leaf_search_request)
+                .await
+                .map(|mut leaf_search_response| {
+                    leaf_search_response.set_legacy_sort_values();
+                    leaf_search_response
+                });
         convert_to_grpc_result(leaf_search_result)
     }
 
@@ -70,8 +79,16 @@ impl grpc::SearchService for GrpcSearchAdapter {
         request: tonic::Request<quickwit_proto::search::FetchDocsRequest>,
     ) -> Result<tonic::Response<quickwit_proto::search::FetchDocsResponse>, tonic::Status> {
         set_parent_span_from_request_metadata(request.metadata());
-        let fetch_docs_request = request.into_inner();
-        let fetch_docs_result = self.0.fetch_docs(fetch_docs_request).await;
+        let mut fetch_docs_request = request.into_inner();
+        fetch_docs_request.upgrade_legacy_sort_values();
+        let fetch_docs_result =
+            self.0
+                .fetch_docs(fetch_docs_request)
+                .await
+                .map(|mut fetch_docs_response| {
+                    fetch_docs_response.set_legacy_sort_values();
+                    fetch_docs_response
+                });
         convert_to_grpc_result(fetch_docs_result)
     }
 
@@ -24,26 +24,41 @@ use futures::stream::StreamExt;
 use hyper::header::HeaderValue;
 use hyper::HeaderMap;
 use percent_encoding::percent_decode_str;
-use quickwit_config::validate_index_id_pattern;
-use quickwit_proto::search::{CountHits, OutputFormat, SortField, SortOrder};
-use quickwit_proto::ServiceError;
+use quickwit_common::is_false;
+use quickwit_config::{validate_index_id_pattern, ApiKeyPermission};
+use quickwit_proto::search::{CollapseOptions, CountHits, OutputFormat, SortField, SortOrder};
+use quickwit_proto::{ServiceError, ServiceErrorCode};
 use quickwit_query::query_ast::query_ast_from_user_text;
-use quickwit_search::{SearchError, SearchResponseRest, SearchService};
+use quickwit_search::{
+    AsyncSearchResponse, AsyncSearchResponseRest, SearchError, SearchResponseRest, SearchService,
+    DEFAULT_ASYNC_SEARCH_KEEP_ALIVE,
+};
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value as JsonValue;
+use thiserror::Error;
 use tracing::info;
 use warp::hyper::header::CONTENT_TYPE;
 use warp::hyper::StatusCode;
 use warp::{reply, Filter, Rejection, Reply};
 
+use crate::auth::{api_key_filter, check_index_permission, AuthError, AuthenticatedApiKey};
+use crate::format::extract_format_from_qs;
 use crate::json_api_response::make_json_api_response;
 use crate::simple_list::{from_simple_list, to_simple_list};
 use crate::{with_arg, BodyFormat};
 
 #[derive(utoipa::OpenApi)]
 #[openapi(
-    paths(search_get_handler, search_post_handler, search_stream_handler,),
+    paths(
+        search_get_handler,
+        search_post_handler,
+        search_stream_handler,
+        async_search_submit_handler,
+        async_search_get_handler,
+        async_search_delete_handler,
+    ),
     components(schemas(
+        AsyncSearchResponseRest,
         BodyFormat,
         OutputFormat,
         SearchRequestQueryString,
@@ -231,6 +246,25 @@ pub struct SearchRequestQueryString {
     #[serde(with = "count_hits_from_bool")]
     #[serde(default = "count_hits_from_bool::default")]
     pub count_all: CountHits,
+    /// If set, the search stops after this duration (e.g. `500ms`, `2s`) and returns the
+    /// partial results collected so far with `timed_out` set to true.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<String>,
+    /// If set to true, the response contains a profile of the search: the rewritten query and
+    /// a per-split breakdown of the time spent and the bytes fetched.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub profile: bool,
+    /// If set, hits are grouped by the value of this fast text field and only the best hit of
+    /// each group is returned. `max_hits` and `start_offset` then apply to the groups.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collapse_field: Option<String>,
+    /// Number of best hits of each group returned as inner hits when collapsing hits.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collapse_inner_hits: Option<u32>,
 }
 
 mod count_hits_from_bool {
@@ -270,6 +304,26 @@ pub fn search_request_from_api_request(
     // the user of the docmapper default fields (which we do not have at this point).
     let query_ast = query_ast_from_user_text(&search_request.query, search_request.search_fields);
     let query_ast_json = serde_json::to_string(&query_ast)?;
+    let timeout_millis = search_request
+        .timeout
+        .as_deref()
+        .map(parse_search_timeout)
+        .transpose()?;
+    let collapse = match (
+        search_request.collapse_field,
+        search_request.collapse_inner_hits,
+    ) {
+        (Some(field_name), max_inner_hits_opt) => Some(CollapseOptions {
+            field_name,
+            max_inner_hits: max_inner_hits_opt.unwrap_or(0),
+        }),
+        (None, Some(_)) => {
+            return Err(SearchError::InvalidArgument(
+                "`collapse_inner_hits` requires `collapse_field` to be set".to_string(),
+            ));
+        }
+        (None, None) => None,
+    };
     let search_request = quickwit_proto::search::SearchRequest {
         index_id_patterns,
         query_ast: query_ast_json,
@@ -285,10 +339,23 @@ pub fn search_request_from_api_request(
         scroll_ttl_secs: None,
         search_after: None,
         count_hits: search_request.count_all.into(),
+        timeout_millis,
+        profile: search_request.profile,
+        snippet_options: None,
+        collapse,
     };
     Ok(search_request)
 }
 
+/// Parses a search timeout expressed as a human readable duration (`500ms`, `2s`, etc.) into
+/// milliseconds.
+pub(crate) fn parse_search_timeout(timeout_str: &str) -> Result<u64, SearchError> {
+    let timeout = humantime::parse_duration(timeout_str).map_err(|_err| {
+        SearchError::InvalidArgument(format!("invalid search timeout: `{timeout_str}`"))
+    })?;
+    Ok(timeout.as_millis() as u64)
+}
+
 async fn search_endpoint(
     index_id_patterns: Vec<String>,
     search_request: SearchRequestQueryString,
@@ -519,11 +586,228 @@ fn search_stream_filter(
         .and(serde_qs::warp::query(serde_qs::Config::default()))
 }
 
+/// This struct represents the query string of the async search submission endpoint.
+#[derive(Debug, Default, Eq, PartialEq, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AsyncSearchQueryString {
+    /// Duration for which the search and its results are kept (e.g. `10m`), 5 minutes by
+    /// default. The search is cancelled if it is still running when this duration expires.
+    #[serde(default)]
+    pub keep_alive: Option<String>,
+}
+
+async fn async_search_submit_endpoint(
+    index_id_patterns: Vec<String>,
+    async_search_query_string: AsyncSearchQueryString,
+    search_request: SearchRequestQueryString,
+    search_service: &dyn SearchService,
+) -> Result<AsyncSearchResponseRest, SearchError> {
+    let keep_alive = match async_search_query_string.keep_alive.as_deref() {
+        Some(keep_alive_str) => humantime::parse_duration(keep_alive_str).map_err(|_err| {
+            SearchError::InvalidArgument(format!(
+                "invalid async search keep alive: `{keep_alive_str}`"
+            ))
+        })?,
+        None => DEFAULT_ASYNC_SEARCH_KEEP_ALIVE,
+    };
+    let search_request = search_request_from_api_request(index_id_patterns, search_request)?;
+    let async_search_response = search_service
+        .submit_async_search(search_request, keep_alive)
+        .await?;
+    AsyncSearchResponseRest::try_from(async_search_response)
+}
+
+fn async_search_submit_filter() -> impl Filter<
+    Extract = (
+        Vec<String>,
+        AsyncSearchQueryString,
+        SearchRequestQueryString,
+    ),
+    Error = Rejection,
+> + Clone {
+    warp::path!(String / "async_search")
+        .and_then(extract_index_id_patterns)
+        .and(warp::post())
+        .and(serde_qs::warp::query(serde_qs::Config::default()))
+        .and(warp::body::content_length_limit(1024 * 1024))
+        .and(warp::body::json())
+}
+
+async fn async_search_submit(
+    index_id_patterns: Vec<String>,
+    async_search_query_string: AsyncSearchQueryString,
+    search_request: SearchRequestQueryString,
+    search_service: Arc<dyn SearchService>,
+) -> impl warp::Reply {
+    info!(request =? search_request, "async_search_submit");
+    let body_format = search_request.format;
+    let result = async_search_submit_endpoint(
+        index_id_patterns,
+        async_search_query_string,
+        search_request,
+        &*search_service,
+    )
+    .await;
+    make_json_api_response(result, body_format)
+}
+
+#[derive(Debug, Error)]
+enum AsyncSearchError {
+    #[error(transparent)]
+    Auth(#[from] AuthError),
+    #[error(transparent)]
+    Search(#[from] SearchError),
+}
+
+impl ServiceError for AsyncSearchError {
+    fn error_code(&self) -> ServiceErrorCode {
+        match self {
+            Self::Auth(error) => error.error_code(),
+            Self::Search(error) => error.error_code(),
+        }
+    }
+}
+
+/// Fetches an async search and checks that the API key of the request grants the `search`
+/// permission on the indexes it targets. The ID of an async search alone does not grant access
+/// to its results.
+async fn get_authorized_async_search(
+    async_search_id: String,
+    search_service: &dyn SearchService,
+    api_key_opt: Option<&AuthenticatedApiKey>,
+) -> Result<AsyncSearchResponse, AsyncSearchError> {
+    let async_search_response = search_service.get_async_search(async_search_id).await?;
+    check_index_permission(
+        api_key_opt,
+        ApiKeyPermission::Search,
+        &async_search_response.index_id_patterns.join(","),
+    )?;
+    Ok(async_search_response)
+}
+
+/// Returns the handlers of the async search API.
+pub fn async_search_handlers(
+    search_service: Arc<dyn SearchService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    async_search_submit_handler(search_service.clone())
+        .or(async_search_get_handler(search_service.clone()))
+        .or(async_search_delete_handler(search_service))
+}
+
+#[utoipa::path(
+    post,
+    tag = "Search",
+    path = "/{index_id}/async_search",
+    request_body = SearchRequestQueryString,
+    responses(
+        (status = 200, description = "Successfully submitted async search.", body = AsyncSearchResponseRest)
+    ),
+    params(
+        AsyncSearchQueryString,
+        ("index_id" = String, Path, description = "The index ID to search."),
+    )
+)]
+/// Submit Async Search
+///
+/// Starts the search in the background and returns its ID right away. The results merged so far
+/// can be polled with `GET /async_search/{async_search_id}`.
+pub fn async_search_submit_handler(
+    search_service: Arc<dyn SearchService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    async_search_submit_filter()
+        .and(with_arg(search_service))
+        .then(async_search_submit)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Search",
+    path = "/async_search/{async_search_id}",
+    responses(
+        (status = 200, description = "Successfully fetched async search.", body = AsyncSearchResponseRest)
+    ),
+    params(
+        ("async_search_id" = String, Path, description = "The ID of the async search."),
+    )
+)]
+/// Get Async Search
+///
+/// Returns the state of an async search. While the search is running, the response only contains
+/// the number of hits and the aggregations merged so far.
+pub fn async_search_get_handler(
+    search_service: Arc<dyn SearchService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("async_search" / String)
+        .and(warp::get())
+        .and(with_arg(search_service))
+        .and(api_key_filter())
+        .then(async_search_get)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+async fn async_search_get(
+    async_search_id: String,
+    search_service: Arc<dyn SearchService>,
+    api_key_opt: Option<AuthenticatedApiKey>,
+) -> Result<AsyncSearchResponseRest, AsyncSearchError> {
+    let async_search_response =
+        get_authorized_async_search(async_search_id, &*search_service, api_key_opt.as_ref())
+            .await?;
+    let async_search_response_rest = AsyncSearchResponseRest::try_from(async_search_response)?;
+    Ok(async_search_response_rest)
+}
+
+#[utoipa::path(
+    delete,
+    tag = "Search",
+    path = "/async_search/{async_search_id}",
+    responses(
+        (status = 200, description = "Successfully deleted async search.")
+    ),
+    params(
+        ("async_search_id" = String, Path, description = "The ID of the async search."),
+    )
+)]
+/// Delete Async Search
+///
+/// Deletes an async search and its results, cancelling the search if it is still running.
+pub fn async_search_delete_handler(
+    search_service: Arc<dyn SearchService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("async_search" / String)
+        .and(warp::delete())
+        .and(with_arg(search_service))
+        .and(api_key_filter())
+        .then(async_search_delete)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+async fn async_search_delete(
+    async_search_id: String,
+    search_service: Arc<dyn SearchService>,
+    api_key_opt: Option<AuthenticatedApiKey>,
+) -> Result<(), AsyncSearchError> {
+    info!(async_search_id=%async_search_id, "async_search_delete");
+    get_authorized_async_search(
+        async_search_id.clone(),
+        &*search_service,
+        api_key_opt.as_ref(),
+    )
+    .await?;
+    search_service.delete_async_search(async_search_id).await?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use assert_json_diff::{assert_json_eq, assert_json_include};
     use bytes::Bytes;
     use mockall::predicate;
+    use quickwit_config::ApiKeyConfig;
     use quickwit_search::{MockSearchService, SearchError};
     use serde_json::{json, Value as JsonValue};
 
@@ -572,6 +856,10 @@ mod tests {
             elapsed_time_micros: 0u64,
             errors: Vec::new(),
             aggregations: None,
+            timed_out: false,
+            skipped_split_ids: Vec::new(),
+            profile: None,
+            inner_hits: None,
         };
         let search_response_json: JsonValue = serde_json::to_value(search_response)?;
         let expected_search_response_json: JsonValue = json!({
@@ -1006,6 +1294,281 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_rest_search_api_timeout_parameter() -> anyhow::Result<()> {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .with(predicate::function(
+                |search_request: &quickwit_proto::search::SearchRequest| {
+                    search_request.timeout_millis == Some(1_500)
+                },
+            ))
+            .returning(|_| {
+                Ok(quickwit_proto::search::SearchResponse {
+                    timed_out: true,
+                    skipped_split_ids: vec!["split-1".to_string()],
+                    ..Default::default()
+                })
+            });
+        let rest_search_api_handler = search_handler(mock_search_service);
+        let resp = warp::test::request()
+            .path("/quickwit-demo-index/search?query=*&timeout=1s500ms")
+            .reply(&rest_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body())?;
+        let expected_response_json = json!({
+            "timed_out": true,
+            "skipped_split_ids": ["split-1"],
+        });
+        assert_json_include!(actual: resp_json, expected: expected_response_json);
+
+        let resp = warp::test::request()
+            .path("/quickwit-demo-index/search?query=*&timeout=soon")
+            .reply(&rest_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rest_search_api_profile_parameter() -> anyhow::Result<()> {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .with(predicate::function(
+                |search_request: &quickwit_proto::search::SearchRequest| search_request.profile,
+            ))
+            .returning(|_| {
+                Ok(quickwit_proto::search::SearchResponse {
+                    profile: Some(quickwit_proto::search::SearchProfile {
+                        num_docs_matched: 3,
+                        split_profiles: vec![quickwit_proto::search::SplitSearchProfile {
+                            split_id: "split-1".to_string(),
+                            num_docs_matched: 3,
+                            ..Default::default()
+                        }],
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })
+            });
+        let rest_search_api_handler = search_handler(mock_search_service);
+        let resp = warp::test::request()
+            .path("/quickwit-demo-index/search?query=*&profile=true")
+            .reply(&rest_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body())?;
+        let expected_response_json = json!({
+            "profile": {
+                "num_docs_matched": 3,
+                "split_profiles": [{"split_id": "split-1", "num_docs_matched": 3}],
+            },
+        });
+        assert_json_include!(actual: resp_json, expected: expected_response_json);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rest_search_api_collapse_parameters() -> anyhow::Result<()> {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .with(predicate::function(
+                |search_request: &quickwit_proto::search::SearchRequest| {
+                    search_request.collapse
+                        == Some(CollapseOptions {
+                            field_name: "service".to_string(),
+                            max_inner_hits: 2,
+                        })
+                },
+            ))
+            .returning(|_| {
+                let hit = |json: &str| quickwit_proto::search::Hit {
+                    json: json.to_string(),
+                    partial_hit: None,
+                    snippet: None,
+                    index_id: "quickwit-demo-index".to_string(),
+                    inner_hits: Vec::new(),
+                };
+                let mut group_hit = hit(r#"{"service": "api", "severity": 3}"#);
+                group_hit.inner_hits = vec![
+                    hit(r#"{"service": "api", "severity": 3}"#),
+                    hit(r#"{"service": "api", "severity": 2}"#),
+                ];
+                Ok(quickwit_proto::search::SearchResponse {
+                    hits: vec![group_hit],
+                    num_hits: 2,
+                    ..Default::default()
+                })
+            });
+        let rest_search_api_handler = search_handler(mock_search_service);
+        let resp = warp::test::request()
+            .path(
+                "/quickwit-demo-index/search?query=*&collapse_field=service&collapse_inner_hits=2",
+            )
+            .reply(&rest_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body())?;
+        let expected_response_json = json!({
+            "num_hits": 2,
+            "hits": [{"service": "api", "severity": 3}],
+            "inner_hits": [[
+                {"service": "api", "severity": 3},
+                {"service": "api", "severity": 2},
+            ]],
+        });
+        assert_json_include!(actual: resp_json, expected: expected_response_json);
+
+        let rest_search_api_handler = search_handler(MockSearchService::new());
+        let resp = warp::test::request()
+            .path("/quickwit-demo-index/search?query=*&collapse_inner_hits=2")
+            .reply(&rest_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rest_async_search_api() -> anyhow::Result<()> {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_submit_async_search()
+            .withf(|search_request, keep_alive| {
+                search_request.index_id_patterns == vec!["quickwit-demo-index".to_string()]
+                    && *keep_alive == Duration::from_secs(10 * 60)
+            })
+            .returning(|_, _| {
+                Ok(AsyncSearchResponse {
+                    id: "01HAV29D4XY3D462FS3D8K5Q2H".to_string(),
+                    index_id_patterns: vec!["quickwit-demo-index".to_string()],
+                    is_running: true,
+                    is_partial: true,
+                    start_timestamp: 1_000,
+                    expiration_timestamp: 1_600,
+                    response: None,
+                    error: None,
+                })
+            });
+        mock_search_service
+            .expect_get_async_search()
+            .withf(|async_search_id| async_search_id == "01HAV29D4XY3D462FS3D8K5Q2H")
+            .returning(|async_search_id| {
+                Ok(AsyncSearchResponse {
+                    id: async_search_id,
+                    index_id_patterns: vec!["quickwit-demo-index".to_string()],
+                    is_running: true,
+                    is_partial: true,
+                    start_timestamp: 1_000,
+                    expiration_timestamp: 1_600,
+                    response: Some(quickwit_proto::search::SearchResponse {
+                        num_hits: 3,
+                        aggregation: Some(r#"{"total": {"value": 6.0}}"#.to_string()),
+                        ..Default::default()
+                    }),
+                    error: None,
+                })
+            });
+        mock_search_service
+            .expect_delete_async_search()
+            .returning(|async_search_id| Err(SearchError::AsyncSearchNotFound(async_search_id)));
+        let rest_async_search_api_handler =
+            async_search_handlers(Arc::new(mock_search_service)).recover(recover_fn);
+
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/quickwit-demo-index/async_search?keep_alive=10m")
+            .json(&json!({"query": "*"}))
+            .reply(&rest_async_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body())?;
+        let expected_response_json = json!({
+            "id": "01HAV29D4XY3D462FS3D8K5Q2H",
+            "is_running": true,
+            "expiration_timestamp": 1_600,
+        });
+        assert_json_include!(actual: resp_json, expected: expected_response_json);
+
+        let resp = warp::test::request()
+            .path("/async_search/01HAV29D4XY3D462FS3D8K5Q2H")
+            .reply(&rest_async_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body())?;
+        let expected_response_json = json!({
+            "is_partial": true,
+            "response": {
+                "num_hits": 3,
+                "aggregations": {"total": {"value": 6.0}},
+            },
+        });
+        assert_json_include!(actual: resp_json, expected: expected_response_json);
+
+        let resp = warp::test::request()
+            .method("DELETE")
+            .path("/async_search/01HAV29D4XY3D462FS3D8K5Q2H")
+            .reply(&rest_async_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 404);
+
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/quickwit-demo-index/async_search?keep_alive=forever")
+            .json(&json!({"query": "*"}))
+            .reply(&rest_async_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rest_async_search_api_checks_index_permissions_of_api_key() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_get_async_search()
+            .returning(|async_search_id| {
+                Ok(AsyncSearchResponse {
+                    id: async_search_id,
+                    index_id_patterns: vec!["my-index-1".to_string(), "my-index-2".to_string()],
+                    is_running: true,
+                    is_partial: true,
+                    start_timestamp: 1_000,
+                    expiration_timestamp: 1_600,
+                    response: None,
+                    error: None,
+                })
+            });
+        mock_search_service.expect_delete_async_search().never();
+        let rest_async_search_api_handler =
+            async_search_handlers(Arc::new(mock_search_service)).recover(recover_fn);
+        let api_key = AuthenticatedApiKey::try_new(&ApiKeyConfig {
+            name: "my-index-1-search".to_string(),
+            key: "secret-key".to_string(),
+            permissions: vec![ApiKeyPermission::Search],
+            index_id_patterns: vec!["my-index-1".to_string()],
+        })
+        .unwrap();
+
+        for method in ["GET", "DELETE"] {
+            let resp = warp::test::request()
+                .method(method)
+                .path("/async_search/01HAV29D4XY3D462FS3D8K5Q2H")
+                .extension(api_key.clone())
+                .reply(&rest_async_search_api_handler)
+                .await;
+            assert_eq!(resp.status(), 403);
+            let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+            assert!(resp_json["message"]
+                .as_str()
+                .unwrap()
+                .contains("on index `my-index-2`"));
+        }
+    }
+
     #[tokio::test]
     async fn test_rest_search_api_with_index_does_not_exist() -> anyhow::Result<()> {
         let mut mock_search_service = MockSearchService::new();
@@ -1175,6 +1738,7 @@ mod tests {
                     partial_hit: None,
                     snippet: Some(r#"{"title": [], "body": ["foo <em>bar</em> baz"]}"#.to_string()),
                     index_id: "quickwit-demo-index".to_string(),
+                    inner_hits: Vec::new(),
                 }],
                 num_hits: 1,
                 elapsed_time_micros: 16,
@@ -30,7 +30,7 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 pub use quickwit_cache::QuickwitCache;
-pub use storage_with_cache::StorageWithCache;
+pub use storage_with_cache::{StorageCacheStats, StorageWithCache};
 
 pub use self::byte_range_cache::ByteRangeCache;
 pub use self::memory_sized_cache::MemorySizedCache;
@@ -51,6 +51,21 @@ pub fn wrap_storage_with_cache(
     Arc::new(StorageWithCache {
         storage,
         cache: long_term_cache,
+        stats_opt: None,
+    })
+}
+
+/// Same as [`wrap_storage_with_cache`], but records the reads served by the returned storage in
+/// `stats`.
+pub fn wrap_storage_with_cache_and_stats(
+    long_term_cache: Arc<dyn StorageCache>,
+    storage: Arc<dyn Storage>,
+    stats: Arc<StorageCacheStats>,
+) -> Arc<dyn Storage> {
+    Arc::new(StorageWithCache {
+        storage,
+        cache: long_term_cache,
+        stats_opt: Some(stats),
     })
 }
 
@@ -20,6 +20,7 @@
 use std::fmt;
 use std::ops::Range;
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -28,12 +29,63 @@ use tokio::io::AsyncRead;
 
 use crate::cache::StorageCache;
 use crate::storage::SendableAsync;
-use crate::{BulkDeleteError, OwnedBytes, Storage, StorageResult};
+use crate::{BulkDeleteError, FileMetadataStream, OwnedBytes, Storage, StorageResult};
+
+/// Counters of the reads served by a [`StorageWithCache`].
+#[derive(Debug, Default)]
+pub struct StorageCacheStats {
+    num_hits: AtomicU64,
+    num_misses: AtomicU64,
+    num_bytes_fetched: AtomicU64,
+}
+
+impl StorageCacheStats {
+    /// Number of reads served by the cache.
+    pub fn num_hits(&self) -> u64 {
+        self.num_hits.load(Ordering::Relaxed)
+    }
+
+    /// Number of reads forwarded to the underlying storage.
+    pub fn num_misses(&self) -> u64 {
+        self.num_misses.load(Ordering::Relaxed)
+    }
+
+    /// Number of bytes read from the underlying storage.
+    pub fn num_bytes_fetched(&self) -> u64 {
+        self.num_bytes_fetched.load(Ordering::Relaxed)
+    }
+
+    fn record_hit(&self) {
+        self.num_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn record_miss(&self, num_bytes: usize) {
+        self.num_misses.fetch_add(1, Ordering::Relaxed);
+        self.num_bytes_fetched
+            .fetch_add(num_bytes as u64, Ordering::Relaxed);
+    }
+}
 
 /// Use with care, StorageWithCache is read-only.
 pub struct StorageWithCache {
     pub storage: Arc<dyn Storage>,
     pub cache: Arc<dyn StorageCache>,
+    /// If set, the reads served by this storage are recorded in these counters.
+    pub stats_opt: Option<Arc<StorageCacheStats>>,
+}
+
+impl StorageWithCache {
+    fn record_hit(&self) {
+        if let Some(stats) = &self.stats_opt {
+            stats.record_hit();
+        }
+    }
+
+    fn record_miss(&self, num_bytes: usize) {
+        if let Some(stats) = &self.stats_opt {
+            stats.record_miss(num_bytes);
+        }
+    }
 }
 
 impl fmt::Debug for StorageWithCache {
@@ -62,9 +114,11 @@ impl Storage for StorageWithCache {
 
     async fn get_slice(&self, path: &Path, byte_range: Range<usize>) -> StorageResult<OwnedBytes> {
         if let Some(bytes) = self.cache.get(path, byte_range.clone()).await {
+            self.record_hit();
             Ok(bytes)
         } else {
             let bytes = self.storage.get_slice(path, byte_range.clone()).await?;
+            self.record_miss(bytes.len());
             self.cache
                 .put(path.to_owned(), byte_range, bytes.clone())
                 .await;
@@ -85,9 +139,11 @@ impl Storage for StorageWithCache {
 
     async fn get_all(&self, path: &Path) -> StorageResult<OwnedBytes> {
         if let Some(bytes) = self.cache.get_all(path).await {
+            self.record_hit();
             Ok(bytes)
         } else {
             let bytes = self.storage.get_all(path).await?;
+            self.record_miss(bytes.len());
             self.cache.put_all(path.to_owned(), bytes.clone()).await;
             Ok(bytes)
         }
@@ -101,6 +157,10 @@ impl Storage for StorageWithCache {
         unimplemented!("Failed to delete files `{paths:?}`. `StorageWithCache` is read-only.")
     }
 
+    async fn delete_prefix(&self, prefix: &Path) -> Result<(), BulkDeleteError> {
+        unimplemented!("Failed to delete prefix `{prefix:?}`. `StorageWithCache` is read-only.")
+    }
+
     async fn exists(&self, path: &Path) -> StorageResult<bool> {
         self.storage.exists(path).await
     }
@@ -109,6 +169,10 @@ impl Storage for StorageWithCache {
         self.storage.file_num_bytes(path).await
     }
 
+    fn list(&self, prefix: &Path) -> FileMetadataStream {
+        self.storage.list(prefix)
+    }
+
     fn uri(&self) -> &Uri {
         self.storage.uri()
     }
@@ -148,9 +212,11 @@ mod tests {
             .times(1)
             .returning(|_path| Ok(OwnedBytes::new(vec![1, 2, 3])));
 
+        let stats = Arc::new(StorageCacheStats::default());
         let storage_with_cache = StorageWithCache {
             storage: Arc::new(mock_storage),
             cache: Arc::new(mock_cache),
+            stats_opt: Some(stats.clone()),
         };
 
         let data1 = storage_with_cache
@@ -163,5 +229,8 @@ mod tests {
             .await
             .unwrap();
         assert_eq!(data1, data2);
+        assert_eq!(stats.num_hits(), 1);
+        assert_eq!(stats.num_misses(), 1);
+        assert_eq!(stats.num_bytes_fetched(), 3);
     }
 }
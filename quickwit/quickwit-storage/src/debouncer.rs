@@ -32,7 +32,7 @@ use tantivy::directory::OwnedBytes;
 use tokio::io::AsyncRead;
 
 use crate::storage::SendableAsync;
-use crate::{BulkDeleteError, Storage, StorageResult};
+use crate::{BulkDeleteError, FileMetadataStream, Storage, StorageResult};
 
 /// The AsyncDebouncer debounces inflight Futures, so that concurrent async request to the same data
 /// source can be deduplicated.
@@ -199,6 +199,10 @@ impl<T: Storage> Storage for DebouncedStorage<T> {
     async fn file_num_bytes(&self, path: &Path) -> StorageResult<u64> {
         self.underlying.file_num_bytes(path).await
     }
+
+    fn list(&self, prefix: &Path) -> FileMetadataStream {
+        self.underlying.list(prefix)
+    }
 }
 
 #[cfg(test)]
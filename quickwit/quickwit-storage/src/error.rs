@@ -41,6 +41,8 @@ pub enum StorageErrorKind {
     Timeout,
     /// Io error.
     Io,
+    /// The operation is not supported by the storage.
+    Unsupported,
 }
 
 /// Generic Storage Resolver Error.
@@ -39,7 +39,7 @@ pub(crate) use debouncer::DebouncedStorage;
 
 pub use self::metrics::STORAGE_METRICS;
 pub use self::payload::PutPayload;
-pub use self::storage::Storage;
+pub use self::storage::{FileMetadata, FileMetadataStream, Storage};
 
 mod bundle_storage;
 mod error;
@@ -66,7 +66,8 @@ pub use self::bundle_storage::{BundleStorage, BundleStorageFileOffsets};
 #[cfg(any(test, feature = "testsuite"))]
 pub use self::cache::MockStorageCache;
 pub use self::cache::{
-    wrap_storage_with_cache, ByteRangeCache, MemorySizedCache, QuickwitCache, StorageCache,
+    wrap_storage_with_cache, wrap_storage_with_cache_and_stats, ByteRangeCache, MemorySizedCache,
+    QuickwitCache, StorageCache, StorageCacheStats,
 };
 pub use self::local_file_storage::{LocalFileStorage, LocalFileStorageFactory};
 #[cfg(feature = "azure")]
@@ -153,9 +154,10 @@ mod tests {
 #[cfg(any(test, feature = "integration-testsuite"))]
 pub(crate) mod test_suite {
 
-    use std::path::Path;
+    use std::path::{Path, PathBuf};
 
     use anyhow::Context;
+    use futures::TryStreamExt;
     use tokio::io::AsyncReadExt;
 
     use crate::{Storage, StorageErrorKind};
@@ -258,6 +260,69 @@ pub(crate) mod test_suite {
         Ok(())
     }
 
+    async fn list_paths(storage: &dyn Storage, prefix: &str) -> anyhow::Result<Vec<PathBuf>> {
+        let paths = storage
+            .list(Path::new(prefix))
+            .map_ok(|file_metadata| file_metadata.path)
+            .try_collect()
+            .await?;
+        Ok(paths)
+    }
+
+    async fn test_write_and_list(storage: &mut dyn Storage) -> anyhow::Result<()> {
+        let test_paths = [
+            Path::new("list/foo/bar.split"),
+            Path::new("list/foo/baz.split"),
+            Path::new("list/foobar.json"),
+            Path::new("list/qux.json"),
+        ];
+        for test_path in test_paths {
+            storage.put(test_path, Box::new(b"123".to_vec())).await?;
+        }
+        assert_eq!(list_paths(storage, "list/").await?, test_paths);
+        assert_eq!(list_paths(storage, "list/foo").await?, test_paths[..3]);
+        assert_eq!(list_paths(storage, "list/foo/").await?, test_paths[..2]);
+        assert_eq!(list_paths(storage, "list/foo/baz").await?, test_paths[1..2]);
+        assert!(list_paths(storage, "list/missing").await?.is_empty());
+
+        let file_metadata = storage
+            .list(test_paths[3])
+            .try_next()
+            .await?
+            .context("expected one file")?;
+        assert_eq!(file_metadata.path, test_paths[3]);
+        assert_eq!(file_metadata.num_bytes, 3);
+
+        storage.bulk_delete(&test_paths).await?;
+        assert!(list_paths(storage, "list/").await?.is_empty());
+        Ok(())
+    }
+
+    async fn test_write_and_delete_prefix(storage: &mut dyn Storage) -> anyhow::Result<()> {
+        let test_paths = [
+            Path::new("delete_prefix/foo/bar.split"),
+            Path::new("delete_prefix/foo/baz.split"),
+            Path::new("delete_prefix/foobar.json"),
+        ];
+        for test_path in test_paths {
+            storage.put(test_path, Box::new(b"123".to_vec())).await?;
+        }
+        storage
+            .delete_prefix(Path::new("delete_prefix/foo/"))
+            .await?;
+        assert_eq!(
+            list_paths(storage, "delete_prefix/").await?,
+            test_paths[2..]
+        );
+
+        storage.delete_prefix(Path::new("delete_prefix/")).await?;
+        assert!(list_paths(storage, "delete_prefix/").await?.is_empty());
+
+        // Deleting an empty prefix is a no-op.
+        storage.delete_prefix(Path::new("delete_prefix/")).await?;
+        Ok(())
+    }
+
     async fn test_file_size(storage: &mut dyn Storage) -> anyhow::Result<()> {
         let test_path = Path::new("write_for_filesize");
         let payload_bytes = b"abcdefghijklmnopqrstuvwxyz";
@@ -330,6 +395,12 @@ pub(crate) mod test_suite {
         test_write_and_bulk_delete(storage)
             .await
             .context("write_and_bulk_delete")?;
+        test_write_and_list(storage)
+            .await
+            .context("write_and_list")?;
+        test_write_and_delete_prefix(storage)
+            .await
+            .context("write_and_delete_prefix")?;
         test_exists(storage).await.context("exists")?;
         test_write_and_delete_with_dir_separator(storage)
             .await
@@ -23,6 +23,7 @@ use std::io::{ErrorKind, SeekFrom};
 use std::ops::Range;
 use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
+use std::time::UNIX_EPOCH;
 
 use async_trait::async_trait;
 use futures::future::{BoxFuture, FutureExt};
@@ -33,10 +34,10 @@ use quickwit_config::StorageBackend;
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 use tracing::warn;
 
-use crate::storage::SendableAsync;
+use crate::storage::{file_metadata_stream_from_future, SendableAsync};
 use crate::{
-    BulkDeleteError, DebouncedStorage, DeleteFailure, OwnedBytes, Storage, StorageError,
-    StorageErrorKind, StorageFactory, StorageResolverError, StorageResult,
+    BulkDeleteError, DebouncedStorage, DeleteFailure, FileMetadata, FileMetadataStream, OwnedBytes,
+    Storage, StorageError, StorageErrorKind, StorageFactory, StorageResolverError, StorageResult,
 };
 
 /// File system compatible storage implementation.
@@ -120,6 +121,56 @@ fn ensure_valid_relative_path(path: &Path) -> StorageResult<()> {
     Ok(())
 }
 
+/// Lists the files under `root` whose relative path starts with `prefix`, sorted by path.
+fn list_files_blocking(root: &Path, prefix: &Path) -> StorageResult<Vec<FileMetadata>> {
+    ensure_valid_relative_path(prefix)?;
+    let prefix_str = prefix.to_string_lossy();
+    // All the matching files live under the directory of the prefix. The prefix itself is a
+    // directory if it ends with a separator.
+    let prefix_dir = if prefix_str.is_empty() || prefix_str.ends_with('/') {
+        prefix
+    } else {
+        prefix.parent().unwrap_or(Path::new(""))
+    };
+    let mut files = Vec::new();
+    let mut dirs = vec![prefix_dir.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        let dir_entries = match std::fs::read_dir(root.join(&dir)) {
+            Ok(dir_entries) => dir_entries,
+            Err(error) if error.kind() == ErrorKind::NotFound => continue,
+            Err(error) => return Err(error.into()),
+        };
+        for dir_entry_res in dir_entries {
+            let dir_entry = dir_entry_res?;
+            let path = dir.join(dir_entry.file_name());
+
+            if !path.to_string_lossy().starts_with(prefix_str.as_ref()) {
+                continue;
+            }
+            let file_type = dir_entry.file_type()?;
+
+            if file_type.is_dir() {
+                dirs.push(path);
+            } else if file_type.is_file() {
+                let metadata = dir_entry.metadata()?;
+                let last_modified_opt = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                    .map(|duration| duration.as_secs() as i64);
+                files.push(FileMetadata {
+                    path,
+                    num_bytes: metadata.len(),
+                    last_modified_opt,
+                });
+            }
+        }
+    }
+    files.sort_by(|left, right| left.path.as_os_str().cmp(right.path.as_os_str()));
+    Ok(files)
+}
+
 /// Delete empty directories starting from `{root}/{path}` directory and stopping at `{root}`
 /// directory. Note that the `{root}` directory is not deleted.
 fn delete_all_dirs_if_empty<'a>(
@@ -322,6 +373,18 @@ impl Storage for LocalFileStorage {
         &self.uri
     }
 
+    fn list(&self, prefix: &Path) -> FileMetadataStream {
+        let root = self.root.clone();
+        let prefix = prefix.to_path_buf();
+        file_metadata_stream_from_future(async move {
+            tokio::task::spawn_blocking(move || list_files_blocking(&root, &prefix))
+                .await
+                .map_err(|_| {
+                    StorageErrorKind::Internal.with_error(anyhow::anyhow!("listing files panicked"))
+                })?
+        })
+    }
+
     async fn file_num_bytes(&self, path: &Path) -> StorageResult<u64> {
         let full_path = self.full_path(path)?;
         match tokio::fs::metadata(full_path).await {
@@ -367,6 +430,8 @@ mod tests {
 
     use std::str::FromStr;
 
+    use futures::TryStreamExt;
+
     use super::*;
     use crate::test_suite::storage_test_suite;
 
@@ -379,6 +444,19 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_local_file_storage_list_outside_of_root() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let uri = Uri::from_str(&format!("{}", temp_dir.path().display())).unwrap();
+        let local_file_storage = LocalFileStorage::from_uri(&uri).unwrap();
+        let error = local_file_storage
+            .list(Path::new("../foo"))
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), StorageErrorKind::Unauthorized);
+    }
+
     #[tokio::test]
     async fn test_local_file_storage_forbids_double_dot() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -33,7 +33,7 @@ use azure_storage_blobs::blob::operations::GetBlobResponse;
 use azure_storage_blobs::prelude::*;
 use bytes::Bytes;
 use futures::io::{Error as FutureError, ErrorKind as FutureErrorKind};
-use futures::stream::{StreamExt, TryStreamExt};
+use futures::stream::{self, StreamExt, TryStreamExt};
 use md5::Digest;
 use once_cell::sync::OnceCell;
 use quickwit_common::retry::{retry, RetryParams, Retryable};
@@ -51,8 +51,9 @@ use tracing::{instrument, warn};
 use crate::debouncer::DebouncedStorage;
 use crate::storage::SendableAsync;
 use crate::{
-    BulkDeleteError, DeleteFailure, MultiPartPolicy, PutPayload, Storage, StorageError,
-    StorageErrorKind, StorageFactory, StorageResolverError, StorageResult, STORAGE_METRICS,
+    BulkDeleteError, DeleteFailure, FileMetadata, FileMetadataStream, MultiPartPolicy, PutPayload,
+    Storage, StorageError, StorageErrorKind, StorageFactory, StorageResolverError, StorageResult,
+    STORAGE_METRICS,
 };
 
 /// Azure object storage resolver.
@@ -499,6 +500,38 @@ impl Storage for AzureBlobStorage {
         }
     }
 
+    fn list(&self, prefix: &Path) -> FileMetadataStream {
+        let blob_prefix = self.blob_name(prefix);
+        let storage_prefix = self.prefix.clone();
+
+        // Without a delimiter, Azure lists the blobs recursively. The pageable stream takes care
+        // of fetching the next pages with the continuation marker.
+        let mut list_blobs_builder = self.container_client.list_blobs();
+        if !blob_prefix.is_empty() {
+            list_blobs_builder = list_blobs_builder.prefix(blob_prefix);
+        }
+        list_blobs_builder
+            .into_stream()
+            .map_err(|error| StorageError::from(AzureErrorWrapper::from(error)))
+            .map_ok(move |list_blobs_response| {
+                let files: Vec<FileMetadata> = list_blobs_response
+                    .blobs
+                    .blobs()
+                    .filter_map(|blob| {
+                        let path = Path::new(&blob.name).strip_prefix(&storage_prefix).ok()?;
+                        Some(FileMetadata {
+                            path: path.to_path_buf(),
+                            num_bytes: blob.properties.content_length,
+                            last_modified_opt: Some(blob.properties.last_modified.unix_timestamp()),
+                        })
+                    })
+                    .collect();
+                stream::iter(files.into_iter().map(Ok))
+            })
+            .try_flatten()
+            .boxed()
+    }
+
     fn uri(&self) -> &Uri {
         &self.uri
     }
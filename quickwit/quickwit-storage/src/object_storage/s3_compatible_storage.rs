@@ -34,7 +34,7 @@ use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart, Delete, ObjectI
 use aws_sdk_s3::Client as S3Client;
 use aws_smithy_http::byte_stream::ByteStream;
 use base64::prelude::{Engine, BASE64_STANDARD};
-use futures::{stream, StreamExt};
+use futures::{stream, StreamExt, TryStreamExt};
 use once_cell::sync::{Lazy, OnceCell};
 use quickwit_aws::get_aws_config;
 use quickwit_aws::retry::{aws_retry, AwsRetryable};
@@ -50,8 +50,8 @@ use tracing::{info, instrument, warn};
 use crate::object_storage::MultiPartPolicy;
 use crate::storage::SendableAsync;
 use crate::{
-    BulkDeleteError, DeleteFailure, OwnedBytes, Storage, StorageError, StorageErrorKind,
-    StorageResolverError, StorageResult, STORAGE_METRICS,
+    BulkDeleteError, DeleteFailure, FileMetadata, FileMetadataStream, OwnedBytes, Storage,
+    StorageError, StorageErrorKind, StorageResolverError, StorageResult, STORAGE_METRICS,
 };
 
 /// Semaphore to limit the number of concurent requests to the object store. Some object stores
@@ -820,6 +820,63 @@ impl Storage for S3CompatibleObjectStorage {
         Ok(head_object_output.content_length() as u64)
     }
 
+    fn list(&self, prefix: &Path) -> FileMetadataStream {
+        let s3_client = self.s3_client.clone();
+        let bucket = self.bucket.clone();
+        let key_prefix = self.key(prefix);
+        let storage_prefix = self.prefix.clone();
+        let retry_params = self.retry_params;
+
+        // The state of the pagination is the continuation token of the next page to fetch, and
+        // `None` once the last page has been fetched.
+        stream::try_unfold(Some(None), move |next_page_opt: Option<Option<String>>| {
+            let s3_client = s3_client.clone();
+            let bucket = bucket.clone();
+            let key_prefix = key_prefix.clone();
+            let storage_prefix = storage_prefix.clone();
+            async move {
+                let Some(continuation_token_opt) = next_page_opt else {
+                    return Ok(None);
+                };
+                let _permit = REQUEST_SEMAPHORE.acquire().await;
+                let list_objects_output = aws_retry(&retry_params, || async {
+                    s3_client
+                        .list_objects_v2()
+                        .bucket(&bucket)
+                        .prefix(&key_prefix)
+                        .set_continuation_token(continuation_token_opt.clone())
+                        .send()
+                        .await
+                })
+                .await?;
+                let files: Vec<FileMetadata> = list_objects_output
+                    .contents()
+                    .unwrap_or_default()
+                    .iter()
+                    .filter_map(|object| {
+                        let key = object.key()?;
+                        // FIXME: This may not work on Windows.
+                        let path = Path::new(key).strip_prefix(&storage_prefix).ok()?;
+                        Some(FileMetadata {
+                            path: path.to_path_buf(),
+                            num_bytes: object.size() as u64,
+                            last_modified_opt: object
+                                .last_modified()
+                                .map(|last_modified| last_modified.secs()),
+                        })
+                    })
+                    .collect();
+                let next_page_opt = list_objects_output
+                    .next_continuation_token()
+                    .map(|continuation_token| Some(continuation_token.to_string()));
+                Ok::<_, StorageError>(Some((files, next_page_opt)))
+            }
+        })
+        .map_ok(|files| stream::iter(files.into_iter().map(Ok)))
+        .try_flatten()
+        .boxed()
+    }
+
     fn uri(&self) -> &Uri {
         &self.uri
     }
@@ -1130,4 +1187,100 @@ mod tests {
         let delete_objects_error = bulk_delete_error.error.unwrap();
         assert!(delete_objects_error.to_string().contains("MalformedXML"));
     }
+
+    #[tokio::test]
+    async fn test_s3_compatible_storage_list() {
+        let client = TestConnection::new(vec![
+            (
+                http::Request::builder()
+                    .body(SdkBody::from(Body::empty()))
+                    .unwrap(),
+                http::Response::builder()
+                    .status(200)
+                    .body(SdkBody::from(Body::from(Bytes::from(
+                        r#"<?xml version="1.0" encoding="UTF-8"?>
+                        <ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                            <Name>bucket</Name>
+                            <Prefix>indexes/foo</Prefix>
+                            <KeyCount>2</KeyCount>
+                            <MaxKeys>2</MaxKeys>
+                            <IsTruncated>true</IsTruncated>
+                            <NextContinuationToken>page-2</NextContinuationToken>
+                            <Contents>
+                                <Key>indexes/foo/bar.split</Key>
+                                <LastModified>2024-01-01T00:00:00.000Z</LastModified>
+                                <Size>42</Size>
+                            </Contents>
+                            <Contents>
+                                <Key>indexes/foo/baz.split</Key>
+                                <LastModified>2024-01-01T00:00:00.000Z</LastModified>
+                                <Size>43</Size>
+                            </Contents>
+                        </ListBucketResult>"#,
+                    ))))
+                    .unwrap(),
+            ),
+            (
+                http::Request::builder()
+                    .body(SdkBody::from(Body::empty()))
+                    .unwrap(),
+                http::Response::builder()
+                    .status(200)
+                    .body(SdkBody::from(Body::from(Bytes::from(
+                        r#"<?xml version="1.0" encoding="UTF-8"?>
+                        <ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                            <Name>bucket</Name>
+                            <Prefix>indexes/foo</Prefix>
+                            <KeyCount>1</KeyCount>
+                            <MaxKeys>2</MaxKeys>
+                            <IsTruncated>false</IsTruncated>
+                            <Contents>
+                                <Key>indexes/foobar.json</Key>
+                                <LastModified>2024-01-01T00:00:00.000Z</LastModified>
+                                <Size>44</Size>
+                            </Contents>
+                        </ListBucketResult>"#,
+                    ))))
+                    .unwrap(),
+            ),
+        ]);
+        let credentials = Credentials::new("mock_key", "mock_secret", None, None, "mock_provider");
+        let config = aws_sdk_s3::Config::builder()
+            .region(Some(Region::new("Foo")))
+            .http_connector(client.clone())
+            .credentials_provider(credentials)
+            .build();
+        let s3_client = S3Client::from_conf(config);
+        let uri = Uri::for_test("s3://bucket/indexes");
+        let bucket = "bucket".to_string();
+        let prefix = PathBuf::from("indexes");
+
+        let s3_storage = S3CompatibleObjectStorage {
+            s3_client,
+            uri,
+            bucket,
+            prefix,
+            multipart_policy: MultiPartPolicy::default(),
+            retry_params: RetryParams::default(),
+            disable_multi_object_delete: false,
+            disable_multipart_upload: false,
+        };
+        let files: Vec<FileMetadata> = s3_storage
+            .list(Path::new("foo"))
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[0].path, Path::new("foo/bar.split"));
+        assert_eq!(files[0].num_bytes, 42);
+        assert_eq!(files[0].last_modified_opt, Some(1_704_067_200));
+        assert_eq!(files[1].path, Path::new("foo/baz.split"));
+        assert_eq!(files[2].path, Path::new("foobar.json"));
+
+        let requests = client.requests();
+        assert_eq!(requests.len(), 2);
+        let second_request_uri = requests[1].actual.uri().to_string();
+        assert!(second_request_uri.contains("continuation-token=page-2"));
+        assert!(second_request_uri.contains("prefix=indexes%2Ffoo"));
+    }
 }
@@ -23,14 +23,15 @@ use std::path::Path;
 
 use async_trait::async_trait;
 use bytesize::ByteSize;
-use opendal::Operator;
+use futures::{future, stream, StreamExt, TryStreamExt};
+use opendal::{Metakey, Operator};
 use quickwit_common::uri::Uri;
 use tokio::io::{AsyncRead, AsyncWriteExt};
 
 use crate::storage::SendableAsync;
 use crate::{
-    BulkDeleteError, OwnedBytes, PutPayload, Storage, StorageError, StorageErrorKind,
-    StorageResolverError, StorageResult,
+    BulkDeleteError, FileMetadata, FileMetadataStream, OwnedBytes, PutPayload, Storage,
+    StorageError, StorageErrorKind, StorageResolverError, StorageResult,
 };
 
 /// OpenDAL based storage implementation.
@@ -194,6 +195,43 @@ impl Storage for OpendalStorage {
         Ok(meta.content_length())
     }
 
+    fn list(&self, prefix: &Path) -> FileMetadataStream {
+        let op = self.op.clone();
+        let prefix = prefix.as_os_str().to_string_lossy().to_string();
+        // OpenDAL lists "directories": we recursively list the deepest directory containing the
+        // prefix and filter out the entries that do not match the prefix.
+        let dir = match prefix.rfind('/') {
+            Some(separator_pos) => prefix[..=separator_pos].to_string(),
+            None => String::new(),
+        };
+        let lister_future = async move {
+            let lister = op
+                .lister_with(&dir)
+                .recursive(true)
+                .metakey(Metakey::Mode | Metakey::ContentLength | Metakey::LastModified)
+                .await?;
+            Ok::<_, StorageError>(lister.map_err(StorageError::from))
+        };
+        stream::once(lister_future)
+            .try_flatten()
+            .try_filter_map(move |entry| {
+                let metadata = entry.metadata();
+                let file_metadata_opt = if metadata.is_file() && entry.path().starts_with(&prefix) {
+                    Some(FileMetadata {
+                        path: entry.path().into(),
+                        num_bytes: metadata.content_length(),
+                        last_modified_opt: metadata
+                            .last_modified()
+                            .map(|last_modified| last_modified.timestamp()),
+                    })
+                } else {
+                    None
+                };
+                future::ready(Ok(file_metadata_opt))
+            })
+            .boxed()
+    }
+
     fn uri(&self) -> &Uri {
         &self.uri
     }
@@ -23,11 +23,13 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use futures::{future, StreamExt, TryStreamExt};
 use quickwit_common::uri::Uri;
 use tokio::io::AsyncRead;
+use tracing::warn;
 
 use crate::storage::SendableAsync;
-use crate::{BulkDeleteError, OwnedBytes, Storage};
+use crate::{BulkDeleteError, FileMetadataStream, OwnedBytes, Storage};
 
 /// This storage acts as a proxy to another storage that simply modifies each API call
 /// by preceding each path with a given a prefix.
@@ -119,6 +121,30 @@ impl Storage for PrefixStorage {
     async fn file_num_bytes(&self, path: &Path) -> crate::StorageResult<u64> {
         self.storage.file_num_bytes(&self.prefix.join(path)).await
     }
+
+    fn list(&self, prefix: &Path) -> FileMetadataStream {
+        let storage_prefix = self.prefix.clone();
+        self.storage
+            .list(&self.prefix.join(prefix))
+            .try_filter_map(move |mut file_metadata| {
+                let file_metadata_opt = match file_metadata.path.strip_prefix(&storage_prefix) {
+                    Ok(stripped_path) => {
+                        file_metadata.path = stripped_path.to_path_buf();
+                        Some(file_metadata)
+                    }
+                    Err(_) => {
+                        warn!(
+                            path=%file_metadata.path.display(),
+                            prefix=%storage_prefix.display(),
+                            "skipping listed file outside of the storage prefix"
+                        );
+                        None
+                    }
+                };
+                future::ready(Ok(file_metadata_opt))
+            })
+            .boxed()
+    }
 }
 
 /// Creates a [`PrefixStorage`] using an underlying storage and a prefix.
@@ -191,7 +217,66 @@ mod tests {
     use std::collections::HashMap;
 
     use super::*;
-    use crate::DeleteFailure;
+    use crate::{DeleteFailure, FileMetadata, MockStorage, RamStorage};
+
+    #[tokio::test]
+    async fn test_prefix_storage_list() {
+        let ram_storage = RamStorage::builder()
+            .put("indexes/foo/bar.split", b"123")
+            .put("indexes/qux.split", b"1234")
+            .put("indexes-other/baz.split", b"12345")
+            .build();
+        let prefix_storage = add_prefix_to_storage(
+            Arc::new(ram_storage),
+            PathBuf::from("indexes"),
+            Uri::for_test("ram:///indexes"),
+        );
+        let paths: Vec<PathBuf> = prefix_storage
+            .list(Path::new(""))
+            .map_ok(|file_metadata| file_metadata.path)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(
+            paths,
+            [PathBuf::from("foo/bar.split"), PathBuf::from("qux.split")]
+        );
+        let files: Vec<FileMetadata> = prefix_storage
+            .list(Path::new("q"))
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, Path::new("qux.split"));
+        assert_eq!(files[0].num_bytes, 4);
+    }
+
+    #[tokio::test]
+    async fn test_prefix_storage_list_skips_files_outside_prefix() {
+        let mut mock_storage = MockStorage::default();
+        mock_storage.expect_list().returning(|_| {
+            let files = ["indexes/foo.split", "indexes-other/bar.split"].map(|path| {
+                Ok(FileMetadata {
+                    path: PathBuf::from(path),
+                    num_bytes: 3,
+                    last_modified_opt: None,
+                })
+            });
+            futures::stream::iter(files).boxed()
+        });
+        let prefix_storage = add_prefix_to_storage(
+            Arc::new(mock_storage),
+            PathBuf::from("indexes"),
+            Uri::for_test("ram:///indexes"),
+        );
+        let paths: Vec<PathBuf> = prefix_storage
+            .list(Path::new(""))
+            .map_ok(|file_metadata| file_metadata.path)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(paths, [PathBuf::from("foo.split")]);
+    }
 
     #[test]
     fn test_strip_prefix_from_error() {
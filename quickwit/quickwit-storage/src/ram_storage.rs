@@ -31,10 +31,10 @@ use tokio::io::{AsyncRead, AsyncWriteExt};
 use tokio::sync::RwLock;
 
 use crate::prefix_storage::add_prefix_to_storage;
-use crate::storage::SendableAsync;
+use crate::storage::{file_metadata_stream_from_future, SendableAsync};
 use crate::{
-    BulkDeleteError, OwnedBytes, Storage, StorageErrorKind, StorageFactory, StorageResolverError,
-    StorageResult,
+    BulkDeleteError, FileMetadata, FileMetadataStream, OwnedBytes, Storage, StorageErrorKind,
+    StorageFactory, StorageResolverError, StorageResult,
 };
 
 /// In Ram implementation of quickwit's storage.
@@ -160,6 +160,26 @@ impl Storage for RamStorage {
             Err(StorageErrorKind::NotFound.with_error(err))
         }
     }
+
+    fn list(&self, prefix: &Path) -> FileMetadataStream {
+        let files = self.files.clone();
+        let prefix = prefix.to_string_lossy().to_string();
+        file_metadata_stream_from_future(async move {
+            let mut listed_files: Vec<FileMetadata> = files
+                .read()
+                .await
+                .iter()
+                .filter(|(path, _)| path.to_string_lossy().starts_with(&prefix))
+                .map(|(path, payload_bytes)| FileMetadata {
+                    path: path.clone(),
+                    num_bytes: payload_bytes.len() as u64,
+                    last_modified_opt: None,
+                })
+                .collect();
+            listed_files.sort_by(|left, right| left.path.as_os_str().cmp(right.path.as_os_str()));
+            Ok(listed_files)
+        })
+    }
 }
 
 /// Builder to create a prepopulated [`RamStorage`]. This is mostly useful for tests.
@@ -23,6 +23,8 @@ use std::ops::Range;
 use std::path::{Path, PathBuf};
 
 use async_trait::async_trait;
+use futures::future::Future;
+use futures::stream::{self, BoxStream, StreamExt, TryChunksError, TryStreamExt};
 use quickwit_common::uri::Uri;
 use tempfile::TempPath;
 use tokio::fs::File;
@@ -35,6 +37,24 @@ use crate::{BulkDeleteError, OwnedBytes, PutPayload, StorageErrorKind, StorageRe
 pub trait SendableAsync: AsyncWrite + Send + Unpin {}
 impl<W: AsyncWrite + Send + Unpin> SendableAsync for W {}
 
+/// Maximum number of files deleted at once by [`Storage::delete_prefix`].
+const DELETE_PREFIX_BATCH_SIZE: usize = 1_000;
+
+/// Metadata of a file returned by [`Storage::list`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FileMetadata {
+    /// Path of the file, relative to the root of the storage.
+    pub path: PathBuf,
+    /// Size of the file in bytes.
+    pub num_bytes: u64,
+    /// Time of the last modification of the file, expressed as a Unix timestamp in seconds,
+    /// if the storage keeps track of it.
+    pub last_modified_opt: Option<i64>,
+}
+
+/// Stream of files returned by [`Storage::list`].
+pub type FileMetadataStream = BoxStream<'static, StorageResult<FileMetadata>>;
+
 /// Storage meant to receive and serve quickwit's split.
 ///
 /// Object storage are the primary target implementation of this trait,
@@ -126,6 +146,39 @@ pub trait Storage: fmt::Debug + Send + Sync + 'static {
     /// successfully deleted while others are not.
     async fn bulk_delete<'a>(&self, paths: &[&'a Path]) -> Result<(), BulkDeleteError>;
 
+    /// Deletes all the files whose path starts with `prefix`, matched as a plain string like in
+    /// [`Storage::list`].
+    ///
+    /// The files are listed and deleted in batches. On failure, the returned error lists the files
+    /// deleted before the operation was aborted.
+    async fn delete_prefix(&self, prefix: &Path) -> Result<(), BulkDeleteError> {
+        let mut file_chunks = self.list(prefix).try_chunks(DELETE_PREFIX_BATCH_SIZE);
+        let mut successes = Vec::new();
+
+        loop {
+            let files = match file_chunks.try_next().await {
+                Ok(Some(files)) => files,
+                Ok(None) => return Ok(()),
+                Err(TryChunksError(files, error)) => {
+                    return Err(BulkDeleteError {
+                        error: Some(error),
+                        successes,
+                        unattempted: files.into_iter().map(|file| file.path).collect(),
+                        ..Default::default()
+                    });
+                }
+            };
+            let paths: Vec<&Path> = files.iter().map(|file| file.path.as_path()).collect();
+
+            if let Err(mut bulk_delete_error) = self.bulk_delete(&paths).await {
+                successes.append(&mut bulk_delete_error.successes);
+                bulk_delete_error.successes = successes;
+                return Err(bulk_delete_error);
+            }
+            successes.extend(files.into_iter().map(|file| file.path));
+        }
+    }
+
     /// Returns whether a file exists or not.
     async fn exists(&self, path: &Path) -> StorageResult<bool> {
         match self.file_num_bytes(path).await {
@@ -138,10 +191,35 @@ pub trait Storage: fmt::Debug + Send + Sync + 'static {
     /// Returns a file size.
     async fn file_num_bytes(&self, path: &Path) -> StorageResult<u64>;
 
+    /// Lists the files whose path starts with `prefix`, in the lexicographic order of their
+    /// paths.
+    ///
+    /// As for object storages, `prefix` is matched as a plain string: `foo/ba` matches both
+    /// `foo/bar.split` and `foo/baz/qux.split`. Implementations fetch the listing page by page
+    /// as the stream is consumed.
+    fn list(&self, prefix: &Path) -> FileMetadataStream {
+        let error = StorageErrorKind::Unsupported.with_error(anyhow::anyhow!(
+            "storage `{}` does not support listing files (prefix `{}`)",
+            self.uri(),
+            prefix.display()
+        ));
+        stream::once(async move { Err(error) }).boxed()
+    }
+
     /// Returns an URI identifying the storage
     fn uri(&self) -> &Uri;
 }
 
+/// Turns a future resolving to a complete listing of files into a [`FileMetadataStream`]. Useful
+/// for storages that cannot paginate their listing.
+pub(crate) fn file_metadata_stream_from_future<F>(listing_future: F) -> FileMetadataStream
+where F: Future<Output = StorageResult<Vec<FileMetadata>>> + Send + 'static {
+    stream::once(listing_future)
+        .map_ok(|files| stream::iter(files.into_iter().map(Ok)))
+        .try_flatten()
+        .boxed()
+}
+
 async fn default_copy_to_file<S: Storage + ?Sized>(
     storage: &S,
     path: &Path,